@@ -50,7 +50,7 @@ pub enum NonLinearSolverError {
 #[derive(Debug, Error)]
 pub enum OdeSolverError {
     #[error(
-        "Stop time = {} is less than current state time = {}",
+        "Stop time = {} is before current state time = {} in the direction of integration",
         stop_time,
         state_time
     )]
@@ -67,15 +67,19 @@ pub enum OdeSolverError {
     SensitivitySolveFailed,
     #[error("Step size is too small at time = {time}")]
     StepSizeTooSmall { time: f64 },
+    #[error("Maximum number of steps ({max_steps}) reached at time = {time}")]
+    MaxStepsReached { max_steps: usize, time: f64 },
     #[error("Sensitivity requested but equations do not support it")]
     SensitivityNotSupported,
     #[error("Failed to get mutable reference to equations. If there is a solver created with this problem, call solver.take_state() to release the problem")]
     FailedToGetMutableReference,
     #[error("Builder error: {0}")]
     BuilderError(String),
-    #[error("t_eval must be increasing and all values must be greater than or equal to the current time")]
-    StateProblemMismatch,
     #[error("State is not consistent with the problem equations")]
+    StateProblemMismatch,
+    #[error("Integrating backwards in time is not supported by this solver")]
+    BackwardIntegrationNotSupported,
+    #[error("t_eval must be non-empty and monotonic, and all values must be at or after the current time in the direction of integration")]
     InvalidTEval,
     #[error("Sundials error: {0}")]
     SundialsError(String),
@@ -25,15 +25,35 @@
 //! To solve the problem given the initial state, you need to choose a solver. DiffSol provides the following solvers:
 //! - A Backwards Difference Formulae [Bdf] solver, suitable for stiff problems and singular mass matrices.
 //! - A Singly Diagonally Implicit Runge-Kutta (SDIRK or ESDIRK) solver [Sdirk]. You can use your own butcher tableau using [Tableau] or use one of the provided ([Tableau::tr_bdf2], [Tableau::esdirk34]).
-//!
-//! The easiest way to create a solver is to use one of the provided methods on the [OdeSolverProblem] struct ([OdeSolverProblem::bdf_solver], [OdeSolverProblem::tr_bdf2_solver], [OdeSolverProblem::esdirk34_solver]).
-//! These create a new solver from a provided state and problem. Alternatively, you can create both the solver and the state at once using [OdeSolverProblem::bdf], [OdeSolverProblem::tr_bdf2], [OdeSolverProblem::esdirk34].
+//! - An explicit Runge-Kutta solver [ExplicitRk], suitable for non-stiff problems. This solver does not require a Jacobian and can be used with equations that only implement [NonLinearOp] for the right-hand side (e.g. using [OdeBuilder::rhs]). You can use your own butcher tableau using [Tableau] or use one of the provided ([Tableau::dopri5], [Tableau::tsit45], [Tableau::vern56], [Tableau::vern67], [Tableau::vern78], [Tableau::vern89]). The higher order Verner methods are well suited to problems solved with tight tolerances.
+//! - A Rosenbrock solver [Rosenbrock], suitable for moderately stiff problems and singular mass matrices. Each step requires one Jacobian evaluation and factorisation, and no nonlinear solves. You can use your own tableau using [RosenbrockTableau] or use one of the provided ([RosenbrockTableau::rodas4], [RosenbrockTableau::rodas5], [RosenbrockTableau::rodas5p]).
+//! - A Radau IIA solver of order 5 [Radau], suitable for very stiff problems and index-1 DAEs (singular mass matrices). Each Newton iteration requires the solution of one real and one complex linear system.
+//! - A variable-order Adams-Moulton solver [Adams] (orders 1 to 12), suitable for non-stiff problems with expensive right-hand sides. Like [ExplicitRk], this solver does not require a Jacobian, and typically needs fewer right-hand side evaluations per step.
+//! - An automatic stiffness-switching solver [Lsoda], suitable for problems that may or may not be stiff. This solver starts with the [Adams] method and switches to and from the [Bdf] method as the stiffness of the problem changes.
+//! - An implicit-explicit (IMEX) additive Runge-Kutta solver [ImexArk], suitable for problems where the right-hand side can be split into a stiff part and a non-stiff part (see [OdeEquationsImex]). Only the Jacobian of the stiff part is required. The [OdeSolverProblem::ark436l2sa] method uses the ARK4(3)6L\[2\]SA method of Kennedy and Carpenter.
+//! - An exponential Rosenbrock solver [ExponentialRosenbrock], suitable for large stiff problems (e.g. semi-linear parabolic problems). This solver only requires Jacobian-vector products, and never forms or factorises the Jacobian. You can use your own tableau using [ExponentialRosenbrockTableau] or use one of the provided ([ExponentialRosenbrockTableau::exprb32], [ExponentialRosenbrockTableau::exprb43]).
+//! - Solvers for second-order problems `M y'' = f(t, y, y')` (see [OdeEquationsSecondOrder] and [OdeBuilder::rhs_second_order]): a fixed-step symplectic solver [Symplectic], suitable for long-time integration of conservative (e.g. Hamiltonian) systems, using one of the provided tableaus ([SymplecticTableau::velocity_verlet], [SymplecticTableau::yoshida4]), and the generalized-alpha solver [GeneralizedAlpha], suitable for damped and stiff problems in structural dynamics ([GeneralizedAlphaParameters::newmark], [GeneralizedAlphaParameters::chung_hulbert]). The positions and velocities of the solution can be obtained separately using the [SecondOrderOdeSolverMethod] trait.
+//! - A solver for delay differential equations [Dde] (see [OdeEquationsDelay] and [OdeBuilder::rhs_delay]), with constant or state-dependent delays. This solver uses another solver (e.g. [Bdf] or [Sdirk]) to take each step, keeps a dense history of the solution to evaluate the delayed states, and tracks the discontinuities in the derivatives of the solution so that each step lands on them.
+//! - Solvers for stochastic differential equations `dy = f(t, y) dt + G(t, y) dW` [Sde] (see [SdeProblem] and [OdeBuilder::build_sde]): the fixed-step Euler-Maruyama and Milstein methods, and the adaptive SRA1 method for additive noise ([SdeProblem::euler_maruyama], [SdeProblem::milstein], [SdeProblem::sra1]). The noise is generated from a seed and stored in a [BrownianPath], so that solutions are reproducible and different solvers can be run on the same realisation of the noise.
+//! - A Backwards Difference Formulae solver for fully implicit DAEs in residual form `F(t, y, y') = 0` [BdfDae] (see [OdeEquationsResidual], [DaeProblem] and [OdeBuilder::build_residual]). The consistent initial algebraic variables and derivatives are calculated before the first step ([DaeProblem::set_consistent]).
+//! - Solvers for two-point boundary value problems `y' = f(t, y)`, `g(y(t_a), y(t_b)) = 0` (see [BvpProblem]): multiple shooting ([BvpProblem::multiple_shooting]), which integrates the ODE between the shooting nodes along with the sensitivities wrt the initial state of each interval (see [InitialSensEquations]), and Hermite-Simpson collocation ([BvpProblem::collocation]), which is suitable for problems that are unstable to integrate.
+//! - A parallel-in-time driver [Parareal], which combines a cheap coarse solver and an accurate fine solver (any [OdeSolverMethod]) and runs the fine propagations over each time slice on separate threads.
+//!
+//! The easiest way to create a solver is to use one of the provided methods on the [OdeSolverProblem] struct ([OdeSolverProblem::bdf_solver], [OdeSolverProblem::tr_bdf2_solver], [OdeSolverProblem::esdirk34_solver], [OdeSolverProblem::dopri5_solver], [OdeSolverProblem::tsit45_solver], [OdeSolverProblem::vern56_solver], [OdeSolverProblem::vern67_solver], [OdeSolverProblem::vern78_solver], [OdeSolverProblem::vern89_solver], [OdeSolverProblem::rodas4_solver], [OdeSolverProblem::rodas5_solver], [OdeSolverProblem::rodas5p_solver], [OdeSolverProblem::radau_solver], [OdeSolverProblem::adams_solver], [OdeSolverProblem::lsoda_solver], [OdeSolverProblem::ark436l2sa_solver], [OdeSolverProblem::exprb32_solver], [OdeSolverProblem::exprb43_solver], [OdeSolverProblem::symplectic_solver], [OdeSolverProblem::generalized_alpha_solver], [OdeSolverProblem::dde_solver]).
+//! These create a new solver from a provided state and problem. Alternatively, you can create both the solver and the state at once using [OdeSolverProblem::bdf], [OdeSolverProblem::tr_bdf2], [OdeSolverProblem::esdirk34], [OdeSolverProblem::dopri5], [OdeSolverProblem::tsit45], [OdeSolverProblem::vern56], [OdeSolverProblem::vern67], [OdeSolverProblem::vern78], [OdeSolverProblem::vern89], [OdeSolverProblem::rodas4], [OdeSolverProblem::rodas5], [OdeSolverProblem::rodas5p], [OdeSolverProblem::radau], [OdeSolverProblem::adams], [OdeSolverProblem::lsoda], [OdeSolverProblem::ark436l2sa], [OdeSolverProblem::exprb32], [OdeSolverProblem::exprb43], [OdeSolverProblem::velocity_verlet], [OdeSolverProblem::yoshida4], [OdeSolverProblem::newmark], [OdeSolverProblem::generalized_alpha], [OdeSolverProblem::dde_bdf], [OdeSolverProblem::dde_tr_bdf2].
 //!
 //! See the [OdeSolverMethod] trait for a more detailed description of the available methods on each solver. Possible workflows are:
 //! - Use the [OdeSolverMethod::step] method to step the solution forward in time with an internal time step chosen by the solver to meet the error tolerances.
 //! - Use the [OdeSolverMethod::interpolate] method to interpolate the solution between the last two time steps.
 //! - Use the [OdeSolverMethod::set_stop_time] method to stop the solver at a specific time (i.e. this will override the internal time step so that the solver stops at the specified time).
+//! - Use [Bdf::set_step_controller] or [Sdirk::set_step_controller] to choose how the step size is adapted to the error estimates (see [StepController]), for example using the [PiController], [PidController] or [PredictiveController] to reduce step rejections and oscillations in the step size.
+//! - Use [OdeBuilder::solver_options] to limit the step size or the number of steps of the [Bdf] and [Sdirk] solvers, or to change the maximum order and Newton iteration settings (see [SolverOptions]).
+//! - Use [OdeBuilder::constraints] to require that components of the solution stay non-negative, positive, non-positive or negative (see [Constraint]). The [Bdf] and [Sdirk] solvers reject and shrink any step that violates a constraint.
+//! - Use [Bdf::set_projection] or [Sdirk::set_projection] to project the solution onto the manifold of a set of invariants `g(y) = 0` after each step (see [ManifoldProjection]), for example to preserve the total mass or energy of the system over long integrations.
+//! - Use [OdeBuilder::error_norm] to change the norm used to measure the local error and the Newton updates of the [Bdf] and [Sdirk] solvers (see [ErrorNorm]), for example to use a max-norm ([MaxNorm]) or a mesh-weighted L2 norm that can also exclude components from the error control ([WeightedL2Norm]).
+//! - Use [Bdf::set_fixed_step] or [Sdirk::set_fixed_step] to take steps of a prescribed constant size or along a prescribed time grid, with error control disabled (see [FixedStep]).
 //! - Alternatively, use the convenience functions [OdeSolverMethod::solve] or [OdeSolverMethod::solve_dense] that will both initialise the problem and solve the problem up to a specific time or a sequence of times.
+//! - To integrate backwards in time, pass a final time before the current time to [OdeSolverMethod::solve] or decreasing times to [OdeSolverMethod::solve_dense]. When stepping manually, use a negative initial step size ([OdeBuilder::h0]) or reverse the step size using [OdeSolverMethod::state_mut] before calling [OdeSolverMethod::set_stop_time].
 //!
 //! ## DiffSL
 //!
@@ -120,9 +140,16 @@
 //! - [NalgebraLU]: a direct solver that uses the LU decomposition implemented in the [nalgebra](https://nalgebra.org) library.
 //! - [FaerLU]: a direct solver that uses the LU decomposition implemented in the [faer](https://github.com/sarah-ek/faer-rs) library.
 //! - [FaerSparseLU]: a sparse direct solver that uses the sparse LU decomposition implemented in the [faer](https://github.com/sarah-ek/faer-rs).
+//! - [Gmres] and [BiCGStab]: matrix-free iterative solvers that only use the action of the Jacobian on a vector, so the Jacobian is never assembled or factorised.
+//!   With the [NewtonNonlinearSolver] these give a Jacobian-free Newton-Krylov method, and can be used with the [Bdf] and [Sdirk] solvers for large sparse problems.
+//!   For stiff problems these solvers should be used with a [Preconditioner], such as [Jacobi], [BlockJacobi] or the incomplete LU factorisation [Ilu0], which are set up from the
+//!   (sparse) Jacobian each time the Jacobian would be updated, e.g. `Gmres<SparseColMat<f64>, Ilu0<SparseColMat<f64>>>`.
 //!
 //! The provided nonlinear solvers are:
 //! - [NewtonNonlinearSolver]: a nonlinear solver that uses the Newton method.
+//! - [AndersonNonlinearSolver]: a fixed-point iteration with Anderson acceleration, which does not use the Jacobian. This can be used with the [Bdf] solver (using [Bdf::new]) for non-stiff or mildly stiff problems where calculating the Jacobian is expensive.
+//! - [LineSearchNewtonNonlinearSolver]: a Newton method with a backtracking line search, which converges from poor initial guesses. This can be used to calculate
+//!   consistent initial conditions for problems with a mass matrix (see [SolverOptions::line_search_consistent_init]), or steady states.
 //!
 //! ## Matrix and vector types
 //!
@@ -156,7 +183,10 @@ pub mod vector;
 pub mod sundials_sys;
 
 pub use linear_solver::LinearSolver;
-pub use linear_solver::{faer::sparse_lu::FaerSparseLU, FaerLU, NalgebraLU};
+pub use linear_solver::{
+    faer::sparse_lu::FaerSparseLU, BiCGStab, BlockJacobi, FaerLU, Gmres, Ilu0, Jacobi, NalgebraLU,
+    NoPreconditioner, Preconditioner,
+};
 
 pub use matrix::sparse_faer::SparseColMat;
 
@@ -184,23 +214,44 @@ use matrix::{
     sparsity::Dense, sparsity::DenseRef, sparsity::MatrixSparsity, sparsity::MatrixSparsityRef,
     DenseMatrix, MatrixCommon, MatrixRef, MatrixView, MatrixViewMut,
 };
+pub use nonlinear_solver::{
+    anderson::AndersonNonlinearSolver, error_norm::ErrorNorm, error_norm::MaxNorm,
+    error_norm::RmsNorm, error_norm::WeightedL2Norm, line_search::LineSearchNewtonNonlinearSolver,
+    newton::NewtonNonlinearSolver, NonLinearSolver,
+};
 use nonlinear_solver::{
     convergence::Convergence, convergence::ConvergenceStatus, root::RootFinder,
 };
-pub use nonlinear_solver::{newton::NewtonNonlinearSolver, NonLinearSolver};
 use ode_solver::jacobian_update::JacobianUpdate;
 pub use ode_solver::state::{StateRef, StateRefMut};
 pub use ode_solver::{
-    adjoint_equations::AdjointContext, adjoint_equations::AdjointEquations,
-    adjoint_equations::AdjointInit, adjoint_equations::AdjointRhs, bdf::Bdf, bdf_state::BdfState,
-    builder::OdeBuilder, checkpointing::Checkpointing, checkpointing::HermiteInterpolator,
-    equations::AugmentedOdeEquations, equations::AugmentedOdeEquationsImplicit, equations::NoAug,
-    equations::OdeEquations, equations::OdeEquationsAdjoint, equations::OdeEquationsImplicit,
-    equations::OdeEquationsRef, equations::OdeEquationsSens, equations::OdeSolverEquations,
-    method::AdjointOdeSolverMethod, method::AugmentedOdeSolverMethod, method::OdeSolverMethod,
-    method::OdeSolverStopReason, problem::OdeSolverProblem, sdirk::Sdirk, sdirk_state::SdirkState,
-    sens_equations::SensEquations, sens_equations::SensInit, sens_equations::SensRhs,
-    state::OdeSolverState, tableau::Tableau,
+    adams::Adams, adjoint_equations::AdjointContext, adjoint_equations::AdjointEquations,
+    adjoint_equations::AdjointInit, adjoint_equations::AdjointRhs, bdf::Bdf, bdf_dae::BdfDae,
+    bdf_state::BdfState, brownian::BrownianPath, builder::OdeBuilder, bvp::BvpProblem,
+    bvp::BvpSolution, checkpointing::Checkpointing, checkpointing::HermiteInterpolator,
+    constraints::Constraint, dae_problem::DaeProblem, dde::Dde, equations::AugmentedOdeEquations,
+    equations::AugmentedOdeEquationsImplicit, equations::NoAug, equations::OdeEquations,
+    equations::OdeEquationsAdjoint, equations::OdeEquationsDelay, equations::OdeEquationsImex,
+    equations::OdeEquationsImexRef, equations::OdeEquationsImplicit, equations::OdeEquationsRef,
+    equations::OdeEquationsResidual, equations::OdeEquationsSecondOrder,
+    equations::OdeEquationsSecondOrderRef, equations::OdeEquationsSens,
+    equations::OdeSolverEquations, explicit_rk::ExplicitRk,
+    exponential_rosenbrock::ExponentialRosenbrock,
+    exponential_rosenbrock_tableau::ExponentialRosenbrockTableau, fixed_step::FixedStep,
+    generalized_alpha::GeneralizedAlpha, generalized_alpha::GeneralizedAlphaParameters,
+    imex_ark::ImexArk, initial_sens_equations::InitialSensEquations,
+    initial_sens_equations::InitialSensInit, initial_sens_equations::InitialSensRhs, lsoda::Lsoda,
+    lsoda::LsodaMethod, method::AdjointOdeSolverMethod, method::AugmentedOdeSolverMethod,
+    method::OdeSolverMethod, method::OdeSolverStopReason, method::SecondOrderOdeSolverMethod,
+    options::SolverOptions, parareal::Parareal, problem::OdeSolverProblem,
+    projection::ManifoldProjection, radau::Radau, rosenbrock::Rosenbrock,
+    rosenbrock_tableau::RosenbrockTableau, sde::Sde, sde::SdeMethod, sde_problem::NoiseType,
+    sde_problem::SdeProblem, sdirk::Sdirk, sdirk_state::SdirkState, sens_equations::SensEquations,
+    sens_equations::SensInit, sens_equations::SensRhs, state::OdeSolverState,
+    step_controller::IController, step_controller::PiController, step_controller::PidController,
+    step_controller::PredictiveController, step_controller::StepController,
+    step_controller::StepControllerClone, symplectic::Symplectic,
+    symplectic_tableau::SymplecticTableau, tableau::Tableau,
 };
 pub use op::constant_op::{ConstantOp, ConstantOpSens, ConstantOpSensAdjoint};
 pub use op::linear_op::{LinearOp, LinearOpSens, LinearOpTranspose};
@@ -208,9 +259,11 @@ pub use op::nonlinear_op::{
     NonLinearOp, NonLinearOpAdjoint, NonLinearOpJacobian, NonLinearOpSens, NonLinearOpSensAdjoint,
 };
 pub use op::{
-    closure::Closure, closure_with_adjoint::ClosureWithAdjoint, constant_closure::ConstantClosure,
-    constant_closure_with_adjoint::ConstantClosureWithAdjoint, linear_closure::LinearClosure,
-    unit::UnitCallable, BuilderOp, Op, ParameterisedOp,
+    closure::Closure, closure_delay::ClosureDelay, closure_diffusion::ClosureDiffusion,
+    closure_imex::ClosureImex, closure_residual::ClosureResidual,
+    closure_second_order::ClosureSecondOrder, closure_with_adjoint::ClosureWithAdjoint,
+    constant_closure::ConstantClosure, constant_closure_with_adjoint::ConstantClosureWithAdjoint,
+    linear_closure::LinearClosure, unit::UnitCallable, BuilderOp, Op, ParameterisedOp,
 };
 use op::{
     closure_no_jac::ClosureNoJac, closure_with_sens::ClosureWithSens,
@@ -0,0 +1,215 @@
+use std::cell::RefCell;
+
+use num_traits::{One, Zero};
+
+use crate::{
+    error::{DiffsolError, LinearSolverError},
+    linear_solver_error, LinearSolver, Matrix, NonLinearOpJacobian, Vector,
+};
+
+use super::{dot, preconditioner::NoPreconditioner, Preconditioner};
+
+/// A matrix-free [LinearSolver] that uses the BiCGStab method (H. A. van der Vorst, Bi-CGSTAB: A fast and smoothly converging variant of Bi-CG
+/// for the solution of nonsymmetric linear systems, SIAM J. Sci. Stat. Comput. 13 (1992) 631–644).
+///
+/// Like [crate::Gmres], only the action of the linearised operator on a vector is used, but the memory used does not grow with the number of
+/// iterations (two products with the operator are needed for each iteration). The preconditioner `P` (see [Preconditioner]) is applied on the right,
+/// and the default is no preconditioning. The operator must be given when solving (see [LinearSolver::solve_in_place_with_op]),
+/// so this solver can only be used by the [crate::Bdf] and [crate::Sdirk] ODE solvers (without forward sensitivities), and [LinearSolver::solve_in_place] returns an error.
+#[derive(Clone)]
+pub struct BiCGStab<M: Matrix, P: Preconditioner<M> = NoPreconditioner> {
+    max_iter: usize,
+    preconditioner: P,
+    x: M::V,
+    t: M::T,
+    work: RefCell<BiCGStabWork<M::V>>,
+}
+
+#[derive(Clone)]
+struct BiCGStabWork<V: Vector> {
+    x: V,
+    r: V,
+    r0: V,
+    p: V,
+    p_hat: V,
+    v: V,
+    s: V,
+    s_hat: V,
+    t: V,
+}
+
+impl<V: Vector> BiCGStabWork<V> {
+    fn new(nstates: usize) -> Self {
+        Self {
+            x: V::zeros(nstates),
+            r: V::zeros(nstates),
+            r0: V::zeros(nstates),
+            p: V::zeros(nstates),
+            p_hat: V::zeros(nstates),
+            v: V::zeros(nstates),
+            s: V::zeros(nstates),
+            s_hat: V::zeros(nstates),
+            t: V::zeros(nstates),
+        }
+    }
+}
+
+impl<M: Matrix, P: Preconditioner<M>> BiCGStab<M, P> {
+    /// Create a new solver with the given preconditioner, which fails if the problem has not been solved after `max_iter` iterations.
+    pub fn new(max_iter: usize, preconditioner: P) -> Self {
+        Self {
+            max_iter,
+            preconditioner,
+            x: M::V::zeros(0),
+            t: M::T::zero(),
+            work: RefCell::new(BiCGStabWork::new(0)),
+        }
+    }
+
+    pub fn preconditioner(&self) -> &P {
+        &self.preconditioner
+    }
+}
+
+impl<M: Matrix, P: Preconditioner<M>> Default for BiCGStab<M, P> {
+    fn default() -> Self {
+        Self::new(500, P::default())
+    }
+}
+
+impl<M: Matrix, P: Preconditioner<M>> LinearSolver<M> for BiCGStab<M, P> {
+    fn set_problem<C: NonLinearOpJacobian<V = M::V, T = M::T, M = M>>(&mut self, op: &C) {
+        self.preconditioner.set_problem(op);
+        let n = op.nstates();
+        self.x = M::V::zeros(n);
+        self.work = RefCell::new(BiCGStabWork::new(n));
+    }
+
+    fn set_linearisation<C: NonLinearOpJacobian<V = M::V, T = M::T, M = M>>(
+        &mut self,
+        op: &C,
+        x: &M::V,
+        t: M::T,
+    ) {
+        self.preconditioner.set_linearisation(op, x, t);
+        self.x.copy_from(x);
+        self.t = t;
+    }
+
+    fn solve_in_place(&self, _b: &mut M::V) -> Result<(), DiffsolError> {
+        Err(linear_solver_error!(
+            Other,
+            "BiCGStab is matrix-free and must be given the operator using solve_in_place_with_op"
+                .to_string()
+        ))
+    }
+
+    fn is_matrix_free(&self) -> bool {
+        true
+    }
+
+    fn solve_in_place_with_op<C: NonLinearOpJacobian<V = M::V, T = M::T, M = M>>(
+        &self,
+        op: &C,
+        b: &mut M::V,
+        rtol: M::T,
+    ) -> Result<(), DiffsolError> {
+        if b.len() != self.x.len() {
+            return Err(linear_solver_error!(LinearSolverNotSetup));
+        }
+        let bnorm = b.norm();
+        if bnorm == M::T::zero() {
+            return Ok(());
+        }
+        let tol = rtol * bnorm;
+        let mut work = self.work.borrow_mut();
+        let BiCGStabWork {
+            x,
+            r,
+            r0,
+            p,
+            p_hat,
+            v,
+            s,
+            s_hat,
+            t,
+        } = &mut *work;
+
+        // start from x = 0, so the initial residual is b
+        x.fill(M::T::zero());
+        r.copy_from(b);
+        r0.copy_from(b);
+        p.fill(M::T::zero());
+        v.fill(M::T::zero());
+        let mut rho = M::T::one();
+        let mut alpha = M::T::one();
+        let mut omega = M::T::one();
+        for _ in 0..self.max_iter {
+            let rho_new = dot(r0, r);
+            if rho_new == M::T::zero() || omega == M::T::zero() {
+                return Err(linear_solver_error!(
+                    Other,
+                    "BiCGStab failed due to a breakdown of the method".to_string()
+                ));
+            }
+
+            // p = r + beta (p - omega v)
+            let beta = (rho_new / rho) * (alpha / omega);
+            p.axpy(-omega, v, M::T::one());
+            p.axpy(M::T::one(), r, beta);
+            rho = rho_new;
+
+            // v = A P^{-1} p
+            p_hat.copy_from(p);
+            self.preconditioner.solve_in_place(p_hat);
+            op.jac_mul_inplace(&self.x, self.t, p_hat, v);
+            let r0v = dot(r0, v);
+            if r0v == M::T::zero() {
+                return Err(linear_solver_error!(
+                    Other,
+                    "BiCGStab failed due to a breakdown of the method".to_string()
+                ));
+            }
+            alpha = rho / r0v;
+
+            // s = r - alpha v
+            s.copy_from(r);
+            s.axpy(-alpha, v, M::T::one());
+            if s.norm() <= tol {
+                x.axpy(alpha, p_hat, M::T::one());
+                b.copy_from(x);
+                return Ok(());
+            }
+
+            // t = A P^{-1} s
+            s_hat.copy_from(s);
+            self.preconditioner.solve_in_place(s_hat);
+            op.jac_mul_inplace(&self.x, self.t, s_hat, t);
+            let tt = dot(t, t);
+            omega = if tt == M::T::zero() {
+                M::T::zero()
+            } else {
+                dot(t, s) / tt
+            };
+            x.axpy(alpha, p_hat, M::T::one());
+            x.axpy(omega, s_hat, M::T::one());
+
+            // r = s - omega t
+            r.copy_from(s);
+            r.axpy(-omega, t, M::T::one());
+            if r.norm() <= tol {
+                b.copy_from(x);
+                return Ok(());
+            }
+        }
+        Err(linear_solver_error!(
+            Other,
+            format!(
+                "BiCGStab did not converge after {} iterations, residual norm {} > {}",
+                self.max_iter,
+                r.norm(),
+                tol
+            )
+        ))
+    }
+}
@@ -0,0 +1,57 @@
+use num_traits::abs;
+
+use crate::Scalar;
+
+/// LU factorisation with partial pivoting of the small `m x m` matrix `a` (stored by rows), returns `None` if `a` is singular.
+pub(crate) fn lu_factorise<T: Scalar>(a: &mut [T], m: usize) -> Option<Vec<usize>> {
+    let mut pivots = Vec::with_capacity(m);
+    for k in 0..m {
+        let mut p = k;
+        for i in k + 1..m {
+            if abs(a[i * m + k]) > abs(a[p * m + k]) {
+                p = i;
+            }
+        }
+        if a[p * m + k] == T::zero() {
+            return None;
+        }
+        if p != k {
+            for j in 0..m {
+                a.swap(k * m + j, p * m + j);
+            }
+        }
+        pivots.push(p);
+        for i in k + 1..m {
+            let l = a[i * m + k] / a[k * m + k];
+            a[i * m + k] = l;
+            for j in k + 1..m {
+                let akj = a[k * m + j];
+                a[i * m + j] -= l * akj;
+            }
+        }
+    }
+    Some(pivots)
+}
+
+/// Solve `a x = b` in place using the factorisation from [lu_factorise].
+pub(crate) fn lu_solve<T: Scalar>(a: &[T], pivots: &[usize], m: usize, b: &mut [T]) {
+    for (k, &p) in pivots.iter().enumerate() {
+        b.swap(k, p);
+    }
+    for i in 0..m {
+        let row = &a[i * m..i * m + i];
+        let sum = row
+            .iter()
+            .zip(b.iter())
+            .fold(b[i], |acc, (&aij, &bj)| acc - aij * bj);
+        b[i] = sum;
+    }
+    for i in (0..m).rev() {
+        let row = &a[i * m + i + 1..(i + 1) * m];
+        let sum = row
+            .iter()
+            .zip(b[i + 1..].iter())
+            .fold(b[i], |acc, (&aij, &bj)| acc - aij * bj);
+        b[i] = sum / a[i * m + i];
+    }
+}
@@ -0,0 +1,237 @@
+use std::cell::RefCell;
+
+use nalgebra::ComplexField;
+use num_traits::{abs, One, Zero};
+
+use crate::{
+    error::{DiffsolError, LinearSolverError},
+    linear_solver_error, LinearSolver, Matrix, NonLinearOpJacobian, Vector,
+};
+
+use super::{dot, preconditioner::NoPreconditioner, Preconditioner};
+
+/// A matrix-free [LinearSolver] that uses the restarted GMRES method (Y. Saad and M. H. Schultz, GMRES: A generalized minimal residual algorithm
+/// for solving nonsymmetric linear systems, SIAM J. Sci. Stat. Comput. 7 (1986) 856–869).
+///
+/// Only the action of the linearised operator on a vector is calculated using [NonLinearOpJacobian::jac_mul_inplace], and the Jacobian is only assembled
+/// if the preconditioner uses it, so this solver can be used for large sparse problems where factorising the Jacobian is expensive. Used with the
+/// [crate::NewtonNonlinearSolver] this gives a Jacobian-free Newton-Krylov method, where the tolerance of each linear solve is chosen by the
+/// nonlinear solver. The number of iterations depends on the conditioning of the problem, and can be reduced using the preconditioner `P` (see [Preconditioner]),
+/// which is applied on the right so that the residual of the original problem is used to check convergence. The default is no preconditioning.
+///
+/// The operator must be given when solving (see [LinearSolver::solve_in_place_with_op]), so this solver can only be used by the [crate::Bdf] and [crate::Sdirk]
+/// ODE solvers (without forward sensitivities), and [LinearSolver::solve_in_place] returns an error.
+#[derive(Clone)]
+pub struct Gmres<M: Matrix, P: Preconditioner<M> = NoPreconditioner> {
+    restart: usize,
+    max_restarts: usize,
+    preconditioner: P,
+    x: M::V,
+    t: M::T,
+    work: RefCell<GmresWork<M::V>>,
+}
+
+#[derive(Clone)]
+struct GmresWork<V: Vector> {
+    basis: Vec<V>,
+    w: V,
+    z: V,
+    x: V,
+    // upper Hessenberg matrix, (restart + 1) x restart, stored by columns
+    hessenberg: Vec<V::T>,
+    cs: Vec<V::T>,
+    sn: Vec<V::T>,
+    g: Vec<V::T>,
+}
+
+impl<V: Vector> GmresWork<V> {
+    fn new(nstates: usize, restart: usize) -> Self {
+        Self {
+            basis: vec![V::zeros(nstates); restart + 1],
+            w: V::zeros(nstates),
+            z: V::zeros(nstates),
+            x: V::zeros(nstates),
+            hessenberg: vec![V::T::zero(); (restart + 1) * restart],
+            cs: vec![V::T::zero(); restart],
+            sn: vec![V::T::zero(); restart],
+            g: vec![V::T::zero(); restart + 1],
+        }
+    }
+}
+
+impl<M: Matrix, P: Preconditioner<M>> Gmres<M, P> {
+    /// Create a new solver with the given preconditioner, which restarts after `restart` iterations and fails if the problem has not been solved after `max_restarts` restarts.
+    pub fn new(restart: usize, max_restarts: usize, preconditioner: P) -> Self {
+        let restart = restart.max(1);
+        Self {
+            restart,
+            max_restarts,
+            preconditioner,
+            x: M::V::zeros(0),
+            t: M::T::zero(),
+            work: RefCell::new(GmresWork::new(0, restart)),
+        }
+    }
+
+    pub fn preconditioner(&self) -> &P {
+        &self.preconditioner
+    }
+}
+
+impl<M: Matrix, P: Preconditioner<M>> Default for Gmres<M, P> {
+    fn default() -> Self {
+        Self::new(30, 10, P::default())
+    }
+}
+
+impl<M: Matrix, P: Preconditioner<M>> LinearSolver<M> for Gmres<M, P> {
+    fn set_problem<C: NonLinearOpJacobian<V = M::V, T = M::T, M = M>>(&mut self, op: &C) {
+        self.preconditioner.set_problem(op);
+        let n = op.nstates();
+        self.x = M::V::zeros(n);
+        self.work = RefCell::new(GmresWork::new(n, self.restart.min(n).max(1)));
+    }
+
+    fn set_linearisation<C: NonLinearOpJacobian<V = M::V, T = M::T, M = M>>(
+        &mut self,
+        op: &C,
+        x: &M::V,
+        t: M::T,
+    ) {
+        self.preconditioner.set_linearisation(op, x, t);
+        self.x.copy_from(x);
+        self.t = t;
+    }
+
+    fn solve_in_place(&self, _b: &mut M::V) -> Result<(), DiffsolError> {
+        Err(linear_solver_error!(
+            Other,
+            "Gmres is matrix-free and must be given the operator using solve_in_place_with_op"
+                .to_string()
+        ))
+    }
+
+    fn is_matrix_free(&self) -> bool {
+        true
+    }
+
+    fn solve_in_place_with_op<C: NonLinearOpJacobian<V = M::V, T = M::T, M = M>>(
+        &self,
+        op: &C,
+        b: &mut M::V,
+        rtol: M::T,
+    ) -> Result<(), DiffsolError> {
+        if b.len() != self.x.len() {
+            return Err(linear_solver_error!(LinearSolverNotSetup));
+        }
+        let bnorm = b.norm();
+        if bnorm == M::T::zero() {
+            return Ok(());
+        }
+        let tol = rtol * bnorm;
+        let mut work = self.work.borrow_mut();
+        let GmresWork {
+            basis,
+            w,
+            z,
+            x,
+            hessenberg: h,
+            cs,
+            sn,
+            g,
+        } = &mut *work;
+        let m = cs.len();
+        let idx = |i: usize, j: usize| i + j * (m + 1);
+
+        // start from x = 0, so the initial residual is b
+        x.fill(M::T::zero());
+        w.copy_from(b);
+        let mut nrestarts = 0;
+        loop {
+            let beta = w.norm();
+            if beta <= tol {
+                b.copy_from(x);
+                return Ok(());
+            }
+            if nrestarts >= self.max_restarts {
+                return Err(linear_solver_error!(
+                    Other,
+                    format!(
+                        "Gmres did not converge after {} restarts, residual norm {} > {}",
+                        self.max_restarts, beta, tol
+                    )
+                ));
+            }
+            nrestarts += 1;
+            basis[0].axpy(M::T::one() / beta, w, M::T::zero());
+            g.fill(M::T::zero());
+            g[0] = beta;
+
+            // arnoldi process, the least squares problem is solved using givens rotations
+            let mut k = 0;
+            for j in 0..m {
+                // w = A P^{-1} v_j
+                z.copy_from(&basis[j]);
+                self.preconditioner.solve_in_place(z);
+                op.jac_mul_inplace(&self.x, self.t, z, w);
+
+                // modified Gram-Schmidt
+                for (i, v) in basis.iter().enumerate().take(j + 1) {
+                    let hij = dot(w, v);
+                    h[idx(i, j)] = hij;
+                    w.axpy(-hij, v, M::T::one());
+                }
+                let hnext = w.norm();
+                h[idx(j + 1, j)] = hnext;
+                if hnext != M::T::zero() {
+                    basis[j + 1].axpy(M::T::one() / hnext, w, M::T::zero());
+                }
+
+                // apply the previous rotations to the new column, then eliminate the subdiagonal entry
+                for (i, (&c, &s)) in cs.iter().zip(sn.iter()).enumerate().take(j) {
+                    let hi = h[idx(i, j)];
+                    let hi1 = h[idx(i + 1, j)];
+                    h[idx(i, j)] = c * hi + s * hi1;
+                    h[idx(i + 1, j)] = c * hi1 - s * hi;
+                }
+                let hjj = h[idx(j, j)];
+                let denom = (hjj * hjj + hnext * hnext).sqrt();
+                if denom == M::T::zero() {
+                    return Err(linear_solver_error!(
+                        Other,
+                        "Gmres failed, the linearised operator is singular".to_string()
+                    ));
+                }
+                cs[j] = hjj / denom;
+                sn[j] = hnext / denom;
+                h[idx(j, j)] = denom;
+                h[idx(j + 1, j)] = M::T::zero();
+                g[j + 1] = -sn[j] * g[j];
+                g[j] *= cs[j];
+                k = j + 1;
+                if abs(g[j + 1]) <= tol || hnext == M::T::zero() {
+                    break;
+                }
+            }
+
+            // solve the upper triangular system H y = g (y is stored in g) and update x += P^{-1} V y
+            for i in (0..k).rev() {
+                let mut sum = g[i];
+                for (l, &gl) in g.iter().enumerate().take(k).skip(i + 1) {
+                    sum -= h[idx(i, l)] * gl;
+                }
+                g[i] = sum / h[idx(i, i)];
+            }
+            z.fill(M::T::zero());
+            for (&gi, v) in g.iter().zip(basis.iter()).take(k) {
+                z.axpy(gi, v, M::T::one());
+            }
+            self.preconditioner.solve_in_place(z);
+            x.axpy(M::T::one(), z, M::T::one());
+
+            // w = b - A x
+            op.jac_mul_inplace(&self.x, self.t, x, w);
+            w.axpy(M::T::one(), b, -M::T::one());
+        }
+    }
+}
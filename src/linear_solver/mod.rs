@@ -1,4 +1,11 @@
-use crate::{error::DiffsolError, Matrix, NonLinearOpJacobian};
+use num_traits::Zero;
+
+use crate::{error::DiffsolError, Matrix, NonLinearOpJacobian, Vector};
+
+pub mod bicgstab;
+pub(crate) mod dense_lu;
+pub mod gmres;
+pub mod preconditioner;
 
 #[cfg(feature = "nalgebra")]
 pub mod nalgebra;
@@ -12,8 +19,11 @@ pub mod sundials;
 #[cfg(feature = "suitesparse")]
 pub mod suitesparse;
 
+pub use bicgstab::BiCGStab;
 pub use faer::lu::LU as FaerLU;
+pub use gmres::Gmres;
 pub use nalgebra::lu::LU as NalgebraLU;
+pub use preconditioner::{BlockJacobi, Ilu0, Jacobi, NoPreconditioner, Preconditioner};
 
 /// A solver for the linear problem `Ax = b`, where `A` is a linear operator that is obtained by taking the linearisation of a nonlinear operator `C`
 pub trait LinearSolver<M: Matrix>: Default {
@@ -40,6 +50,29 @@ pub trait LinearSolver<M: Matrix>: Default {
     }
 
     fn solve_in_place(&self, b: &mut M::V) -> Result<(), DiffsolError>;
+
+    /// Returns true if the solver only uses the action of the linearised operator on a vector (i.e. [NonLinearOpJacobian::jac_mul_inplace]),
+    /// in which case the operator must be passed to the solver using [Self::solve_in_place_with_op].
+    fn is_matrix_free(&self) -> bool {
+        false
+    }
+
+    /// Solve the problem `Ax = b` in place, where `A` is the linearisation of `op` at the point given to [Self::set_linearisation].
+    /// Iterative solvers stop once the residual satisfies `||b - Ax|| <= rtol ||b||`.
+    /// By default `op` and `rtol` are ignored and the problem is solved using [Self::solve_in_place].
+    fn solve_in_place_with_op<C: NonLinearOpJacobian<V = M::V, T = M::T, M = M>>(
+        &self,
+        _op: &C,
+        b: &mut M::V,
+        _rtol: M::T,
+    ) -> Result<(), DiffsolError> {
+        self.solve_in_place(b)
+    }
+}
+
+/// The dot product of two vectors
+pub(crate) fn dot<V: Vector>(a: &V, b: &V) -> V::T {
+    a.binary_fold(b, V::T::zero(), |acc, x, y, _| acc + x * y)
 }
 
 pub struct LinearSolveSolution<V> {
@@ -56,7 +89,7 @@ impl<V> LinearSolveSolution<V> {
 #[cfg(test)]
 pub mod tests {
     use crate::{
-        linear_solver::{FaerLU, NalgebraLU},
+        linear_solver::{BiCGStab, FaerLU, Gmres, Ilu0, NalgebraLU},
         op::{closure::Closure, ParameterisedOp},
         scalar::scale,
         vector::VectorRef,
@@ -120,6 +153,33 @@ pub mod tests {
         }
     }
 
+    pub fn test_matrix_free_linear_solver<'a, C>(
+        mut solver: impl LinearSolver<C::M>,
+        op: C,
+        rtol: C::T,
+        atol: &'a C::V,
+        solns: Vec<LinearSolveSolution<C::V>>,
+    ) where
+        C: NonLinearOpJacobian,
+        for<'b> &'b C::V: VectorRef<C::V>,
+    {
+        assert!(solver.is_matrix_free());
+        solver.set_problem(&op);
+        let x = C::V::zeros(op.nout());
+        let t = C::T::zero();
+        solver.set_linearisation(&op, &x, t);
+        for soln in solns {
+            // the operator must be given to the solver
+            assert!(solver.solve(&soln.b).is_err());
+            let mut x = soln.b.clone();
+            solver
+                .solve_in_place_with_op(&op, &mut x, C::T::from(1e-10))
+                .unwrap();
+            let tol = { &soln.x * scale(rtol) + atol };
+            x.assert_eq(&soln.x, &tol);
+        }
+    }
+
     type MCpuNalgebra = nalgebra::DMatrix<f64>;
     type MCpuFaer = faer::Mat<f64>;
 
@@ -139,4 +199,31 @@ pub mod tests {
         let s = FaerLU::default();
         test_linear_solver(s, op, rtol, &atol, solns);
     }
+
+    #[test]
+    fn test_gmres_nalgebra() {
+        let (op, rtol, atol, solns) = linear_problem::<MCpuNalgebra>();
+        let p = nalgebra::DVector::zeros(0);
+        let op = ParameterisedOp::new(&op, &p);
+        let s: Gmres<MCpuNalgebra> = Gmres::default();
+        test_matrix_free_linear_solver(s, op, rtol, &atol, solns);
+    }
+
+    #[test]
+    fn test_gmres_ilu0_nalgebra() {
+        let (op, rtol, atol, solns) = linear_problem::<MCpuNalgebra>();
+        let p = nalgebra::DVector::zeros(0);
+        let op = ParameterisedOp::new(&op, &p);
+        let s: Gmres<MCpuNalgebra, Ilu0<MCpuNalgebra>> = Gmres::default();
+        test_matrix_free_linear_solver(s, op, rtol, &atol, solns);
+    }
+
+    #[test]
+    fn test_bicgstab_faer() {
+        let (op, rtol, atol, solns) = linear_problem::<MCpuFaer>();
+        let p = faer::Col::zeros(0);
+        let op = ParameterisedOp::new(&op, &p);
+        let s: BiCGStab<MCpuFaer> = BiCGStab::default();
+        test_matrix_free_linear_solver(s, op, rtol, &atol, solns);
+    }
 }
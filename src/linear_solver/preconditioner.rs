@@ -0,0 +1,393 @@
+use num_traits::{One, Zero};
+
+use crate::{Matrix, NonLinearOpJacobian, Scalar, Vector};
+
+use super::dense_lu::{lu_factorise, lu_solve};
+
+type LuFactors<T> = (Vec<T>, Vec<usize>);
+
+/// A preconditioner `P` for the matrix-free linear solvers [crate::Gmres] and [crate::BiCGStab], which solve the right-preconditioned problem `A P^{-1} y = b`, `x = P^{-1} y`.
+///
+/// `P` should approximate the linearised operator `A`, while being cheap to set up and apply. The preconditioner is set up in [Self::set_linearisation],
+/// which the linear solver calls each time its linearisation is updated, so within the ODE solvers it is refreshed on the same schedule as the Jacobian
+/// (see [crate::ode_solver::jacobian_update::JacobianUpdate]).
+///
+/// The provided [Jacobi], [BlockJacobi] and [Ilu0] preconditioners assemble the Jacobian of the operator using its sparsity pattern (e.g. a [crate::SparseColMat]
+/// or [nalgebra_sparse::CscMatrix]) and factorise it, and can also be set up from any other matrix using their `set_matrix` method.
+/// To precondition using an approximation of the operator instead (e.g. only the stiff terms of a PDE), implement this trait for your own type.
+pub trait Preconditioner<M: Matrix>: Default {
+    /// Set the problem to be preconditioned, this will normally allocate the Jacobian using the sparsity pattern of `op`.
+    fn set_problem<C: NonLinearOpJacobian<V = M::V, T = M::T, M = M>>(&mut self, op: &C);
+
+    /// Set up the preconditioner for the linearisation of `op` at the point `x` and time `t`.
+    fn set_linearisation<C: NonLinearOpJacobian<V = M::V, T = M::T, M = M>>(
+        &mut self,
+        op: &C,
+        x: &M::V,
+        t: M::T,
+    );
+
+    /// Apply the preconditioner in place, i.e. `x <- P^{-1} x`.
+    fn solve_in_place(&self, x: &mut M::V);
+}
+
+/// The identity preconditioner `P = I`, i.e. no preconditioning (the default for the Krylov solvers).
+#[derive(Clone, Copy, Debug, Default)]
+pub struct NoPreconditioner;
+
+impl<M: Matrix> Preconditioner<M> for NoPreconditioner {
+    fn set_problem<C: NonLinearOpJacobian<V = M::V, T = M::T, M = M>>(&mut self, _op: &C) {}
+
+    fn set_linearisation<C: NonLinearOpJacobian<V = M::V, T = M::T, M = M>>(
+        &mut self,
+        _op: &C,
+        _x: &M::V,
+        _t: M::T,
+    ) {
+    }
+
+    fn solve_in_place(&self, _x: &mut M::V) {}
+}
+
+/// Allocate the Jacobian of `op` using its sparsity pattern
+fn new_jacobian<M: Matrix, C: NonLinearOpJacobian<V = M::V, T = M::T, M = M>>(op: &C) -> M {
+    M::new_from_sparsity(op.nout(), op.nstates(), op.jacobian_sparsity())
+}
+
+/// The Jacobi (diagonal) preconditioner `P = diag(A)`. Zero diagonal entries are replaced by one.
+#[derive(Clone)]
+pub struct Jacobi<M: Matrix> {
+    matrix: Option<M>,
+    inv_diagonal: M::V,
+}
+
+impl<M: Matrix> Default for Jacobi<M> {
+    fn default() -> Self {
+        Self {
+            matrix: None,
+            inv_diagonal: M::V::zeros(0),
+        }
+    }
+}
+
+impl<M: Matrix> Jacobi<M> {
+    /// Set up the preconditioner from the matrix `a`
+    pub fn set_matrix(&mut self, a: &M) {
+        let mut diagonal = a.diagonal();
+        diagonal.map_inplace(|d| {
+            if d == M::T::zero() {
+                M::T::one()
+            } else {
+                M::T::one() / d
+            }
+        });
+        self.inv_diagonal = diagonal;
+    }
+}
+
+impl<M: Matrix> Preconditioner<M> for Jacobi<M> {
+    fn set_problem<C: NonLinearOpJacobian<V = M::V, T = M::T, M = M>>(&mut self, op: &C) {
+        self.matrix = Some(new_jacobian(op));
+        self.inv_diagonal = M::V::from_element(op.nstates(), M::T::one());
+    }
+
+    fn set_linearisation<C: NonLinearOpJacobian<V = M::V, T = M::T, M = M>>(
+        &mut self,
+        op: &C,
+        x: &M::V,
+        t: M::T,
+    ) {
+        let mut matrix = self.matrix.take().expect("Matrix not set");
+        op.jacobian_inplace(x, t, &mut matrix);
+        self.set_matrix(&matrix);
+        self.matrix = Some(matrix);
+    }
+
+    fn solve_in_place(&self, x: &mut M::V) {
+        x.component_mul_assign(&self.inv_diagonal);
+    }
+}
+
+/// The block-Jacobi preconditioner, where `P` is the block diagonal part of `A` with blocks of size `BLOCK_SIZE` (the last block is smaller if
+/// the number of states is not a multiple of `BLOCK_SIZE`).
+///
+/// This is suited to problems where the states are ordered so that strongly coupled states are next to each other, for example a reaction-diffusion
+/// model with the species at each mesh point stored together, where the reaction terms only couple states within each block.
+/// Each block is factorised using a dense LU decomposition, and a singular block is replaced by the identity.
+#[derive(Clone)]
+pub struct BlockJacobi<M: Matrix, const BLOCK_SIZE: usize> {
+    matrix: Option<M>,
+    // the LU factorisation and pivots of each block, stored by rows
+    blocks: Vec<Option<LuFactors<M::T>>>,
+}
+
+impl<M: Matrix, const BLOCK_SIZE: usize> Default for BlockJacobi<M, BLOCK_SIZE> {
+    fn default() -> Self {
+        Self {
+            matrix: None,
+            blocks: Vec::new(),
+        }
+    }
+}
+
+impl<M: Matrix, const BLOCK_SIZE: usize> BlockJacobi<M, BLOCK_SIZE> {
+    const BS: usize = if BLOCK_SIZE == 0 { 1 } else { BLOCK_SIZE };
+
+    /// Set up the preconditioner from the matrix `a`
+    pub fn set_matrix(&mut self, a: &M) {
+        let n = a.nrows();
+        let bs = Self::BS;
+        let block_len = |b: usize| bs.min(n - b * bs);
+        let nblocks = n.div_ceil(bs);
+        let mut blocks = (0..nblocks)
+            .map(|b| vec![M::T::zero(); block_len(b) * block_len(b)])
+            .collect::<Vec<_>>();
+        for (i, j, &v) in a.triplet_iter() {
+            let b = i / bs;
+            if j / bs == b {
+                let m = block_len(b);
+                blocks[b][(i - b * bs) * m + (j - b * bs)] = v;
+            }
+        }
+        self.blocks = blocks
+            .into_iter()
+            .enumerate()
+            .map(|(b, mut lu)| lu_factorise(&mut lu, block_len(b)).map(|pivots| (lu, pivots)))
+            .collect();
+    }
+}
+
+impl<M: Matrix, const BLOCK_SIZE: usize> Preconditioner<M> for BlockJacobi<M, BLOCK_SIZE> {
+    fn set_problem<C: NonLinearOpJacobian<V = M::V, T = M::T, M = M>>(&mut self, op: &C) {
+        self.matrix = Some(new_jacobian(op));
+        self.blocks = Vec::new();
+    }
+
+    fn set_linearisation<C: NonLinearOpJacobian<V = M::V, T = M::T, M = M>>(
+        &mut self,
+        op: &C,
+        x: &M::V,
+        t: M::T,
+    ) {
+        let mut matrix = self.matrix.take().expect("Matrix not set");
+        op.jacobian_inplace(x, t, &mut matrix);
+        self.set_matrix(&matrix);
+        self.matrix = Some(matrix);
+    }
+
+    fn solve_in_place(&self, x: &mut M::V) {
+        let bs = Self::BS;
+        let x = x.as_mut_slice();
+        for (b, block) in self.blocks.iter().enumerate() {
+            if let Some((lu, pivots)) = block {
+                let start = b * bs;
+                let m = pivots.len();
+                lu_solve(lu, pivots, m, &mut x[start..start + m]);
+            }
+        }
+    }
+}
+
+/// The incomplete LU factorisation with zero fill-in, ILU(0), where `P = LU` and `L` and `U` have the same sparsity pattern as the lower and
+/// upper triangular parts of `A`. For a dense matrix this is the LU decomposition (without pivoting).
+///
+/// This is usually a much better preconditioner than [Jacobi] for discretised PDEs. Zero pivots are replaced by one.
+#[derive(Clone)]
+pub struct Ilu0<M: Matrix> {
+    matrix: Option<M>,
+    // L (unit lower triangular, diagonal not stored) and U stored together by rows
+    row_ptrs: Vec<usize>,
+    col_indices: Vec<usize>,
+    values: Vec<M::T>,
+    diagonal: Vec<usize>,
+}
+
+impl<M: Matrix> Default for Ilu0<M> {
+    fn default() -> Self {
+        Self {
+            matrix: None,
+            row_ptrs: vec![0],
+            col_indices: Vec::new(),
+            values: Vec::new(),
+            diagonal: Vec::new(),
+        }
+    }
+}
+
+impl<M: Matrix> Ilu0<M> {
+    /// Set up the preconditioner from the matrix `a`
+    pub fn set_matrix(&mut self, a: &M) {
+        let n = a.nrows();
+
+        // convert to compressed rows (sorted by column), including the diagonal
+        let mut rows = vec![Vec::new(); n];
+        for (i, j, &v) in a.triplet_iter() {
+            rows[i].push((j, v));
+        }
+        self.row_ptrs = Vec::with_capacity(n + 1);
+        self.row_ptrs.push(0);
+        self.col_indices.clear();
+        self.values.clear();
+        self.diagonal.clear();
+        for (i, row) in rows.iter_mut().enumerate() {
+            if !row.iter().any(|&(j, _)| j == i) {
+                row.push((i, M::T::zero()));
+            }
+            row.sort_by_key(|&(j, _)| j);
+            let start = self.col_indices.len();
+            for &(j, v) in row.iter() {
+                // duplicate entries are summed
+                if self.col_indices.len() > start && self.col_indices.last() == Some(&j) {
+                    *self.values.last_mut().unwrap() += v;
+                } else {
+                    if j == i {
+                        self.diagonal.push(self.col_indices.len());
+                    }
+                    self.col_indices.push(j);
+                    self.values.push(v);
+                }
+            }
+            self.row_ptrs.push(self.col_indices.len());
+        }
+
+        // incomplete factorisation (IKJ variant), the position of each entry in the current row is stored in `position`
+        let mut position = vec![usize::MAX; n];
+        for i in 0..n {
+            let (start, end) = (self.row_ptrs[i], self.row_ptrs[i + 1]);
+            for idx in start..end {
+                position[self.col_indices[idx]] = idx;
+            }
+            for idx in start..end {
+                let k = self.col_indices[idx];
+                if k >= i {
+                    break;
+                }
+                let lik = self.values[idx] / self.values[self.diagonal[k]];
+                self.values[idx] = lik;
+                for kj in self.diagonal[k] + 1..self.row_ptrs[k + 1] {
+                    let ij = position[self.col_indices[kj]];
+                    if ij != usize::MAX {
+                        let ukj = self.values[kj];
+                        self.values[ij] -= lik * ukj;
+                    }
+                }
+            }
+            if self.values[self.diagonal[i]] == M::T::zero() {
+                self.values[self.diagonal[i]] = M::T::one();
+            }
+            for idx in start..end {
+                position[self.col_indices[idx]] = usize::MAX;
+            }
+        }
+    }
+}
+
+impl<M: Matrix> Preconditioner<M> for Ilu0<M> {
+    fn set_problem<C: NonLinearOpJacobian<V = M::V, T = M::T, M = M>>(&mut self, op: &C) {
+        *self = Self {
+            matrix: Some(new_jacobian(op)),
+            ..Self::default()
+        };
+    }
+
+    fn set_linearisation<C: NonLinearOpJacobian<V = M::V, T = M::T, M = M>>(
+        &mut self,
+        op: &C,
+        x: &M::V,
+        t: M::T,
+    ) {
+        let mut matrix = self.matrix.take().expect("Matrix not set");
+        op.jacobian_inplace(x, t, &mut matrix);
+        self.set_matrix(&matrix);
+        self.matrix = Some(matrix);
+    }
+
+    fn solve_in_place(&self, x: &mut M::V) {
+        let n = self.diagonal.len();
+        let x = x.as_mut_slice();
+        // L y = x
+        for i in 0..n {
+            let row = self.row_ptrs[i]..self.diagonal[i];
+            let sum = sparse_dot(&self.col_indices[row.clone()], &self.values[row], x);
+            x[i] -= sum;
+        }
+        // U x = y
+        for i in (0..n).rev() {
+            let row = self.diagonal[i] + 1..self.row_ptrs[i + 1];
+            let sum = sparse_dot(&self.col_indices[row.clone()], &self.values[row], x);
+            x[i] = (x[i] - sum) / self.values[self.diagonal[i]];
+        }
+    }
+}
+
+fn sparse_dot<T: Scalar>(col_indices: &[usize], values: &[T], x: &[T]) -> T {
+    col_indices
+        .iter()
+        .zip(values.iter())
+        .fold(T::zero(), |acc, (&j, &v)| acc + v * x[j])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{BlockJacobi, Ilu0, Jacobi, Preconditioner};
+    use crate::{Matrix, SparseColMat, Vector};
+
+    type M = SparseColMat<f64>;
+    type V = faer::Col<f64>;
+
+    // the tridiagonal matrix with 4 on the diagonal and -1 on the off-diagonals
+    fn tridiagonal(n: usize) -> M {
+        let mut triplets = Vec::new();
+        for i in 0..n {
+            triplets.push((i, i, 4.0));
+            if i > 0 {
+                triplets.push((i, i - 1, -1.0));
+                triplets.push((i - 1, i, -1.0));
+            }
+        }
+        M::try_from_triplets(n, n, triplets).unwrap()
+    }
+
+    fn residual(a: &M, x: &V, b: &V) -> f64 {
+        let mut r = b.clone();
+        a.gemv(1.0, x, -1.0, &mut r);
+        r.norm()
+    }
+
+    #[test]
+    fn test_preconditioners() {
+        let n = 5;
+        let a = tridiagonal(n);
+        let b = V::from_vec(vec![1.0, 2.0, 3.0, 4.0, 5.0]);
+
+        let mut jacobi = Jacobi::<M>::default();
+        jacobi.set_matrix(&a);
+        let mut x = b.clone();
+        jacobi.solve_in_place(&mut x);
+        x.assert_eq_st(&V::from_vec(vec![0.25, 0.5, 0.75, 1.0, 1.25]), 1e-12);
+
+        // a single block is the exact inverse
+        let mut block_jacobi = BlockJacobi::<M, 5>::default();
+        block_jacobi.set_matrix(&a);
+        let mut x = b.clone();
+        block_jacobi.solve_in_place(&mut x);
+        assert!(residual(&a, &x, &b) < 1e-12);
+
+        // blocks of size 2, 2 and 1
+        let mut block_jacobi = BlockJacobi::<M, 2>::default();
+        block_jacobi.set_matrix(&a);
+        let mut x = b.clone();
+        block_jacobi.solve_in_place(&mut x);
+        x.assert_eq_st(
+            &V::from_vec(vec![6.0 / 15.0, 9.0 / 15.0, 16.0 / 15.0, 19.0 / 15.0, 1.25]),
+            1e-12,
+        );
+
+        // ilu(0) of a tridiagonal matrix is the exact lu decomposition
+        let mut ilu = Ilu0::<M>::default();
+        ilu.set_matrix(&a);
+        let mut x = b.clone();
+        ilu.solve_in_place(&mut x);
+        assert!(residual(&a, &x, &b) < 1e-12);
+    }
+}
@@ -0,0 +1,232 @@
+use num_traits::{One, Zero};
+
+use crate::{
+    error::{DiffsolError, NonLinearSolverError},
+    linear_solver::dot,
+    non_linear_solver_error, Convergence, ConvergenceStatus, Matrix, NonLinearOp,
+    NonLinearOpJacobian, NonLinearSolver, Scalar, Vector,
+};
+
+/// A nonlinear solver for the problem `F(x) = 0` that uses a fixed-point iteration with Anderson acceleration (D. G. Anderson, Iterative procedures
+/// for nonlinear integral equations, J. ACM 12 (1965) 547–560, and H. F. Walker and P. Ni, Anderson acceleration for fixed-point iterations,
+/// SIAM J. Numer. Anal. 49 (2011) 1715–1735).
+///
+/// The underlying iteration is the damped fixed-point iteration `x <- x - beta F(x)`, where `beta` is the damping factor, so no Jacobian is calculated
+/// or factorised. This converges if `F(x) = x - g(x)` and `g` is a contraction, for example for the implicit equations of the [crate::Bdf] solver applied
+/// to non-stiff or mildly stiff ODEs without a mass matrix. Each update is accelerated using the residuals of the last `depth` iterations, which are
+/// combined to minimise the linearised residual in a least squares sense.
+///
+/// To use this solver with [crate::Bdf], construct the solver directly using [crate::Bdf::new], e.g. `Bdf::new(&problem, state, AndersonNonlinearSolver::default())`.
+#[derive(Clone)]
+pub struct AndersonNonlinearSolver<M: Matrix> {
+    depth: usize,
+    damping: M::T,
+    f: M::V,
+    f_prev: M::V,
+    dx: M::V,
+    dx_history: Vec<M::V>,
+    df_history: Vec<M::V>,
+}
+
+impl<M: Matrix> AndersonNonlinearSolver<M> {
+    /// Create a new solver that uses the last `depth` iterations to accelerate each update (a `depth` of zero gives the damped fixed-point iteration),
+    /// with the damping factor `damping` (normally between 0 and 1).
+    pub fn new(depth: usize, damping: M::T) -> Self {
+        Self {
+            depth,
+            damping,
+            f: M::V::zeros(0),
+            f_prev: M::V::zeros(0),
+            dx: M::V::zeros(0),
+            dx_history: Vec::new(),
+            df_history: Vec::new(),
+        }
+    }
+    pub fn depth(&self) -> usize {
+        self.depth
+    }
+    pub fn damping(&self) -> M::T {
+        self.damping
+    }
+}
+
+impl<M: Matrix> Default for AndersonNonlinearSolver<M> {
+    fn default() -> Self {
+        Self::new(5, M::T::one())
+    }
+}
+
+impl<M: Matrix> NonLinearSolver<M> for AndersonNonlinearSolver<M> {
+    fn set_problem<C: NonLinearOpJacobian<V = M::V, T = M::T, M = M>>(&mut self, op: &C) {
+        let n = op.nstates();
+        self.f = M::V::zeros(n);
+        self.f_prev = M::V::zeros(n);
+        self.dx = M::V::zeros(n);
+        self.dx_history = vec![M::V::zeros(n); self.depth];
+        self.df_history = vec![M::V::zeros(n); self.depth];
+    }
+
+    // no jacobian is used
+    fn reset_jacobian<C: NonLinearOpJacobian<V = M::V, T = M::T, M = M>>(
+        &mut self,
+        _op: &C,
+        _x: &M::V,
+        _t: M::T,
+    ) {
+    }
+
+    fn solve_linearised_in_place(&self, _x: &mut M::V) -> Result<(), DiffsolError> {
+        Err(non_linear_solver_error!(
+            Other,
+            "AndersonNonlinearSolver does not use a Jacobian, so cannot solve the linearised problem"
+                .to_string()
+        ))
+    }
+
+    fn solve_in_place<C: NonLinearOp<V = M::V, T = M::T, M = M>>(
+        &mut self,
+        op: &C,
+        xn: &mut M::V,
+        t: M::T,
+        error_y: &M::V,
+        convergence: &mut Convergence<M::V>,
+    ) -> Result<(), DiffsolError> {
+        if self.f.len() != op.nstates() {
+            panic!("AndersonNonlinearSolver::solve_in_place() called before set_problem");
+        }
+        if xn.len() != op.nstates() {
+            panic!("AndersonNonlinearSolver::solve() called with state of wrong size, expected {}, got {}", op.nstates(), xn.len());
+        }
+        let depth = self.dx_history.len();
+        let mut gram = vec![M::T::zero(); depth * depth];
+        let mut rhs = vec![M::T::zero(); depth];
+        let mut gamma = vec![M::T::zero(); depth];
+        let beta = self.damping;
+        convergence.reset();
+        let mut k = 0;
+        loop {
+            op.call_inplace(xn, t, &mut self.f);
+
+            // the differences x_k - x_{k-1} and F(x_k) - F(x_{k-1}) are stored in a circular buffer
+            if k > 0 && depth > 0 {
+                let slot = (k - 1) % depth;
+                self.dx_history[slot].copy_from(&self.dx);
+                self.df_history[slot].copy_from(&self.f);
+                self.df_history[slot] -= &self.f_prev;
+            }
+            let m = k.min(depth);
+
+            // dx = -beta F(x) - (dX - beta dF) gamma, where gamma minimises ||F(x) - dF gamma||
+            self.dx.axpy(-beta, &self.f, M::T::zero());
+            if m > 0 {
+                for i in 0..m {
+                    for j in 0..=i {
+                        let gij = dot(&self.df_history[i], &self.df_history[j]);
+                        gram[i * m + j] = gij;
+                        gram[j * m + i] = gij;
+                    }
+                    rhs[i] = dot(&self.df_history[i], &self.f);
+                }
+                least_squares(&gram[..m * m], &rhs[..m], m, &mut gamma[..m]);
+                for ((&g, dx), df) in gamma
+                    .iter()
+                    .zip(self.dx_history.iter())
+                    .zip(self.df_history.iter())
+                    .take(m)
+                {
+                    self.dx.axpy(-g, dx, M::T::one());
+                    self.dx.axpy(g * beta, df, M::T::one());
+                }
+            }
+            xn.axpy(M::T::one(), &self.dx, M::T::one());
+            self.f_prev.copy_from(&self.f);
+            k += 1;
+
+            match convergence.check_new_iteration(&mut self.dx, error_y) {
+                ConvergenceStatus::Continue => continue,
+                ConvergenceStatus::Converged => return Ok(()),
+                // the rate of the first iterations is not representative of the accelerated iteration, so only
+                // give up on a slow rate of convergence once the history is full
+                ConvergenceStatus::Diverged
+                    if k <= depth && convergence.niter() < convergence.max_iter() =>
+                {
+                    continue
+                }
+                ConvergenceStatus::Diverged | ConvergenceStatus::MaximumIterations => break,
+            }
+        }
+        Err(non_linear_solver_error!(
+            Other,
+            "Anderson accelerated fixed-point iteration did not converge".to_string()
+        ))
+    }
+}
+
+// a column is dropped if its component orthogonal to the previous columns is smaller than this (relative to its norm, squared)
+const DROP_TOL: f64 = 1e-8;
+
+/// Solve the normal equations `G gamma = rhs` of a small least squares problem, where `G` is the `m x m` Gram matrix of the columns (stored by rows).
+/// A Cholesky factorisation is used which drops any column that is (nearly) linearly dependent on the previous columns, and sets its coefficient to zero.
+fn least_squares<T: Scalar>(gram: &[T], rhs: &[T], m: usize, gamma: &mut [T]) {
+    let mut l = vec![T::zero(); m * m];
+    let mut active = vec![false; m];
+    for j in 0..m {
+        let mut d = gram[j * m + j];
+        for k in 0..j {
+            d -= l[j * m + k] * l[j * m + k];
+        }
+        if d <= T::zero() || d <= T::from(DROP_TOL) * gram[j * m + j] {
+            continue;
+        }
+        let ljj = d.sqrt();
+        l[j * m + j] = ljj;
+        active[j] = true;
+        for i in j + 1..m {
+            let mut s = gram[i * m + j];
+            for k in 0..j {
+                s -= l[i * m + k] * l[j * m + k];
+            }
+            l[i * m + j] = s / ljj;
+        }
+    }
+
+    // L z = rhs, then L^T gamma = z
+    for j in 0..m {
+        gamma[j] = if active[j] {
+            let mut s = rhs[j];
+            for k in 0..j {
+                s -= l[j * m + k] * gamma[k];
+            }
+            s / l[j * m + j]
+        } else {
+            T::zero()
+        };
+    }
+    for j in (0..m).rev() {
+        if active[j] {
+            let mut s = gamma[j];
+            for i in j + 1..m {
+                s -= l[i * m + j] * gamma[i];
+            }
+            gamma[j] = s / l[j * m + j];
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::least_squares;
+
+    #[test]
+    fn test_least_squares() {
+        // columns (1, 0, 0), (1, 1, 0) and (2, 1, 0), the last is dependent on the first two
+        let gram = [1.0, 1.0, 2.0, 1.0, 2.0, 3.0, 2.0, 3.0, 5.0];
+        // b = (1, 2, 3), the least squares solution is (-1, 2)
+        let rhs = [1.0, 3.0, 4.0];
+        let mut gamma = [0.0; 3];
+        least_squares(&gram, &rhs, 3, &mut gamma);
+        assert!((gamma[0] + 1.0).abs() < 1e-12);
+        assert!((gamma[1] - 2.0).abs() < 1e-12);
+        assert_eq!(gamma[2], 0.0);
+    }
+}
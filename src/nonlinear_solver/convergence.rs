@@ -1,7 +1,10 @@
+use std::sync::Arc;
+
 use nalgebra::ComplexField;
 use num_traits::{One, Pow};
 
-use crate::{scalar::IndexType, Scalar, Vector};
+use super::error_norm::squared_norm;
+use crate::{scalar::IndexType, ErrorNorm, Scalar, Vector};
 
 #[derive(Clone)]
 pub struct Convergence<'a, V: Vector> {
@@ -11,6 +14,7 @@ pub struct Convergence<'a, V: Vector> {
     max_iter: IndexType,
     niter: IndexType,
     old_norm: Option<V::T>,
+    error_norm: Option<Arc<dyn ErrorNorm<V::T>>>,
 }
 
 pub enum ConvergenceStatus {
@@ -30,6 +34,10 @@ impl<'a, V: Vector> Convergence<'a, V> {
     pub fn niter(&self) -> IndexType {
         self.niter
     }
+    /// Set the norm used to measure the size of each Newton update, or `None` to use the default weighted RMS norm (see [ErrorNorm]).
+    pub fn set_error_norm(&mut self, error_norm: Option<Arc<dyn ErrorNorm<V::T>>>) {
+        self.error_norm = error_norm;
+    }
     pub fn new(rtol: V::T, atol: &'a V) -> Self {
         let minimum_tol = V::T::from(10.0) * V::T::EPSILON / rtol;
         let maximum_tol = V::T::from(0.03);
@@ -47,6 +55,7 @@ impl<'a, V: Vector> Convergence<'a, V> {
             max_iter: 10,
             old_norm: None,
             niter: 0,
+            error_norm: None,
         }
     }
     pub fn reset(&mut self) {
@@ -56,7 +65,7 @@ impl<'a, V: Vector> Convergence<'a, V> {
 
     pub fn check_new_iteration(&mut self, dy: &mut V, y: &V) -> ConvergenceStatus {
         self.niter += 1;
-        let norm = dy.squared_norm(y, self.atol, self.rtol).sqrt();
+        let norm = squared_norm(self.error_norm.as_ref(), dy, y, self.atol, self.rtol).sqrt();
         // if norm is zero then we are done
         if norm <= V::T::EPSILON {
             return ConvergenceStatus::Converged;
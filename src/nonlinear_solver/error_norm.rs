@@ -0,0 +1,201 @@
+use std::{fmt::Debug, sync::Arc};
+
+use num_traits::abs;
+
+use crate::{
+    error::{DiffsolError, OdeSolverError},
+    ode_solver_error, Scalar, Vector, VectorView,
+};
+
+/// A norm used to measure an error vector (e.g. the local error estimate of a step, or the update of a Newton iteration) relative to the tolerances.
+///
+/// The error `e` is scaled component-wise by `atol + rtol * |y|`, where `y` is the current solution, and the norm returns the **square** of the
+/// norm of the scaled error, so that a value of 1 or less means that the error is within tolerance.
+/// The norm is set using [crate::OdeBuilder::error_norm], and is used by the [crate::Bdf] and [crate::Sdirk] solvers for the error of the state in the
+/// error test of each step and to check the convergence of their Newton iterations (the errors of any outputs and sensitivities use the RMS norm). The default is the weighted root-mean-square norm [RmsNorm].
+pub trait ErrorNorm<T: Scalar>: Debug + Send + Sync {
+    /// Return the squared norm of `error` scaled by `atol + rtol * |y|`.
+    fn squared_norm(&self, error: &[T], y: &[T], atol: &[T], rtol: T) -> T;
+
+    /// Check that the norm can be used for a problem with `nstates` states.
+    fn check(&self, _nstates: usize) -> Result<(), DiffsolError> {
+        Ok(())
+    }
+}
+
+/// Return the squared norm of `error` using `error_norm`, or the default weighted RMS norm [Vector::squared_norm] if this is `None`.
+pub(crate) fn squared_norm<V: Vector>(
+    error_norm: Option<&Arc<dyn ErrorNorm<V::T>>>,
+    error: &V,
+    y: &V,
+    atol: &V,
+    rtol: V::T,
+) -> V::T {
+    match error_norm {
+        Some(error_norm) => {
+            error_norm.squared_norm(error.as_slice(), y.as_slice(), atol.as_slice(), rtol)
+        }
+        None => error.squared_norm(y, atol, rtol),
+    }
+}
+
+/// Same as [squared_norm], for a view of the error vector.
+pub(crate) fn view_squared_norm<'b, V: Vector>(
+    error_norm: Option<&Arc<dyn ErrorNorm<V::T>>>,
+    error: V::View<'b>,
+    y: &V,
+    atol: &V,
+    rtol: V::T,
+) -> V::T {
+    match error_norm {
+        Some(error_norm) => {
+            error_norm.squared_norm(error.as_slice(), y.as_slice(), atol.as_slice(), rtol)
+        }
+        None => error.squared_norm(y, atol, rtol),
+    }
+}
+
+fn scaled<T: Scalar>(error: T, y: T, atol: T, rtol: T) -> T {
+    error / (abs(y) * rtol + atol)
+}
+
+/// The weighted root-mean-square norm `sqrt(1/n sum_i (e_i / (atol_i + rtol |y_i|))^2)`, this is the default norm (see [ErrorNorm]).
+#[derive(Clone, Copy, Debug, Default)]
+pub struct RmsNorm;
+
+impl<T: Scalar> ErrorNorm<T> for RmsNorm {
+    fn squared_norm(&self, error: &[T], y: &[T], atol: &[T], rtol: T) -> T {
+        let mut acc = T::zero();
+        for ((&e, &y), &a) in error.iter().zip(y.iter()).zip(atol.iter()) {
+            let e = scaled(e, y, a, rtol);
+            acc += e * e;
+        }
+        acc / T::from(error.len() as f64)
+    }
+}
+
+/// The weighted max-norm `max_i |e_i / (atol_i + rtol |y_i|)|` (see [ErrorNorm]).
+#[derive(Clone, Copy, Debug, Default)]
+pub struct MaxNorm;
+
+impl<T: Scalar> ErrorNorm<T> for MaxNorm {
+    fn squared_norm(&self, error: &[T], y: &[T], atol: &[T], rtol: T) -> T {
+        let mut acc = T::zero();
+        for ((&e, &y), &a) in error.iter().zip(y.iter()).zip(atol.iter()) {
+            let e = scaled(e, y, a, rtol);
+            let e2 = e * e;
+            if e2 > acc {
+                acc = e2;
+            }
+        }
+        acc
+    }
+}
+
+/// The weighted L2 norm `sqrt(sum_i w_i (e_i / (atol_i + rtol |y_i|))^2 / sum_i w_i)` with a non-negative weight `w_i` for each component (see [ErrorNorm]).
+///
+/// The weights can be used to approximate the L2 norm of a function on a non-uniform mesh (e.g. using the size of each mesh cell),
+/// and a component with a weight of zero is excluded from the error control entirely. With equal weights this is the same as [RmsNorm].
+#[derive(Clone, Debug)]
+pub struct WeightedL2Norm<T: Scalar> {
+    weights: Vec<T>,
+    sum_weights: T,
+}
+
+impl<T: Scalar> WeightedL2Norm<T> {
+    /// Create a new norm with the given weights (one for each state), which must be non-negative and not all zero.
+    pub fn new(weights: Vec<T>) -> Self {
+        let sum_weights = weights.iter().fold(T::zero(), |acc, &w| acc + w);
+        Self {
+            weights,
+            sum_weights,
+        }
+    }
+
+    pub fn weights(&self) -> &[T] {
+        self.weights.as_slice()
+    }
+}
+
+impl<T: Scalar> ErrorNorm<T> for WeightedL2Norm<T> {
+    fn squared_norm(&self, error: &[T], y: &[T], atol: &[T], rtol: T) -> T {
+        let mut acc = T::zero();
+        for (((&e, &y), &a), &w) in error
+            .iter()
+            .zip(y.iter())
+            .zip(atol.iter())
+            .zip(self.weights.iter())
+        {
+            if w != T::zero() {
+                let e = scaled(e, y, a, rtol);
+                acc += w * e * e;
+            }
+        }
+        acc / self.sum_weights
+    }
+
+    fn check(&self, nstates: usize) -> Result<(), DiffsolError> {
+        if self.weights.len() != nstates {
+            return Err(ode_solver_error!(
+                BuilderError,
+                format!(
+                    "Number of error norm weights ({}) must be equal to the number of states ({})",
+                    self.weights.len(),
+                    nstates
+                )
+            ));
+        }
+        if self
+            .weights
+            .iter()
+            .any(|&w| w < T::zero() || Scalar::is_nan(w))
+            || self.sum_weights <= T::zero()
+        {
+            return Err(ode_solver_error!(
+                BuilderError,
+                "Error norm weights must be non-negative and not all zero"
+            ));
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ErrorNorm, MaxNorm, RmsNorm, WeightedL2Norm};
+    use crate::Vector;
+
+    #[test]
+    fn test_error_norms() {
+        let error = [1.0, -2.0, 3.0];
+        let y = [0.0, 1.0, -1.0];
+        let atol = [1.0, 1.0, 2.0];
+        let rtol = 1.0;
+        // the error is scaled by [1, 2, 3]
+        assert!((RmsNorm.squared_norm(&error, &y, &atol, rtol) - 1.0).abs() < 1e-12);
+        assert!((MaxNorm.squared_norm(&error, &y, &atol, rtol) - 1.0).abs() < 1e-12);
+
+        let error = [1.0, 0.0, 3.0];
+        assert!((RmsNorm.squared_norm(&error, &y, &atol, rtol) - 2.0 / 3.0).abs() < 1e-12);
+        assert!((MaxNorm.squared_norm(&error, &y, &atol, rtol) - 1.0).abs() < 1e-12);
+
+        // the rms norm is the same as the default norm used by the vectors
+        let v = |x: &[f64]| nalgebra::DVector::from_vec(x.to_vec());
+        assert!(
+            (RmsNorm.squared_norm(&error, &y, &atol, rtol)
+                - v(&error).squared_norm(&v(&y), &v(&atol), rtol))
+            .abs()
+                < 1e-12
+        );
+
+        // equal weights give the rms norm, a zero weight excludes a component
+        let norm = WeightedL2Norm::new(vec![2.0, 2.0, 2.0]);
+        assert!(norm.check(3).is_ok());
+        assert!(norm.check(2).is_err());
+        assert!((norm.squared_norm(&error, &y, &atol, rtol) - 2.0 / 3.0).abs() < 1e-12);
+        let norm = WeightedL2Norm::new(vec![1.0, 1.0, 0.0]);
+        assert!((norm.squared_norm(&error, &y, &atol, rtol) - 0.5).abs() < 1e-12);
+        assert!(WeightedL2Norm::new(vec![1.0, -1.0, 1.0]).check(3).is_err());
+        assert!(WeightedL2Norm::new(vec![0.0, 0.0, 0.0]).check(3).is_err());
+    }
+}
@@ -0,0 +1,240 @@
+use num_traits::{One, Zero};
+
+use crate::{
+    error::{DiffsolError, NonLinearSolverError},
+    non_linear_solver_error, Convergence, ConvergenceStatus, LinearSolver, Matrix, NonLinearOp,
+    NonLinearOpJacobian, NonLinearSolver, Scalar, Vector,
+};
+
+use super::newton::forcing_term;
+
+// sufficient decrease parameter of the Armijo condition
+const ARMIJO_C1: f64 = 1e-4;
+// each backtracking step reduces the damping factor by a factor in this range
+const BACKTRACK_MIN: f64 = 0.1;
+const BACKTRACK_MAX: f64 = 0.5;
+
+/// A globalised Newton solver for the nonlinear problem `F(x) = 0`, that uses the linear solver `Ls` to calculate the Newton direction `dx = -J^{-1} F(x)`
+/// and a backtracking line search to choose the damping factor `lambda` of each update `x <- x + lambda dx`.
+///
+/// The damping factor starts at 1 (a full Newton step) and is reduced using a quadratic model of `||F(x + lambda dx)||^2` until the Armijo condition
+/// `||F(x + lambda dx)|| <= (1 - c lambda (1 - eta)) ||F(x)||` is satisfied, where `c = 1e-4` and `eta` is the relative tolerance of the linear solve
+/// (zero unless the linear solver is matrix-free, see [crate::NewtonNonlinearSolver]). Unlike [crate::NewtonNonlinearSolver], the iteration does not give up
+/// if it is not converging quickly, and the Jacobian is recalculated at every iteration, so it converges from much worse initial guesses at the cost of more work per iteration.
+/// The Jacobian is only recalculated if the solver is called using [NonLinearSolver::solve_in_place_with_jacobian], otherwise the linearisation set by
+/// [NonLinearSolver::reset_jacobian] is used for every iteration.
+/// This makes it suitable for calculating consistent initial conditions (see [crate::SolverOptions::line_search_consistent_init]) and steady states,
+/// rather than the implicit equations of each step of an ODE solver.
+///
+/// Convergence is checked using the [Convergence] object only after full Newton steps, and the solver fails if it has not converged after `max_iter` iterations
+/// (default: 50), or if the damping factor is reduced below `min_damping` (default: `1e-6`).
+pub struct LineSearchNewtonNonlinearSolver<M: Matrix, Ls: LinearSolver<M>> {
+    linear_solver: Ls,
+    max_iter: usize,
+    min_damping: M::T,
+    f: M::V,
+    dx: M::V,
+    x_trial: M::V,
+    f_trial: M::V,
+}
+
+impl<M: Matrix, Ls: LinearSolver<M>> LineSearchNewtonNonlinearSolver<M, Ls> {
+    pub fn new(linear_solver: Ls) -> Self {
+        Self {
+            linear_solver,
+            max_iter: 50,
+            min_damping: M::T::from(1e-6),
+            f: M::V::zeros(0),
+            dx: M::V::zeros(0),
+            x_trial: M::V::zeros(0),
+            f_trial: M::V::zeros(0),
+        }
+    }
+    pub fn linear_solver(&self) -> &Ls {
+        &self.linear_solver
+    }
+    pub fn max_iter(&self) -> usize {
+        self.max_iter
+    }
+    pub fn set_max_iter(&mut self, value: usize) {
+        self.max_iter = value;
+    }
+    pub fn min_damping(&self) -> M::T {
+        self.min_damping
+    }
+    pub fn set_min_damping(&mut self, value: M::T) {
+        self.min_damping = value;
+    }
+
+    /// Run the line search iteration, `newton_direction` is given the linear solver, the current iterate `x` and `-F(x)` in `dx`,
+    /// and overwrites `dx` with the Newton direction, returning the relative tolerance used for the linear solve.
+    fn line_search<C: NonLinearOp<V = M::V, T = M::T, M = M>>(
+        &mut self,
+        op: &C,
+        xn: &mut M::V,
+        t: M::T,
+        error_y: &M::V,
+        convergence: &mut Convergence<M::V>,
+        mut newton_direction: impl FnMut(
+            &mut Ls,
+            &M::V,
+            &mut M::V,
+            M::T,
+            M::T,
+        ) -> Result<M::T, DiffsolError>,
+    ) -> Result<(), DiffsolError> {
+        if self.f.len() != op.nstates() {
+            panic!("LineSearchNewtonNonlinearSolver::solve_in_place() called before set_problem");
+        }
+        if xn.len() != op.nstates() {
+            panic!("LineSearchNewtonNonlinearSolver::solve() called with state of wrong size, expected {}, got {}", op.nstates(), xn.len());
+        }
+        let one = M::T::one();
+        let c1 = M::T::from(ARMIJO_C1);
+        convergence.reset();
+        op.call_inplace(xn, t, &mut self.f);
+        let mut fnorm = self.f.norm();
+        let mut prev_fnorm = M::T::zero();
+        for _ in 0..self.max_iter {
+            if fnorm == M::T::zero() {
+                return Ok(());
+            }
+            self.dx.axpy(-one, &self.f, M::T::zero());
+            let eta =
+                newton_direction(&mut self.linear_solver, xn, &mut self.dx, fnorm, prev_fnorm)?;
+
+            // backtrack from a full Newton step until the residual is sufficiently reduced
+            let mut lambda = one;
+            loop {
+                self.x_trial.copy_from(xn);
+                self.x_trial.axpy(lambda, &self.dx, one);
+                op.call_inplace(&self.x_trial, t, &mut self.f_trial);
+                let fnorm_trial = self.f_trial.norm();
+                if fnorm_trial <= (one - c1 * lambda * (one - eta)) * fnorm {
+                    prev_fnorm = fnorm;
+                    fnorm = fnorm_trial;
+                    break;
+                }
+
+                // minimise the quadratic model of ||F(x + lambda dx)||^2, or halve the damping factor if the residual is not finite
+                let mut factor = M::T::from(BACKTRACK_MAX);
+                if !Scalar::is_nan(fnorm_trial) && fnorm_trial != M::T::INFINITY {
+                    let f0_sq = fnorm * fnorm;
+                    let denom =
+                        fnorm_trial * fnorm_trial - f0_sq + M::T::from(2.0) * f0_sq * lambda;
+                    if denom > M::T::zero() {
+                        factor = f0_sq * lambda / denom;
+                    }
+                }
+                if factor < M::T::from(BACKTRACK_MIN) {
+                    factor = M::T::from(BACKTRACK_MIN);
+                } else if factor > M::T::from(BACKTRACK_MAX) {
+                    factor = M::T::from(BACKTRACK_MAX);
+                }
+                lambda *= factor;
+                if lambda < self.min_damping {
+                    return Err(non_linear_solver_error!(
+                        Other,
+                        format!(
+                            "Line search failed, no sufficient decrease of the residual norm {} was found",
+                            fnorm
+                        )
+                    ));
+                }
+            }
+            xn.copy_from(&self.x_trial);
+            std::mem::swap(&mut self.f, &mut self.f_trial);
+
+            // the convergence rate is only estimated from consecutive full Newton steps
+            if lambda == one {
+                if matches!(
+                    convergence.check_new_iteration(&mut self.dx, error_y),
+                    ConvergenceStatus::Converged
+                ) {
+                    return Ok(());
+                }
+            } else {
+                convergence.reset();
+            }
+        }
+        Err(non_linear_solver_error!(
+            Other,
+            format!(
+                "Line search Newton iteration did not converge after {} iterations, residual norm {}",
+                self.max_iter, fnorm
+            )
+        ))
+    }
+}
+
+impl<M: Matrix, Ls: LinearSolver<M>> Default for LineSearchNewtonNonlinearSolver<M, Ls> {
+    fn default() -> Self {
+        Self::new(Ls::default())
+    }
+}
+
+impl<M: Matrix, Ls: LinearSolver<M>> NonLinearSolver<M> for LineSearchNewtonNonlinearSolver<M, Ls> {
+    fn set_problem<C: NonLinearOpJacobian<V = M::V, T = M::T, M = M>>(&mut self, op: &C) {
+        self.linear_solver.set_problem(op);
+        let n = op.nstates();
+        self.f = M::V::zeros(n);
+        self.dx = M::V::zeros(n);
+        self.x_trial = M::V::zeros(n);
+        self.f_trial = M::V::zeros(n);
+    }
+
+    fn reset_jacobian<C: NonLinearOpJacobian<V = M::V, T = M::T, M = M>>(
+        &mut self,
+        op: &C,
+        x: &M::V,
+        t: M::T,
+    ) {
+        self.linear_solver.set_linearisation(op, x, t);
+    }
+
+    fn solve_linearised_in_place(&self, x: &mut M::V) -> Result<(), DiffsolError> {
+        self.linear_solver.solve_in_place(x)
+    }
+
+    fn solve_in_place<C: NonLinearOp<V = M::V, T = M::T, M = M>>(
+        &mut self,
+        op: &C,
+        xn: &mut M::V,
+        t: M::T,
+        error_y: &M::V,
+        convergence: &mut Convergence<M::V>,
+    ) -> Result<(), DiffsolError> {
+        self.line_search(op, xn, t, error_y, convergence, |ls, _x, dx, _, _| {
+            ls.solve_in_place(dx)?;
+            Ok(M::T::zero())
+        })
+    }
+
+    fn solve_in_place_with_jacobian<C: NonLinearOpJacobian<V = M::V, T = M::T, M = M>>(
+        &mut self,
+        op: &C,
+        xn: &mut M::V,
+        t: M::T,
+        error_y: &M::V,
+        convergence: &mut Convergence<M::V>,
+    ) -> Result<(), DiffsolError> {
+        self.line_search(
+            op,
+            xn,
+            t,
+            error_y,
+            convergence,
+            |ls, x, dx, fnorm, prev_fnorm| {
+                ls.set_linearisation(op, x, t);
+                if ls.is_matrix_free() {
+                    let eta = forcing_term(fnorm, prev_fnorm);
+                    ls.solve_in_place_with_op(op, dx, eta)?;
+                    Ok(eta)
+                } else {
+                    ls.solve_in_place(dx)?;
+                    Ok(M::T::zero())
+                }
+            },
+        )
+    }
+}
@@ -35,7 +35,7 @@ pub trait NonLinearSolver<M: Matrix>: Default {
         convergence: &mut Convergence<'_, M::V>,
     ) -> Result<M::V, DiffsolError> {
         let mut x = x.clone();
-        self.solve_in_place(op, &mut x, t, error_y, convergence)?;
+        self.solve_in_place_with_jacobian(op, &mut x, t, error_y, convergence)?;
         Ok(x)
     }
 
@@ -49,21 +49,41 @@ pub trait NonLinearSolver<M: Matrix>: Default {
         convergence: &mut Convergence<'_, M::V>,
     ) -> Result<(), DiffsolError>;
 
+    /// Solve the problem `F(x) = 0` in place, where the Jacobian of `op` is available to the solver,
+    /// e.g. for matrix-free linear solvers that only use the action of the Jacobian on a vector.
+    /// By default this is the same as [Self::solve_in_place].
+    fn solve_in_place_with_jacobian<C: NonLinearOpJacobian<V = M::V, T = M::T, M = M>>(
+        &mut self,
+        op: &C,
+        x: &mut C::V,
+        t: C::T,
+        error_y: &C::V,
+        convergence: &mut Convergence<'_, M::V>,
+    ) -> Result<(), DiffsolError> {
+        self.solve_in_place(op, x, t, error_y, convergence)
+    }
+
     /// Solve the linearised problem `J * x = b`, where `J` was calculated using [Self::reset_jacobian].
     /// The input `b` is provided in `x`, and the solution is returned in `x`.
     fn solve_linearised_in_place(&self, x: &mut M::V) -> Result<(), DiffsolError>;
 }
 
+pub mod anderson;
 pub mod convergence;
+pub mod error_norm;
+pub mod line_search;
 pub mod newton;
 pub mod root;
 
 //tests
 #[cfg(test)]
 pub mod tests {
-    use self::newton::NewtonNonlinearSolver;
+    use self::{
+        anderson::AndersonNonlinearSolver, line_search::LineSearchNewtonNonlinearSolver,
+        newton::NewtonNonlinearSolver,
+    };
     use crate::{
-        linear_solver::nalgebra::lu::LU,
+        linear_solver::{nalgebra::lu::LU, BiCGStab, Gmres},
         matrix::MatrixCommon,
         op::{closure::Closure, ParameterisedOp},
         scale, DenseMatrix, Vector,
@@ -147,4 +167,46 @@ pub mod tests {
         let s = NewtonNonlinearSolver::new(lu);
         test_nonlinear_solver(s, op, rtol, &atol, soln);
     }
+
+    #[test]
+    fn test_anderson_cpu_square() {
+        let (op, rtol, atol, soln) = get_square_problem::<MCpu>();
+        let p = nalgebra::DVector::zeros(0);
+        let op = ParameterisedOp::new(&op, &p);
+        let s = AndersonNonlinearSolver::<MCpu>::new(2, 0.1);
+        test_nonlinear_solver(s, op, rtol, &atol, soln);
+    }
+
+    #[test]
+    fn test_line_search_newton_cpu_square() {
+        let p = nalgebra::DVector::zeros(0);
+        let (op, rtol, atol, mut soln) = get_square_problem::<MCpu>();
+        // poor initial guesses, a full Newton step from near the singular Jacobian at zero overshoots the solution
+        for x0 in [0.1, 50.0] {
+            soln.push(NonLinearSolveSolution::new(
+                nalgebra::DVector::from_element(2, x0),
+                nalgebra::DVector::from_element(2, 2.0),
+            ));
+        }
+        let op = ParameterisedOp::new(&op, &p);
+        let s = LineSearchNewtonNonlinearSolver::new(LU::default());
+        test_nonlinear_solver(s, op, rtol, &atol, soln);
+        let (op, rtol, atol, soln) = get_square_problem::<MCpu>();
+        let op = ParameterisedOp::new(&op, &p);
+        let s = LineSearchNewtonNonlinearSolver::new(Gmres::<MCpu>::default());
+        test_nonlinear_solver(s, op, rtol, &atol, soln);
+    }
+
+    #[test]
+    fn test_newton_krylov_cpu_square() {
+        let p = nalgebra::DVector::zeros(0);
+        let (op, rtol, atol, soln) = get_square_problem::<MCpu>();
+        let op = ParameterisedOp::new(&op, &p);
+        let s = NewtonNonlinearSolver::new(Gmres::<MCpu>::default());
+        test_nonlinear_solver(s, op, rtol, &atol, soln);
+        let (op, rtol, atol, soln) = get_square_problem::<MCpu>();
+        let op = ParameterisedOp::new(&op, &p);
+        let s = NewtonNonlinearSolver::new(BiCGStab::<MCpu>::default());
+        test_nonlinear_solver(s, op, rtol, &atol, soln);
+    }
 }
@@ -1,7 +1,11 @@
+use std::cell::Cell;
+
+use num_traits::Zero;
+
 use crate::{
     error::{DiffsolError, NonLinearSolverError},
     non_linear_solver_error, Convergence, ConvergenceStatus, LinearSolver, Matrix, NonLinearOp,
-    NonLinearOpJacobian, NonLinearSolver, Vector,
+    NonLinearOpJacobian, NonLinearSolver, Scalar, Vector,
 };
 
 pub fn newton_iteration<V: Vector>(
@@ -34,6 +38,34 @@ pub fn newton_iteration<V: Vector>(
     Err(non_linear_solver_error!(NewtonDidNotConverge))
 }
 
+// parameters of the Eisenstat-Walker forcing terms, used with matrix-free linear solvers
+const ETA_0: f64 = 0.1;
+const ETA_MAX: f64 = 0.1;
+const ETA_MIN: f64 = 1e-8;
+const ETA_GAMMA: f64 = 0.9;
+
+/// Return the forcing term for the next inexact Newton iteration, given the norm of the residual at the current and
+/// previous iterations (choice 2 of S. C. Eisenstat and H. F. Walker, Choosing the forcing terms in an inexact Newton method, SIAM J. Sci. Comput. 17 (1996) 16–32).
+pub(super) fn forcing_term<T: Scalar>(norm: T, prev_norm: T) -> T {
+    if prev_norm == T::zero() {
+        return T::from(ETA_0);
+    }
+    let ratio = norm / prev_norm;
+    let eta = T::from(ETA_GAMMA) * ratio * ratio;
+    if eta > T::from(ETA_MAX) {
+        T::from(ETA_MAX)
+    } else if eta < T::from(ETA_MIN) {
+        T::from(ETA_MIN)
+    } else {
+        eta
+    }
+}
+
+/// A Newton solver for the nonlinear problem `F(x) = 0`, that uses the linear solver `Ls` to solve the linearised problem at each iteration.
+///
+/// If the linear solver is matrix-free (e.g. [crate::Gmres] or [crate::BiCGStab]) then an inexact Newton method is used, where each linear problem
+/// `J dx = F(x)` is only solved to a relative tolerance `eta`, given by the Eisenstat-Walker forcing terms `eta_k = 0.9 (||F(x_k)|| / ||F(x_{k-1})||)^2`,
+/// bounded above by 0.1, so that the linear problems are solved more accurately as the Newton iteration converges.
 pub struct NewtonNonlinearSolver<M: Matrix, Ls: LinearSolver<M>> {
     linear_solver: Ls,
     is_jacobian_set: bool,
@@ -98,4 +130,31 @@ impl<M: Matrix, Ls: LinearSolver<M>> NonLinearSolver<M> for NewtonNonlinearSolve
         let fun = |x: &C::V, y: &mut C::V| op.call_inplace(x, t, y);
         newton_iteration(xn, &mut self.tmp, error_y, fun, linear_solver, convergence)
     }
+
+    fn solve_in_place_with_jacobian<C: NonLinearOpJacobian<V = M::V, T = M::T, M = M>>(
+        &mut self,
+        op: &C,
+        xn: &mut M::V,
+        t: M::T,
+        error_y: &M::V,
+        convergence: &mut Convergence<M::V>,
+    ) -> Result<(), DiffsolError> {
+        if !self.linear_solver.is_matrix_free() {
+            return self.solve_in_place(op, xn, t, error_y, convergence);
+        }
+        if !self.is_jacobian_set {
+            panic!("NewtonNonlinearSolver::solve_in_place() called before reset_jacobian");
+        }
+        if xn.len() != op.nstates() {
+            panic!("NewtonNonlinearSolver::solve() called with state of wrong size, expected {}, got {}", op.nstates(), xn.len());
+        }
+        let fun = |x: &C::V, y: &mut C::V| op.call_inplace(x, t, y);
+        let prev_norm = Cell::new(M::T::zero());
+        let linear_solver = |x: &mut C::V| {
+            let norm = x.norm();
+            let eta = forcing_term(norm, prev_norm.replace(norm));
+            self.linear_solver.solve_in_place_with_op(op, x, eta)
+        };
+        newton_iteration(xn, &mut self.tmp, error_y, fun, linear_solver, convergence)
+    }
 }
@@ -0,0 +1,927 @@
+use crate::error::DiffsolError;
+use crate::error::OdeSolverError;
+use crate::ode_solver_error;
+use crate::{
+    nonlinear_solver::root::RootFinder, scale, BdfState, Convergence, ConvergenceStatus,
+    DefaultDenseMatrix, DenseMatrix, IndexType, NonLinearOp, OdeEquations, OdeSolverMethod,
+    OdeSolverProblem, OdeSolverState, OdeSolverStopReason, Op, Scalar, StateRef, StateRefMut,
+    Vector, VectorRef, VectorView, VectorViewMut,
+};
+use num_traits::abs;
+use num_traits::One;
+use num_traits::Pow;
+use num_traits::Zero;
+use std::ops::MulAssign;
+
+use super::bdf::BdfStatistics;
+use super::method::{check_tstop, init_root_finder, TstopCheck};
+
+/// Computes the backward differences `[p(0), ∇p(0), ..., ∇^q p(0)]` (on a unit grid) of the polynomial
+///
+/// `p(s) = \int_{-shift}^s \prod_{j=shift}^{shift + q - 2} (u + j) du / (q - 1)!`
+///
+/// With `shift = 1` this is the polynomial used to correct the predicted solution of an Adams-Moulton
+/// method of order q (it is zero at s = -1 and its derivative is 1 at s = 0 and zero at s = -1, ..., -(q-1)).
+/// With `shift = 0` this is the polynomial used to remove the highest difference when the order is reduced,
+/// keeping the value at s = 0 and the derivatives at s = 0, ..., -(q-2) unchanged.
+fn integrated_polynomial_differences(q: usize, shift: usize) -> Vec<f64> {
+    // coefficients of the integrand, in increasing powers of u
+    let mut p = vec![1i128];
+    for j in shift..shift + q - 1 {
+        let mut next = vec![0; p.len() + 1];
+        for (k, c) in p.iter().enumerate() {
+            next[k] += c * j as i128;
+            next[k + 1] += c;
+        }
+        p = next;
+    }
+
+    // the integral has integer values once scaled by the lcm of 1, ..., q (the denominators of its coefficients),
+    // so evaluate it and take the differences exactly, otherwise the cancellation at high orders loses ~1e-10
+    let gcd = |mut a: i128, mut b: i128| {
+        while b != 0 {
+            (a, b) = (b, a % b);
+        }
+        a
+    };
+    let lcm = (1..=q as i128).fold(1, |l, k| l / gcd(l, k) * k);
+    let integral = |s: i128| {
+        p.iter()
+            .enumerate()
+            .map(|(k, c)| c * s.pow(k as u32 + 1) * (lcm / (k as i128 + 1)))
+            .sum::<i128>()
+    };
+    let s0 = integral(-(shift as i128));
+    let mut values = (0..=q)
+        .map(|i| integral(-(i as i128)) - s0)
+        .collect::<Vec<_>>();
+
+    // backward differences at s = 0
+    let scale = lcm as f64 * (1..q).map(|j| j as f64).product::<f64>();
+    let mut ret = Vec::with_capacity(q + 1);
+    for j in 0..=q {
+        ret.push(values[0] as f64 / scale);
+        for i in 0..q - j {
+            values[i] -= values[i + 1];
+        }
+    }
+    ret
+}
+
+/// A variable-order, variable-step Adams-Moulton multistep integrator (orders 1 to 12), suitable for non-stiff problems
+/// where each evaluation of the right-hand side is expensive.
+///
+/// The solution is stored using the same modified divided differences as the [crate::Bdf] solver (see [BdfState]), and
+/// the same methods are used to change the step size and interpolate the solution. Each step predicts the solution
+/// using the polynomial through the previous steps, and then corrects it with the Adams-Moulton formula using a
+/// fixed-point (functional) iteration, so no Jacobian is ever evaluated or factorised. The step size and order are
+/// chosen in a similar way to CVODE \[1\]: the order is only changed after running at the current order for `order + 1`
+/// steps, and is reduced if the error test fails repeatedly.
+///
+/// Problems with a mass matrix and forward sensitivities are not supported by this solver.
+///
+/// # References
+///
+/// \[1\] Hindmarsh, A. C., Brown, P. N., Grant, K. E., Lee, S. L., Serban, R., Shumaker, D. E., & Woodward, C. S. (2005). SUNDIALS: Suite of nonlinear and differential/algebraic equation solvers. ACM Transactions on Mathematical Software (TOMS), 31(3), 363-396.
+pub struct Adams<'a, Eqn, M = <<Eqn as Op>::V as DefaultDenseMatrix>::M>
+where
+    Eqn: OdeEquations,
+    Eqn::V: DefaultDenseMatrix,
+    M: DenseMatrix<T = Eqn::T, V = Eqn::V>,
+{
+    problem: &'a OdeSolverProblem<Eqn>,
+    convergence: Convergence<'a, Eqn::V>,
+    state: BdfState<Eqn::V, M>,
+    n_equal_steps: usize,
+    y_predict: Eqn::V,
+    hdy_predict: Eqn::V,
+    y_correction: Eqn::V,
+    y_delta: Eqn::V,
+    f: Eqn::V,
+    g_predict: Eqn::V,
+    hdg_predict: Eqn::V,
+    g_delta: Eqn::V,
+    t_predict: Eqn::T,
+    diff_tmp: M,
+    gdiff_tmp: M,
+    u: M,
+    gamma: Vec<Eqn::T>,
+    l: Vec<Vec<Eqn::T>>,
+    m: Vec<Vec<Eqn::T>>,
+    error_const2: Vec<Eqn::T>,
+    statistics: BdfStatistics,
+    root_finder: Option<RootFinder<Eqn::V>>,
+    tstop: Option<Eqn::T>,
+    is_state_modified: bool,
+}
+
+impl<M, Eqn> Clone for Adams<'_, Eqn, M>
+where
+    Eqn: OdeEquations,
+    Eqn::V: DefaultDenseMatrix,
+    M: DenseMatrix<T = Eqn::T, V = Eqn::V>,
+{
+    fn clone(&self) -> Self {
+        Self {
+            problem: self.problem,
+            convergence: self.convergence.clone(),
+            state: self.state.clone(),
+            n_equal_steps: self.n_equal_steps,
+            y_predict: self.y_predict.clone(),
+            hdy_predict: self.hdy_predict.clone(),
+            y_correction: self.y_correction.clone(),
+            y_delta: self.y_delta.clone(),
+            f: self.f.clone(),
+            g_predict: self.g_predict.clone(),
+            hdg_predict: self.hdg_predict.clone(),
+            g_delta: self.g_delta.clone(),
+            t_predict: self.t_predict,
+            diff_tmp: self.diff_tmp.clone(),
+            gdiff_tmp: self.gdiff_tmp.clone(),
+            u: self.u.clone(),
+            gamma: self.gamma.clone(),
+            l: self.l.clone(),
+            m: self.m.clone(),
+            error_const2: self.error_const2.clone(),
+            statistics: self.statistics.clone(),
+            root_finder: self.root_finder.clone(),
+            tstop: self.tstop,
+            is_state_modified: self.is_state_modified,
+        }
+    }
+}
+
+impl<'a, M, Eqn> Adams<'a, Eqn, M>
+where
+    Eqn: OdeEquations,
+    Eqn::V: DefaultDenseMatrix,
+    M: DenseMatrix<T = Eqn::T, V = Eqn::V>,
+    for<'b> &'b Eqn::V: VectorRef<Eqn::V>,
+{
+    pub(crate) const MAX_ORDER: IndexType = 12;
+    const FIXED_POINT_MAXITER: IndexType = 4;
+    const FIXED_POINT_FAIL_FACTOR: f64 = 0.25;
+    const SAFETY: f64 = 0.9;
+    const MIN_FACTOR: f64 = 0.5;
+    const MAX_FACTOR: f64 = 2.1;
+    const MAX_THRESHOLD: f64 = 2.0;
+    const MIN_THRESHOLD: f64 = 0.9;
+    const MIN_TIMESTEP: f64 = 1e-32;
+
+    pub fn new(
+        problem: &'a OdeSolverProblem<Eqn>,
+        mut state: BdfState<Eqn::V, M>,
+    ) -> Result<Self, DiffsolError> {
+        if problem.eqn.mass().is_some() {
+            return Err(ode_solver_error!(
+                Other,
+                "Adams solvers do not support a mass matrix"
+            ));
+        }
+
+        // gamma_j = \sum_{i=1}^j 1 / i, used to calculate h * dy/dt of the predicted polynomial
+        let mut gamma = vec![Eqn::T::zero()];
+        for i in 1..=Self::MAX_ORDER {
+            gamma.push(gamma[i - 1] + Eqn::T::one() / Eqn::T::from(i as f64));
+        }
+
+        // the error constants of the Adams-Moulton methods are the coefficients of the
+        // generating function -t / ln(1 - t), which satisfy the recurrence
+        // c_0 = 1, c_j = -\sum_{i=1}^j c_{j-i} / (i + 1)
+        let mut error_const = vec![1.0];
+        for j in 1..=Self::MAX_ORDER {
+            let c_j = -(1..=j)
+                .map(|i| error_const[j - i] / (i as f64 + 1.0))
+                .sum::<f64>();
+            error_const.push(c_j);
+        }
+        let error_const2 = error_const
+            .into_iter()
+            .map(|c| Eqn::T::from(c * c))
+            .collect();
+
+        // coefficients to correct the differences after each step (l) and to reduce the order (m)
+        let mut l = vec![vec![]];
+        let mut m = vec![vec![]];
+        for q in 1..=Self::MAX_ORDER {
+            l.push(
+                integrated_polynomial_differences(q, 1)
+                    .into_iter()
+                    .map(Eqn::T::from)
+                    .collect(),
+            );
+            m.push(
+                integrated_polynomial_differences(q, 0)
+                    .into_iter()
+                    .map(Eqn::T::from)
+                    .collect(),
+            );
+        }
+
+        state.check_consistent_with_problem(problem)?;
+
+        let mut convergence = Convergence::new(problem.rtol, &problem.atol);
+        convergence.set_max_iter(Self::FIXED_POINT_MAXITER);
+
+        state.set_problem(problem)?;
+        state.ensure_diff_columns(Self::MAX_ORDER + 3);
+
+        // setup root solver
+        let mut root_finder = None;
+        if let Some(root_fn) = problem.eqn.root() {
+            root_finder = Some(RootFinder::new(root_fn.nout()));
+            root_finder
+                .as_ref()
+                .unwrap()
+                .init(&root_fn, &state.y, state.t);
+        }
+
+        // allocate internal state
+        let nstates = problem.eqn.rhs().nstates();
+        let nout = if problem.integrate_out {
+            problem.eqn.out().unwrap().nout()
+        } else {
+            0
+        };
+        let diff_tmp = M::zeros(nstates, state.diff.ncols());
+        let gdiff_tmp = M::zeros(nout, state.gdiff.ncols());
+        let u = BdfState::<Eqn::V, M>::compute_r(state.order, Eqn::T::one());
+
+        Ok(Self {
+            problem,
+            convergence,
+            state,
+            n_equal_steps: 0,
+            y_predict: <Eqn::V as Vector>::zeros(nstates),
+            hdy_predict: <Eqn::V as Vector>::zeros(nstates),
+            y_correction: <Eqn::V as Vector>::zeros(nstates),
+            y_delta: <Eqn::V as Vector>::zeros(nstates),
+            f: <Eqn::V as Vector>::zeros(nstates),
+            g_predict: <Eqn::V as Vector>::zeros(nout),
+            hdg_predict: <Eqn::V as Vector>::zeros(nout),
+            g_delta: <Eqn::V as Vector>::zeros(nout),
+            t_predict: Eqn::T::zero(),
+            diff_tmp,
+            gdiff_tmp,
+            u,
+            gamma,
+            l,
+            m,
+            error_const2,
+            statistics: BdfStatistics::default(),
+            root_finder,
+            tstop: None,
+            is_state_modified: false,
+        })
+    }
+
+    pub fn get_statistics(&self) -> &BdfStatistics {
+        &self.statistics
+    }
+
+    fn initialise_to_first_order(&mut self) {
+        self.n_equal_steps = 0;
+        self.state.initialise_diff_to_first_order();
+        if self.problem.integrate_out {
+            self.state.initialise_gdiff_to_first_order();
+        }
+        self.u = BdfState::<Eqn::V, M>::compute_r(1, Eqn::T::one());
+        self.is_state_modified = false;
+    }
+
+    fn _update_step_size(&mut self, factor: Eqn::T) -> Result<Eqn::T, DiffsolError> {
+        let new_h = factor * self.state.h;
+        self.n_equal_steps = 0;
+
+        // update D using equations in section 3.2 of [1]
+        let order = self.state.order;
+        let r = BdfState::<Eqn::V, M>::compute_r(order, factor);
+        let ru = r.mat_mul(&self.u);
+        BdfState::<Eqn::V, M>::update_diff_for_step_size(
+            &ru,
+            &mut self.state.diff,
+            &mut self.diff_tmp,
+            order,
+        );
+        if self.problem.integrate_out {
+            BdfState::<Eqn::V, M>::update_diff_for_step_size(
+                &ru,
+                &mut self.state.gdiff,
+                &mut self.gdiff_tmp,
+                order,
+            );
+        }
+
+        self.state.h = new_h;
+
+        // if step size too small, then fail
+        if abs(self.state.h) < Eqn::T::from(Self::MIN_TIMESTEP) {
+            return Err(DiffsolError::from(OdeSolverError::StepSizeTooSmall {
+                time: self.state.t.into(),
+            }));
+        }
+        Ok(new_h)
+    }
+
+    /// predict the solution `y^0_{n+1}` and its scaled derivative `h * dy/dt` at the next step
+    fn _predict(
+        y_predict: &mut Eqn::V,
+        hdy_predict: &mut Eqn::V,
+        diff: &M,
+        gamma: &[Eqn::T],
+        order: usize,
+    ) {
+        BdfState::<Eqn::V, M>::predict_using_diff(y_predict, diff, order);
+        hdy_predict.fill(Eqn::T::zero());
+        for (j, gamma_j) in gamma.iter().enumerate().take(order + 1).skip(1) {
+            hdy_predict.axpy_v(*gamma_j, &diff.column(j), Eqn::T::one());
+        }
+    }
+
+    fn _predict_forward(&mut self) {
+        let state = &self.state;
+        Self::_predict(
+            &mut self.y_predict,
+            &mut self.hdy_predict,
+            &state.diff,
+            &self.gamma,
+            state.order,
+        );
+        if self.problem.integrate_out {
+            Self::_predict(
+                &mut self.g_predict,
+                &mut self.hdg_predict,
+                &state.gdiff,
+                &self.gamma,
+                state.order,
+            );
+        }
+        self.t_predict = state.t + state.h;
+    }
+
+    /// solve the Adams-Moulton equation `y_{n+1} = y^0_{n+1} + l_0 (h f(t_{n+1}, y_{n+1}) - h dy^0/dt)`
+    /// using fixed-point iteration, returning true if the iteration converged. On return `y_delta` contains
+    /// `h f(t_{n+1}, y_{n+1}) - h dy^0/dt`, `y_correction` contains `y_{n+1}` and `f` contains the rhs
+    /// evaluated at the last iterate
+    fn _solve_corrector(&mut self) -> bool {
+        let order = self.state.order;
+        let h = self.state.h;
+        let l0 = self.l[order][0];
+        let rhs = self.problem.eqn.rhs();
+        self.convergence.reset();
+        self.y_correction.copy_from(&self.y_predict);
+        let ret = loop {
+            rhs.call_inplace(&self.y_correction, self.t_predict, &mut self.f);
+
+            // y_delta = h * f - h dy^0/dt
+            self.y_delta.copy_from(&self.f);
+            self.y_delta.axpy(-Eqn::T::one(), &self.hdy_predict, h);
+
+            // the fixed-point update is y^0 + l_0 * y_delta - y
+            let mut dy = &self.y_predict - &self.y_correction;
+            dy.axpy(l0, &self.y_delta, Eqn::T::one());
+            self.y_correction += &dy;
+
+            match self
+                .convergence
+                .check_new_iteration(&mut dy, &self.y_correction)
+            {
+                ConvergenceStatus::Converged => break true,
+                ConvergenceStatus::Continue => continue,
+                ConvergenceStatus::Diverged | ConvergenceStatus::MaximumIterations => break false,
+            }
+        };
+        self.statistics.number_of_nonlinear_solver_iterations += self.convergence.niter();
+        ret
+    }
+
+    fn calculate_output_delta(&mut self) {
+        let out = self.problem.eqn.out().unwrap();
+        out.call_inplace(&self.y_correction, self.t_predict, &mut self.state.dg);
+        self.g_delta.copy_from(&self.state.dg);
+        self.g_delta
+            .axpy(-Eqn::T::one(), &self.hdg_predict, self.state.h);
+    }
+
+    fn _update_diff(order: usize, l: &[Eqn::T], d: &Eqn::V, diff: &mut M) {
+        // the new highest differences are calculated in the same way as the bdf solver,
+        // D^{q+1} y_{n+1} = d, D^{q+2} y_{n+1} = d - D^{q+1} y_n
+        let d_minus_order_plus_one = d - diff.column(order + 1);
+        diff.column_mut(order + 2)
+            .copy_from(&d_minus_order_plus_one);
+        diff.column_mut(order + 1).copy_from(d);
+
+        // shift the predicted polynomial forward to the new step, D^j y^0_{n+1} = \sum_{i=j}^q D^i y_n
+        for i in (0..order).rev() {
+            diff.column_axpy(Eqn::T::one(), i + 1, Eqn::T::one(), i);
+        }
+
+        // and correct it using the Adams-Moulton correction polynomial
+        for (j, l_j) in l.iter().enumerate() {
+            diff.column_mut(j).axpy(*l_j, d, Eqn::T::one());
+        }
+    }
+
+    fn _reduce_diff_order(m: &[Eqn::T], diff: &mut M, order: usize) {
+        // remove the highest difference, keeping y_n and the derivatives of the polynomial
+        // at the previous order - 1 steps unchanged
+        for (j, m_j) in m.iter().enumerate().take(order) {
+            diff.column_axpy(-*m_j, order, Eqn::T::one(), j);
+        }
+        diff.column_mut(order).mul_assign(scale(Eqn::T::zero()));
+    }
+
+    fn reduce_order(&mut self) {
+        let order = self.state.order;
+        Self::_reduce_diff_order(&self.m[order], &mut self.state.diff, order);
+        if self.problem.integrate_out {
+            Self::_reduce_diff_order(&self.m[order], &mut self.state.gdiff, order);
+        }
+        self.state.order = order - 1;
+        self.u = BdfState::<Eqn::V, M>::compute_r(order - 1, Eqn::T::one());
+    }
+
+    fn increase_order(&mut self) {
+        // the polynomial is unchanged, so the new highest difference is zero
+        let order = self.state.order;
+        self.state
+            .diff
+            .column_mut(order + 1)
+            .mul_assign(scale(Eqn::T::zero()));
+        if self.problem.integrate_out {
+            self.state
+                .gdiff
+                .column_mut(order + 1)
+                .mul_assign(scale(Eqn::T::zero()));
+        }
+        self.state.order = order + 1;
+        self.u = BdfState::<Eqn::V, M>::compute_r(order + 1, Eqn::T::one());
+    }
+
+    fn error_control(&self) -> Eqn::T {
+        let state = &self.state;
+        let order = state.order;
+        let atol = &self.problem.atol;
+        let rtol = self.problem.rtol;
+        let mut error_norm =
+            self.y_delta.squared_norm(&state.y, atol, rtol) * self.error_const2[order];
+        if self.problem.output_in_error_control() {
+            let rtol = self.problem.out_rtol.unwrap();
+            let atol = self.problem.out_atol.as_ref().unwrap();
+            error_norm +=
+                self.g_delta.squared_norm(&state.g, atol, rtol) * self.error_const2[order];
+            error_norm /= Eqn::T::from(2.0);
+        }
+        error_norm
+    }
+
+    fn predict_error_control(&self, order: usize) -> Eqn::T {
+        let state = &self.state;
+        let atol = &self.problem.atol;
+        let rtol = self.problem.rtol;
+        let mut error_norm = state
+            .diff
+            .column(order + 1)
+            .squared_norm(&state.y, atol, rtol)
+            * self.error_const2[order];
+        if self.problem.output_in_error_control() {
+            let rtol = self.problem.out_rtol.unwrap();
+            let atol = self.problem.out_atol.as_ref().unwrap();
+            error_norm += state
+                .gdiff
+                .column(order + 1)
+                .squared_norm(&state.g, atol, rtol)
+                * self.error_const2[order];
+            error_norm /= Eqn::T::from(2.0);
+        }
+        error_norm
+    }
+
+    fn handle_tstop(
+        &mut self,
+        tstop: Eqn::T,
+    ) -> Result<Option<OdeSolverStopReason<Eqn::T>>, DiffsolError> {
+        let check = check_tstop(self.state.t, self.state.h, tstop);
+        if check.is_err() {
+            self.tstop = None;
+        }
+        match check? {
+            TstopCheck::Reached => {
+                self.tstop = None;
+                return Ok(Some(OdeSolverStopReason::TstopReached));
+            }
+            TstopCheck::ScaleStep(factor) => {
+                // update step size ignoring the possible "step size too small" error
+                _ = self._update_step_size(factor);
+            }
+            TstopCheck::NotReached => {}
+        }
+        Ok(None)
+    }
+}
+
+impl<'a, M, Eqn> OdeSolverMethod<'a, Eqn> for Adams<'a, Eqn, M>
+where
+    Eqn: OdeEquations,
+    Eqn::V: DefaultDenseMatrix,
+    M: DenseMatrix<T = Eqn::T, V = Eqn::V>,
+    for<'b> &'b Eqn::V: VectorRef<Eqn::V>,
+{
+    type State = BdfState<Eqn::V, M>;
+
+    fn problem(&self) -> &'a OdeSolverProblem<Eqn> {
+        self.problem
+    }
+
+    fn order(&self) -> usize {
+        self.state.order
+    }
+
+    fn set_state(&mut self, mut state: Self::State) {
+        state.ensure_diff_columns(Self::MAX_ORDER + 3);
+        self.state = state;
+        self.u = BdfState::<Eqn::V, M>::compute_r(self.state.order, Eqn::T::one());
+        self.n_equal_steps = 0;
+
+        // unset the stop time if it is not ahead of the new state
+        if let Some(tstop) = self.tstop {
+            let state = &self.state;
+            if (state.h > Eqn::T::zero() && tstop <= state.t)
+                || (state.h < Eqn::T::zero() && tstop >= state.t)
+            {
+                self.tstop = None;
+            }
+        }
+
+        // reinitialise root finder if needed
+        init_root_finder(
+            self.problem,
+            self.root_finder.as_ref(),
+            &self.state.y,
+            self.state.t,
+        );
+    }
+
+    fn into_state(self) -> BdfState<Eqn::V, M> {
+        self.state
+    }
+
+    fn checkpoint(&mut self) -> Self::State {
+        self.state.clone()
+    }
+
+    fn step(&mut self) -> Result<OdeSolverStopReason<Eqn::T>, DiffsolError> {
+        let problem = self.problem;
+        let integrate_out = problem.integrate_out;
+        let mut n_error_test_failures = 0;
+        let mut error_norm: Eqn::T;
+
+        if self.is_state_modified {
+            // reinitialise root finder if needed
+            init_root_finder(
+                problem,
+                self.root_finder.as_ref(),
+                &self.state.y,
+                self.state.t,
+            );
+            // reinitialise diff matrix
+            self.initialise_to_first_order();
+
+            // reinitialise tstop if needed
+            if let Some(t_stop) = self.tstop {
+                self.set_stop_time(t_stop)?;
+            }
+        }
+
+        // loop until step is accepted
+        loop {
+            self._predict_forward();
+            let order = self.state.order;
+
+            if !self._solve_corrector() {
+                // fixed-point iteration did not converge, reduce the step size and try again
+                self.statistics.number_of_nonlinear_solver_fails += 1;
+                self._update_step_size(Eqn::T::from(Self::FIXED_POINT_FAIL_FACTOR))?;
+                continue;
+            }
+
+            if integrate_out {
+                self.calculate_output_delta();
+            }
+
+            // the local error is C_q * h^{q+1} y^{q+1}, and y_delta \approx h^{q+1} y^{q+1}
+            error_norm = self.error_control();
+
+            // do the error test
+            if error_norm <= Eqn::T::one() {
+                // step is accepted
+                break;
+            }
+
+            // step is rejected, if this has happened repeatedly then the higher differences are
+            // probably not reliable, so reduce the order
+            self.statistics.number_of_error_test_failures += 1;
+            n_error_test_failures += 1;
+            if n_error_test_failures >= 2 && order > 1 {
+                self.reduce_order();
+            }
+
+            // calculate optimal step size factor and reduce step size and try again
+            let mut factor = Eqn::T::from(Self::SAFETY)
+                * error_norm.pow(Eqn::T::from(-0.5 / (order as f64 + 1.0)));
+            if factor < Eqn::T::from(Self::MIN_FACTOR) {
+                factor = Eqn::T::from(Self::MIN_FACTOR);
+            }
+            self._update_step_size(factor)?;
+        }
+
+        // take the accepted step
+        {
+            let order = self.state.order;
+            let state = &mut self.state;
+            Self::_update_diff(order, &self.l[order], &self.y_delta, &mut state.diff);
+            std::mem::swap(&mut state.y, &mut self.y_correction);
+            std::mem::swap(&mut state.dy, &mut self.f);
+            state.t = self.t_predict;
+
+            if integrate_out {
+                Self::_update_diff(order, &self.l[order], &self.g_delta, &mut state.gdiff);
+                state.g.copy_from(&self.g_predict);
+                state.g.axpy(self.l[order][0], &self.g_delta, Eqn::T::one());
+            }
+        }
+        self.statistics.number_of_steps += 1;
+
+        // a change in order is only done after running at order k for k + 1 steps
+        self.n_equal_steps += 1;
+
+        if self.n_equal_steps > self.state.order {
+            let order = self.state.order;
+            let error_m_norm = if order > 1 {
+                self.predict_error_control(order - 1)
+            } else {
+                Eqn::T::INFINITY
+            };
+            let error_p_norm = if order < Self::MAX_ORDER {
+                self.predict_error_control(order + 1)
+            } else {
+                Eqn::T::INFINITY
+            };
+            let factors = [error_m_norm, error_norm, error_p_norm]
+                .into_iter()
+                .enumerate()
+                .map(|(i, error_norm)| {
+                    error_norm.pow(Eqn::T::from(-0.5 / (i as f64 + order as f64)))
+                })
+                .collect::<Vec<_>>();
+
+            // pick the order that maximises the resultant step size
+            let max_index = factors
+                .iter()
+                .enumerate()
+                .max_by(|(_, a), (_, b)| a.partial_cmp(b).unwrap())
+                .unwrap()
+                .0;
+            match max_index {
+                0 => self.reduce_order(),
+                2 => self.increase_order(),
+                _ => (),
+            }
+
+            let mut factor = Eqn::T::from(Self::SAFETY) * factors[max_index];
+            if factor > Eqn::T::from(Self::MAX_FACTOR) {
+                factor = Eqn::T::from(Self::MAX_FACTOR);
+            }
+            if factor < Eqn::T::from(Self::MIN_FACTOR) {
+                factor = Eqn::T::from(Self::MIN_FACTOR);
+            }
+            if factor >= Eqn::T::from(Self::MAX_THRESHOLD)
+                || factor < Eqn::T::from(Self::MIN_THRESHOLD)
+                || max_index == 0
+                || max_index == 2
+            {
+                self._update_step_size(factor)?;
+            }
+        }
+
+        // check for root within accepted step
+        if let Some(root_fn) = problem.eqn.root() {
+            let ret = self.root_finder.as_ref().unwrap().check_root(
+                &|t: <Eqn as Op>::T| self.interpolate(t),
+                &root_fn,
+                &self.state.y,
+                self.state.t,
+            );
+            if let Some(root) = ret {
+                return Ok(OdeSolverStopReason::RootFound(root));
+            }
+        }
+
+        if let Some(tstop) = self.tstop {
+            if let Some(reason) = self.handle_tstop(tstop).unwrap() {
+                return Ok(reason);
+            }
+        }
+
+        // just a normal step, no roots or tstop reached
+        Ok(OdeSolverStopReason::InternalTimestep)
+    }
+
+    fn set_stop_time(&mut self, tstop: <Eqn as Op>::T) -> Result<(), DiffsolError> {
+        self.tstop = Some(tstop);
+        if let Some(OdeSolverStopReason::TstopReached) = self.handle_tstop(tstop)? {
+            let error = OdeSolverError::StopTimeBeforeCurrentTime {
+                stop_time: tstop.into(),
+                state_time: self.state.t.into(),
+            };
+            self.tstop = None;
+            return Err(DiffsolError::from(error));
+        }
+        Ok(())
+    }
+
+    fn interpolate(&self, t: Eqn::T) -> Result<Eqn::V, DiffsolError> {
+        let state = &self.state;
+        if self.is_state_modified {
+            if t == state.t {
+                return Ok(state.y.clone());
+            } else {
+                return Err(ode_solver_error!(InterpolationTimeOutsideCurrentStep));
+            }
+        }
+        // check that t is before/after the current time depending on the direction
+        let is_forward = state.h > Eqn::T::zero();
+        if (is_forward && t > state.t) || (!is_forward && t < state.t) {
+            return Err(ode_solver_error!(InterpolationTimeAfterCurrentTime));
+        }
+        Ok(BdfState::<Eqn::V, M>::interpolate_from_diff(
+            t,
+            &state.diff,
+            state.t,
+            state.h,
+            state.order,
+        ))
+    }
+
+    fn interpolate_out(&self, t: Eqn::T) -> Result<Eqn::V, DiffsolError> {
+        let state = &self.state;
+        if self.is_state_modified {
+            if t == state.t {
+                return Ok(state.g.clone());
+            } else {
+                return Err(ode_solver_error!(InterpolationTimeOutsideCurrentStep));
+            }
+        }
+        // check that t is before/after the current time depending on the direction
+        let is_forward = state.h > Eqn::T::zero();
+        if (is_forward && t > state.t) || (!is_forward && t < state.t) {
+            return Err(ode_solver_error!(InterpolationTimeAfterCurrentTime));
+        }
+        Ok(BdfState::<Eqn::V, M>::interpolate_from_diff(
+            t,
+            &state.gdiff,
+            state.t,
+            state.h,
+            state.order,
+        ))
+    }
+
+    fn interpolate_sens(&self, _t: <Eqn as Op>::T) -> Result<Vec<Eqn::V>, DiffsolError> {
+        Ok(vec![])
+    }
+
+    fn state(&self) -> StateRef<Eqn::V> {
+        self.state.as_ref()
+    }
+
+    fn state_mut(&mut self) -> StateRefMut<Eqn::V> {
+        self.is_state_modified = true;
+        self.state.as_mut()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        ode_solver::{
+            test_models::{
+                exponential_decay::{
+                    exponential_decay_problem, exponential_decay_problem_no_jacobian,
+                    exponential_decay_problem_with_root, negative_exponential_decay_problem,
+                },
+                gaussian_decay::gaussian_decay_problem,
+            },
+            tests::{
+                test_checkpointing, test_interpolate, test_ode_solver, test_problem,
+                test_state_mut, test_state_mut_on_problem,
+            },
+        },
+        OdeEquations, OdeSolverMethod, Op,
+    };
+
+    use super::integrated_polynomial_differences;
+    use num_traits::abs;
+
+    type M = nalgebra::DMatrix<f64>;
+
+    #[test]
+    fn adams_correction_coefficients() {
+        // order 1 is backward euler, order 2 is the trapezoidal rule
+        assert_eq!(integrated_polynomial_differences(1, 1), vec![1.0, 1.0]);
+        assert_eq!(integrated_polynomial_differences(2, 1), vec![0.5, 0.5, 1.0]);
+        for q in 1..=12 {
+            let l = integrated_polynomial_differences(q, 1);
+            assert!(abs(l[q] - 1.0) < 1e-10, "l[{}] = {}", q, l[q]);
+            let m = integrated_polynomial_differences(q, 0);
+            assert!(abs(m[0]) < 1e-10, "m[0] = {}", m[0]);
+            assert!(abs(m[q] - 1.0) < 1e-10, "m[{}] = {}", q, m[q]);
+        }
+    }
+
+    #[test]
+    fn adams_state_mut() {
+        test_state_mut(test_problem::<M>().adams().unwrap());
+    }
+
+    #[test]
+    fn adams_test_interpolate() {
+        test_interpolate(test_problem::<M>().adams().unwrap());
+    }
+
+    #[test]
+    fn adams_test_checkpointing() {
+        let (problem, soln) = exponential_decay_problem::<M>(false);
+        let s1 = problem.adams().unwrap();
+        let s2 = problem.adams().unwrap();
+        test_checkpointing(soln, s1, s2);
+    }
+
+    #[test]
+    fn adams_test_state_mut_exponential_decay() {
+        let (p, soln) = exponential_decay_problem::<M>(false);
+        let s = p.adams().unwrap();
+        test_state_mut_on_problem(s, soln);
+    }
+
+    #[test]
+    fn adams_test_nalgebra_negative_exponential_decay() {
+        let (problem, soln) = negative_exponential_decay_problem::<M>(false);
+        let mut s = problem.adams().unwrap();
+        test_ode_solver(&mut s, soln, None, false, false);
+    }
+
+    #[test]
+    fn test_adams_nalgebra_exponential_decay() {
+        let (problem, soln) = exponential_decay_problem::<M>(false);
+        let mut s = problem.adams().unwrap();
+        test_ode_solver(&mut s, soln, None, false, false);
+        assert_eq!(s.get_statistics().number_of_linear_solver_setups, 0);
+        assert!(
+            s.get_statistics().number_of_nonlinear_solver_iterations
+                >= s.get_statistics().number_of_steps
+        );
+        assert_eq!(problem.eqn.rhs().statistics().number_of_jac_muls, 0);
+        assert_eq!(problem.eqn.rhs().statistics().number_of_matrix_evals, 0);
+    }
+
+    #[test]
+    fn test_adams_nalgebra_exponential_decay_no_jacobian() {
+        let (problem, soln) = exponential_decay_problem_no_jacobian::<M>();
+        let mut s = problem.adams().unwrap();
+        test_ode_solver(&mut s, soln, None, false, false);
+    }
+
+    #[test]
+    fn test_adams_nalgebra_gaussian_decay() {
+        let (problem, soln) = gaussian_decay_problem::<M>(false, 10);
+        let mut s = problem.adams().unwrap();
+        test_ode_solver(&mut s, soln, None, false, false);
+    }
+
+    #[test]
+    fn test_adams_order_increases_above_bdf_max_order() {
+        let (problem, _soln) = exponential_decay_problem::<M>(false);
+        let mut s = problem.adams().unwrap();
+        let mut max_order = s.order();
+        while s.state().t < 10.0 {
+            s.step().unwrap();
+            max_order = max_order.max(s.order());
+        }
+        assert!(max_order > 5, "max order = {}", max_order);
+    }
+
+    #[test]
+    fn test_tstop_adams() {
+        let (problem, soln) = exponential_decay_problem::<M>(false);
+        let mut s = problem.adams().unwrap();
+        test_ode_solver(&mut s, soln, None, true, false);
+    }
+
+    #[test]
+    fn test_root_finder_adams() {
+        let (problem, soln) = exponential_decay_problem_with_root::<M>(false);
+        let mut s = problem.adams().unwrap();
+        let y = test_ode_solver(&mut s, soln, None, false, false);
+        assert!(abs(y[0] - 0.6) < 1e-6, "y[0] = {}", y[0]);
+    }
+}
@@ -665,6 +665,7 @@ mod tests {
             s: Vec::new(),
             ds: Vec::new(),
             h: 0.0,
+            last_step: None,
         };
         let solver = problem.esdirk34_solver::<LS>(state.clone()).unwrap();
         let checkpointer = Checkpointing::new(solver, 0, vec![state.clone(), state.clone()], None);
@@ -735,6 +736,7 @@ mod tests {
             s: Vec::new(),
             ds: Vec::new(),
             h: 0.0,
+            last_step: None,
         };
         let solver = problem
             .esdirk34_solver::<FaerSparseLU<f64>>(state.clone())
@@ -1,5 +1,4 @@
 use nalgebra::ComplexField;
-use std::ops::AddAssign;
 
 use crate::{
     error::{DiffsolError, OdeSolverError},
@@ -13,13 +12,18 @@ use serde::Serialize;
 use crate::ode_solver_error;
 use crate::{
     matrix::MatrixRef, nonlinear_solver::root::RootFinder, op::bdf::BdfCallable, scalar::scale,
-    AugmentedOdeEquations, BdfState, DenseMatrix, IndexType, JacobianUpdate, MatrixViewMut,
-    NonLinearOp, NonLinearSolver, OdeEquationsImplicit, OdeSolverMethod, OdeSolverProblem,
-    OdeSolverState, OdeSolverStopReason, Op, Scalar, Vector, VectorRef, VectorView, VectorViewMut,
+    AugmentedOdeEquations, BdfState, DenseMatrix, FixedStep, IController, IndexType,
+    JacobianUpdate, ManifoldProjection, NonLinearOp, NonLinearSolver, OdeEquationsImplicit,
+    OdeSolverMethod, OdeSolverProblem, OdeSolverState, OdeSolverStopReason, Op, Scalar,
+    StepController, Vector, VectorRef, VectorView, VectorViewMut,
 };
 
-use super::method::{AdjointOdeSolverMethod, AugmentedOdeSolverMethod};
+use super::constraints::{check_constraints, constraint_step_factor};
+use super::method::{
+    check_tstop, init_root_finder, AdjointOdeSolverMethod, AugmentedOdeSolverMethod, TstopCheck,
+};
 use super::{jacobian_update::SolverState, method::SensitivitiesOdeSolverMethod};
+use crate::nonlinear_solver::error_norm::{squared_norm, view_squared_norm};
 
 #[derive(Clone, Debug, Serialize, Default)]
 pub struct BdfStatistics {
@@ -28,6 +32,8 @@ pub struct BdfStatistics {
     pub number_of_error_test_failures: usize,
     pub number_of_nonlinear_solver_iterations: usize,
     pub number_of_nonlinear_solver_fails: usize,
+    /// Number of steps rejected because the solution violated a [crate::Constraint]
+    pub number_of_constraint_failures: usize,
 }
 
 impl<'a, M, Eqn, Nls, AugEqn> AugmentedOdeSolverMethod<'a, Eqn, AugEqn>
@@ -141,6 +147,10 @@ pub struct Bdf<
     root_finder: Option<RootFinder<Eqn::V>>,
     is_state_modified: bool,
     jacobian_update: JacobianUpdate<Eqn::T>,
+    fixed_step: Option<FixedStep<Eqn::T>>,
+    step_controller: Box<dyn StepController<Eqn::T>>,
+    steps_since_stop_time: usize,
+    projection: Option<ManifoldProjection<'a, Eqn::M>>,
 }
 
 impl<M, Eqn, Nls, AugmentedEqn> Clone for Bdf<'_, Eqn, Nls, M, AugmentedEqn>
@@ -193,6 +203,10 @@ where
             root_finder: self.root_finder.clone(),
             is_state_modified: self.is_state_modified,
             jacobian_update: self.jacobian_update.clone(),
+            fixed_step: self.fixed_step.clone(),
+            projection: self.projection.clone(),
+            step_controller: self.step_controller.clone(),
+            steps_since_stop_time: self.steps_since_stop_time,
         }
     }
 }
@@ -254,9 +268,18 @@ where
         }
 
         state.check_consistent_with_problem(problem)?;
+        if let Some(constraints) = problem.options.constraints.as_ref() {
+            check_constraints(constraints, state.y.as_slice())?;
+        }
 
         let mut convergence = Convergence::new(problem.rtol, &problem.atol);
-        convergence.set_max_iter(Self::NEWTON_MAXITER);
+        convergence.set_error_norm(problem.options.error_norm.clone());
+        convergence.set_max_iter(
+            problem
+                .options
+                .max_newton_iter
+                .unwrap_or(Self::NEWTON_MAXITER),
+        );
 
         let op = if integrate_main_eqn {
             // setup linear solver for first step
@@ -283,7 +306,7 @@ where
 
         // (re)allocate internal state
         let nstates = problem.eqn.rhs().nstates();
-        let diff_tmp = M::zeros(nstates, BdfState::<Eqn::V, M>::MAX_ORDER + 3);
+        let diff_tmp = M::zeros(nstates, state.diff.ncols());
         let y_delta = <Eqn::V as Vector>::zeros(nstates);
         let y_predict = <Eqn::V as Vector>::zeros(nstates);
 
@@ -293,10 +316,10 @@ where
             0
         };
         let g_delta = <Eqn::V as Vector>::zeros(nout);
-        let gdiff_tmp = M::zeros(nout, BdfState::<Eqn::V, M>::MAX_ORDER + 3);
+        let gdiff_tmp = M::zeros(nout, state.gdiff.ncols());
 
         // init U matrix
-        let u = Self::_compute_r(state.order, Eqn::T::one());
+        let u = BdfState::<Eqn::V, M>::compute_r(state.order, Eqn::T::one());
         let is_state_modified = false;
 
         Ok(Self {
@@ -325,7 +348,11 @@ where
             tstop: None,
             root_finder,
             is_state_modified,
-            jacobian_update: JacobianUpdate::default(),
+            jacobian_update: JacobianUpdate::from_options(&problem.options),
+            fixed_step: None,
+            projection: problem.projection.clone(),
+            step_controller: Box::new(IController),
+            steps_since_stop_time: 0,
         })
     }
 
@@ -364,7 +391,7 @@ where
         ret.s_predict = <Eqn::V as Vector>::zeros(nstates);
         if let Some(out) = ret.s_op.as_ref().unwrap().eqn().out() {
             ret.sg_deltas = vec![<Eqn::V as Vector>::zeros(out.nout()); naug];
-            ret.sgdiff_tmp = M::zeros(out.nout(), BdfState::<Eqn::V, M>::MAX_ORDER + 3);
+            ret.sgdiff_tmp = M::zeros(out.nout(), ret.state.sgdiff[0].ncols());
         }
         Ok(ret)
     }
@@ -373,30 +400,53 @@ where
         &self.statistics
     }
 
-    fn _compute_r(order: usize, factor: Eqn::T) -> M {
-        //computes the R matrix with entries
-        //given by the first equation on page 8 of [1]
-        //
-        //This is used to update the differences matrix when step size h is varied
-        //according to factor = h_{n+1} / h_n
-        //
-        //Note that the U matrix also defined in the same section can be also be
-        //found using factor = 1, which corresponds to R with a constant step size
-        let mut r = M::zeros(order + 1, order + 1);
-
-        // r[0, 0:order] = 1
-        for j in 0..=order {
-            r[(0, j)] = M::T::one();
-        }
-        // r[i, j] = r[i, j-1] * (j - 1 - factor * i) / j
-        for i in 1..=order {
-            for j in 1..=order {
-                let i_t = M::T::from(i as f64);
-                let j_t = M::T::from(j as f64);
-                r[(i, j)] = r[(i - 1, j)] * (i_t - M::T::one() - factor * j_t) / i_t;
-            }
+    // maximum order of the method, set by the solver options
+    fn max_order(&self) -> usize {
+        std::cmp::min(
+            self.ode_problem.options.max_order,
+            BdfState::<Eqn::V, M>::MAX_ORDER,
+        )
+    }
+
+    /// Run the solver in fixed-step mode, taking the steps given by `fixed_step` with error control disabled (see [FixedStep]),
+    /// or pass `None` to return to adaptive step size control.
+    ///
+    /// For [FixedStep::Constant] the order of the method is still selected using the error estimates, while for [FixedStep::Grid]
+    /// the order is reduced to one and kept there, since the step size changes at every grid point. If the Newton iteration fails to
+    /// converge for a step, even after updating the Jacobian, then [Self::step] returns the error since the step size cannot be reduced.
+    pub fn set_fixed_step(
+        &mut self,
+        fixed_step: Option<FixedStep<Eqn::T>>,
+    ) -> Result<(), DiffsolError> {
+        if let Some(fixed_step) = fixed_step.as_ref() {
+            fixed_step.check()?;
+        }
+        self.fixed_step = fixed_step;
+        Ok(())
+    }
+
+    pub fn fixed_step(&self) -> Option<&FixedStep<Eqn::T>> {
+        self.fixed_step.as_ref()
+    }
+
+    /// Project the solution onto the manifold of a set of invariants after each step (see [ManifoldProjection]),
+    /// or pass `None` to turn off the projection.
+    pub fn set_projection(
+        &mut self,
+        projection: Option<ManifoldProjection<'a, Eqn::M>>,
+    ) -> Result<(), DiffsolError> {
+        if let Some(projection) = projection.as_ref() {
+            projection.check(self.state.y.len())?;
         }
-        r
+        self.projection = projection;
+        Ok(())
+    }
+
+    /// Set the controller used to choose the step size (default [IController]).
+    /// The controller is used after each rejected step, and when the step size is changed after an accepted step,
+    /// which happens at most every `order + 1` steps and only if the order of the method is unchanged.
+    pub fn set_step_controller(&mut self, step_controller: impl StepController<Eqn::T> + 'static) {
+        self.step_controller = Box::new(step_controller);
     }
 
     fn _jacobian_updates(&mut self, c: Eqn::T, state: SolverState) {
@@ -431,23 +481,30 @@ where
         //- constant c = h / (1-kappa) gamma_k term
         //- lu factorisation of (M - c * J) used in newton iteration (same equation)
 
+        // limit the new step size to the maximum step size, unless the steps are prescribed in fixed-step mode
+        let mut factor = factor;
+        let max_step = self.ode_problem.options.max_step;
+        if self.fixed_step.is_none() && abs(factor * self.state.h) > max_step {
+            factor = max_step / abs(factor * self.state.h) * factor;
+        }
+
         let new_h = factor * self.state.h;
         self.n_equal_steps = 0;
 
         // update D using equations in section 3.2 of [1]
         let order = self.state.order;
-        let r = Self::_compute_r(order, factor);
+        let r = BdfState::<Eqn::V, M>::compute_r(order, factor);
         let ru = r.mat_mul(&self.u);
         {
             if self.op.is_some() {
-                Self::_update_diff_for_step_size(
+                BdfState::<Eqn::V, M>::update_diff_for_step_size(
                     &ru,
                     &mut self.state.diff,
                     &mut self.diff_tmp,
                     order,
                 );
                 if self.ode_problem.integrate_out {
-                    Self::_update_diff_for_step_size(
+                    BdfState::<Eqn::V, M>::update_diff_for_step_size(
                         &ru,
                         &mut self.state.gdiff,
                         &mut self.gdiff_tmp,
@@ -456,11 +513,21 @@ where
                 }
             }
             for diff in self.state.sdiff.iter_mut() {
-                Self::_update_diff_for_step_size(&ru, diff, &mut self.diff_tmp, order);
+                BdfState::<Eqn::V, M>::update_diff_for_step_size(
+                    &ru,
+                    diff,
+                    &mut self.diff_tmp,
+                    order,
+                );
             }
 
             for diff in self.state.sgdiff.iter_mut() {
-                Self::_update_diff_for_step_size(&ru, diff, &mut self.sgdiff_tmp, order);
+                BdfState::<Eqn::V, M>::update_diff_for_step_size(
+                    &ru,
+                    diff,
+                    &mut self.sgdiff_tmp,
+                    order,
+                );
             }
         }
 
@@ -474,7 +541,12 @@ where
         self.state.h = new_h;
 
         // if step size too small, then fail
-        if self.state.h.abs() < Eqn::T::from(Self::MIN_TIMESTEP) {
+        let min_step = self
+            .ode_problem
+            .options
+            .min_step
+            .unwrap_or(Eqn::T::from(Self::MIN_TIMESTEP));
+        if self.state.h.abs() < min_step {
             return Err(DiffsolError::from(OdeSolverError::StepSizeTooSmall {
                 time: self.state.t.into(),
             }));
@@ -482,17 +554,6 @@ where
         Ok(new_h)
     }
 
-    fn _update_diff_for_step_size(ru: &M, diff: &mut M, diff_tmp: &mut M, order: usize) {
-        // D[0:order+1] = R * U * D[0:order+1]
-        {
-            let d_zero_order = diff.columns(0, order + 1);
-            let mut d_zero_order_tmp = diff_tmp.columns_mut(0, order + 1);
-            d_zero_order_tmp.gemm_vo(Eqn::T::one(), &d_zero_order, ru, Eqn::T::zero());
-            // diff_sub = diff * RU
-        }
-        std::mem::swap(diff, diff_tmp);
-    }
-
     fn calculate_output_delta(&mut self) {
         // integrate output function
         let state = &mut self.state;
@@ -546,7 +607,7 @@ where
 
         // integrate output function
         if self.ode_problem.integrate_out {
-            Self::_predict_using_diff(&mut state.g, &state.gdiff, order);
+            BdfState::<Eqn::V, M>::predict_using_diff(&mut state.g, &state.gdiff, order);
             state.g.axpy(Eqn::T::one(), &self.g_delta, Eqn::T::one());
 
             // update output difference
@@ -561,7 +622,11 @@ where
 
                 // integrate sensitivity output equations
                 if self.s_op.as_ref().unwrap().eqn().out().is_some() {
-                    Self::_predict_using_diff(&mut state.sg[i], &state.sgdiff[i], order);
+                    BdfState::<Eqn::V, M>::predict_using_diff(
+                        &mut state.sg[i],
+                        &state.sgdiff[i],
+                        order,
+                    );
                     state.sg[i].axpy(Eqn::T::one(), &self.sg_deltas[i], Eqn::T::one());
 
                     // update sensitivity output difference
@@ -591,17 +656,9 @@ where
         }
     }
 
-    // predict forward to new step (eq 2 in [1])
-    fn _predict_using_diff(y_predict: &mut Eqn::V, diff: &M, order: usize) {
-        y_predict.fill(Eqn::T::zero());
-        for i in 0..=order {
-            y_predict.add_assign(diff.column(i));
-        }
-    }
-
     fn _predict_forward(&mut self) {
         let state = &self.state;
-        Self::_predict_using_diff(&mut self.y_predict, &state.diff, state.order);
+        BdfState::<Eqn::V, M>::predict_using_diff(&mut self.y_predict, &state.diff, state.order);
 
         // update psi and c (h, D, y0 has changed)
         if let Some(op) = self.op.as_mut() {
@@ -623,31 +680,20 @@ where
         &mut self,
         tstop: Eqn::T,
     ) -> Result<Option<OdeSolverStopReason<Eqn::T>>, DiffsolError> {
-        // check if the we are at tstop
-        let state = &self.state;
-        let troundoff = Eqn::T::from(100.0) * Eqn::T::EPSILON * (abs(state.t) + abs(state.h));
-        if abs(state.t - tstop) <= troundoff {
+        let check = check_tstop(self.state.t, self.state.h, tstop);
+        if check.is_err() {
             self.tstop = None;
-            return Ok(Some(OdeSolverStopReason::TstopReached));
-        } else if (state.h > M::T::zero() && tstop < state.t - troundoff)
-            || (state.h < M::T::zero() && tstop > state.t + troundoff)
-        {
-            let error = OdeSolverError::StopTimeBeforeCurrentTime {
-                stop_time: self.tstop.unwrap().into(),
-                state_time: state.t.into(),
-            };
-            self.tstop = None;
-
-            return Err(DiffsolError::from(error));
         }
-
-        // check if the next step will be beyond tstop, if so adjust the step size
-        if (state.h > M::T::zero() && state.t + state.h > tstop + troundoff)
-            || (state.h < M::T::zero() && state.t + state.h < tstop - troundoff)
-        {
-            let factor = (tstop - state.t) / state.h;
-            // update step size ignoring the possible "step size too small" error
-            _ = self._update_step_size(factor);
+        match check? {
+            TstopCheck::Reached => {
+                self.tstop = None;
+                return Ok(Some(OdeSolverStopReason::TstopReached));
+            }
+            TstopCheck::ScaleStep(factor) => {
+                // update step size ignoring the possible "step size too small" error
+                _ = self._update_step_size(factor);
+            }
+            TstopCheck::NotReached => {}
         }
         Ok(None)
     }
@@ -666,23 +712,12 @@ where
             }
         }
 
-        self.u = Self::_compute_r(1, Eqn::T::one());
+        self.u = BdfState::<Eqn::V, M>::compute_r(1, Eqn::T::one());
+        self.step_controller.reset();
+        self.steps_since_stop_time = 0;
         self.is_state_modified = false;
     }
 
-    //interpolate solution at time values t* where t-h < t* < t
-    //definition of the interpolating polynomial can be found on page 7 of [1]
-    fn interpolate_from_diff(t: Eqn::T, diff: &M, t1: Eqn::T, h: Eqn::T, order: usize) -> Eqn::V {
-        let mut time_factor = Eqn::T::from(1.0);
-        let mut order_summation = diff.column(0).into_owned();
-        for i in 0..order {
-            let i_t = Eqn::T::from(i as f64);
-            time_factor *= (t - (t1 - h * i_t)) / (h * (Eqn::T::one() + i_t));
-            order_summation += diff.column(i + 1) * scale(time_factor);
-        }
-        order_summation
-    }
-
     fn error_control(&self) -> Eqn::T {
         let state = &self.state;
         let order = state.order;
@@ -705,8 +740,13 @@ where
         if self.op.is_some() {
             let atol = &self.ode_problem.atol;
             let rtol = self.ode_problem.rtol;
-            error_norm +=
-                self.y_delta.squared_norm(&state.y, atol, rtol) * self.error_const2[order - 1];
+            error_norm += squared_norm(
+                self.ode_problem.options.error_norm.as_ref(),
+                &self.y_delta,
+                &state.y,
+                atol,
+                rtol,
+            ) * self.error_const2[order - 1];
             ncontrib += 1;
             if output_in_error_control {
                 let rtol = self.ode_problem.out_rtol.unwrap();
@@ -761,11 +801,13 @@ where
         let mut error_norm = M::T::zero();
         let mut ncontrib = 0;
         if self.op.is_some() {
-            error_norm += state
-                .diff
-                .column(order + 1)
-                .squared_norm(&state.y, atol, rtol)
-                * self.error_const2[order];
+            error_norm += view_squared_norm(
+                self.ode_problem.options.error_norm.as_ref(),
+                state.diff.column(order + 1),
+                &state.y,
+                atol,
+                rtol,
+            ) * self.error_const2[order];
             ncontrib += 1;
             if output_in_error_control {
                 let rtol = self.ode_problem.out_rtol.unwrap();
@@ -828,7 +870,11 @@ where
             {
                 let state = &self.state;
                 // predict forward to new step
-                Self::_predict_using_diff(&mut self.s_predict, &state.sdiff[i], order);
+                BdfState::<Eqn::V, M>::predict_using_diff(
+                    &mut self.s_predict,
+                    &state.sdiff[i],
+                    order,
+                );
 
                 // setup op
                 s_op.set_psi_and_y0(
@@ -846,7 +892,7 @@ where
                 let s_new = &mut self.state.s[i];
                 s_new.copy_from(&self.s_predict);
                 // todo: should be a separate convergence object?
-                self.nonlinear_solver.solve_in_place(
+                self.nonlinear_solver.solve_in_place_with_jacobian(
                     &*s_op,
                     s_new,
                     t_new,
@@ -886,6 +932,14 @@ where
     fn set_state(&mut self, state: Self::State) {
         let old_order = self.state.order;
         self.state = state;
+        self.steps_since_stop_time = 0;
+
+        // the state might come from a solver that supports higher orders (e.g. the adams solver),
+        // bdf methods above MAX_ORDER are unstable so drop the higher differences (also done if the order is limited by the solver options)
+        let max_order = self.max_order();
+        if self.state.order > max_order {
+            self.state.order = max_order;
+        }
 
         if let Some(op) = self.op.as_mut() {
             op.set_c(self.state.h, self.alpha[self.state.order]);
@@ -893,9 +947,40 @@ where
 
         // order might have changed
         if self.state.order != old_order {
-            self.u = Self::_compute_r(self.state.order, Eqn::T::one());
+            self.u = BdfState::<Eqn::V, M>::compute_r(self.state.order, Eqn::T::one());
+        }
+
+        // the new state might have a different number of difference columns
+        if self.diff_tmp.ncols() != self.state.diff.ncols() {
+            self.diff_tmp = M::zeros(self.state.diff.nrows(), self.state.diff.ncols());
+        }
+        if self.gdiff_tmp.ncols() != self.state.gdiff.ncols() {
+            self.gdiff_tmp = M::zeros(self.state.gdiff.nrows(), self.state.gdiff.ncols());
+        }
+        if let Some(sgdiff) = self.state.sgdiff.first() {
+            if self.sgdiff_tmp.ncols() != sgdiff.ncols() {
+                self.sgdiff_tmp = M::zeros(sgdiff.nrows(), sgdiff.ncols());
+            }
+        }
+
+        // unset the stop time if it is not ahead of the new state
+        if let Some(tstop) = self.tstop {
+            let state = &self.state;
+            if (state.h > Eqn::T::zero() && tstop <= state.t)
+                || (state.h < Eqn::T::zero() && tstop >= state.t)
+            {
+                self.tstop = None;
+            }
         }
 
+        // reinitialise root finder if needed
+        init_root_finder(
+            self.ode_problem,
+            self.root_finder.as_ref(),
+            &self.state.y,
+            self.state.t,
+        );
+
         // reinitialise jacobian updates as if a checkpoint was taken
         self._jacobian_updates(
             self.state.h * self.alpha[self.state.order],
@@ -918,7 +1003,7 @@ where
         if (is_forward && t > state.t) || (!is_forward && t < state.t) {
             return Err(ode_solver_error!(InterpolationTimeAfterCurrentTime));
         }
-        Ok(Self::interpolate_from_diff(
+        Ok(BdfState::<Eqn::V, M>::interpolate_from_diff(
             t,
             &state.diff,
             state.t,
@@ -942,7 +1027,7 @@ where
         if (is_forward && t > state.t) || (!is_forward && t < state.t) {
             return Err(ode_solver_error!(InterpolationTimeAfterCurrentTime));
         }
-        Ok(Self::interpolate_from_diff(
+        Ok(BdfState::<Eqn::V, M>::interpolate_from_diff(
             t,
             &state.gdiff,
             state.t,
@@ -969,7 +1054,7 @@ where
 
         let mut s = Vec::with_capacity(state.s.len());
         for i in 0..state.s.len() {
-            s.push(Self::interpolate_from_diff(
+            s.push(BdfState::<Eqn::V, M>::interpolate_from_diff(
                 t,
                 &state.sdiff[i],
                 state.t,
@@ -1016,14 +1101,13 @@ where
         let mut convergence_fail = false;
 
         if self.is_state_modified {
-            // reinitalise root finder if needed
-            if let Some(root_fn) = problem.eqn.root() {
-                let state = &self.state;
-                self.root_finder
-                    .as_ref()
-                    .unwrap()
-                    .init(&root_fn, &state.y, state.t);
-            }
+            // reinitialise root finder if needed
+            init_root_finder(
+                problem,
+                self.root_finder.as_ref(),
+                &self.state.y,
+                self.state.t,
+            );
             // reinitialise diff matrix
             self.initialise_to_first_order();
 
@@ -1033,6 +1117,40 @@ where
             }
         }
 
+        if let Some(max_steps) = problem.options.max_steps {
+            if self.steps_since_stop_time >= max_steps {
+                return Err(DiffsolError::from(OdeSolverError::MaxStepsReached {
+                    max_steps,
+                    time: self.state.t.into(),
+                }));
+            }
+        }
+
+        // in fixed-step mode set the prescribed step size, shortened if needed to stop at tstop
+        if let Some(fixed_step) = self.fixed_step.as_ref() {
+            let t = self.state.t;
+            let mut h = fixed_step.step_size(t)?;
+            if let Some(tstop) = self.tstop {
+                if (h > Eqn::T::zero() && t + h > tstop) || (h < Eqn::T::zero() && t + h < tstop) {
+                    h = tstop - t;
+                }
+            }
+            if h != self.state.h {
+                let new_h = self._update_step_size(h / self.state.h)?;
+                self._jacobian_updates(
+                    new_h * self.alpha[self.state.order],
+                    SolverState::StepSuccess,
+                );
+            }
+        } else if abs(self.state.h) > problem.options.max_step {
+            // the step size might be larger than the maximum (e.g. the initial step size)
+            let new_h = self._update_step_size(Eqn::T::one())?;
+            self._jacobian_updates(
+                new_h * self.alpha[self.state.order],
+                SolverState::StepSuccess,
+            );
+        }
+
         self._predict_forward();
 
         // loop until step is accepted
@@ -1043,7 +1161,7 @@ where
             // solve BDF equation using y0 as starting point
             let mut solve_result = Ok(());
             if let Some(op) = self.op.as_ref() {
-                solve_result = self.nonlinear_solver.solve_in_place(
+                solve_result = self.nonlinear_solver.solve_in_place_with_jacobian(
                     op,
                     &mut self.y_delta,
                     self.t_predict,
@@ -1076,9 +1194,13 @@ where
             }
 
             // handle case where either nonlinear solve failed
-            if solve_result.is_err() {
+            if let Err(err) = solve_result {
                 self.statistics.number_of_nonlinear_solver_fails += 1;
                 if convergence_fail {
+                    // the step size is prescribed in fixed-step mode, so report the failure
+                    if self.fixed_step.is_some() {
+                        return Err(err);
+                    }
                     // newton iteration did not converge, but jacobian has already been
                     // evaluated so reduce step size by 0.3 (as per [1]) and try again
                     let new_h = self._update_step_size(Eqn::T::from(0.3))?;
@@ -1103,6 +1225,44 @@ where
                 continue;
             }
 
+            // check the inequality constraints on the new solution y_predict + y_delta, and update y_delta with any
+            // small violations that have been corrected
+            if let (Some(constraints), true) =
+                (problem.options.constraints.as_ref(), self.op.is_some())
+            {
+                let mut y_new = self.y_predict.clone();
+                y_new += &self.y_delta;
+                let factor = constraint_step_factor(
+                    constraints,
+                    self.state.y.as_slice(),
+                    y_new.as_mut_slice(),
+                    problem.atol.as_slice(),
+                    problem.rtol,
+                );
+                if let Some(factor) = factor {
+                    if self.fixed_step.is_some() {
+                        return Err(ode_solver_error!(
+                            Other,
+                            format!(
+                                "Constraints violated at time {} in fixed-step mode",
+                                self.t_predict
+                            )
+                        ));
+                    }
+                    let new_h = self._update_step_size(factor)?;
+                    self._jacobian_updates(new_h * self.alpha[order], SolverState::ErrorTestFail);
+
+                    // new prediction
+                    self._predict_forward();
+
+                    // update statistics
+                    self.statistics.number_of_constraint_failures += 1;
+                    continue;
+                }
+                self.y_delta.copy_from(&y_new);
+                self.y_delta -= &self.y_predict;
+            }
+
             error_norm = self.error_control();
 
             // need to caulate safety even if step is accepted
@@ -1110,15 +1270,50 @@ where
             let niter = self.convergence.niter() as f64;
             safety = Eqn::T::from(0.9 * (2.0 * maxiter + 1.0) / (2.0 * maxiter + niter));
 
-            // do the error test
-            if error_norm <= Eqn::T::from(1.0) {
-                // step is accepted
+            // do the error test (always passes in fixed-step mode)
+            if error_norm <= Eqn::T::from(1.0) || self.fixed_step.is_some() {
+                // step is accepted, project the new solution y_predict + y_delta onto the invariant manifold
+                // and store it in y_predict, correcting y_delta so that the differences are consistent with it
+                if let (Some(projection), true) = (self.projection.as_ref(), self.op.is_some()) {
+                    let mut y_new = self.y_predict.clone();
+                    y_new += &self.y_delta;
+                    let problem = self.ode_problem;
+                    let result = projection.project(
+                        &mut y_new,
+                        self.t_predict,
+                        problem.rtol,
+                        &problem.atol,
+                        problem.options.error_norm.clone(),
+                    );
+                    if let Err(err) = result {
+                        if self.fixed_step.is_some() {
+                            return Err(err);
+                        }
+                        let new_h = self._update_step_size(Eqn::T::from(0.25))?;
+                        self._jacobian_updates(
+                            new_h * self.alpha[order],
+                            SolverState::ErrorTestFail,
+                        );
+
+                        // new prediction
+                        self._predict_forward();
+                        continue;
+                    }
+                    self.y_delta.copy_from(&y_new);
+                    self.y_delta -= &self.y_predict;
+                    self.y_predict.copy_from(&y_new);
+                } else if problem.options.constraints.is_some() && self.op.is_some() {
+                    // report the corrected solution, which satisfies the constraints
+                    self.y_predict += &self.y_delta;
+                }
                 break;
             } else {
                 // step is rejected
-                // calculate optimal step size factor as per eq 2.46 of [2]
+                // calculate optimal step size factor (as per eq 2.46 of [2] for the default controller)
                 // and reduce step size and try again
-                let mut factor = safety * error_norm.pow(Eqn::T::from(-0.5 / (order as f64 + 1.0)));
+                let mut factor =
+                    self.step_controller
+                        .reject(self.state.h, error_norm.sqrt(), order + 1, safety);
                 if factor < Eqn::T::from(Self::MIN_FACTOR) {
                     factor = Eqn::T::from(Self::MIN_FACTOR);
                 }
@@ -1151,6 +1346,7 @@ where
             self.statistics.number_of_linear_solver_setups = s_op.number_of_jac_evals();
         }
         self.statistics.number_of_steps += 1;
+        self.steps_since_stop_time += 1;
         self.jacobian_update.step();
 
         // a change in order is only done after running at order k for k + 1 steps
@@ -1168,7 +1364,7 @@ where
                 } else {
                     Eqn::T::INFINITY
                 };
-                let error_p_norm = if order < BdfState::<Eqn::V, M>::MAX_ORDER {
+                let error_p_norm = if order < self.max_order() {
                     self.predict_error_control(order + 1)
                 } else {
                     Eqn::T::INFINITY
@@ -1186,13 +1382,18 @@ where
 
             // now we have the three factors for orders k-1, k and k+1, pick the maximum in
             // order to maximise the resultant step size
-            let max_index = factors
+            let mut max_index = factors
                 .iter()
                 .enumerate()
                 .max_by(|(_, a), (_, b)| a.partial_cmp(b).unwrap())
                 .unwrap()
                 .0;
 
+            // the step size changes at every point of a time grid, so the order is reduced to (and kept at) one
+            if matches!(self.fixed_step, Some(FixedStep::Grid(_))) {
+                max_index = if self.state.order > 1 { 0 } else { 1 };
+            }
+
             // update order and update the U matrix
             let order = {
                 let old_order = self.state.order;
@@ -1204,25 +1405,40 @@ where
                 };
                 self.state.order = new_order;
                 if max_index != 1 {
-                    self.u = Self::_compute_r(new_order, Eqn::T::one());
+                    self.u = BdfState::<Eqn::V, M>::compute_r(new_order, Eqn::T::one());
                 }
                 new_order
             };
 
-            let mut factor = safety * factors[max_index];
-            if factor > Eqn::T::from(Self::MAX_FACTOR) {
-                factor = Eqn::T::from(Self::MAX_FACTOR);
-            }
-            if factor < Eqn::T::from(Self::MIN_FACTOR) {
-                factor = Eqn::T::from(Self::MIN_FACTOR);
-            }
-            if factor >= Eqn::T::from(Self::MAX_THRESHOLD)
-                || factor < Eqn::T::from(Self::MIN_THRESHOLD)
-                || max_index == 0
-                || max_index == 2
-            {
-                let new_h = self._update_step_size(factor)?;
-                self._jacobian_updates(new_h * self.alpha[order], SolverState::StepSuccess);
+            if self.fixed_step.is_some() {
+                // keep the step size, but update the newton iteration for the new order
+                if max_index != 1 {
+                    let new_h = self._update_step_size(Eqn::T::one())?;
+                    self._jacobian_updates(new_h * self.alpha[order], SolverState::StepSuccess);
+                }
+            } else {
+                // the step size controller is used if the order is unchanged, otherwise its history is no longer valid
+                let mut factor = if max_index == 1 {
+                    self.step_controller
+                        .accept(self.state.h, error_norm.sqrt(), order + 1, safety)
+                } else {
+                    self.step_controller.reset();
+                    safety * factors[max_index]
+                };
+                if factor > Eqn::T::from(Self::MAX_FACTOR) {
+                    factor = Eqn::T::from(Self::MAX_FACTOR);
+                }
+                if factor < Eqn::T::from(Self::MIN_FACTOR) {
+                    factor = Eqn::T::from(Self::MIN_FACTOR);
+                }
+                if factor >= Eqn::T::from(Self::MAX_THRESHOLD)
+                    || factor < Eqn::T::from(Self::MIN_THRESHOLD)
+                    || max_index == 0
+                    || max_index == 2
+                {
+                    let new_h = self._update_step_size(factor)?;
+                    self._jacobian_updates(new_h * self.alpha[order], SolverState::StepSuccess);
+                }
             }
         }
 
@@ -1249,8 +1465,20 @@ where
         Ok(OdeSolverStopReason::InternalTimestep)
     }
 
+    fn reverse_direction(&mut self) -> Result<(), DiffsolError> {
+        // the differences are rescaled to the reversed step size, as for any other change of step size
+        let new_h = self._update_step_size(-Eqn::T::one())?;
+        self._jacobian_updates(
+            new_h * self.alpha[self.state.order],
+            SolverState::StepSuccess,
+        );
+        self.tstop = None;
+        Ok(())
+    }
+
     fn set_stop_time(&mut self, tstop: <Eqn as Op>::T) -> Result<(), DiffsolError> {
         self.tstop = Some(tstop);
+        self.steps_since_stop_time = 0;
         if let Some(OdeSolverStopReason::TstopReached) = self.handle_tstop(tstop)? {
             let error = OdeSolverError::StopTimeBeforeCurrentTime {
                 stop_time: tstop.into(),
@@ -1286,15 +1514,25 @@ mod test {
                 robertson::{robertson, robertson_sens},
                 robertson_ode::robertson_ode,
                 robertson_ode_with_sens::robertson_ode_with_sens,
+                spring_mass::spring_mass_problem,
             },
             tests::{
-                test_checkpointing, test_interpolate, test_ode_solver, test_ode_solver_adjoint,
-                test_problem, test_state_mut, test_state_mut_on_problem,
+                robertson_ode_with_constraints, spring_mass_energy, test_checkpointing,
+                test_constraints, test_error_norm, test_fixed_step, test_interpolate,
+                test_max_step_and_max_steps, test_ode_solver, test_ode_solver_adjoint,
+                test_problem, test_projection, test_state_mut, test_state_mut_on_problem,
+                test_step_controller, RecordingController,
             },
         },
-        FaerLU, FaerSparseLU, OdeEquations, OdeSolverMethod, Op, SparseColMat, Vector,
+        AndersonNonlinearSolver, Bdf, Constraint, ErrorNorm, FaerLU, FaerSparseLU, FixedStep,
+        Gmres, Ilu0, ManifoldProjection, MaxNorm, OdeBuilder, OdeEquations, OdeSolverMethod,
+        OdeSolverStopReason, Op, PiController, PredictiveController, SolverOptions, SparseColMat,
+        Vector, WeightedL2Norm,
     };
 
+    use std::sync::Arc;
+
+    use nalgebra::DVector;
     use num_traits::abs;
 
     type M = nalgebra::DMatrix<f64>;
@@ -1334,6 +1572,7 @@ mod test {
         number_of_error_test_failures: 0
         number_of_nonlinear_solver_iterations: 82
         number_of_nonlinear_solver_fails: 0
+        number_of_constraint_failures: 0
         "###);
         insta::assert_yaml_snapshot!(problem.eqn.rhs().statistics(), @r###"
         number_of_calls: 84
@@ -1371,6 +1610,7 @@ mod test {
         number_of_error_test_failures: 0
         number_of_nonlinear_solver_iterations: 82
         number_of_nonlinear_solver_fails: 0
+        number_of_constraint_failures: 0
         "###);
         insta::assert_yaml_snapshot!(problem.eqn.rhs().statistics(), @r###"
         number_of_calls: 84
@@ -1391,6 +1631,7 @@ mod test {
         number_of_error_test_failures: 1
         number_of_nonlinear_solver_iterations: 234
         number_of_nonlinear_solver_fails: 0
+        number_of_constraint_failures: 0
         "###);
         insta::assert_yaml_snapshot!(problem.eqn.statistics(), @r###"
         number_of_calls: 89
@@ -1415,6 +1656,7 @@ mod test {
         number_of_error_test_failures: 1
         number_of_nonlinear_solver_iterations: 234
         number_of_nonlinear_solver_fails: 0
+        number_of_constraint_failures: 0
         "###);
     }
 
@@ -1474,6 +1716,7 @@ mod test {
         number_of_error_test_failures: 4
         number_of_nonlinear_solver_iterations: 79
         number_of_nonlinear_solver_fails: 0
+        number_of_constraint_failures: 0
         "###);
         insta::assert_yaml_snapshot!(problem.eqn.rhs().statistics(), @r###"
         number_of_calls: 83
@@ -1501,6 +1744,7 @@ mod test {
         number_of_error_test_failures: 3
         number_of_nonlinear_solver_iterations: 155
         number_of_nonlinear_solver_fails: 0
+        number_of_constraint_failures: 0
         "###);
         insta::assert_yaml_snapshot!(problem.eqn.rhs().statistics(), @r###"
         number_of_calls: 71
@@ -1526,6 +1770,7 @@ mod test {
         number_of_error_test_failures: 3
         number_of_nonlinear_solver_iterations: 155
         number_of_nonlinear_solver_fails: 0
+        number_of_constraint_failures: 0
         "###);
     }
 
@@ -1540,6 +1785,7 @@ mod test {
         number_of_error_test_failures: 3
         number_of_nonlinear_solver_iterations: 722
         number_of_nonlinear_solver_fails: 19
+        number_of_constraint_failures: 0
         "###);
         insta::assert_yaml_snapshot!(problem.eqn.rhs().statistics(), @r###"
         number_of_calls: 725
@@ -1549,6 +1795,33 @@ mod test {
         "###);
     }
 
+    #[test]
+    fn test_bdf_nalgebra_robertson_pi_controller() {
+        let (problem, soln) = robertson::<M>(false);
+        let mut s = problem.bdf::<LS>().unwrap();
+        s.set_step_controller(PiController::default());
+        test_ode_solver(&mut s, soln, None, false, false);
+    }
+
+    #[test]
+    fn bdf_test_step_controller_calls() {
+        let (problem, _soln) = robertson::<M>(false);
+        let controller = RecordingController::default();
+        let mut s = problem.bdf::<LS>().unwrap();
+        s.set_step_controller(controller.clone());
+        let (naccept, nreject, nsteps) = test_step_controller(&mut s, &controller, 1.0);
+        assert!(naccept > 0 && naccept <= nsteps);
+        assert_eq!(nreject, s.get_statistics().number_of_error_test_failures);
+    }
+
+    #[test]
+    fn test_bdf_nalgebra_robertson_predictive_controller() {
+        let (problem, soln) = robertson::<M>(false);
+        let mut s = problem.bdf::<LS>().unwrap();
+        s.set_step_controller(PredictiveController::default());
+        test_ode_solver(&mut s, soln, None, false, false);
+    }
+
     #[test]
     fn bdf_test_faer_sparse_robertson() {
         let (problem, soln) = robertson::<SparseColMat<f64>>(false);
@@ -1586,6 +1859,7 @@ mod test {
         number_of_error_test_failures: 4
         number_of_nonlinear_solver_iterations: 3107
         number_of_nonlinear_solver_fails: 81
+        number_of_constraint_failures: 0
         "###);
         insta::assert_yaml_snapshot!(problem.eqn.rhs().statistics(), @r###"
         number_of_calls: 996
@@ -1606,6 +1880,7 @@ mod test {
         number_of_error_test_failures: 3
         number_of_nonlinear_solver_iterations: 722
         number_of_nonlinear_solver_fails: 19
+        number_of_constraint_failures: 0
         "###);
         insta::assert_yaml_snapshot!(problem.eqn.rhs().statistics(), @r###"
         number_of_calls: 725
@@ -1626,6 +1901,7 @@ mod test {
         number_of_error_test_failures: 1
         number_of_nonlinear_solver_iterations: 911
         number_of_nonlinear_solver_fails: 15
+        number_of_constraint_failures: 0
         "###);
         insta::assert_yaml_snapshot!(problem.eqn.rhs().statistics(), @r###"
         number_of_calls: 913
@@ -1646,6 +1922,7 @@ mod test {
         number_of_error_test_failures: 5
         number_of_nonlinear_solver_iterations: 3779
         number_of_nonlinear_solver_fails: 70
+        number_of_constraint_failures: 0
         "###);
         insta::assert_yaml_snapshot!(problem.eqn.rhs().statistics(), @r###"
         number_of_calls: 1157
@@ -1666,6 +1943,7 @@ mod test {
         number_of_error_test_failures: 0
         number_of_nonlinear_solver_iterations: 355
         number_of_nonlinear_solver_fails: 3
+        number_of_constraint_failures: 0
         "###);
         insta::assert_yaml_snapshot!(problem.eqn.rhs().statistics(), @r###"
         number_of_calls: 357
@@ -1686,6 +1964,7 @@ mod test {
         number_of_error_test_failures: 0
         number_of_nonlinear_solver_iterations: 355
         number_of_nonlinear_solver_fails: 3
+        number_of_constraint_failures: 0
         "###);
         insta::assert_yaml_snapshot!(problem.eqn.rhs().statistics(), @r###"
         number_of_calls: 357
@@ -1706,6 +1985,7 @@ mod test {
         number_of_error_test_failures: 1
         number_of_nonlinear_solver_iterations: 130
         number_of_nonlinear_solver_fails: 0
+        number_of_constraint_failures: 0
         "###);
         insta::assert_yaml_snapshot!(problem.eqn.rhs().statistics(), @r###"
         number_of_calls: 132
@@ -1726,6 +2006,7 @@ mod test {
         number_of_error_test_failures: 0
         number_of_nonlinear_solver_iterations: 330
         number_of_nonlinear_solver_fails: 0
+        number_of_constraint_failures: 0
         "###);
         insta::assert_yaml_snapshot!(problem.eqn.rhs().statistics(), @r###"
         number_of_calls: 333
@@ -1735,6 +2016,22 @@ mod test {
         "###);
     }
 
+    #[test]
+    fn test_bdf_gmres_heat2d() {
+        let (problem, soln) = head2d_problem::<SparseColMat<f64>, 10>();
+        let mut s = problem.bdf::<Gmres<SparseColMat<f64>>>().unwrap();
+        test_ode_solver(&mut s, soln, None, false, false);
+    }
+
+    #[test]
+    fn test_bdf_gmres_ilu0_heat2d() {
+        let (problem, soln) = head2d_problem::<SparseColMat<f64>, 10>();
+        let mut s = problem
+            .bdf::<Gmres<SparseColMat<f64>, Ilu0<SparseColMat<f64>>>>()
+            .unwrap();
+        test_ode_solver(&mut s, soln, None, false, false);
+    }
+
     #[cfg(feature = "diffsl-llvm")]
     #[test]
     fn test_bdf_faer_sparse_heat2d_diffsl() {
@@ -1757,6 +2054,7 @@ mod test {
         number_of_error_test_failures: 2
         number_of_nonlinear_solver_iterations: 355
         number_of_nonlinear_solver_fails: 14
+        number_of_constraint_failures: 0
         "###);
     }
 
@@ -1836,4 +2134,238 @@ mod test {
             assert!((t - expected_t[i]).abs() < 1e-4);
         }
     }
+
+    #[test]
+    fn bdf_test_fixed_step() {
+        let (problem, soln) = exponential_decay_problem::<M>(false);
+        let expected = &soln.solution_points[1];
+        assert_eq!(expected.t, 1.0);
+
+        // constant steps, and a time grid that is followed exactly with the last step shortened to stop at tstop
+        let mut s = problem.bdf::<LS>().unwrap();
+        s.set_fixed_step(Some(FixedStep::Constant(0.125))).unwrap();
+        let expected_t = (1..=8).map(|i| i as f64 * 0.125).collect::<Vec<_>>();
+        test_fixed_step(s, &expected_t, &expected.state, 1e-3);
+        let mut s = problem.bdf::<LS>().unwrap();
+        s.set_fixed_step(Some(FixedStep::Grid(vec![0.0, 0.1, 0.3, 0.7, 1.5])))
+            .unwrap();
+        test_fixed_step(s, &[0.1, 0.3, 0.7, 1.0], &expected.state, 1e-2);
+
+        // the prescribed steps are taken even if they are larger than max_step
+        let (mut problem_max_step, _soln) = exponential_decay_problem::<M>(false);
+        problem_max_step.options.max_step = 0.1;
+        let mut s = problem_max_step.bdf::<LS>().unwrap();
+        s.set_fixed_step(Some(FixedStep::Grid(vec![0.0, 0.5, 1.0])))
+            .unwrap();
+        test_fixed_step(s, &[0.5, 1.0], &expected.state, 1e-2);
+
+        // the order is kept at one on a time grid, but still adapted for a constant step size
+        let grid = (0..=40)
+            .map(|i| (i as f64 / 40.0).powi(2))
+            .collect::<Vec<_>>();
+        let mut s = problem.bdf::<LS>().unwrap();
+        s.set_fixed_step(Some(FixedStep::Grid(grid))).unwrap();
+        s.set_stop_time(1.0).unwrap();
+        while s.step().unwrap() != OdeSolverStopReason::TstopReached {
+            assert_eq!(s.order(), 1);
+        }
+        assert_eq!(s.get_statistics().number_of_error_test_failures, 0);
+        let mut s = problem.bdf::<LS>().unwrap();
+        s.set_fixed_step(Some(FixedStep::Constant(0.025))).unwrap();
+        s.set_stop_time(1.0).unwrap();
+        let mut max_order = 1;
+        while s.step().unwrap() != OdeSolverStopReason::TstopReached {
+            max_order = max_order.max(s.order());
+        }
+        assert!(max_order > 1);
+    }
+
+    #[test]
+    fn bdf_test_fixed_step_newton_failure() {
+        // dy/dt = y^2, y(0) = 1, a backward euler step of size 1 has no real solution
+        let problem = OdeBuilder::<M>::new()
+            .rhs_implicit(
+                |x, _p, _t, y| y[0] = x[0] * x[0],
+                |x, _p, _t, v, y| y[0] = 2.0 * x[0] * v[0],
+            )
+            .init(|_p, _t| nalgebra::DVector::from_element(1, 1.0))
+            .build()
+            .unwrap();
+        let mut s = problem.bdf::<LS>().unwrap();
+        s.set_fixed_step(Some(FixedStep::Constant(1.0))).unwrap();
+        assert!(s.step().is_err());
+        assert!(s.get_statistics().number_of_nonlinear_solver_fails >= 2);
+        assert!(s.set_fixed_step(Some(FixedStep::Constant(0.0))).is_err());
+    }
+
+    #[test]
+    fn bdf_test_solver_options() {
+        // the step size is limited to max_step
+        let (mut problem, soln) = exponential_decay_problem::<M>(false);
+        problem.options = SolverOptions {
+            max_step: 0.1,
+            ..Default::default()
+        };
+        {
+            let mut s = problem.bdf::<LS>().unwrap();
+            s.set_stop_time(1.0).unwrap();
+            while s.step().unwrap() != OdeSolverStopReason::TstopReached {
+                assert!(s.state().h <= 0.1 + 1e-12, "h = {}", s.state().h);
+            }
+            assert!(s.get_statistics().number_of_steps >= 10);
+            let expected = &soln.solution_points[1];
+            let error = (s.state().y - &expected.state).norm();
+            assert!(error < 1e-4 * expected.state.norm(), "error = {}", error);
+        }
+
+        // the order is limited to max_order
+        problem.options = SolverOptions {
+            max_order: 1,
+            ..Default::default()
+        };
+        {
+            let mut s = problem.bdf::<LS>().unwrap();
+            s.set_stop_time(1.0).unwrap();
+            while s.step().unwrap() != OdeSolverStopReason::TstopReached {
+                assert_eq!(s.order(), 1);
+            }
+        }
+
+        // the step count is limited by max_steps
+        problem.options = SolverOptions {
+            max_step: 0.1,
+            max_steps: Some(5),
+            ..Default::default()
+        };
+        test_max_step_and_max_steps(problem.bdf::<LS>().unwrap());
+    }
+
+    #[test]
+    fn bdf_test_constraints() {
+        let (problem, soln) = robertson_ode_with_constraints::<M>();
+        let mut s = problem.bdf::<LS>().unwrap();
+        test_constraints(&mut s, &soln);
+
+        // dy/dt = -1, y(0) = 1 reaches zero at t = 1, so a step of size 2 violates the constraint y >= 0 and is rejected
+        let problem = OdeBuilder::<M>::new()
+            .rhs_implicit(|_x, _p, _t, y| y[0] = -1.0, |_x, _p, _t, _v, y| y[0] = 0.0)
+            .init(|_p, _t| nalgebra::DVector::from_element(1, 1.0))
+            .constraints([Constraint::NonNegative])
+            .build()
+            .unwrap();
+        let mut state = problem.bdf_state::<LS>().unwrap();
+        state.h = 2.0;
+        let mut s = problem.bdf_solver::<LS>(state).unwrap();
+        s.step().unwrap();
+        assert!(s.state().y[0] >= 0.0, "y = {}", s.state().y[0]);
+        assert!(s.state().t < 1.0, "t = {}", s.state().t);
+        assert!(s.get_statistics().number_of_constraint_failures > 0);
+
+        // the initial state must satisfy the constraints
+        let problem = OdeBuilder::<M>::new()
+            .rhs_implicit(|_x, _p, _t, y| y[0] = -1.0, |_x, _p, _t, _v, y| y[0] = 0.0)
+            .init(|_p, _t| nalgebra::DVector::from_element(1, 0.0))
+            .constraints([Constraint::Positive])
+            .build()
+            .unwrap();
+        assert!(problem.bdf::<LS>().is_err());
+
+        // there must be one constraint per state
+        let problem = OdeBuilder::<M>::new()
+            .rhs_implicit(|_x, _p, _t, y| y[0] = -1.0, |_x, _p, _t, _v, y| y[0] = 0.0)
+            .init(|_p, _t| nalgebra::DVector::from_element(1, 1.0))
+            .constraints([Constraint::Positive, Constraint::None])
+            .build();
+        assert!(problem.is_err());
+    }
+
+    #[test]
+    fn bdf_test_projection() {
+        let (problem, soln) = spring_mass_problem::<M>(false, 0.0);
+        let problem = problem.with_projection(Some(spring_mass_energy())).unwrap();
+        test_projection(problem.bdf::<LS>().unwrap(), soln, 1e-8);
+
+        // the projection can also be set on the solver, and the invariants must have the same number of inputs as there are states
+        let (problem, _soln) = spring_mass_problem::<M>(false, 0.0);
+        let mut s = problem.bdf::<LS>().unwrap();
+        s.set_projection(Some(spring_mass_energy())).unwrap();
+        let wrong_size = || {
+            ManifoldProjection::from_closures(
+                |_x: &DVector<f64>, _t, g: &mut DVector<f64>| g[0] = 0.0,
+                |_x: &DVector<f64>, _t, _v: &DVector<f64>, y: &mut DVector<f64>| y[0] = 0.0,
+                3,
+                1,
+            )
+        };
+        assert!(s.set_projection(Some(wrong_size())).is_err());
+        let (problem, _soln) = spring_mass_problem::<M>(false, 0.0);
+        assert!(problem.with_projection(Some(wrong_size())).is_err());
+    }
+
+    #[test]
+    fn bdf_test_anderson() {
+        let (problem, soln) = exponential_decay_problem::<M>(false);
+        let state = problem.bdf_state::<LS>().unwrap();
+        let mut s: Bdf<_, AndersonNonlinearSolver<M>> =
+            Bdf::new(&problem, state, AndersonNonlinearSolver::default()).unwrap();
+        test_ode_solver(&mut s, soln, None, false, false);
+    }
+
+    #[test]
+    fn bdf_test_line_search_consistent_init() {
+        // dy/dt = -y, 0 = atan(z - y), a full Newton iteration on the algebraic equation diverges from the initial guess z = 10
+        let mut problem = OdeBuilder::<M>::new()
+            .rhs_implicit(
+                |x, _p, _t, y| {
+                    y[0] = -x[0];
+                    y[1] = (x[1] - x[0]).atan();
+                },
+                |x, _p, _t, v, y| {
+                    let d = x[1] - x[0];
+                    y[0] = -v[0];
+                    y[1] = (v[1] - v[0]) / (1.0 + d * d);
+                },
+            )
+            .mass(|x, _p, _t, beta, y| {
+                y[0] = x[0] + beta * y[0];
+                y[1] *= beta;
+            })
+            .init(|_p, _t| DVector::from_vec(vec![1.0, 10.0]))
+            .build()
+            .unwrap();
+        assert!(problem.bdf_state::<LS>().is_err());
+
+        problem.options.line_search_consistent_init = true;
+        let state = problem.bdf_state::<LS>().unwrap();
+        assert!(abs(state.y[0] - 1.0) < 1e-12);
+        assert!(abs(state.y[1] - 1.0) < 1e-6, "z = {}", state.y[1]);
+        assert!(abs(state.dy[0] + 1.0) < 1e-6, "dy = {}", state.dy[0]);
+        let mut s = problem.bdf_solver::<LS>(state).unwrap();
+        s.set_stop_time(1.0).unwrap();
+        while s.step().unwrap() != OdeSolverStopReason::TstopReached {}
+        let expected = (-1.0f64).exp();
+        assert!(abs(s.state().y[1] - expected) < 1e-3 * expected);
+    }
+
+    #[test]
+    fn bdf_test_error_norm() {
+        // the max norm and a weighted L2 norm that excludes the second component
+        let (mut problem, soln) = exponential_decay_problem::<M>(false);
+        let norms: [Arc<dyn ErrorNorm<f64>>; 2] = [
+            Arc::new(MaxNorm),
+            Arc::new(WeightedL2Norm::new(vec![1.0, 0.0])),
+        ];
+        for norm in norms {
+            problem.options.error_norm = Some(norm);
+            test_error_norm(problem.bdf::<LS>().unwrap(), &soln, 1e-4);
+        }
+
+        // the weights must match the number of states
+        let problem = OdeBuilder::<M>::new()
+            .rhs_implicit(|_x, _p, _t, y| y[0] = -1.0, |_x, _p, _t, _v, y| y[0] = 0.0)
+            .init(|_p, _t| DVector::from_element(1, 1.0))
+            .error_norm(WeightedL2Norm::new(vec![1.0, 1.0]))
+            .build();
+        assert!(problem.is_err());
+    }
 }
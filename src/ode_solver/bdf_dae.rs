@@ -0,0 +1,530 @@
+use nalgebra::ComplexField;
+
+use crate::{
+    error::{DiffsolError, OdeSolverError},
+    ode_solver_error,
+    op::bdf_residual::BdfResidualCallable,
+    scalar::scale,
+    BdfState, Convergence, DaeProblem, DefaultDenseMatrix, DenseMatrix, IndexType, JacobianUpdate,
+    NonLinearSolver, OdeEquationsResidual, OdeSolverState, OdeSolverStopReason, Op, Scalar,
+    StateRef, Vector, VectorRef, VectorView, VectorViewMut,
+};
+
+use num_traits::{One, Pow, Zero};
+
+use super::bdf::BdfStatistics;
+use super::jacobian_update::SolverState;
+use super::method::{check_tstop, TstopCheck};
+
+/// A variable order, variable step size BDF solver for fully implicit DAEs in residual form `F(t, y, y') = 0` (see [OdeEquationsResidual]).
+///
+/// The method is the same as the [crate::Bdf] solver (a quasi-constant step size NDF method), with the derivative at the new time point approximated by
+/// `y' = (y - y_0 + psi) / c`, where `y_0` and `psi` are given by the difference array and `c = h / ((1 - kappa_k) gamma_k)`. Each step solves
+/// `F(t, y, (y - y_0 + psi) / c) = 0` for `y` using Newton iterations with the iteration matrix `dF/dy + 1/c dF/dy'`.
+///
+/// The problem is given by a [DaeProblem], which does not have root or output functions, so unlike the other solvers this solver does not
+/// implement [crate::OdeSolverMethod], but provides the same [Self::step], [Self::interpolate] and [Self::set_stop_time] methods.
+pub struct BdfDae<
+    'a,
+    Eqn: OdeEquationsResidual,
+    Nls: NonLinearSolver<Eqn::M>,
+    M: DenseMatrix<T = Eqn::T, V = Eqn::V> = <<Eqn as Op>::V as DefaultDenseMatrix>::M,
+> where
+    Eqn::V: DefaultDenseMatrix,
+{
+    nonlinear_solver: Nls,
+    convergence: Convergence<'a, Eqn::V>,
+    problem: &'a DaeProblem<Eqn>,
+    op: BdfResidualCallable<'a, Eqn>,
+    n_equal_steps: usize,
+    y_delta: Eqn::V,
+    y_predict: Eqn::V,
+    t_predict: Eqn::T,
+    diff_tmp: M,
+    u: M,
+    alpha: Vec<Eqn::T>,
+    gamma: Vec<Eqn::T>,
+    error_const2: Vec<Eqn::T>,
+    statistics: BdfStatistics,
+    state: BdfState<Eqn::V, M>,
+    tstop: Option<Eqn::T>,
+    jacobian_update: JacobianUpdate<Eqn::T>,
+}
+
+impl<'a, M, Eqn, Nls> BdfDae<'a, Eqn, Nls, M>
+where
+    Eqn: OdeEquationsResidual,
+    Eqn::V: DefaultDenseMatrix,
+    M: DenseMatrix<T = Eqn::T, V = Eqn::V>,
+    for<'b> &'b Eqn::V: VectorRef<Eqn::V>,
+    Nls: NonLinearSolver<Eqn::M>,
+{
+    const NEWTON_MAXITER: IndexType = 4;
+    const MIN_FACTOR: f64 = 0.5;
+    const MAX_FACTOR: f64 = 2.1;
+    const MAX_THRESHOLD: f64 = 2.0;
+    const MIN_THRESHOLD: f64 = 0.9;
+    const MIN_TIMESTEP: f64 = 1e-32;
+
+    pub fn new(
+        problem: &'a DaeProblem<Eqn>,
+        mut state: BdfState<Eqn::V, M>,
+        mut nonlinear_solver: Nls,
+    ) -> Result<Self, DiffsolError> {
+        // kappa values for difference orders, taken from Table 1 of [1] (see [crate::Bdf])
+        let kappa = [
+            Eqn::T::from(0.0),
+            Eqn::T::from(-0.1850),
+            Eqn::T::from(-1.0) / Eqn::T::from(9.0),
+            Eqn::T::from(-0.0823),
+            Eqn::T::from(-0.0415),
+            Eqn::T::from(0.0),
+        ];
+        let mut alpha = vec![Eqn::T::zero()];
+        let mut gamma = vec![Eqn::T::zero()];
+        let mut error_const2 = vec![Eqn::T::one()];
+
+        let max_order: usize = BdfState::<Eqn::V, M>::MAX_ORDER;
+
+        #[allow(clippy::needless_range_loop)]
+        for i in 1..=max_order {
+            let i_t = Eqn::T::from(i as f64);
+            let one_over_i = Eqn::T::one() / i_t;
+            let one_over_i_plus_one = Eqn::T::one() / (i_t + Eqn::T::one());
+            gamma.push(gamma[i - 1] + one_over_i);
+            alpha.push(Eqn::T::one() / ((Eqn::T::one() - kappa[i]) * gamma[i]));
+            error_const2.push((kappa[i] * gamma[i] + one_over_i_plus_one).powi(2));
+        }
+
+        let nstates = problem.eqn.nstates();
+        if state.y.len() != nstates || state.diff.nrows() != nstates {
+            return Err(ode_solver_error!(StateProblemMismatch));
+        }
+        if !state.diff_initialised {
+            state.initialise_diff_to_first_order();
+        }
+
+        let mut convergence = Convergence::new(problem.rtol, &problem.atol);
+        convergence.set_max_iter(Self::NEWTON_MAXITER);
+
+        let op = BdfResidualCallable::new(&problem.eqn);
+        op.set_c(state.h, alpha[state.order]);
+        nonlinear_solver.set_problem(&op);
+
+        // (re)allocate internal state
+        let diff_tmp = M::zeros(nstates, state.diff.ncols());
+        let y_delta = <Eqn::V as Vector>::zeros(nstates);
+        let y_predict = <Eqn::V as Vector>::zeros(nstates);
+
+        // init U matrix
+        let u = BdfState::<Eqn::V, M>::compute_r(state.order, Eqn::T::one());
+
+        let mut ret = Self {
+            convergence,
+            op,
+            problem,
+            nonlinear_solver,
+            n_equal_steps: 0,
+            diff_tmp,
+            y_delta,
+            y_predict,
+            t_predict: Eqn::T::zero(),
+            gamma,
+            alpha,
+            error_const2,
+            u,
+            statistics: BdfStatistics::default(),
+            state,
+            tstop: None,
+            jacobian_update: JacobianUpdate::default(),
+        };
+
+        // setup linear solver for first step
+        let c = ret.state.h * ret.alpha[ret.state.order];
+        ret._jacobian_updates(c, SolverState::Checkpoint);
+        Ok(ret)
+    }
+
+    pub fn problem(&self) -> &'a DaeProblem<Eqn> {
+        self.problem
+    }
+
+    pub fn state(&self) -> StateRef<Eqn::V> {
+        self.state.as_ref()
+    }
+
+    pub fn into_state(self) -> BdfState<Eqn::V, M> {
+        self.state
+    }
+
+    pub fn order(&self) -> usize {
+        self.state.order
+    }
+
+    pub fn get_statistics(&self) -> &BdfStatistics {
+        &self.statistics
+    }
+
+    fn _jacobian_updates(&mut self, c: Eqn::T, state: SolverState) {
+        // the iteration matrix depends on the predicted derivative, so it is always evaluated at the prediction for the next step
+        let update = if self.jacobian_update.check_rhs_jacobian_update(c, &state) {
+            self.jacobian_update.update_rhs_jacobian();
+            true
+        } else {
+            self.jacobian_update.check_jacobian_update(c, &state)
+        };
+        if update {
+            self._predict_forward();
+            self.nonlinear_solver
+                .reset_jacobian(&self.op, &self.y_predict, self.t_predict);
+            self.jacobian_update.update_jacobian(c);
+        }
+    }
+
+    fn _update_step_size(&mut self, factor: Eqn::T) -> Result<Eqn::T, DiffsolError> {
+        let new_h = factor * self.state.h;
+        self.n_equal_steps = 0;
+
+        // update D using equations in section 3.2 of [1]
+        let order = self.state.order;
+        let r = BdfState::<Eqn::V, M>::compute_r(order, factor);
+        let ru = r.mat_mul(&self.u);
+        BdfState::<Eqn::V, M>::update_diff_for_step_size(
+            &ru,
+            &mut self.state.diff,
+            &mut self.diff_tmp,
+            order,
+        );
+
+        self.op.set_c(new_h, self.alpha[order]);
+
+        self.state.h = new_h;
+
+        // if step size too small, then fail
+        if self.state.h.abs() < Eqn::T::from(Self::MIN_TIMESTEP) {
+            return Err(DiffsolError::from(OdeSolverError::StepSizeTooSmall {
+                time: self.state.t.into(),
+            }));
+        }
+        Ok(new_h)
+    }
+
+    fn _update_diff(order: usize, d: &Eqn::V, diff: &mut M) {
+        // see [crate::Bdf]
+        let d_minus_order_plus_one = d - diff.column(order + 1);
+        diff.column_mut(order + 2)
+            .copy_from(&d_minus_order_plus_one);
+        diff.column_mut(order + 1).copy_from(d);
+        for i in (0..=order).rev() {
+            diff.column_axpy(Eqn::T::one(), i + 1, Eqn::T::one(), i);
+        }
+    }
+
+    fn _predict_forward(&mut self) {
+        let state = &self.state;
+        BdfState::<Eqn::V, M>::predict_using_diff(&mut self.y_predict, &state.diff, state.order);
+
+        // update psi and c (h, D, y0 has changed)
+        self.op.set_psi_and_y0(
+            &state.diff,
+            self.gamma.as_slice(),
+            self.alpha.as_slice(),
+            state.order,
+            &self.y_predict,
+        );
+
+        // update time
+        self.t_predict = state.t + state.h;
+    }
+
+    fn handle_tstop(
+        &mut self,
+        tstop: Eqn::T,
+    ) -> Result<Option<OdeSolverStopReason<Eqn::T>>, DiffsolError> {
+        let check = check_tstop(self.state.t, self.state.h, tstop);
+        if check.is_err() {
+            self.tstop = None;
+        }
+        match check? {
+            TstopCheck::Reached => {
+                self.tstop = None;
+                return Ok(Some(OdeSolverStopReason::TstopReached));
+            }
+            TstopCheck::ScaleStep(factor) => {
+                // update step size ignoring the possible "step size too small" error
+                _ = self._update_step_size(factor);
+                let c = self.state.h * self.alpha[self.state.order];
+                self._jacobian_updates(c, SolverState::StepSuccess);
+            }
+            TstopCheck::NotReached => {}
+        }
+        Ok(None)
+    }
+
+    fn error_control(&self) -> Eqn::T {
+        let atol = &self.problem.atol;
+        let rtol = self.problem.rtol;
+        self.y_delta.squared_norm(&self.state.y, atol, rtol)
+            * self.error_const2[self.state.order - 1]
+    }
+
+    fn predict_error_control(&self, order: usize) -> Eqn::T {
+        let atol = &self.problem.atol;
+        let rtol = self.problem.rtol;
+        self.state
+            .diff
+            .column(order + 1)
+            .squared_norm(&self.state.y, atol, rtol)
+            * self.error_const2[order]
+    }
+
+    /// Interpolate the solution at a given time, which must be between the current time and the time of the last step.
+    pub fn interpolate(&self, t: Eqn::T) -> Result<Eqn::V, DiffsolError> {
+        let state = &self.state;
+        // check that t is before/after the current time depending on the direction
+        let is_forward = state.h > Eqn::T::zero();
+        if (is_forward && t > state.t) || (!is_forward && t < state.t) {
+            return Err(ode_solver_error!(InterpolationTimeAfterCurrentTime));
+        }
+        Ok(BdfState::<Eqn::V, M>::interpolate_from_diff(
+            t,
+            &state.diff,
+            state.t,
+            state.h,
+            state.order,
+        ))
+    }
+
+    /// Set a stop time for the solver, the solver will adjust its step size so that it stops exactly at this time.
+    pub fn set_stop_time(&mut self, tstop: Eqn::T) -> Result<(), DiffsolError> {
+        self.tstop = Some(tstop);
+        if let Some(OdeSolverStopReason::TstopReached) = self.handle_tstop(tstop)? {
+            let error = OdeSolverError::StopTimeBeforeCurrentTime {
+                stop_time: tstop.into(),
+                state_time: self.state.t.into(),
+            };
+            self.tstop = None;
+            return Err(DiffsolError::from(error));
+        }
+        Ok(())
+    }
+
+    /// Step the solution forward by one internal time step, chosen to meet the error tolerances.
+    pub fn step(&mut self) -> Result<OdeSolverStopReason<Eqn::T>, DiffsolError> {
+        let mut safety: Eqn::T;
+        let mut error_norm: Eqn::T;
+        let mut convergence_fail = false;
+
+        self._predict_forward();
+
+        // loop until step is accepted
+        loop {
+            let order = self.state.order;
+            self.y_delta.copy_from(&self.y_predict);
+
+            // solve BDF equation using y0 as starting point
+            let solve_result = self.nonlinear_solver.solve_in_place_with_jacobian(
+                &self.op,
+                &mut self.y_delta,
+                self.t_predict,
+                &self.y_predict,
+                &mut self.convergence,
+            );
+            // update statistics
+            self.statistics.number_of_nonlinear_solver_iterations += self.convergence.niter();
+
+            // handle case where the nonlinear solve failed
+            if solve_result.is_err() {
+                self.statistics.number_of_nonlinear_solver_fails += 1;
+                if convergence_fail {
+                    // newton iteration did not converge, but jacobian has already been
+                    // evaluated so reduce step size by 0.3 (as per [1]) and try again
+                    let new_h = self._update_step_size(Eqn::T::from(0.3))?;
+                    self._jacobian_updates(
+                        new_h * self.alpha[order],
+                        SolverState::SecondConvergenceFail,
+                    );
+
+                    // new prediction
+                    self._predict_forward();
+                } else {
+                    // newton iteration did not converge, so update jacobian and try again
+                    self._jacobian_updates(
+                        self.state.h * self.alpha[order],
+                        SolverState::FirstConvergenceFail,
+                    );
+                    convergence_fail = true;
+                    // same prediction as last time
+                }
+                continue;
+            }
+
+            // test error is within tolerance, error = C_k * D^{k+1} y_{n+1}
+            self.y_delta -= &self.y_predict;
+            error_norm = self.error_control();
+
+            // need to caulate safety even if step is accepted
+            let maxiter = self.convergence.max_iter() as f64;
+            let niter = self.convergence.niter() as f64;
+            safety = Eqn::T::from(0.9 * (2.0 * maxiter + 1.0) / (2.0 * maxiter + niter));
+
+            // do the error test
+            if error_norm <= Eqn::T::from(1.0) {
+                // step is accepted
+                break;
+            } else {
+                // step is rejected
+                // calculate optimal step size factor as per eq 2.46 of [2]
+                // and reduce step size and try again
+                let mut factor = safety * error_norm.pow(Eqn::T::from(-0.5 / (order as f64 + 1.0)));
+                if factor < Eqn::T::from(Self::MIN_FACTOR) {
+                    factor = Eqn::T::from(Self::MIN_FACTOR);
+                }
+                let new_h = self._update_step_size(factor)?;
+                self._jacobian_updates(new_h * self.alpha[order], SolverState::ErrorTestFail);
+
+                // new prediction
+                self._predict_forward();
+
+                // update statistics
+                self.statistics.number_of_error_test_failures += 1;
+            }
+        }
+
+        // take the accepted step
+        Self::_update_diff(self.state.order, &self.y_delta, &mut self.state.diff);
+        {
+            let state = &mut self.state;
+            state.y.copy_from(&self.y_predict);
+            state.t = self.t_predict;
+            state.dy.copy_from_view(&state.diff.column(1));
+            state.dy *= scale(Eqn::T::one() / state.h);
+        }
+
+        // update statistics
+        self.statistics.number_of_linear_solver_setups = self.op.number_of_jac_evals();
+        self.statistics.number_of_steps += 1;
+        self.jacobian_update.step();
+
+        // a change in order is only done after running at order k for k + 1 steps
+        // (see page 83 of [2])
+        self.n_equal_steps += 1;
+
+        if self.n_equal_steps > self.state.order {
+            let order = self.state.order;
+            let error_m_norm = if order > 1 {
+                self.predict_error_control(order - 1)
+            } else {
+                Eqn::T::INFINITY
+            };
+            let error_p_norm = if order < BdfState::<Eqn::V, M>::MAX_ORDER {
+                self.predict_error_control(order + 1)
+            } else {
+                Eqn::T::INFINITY
+            };
+            let factors = [error_m_norm, error_norm, error_p_norm]
+                .into_iter()
+                .enumerate()
+                .map(|(i, error_norm)| {
+                    error_norm.pow(Eqn::T::from(-0.5 / (i as f64 + order as f64)))
+                })
+                .collect::<Vec<_>>();
+
+            // pick the order with the maximum step size factor
+            let max_index = factors
+                .iter()
+                .enumerate()
+                .max_by(|(_, a), (_, b)| a.partial_cmp(b).unwrap())
+                .unwrap()
+                .0;
+
+            // update order and update the U matrix
+            let new_order = match max_index {
+                0 => order - 1,
+                1 => order,
+                2 => order + 1,
+                _ => unreachable!(),
+            };
+            self.state.order = new_order;
+            if max_index != 1 {
+                self.u = BdfState::<Eqn::V, M>::compute_r(new_order, Eqn::T::one());
+            }
+
+            let mut factor = safety * factors[max_index];
+            if factor > Eqn::T::from(Self::MAX_FACTOR) {
+                factor = Eqn::T::from(Self::MAX_FACTOR);
+            }
+            if factor < Eqn::T::from(Self::MIN_FACTOR) {
+                factor = Eqn::T::from(Self::MIN_FACTOR);
+            }
+            if factor >= Eqn::T::from(Self::MAX_THRESHOLD)
+                || factor < Eqn::T::from(Self::MIN_THRESHOLD)
+                || max_index == 0
+                || max_index == 2
+            {
+                let new_h = self._update_step_size(factor)?;
+                self._jacobian_updates(new_h * self.alpha[new_order], SolverState::StepSuccess);
+            }
+        }
+
+        if let Some(tstop) = self.tstop {
+            if let Some(reason) = self.handle_tstop(tstop)? {
+                return Ok(reason);
+            }
+        }
+
+        // just a normal step, no tstop reached
+        Ok(OdeSolverStopReason::InternalTimestep)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        ode_solver::test_models::exponential_decay_residual::exponential_decay_residual_problem,
+        NalgebraLU, OdeSolverStopReason, Vector,
+    };
+
+    type M = nalgebra::DMatrix<f64>;
+    type LS = NalgebraLU<f64>;
+
+    #[test]
+    fn bdf_dae_consistent_initialisation() {
+        let (problem, _soln) = exponential_decay_residual_problem::<M>();
+        let state = problem.bdf_dae_state::<LS>().unwrap();
+        // algebraic variable y1 = y0, and y0' = -a y0
+        let y_expect = nalgebra::DVector::from_vec(vec![1.0, 1.0]);
+        state.y.assert_eq_st(&y_expect, 1e-6);
+        assert!((state.dy[0] + 0.1).abs() < 1e-6);
+    }
+
+    #[test]
+    fn bdf_dae_exponential_decay_residual() {
+        let (problem, soln) = exponential_decay_residual_problem::<M>();
+        let mut solver = problem.bdf_dae::<LS>().unwrap();
+        for point in soln.solution_points.iter().skip(1) {
+            solver.set_stop_time(point.t).unwrap();
+            loop {
+                match solver.step().unwrap() {
+                    OdeSolverStopReason::InternalTimestep => continue,
+                    OdeSolverStopReason::TstopReached => break,
+                    OdeSolverStopReason::RootFound(_) => unreachable!(),
+                }
+            }
+            let state = solver.state();
+            assert!((state.t - point.t).abs() < 1e-10);
+            state.y.assert_eq_st(&point.state, 1e-4);
+        }
+        let stats = solver.get_statistics();
+        assert!(stats.number_of_steps > 0);
+        assert!(solver.order() > 1);
+
+        // interpolate within the last step
+        let t = solver.state().t - 1e-3;
+        let y = solver.interpolate(t).unwrap();
+        let y_expect = (-0.1 * t).exp();
+        assert!((y[0] - y_expect).abs() < 1e-4);
+        assert!((y[1] - y_expect).abs() < 1e-4);
+        assert!(solver.interpolate(solver.state().t + 1.0).is_err());
+    }
+}
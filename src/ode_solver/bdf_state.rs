@@ -2,9 +2,10 @@ use crate::{
     error::{DiffsolError, OdeSolverError},
     ode_solver_error,
     scalar::IndexType,
-    scale, AugmentedOdeEquations, DefaultDenseMatrix, DenseMatrix, OdeEquations, OdeSolverProblem,
-    OdeSolverState, Op, StateRef, StateRefMut, Vector, VectorViewMut,
+    scale, AugmentedOdeEquations, DefaultDenseMatrix, DenseMatrix, MatrixViewMut, OdeEquations,
+    OdeSolverProblem, OdeSolverState, Op, StateRef, StateRefMut, Vector, VectorView, VectorViewMut,
 };
+use num_traits::{One, Zero};
 use std::ops::MulAssign;
 
 use super::state::StateCommon;
@@ -83,6 +84,88 @@ where
         }
         self.sgdiff_initialised = true;
     }
+
+    /// grow the difference matrices so that they have (at least) `ncols` columns, keeping the existing columns.
+    /// This is used by solvers that support higher orders than the bdf solver (i.e. `MAX_ORDER`).
+    pub(crate) fn ensure_diff_columns(&mut self, ncols: usize) {
+        fn grow<M: DenseMatrix>(diff: &mut M, ncols: usize) {
+            if diff.ncols() >= ncols {
+                return;
+            }
+            let mut new_diff = M::zeros(diff.nrows(), ncols);
+            for j in 0..diff.ncols() {
+                let col = diff.column(j).into_owned();
+                new_diff.column_mut(j).copy_from(&col);
+            }
+            *diff = new_diff;
+        }
+        grow(&mut self.diff, ncols);
+        grow(&mut self.gdiff, ncols);
+        for sdiff in self.sdiff.iter_mut() {
+            grow(sdiff, ncols);
+        }
+        for sgdiff in self.sgdiff.iter_mut() {
+            grow(sgdiff, ncols);
+        }
+    }
+
+    pub(crate) fn compute_r(order: usize, factor: V::T) -> M {
+        //computes the R matrix with entries
+        //given by the first equation on page 8 of [1]
+        //
+        //This is used to update the differences matrix when step size h is varied
+        //according to factor = h_{n+1} / h_n
+        //
+        //Note that the U matrix also defined in the same section can be also be
+        //found using factor = 1, which corresponds to R with a constant step size
+        let mut r = M::zeros(order + 1, order + 1);
+
+        // r[0, 0:order] = 1
+        for j in 0..=order {
+            r[(0, j)] = M::T::one();
+        }
+        // r[i, j] = r[i, j-1] * (j - 1 - factor * i) / j
+        for i in 1..=order {
+            for j in 1..=order {
+                let i_t = M::T::from(i as f64);
+                let j_t = M::T::from(j as f64);
+                r[(i, j)] = r[(i - 1, j)] * (i_t - M::T::one() - factor * j_t) / i_t;
+            }
+        }
+        r
+    }
+
+    pub(crate) fn update_diff_for_step_size(ru: &M, diff: &mut M, diff_tmp: &mut M, order: usize) {
+        // D[0:order+1] = R * U * D[0:order+1]
+        {
+            let d_zero_order = diff.columns(0, order + 1);
+            let mut d_zero_order_tmp = diff_tmp.columns_mut(0, order + 1);
+            d_zero_order_tmp.gemm_vo(V::T::one(), &d_zero_order, ru, V::T::zero());
+            // diff_sub = diff * RU
+        }
+        std::mem::swap(diff, diff_tmp);
+    }
+
+    // predict forward to new step (eq 2 in [1])
+    pub(crate) fn predict_using_diff(y_predict: &mut V, diff: &M, order: usize) {
+        y_predict.fill(V::T::zero());
+        for i in 0..=order {
+            y_predict.add_assign(diff.column(i));
+        }
+    }
+
+    //interpolate solution at time values t* where t-h < t* < t
+    //definition of the interpolating polynomial can be found on page 7 of [1]
+    pub(crate) fn interpolate_from_diff(t: V::T, diff: &M, t1: V::T, h: V::T, order: usize) -> V {
+        let mut time_factor = V::T::from(1.0);
+        let mut order_summation = diff.column(0).into_owned();
+        for i in 0..order {
+            let i_t = V::T::from(i as f64);
+            time_factor *= (t - (t1 - h * i_t)) / (h * (V::T::one() + i_t));
+            order_summation += diff.column(i + 1) * scale(time_factor);
+        }
+        order_summation
+    }
 }
 
 impl<V, M> OdeSolverState<V> for BdfState<V, M>
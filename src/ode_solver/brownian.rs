@@ -0,0 +1,255 @@
+use crate::Vector;
+use nalgebra::ComplexField;
+use num_traits::{One, Zero};
+
+/// A small, deterministic pseudo-random number generator (xoshiro256**, seeded using splitmix64),
+/// so that the noise of a stochastic solve is reproducible given a seed.
+#[derive(Clone)]
+struct Xoshiro256 {
+    s: [u64; 4],
+    spare: Option<f64>,
+}
+
+impl Xoshiro256 {
+    fn new(seed: u64) -> Self {
+        let mut x = seed;
+        let mut splitmix64 = || {
+            x = x.wrapping_add(0x9E3779B97F4A7C15);
+            let mut z = x;
+            z = (z ^ (z >> 30)).wrapping_mul(0xBF58476D1CE4E5B9);
+            z = (z ^ (z >> 27)).wrapping_mul(0x94D049BB133111EB);
+            z ^ (z >> 31)
+        };
+        let s = [splitmix64(), splitmix64(), splitmix64(), splitmix64()];
+        Self { s, spare: None }
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        let s = &mut self.s;
+        let result = s[1].wrapping_mul(5).rotate_left(7).wrapping_mul(9);
+        let t = s[1] << 17;
+        s[2] ^= s[0];
+        s[3] ^= s[1];
+        s[1] ^= s[2];
+        s[0] ^= s[3];
+        s[2] ^= t;
+        s[3] = s[3].rotate_left(45);
+        result
+    }
+
+    /// a uniform sample in the open interval (0, 1)
+    fn uniform(&mut self) -> f64 {
+        ((self.next_u64() >> 11) as f64 + 0.5) / (1u64 << 53) as f64
+    }
+
+    /// a standard normal sample, using the Box-Muller transform
+    fn normal(&mut self) -> f64 {
+        if let Some(z) = self.spare.take() {
+            return z;
+        }
+        let r = (-2.0 * self.uniform().ln()).sqrt();
+        let theta = 2.0 * std::f64::consts::PI * self.uniform();
+        self.spare = Some(r * theta.sin());
+        r * theta.cos()
+    }
+}
+
+/// A realisation of a vector of independent Wiener processes `W(t)`, starting from `W(t0) = 0`.
+///
+/// The path is sampled lazily at the times requested by a solver, and every sample is stored, so that the path is fixed once it has been sampled.
+/// Times after the last sample are sampled forwards in time, and times between two samples are sampled from the Brownian bridge between them.
+/// This means that a rejected step can be retried with a smaller step size on the same path, and that other solvers can be run on
+/// the same path by cloning it. Given the same seed and the same sequence of requested times, the path is always the same.
+///
+/// As well as `W(t)`, the path stores the time integral `Z(t) = ∫ W(s) ds` from `t0` to `t`, which is needed by higher order methods (see [Self::increment]).
+#[derive(Clone)]
+pub struct BrownianPath<V: Vector> {
+    rng: Xoshiro256,
+    ts: Vec<V::T>,
+    ws: Vec<V>,
+    zs: Vec<V>,
+}
+
+impl<V: Vector> BrownianPath<V> {
+    /// Create a new path of `nnoise` independent Wiener processes starting at time `t0`, using the random number generator seeded with `seed`.
+    pub fn new(nnoise: usize, t0: V::T, seed: u64) -> Self {
+        Self {
+            rng: Xoshiro256::new(seed),
+            ts: vec![t0],
+            ws: vec![V::zeros(nnoise)],
+            zs: vec![V::zeros(nnoise)],
+        }
+    }
+
+    /// the number of independent Wiener processes
+    pub fn nnoise(&self) -> usize {
+        self.ws[0].len()
+    }
+
+    /// the times at which the path has been sampled, in increasing order
+    pub fn times(&self) -> &[V::T] {
+        self.ts.as_slice()
+    }
+
+    /// Sample the path at time `t`, returning `W(t)` in `w`.
+    pub fn sample(&mut self, t: V::T, w: &mut V) {
+        let i = self.point(t);
+        w.copy_from(&self.ws[i]);
+    }
+
+    /// Sample the increments of the path over the interval `[t0, t1]`, returning the increment `dw = W(t1) - W(t0)` and
+    /// the iterated integral `dz = ∫ (W(s) - W(t0)) ds` over the interval.
+    pub fn increment(&mut self, t0: V::T, t1: V::T, dw: &mut V, dz: &mut V) {
+        let i0 = self.point(t0);
+        let i1 = self.point(t1);
+        dw.copy_from(&self.ws[i1]);
+        dw.axpy(-V::T::one(), &self.ws[i0], V::T::one());
+        dz.copy_from(&self.zs[i1]);
+        dz.axpy(-V::T::one(), &self.zs[i0], V::T::one());
+        dz.axpy(-(t1 - t0), &self.ws[i0], V::T::one());
+    }
+
+    // return the index of the sample at time t, sampling the path at t if needed
+    fn point(&mut self, t: V::T) -> usize {
+        assert!(
+            t >= self.ts[0],
+            "Cannot sample a Brownian path before its initial time"
+        );
+        let i = self.ts.partition_point(|&ti| ti < t);
+        if i < self.ts.len() && self.ts[i] == t {
+            return i;
+        }
+        let n = self.nnoise();
+        let mut w = V::zeros(n);
+        let mut z = V::zeros(n);
+        if i == self.ts.len() {
+            self.sample_forward(t, &mut w, &mut z);
+        } else {
+            self.sample_bridge(i, t, &mut w, &mut z);
+        }
+        self.ts.insert(i, t);
+        self.ws.insert(i, w);
+        self.zs.insert(i, z);
+        i
+    }
+
+    // sample (W(t), Z(t)) for t after the last sample
+    fn sample_forward(&mut self, t: V::T, w: &mut V, z: &mut V) {
+        let last = self.ts.len() - 1;
+        let s = t - self.ts[last];
+        let sqrt_s = s.sqrt();
+        let half = V::T::from(0.5);
+        let sqrt_3 = V::T::from(3.0f64.sqrt());
+        let (wa, za) = (&self.ws[last], &self.zs[last]);
+        for j in 0..w.len() {
+            let x1 = sqrt_s * V::T::from(self.rng.normal());
+            let x2 = half * s * (x1 + sqrt_s * V::T::from(self.rng.normal()) / sqrt_3);
+            w[j] = wa[j] + x1;
+            z[j] = za[j] + s * wa[j] + x2;
+        }
+    }
+
+    // sample (W(t), Z(t)) for t between the samples at i - 1 and i, conditional on both
+    fn sample_bridge(&mut self, i: usize, t: V::T, w: &mut V, z: &mut V) {
+        let (ta, tb) = (self.ts[i - 1], self.ts[i]);
+        let h = tb - ta;
+        let s = t - ta;
+        let u = s / h;
+        let (two, three, six) = (V::T::from(2.0), V::T::from(3.0), V::T::from(6.0));
+        let (u2, u3) = (u * u, u * u * u);
+
+        // with unit interval length, the regression coefficients and conditional covariance of
+        // X = (W(t) - W(a), ∫_a^t (W - W(a))) given Y = (W(b) - W(a), ∫_a^b (W - W(a)))
+        let k11 = -two * u + three * u2;
+        let k12 = six * u - six * u2;
+        let k21 = u3 - u2;
+        let k22 = three * u2 - two * u3;
+        let c11 = u - (k11 * u + k12 * (u - u2 / two));
+        let c12 = u2 / two - (k11 * u2 / two + k12 * (u2 / two - u3 / six));
+        let c22 = u3 / three - (k21 * u2 / two + k22 * (u2 / two - u3 / six));
+        let zero = V::T::zero();
+        let l11 = if c11 > zero { c11.sqrt() } else { zero };
+        let l21 = if l11 > zero { c12 / l11 } else { zero };
+        let c22 = c22 - l21 * l21;
+        let l22 = if c22 > zero { c22.sqrt() } else { zero };
+        let sqrt_h = h.sqrt();
+        let h32 = h * sqrt_h;
+
+        let (wa, za) = (&self.ws[i - 1], &self.zs[i - 1]);
+        let (wb, zb) = (&self.ws[i], &self.zs[i]);
+        for j in 0..w.len() {
+            let y1 = wb[j] - wa[j];
+            let y2 = zb[j] - za[j] - h * wa[j];
+            let n1 = V::T::from(self.rng.normal());
+            let n2 = V::T::from(self.rng.normal());
+            let x1 = k11 * y1 + k12 * y2 / h + sqrt_h * l11 * n1;
+            let x2 = h * k21 * y1 + k22 * y2 + h32 * (l21 * n1 + l22 * n2);
+            w[j] = wa[j] + x1;
+            z[j] = za[j] + s * wa[j] + x2;
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{BrownianPath, Xoshiro256};
+
+    type V = nalgebra::DVector<f64>;
+
+    #[test]
+    fn test_normal_samples() {
+        let mut rng = Xoshiro256::new(42);
+        let n = 100000;
+        let samples: Vec<f64> = (0..n).map(|_| rng.normal()).collect();
+        let mean = samples.iter().sum::<f64>() / n as f64;
+        let var = samples.iter().map(|x| (x - mean) * (x - mean)).sum::<f64>() / n as f64;
+        assert!(mean.abs() < 0.015, "mean = {}", mean);
+        assert!((var - 1.0).abs() < 0.02, "var = {}", var);
+    }
+
+    #[test]
+    fn test_brownian_path_is_reproducible() {
+        let mut path1 = BrownianPath::<V>::new(2, 0.0, 1234);
+        let mut path2 = BrownianPath::<V>::new(2, 0.0, 1234);
+        let mut path3 = BrownianPath::<V>::new(2, 0.0, 4321);
+        let (mut w1, mut w2, mut w3) = (V::zeros(2), V::zeros(2), V::zeros(2));
+        for t in [0.5, 1.0, 0.25, 2.0] {
+            path1.sample(t, &mut w1);
+            path2.sample(t, &mut w2);
+            path3.sample(t, &mut w3);
+            assert_eq!(w1, w2);
+            assert_ne!(w1, w3);
+        }
+
+        // the path is fixed once it has been sampled
+        path1.sample(0.5, &mut w2);
+        path1.sample(0.25, &mut w1);
+        path1.sample(0.5, &mut w3);
+        assert_eq!(w2, w3);
+        assert_eq!(path1.times(), &[0.0, 0.25, 0.5, 1.0, 2.0]);
+    }
+
+    #[test]
+    fn test_brownian_path_increments() {
+        // the increments over many paths should have the right mean and (co)variances, including
+        // increments that are sampled from the Brownian bridge
+        let npaths = 20000;
+        let h: f64 = 0.3;
+        let (mut sum_w2, mut sum_z2, mut sum_wz, mut sum_w) = (0.0, 0.0, 0.0, 0.0);
+        let (mut dw, mut dz) = (V::zeros(1), V::zeros(1));
+        for seed in 0..npaths {
+            let mut path = BrownianPath::<V>::new(1, 0.0, seed);
+            path.increment(0.0, 1.0, &mut dw, &mut dz);
+            path.increment(0.2, 0.2 + h, &mut dw, &mut dz);
+            sum_w += dw[0];
+            sum_w2 += dw[0] * dw[0];
+            sum_z2 += dz[0] * dz[0];
+            sum_wz += dw[0] * dz[0];
+        }
+        let n = npaths as f64;
+        assert!((sum_w / n).abs() < 0.02);
+        assert!((sum_w2 / n - h).abs() < 0.05 * h);
+        assert!((sum_z2 / n - h.powi(3) / 3.0).abs() < 0.05 * h.powi(3));
+        assert!((sum_wz / n - h.powi(2) / 2.0).abs() < 0.04 * h.powi(2));
+    }
+}
@@ -1,12 +1,20 @@
 use nalgebra::DMatrix;
+use std::sync::Arc;
 
 use crate::{
     error::{DiffsolError, OdeSolverError},
     ode_solver_error,
-    op::{linear_closure_with_adjoint::LinearClosureWithAdjoint, BuilderOp},
+    op::{
+        closure_delay::ClosureDelay, closure_diffusion::ClosureDiffusion,
+        closure_imex::ClosureImex, closure_residual::ClosureResidual,
+        closure_second_order::ClosureSecondOrder,
+        linear_closure_with_adjoint::LinearClosureWithAdjoint, BuilderOp,
+    },
     Closure, ClosureNoJac, ClosureWithAdjoint, ClosureWithSens, ConstantClosure,
-    ConstantClosureWithAdjoint, ConstantClosureWithSens, ConstantOp, LinearClosure, LinearOp,
-    Matrix, NonLinearOp, OdeEquations, OdeSolverProblem, Op, ParameterisedOp, UnitCallable, Vector,
+    ConstantClosureWithAdjoint, ConstantClosureWithSens, ConstantOp, Constraint, DaeProblem,
+    ErrorNorm, LinearClosure, LinearOp, ManifoldProjection, Matrix, NoiseType, NonLinearOp,
+    OdeEquations, OdeSolverProblem, Op, ParameterisedOp, SdeProblem, SolverOptions, UnitCallable,
+    Vector, VectorIndex,
 };
 
 use super::equations::OdeSolverEquations;
@@ -33,6 +41,8 @@ pub struct OdeBuilder<
     p: Vec<M::T>,
     use_coloring: bool,
     integrate_out: bool,
+    options: SolverOptions<M::T>,
+    projection: Option<ManifoldProjection<'static, M>>,
     rhs: Option<Rhs>,
     init: Option<Init>,
     mass: Option<Mass>,
@@ -97,6 +107,7 @@ where
     /// - p = []
     /// - use_coloring = false
     /// - constant_mass = false
+    /// - solver_options = SolverOptions::default()
     pub fn new() -> Self {
         let default_atol = vec![M::T::from(1e-6)];
         let default_rtol = 1e-6.into();
@@ -113,6 +124,8 @@ where
             p: vec![],
             use_coloring: false,
             integrate_out: false,
+            options: SolverOptions::default(),
+            projection: None,
             out_rtol: Some(default_rtol),
             out_atol: Some(default_atol.clone()),
             param_rtol: Some(default_rtol),
@@ -122,6 +135,41 @@ where
         }
     }
 
+    /// Set the right-hand side of the ODE, without a Jacobian. The resulting problem can only be solved using explicit solvers (e.g. [OdeSolverProblem::dopri5]).
+    ///
+    /// # Arguments
+    ///
+    /// - `rhs`: Function of type Fn(x: &V, p: &V, t: S, y: &mut V) that computes the right-hand side of the ODE.
+    pub fn rhs<F>(self, rhs: F) -> OdeBuilder<M, ClosureNoJac<M, F>, Init, Mass, Root, Out>
+    where
+        F: Fn(&M::V, &M::V, M::T, &mut M::V),
+    {
+        let nstates = 0;
+        OdeBuilder::<M, ClosureNoJac<M, F>, Init, Mass, Root, Out> {
+            rhs: Some(ClosureNoJac::new(rhs, nstates, nstates, nstates)),
+            init: self.init,
+            mass: self.mass,
+            root: self.root,
+            out: self.out,
+
+            t0: self.t0,
+            h0: self.h0,
+            rtol: self.rtol,
+            atol: self.atol,
+            sens_atol: self.sens_atol,
+            sens_rtol: self.sens_rtol,
+            out_rtol: self.out_rtol,
+            out_atol: self.out_atol,
+            param_rtol: self.param_rtol,
+            param_atol: self.param_atol,
+            p: self.p,
+            use_coloring: self.use_coloring,
+            integrate_out: self.integrate_out,
+            options: self.options,
+            projection: self.projection,
+        }
+    }
+
     /// Set the right-hand side of the ODE.
     ///
     /// # Arguments
@@ -158,6 +206,165 @@ where
             p: self.p,
             use_coloring: self.use_coloring,
             integrate_out: self.integrate_out,
+            options: self.options,
+            projection: self.projection,
+        }
+    }
+
+    /// Set the right-hand side of the ODE as the sum of a non-stiff explicit part and a stiff implicit part, i.e. `F(x) = F_E(x) + F_I(x)`.
+    /// Only the Jacobian of the implicit part is required. The resulting problem implements [crate::OdeEquationsImex] and can be solved
+    /// using IMEX solvers (e.g. [OdeSolverProblem::ark436l2sa]) or explicit solvers.
+    ///
+    /// # Arguments
+    ///
+    /// - `rhs_explicit`: Function of type Fn(x: &V, p: &V, t: S, y: &mut V) that computes the explicit part of the right-hand side of the ODE.
+    /// - `rhs_implicit`: Function of type Fn(x: &V, p: &V, t: S, y: &mut V) that computes the implicit part of the right-hand side of the ODE.
+    /// - `rhs_implicit_jac`: Function of type Fn(x: &V, p: &V, t: S, v: &V, y: &mut V) that computes the multiplication of the Jacobian of the implicit part of the right-hand side with the vector v.
+    pub fn rhs_imex<F, G, H>(
+        self,
+        rhs_explicit: F,
+        rhs_implicit: G,
+        rhs_implicit_jac: H,
+    ) -> OdeBuilder<M, ClosureImex<M, F, G, H>, Init, Mass, Root, Out>
+    where
+        F: Fn(&M::V, &M::V, M::T, &mut M::V),
+        G: Fn(&M::V, &M::V, M::T, &mut M::V),
+        H: Fn(&M::V, &M::V, M::T, &M::V, &mut M::V),
+    {
+        let nstates = 0;
+        OdeBuilder::<M, ClosureImex<M, F, G, H>, Init, Mass, Root, Out> {
+            rhs: Some(ClosureImex::new(
+                rhs_explicit,
+                rhs_implicit,
+                rhs_implicit_jac,
+                nstates,
+                nstates,
+                nstates,
+            )),
+            init: self.init,
+            mass: self.mass,
+            root: self.root,
+            out: self.out,
+
+            t0: self.t0,
+            h0: self.h0,
+            rtol: self.rtol,
+            atol: self.atol,
+            sens_atol: self.sens_atol,
+            sens_rtol: self.sens_rtol,
+            out_rtol: self.out_rtol,
+            out_atol: self.out_atol,
+            param_rtol: self.param_rtol,
+            param_atol: self.param_atol,
+            p: self.p,
+            use_coloring: self.use_coloring,
+            integrate_out: self.integrate_out,
+            options: self.options,
+            projection: self.projection,
+        }
+    }
+
+    /// Set the right-hand side of a second-order ODE `M y'' = f(t, y, y')`. The state of the problem is the positions `y` followed by the velocities `v = y'`,
+    /// so the initial state (see [Self::init]) must be `[y0, v0]` and the number of states is twice the number of positions.
+    /// If a mass matrix is given (see [Self::mass]) it acts on the full state, so it must have the block diagonal form `diag(I, M)`.
+    ///
+    /// The resulting problem implements [crate::OdeEquationsSecondOrder] and can be solved using the second-order solvers
+    /// (e.g. [OdeSolverProblem::velocity_verlet], [OdeSolverProblem::newmark]) or any of the first-order solvers.
+    ///
+    /// # Arguments
+    ///
+    /// - `force`: Function of type Fn(x: &V, p: &V, t: S, y: &mut V) that computes the force `f(t, y, v)` given the full state `x = [y, v]`. The output `y` has the length of the positions.
+    /// - `force_jac`: Function of type Fn(x: &V, p: &V, t: S, v: &V, y: &mut V) that computes the multiplication of the Jacobian of the force (with respect to the full state) with the vector v.
+    pub fn rhs_second_order<F, G>(
+        self,
+        force: F,
+        force_jac: G,
+    ) -> OdeBuilder<M, ClosureSecondOrder<M, F, G>, Init, Mass, Root, Out>
+    where
+        F: Fn(&M::V, &M::V, M::T, &mut M::V),
+        G: Fn(&M::V, &M::V, M::T, &M::V, &mut M::V),
+    {
+        let nstates = 0;
+        OdeBuilder::<M, ClosureSecondOrder<M, F, G>, Init, Mass, Root, Out> {
+            rhs: Some(ClosureSecondOrder::new(force, force_jac, nstates, nstates)),
+            init: self.init,
+            mass: self.mass,
+            root: self.root,
+            out: self.out,
+
+            t0: self.t0,
+            h0: self.h0,
+            rtol: self.rtol,
+            atol: self.atol,
+            sens_atol: self.sens_atol,
+            sens_rtol: self.sens_rtol,
+            out_rtol: self.out_rtol,
+            out_atol: self.out_atol,
+            param_rtol: self.param_rtol,
+            param_atol: self.param_atol,
+            p: self.p,
+            use_coloring: self.use_coloring,
+            integrate_out: self.integrate_out,
+            options: self.options,
+            projection: self.projection,
+        }
+    }
+
+    /// Set the right-hand side of a delay differential equation `M y' = f(t, y(t), y(t - tau_1(t, y)), ..., y(t - tau_m(t, y)))`.
+    /// The delays `tau_i` can depend on both the time and the current state, and must be non-negative. The solution before the
+    /// initial time is given by the `history` function, note that the initial state (see [Self::init]) does not need to match the history at the initial time.
+    ///
+    /// The resulting problem implements [crate::OdeEquationsDelay] and can be solved using the delay solver (e.g. [OdeSolverProblem::dde_bdf]).
+    ///
+    /// # Arguments
+    ///
+    /// - `rhs`: Function of type Fn(x: &V, xlag: &V, p: &V, t: S, y: &mut V) that computes the right-hand side of the DDE. The delayed states are
+    ///   given as a single vector `xlag` of length `ndelays * nstates`, where `xlag[i * nstates + j]` is the `j`-th state at time `t - tau_i`.
+    /// - `rhs_jac`: Function of type Fn(x: &V, xlag: &V, p: &V, t: S, v: &V, y: &mut V) that computes the multiplication of the Jacobian of the right-hand side
+    ///   with respect to the current state `x` with the vector v.
+    /// - `delays`: Function of type Fn(x: &V, p: &V, t: S, tau: &mut V) that computes the delays `tau`, of length `ndelays`.
+    /// - `ndelays`: The number of delays.
+    /// - `history`: Function of type Fn(p: &V, t: S) -> V that returns the solution at times `t` before the initial time.
+    #[allow(clippy::type_complexity)]
+    pub fn rhs_delay<F, G, D, H>(
+        self,
+        rhs: F,
+        rhs_jac: G,
+        delays: D,
+        ndelays: usize,
+        history: H,
+    ) -> OdeBuilder<M, ClosureDelay<M, F, G, D, H>, Init, Mass, Root, Out>
+    where
+        F: Fn(&M::V, &M::V, &M::V, M::T, &mut M::V),
+        G: Fn(&M::V, &M::V, &M::V, M::T, &M::V, &mut M::V),
+        D: Fn(&M::V, &M::V, M::T, &mut M::V),
+        H: Fn(&M::V, M::T) -> M::V,
+    {
+        let nstates = 0;
+        OdeBuilder::<M, ClosureDelay<M, F, G, D, H>, Init, Mass, Root, Out> {
+            rhs: Some(ClosureDelay::new(
+                rhs, rhs_jac, delays, history, ndelays, nstates, nstates, nstates,
+            )),
+            init: self.init,
+            mass: self.mass,
+            root: self.root,
+            out: self.out,
+
+            t0: self.t0,
+            h0: self.h0,
+            rtol: self.rtol,
+            atol: self.atol,
+            sens_atol: self.sens_atol,
+            sens_rtol: self.sens_rtol,
+            out_rtol: self.out_rtol,
+            out_atol: self.out_atol,
+            param_rtol: self.param_rtol,
+            param_atol: self.param_atol,
+            p: self.p,
+            use_coloring: self.use_coloring,
+            integrate_out: self.integrate_out,
+            options: self.options,
+            projection: self.projection,
         }
     }
 
@@ -201,6 +408,8 @@ where
             p: self.p,
             use_coloring: self.use_coloring,
             integrate_out: self.integrate_out,
+            options: self.options,
+            projection: self.projection,
         }
     }
 
@@ -247,6 +456,8 @@ where
             p: self.p,
             use_coloring: self.use_coloring,
             integrate_out: self.integrate_out,
+            options: self.options,
+            projection: self.projection,
         }
     }
 
@@ -279,6 +490,8 @@ where
             p: self.p,
             use_coloring: self.use_coloring,
             integrate_out: self.integrate_out,
+            options: self.options,
+            projection: self.projection,
         }
     }
 
@@ -319,6 +532,8 @@ where
             p: self.p,
             use_coloring: self.use_coloring,
             integrate_out: self.integrate_out,
+            options: self.options,
+            projection: self.projection,
         }
     }
 
@@ -363,6 +578,8 @@ where
             p: self.p,
             use_coloring: self.use_coloring,
             integrate_out: self.integrate_out,
+            options: self.options,
+            projection: self.projection,
         }
     }
 
@@ -395,6 +612,8 @@ where
             p: self.p,
             use_coloring: self.use_coloring,
             integrate_out: self.integrate_out,
+            options: self.options,
+            projection: self.projection,
         }
     }
 
@@ -442,6 +661,8 @@ where
             p: self.p,
             use_coloring: self.use_coloring,
             integrate_out: self.integrate_out,
+            options: self.options,
+            projection: self.projection,
         }
     }
 
@@ -479,6 +700,8 @@ where
             p: self.p,
             use_coloring: self.use_coloring,
             integrate_out: self.integrate_out,
+            options: self.options,
+            projection: self.projection,
         }
     }
 
@@ -512,6 +735,8 @@ where
             p: self.p,
             use_coloring: self.use_coloring,
             integrate_out: self.integrate_out,
+            options: self.options,
+            projection: self.projection,
         }
     }
 
@@ -558,6 +783,8 @@ where
             p: self.p,
             use_coloring: self.use_coloring,
             integrate_out: self.integrate_out,
+            options: self.options,
+            projection: self.projection,
         }
     }
 
@@ -634,6 +861,33 @@ where
         self
     }
 
+    /// Set the options used by the [crate::Bdf] and [crate::Sdirk] solvers (see [SolverOptions]).
+    pub fn solver_options(mut self, options: SolverOptions<M::T>) -> Self {
+        self.options = options;
+        self
+    }
+
+    /// Set inequality constraints on each component of the state, enforced by the [crate::Bdf] and [crate::Sdirk] solvers (see [Constraint]).
+    /// The number of constraints must be equal to the number of states, and the initial state must satisfy the constraints.
+    pub fn constraints(mut self, constraints: impl IntoIterator<Item = Constraint>) -> Self {
+        self.options.constraints = Some(constraints.into_iter().collect());
+        self
+    }
+
+    /// Set the norm used for the error test of each step and the Newton iterations of the [crate::Bdf] and [crate::Sdirk] solvers
+    /// (default [crate::RmsNorm], see [ErrorNorm]).
+    pub fn error_norm(mut self, error_norm: impl ErrorNorm<M::T> + 'static) -> Self {
+        self.options.error_norm = Some(Arc::new(error_norm));
+        self
+    }
+
+    /// Project the solution onto the manifold of a set of invariants after each step of the [crate::Bdf] and [crate::Sdirk] solvers
+    /// (see [ManifoldProjection]), for example using [ManifoldProjection::from_closures].
+    pub fn projection(mut self, projection: ManifoldProjection<'static, M>) -> Self {
+        self.projection = Some(projection);
+        self
+    }
+
     /// Set the initial step size.
     pub fn h0(mut self, h0: f64) -> Self {
         self.h0 = h0.into();
@@ -819,7 +1073,134 @@ where
             self.t0,
             self.h0,
             self.integrate_out,
-        )
+        )?
+        .with_options(self.options)?
+        .with_projection(self.projection)
+    }
+
+    /// Build a stochastic differential equation (SDE) problem `dy = f(t, y) dt + G(t, y) dW`, where the drift `f` is given by the right-hand side
+    /// and the rest of the builder, and the diffusion `G` is given by a closure that computes its action on a vector of Wiener increments `dw`.
+    ///
+    /// # Arguments
+    ///
+    /// - `diffusion`: Function of type Fn(x: &V, p: &V, t: S, dw: &V, y: &mut V) that computes `y = G(t, x) dw`. For diagonal noise this is `y_i = g_i(t, x) dw_i`.
+    /// - `noise`: The structure of the diffusion (see [NoiseType]), this also gives the number of independent Wiener processes.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use diffsol::{OdeBuilder, NoiseType};
+    /// type M = nalgebra::DMatrix<f64>;
+    ///
+    /// // dy = a y dt + b y dW
+    /// let problem = OdeBuilder::<M>::new()
+    ///   .p(vec![0.1, 0.2])
+    ///   .rhs(|x, p, _t, y| y[0] = p[0] * x[0])
+    ///   .init(|_p, _t| nalgebra::DVector::from_element(1, 1.0))
+    ///   .build_sde(|x, p, _t, dw, y| y[0] = p[1] * x[0] * dw[0], NoiseType::Diagonal)
+    ///   .unwrap();
+    /// ```
+    #[allow(clippy::type_complexity)]
+    pub fn build_sde<F>(
+        self,
+        diffusion: F,
+        noise: NoiseType,
+    ) -> Result<SdeProblem<OdeSolverEquations<M, Rhs, Init, Mass, Root, Out>, F>, DiffsolError>
+    where
+        M: Matrix,
+        Rhs: BuilderOp<V = M::V, T = M::T, M = M>,
+        Init: BuilderOp<V = M::V, T = M::T, M = M>,
+        Mass: BuilderOp<V = M::V, T = M::T, M = M>,
+        Root: BuilderOp<V = M::V, T = M::T, M = M>,
+        Out: BuilderOp<V = M::V, T = M::T, M = M>,
+        for<'a> ParameterisedOp<'a, Rhs>: NonLinearOp<M = M, V = M::V, T = M::T>,
+        for<'a> ParameterisedOp<'a, Init>: ConstantOp<M = M, V = M::V, T = M::T>,
+        for<'a> ParameterisedOp<'a, Mass>: LinearOp<M = M, V = M::V, T = M::T>,
+        for<'a> ParameterisedOp<'a, Root>: NonLinearOp<M = M, V = M::V, T = M::T>,
+        for<'a> ParameterisedOp<'a, Out>: NonLinearOp<M = M, V = M::V, T = M::T>,
+        F: Fn(&M::V, &M::V, M::T, &M::V, &mut M::V),
+    {
+        let p = Self::build_p(self.p.clone());
+        let ode = self.build()?;
+        let nstates = ode.eqn.rhs().nstates();
+        if let NoiseType::General(0) | NoiseType::Additive(0) = noise {
+            return Err(ode_solver_error!(
+                BuilderError,
+                "Number of Wiener processes must be greater than zero"
+            ));
+        }
+        let diffusion = ClosureDiffusion::new(diffusion, noise, nstates, p.len());
+        Ok(SdeProblem::new(ode, diffusion, p))
+    }
+
+    /// Build a fully implicit differential-algebraic equation (DAE) problem in residual form `F(t, y, y') = 0` (see [crate::OdeEquationsResidual]).
+    /// Only the initial time, initial step size, tolerances and parameters of the builder are used, the equations are given by the arguments.
+    ///
+    /// # Arguments
+    ///
+    /// - `residual`: Function of type Fn(x: &V, dx: &V, p: &V, t: S, y: &mut V) that computes the residual `y = F(t, x, dx)`.
+    /// - `residual_jac`: Function of type Fn(x: &V, dx: &V, p: &V, t: S, v: &V, y: &mut V) that computes the product of the Jacobian of the residual wrt `x` with the vector v.
+    /// - `residual_jac_dot`: Function of type Fn(x: &V, dx: &V, p: &V, t: S, v: &V, y: &mut V) that computes the product of the Jacobian of the residual wrt `dx` with the vector v.
+    /// - `init`: Function of type Fn(p: &V, t: S) -> V that computes the initial state, the algebraic variables only need to be an initial guess.
+    /// - `algebraic_indices`: The indices of the algebraic variables (i.e. those whose derivatives do not appear in the residual).
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use diffsol::OdeBuilder;
+    /// type M = nalgebra::DMatrix<f64>;
+    ///
+    /// // y0' + a y0 = 0
+    /// // y1 - y0 = 0
+    /// let problem = OdeBuilder::<M>::new()
+    ///   .p(vec![0.1])
+    ///   .build_residual(
+    ///     |x, dx, p, _t, y| { y[0] = dx[0] + p[0] * x[0]; y[1] = x[1] - x[0]; },
+    ///     |_x, _dx, p, _t, v, y| { y[0] = p[0] * v[0]; y[1] = v[1] - v[0]; },
+    ///     |_x, _dx, _p, _t, v, y| { y[0] = v[0]; y[1] = 0.0; },
+    ///     |_p, _t| nalgebra::DVector::from_vec(vec![1.0, 0.0]),
+    ///     &[1],
+    ///   )
+    ///   .unwrap();
+    /// ```
+    #[allow(clippy::type_complexity)]
+    pub fn build_residual<F, G, H, I>(
+        self,
+        residual: F,
+        residual_jac: G,
+        residual_jac_dot: H,
+        init: I,
+        algebraic_indices: &[usize],
+    ) -> Result<DaeProblem<ClosureResidual<M, F, G, H, I>>, DiffsolError>
+    where
+        F: Fn(&M::V, &M::V, &M::V, M::T, &mut M::V),
+        G: Fn(&M::V, &M::V, &M::V, M::T, &M::V, &mut M::V),
+        H: Fn(&M::V, &M::V, &M::V, M::T, &M::V, &mut M::V),
+        I: Fn(&M::V, M::T) -> M::V,
+    {
+        let p = Self::build_p(self.p);
+        let nstates = init(&p, self.t0).len();
+        let atol = Self::build_atol(self.atol, nstates, "states")?;
+        if let Some(&i) = algebraic_indices.iter().find(|&&i| i >= nstates) {
+            return Err(ode_solver_error!(
+                BuilderError,
+                format!(
+                    "Algebraic index {} is out of bounds for {} states",
+                    i, nstates
+                )
+            ));
+        }
+        let algebraic_indices = <M::V as Vector>::Index::from_slice(algebraic_indices);
+        let eqn = ClosureResidual::new(
+            residual,
+            residual_jac,
+            residual_jac_dot,
+            init,
+            algebraic_indices,
+            nstates,
+            p,
+        );
+        Ok(DaeProblem::new(eqn, self.rtol, atol, self.t0, self.h0))
     }
 
     /// Build an ODE problem from a set of equations
@@ -865,6 +1246,8 @@ where
             self.t0,
             self.h0,
             self.integrate_out,
-        )
+        )?
+        .with_options(self.options)?
+        .with_projection(self.projection)
     }
 }
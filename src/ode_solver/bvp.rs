@@ -0,0 +1,770 @@
+use nalgebra::ComplexField;
+use std::cell::RefCell;
+use std::marker::PhantomData;
+use std::ops::{MulAssign, SubAssign};
+
+use num_traits::{One, Zero};
+
+use crate::{
+    error::{DiffsolError, NonLinearSolverError, OdeSolverError},
+    non_linear_solver_error, ode_solver_error, scale, BdfState, DefaultDenseMatrix, DefaultSolver,
+    DenseMatrix, InitialSensEquations, LinearSolver, Matrix, MatrixCommon, MatrixRef, NonLinearOp,
+    NonLinearOpJacobian, OdeEquations, OdeEquationsImplicit, OdeSolverMethod, OdeSolverProblem,
+    OdeSolverState, OdeSolverStopReason, Op, Vector, VectorRef,
+};
+
+use super::state::StateCommon;
+
+/// A two-point boundary value problem (BVP) of the form
+///
+/// $$
+///  y' = f(t, y), \quad t_a \le t \le t_b
+///  g(y(t_a), y(t_b)) = 0
+/// $$
+///
+/// The ODE is given by [Self::ode], which must not have a mass matrix (the initial condition of the ODE problem is not used).
+/// The `nstates` boundary conditions `g` are given by a closure `bc(ya, yb, r)`, and the Jacobian-vector products of `g` wrt `ya` and `yb`
+/// by closures `bc_jac_a(ya, yb, v, r)` and `bc_jac_b(ya, yb, v, r)`.
+///
+/// The BVP is solved for the state at a mesh of times `t_a = t_0 < t_1 < ... < t_m = t_b`, starting from an initial guess, using either:
+/// - multiple shooting ([Self::multiple_shooting]): the ODE is integrated from each mesh point to the next, along with the sensitivities of the state
+///   wrt the initial state of the interval (see [InitialSensEquations]), and a Newton iteration is used to find the states at the mesh points that
+///   satisfy the boundary conditions and make the solution continuous.
+/// - collocation ([Self::collocation]): the solution is approximated by a cubic Hermite polynomial on each interval, and the Hermite-Simpson
+///   collocation equations are solved using a Newton iteration. This method does not integrate the ODE, so is suitable for unstable problems
+///   where integrating from one mesh point to the next would amplify any error in the initial guess.
+pub struct BvpProblem<Eqn, F, Ga, Gb>
+where
+    Eqn: OdeEquations,
+    F: Fn(&Eqn::V, &Eqn::V, &mut Eqn::V),
+    Ga: Fn(&Eqn::V, &Eqn::V, &Eqn::V, &mut Eqn::V),
+    Gb: Fn(&Eqn::V, &Eqn::V, &Eqn::V, &mut Eqn::V),
+{
+    pub ode: OdeSolverProblem<Eqn>,
+    bc: F,
+    bc_jac_a: Ga,
+    bc_jac_b: Gb,
+    max_iter: usize,
+}
+
+/// The solution of a [BvpProblem] at the mesh points `t`, the ith column of `y` is the state at time `t[i]`.
+/// `niter` is the number of Newton iterations taken.
+pub struct BvpSolution<M: DenseMatrix> {
+    pub t: Vec<M::T>,
+    pub y: M,
+    pub niter: usize,
+}
+
+impl<Eqn, F, Ga, Gb> BvpProblem<Eqn, F, Ga, Gb>
+where
+    Eqn: OdeEquations,
+    F: Fn(&Eqn::V, &Eqn::V, &mut Eqn::V),
+    Ga: Fn(&Eqn::V, &Eqn::V, &Eqn::V, &mut Eqn::V),
+    Gb: Fn(&Eqn::V, &Eqn::V, &Eqn::V, &mut Eqn::V),
+{
+    pub fn new(ode: OdeSolverProblem<Eqn>, bc: F, bc_jac_a: Ga, bc_jac_b: Gb) -> Self {
+        Self {
+            ode,
+            bc,
+            bc_jac_a,
+            bc_jac_b,
+            max_iter: 20,
+        }
+    }
+
+    pub fn ode(&self) -> &OdeSolverProblem<Eqn> {
+        &self.ode
+    }
+
+    /// Set the maximum number of Newton iterations (default 20)
+    pub fn set_max_iter(&mut self, max_iter: usize) {
+        self.max_iter = max_iter;
+    }
+
+    pub fn max_iter(&self) -> usize {
+        self.max_iter
+    }
+
+    fn check_mesh<M: DenseMatrix<T = Eqn::T, V = Eqn::V>>(
+        &self,
+        t: &[Eqn::T],
+        y_guess: &M,
+    ) -> Result<(), DiffsolError> {
+        if self.ode.eqn.mass().is_some() {
+            return Err(ode_solver_error!(
+                Other,
+                "Boundary value problems with a mass matrix are not supported"
+            ));
+        }
+        if t.len() < 2 || t.windows(2).any(|w| w[0] >= w[1]) {
+            return Err(ode_solver_error!(
+                Other,
+                "Boundary value problem mesh must have at least two strictly increasing times"
+            ));
+        }
+        if y_guess.nrows() != self.ode.eqn.rhs().nstates() || y_guess.ncols() != t.len() {
+            return Err(ode_solver_error!(StateProblemMismatch));
+        }
+        Ok(())
+    }
+
+    // g(ya, yb), evaluated into the last block of rows of r
+    fn bc_residual(&self, x: &Eqn::V, nnodes: usize, r: &mut Eqn::V) {
+        let n = self.ode.eqn.rhs().nstates();
+        let ya = get_block(x, 0, n);
+        let yb = get_block(x, nnodes - 1, n);
+        let mut g = Eqn::V::zeros(n);
+        (self.bc)(&ya, &yb, &mut g);
+        set_block(r, nnodes - 1, &g);
+    }
+
+    // dg/dya v_a + dg/dyb v_b, evaluated into the last block of rows of y
+    fn bc_jac_mul(&self, x: &Eqn::V, nnodes: usize, v: &Eqn::V, y: &mut Eqn::V) {
+        let n = self.ode.eqn.rhs().nstates();
+        let ya = get_block(x, 0, n);
+        let yb = get_block(x, nnodes - 1, n);
+        let va = get_block(v, 0, n);
+        let vb = get_block(v, nnodes - 1, n);
+        let mut g = Eqn::V::zeros(n);
+        let mut tmp = Eqn::V::zeros(n);
+        (self.bc_jac_a)(&ya, &yb, &va, &mut g);
+        (self.bc_jac_b)(&ya, &yb, &vb, &mut tmp);
+        g.axpy(Eqn::T::one(), &tmp, Eqn::T::one());
+        set_block(y, nnodes - 1, &g);
+    }
+
+    // dg/dya and dg/dyb, added to the last block of rows of jac
+    fn bc_jacobian<M: DenseMatrix<T = Eqn::T, V = Eqn::V>>(
+        &self,
+        x: &Eqn::V,
+        nnodes: usize,
+        jac: &mut M,
+    ) {
+        let n = self.ode.eqn.rhs().nstates();
+        let ya = get_block(x, 0, n);
+        let yb = get_block(x, nnodes - 1, n);
+        let row = (nnodes - 1) * n;
+        let mut e_j = Eqn::V::zeros(n);
+        let mut col = Eqn::V::zeros(n);
+        for j in 0..n {
+            e_j[j] = Eqn::T::one();
+            (self.bc_jac_a)(&ya, &yb, &e_j, &mut col);
+            for k in 0..n {
+                jac[(row + k, j)] += col[k];
+            }
+            (self.bc_jac_b)(&ya, &yb, &e_j, &mut col);
+            for k in 0..n {
+                jac[(row + k, row + j)] += col[k];
+            }
+            e_j[j] = Eqn::T::zero();
+        }
+    }
+
+    // the absolute tolerance of the ODE problem, repeated for each mesh point
+    fn stacked_atol(&self, nnodes: usize) -> Eqn::V {
+        let n = self.ode.eqn.rhs().nstates();
+        let mut atol = Eqn::V::zeros(n * nnodes);
+        for i in 0..nnodes {
+            set_block(&mut atol, i, &self.ode.atol);
+        }
+        atol
+    }
+}
+
+impl<Eqn, F, Ga, Gb> BvpProblem<Eqn, F, Ga, Gb>
+where
+    Eqn: OdeEquationsImplicit,
+    Eqn::V: DefaultDenseMatrix<T = Eqn::T>,
+    <Eqn::V as DefaultDenseMatrix>::M: DefaultSolver,
+    for<'b> &'b Eqn::V: VectorRef<Eqn::V>,
+    for<'b> &'b Eqn::M: MatrixRef<Eqn::M>,
+    F: Fn(&Eqn::V, &Eqn::V, &mut Eqn::V),
+    Ga: Fn(&Eqn::V, &Eqn::V, &Eqn::V, &mut Eqn::V),
+    Gb: Fn(&Eqn::V, &Eqn::V, &Eqn::V, &mut Eqn::V),
+{
+    /// Solve the BVP using multiple shooting with the shooting nodes `t_nodes`, starting from the initial guess `y_guess` of the states
+    /// at the nodes (a dense matrix with one column for each node).
+    ///
+    /// The ODE is integrated between the nodes using the [crate::Bdf] solver with the linear solver `LS`, along with the sensitivities wrt
+    /// the state at the start of each interval. These give the blocks of the Jacobian of the shooting equations, which are solved using a Newton iteration.
+    pub fn multiple_shooting<LS: LinearSolver<Eqn::M>>(
+        &self,
+        t_nodes: &[Eqn::T],
+        y_guess: &<Eqn::V as DefaultDenseMatrix>::M,
+    ) -> Result<BvpSolution<<Eqn::V as DefaultDenseMatrix>::M>, DiffsolError> {
+        self.check_mesh(t_nodes, y_guess)?;
+        if self.ode.integrate_out {
+            return Err(ode_solver_error!(
+                Other,
+                "Cannot integrate out when solving a boundary value problem"
+            ));
+        }
+        let op = ShootingSystem::<Eqn, F, Ga, Gb, LS>::new(self, t_nodes);
+        let mut x = stack(y_guess);
+        let niter = self.newton_solve(&op, &mut x, t_nodes.len(), |x| op.integrate(x))?;
+        Ok(BvpSolution {
+            t: t_nodes.to_vec(),
+            y: unstack(&x, y_guess.nrows(), t_nodes.len()),
+            niter,
+        })
+    }
+
+    /// Solve the BVP using Hermite-Simpson collocation on the mesh `t_mesh`, starting from the initial guess `y_guess` of the states
+    /// at the mesh points (a dense matrix with one column for each mesh point).
+    ///
+    /// On each interval `[t_i, t_{i+1}]` of length `h`, the collocation equations are
+    ///
+    /// $$
+    ///  y_{i+1/2} = (y_i + y_{i+1}) / 2 + h (f_i - f_{i+1}) / 8
+    ///  y_{i+1} - y_i - h (f_i + 4 f(t_i + h/2, y_{i+1/2}) + f_{i+1}) / 6 = 0
+    /// $$
+    ///
+    /// which are fourth order accurate in `h`. The error of the solution is controlled by the mesh, not by the tolerances of the ODE problem.
+    pub fn collocation(
+        &self,
+        t_mesh: &[Eqn::T],
+        y_guess: &<Eqn::V as DefaultDenseMatrix>::M,
+    ) -> Result<BvpSolution<<Eqn::V as DefaultDenseMatrix>::M>, DiffsolError> {
+        self.check_mesh(t_mesh, y_guess)?;
+        let op = CollocationSystem::new(self, t_mesh);
+        let mut x = stack(y_guess);
+        let niter = self.newton_solve(&op, &mut x, t_mesh.len(), |_x| Ok(()))?;
+        Ok(BvpSolution {
+            t: t_mesh.to_vec(),
+            y: unstack(&x, y_guess.nrows(), t_mesh.len()),
+            niter,
+        })
+    }
+
+    // Newton iteration for the shooting and collocation equations. Unlike the modified Newton iteration used by the ODE solvers,
+    // the Jacobian is updated every iteration and there is no check on the rate of convergence, since the initial guess may be poor.
+    // The iteration has converged when the norm of the update (weighted by the tolerances of the ODE problem) is less than one.
+    // `prepare` is called with the current iterate before the equations and Jacobian are evaluated.
+    fn newton_solve<C>(
+        &self,
+        op: &C,
+        x: &mut Eqn::V,
+        nnodes: usize,
+        mut prepare: impl FnMut(&Eqn::V) -> Result<(), DiffsolError>,
+    ) -> Result<usize, DiffsolError>
+    where
+        C: NonLinearOpJacobian<T = Eqn::T, V = Eqn::V, M = <Eqn::V as DefaultDenseMatrix>::M>,
+    {
+        let atol = self.stacked_atol(nnodes);
+        let rtol = self.ode.rtol;
+        let t = self.ode.t0;
+        let mut linear_solver =
+            <<Eqn::V as DefaultDenseMatrix>::M as DefaultSolver>::default_solver();
+        linear_solver.set_problem(op);
+        let mut dx = Eqn::V::zeros(x.len());
+        for niter in 1..=self.max_iter {
+            prepare(x)?;
+            op.call_inplace(x, t, &mut dx);
+            linear_solver.set_linearisation(op, x, t);
+            linear_solver.solve_in_place(&mut dx)?;
+            x.sub_assign(&dx);
+            if dx.squared_norm(x, &atol, rtol).sqrt() < Eqn::T::one() {
+                return Ok(niter);
+            }
+        }
+        Err(non_linear_solver_error!(NewtonDidNotConverge))
+    }
+}
+
+// the multiple shooting equations, for nodes t_0, ..., t_m with unknowns x = [y_0, ..., y_m]
+//
+// F_i(x) = phi(t_{i+1}; t_i, y_i) - y_{i+1}, i = 0, ..., m - 1
+// F_m(x) = g(y_0, y_m)
+//
+// where phi(t; t_i, y_i) is the solution of the ODE at time t starting from y_i at time t_i. The ODE is integrated
+// (along with the state transition matrices S_i = d phi(t_{i+1}; t_i, y_i) / d y_i) by [Self::integrate], and the
+// results are cached, so this must be called before the equations or Jacobian are evaluated.
+struct ShootingSystem<'a, Eqn, F, Ga, Gb, LS>
+where
+    Eqn: OdeEquationsImplicit,
+    F: Fn(&Eqn::V, &Eqn::V, &mut Eqn::V),
+    Ga: Fn(&Eqn::V, &Eqn::V, &Eqn::V, &mut Eqn::V),
+    Gb: Fn(&Eqn::V, &Eqn::V, &Eqn::V, &mut Eqn::V),
+{
+    problem: &'a BvpProblem<Eqn, F, Ga, Gb>,
+    t_nodes: &'a [Eqn::T],
+    y_end: RefCell<Vec<Eqn::V>>,
+    sens_end: RefCell<Vec<Vec<Eqn::V>>>,
+    _phantom: PhantomData<LS>,
+}
+
+impl<'a, Eqn, F, Ga, Gb, LS> ShootingSystem<'a, Eqn, F, Ga, Gb, LS>
+where
+    Eqn: OdeEquationsImplicit,
+    Eqn::V: DefaultDenseMatrix<T = Eqn::T>,
+    for<'b> &'b Eqn::V: VectorRef<Eqn::V>,
+    for<'b> &'b Eqn::M: MatrixRef<Eqn::M>,
+    F: Fn(&Eqn::V, &Eqn::V, &mut Eqn::V),
+    Ga: Fn(&Eqn::V, &Eqn::V, &Eqn::V, &mut Eqn::V),
+    Gb: Fn(&Eqn::V, &Eqn::V, &Eqn::V, &mut Eqn::V),
+    LS: LinearSolver<Eqn::M>,
+{
+    fn new(problem: &'a BvpProblem<Eqn, F, Ga, Gb>, t_nodes: &'a [Eqn::T]) -> Self {
+        let n = problem.ode.eqn.rhs().nstates();
+        let nintervals = t_nodes.len() - 1;
+        Self {
+            problem,
+            t_nodes,
+            y_end: RefCell::new(vec![Eqn::V::zeros(n); nintervals]),
+            sens_end: RefCell::new(vec![vec![Eqn::V::zeros(n); n]; nintervals]),
+            _phantom: PhantomData,
+        }
+    }
+
+    // state at time t0 with the sensitivities wrt the state initialised to the identity matrix
+    fn interval_state(&self, y0: Eqn::V, t0: Eqn::T) -> BdfState<Eqn::V> {
+        let ode = &self.problem.ode;
+        let n = y0.len();
+        let dy = ode.eqn.rhs().call(&y0, t0);
+        let mut s = Vec::with_capacity(n);
+        let mut ds = Vec::with_capacity(n);
+        for j in 0..n {
+            let mut e_j = Eqn::V::zeros(n);
+            e_j[j] = Eqn::T::one();
+            let mut ds_j = Eqn::V::zeros(n);
+            ode.eqn.rhs().jac_mul_inplace(&y0, t0, &e_j, &mut ds_j);
+            s.push(e_j);
+            ds.push(ds_j);
+        }
+        let mut state = BdfState::new_from_common(StateCommon {
+            y: y0,
+            dy,
+            g: Eqn::V::zeros(0),
+            dg: Eqn::V::zeros(0),
+            s,
+            ds,
+            sg: Vec::new(),
+            dsg: Vec::new(),
+            t: t0,
+            h: ode.h0,
+        });
+        state.set_step_size(ode, 1);
+        state
+    }
+
+    fn integrate(&self, x: &Eqn::V) -> Result<(), DiffsolError> {
+        let ode = &self.problem.ode;
+        let n = ode.eqn.rhs().nstates();
+        let mut y_end = self.y_end.borrow_mut();
+        let mut sens_end = self.sens_end.borrow_mut();
+        for (i, t) in self.t_nodes.windows(2).enumerate() {
+            let state = self.interval_state(get_block(x, i, n), t[0]);
+            let mut solver = ode.bdf_solver_aug::<LS, _>(state, InitialSensEquations::new(ode))?;
+            solver.set_stop_time(t[1])?;
+            while solver.step()? != OdeSolverStopReason::TstopReached {}
+            let state = solver.state();
+            y_end[i].copy_from(state.y);
+            for (s_end, s) in sens_end[i].iter_mut().zip(state.s.iter()) {
+                s_end.copy_from(s);
+            }
+        }
+        Ok(())
+    }
+}
+
+impl<Eqn, F, Ga, Gb, LS> Op for ShootingSystem<'_, Eqn, F, Ga, Gb, LS>
+where
+    Eqn: OdeEquationsImplicit,
+    Eqn::V: DefaultDenseMatrix,
+    F: Fn(&Eqn::V, &Eqn::V, &mut Eqn::V),
+    Ga: Fn(&Eqn::V, &Eqn::V, &Eqn::V, &mut Eqn::V),
+    Gb: Fn(&Eqn::V, &Eqn::V, &Eqn::V, &mut Eqn::V),
+{
+    type T = Eqn::T;
+    type V = Eqn::V;
+    type M = <Eqn::V as DefaultDenseMatrix>::M;
+
+    fn nstates(&self) -> usize {
+        self.problem.ode.eqn.rhs().nstates() * self.t_nodes.len()
+    }
+    fn nout(&self) -> usize {
+        self.nstates()
+    }
+    fn nparams(&self) -> usize {
+        0
+    }
+}
+
+impl<Eqn, F, Ga, Gb, LS> NonLinearOp for ShootingSystem<'_, Eqn, F, Ga, Gb, LS>
+where
+    Eqn: OdeEquationsImplicit,
+    Eqn::V: DefaultDenseMatrix,
+    F: Fn(&Eqn::V, &Eqn::V, &mut Eqn::V),
+    Ga: Fn(&Eqn::V, &Eqn::V, &Eqn::V, &mut Eqn::V),
+    Gb: Fn(&Eqn::V, &Eqn::V, &Eqn::V, &mut Eqn::V),
+{
+    fn call_inplace(&self, x: &Self::V, _t: Self::T, y: &mut Self::V) {
+        let n = self.problem.ode.eqn.rhs().nstates();
+        let nnodes = self.t_nodes.len();
+        for (i, y_end) in self.y_end.borrow().iter().enumerate() {
+            let mut r = y_end.clone();
+            r.sub_assign(&get_block(x, i + 1, n));
+            set_block(y, i, &r);
+        }
+        self.problem.bc_residual(x, nnodes, y);
+    }
+}
+
+impl<Eqn, F, Ga, Gb, LS> NonLinearOpJacobian for ShootingSystem<'_, Eqn, F, Ga, Gb, LS>
+where
+    Eqn: OdeEquationsImplicit,
+    Eqn::V: DefaultDenseMatrix,
+    F: Fn(&Eqn::V, &Eqn::V, &mut Eqn::V),
+    Ga: Fn(&Eqn::V, &Eqn::V, &Eqn::V, &mut Eqn::V),
+    Gb: Fn(&Eqn::V, &Eqn::V, &Eqn::V, &mut Eqn::V),
+{
+    // S_i v_i - v_{i+1}
+    fn jac_mul_inplace(&self, x: &Self::V, _t: Self::T, v: &Self::V, y: &mut Self::V) {
+        let n = self.problem.ode.eqn.rhs().nstates();
+        let nnodes = self.t_nodes.len();
+        for (i, sens) in self.sens_end.borrow().iter().enumerate() {
+            let mut r = get_block(v, i + 1, n);
+            r.mul_assign(scale(-Eqn::T::one()));
+            for (j, s_j) in sens.iter().enumerate() {
+                r.axpy(v[i * n + j], s_j, Eqn::T::one());
+            }
+            set_block(y, i, &r);
+        }
+        self.problem.bc_jac_mul(x, nnodes, v, y);
+    }
+
+    fn jacobian_inplace(&self, x: &Self::V, _t: Self::T, y: &mut Self::M) {
+        let n = self.problem.ode.eqn.rhs().nstates();
+        let nnodes = self.t_nodes.len();
+        *y = Self::M::zeros(n * nnodes, n * nnodes);
+        for (i, sens) in self.sens_end.borrow().iter().enumerate() {
+            for (j, s_j) in sens.iter().enumerate() {
+                for k in 0..n {
+                    y[(i * n + k, i * n + j)] = s_j[k];
+                }
+                y[(i * n + j, (i + 1) * n + j)] = -Eqn::T::one();
+            }
+        }
+        self.problem.bc_jacobian(x, nnodes, y);
+    }
+}
+
+// the state, right-hand side and midpoint of the Hermite cubic on the interval [t_i, t_{i+1}]
+struct HermiteSimpsonInterval<V: Vector> {
+    y0: V,
+    y1: V,
+    f0: V,
+    f1: V,
+    y_mid: V,
+    t0: V::T,
+    t1: V::T,
+    t_mid: V::T,
+    h: V::T,
+}
+
+// the Hermite-Simpson collocation equations, for mesh points t_0, ..., t_m with unknowns x = [y_0, ..., y_m]
+//
+// F_i(x) = y_{i+1} - y_i - h_i (f_i + 4 f(t_i + h_i / 2, y_{i+1/2}) + f_{i+1}) / 6, i = 0, ..., m - 1
+// F_m(x) = g(y_0, y_m)
+//
+// where y_{i+1/2} = (y_i + y_{i+1}) / 2 + h_i (f_i - f_{i+1}) / 8
+struct CollocationSystem<'a, Eqn, F, Ga, Gb>
+where
+    Eqn: OdeEquationsImplicit,
+    F: Fn(&Eqn::V, &Eqn::V, &mut Eqn::V),
+    Ga: Fn(&Eqn::V, &Eqn::V, &Eqn::V, &mut Eqn::V),
+    Gb: Fn(&Eqn::V, &Eqn::V, &Eqn::V, &mut Eqn::V),
+{
+    problem: &'a BvpProblem<Eqn, F, Ga, Gb>,
+    t_mesh: &'a [Eqn::T],
+}
+
+impl<'a, Eqn, F, Ga, Gb> CollocationSystem<'a, Eqn, F, Ga, Gb>
+where
+    Eqn: OdeEquationsImplicit,
+    F: Fn(&Eqn::V, &Eqn::V, &mut Eqn::V),
+    Ga: Fn(&Eqn::V, &Eqn::V, &Eqn::V, &mut Eqn::V),
+    Gb: Fn(&Eqn::V, &Eqn::V, &Eqn::V, &mut Eqn::V),
+{
+    fn new(problem: &'a BvpProblem<Eqn, F, Ga, Gb>, t_mesh: &'a [Eqn::T]) -> Self {
+        Self { problem, t_mesh }
+    }
+
+    fn interval(&self, x: &Eqn::V, i: usize) -> HermiteSimpsonInterval<Eqn::V> {
+        let rhs = self.problem.ode.eqn.rhs();
+        let n = rhs.nstates();
+        let (t0, t1) = (self.t_mesh[i], self.t_mesh[i + 1]);
+        let h = t1 - t0;
+        let half = Eqn::T::from(0.5);
+        let eighth = Eqn::T::from(0.125);
+        let y0 = get_block(x, i, n);
+        let y1 = get_block(x, i + 1, n);
+        let f0 = rhs.call(&y0, t0);
+        let f1 = rhs.call(&y1, t1);
+        let mut y_mid = y0.clone();
+        y_mid.axpy(half, &y1, half);
+        y_mid.axpy(h * eighth, &f0, Eqn::T::one());
+        y_mid.axpy(-h * eighth, &f1, Eqn::T::one());
+        HermiteSimpsonInterval {
+            y0,
+            y1,
+            f0,
+            f1,
+            y_mid,
+            t0,
+            t1,
+            t_mid: t0 + half * h,
+            h,
+        }
+    }
+}
+
+impl<Eqn, F, Ga, Gb> Op for CollocationSystem<'_, Eqn, F, Ga, Gb>
+where
+    Eqn: OdeEquationsImplicit,
+    Eqn::V: DefaultDenseMatrix,
+    F: Fn(&Eqn::V, &Eqn::V, &mut Eqn::V),
+    Ga: Fn(&Eqn::V, &Eqn::V, &Eqn::V, &mut Eqn::V),
+    Gb: Fn(&Eqn::V, &Eqn::V, &Eqn::V, &mut Eqn::V),
+{
+    type T = Eqn::T;
+    type V = Eqn::V;
+    type M = <Eqn::V as DefaultDenseMatrix>::M;
+
+    fn nstates(&self) -> usize {
+        self.problem.ode.eqn.rhs().nstates() * self.t_mesh.len()
+    }
+    fn nout(&self) -> usize {
+        self.nstates()
+    }
+    fn nparams(&self) -> usize {
+        0
+    }
+}
+
+impl<Eqn, F, Ga, Gb> NonLinearOp for CollocationSystem<'_, Eqn, F, Ga, Gb>
+where
+    Eqn: OdeEquationsImplicit,
+    Eqn::V: DefaultDenseMatrix,
+    F: Fn(&Eqn::V, &Eqn::V, &mut Eqn::V),
+    Ga: Fn(&Eqn::V, &Eqn::V, &Eqn::V, &mut Eqn::V),
+    Gb: Fn(&Eqn::V, &Eqn::V, &Eqn::V, &mut Eqn::V),
+{
+    fn call_inplace(&self, x: &Self::V, _t: Self::T, y: &mut Self::V) {
+        let rhs = self.problem.ode.eqn.rhs();
+        let nnodes = self.t_mesh.len();
+        let sixth = Eqn::T::one() / Eqn::T::from(6.0);
+        for i in 0..nnodes - 1 {
+            let interval = self.interval(x, i);
+            let f_mid = rhs.call(&interval.y_mid, interval.t_mid);
+            let h = interval.h;
+            let mut r = interval.y1;
+            r.axpy(-Eqn::T::one(), &interval.y0, Eqn::T::one());
+            r.axpy(-h * sixth, &interval.f0, Eqn::T::one());
+            r.axpy(-Eqn::T::from(4.0) * h * sixth, &f_mid, Eqn::T::one());
+            r.axpy(-h * sixth, &interval.f1, Eqn::T::one());
+            set_block(y, i, &r);
+        }
+        self.problem.bc_residual(x, nnodes, y);
+    }
+}
+
+impl<Eqn, F, Ga, Gb> NonLinearOpJacobian for CollocationSystem<'_, Eqn, F, Ga, Gb>
+where
+    Eqn: OdeEquationsImplicit,
+    Eqn::V: DefaultDenseMatrix,
+    F: Fn(&Eqn::V, &Eqn::V, &mut Eqn::V),
+    Ga: Fn(&Eqn::V, &Eqn::V, &Eqn::V, &mut Eqn::V),
+    Gb: Fn(&Eqn::V, &Eqn::V, &Eqn::V, &mut Eqn::V),
+{
+    // dF_i/dy_i v_i + dF_i/dy_{i+1} v_{i+1} = v_{i+1} - v_i - h (J_i v_i + 4 J_{i+1/2} w + J_{i+1} v_{i+1}) / 6,
+    // where w = (v_i + v_{i+1}) / 2 + h (J_i v_i - J_{i+1} v_{i+1}) / 8
+    fn jac_mul_inplace(&self, x: &Self::V, _t: Self::T, v: &Self::V, y: &mut Self::V) {
+        let rhs = self.problem.ode.eqn.rhs();
+        let n = rhs.nstates();
+        let nnodes = self.t_mesh.len();
+        let half = Eqn::T::from(0.5);
+        let eighth = Eqn::T::from(0.125);
+        let sixth = Eqn::T::one() / Eqn::T::from(6.0);
+        let mut a0 = Eqn::V::zeros(n);
+        let mut a1 = Eqn::V::zeros(n);
+        let mut c = Eqn::V::zeros(n);
+        for i in 0..nnodes - 1 {
+            let interval = self.interval(x, i);
+            let h = interval.h;
+            let v0 = get_block(v, i, n);
+            let v1 = get_block(v, i + 1, n);
+            rhs.jac_mul_inplace(&interval.y0, interval.t0, &v0, &mut a0);
+            rhs.jac_mul_inplace(&interval.y1, interval.t1, &v1, &mut a1);
+            let mut w = v0.clone();
+            w.axpy(half, &v1, half);
+            w.axpy(h * eighth, &a0, Eqn::T::one());
+            w.axpy(-h * eighth, &a1, Eqn::T::one());
+            rhs.jac_mul_inplace(&interval.y_mid, interval.t_mid, &w, &mut c);
+            let mut r = v1;
+            r.axpy(-Eqn::T::one(), &v0, Eqn::T::one());
+            r.axpy(-h * sixth, &a0, Eqn::T::one());
+            r.axpy(-Eqn::T::from(4.0) * h * sixth, &c, Eqn::T::one());
+            r.axpy(-h * sixth, &a1, Eqn::T::one());
+            set_block(y, i, &r);
+        }
+        self.problem.bc_jac_mul(x, nnodes, v, y);
+    }
+
+    // the Jacobian is block bidiagonal (apart from the boundary conditions), so each block is assembled column by column
+    fn jacobian_inplace(&self, x: &Self::V, _t: Self::T, y: &mut Self::M) {
+        let rhs = self.problem.ode.eqn.rhs();
+        let n = rhs.nstates();
+        let nnodes = self.t_mesh.len();
+        let half = Eqn::T::from(0.5);
+        let eighth = Eqn::T::from(0.125);
+        let sixth = Eqn::T::one() / Eqn::T::from(6.0);
+        *y = Self::M::zeros(n * nnodes, n * nnodes);
+        let mut e_j = Eqn::V::zeros(n);
+        let mut a = Eqn::V::zeros(n);
+        let mut c = Eqn::V::zeros(n);
+        for i in 0..nnodes - 1 {
+            let interval = self.interval(x, i);
+            let h = interval.h;
+            for j in 0..n {
+                e_j[j] = Eqn::T::one();
+
+                // dF_i/dy_i e_j = -e_j - h (J_i e_j + 4 J_{i+1/2} (e_j / 2 + h J_i e_j / 8)) / 6
+                rhs.jac_mul_inplace(&interval.y0, interval.t0, &e_j, &mut a);
+                let mut w = e_j.clone();
+                w.mul_assign(scale(half));
+                w.axpy(h * eighth, &a, Eqn::T::one());
+                rhs.jac_mul_inplace(&interval.y_mid, interval.t_mid, &w, &mut c);
+                for k in 0..n {
+                    y[(i * n + k, i * n + j)] =
+                        -e_j[k] - h * sixth * (a[k] + Eqn::T::from(4.0) * c[k]);
+                }
+
+                // dF_i/dy_{i+1} e_j = e_j - h (J_{i+1} e_j + 4 J_{i+1/2} (e_j / 2 - h J_{i+1} e_j / 8)) / 6
+                rhs.jac_mul_inplace(&interval.y1, interval.t1, &e_j, &mut a);
+                let mut w = e_j.clone();
+                w.mul_assign(scale(half));
+                w.axpy(-h * eighth, &a, Eqn::T::one());
+                rhs.jac_mul_inplace(&interval.y_mid, interval.t_mid, &w, &mut c);
+                for k in 0..n {
+                    y[(i * n + k, (i + 1) * n + j)] =
+                        e_j[k] - h * sixth * (a[k] + Eqn::T::from(4.0) * c[k]);
+                }
+
+                e_j[j] = Eqn::T::zero();
+            }
+        }
+        self.problem.bc_jacobian(x, nnodes, y);
+    }
+}
+
+// the ith block of length n of the stacked vector x
+fn get_block<V: Vector>(x: &V, i: usize, n: usize) -> V {
+    let mut block = V::zeros(n);
+    for j in 0..n {
+        block[j] = x[i * n + j];
+    }
+    block
+}
+
+fn set_block<V: Vector>(x: &mut V, i: usize, block: &V) {
+    let n = block.len();
+    for j in 0..n {
+        x[i * n + j] = block[j];
+    }
+}
+
+// stack the columns of y into a single vector
+fn stack<M: DenseMatrix>(y: &M) -> M::V {
+    let (n, m) = (y.nrows(), y.ncols());
+    let mut x = M::V::zeros(n * m);
+    for i in 0..m {
+        for j in 0..n {
+            x[i * n + j] = y[(j, i)];
+        }
+    }
+    x
+}
+
+fn unstack<M: DenseMatrix>(x: &M::V, n: usize, m: usize) -> M {
+    let mut y = M::zeros(n, m);
+    for i in 0..m {
+        for j in 0..n {
+            y[(j, i)] = x[i * n + j];
+        }
+    }
+    y
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{ode_solver::test_models::quadratic_bvp::quadratic_bvp_problem, NalgebraLU};
+
+    type Mcpu = nalgebra::DMatrix<f64>;
+
+    // linear interpolation between the boundary values, y(t) = 4 - 3t, y'(t) = -3
+    fn initial_guess(t: &[f64]) -> Mcpu {
+        let mut y = Mcpu::zeros(2, t.len());
+        for (i, &t) in t.iter().enumerate() {
+            y[(0, i)] = 4.0 - 3.0 * t;
+            y[(1, i)] = -3.0;
+        }
+        y
+    }
+
+    fn mesh(n: usize) -> Vec<f64> {
+        (0..=n).map(|i| i as f64 / n as f64).collect()
+    }
+
+    #[test]
+    fn bvp_multiple_shooting_quadratic() {
+        let (problem, soln) = quadratic_bvp_problem::<Mcpu>();
+        let t = mesh(4);
+        let bvp_soln = problem
+            .multiple_shooting::<NalgebraLU<f64>>(&t, &initial_guess(&t))
+            .unwrap();
+        assert!(bvp_soln.niter > 1);
+        assert_eq!(bvp_soln.t, t);
+        for (i, point) in soln.solution_points.iter().enumerate() {
+            assert!((bvp_soln.t[i] - point.t).abs() < 1e-10);
+            let y = bvp_soln.y.column(i).into_owned();
+            let tol = 1e-4 * point.state.norm();
+            assert!((y - &point.state).norm() < tol);
+        }
+    }
+
+    #[test]
+    fn bvp_collocation_quadratic() {
+        let (problem, soln) = quadratic_bvp_problem::<Mcpu>();
+        let t = mesh(4);
+        let bvp_soln = problem.collocation(&t, &initial_guess(&t)).unwrap();
+        assert!(bvp_soln.niter > 1);
+        for (i, point) in soln.solution_points.iter().enumerate() {
+            let y = bvp_soln.y.column(i).into_owned();
+            let tol = 1e-2 * point.state.norm();
+            assert!((y - &point.state).norm() < tol);
+        }
+
+        // Hermite-Simpson collocation is fourth order, so refining the mesh should reduce the error
+        let error = |t: &[f64], y: &Mcpu| {
+            t.iter()
+                .enumerate()
+                .map(|(i, &t)| (y[(0, i)] - 4.0 / ((1.0 + t) * (1.0 + t))).abs())
+                .fold(0.0, f64::max)
+        };
+        let t_fine = mesh(16);
+        let bvp_soln_fine = problem
+            .collocation(&t_fine, &initial_guess(&t_fine))
+            .unwrap();
+        assert!(error(&t_fine, &bvp_soln_fine.y) < 0.1 * error(&t, &bvp_soln.y));
+    }
+
+    #[test]
+    fn bvp_invalid_mesh() {
+        let (problem, _soln) = quadratic_bvp_problem::<Mcpu>();
+        let t = vec![0.0, 0.5, 0.5, 1.0];
+        assert!(problem.collocation(&t, &initial_guess(&t)).is_err());
+        let t = mesh(4);
+        assert!(problem.collocation(&t, &Mcpu::zeros(2, 3)).is_err());
+    }
+}
@@ -66,19 +66,84 @@ where
         Ok(())
     }
 
+    /// Append a new point to the end of the interpolator. The time `t` must be greater than the last time in the interpolator.
+    pub fn push(&mut self, y: V, ydot: V, t: V::T) {
+        assert!(
+            self.ts.last().is_none_or(|&t_last| t > t_last),
+            "HermiteInterpolator times must be strictly increasing"
+        );
+        self.ys.push(y);
+        self.ydots.push(ydot);
+        self.ts.push(t);
+    }
+
+    /// Remove all the points with times at or after `t`.
+    pub fn truncate(&mut self, t: V::T) {
+        let n = self.ts.partition_point(|&t0| t0 < t);
+        self.ys.truncate(n);
+        self.ydots.truncate(n);
+        self.ts.truncate(n);
+    }
+
+    pub fn clear(&mut self) {
+        self.ys.clear();
+        self.ydots.clear();
+        self.ts.clear();
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.ts.is_empty()
+    }
+
+    /// the first time in the interpolator, or None if it is empty
+    pub fn first_time(&self) -> Option<V::T> {
+        self.ts.first().copied()
+    }
+
+    /// the last time in the interpolator, or None if it is empty
+    pub fn last_time(&self) -> Option<V::T> {
+        self.ts.last().copied()
+    }
+
     pub fn interpolate(&self, t: V::T, y: &mut V) -> Option<()> {
-        if t < self.ts[0] || t > self.ts[self.ts.len() - 1] {
+        if self.ts.is_empty() || t < self.ts[0] || t > self.ts[self.ts.len() - 1] {
             return None;
         }
         if t == self.ts[0] {
             y.copy_from(&self.ys[0]);
             return Some(());
         }
+        // index of the first time greater than t
         let idx = self
             .ts
-            .iter()
-            .position(|&t0| t0 > t)
-            .unwrap_or(self.ts.len() - 1);
+            .partition_point(|&t0| t0 <= t)
+            .min(self.ts.len() - 1);
+        self.hermite(idx, t, y);
+        Some(())
+    }
+
+    /// Evaluate the solution at any time `t` after the first time in the interpolator. Times within the interpolator are interpolated,
+    /// times after the last time are extrapolated using the cubic Hermite polynomial of the last segment (or a linear
+    /// extrapolation if there is only a single point). Returns None if the interpolator is empty or `t` is before the first time.
+    pub fn extrapolate(&self, t: V::T, y: &mut V) -> Option<()> {
+        let n = self.ts.len();
+        if n == 0 || t < self.ts[0] {
+            return None;
+        }
+        if t <= self.ts[n - 1] {
+            return self.interpolate(t, y);
+        }
+        if n == 1 {
+            y.copy_from(&self.ys[0]);
+            y.axpy(t - self.ts[0], &self.ydots[0], V::T::one());
+        } else {
+            self.hermite(n - 1, t, y);
+        }
+        Some(())
+    }
+
+    // evaluate the cubic Hermite polynomial of the segment ending at index idx at time t
+    fn hermite(&self, idx: usize, t: V::T, y: &mut V) {
         let t0 = self.ts[idx - 1];
         let t1 = self.ts[idx];
         let h = t1 - t0;
@@ -102,7 +167,6 @@ where
             theta * (theta - V::T::from(1.0)),
         );
         y.axpy(theta, u1, V::T::one());
-        Some(())
     }
 }
 
@@ -271,4 +335,36 @@ mod tests {
             y.assert_eq_norm(&point.state, &problem.atol, problem.rtol, 10.0);
         }
     }
+
+    #[test]
+    fn test_hermite_interpolator_push_and_extrapolate() {
+        // y = t^3 is represented exactly by a cubic Hermite polynomial
+        let y = |t: f64| DVector::from_vec(vec![t * t * t]);
+        let dy = |t: f64| DVector::from_vec(vec![3.0 * t * t]);
+        let mut interpolator = HermiteInterpolator::default();
+        assert!(interpolator.is_empty());
+        let mut out = DVector::zeros(1);
+        assert!(interpolator.extrapolate(0.0, &mut out).is_none());
+
+        // a single point is extrapolated linearly
+        interpolator.push(y(1.0), dy(1.0), 1.0);
+        interpolator.extrapolate(2.0, &mut out).unwrap();
+        out.assert_eq_st(&DVector::from_vec(vec![4.0]), 1e-12);
+
+        interpolator.push(y(2.0), dy(2.0), 2.0);
+        interpolator.push(y(3.0), dy(3.0), 3.0);
+        assert_eq!(interpolator.first_time(), Some(1.0));
+        assert_eq!(interpolator.last_time(), Some(3.0));
+        for t in [1.0, 1.5, 2.0, 2.7, 3.0, 3.5] {
+            interpolator.extrapolate(t, &mut out).unwrap();
+            out.assert_eq_st(&y(t), 1e-12);
+        }
+        assert!(interpolator.interpolate(3.5, &mut out).is_none());
+        assert!(interpolator.extrapolate(0.5, &mut out).is_none());
+
+        interpolator.truncate(2.0);
+        assert_eq!(interpolator.last_time(), Some(1.0));
+        interpolator.clear();
+        assert!(interpolator.is_empty());
+    }
 }
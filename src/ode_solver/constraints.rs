@@ -0,0 +1,187 @@
+use num_traits::abs;
+
+use crate::{
+    error::{DiffsolError, OdeSolverError},
+    ode_solver_error, Scalar,
+};
+
+/// An inequality constraint on a component of the state, as used by the CVODE and IDA solvers of SUNDIALS.
+///
+/// Constraints are set for each state component using [crate::OdeBuilder::constraints] (stored in [crate::SolverOptions::constraints]),
+/// and are enforced by the [crate::Bdf] and [crate::Sdirk] solvers: if the solution at the end of a step violates a constraint then the step is
+/// rejected and retried with a smaller step size, chosen so that the solution (if it varies linearly over the step) stays within the constraint.
+/// A component that violates its constraint by less than a tenth of its tolerance is instead moved back onto the boundary of the constraint.
+/// The number of steps rejected in this way is given by [crate::ode_solver::bdf::BdfStatistics::number_of_constraint_failures].
+/// This is useful for models of concentrations (e.g. chemical kinetics), where small negative values caused by numerical error can make the solution blow up.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Constraint {
+    /// No constraint
+    #[default]
+    None,
+    /// `y >= 0`
+    NonNegative,
+    /// `y > 0`
+    Positive,
+    /// `y <= 0`
+    NonPositive,
+    /// `y < 0`
+    Negative,
+}
+
+impl Constraint {
+    /// Returns true if `y` satisfies the constraint
+    pub fn is_satisfied<T: Scalar>(&self, y: T) -> bool {
+        match self {
+            Constraint::None => true,
+            Constraint::NonNegative => y >= T::zero(),
+            Constraint::Positive => y > T::zero(),
+            Constraint::NonPositive => y <= T::zero(),
+            Constraint::Negative => y < T::zero(),
+        }
+    }
+}
+
+// the step size is multiplied by a factor between MIN_FACTOR and MAX_FACTOR after a constraint failure
+const MAX_FACTOR: f64 = 0.9;
+const MIN_FACTOR: f64 = 0.1;
+
+// a component that violates its constraint by less than this fraction of its tolerance is corrected instead
+const MAX_CORRECTION: f64 = 0.1;
+
+/// Check that the initial state `y` satisfies the constraints
+pub(crate) fn check_constraints<T: Scalar>(
+    constraints: &[Constraint],
+    y: &[T],
+) -> Result<(), DiffsolError> {
+    if let Some(i) = constraints
+        .iter()
+        .zip(y.iter())
+        .position(|(c, &y)| !c.is_satisfied(y))
+    {
+        return Err(ode_solver_error!(
+            Other,
+            format!(
+                "Initial state component {} = {} does not satisfy the constraint {:?}",
+                i, y[i], constraints[i]
+            )
+        ));
+    }
+    Ok(())
+}
+
+/// Check the solution `y_new` at the end of a step starting from `y_old`. A component that violates its constraint by less than a tenth of its
+/// tolerance `atol_i + rtol |y_old_i|` (e.g. due to rounding errors) is moved onto the boundary of the constraint (or just inside it for a strict
+/// inequality), and `None` is returned if there are no other violations. Otherwise returns the factor to reduce the step size by, so that the first
+/// component to violate its constraint (assuming that the solution varies linearly over the step) stays within the constraint.
+pub(crate) fn constraint_step_factor<T: Scalar>(
+    constraints: &[Constraint],
+    y_old: &[T],
+    y_new: &mut [T],
+    atol: &[T],
+    rtol: T,
+) -> Option<T> {
+    let max_correction = T::from(MAX_CORRECTION);
+    let mut fraction: Option<T> = None;
+    let mut correct = true;
+    for (((c, &y0), &y1), &a) in constraints
+        .iter()
+        .zip(y_old.iter())
+        .zip(y_new.iter())
+        .zip(atol.iter())
+    {
+        if c.is_satisfied(y1) {
+            continue;
+        }
+        if abs(y1) > max_correction * (a + rtol * abs(y0)) {
+            correct = false;
+        }
+        // fraction of the step at which the component reaches zero
+        let r = if y0 != y1 { y0 / (y0 - y1) } else { T::zero() };
+        if fraction.is_none_or(|f| r < f) {
+            fraction = Some(r);
+        }
+    }
+    if correct {
+        for (((c, &y0), y1), &a) in constraints
+            .iter()
+            .zip(y_old.iter())
+            .zip(y_new.iter_mut())
+            .zip(atol.iter())
+        {
+            if !c.is_satisfied(*y1) {
+                let tol = max_correction * (a + rtol * abs(y0));
+                *y1 = match c {
+                    Constraint::Positive => tol,
+                    Constraint::Negative => -tol,
+                    _ => T::zero(),
+                };
+            }
+        }
+        return None;
+    }
+    fraction.map(|r| {
+        let factor = T::from(MAX_FACTOR) * r;
+        if factor < T::from(MIN_FACTOR) {
+            T::from(MIN_FACTOR)
+        } else if factor > T::from(MAX_FACTOR) {
+            T::from(MAX_FACTOR)
+        } else {
+            factor
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{check_constraints, constraint_step_factor, Constraint};
+
+    #[test]
+    fn test_constraints() {
+        let constraints = [
+            Constraint::None,
+            Constraint::NonNegative,
+            Constraint::Positive,
+            Constraint::NonPositive,
+            Constraint::Negative,
+        ];
+        assert!(check_constraints(&constraints, &[-1.0, 0.0, 1.0, 0.0, -1.0]).is_ok());
+        assert!(check_constraints(&constraints, &[-1.0, 0.0, 0.0, 0.0, -1.0]).is_err());
+        assert!(check_constraints(&constraints, &[-1.0, 0.0, 1.0, 0.0, 0.0]).is_err());
+
+        let y_old = [1.0, 1.0, 1.0, -1.0, -1.0];
+        let atol = [1e-6; 5];
+        let rtol = 1e-6;
+        let mut y_new = [-1.0, 0.5, 0.5, -0.5, -0.5];
+        assert_eq!(
+            constraint_step_factor(&constraints, &y_old, &mut y_new, &atol, rtol),
+            None
+        );
+        assert_eq!(y_new, [-1.0, 0.5, 0.5, -0.5, -0.5]);
+
+        // the non-negative component reaches zero a quarter of the way through the step
+        let mut y_new = [-1.0, -3.0, 0.5, -0.5, -0.5];
+        let factor = constraint_step_factor(&constraints, &y_old, &mut y_new, &atol, rtol).unwrap();
+        assert!((factor - 0.9 * 0.25).abs() < 1e-12);
+
+        // the factor is bounded below
+        let mut y_new = [-1.0, 0.5, 0.5, 0.5, -0.5];
+        let factor = constraint_step_factor(
+            &constraints,
+            &[1.0, 1.0, 1.0, -1e-6, -1.0],
+            &mut y_new,
+            &atol,
+            rtol,
+        )
+        .unwrap();
+        assert_eq!(factor, 0.1);
+
+        // violations smaller than a tenth of the tolerance are moved onto (or just inside) the constraint boundary
+        let mut y_new = [-1.0, -1e-8, -1e-8, 1e-8, 1e-8];
+        assert_eq!(
+            constraint_step_factor(&constraints, &y_old, &mut y_new, &atol, rtol),
+            None
+        );
+        let tol = 0.1 * (1e-6 + 1e-6 * 1.0);
+        assert_eq!(y_new, [-1.0, 0.0, tol, 0.0, -tol]);
+    }
+}
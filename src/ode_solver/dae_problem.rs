@@ -0,0 +1,132 @@
+use nalgebra::ComplexField;
+
+use crate::{
+    error::DiffsolError, op::init::ResidualInitOp, BdfDae, BdfState, Convergence,
+    DefaultDenseMatrix, LinearSolver, NewtonNonlinearSolver, NonLinearSolver, OdeEquationsResidual,
+    OdeSolverState, Vector, VectorRef,
+};
+
+use num_traits::Zero;
+
+use super::state::StateCommon;
+
+/// A fully implicit differential-algebraic equation (DAE) problem of the form
+///
+/// $$
+///  F(t, y, y') = 0
+///  y(t_0) = y_0(t_0)
+/// $$
+///
+/// The equations are given by [Self::eqn] (see [OdeEquationsResidual]), and the remaining fields give the tolerances, initial time and initial step size.
+///
+/// DAE problems can be created using [crate::OdeBuilder::build_residual], and solved using the [BdfDae] solver (e.g. [Self::bdf_dae]).
+pub struct DaeProblem<Eqn: OdeEquationsResidual> {
+    pub eqn: Eqn,
+    pub rtol: Eqn::T,
+    pub atol: Eqn::V,
+    pub t0: Eqn::T,
+    pub h0: Eqn::T,
+}
+
+impl<Eqn: OdeEquationsResidual> DaeProblem<Eqn> {
+    pub fn new(eqn: Eqn, rtol: Eqn::T, atol: Eqn::V, t0: Eqn::T, h0: Eqn::T) -> Self {
+        Self {
+            eqn,
+            rtol,
+            atol,
+            t0,
+            h0,
+        }
+    }
+
+    pub fn eqn(&self) -> &Eqn {
+        &self.eqn
+    }
+
+    pub fn eqn_mut(&mut self) -> &mut Eqn {
+        &mut self.eqn
+    }
+
+    pub fn set_params(&mut self, p: &Eqn::V) {
+        self.eqn.set_params(p);
+    }
+
+    /// Calculate consistent initial algebraic variables `y` and derivatives `dy` at time `t`, keeping the differential variables of `y`
+    /// and the algebraic variables of `dy` fixed (see [ResidualInitOp]).
+    pub fn set_consistent<S: NonLinearSolver<Eqn::M>>(
+        &self,
+        y: &mut Eqn::V,
+        dy: &mut Eqn::V,
+        t: Eqn::T,
+        root_solver: &mut S,
+    ) -> Result<(), DiffsolError> {
+        let f = ResidualInitOp::new(&self.eqn, y, dy);
+        root_solver.set_problem(&f);
+        let mut x = f.initial_guess();
+        let xerr = x.clone();
+        root_solver.reset_jacobian(&f, &x, t);
+        let mut convergence = Convergence::new(self.rtol, &self.atol);
+        root_solver.solve_in_place_with_jacobian(&f, &mut x, t, &xerr, &mut convergence)?;
+        f.scatter_soln(&x, y, dy);
+        Ok(())
+    }
+}
+
+impl<Eqn> DaeProblem<Eqn>
+where
+    Eqn: OdeEquationsResidual,
+    Eqn::V: DefaultDenseMatrix,
+    for<'b> &'b Eqn::V: VectorRef<Eqn::V>,
+{
+    /// Create a new state for the [BdfDae] solver, with consistent initial algebraic variables and derivatives.
+    pub fn bdf_dae_state<LS: LinearSolver<Eqn::M>>(
+        &self,
+    ) -> Result<BdfState<Eqn::V>, DiffsolError> {
+        let nstates = self.eqn.nstates();
+        let mut y = Eqn::V::zeros(nstates);
+        self.eqn.init_inplace(self.t0, &mut y);
+        let mut dy = Eqn::V::zeros(nstates);
+        let mut root_solver = NewtonNonlinearSolver::new(LS::default());
+        self.set_consistent(&mut y, &mut dy, self.t0, &mut root_solver)?;
+
+        // size of first step, as in the ODE solvers but only using the initial state and derivative since
+        // the right-hand side is not available for residual equations
+        let d0 = y.squared_norm(&y, &self.atol, self.rtol).sqrt();
+        let d1 = dy.squared_norm(&y, &self.atol, self.rtol).sqrt();
+        let mut h = if d0 < Eqn::T::from(1e-5) || d1 < Eqn::T::from(1e-5) {
+            Eqn::T::from(1e-6)
+        } else {
+            Eqn::T::from(0.01) * (d0 / d1)
+        };
+        if self.h0 < Eqn::T::zero() {
+            h = -h;
+        }
+        Ok(BdfState::new_from_common(StateCommon {
+            y,
+            dy,
+            g: Eqn::V::zeros(0),
+            dg: Eqn::V::zeros(0),
+            s: Vec::new(),
+            ds: Vec::new(),
+            sg: Vec::new(),
+            dsg: Vec::new(),
+            t: self.t0,
+            h,
+        }))
+    }
+
+    pub fn bdf_dae_solver<LS: LinearSolver<Eqn::M>>(
+        &self,
+        state: BdfState<Eqn::V>,
+    ) -> Result<BdfDae<'_, Eqn, NewtonNonlinearSolver<Eqn::M, LS>>, DiffsolError> {
+        let newton_solver = NewtonNonlinearSolver::new(LS::default());
+        BdfDae::new(self, state, newton_solver)
+    }
+
+    pub fn bdf_dae<LS: LinearSolver<Eqn::M>>(
+        &self,
+    ) -> Result<BdfDae<'_, Eqn, NewtonNonlinearSolver<Eqn::M, LS>>, DiffsolError> {
+        let state = self.bdf_dae_state::<LS>()?;
+        self.bdf_dae_solver(state)
+    }
+}
@@ -0,0 +1,569 @@
+use nalgebra::ComplexField;
+use std::marker::PhantomData;
+
+use crate::error::{DiffsolError, OdeSolverError};
+use crate::{
+    ode_solver_error, NonLinearOp, OdeEquationsDelay, OdeSolverMethod, OdeSolverProblem,
+    OdeSolverStopReason, Scalar, StateRef, StateRefMut, Vector,
+};
+use num_traits::{abs, One, Zero};
+
+// a discontinuity in the solution or one of its derivatives, a discontinuity of level `k` is a
+// jump in the `k`-th derivative of the solution
+#[derive(Clone)]
+struct Discontinuity<T: Scalar> {
+    t: T,
+    level: usize,
+    // the time of the discontinuity propagated through each delay, if it has been reached
+    children: Vec<Option<T>>,
+    // the rate of change of `t - tau_i(t, y(t))` over the last step, used to predict where the next child is
+    slopes: Vec<T>,
+}
+
+impl<T: Scalar> Discontinuity<T> {
+    fn new(t: T, level: usize, ndelays: usize) -> Self {
+        Self {
+            t,
+            level,
+            children: vec![None; ndelays],
+            slopes: vec![T::one(); ndelays],
+        }
+    }
+}
+
+/// A solver for delay differential equations (see [OdeEquationsDelay]) that uses another solver (e.g. [crate::Bdf] or [crate::Sdirk]) to take each step.
+///
+/// The accepted steps of the solution are stored in the history of the equations (see [OdeEquationsDelay::history]), which is used to evaluate the delayed states
+/// using Hermite interpolation. If a delayed time is later than the last accepted step (i.e. a delay is smaller than the current step size),
+/// the delayed state is extrapolated from the last accepted step.
+///
+/// The solution of a DDE generally has discontinuities in its derivatives, starting with the jump in the first derivative at the initial time, and propagated by
+/// the delays, so that a discontinuity at time `d` gives another discontinuity of one higher level at each time `t` where `t - tau_i(t, y(t)) = d`.
+/// These are tracked up to a maximum level (see [Self::set_max_level]), and the stop time of the inner solver is set so that it steps exactly onto each predicted
+/// discontinuity, after which the inner solver is restarted. For state-dependent delays the prediction is refined after each step, if a discontinuity is
+/// passed within a step it is located by bisection using the dense output of the inner solver.
+///
+/// Note that the history is stored in the problem, so only one solver should be used with a problem at a time. The history is reset when the solver is created.
+///
+/// Restrictions:
+/// - Forward sensitivities and adjoints are not supported.
+/// - The delays must be non-negative.
+pub struct Dde<'a, Eqn, Method>
+where
+    Eqn: OdeEquationsDelay,
+    Method: OdeSolverMethod<'a, Eqn>,
+{
+    solver: Method,
+    discontinuities: Vec<Discontinuity<Eqn::T>>,
+    max_level: usize,
+    tstop: Option<Eqn::T>,
+    solver_tstop: Option<Eqn::T>,
+    old_y: Eqn::V,
+    tau_old: Eqn::V,
+    tau_new: Eqn::V,
+    is_state_mutated: bool,
+    // the inner solver is restarted at the start of the next step, so that the last step can still be interpolated
+    restart_solver: bool,
+    _phantom: PhantomData<&'a Eqn>,
+}
+
+impl<'a, Eqn, Method> Clone for Dde<'a, Eqn, Method>
+where
+    Eqn: OdeEquationsDelay,
+    Method: OdeSolverMethod<'a, Eqn>,
+{
+    fn clone(&self) -> Self {
+        Self {
+            solver: self.solver.clone(),
+            discontinuities: self.discontinuities.clone(),
+            max_level: self.max_level,
+            tstop: self.tstop,
+            solver_tstop: self.solver_tstop,
+            old_y: self.old_y.clone(),
+            tau_old: self.tau_old.clone(),
+            tau_new: self.tau_new.clone(),
+            is_state_mutated: self.is_state_mutated,
+            restart_solver: self.restart_solver,
+            _phantom: PhantomData,
+        }
+    }
+}
+
+impl<'a, Eqn, Method> Dde<'a, Eqn, Method>
+where
+    Eqn: OdeEquationsDelay,
+    Method: OdeSolverMethod<'a, Eqn>,
+{
+    /// the default maximum level of the tracked discontinuities, which is sufficient for solvers up to fifth order
+    pub const DEFAULT_MAX_LEVEL: usize = 5;
+
+    const MAX_BISECTION_ITER: usize = 100;
+
+    /// Create a new DDE solver that uses `solver` to take each step, starting from the current state of `solver`.
+    pub fn new(solver: Method) -> Result<Self, DiffsolError> {
+        let eqn = &solver.problem().eqn;
+        let ndelays = eqn.ndelays();
+        let state = solver.state();
+        eqn.history().borrow_mut().clear();
+        Self::push_history(eqn, state.y, state.t);
+        let discontinuities = vec![Discontinuity::new(state.t, 0, ndelays)];
+        let old_y = state.y.clone();
+        Ok(Self {
+            solver,
+            discontinuities,
+            max_level: Self::DEFAULT_MAX_LEVEL,
+            tstop: None,
+            solver_tstop: None,
+            old_y,
+            tau_old: Eqn::V::zeros(ndelays),
+            tau_new: Eqn::V::zeros(ndelays),
+            is_state_mutated: false,
+            restart_solver: false,
+            _phantom: PhantomData,
+        })
+    }
+
+    /// Set the maximum level of the discontinuities that are tracked. Discontinuities in derivatives higher than the order of the inner solver do not affect its accuracy.
+    pub fn set_max_level(&mut self, max_level: usize) {
+        self.max_level = max_level;
+    }
+
+    pub fn max_level(&self) -> usize {
+        self.max_level
+    }
+
+    /// the inner solver used to take each step
+    pub fn solver(&self) -> &Method {
+        &self.solver
+    }
+
+    /// the times and levels of the discontinuities found so far, in increasing order of time
+    pub fn discontinuities(&self) -> Vec<(Eqn::T, usize)> {
+        self.discontinuities
+            .iter()
+            .map(|d| (d.t, d.level))
+            .collect()
+    }
+
+    // the tolerance used to decide if a discontinuity has been reached
+    fn tolerance(t: Eqn::T) -> Eqn::T {
+        Eqn::T::EPSILON.sqrt() * (Eqn::T::one() + abs(t))
+    }
+
+    fn add_discontinuity(&mut self, t: Eqn::T, level: usize) {
+        let tol = Self::tolerance(t);
+        if self
+            .discontinuities
+            .iter()
+            .any(|d| abs(d.t - t) <= tol && d.level <= level)
+        {
+            return;
+        }
+        let ndelays = self.problem().eqn.ndelays();
+        let idx = self.discontinuities.partition_point(|d| d.t < t);
+        self.discontinuities
+            .insert(idx, Discontinuity::new(t, level, ndelays));
+    }
+
+    // add the solution y at time t to the end of the history. The derivative of the inner solver can be low order
+    // (e.g. Bdf uses a backward difference), so the rhs is evaluated instead
+    fn push_history(eqn: &Eqn, y: &Eqn::V, t: Eqn::T) {
+        let dy = eqn.rhs().call(y, t);
+        eqn.history().borrow_mut().push(y.clone(), dy, t);
+    }
+
+    // remove all the history and discontinuities after time t, and add the current state of the inner solver to the history
+    fn reset_history(&mut self) {
+        let eqn = &self.problem().eqn;
+        let state = self.solver.state();
+        let t = state.t;
+        eqn.history().borrow_mut().truncate(t);
+        Self::push_history(eqn, state.y, t);
+        let tol = Self::tolerance(t);
+        self.discontinuities.retain(|d| d.t <= t + tol);
+        for d in self.discontinuities.iter_mut() {
+            for child in d.children.iter_mut() {
+                if child.is_some_and(|c| c > t + tol) {
+                    *child = None;
+                }
+            }
+        }
+    }
+
+    // locate the time in (t0, t1] where `t - tau_i(t, y(t)) = d` using bisection on the dense output of the inner solver
+    fn locate(
+        &self,
+        d: Eqn::T,
+        i: usize,
+        mut t0: Eqn::T,
+        mut t1: Eqn::T,
+    ) -> Result<Eqn::T, DiffsolError> {
+        let eqn = &self.problem().eqn;
+        let tol = Self::tolerance(t1);
+        let two = Eqn::T::from(2.0);
+        let mut tau = Eqn::V::zeros(eqn.ndelays());
+        for _ in 0..Self::MAX_BISECTION_ITER {
+            if t1 - t0 <= tol {
+                break;
+            }
+            let t_mid = (t0 + t1) / two;
+            let y_mid = self.solver.interpolate(t_mid)?;
+            eqn.delays_inplace(&y_mid, t_mid, &mut tau);
+            if t_mid - tau[i] - d >= Eqn::T::zero() {
+                t1 = t_mid;
+            } else {
+                t0 = t_mid;
+            }
+        }
+        Ok(t1)
+    }
+}
+
+impl<'a, Eqn, Method> OdeSolverMethod<'a, Eqn> for Dde<'a, Eqn, Method>
+where
+    Eqn: OdeEquationsDelay,
+    Method: OdeSolverMethod<'a, Eqn>,
+{
+    type State = Method::State;
+
+    fn problem(&self) -> &'a OdeSolverProblem<Eqn> {
+        self.solver.problem()
+    }
+
+    fn order(&self) -> usize {
+        self.solver.order()
+    }
+
+    fn checkpoint(&mut self) -> Self::State {
+        self.solver.checkpoint()
+    }
+
+    fn set_state(&mut self, state: Self::State) {
+        self.solver.set_state(state);
+        self.reset_history();
+        self.is_state_mutated = false;
+        self.restart_solver = false;
+    }
+
+    fn into_state(self) -> Self::State {
+        self.solver.into_state()
+    }
+
+    fn state(&self) -> StateRef<Eqn::V> {
+        self.solver.state()
+    }
+
+    fn state_mut(&mut self) -> StateRefMut<Eqn::V> {
+        self.is_state_mutated = true;
+        self.solver.state_mut()
+    }
+
+    fn reverse_direction(&mut self) -> Result<(), DiffsolError> {
+        // the history is only given before the initial time, so delay equations can only be integrated forwards in time
+        Err(ode_solver_error!(BackwardIntegrationNotSupported))
+    }
+
+    fn set_stop_time(&mut self, tstop: Eqn::T) -> Result<(), DiffsolError> {
+        // the history is only given before the initial time, so delay equations can only be integrated forwards in time
+        let t = self.solver.state().t;
+        if tstop < t {
+            return Err(DiffsolError::from(
+                OdeSolverError::StopTimeBeforeCurrentTime {
+                    stop_time: tstop.into(),
+                    state_time: t.into(),
+                },
+            ));
+        }
+        self.solver.set_stop_time(tstop)?;
+        self.solver_tstop = Some(tstop);
+        self.tstop = Some(tstop);
+        Ok(())
+    }
+
+    fn step(&mut self) -> Result<OdeSolverStopReason<Eqn::T>, DiffsolError> {
+        let eqn = &self.problem().eqn;
+        let ndelays = eqn.ndelays();
+
+        // a mutated state is a jump in the solution
+        if self.is_state_mutated {
+            self.reset_history();
+            self.add_discontinuity(self.solver.state().t, 0);
+            self.is_state_mutated = false;
+        }
+
+        // restart the inner solver if the last step landed on a discontinuity
+        if self.restart_solver {
+            self.solver.state_mut();
+            self.restart_solver = false;
+        }
+
+        let t_old = self.solver.state().t;
+        self.old_y.copy_from(self.solver.state().y);
+        eqn.delays_inplace(&self.old_y, t_old, &mut self.tau_old);
+
+        // predict the next discontinuity, any that have already been reached (e.g. for vanishing delays) are propagated
+        let tol = Self::tolerance(t_old);
+        let mut target = self.tstop;
+        let mut new_discontinuities = Vec::new();
+        for d in self
+            .discontinuities
+            .iter_mut()
+            .filter(|d| d.level < self.max_level)
+        {
+            for i in 0..ndelays {
+                if d.children[i].is_some() {
+                    continue;
+                }
+                let g = t_old - self.tau_old[i] - d.t;
+                if g >= -tol {
+                    d.children[i] = Some(t_old);
+                    new_discontinuities.push((t_old, d.level + 1));
+                } else if d.slopes[i] > Eqn::T::zero() {
+                    let t_pred = t_old - g / d.slopes[i];
+                    if t_pred > t_old + tol && target.is_none_or(|t| t_pred < t) {
+                        target = Some(t_pred);
+                    }
+                }
+            }
+        }
+        for (t, level) in new_discontinuities.drain(..) {
+            self.add_discontinuity(t, level);
+        }
+
+        // step the inner solver, stopping at the next discontinuity or the stop time
+        if let Some(target) = target {
+            if self.solver_tstop != Some(target) {
+                self.solver.set_stop_time(target)?;
+                self.solver_tstop = Some(target);
+            }
+        }
+        let reason = self.solver.step()?;
+        let t_new = self.solver.state().t;
+        Self::push_history(eqn, self.solver.state().y, t_new);
+
+        // find the discontinuities that have been reached or passed during the step
+        eqn.delays_inplace(self.solver.state().y, t_new, &mut self.tau_new);
+        let tol = Self::tolerance(t_new);
+        let mut located = Vec::new();
+        for (j, d) in self.discontinuities.iter_mut().enumerate() {
+            if d.level >= self.max_level {
+                continue;
+            }
+            for i in 0..ndelays {
+                if d.children[i].is_some() {
+                    continue;
+                }
+                let g_old = t_old - self.tau_old[i] - d.t;
+                let g_new = t_new - self.tau_new[i] - d.t;
+                d.slopes[i] = (g_new - g_old) / (t_new - t_old);
+                if g_new >= -tol {
+                    if g_new <= tol {
+                        d.children[i] = Some(t_new);
+                        new_discontinuities.push((t_new, d.level + 1));
+                    } else {
+                        located.push((j, i));
+                    }
+                }
+            }
+        }
+        for (j, i) in located {
+            let (d, level) = (self.discontinuities[j].t, self.discontinuities[j].level);
+            let t = self.locate(d, i, t_old, t_new)?;
+            self.discontinuities[j].children[i] = Some(t);
+            new_discontinuities.push((t, level + 1));
+        }
+        for (t, level) in new_discontinuities {
+            self.add_discontinuity(t, level);
+        }
+
+        match reason {
+            OdeSolverStopReason::TstopReached => {
+                let is_user_tstop = self.solver_tstop == self.tstop;
+                self.solver_tstop = None;
+
+                // restart the inner solver on the next step if we have landed on a discontinuity
+                let tol = Self::tolerance(t_new);
+                if self.discontinuities.iter().any(|d| abs(d.t - t_new) <= tol) {
+                    self.restart_solver = true;
+                }
+                if is_user_tstop {
+                    self.tstop = None;
+                    Ok(OdeSolverStopReason::TstopReached)
+                } else {
+                    Ok(OdeSolverStopReason::InternalTimestep)
+                }
+            }
+            reason => Ok(reason),
+        }
+    }
+
+    fn interpolate(&self, t: Eqn::T) -> Result<Eqn::V, DiffsolError> {
+        self.solver.interpolate(t)
+    }
+
+    fn interpolate_out(&self, t: Eqn::T) -> Result<Eqn::V, DiffsolError> {
+        self.solver.interpolate_out(t)
+    }
+
+    fn interpolate_sens(&self, t: Eqn::T) -> Result<Vec<Eqn::V>, DiffsolError> {
+        self.solver.interpolate_sens(t)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        error::{DiffsolError, OdeSolverError},
+        ode_solver::{
+            test_models::delay_decay::{delay_decay_problem, delay_decay_state_dependent_problem},
+            tests::test_ode_solver,
+        },
+        NalgebraLU, OdeEquationsDelay, OdeSolverMethod, OdeSolverStopReason, Vector,
+    };
+
+    use num_traits::abs;
+
+    type M = nalgebra::DMatrix<f64>;
+    type LS = NalgebraLU<f64>;
+
+    fn assert_has_discontinuity(discontinuities: &[(f64, usize)], t: f64, level: usize) {
+        assert!(
+            discontinuities
+                .iter()
+                .any(|&(td, l)| abs(td - t) < 1e-6 && l == level),
+            "no discontinuity of level {} at t = {}, found {:?}",
+            level,
+            t,
+            discontinuities
+        );
+    }
+
+    #[test]
+    fn test_dde_bdf_nalgebra_delay_decay() {
+        let (problem, soln) = delay_decay_problem::<M>(false);
+        let mut s = problem.dde_bdf::<LS>().unwrap();
+        test_ode_solver(&mut s, soln, None, false, false);
+        let discontinuities = s.discontinuities();
+        for (i, t) in [0.0, 1.0, 2.0, 3.0].into_iter().enumerate() {
+            assert_has_discontinuity(&discontinuities, t, i);
+        }
+    }
+
+    #[test]
+    fn test_dde_tr_bdf2_nalgebra_delay_decay() {
+        let (problem, soln) = delay_decay_problem::<M>(false);
+        let mut s = problem.dde_tr_bdf2::<LS>().unwrap();
+        // second order method, and the rhs does not damp the local errors, so they accumulate
+        test_ode_solver(&mut s, soln, Some(1e-4), true, false);
+        let discontinuities = s.discontinuities();
+        for (i, t) in [0.0, 1.0, 2.0, 3.0].into_iter().enumerate() {
+            assert_has_discontinuity(&discontinuities, t, i);
+        }
+    }
+
+    #[test]
+    fn test_dde_lands_on_discontinuities() {
+        let (problem, _soln) = delay_decay_problem::<M>(false);
+        let mut s = problem.dde_bdf::<LS>().unwrap();
+        s.set_stop_time(3.5).unwrap();
+        let mut ts = Vec::new();
+        while s.step().unwrap() != OdeSolverStopReason::TstopReached {
+            ts.push(s.state().t);
+        }
+        for t in [1.0, 2.0, 3.0] {
+            assert!(
+                ts.iter().any(|&ts| abs(ts - t) < 1e-10),
+                "no step at t = {}",
+                t
+            );
+        }
+        assert_eq!(s.state().t, 3.5);
+    }
+
+    #[test]
+    fn test_dde_max_level() {
+        let (problem, _soln) = delay_decay_problem::<M>(false);
+        let mut s = problem.dde_bdf::<LS>().unwrap();
+        assert_eq!(s.max_level(), 5);
+        s.set_max_level(2);
+        s.set_stop_time(4.5).unwrap();
+        while s.step().unwrap() != OdeSolverStopReason::TstopReached {}
+        let discontinuities = s.discontinuities();
+        assert_eq!(discontinuities.len(), 3);
+        assert_has_discontinuity(&discontinuities, 2.0, 2);
+    }
+
+    #[test]
+    fn test_dde_bdf_nalgebra_state_dependent_delay() {
+        let (problem, soln) = delay_decay_state_dependent_problem::<M>(false);
+        let mut s = problem.dde_bdf::<LS>().unwrap();
+        test_ode_solver(&mut s, soln, None, false, false);
+        let discontinuities = s.discontinuities();
+        for (i, t) in [0.0, 1.0, 2.0].into_iter().enumerate() {
+            assert_has_discontinuity(&discontinuities, t, i);
+        }
+    }
+
+    #[test]
+    fn test_dde_history() {
+        let (problem, soln) = delay_decay_problem::<M>(false);
+        let mut s = problem.dde_bdf::<LS>().unwrap();
+        let point = soln.solution_points.last().unwrap();
+        s.set_stop_time(point.t).unwrap();
+        while s.step().unwrap() != OdeSolverStopReason::TstopReached {}
+
+        // before the initial time the history function is used
+        let mut y = nalgebra::DVector::zeros(2);
+        problem.eqn.history_inplace(-0.5, &mut y);
+        y.assert_eq_st(&nalgebra::DVector::from_vec(vec![1.0, 0.5]), 1e-15);
+
+        // otherwise the accepted steps are interpolated
+        for point in soln.solution_points.iter() {
+            problem.eqn.history_inplace(point.t, &mut y);
+            y.assert_eq_st(&point.state, 1e-3);
+        }
+    }
+
+    #[test]
+    fn test_dde_state_mut() {
+        let (problem, _soln) = delay_decay_problem::<M>(false);
+        let mut s = problem.dde_bdf::<LS>().unwrap();
+        s.set_stop_time(0.5).unwrap();
+        while s.step().unwrap() != OdeSolverStopReason::TstopReached {}
+        s.state_mut().y.fill(0.0);
+        s.step().unwrap();
+
+        // the jump in the solution is a new discontinuity, which is propagated by the delay
+        let discontinuities = s.discontinuities();
+        assert_has_discontinuity(&discontinuities, 0.5, 0);
+        s.set_stop_time(1.6).unwrap();
+        while s.step().unwrap() != OdeSolverStopReason::TstopReached {}
+        assert_has_discontinuity(&s.discontinuities(), 1.5, 1);
+    }
+
+    #[test]
+    fn test_dde_rejects_backward_integration() {
+        let (problem, _soln) = delay_decay_problem::<M>(false);
+        let mut s = problem.dde_bdf::<LS>().unwrap();
+        s.set_stop_time(1.0).unwrap();
+        while s.step().unwrap() != OdeSolverStopReason::TstopReached {}
+        for err in [
+            s.solve(0.5).unwrap_err(),
+            s.solve_dense(&[0.5]).unwrap_err(),
+        ] {
+            assert!(
+                matches!(
+                    err,
+                    DiffsolError::OdeSolverError(OdeSolverError::BackwardIntegrationNotSupported)
+                ),
+                "{}",
+                err
+            );
+        }
+
+        // the solver can still carry on forwards
+        assert!(s.state().h > 0.0);
+        s.solve(1.5).unwrap();
+        assert_eq!(s.state().t, 1.5);
+    }
+}
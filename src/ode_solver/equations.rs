@@ -1,9 +1,16 @@
 use crate::{
-    op::{constant_op::ConstantOpSensAdjoint, linear_op::LinearOpTranspose, ParameterisedOp},
-    ConstantOp, ConstantOpSens, LinearOp, Matrix, NonLinearOp, NonLinearOpAdjoint,
-    NonLinearOpJacobian, NonLinearOpSens, NonLinearOpSensAdjoint, Op, Vector,
+    op::{
+        closure_delay::ClosureDelay, closure_imex::ClosureImex,
+        closure_second_order::ClosureSecondOrder, constant_op::ConstantOpSensAdjoint,
+        linear_op::LinearOpTranspose, ParameterisedOp,
+    },
+    Closure, ClosureNoJac, ConstantOp, ConstantOpSens, HermiteInterpolator, LinearOp, Matrix,
+    NonLinearOp, NonLinearOpAdjoint, NonLinearOpJacobian, NonLinearOpSens, NonLinearOpSensAdjoint,
+    Op, Vector,
 };
+use num_traits::{One, Zero};
 use serde::Serialize;
+use std::cell::RefCell;
 
 #[derive(Clone, Debug, Serialize)]
 pub struct OdeEquationsStatistics {
@@ -128,7 +135,7 @@ impl<Eqn: OdeEquations> OdeEquations for NoAug<Eqn> {
     }
 }
 
-impl<Eqn: OdeEquationsImplicit> AugmentedOdeEquations<Eqn> for NoAug<Eqn> {
+impl<Eqn: OdeEquations> AugmentedOdeEquations<Eqn> for NoAug<Eqn> {
     fn update_rhs_out_state(&mut self, _y: &Eqn::V, _dy: &Eqn::V, _t: Eqn::T) {
         panic!("This should never be called")
     }
@@ -353,6 +360,210 @@ impl<T> OdeEquationsAdjoint for T where
 {
 }
 
+/// this is the reference trait for ODE equations with a right-hand side that is split into a non-stiff explicit part and a stiff implicit part, i.e.
+///
+/// $$
+///  \frac{dy}{dt} = F_E(t, y) + F_I(t, y)
+/// $$
+///
+/// See [OdeEquationsImex] for the main trait. The `Rhs` of the [OdeEquationsRef] supertrait is the sum of the two parts.
+pub trait OdeEquationsImexRef<'a, ImplicitBounds: Sealed = Bounds<&'a Self>>:
+    OdeEquationsRef<'a, ImplicitBounds>
+{
+    type RhsExplicit: NonLinearOp<M = Self::M, V = Self::V, T = Self::T>;
+    type RhsImplicit: NonLinearOpJacobian<M = Self::M, V = Self::V, T = Self::T>;
+}
+
+impl<'a, T: OdeEquationsImexRef<'a>> OdeEquationsImexRef<'a> for &T {
+    type RhsExplicit = <T as OdeEquationsImexRef<'a>>::RhsExplicit;
+    type RhsImplicit = <T as OdeEquationsImexRef<'a>>::RhsImplicit;
+}
+
+/// this is the trait for ODE equations with a right-hand side that is split into a non-stiff explicit part `F_E(t, y)` and a stiff implicit part `F_I(t, y)`, i.e.
+///
+/// $$
+///  \frac{dy}{dt} = F_E(t, y) + F_I(t, y)
+/// $$
+///
+/// The [OdeEquations::rhs] function returns the sum `F_E + F_I`, while the two parts are returned separately by [OdeEquationsImex::rhs_explicit] and [OdeEquationsImex::rhs_implicit].
+/// Only the implicit part needs a Jacobian. These equations are solved using IMEX methods such as [crate::ImexArk], and can be created using [crate::OdeBuilder::rhs_imex].
+pub trait OdeEquationsImex: OdeEquations + for<'a> OdeEquationsImexRef<'a> {
+    /// returns the non-stiff part of the right-hand side `F_E(t, y)` as a [NonLinearOp]
+    fn rhs_explicit(&self) -> <Self as OdeEquationsImexRef<'_>>::RhsExplicit;
+
+    /// returns the stiff part of the right-hand side `F_I(t, y)` as a [NonLinearOpJacobian]
+    fn rhs_implicit(&self) -> <Self as OdeEquationsImexRef<'_>>::RhsImplicit;
+}
+
+impl<T: OdeEquationsImex> OdeEquationsImex for &'_ T {
+    fn rhs_explicit(&self) -> <Self as OdeEquationsImexRef<'_>>::RhsExplicit {
+        (*self).rhs_explicit()
+    }
+
+    fn rhs_implicit(&self) -> <Self as OdeEquationsImexRef<'_>>::RhsImplicit {
+        (*self).rhs_implicit()
+    }
+}
+
+/// this is the reference trait for second-order ODE equations, see [OdeEquationsSecondOrder] for the main trait.
+pub trait OdeEquationsSecondOrderRef<'a, ImplicitBounds: Sealed = Bounds<&'a Self>>:
+    OdeEquationsRef<'a, ImplicitBounds>
+{
+    type Force: NonLinearOpJacobian<M = Self::M, V = Self::V, T = Self::T>;
+}
+
+impl<'a, T: OdeEquationsSecondOrderRef<'a>> OdeEquationsSecondOrderRef<'a> for &T {
+    type Force = <T as OdeEquationsSecondOrderRef<'a>>::Force;
+}
+
+/// this is the trait for second-order ODE equations of the form
+///
+/// $$
+///  M \frac{d^2y}{dt^2} = f(t, y, \frac{dy}{dt})
+/// $$
+///
+/// The state of the equations is the positions `y` followed by the velocities `v = dy/dt`, so the [OdeEquations::rhs] function is the first-order form `[v, f(t, y, v)]`
+/// and the equations can be solved by any of the solvers. If a mass matrix is given it acts on the full state, so it must have the block diagonal form `diag(I, M)`.
+///
+/// The force `f(t, y, v)` is returned separately by [OdeEquationsSecondOrder::force], and is used by the second-order solvers
+/// (e.g. [crate::Symplectic] and [crate::GeneralizedAlpha]). These equations can be created using [crate::OdeBuilder::rhs_second_order].
+pub trait OdeEquationsSecondOrder: OdeEquations + for<'a> OdeEquationsSecondOrderRef<'a> {
+    /// returns the force `f(t, y, v)` as a [NonLinearOpJacobian] of the full state `[y, v]`, with the number of outputs equal to the number of positions
+    fn force(&self) -> <Self as OdeEquationsSecondOrderRef<'_>>::Force;
+
+    /// returns the number of positions `y`, which is half the number of states
+    fn npositions(&self) -> usize {
+        self.rhs().nstates() / 2
+    }
+}
+
+impl<T: OdeEquationsSecondOrder> OdeEquationsSecondOrder for &'_ T {
+    fn force(&self) -> <Self as OdeEquationsSecondOrderRef<'_>>::Force {
+        (*self).force()
+    }
+
+    fn npositions(&self) -> usize {
+        (*self).npositions()
+    }
+}
+
+/// this is the trait for delay differential equations of the form
+///
+/// $$
+///  M \frac{dy}{dt} = f(t, y(t), y(t - \tau_1(t, y)), \ldots, y(t - \tau_m(t, y)))
+/// $$
+///
+/// where the delays `tau_i` can depend on the time and the current state. The solution before the initial time is given by a history function.
+/// The [OdeEquations::rhs] function evaluates the delayed states using the history function and the accepted steps stored in [OdeEquationsDelay::history],
+/// which is kept up to date by the delay solver [crate::Dde]. These equations can be created using [crate::OdeBuilder::rhs_delay].
+pub trait OdeEquationsDelay: OdeEquations {
+    /// returns the number of delays `m`
+    fn ndelays(&self) -> usize;
+
+    /// computes the delays `tau(t, y)`, the output `tau` has length [Self::ndelays]
+    fn delays_inplace(&self, y: &Self::V, t: Self::T, tau: &mut Self::V);
+
+    /// evaluates the solution at time `t` using the history function for times before the first accepted step, and the accepted steps otherwise
+    fn history_inplace(&self, t: Self::T, y: &mut Self::V);
+
+    /// returns the accepted steps of the solution, used to evaluate the delayed states
+    fn history(&self) -> &RefCell<HermiteInterpolator<Self::V>>;
+}
+
+impl<T: OdeEquationsDelay> OdeEquationsDelay for &'_ T {
+    fn ndelays(&self) -> usize {
+        (*self).ndelays()
+    }
+
+    fn delays_inplace(&self, y: &Self::V, t: Self::T, tau: &mut Self::V) {
+        (*self).delays_inplace(y, t, tau)
+    }
+
+    fn history_inplace(&self, t: Self::T, y: &mut Self::V) {
+        (*self).history_inplace(t, y)
+    }
+
+    fn history(&self) -> &RefCell<HermiteInterpolator<Self::V>> {
+        (*self).history()
+    }
+}
+
+/// this is the trait for fully implicit differential-algebraic equations (DAEs) in residual form
+///
+/// $$
+///  F(t, y, y') = 0
+///  y(t_0) = y_0(t_0)
+/// $$
+///
+/// Unlike [OdeEquationsImplicit], the residual can depend nonlinearly on the derivative `y'`. The components of `y` whose derivatives do not
+/// appear in `F` are the algebraic variables, given by [Self::algebraic_indices]. The initial state `y_0` only needs to be a guess for the algebraic variables,
+/// the consistent initial algebraic variables and derivatives are calculated using [crate::op::init::ResidualInitOp]. Implicit solvers need the
+/// iteration matrix `dF/dy + c dF/dy'`, which is calculated from the Jacobian-vector products [Self::residual_jac_mul_inplace] and [Self::residual_jac_dot_mul_inplace].
+///
+/// These equations can be created using [crate::OdeBuilder::build_residual], and solved using [crate::BdfDae].
+pub trait OdeEquationsResidual: Op {
+    /// computes the residual `r = F(t, y, y')`
+    fn residual_inplace(&self, y: &Self::V, dy: &Self::V, t: Self::T, r: &mut Self::V);
+
+    /// computes the product of the Jacobian of the residual wrt `y` with a vector, `r = dF/dy v`
+    fn residual_jac_mul_inplace(
+        &self,
+        y: &Self::V,
+        dy: &Self::V,
+        t: Self::T,
+        v: &Self::V,
+        r: &mut Self::V,
+    );
+
+    /// computes the product of the Jacobian of the residual wrt `y'` with a vector, `r = dF/dy' v`
+    fn residual_jac_dot_mul_inplace(
+        &self,
+        y: &Self::V,
+        dy: &Self::V,
+        t: Self::T,
+        v: &Self::V,
+        r: &mut Self::V,
+    );
+
+    /// computes the iteration matrix `dF/dy + c dF/dy'` and stores it in `jac`, which should have been initialised using [Self::residual_jacobian_sparsity].
+    /// The default implementation computes the matrix column by column using the Jacobian-vector products.
+    fn residual_jacobian_inplace(
+        &self,
+        y: &Self::V,
+        dy: &Self::V,
+        t: Self::T,
+        c: Self::T,
+        jac: &mut Self::M,
+    ) {
+        let n = self.nstates();
+        let mut v = Self::V::zeros(n);
+        let mut col = Self::V::zeros(n);
+        let mut col_dot = Self::V::zeros(n);
+        for j in 0..n {
+            v[j] = Self::T::one();
+            self.residual_jac_mul_inplace(y, dy, t, &v, &mut col);
+            self.residual_jac_dot_mul_inplace(y, dy, t, &v, &mut col_dot);
+            col.axpy(c, &col_dot, Self::T::one());
+            jac.set_column(j, &col);
+            v[j] = Self::T::zero();
+        }
+    }
+
+    /// returns the sparsity pattern of the iteration matrix (if available)
+    fn residual_jacobian_sparsity(&self) -> Option<<Self::M as Matrix>::Sparsity> {
+        None
+    }
+
+    /// computes the initial state `y_0(t)`
+    fn init_inplace(&self, t: Self::T, y: &mut Self::V);
+
+    /// returns the indices of the algebraic variables
+    fn algebraic_indices(&self) -> <Self::V as Vector>::Index;
+
+    /// sets the current parameters of the equations
+    fn set_params(&mut self, p: &Self::V);
+}
+
 /// This struct implements the ODE equation trait [OdeEquations] for a given right-hand side op, mass op, optional root op, and initial condition function.
 ///
 /// While the [crate::OdeBuilder] struct is the easiest way to define an ODE problem,
@@ -494,6 +705,122 @@ where
     }
 }
 
+impl<'a, M, F, G, H, Init, Mass, Root, Out> OdeEquationsImexRef<'a>
+    for OdeSolverEquations<M, ClosureImex<M, F, G, H>, Init, Mass, Root, Out>
+where
+    M: Matrix,
+    F: Fn(&M::V, &M::V, M::T, &mut M::V),
+    G: Fn(&M::V, &M::V, M::T, &mut M::V),
+    H: Fn(&M::V, &M::V, M::T, &M::V, &mut M::V),
+    Init: Op<M = M, V = M::V, T = M::T>,
+    Mass: Op<M = M, V = M::V, T = M::T>,
+    Root: Op<M = M, V = M::V, T = M::T>,
+    Out: Op<M = M, V = M::V, T = M::T>,
+    ParameterisedOp<'a, Init>: ConstantOp<M = M, V = M::V, T = M::T>,
+    ParameterisedOp<'a, Mass>: LinearOp<M = M, V = M::V, T = M::T>,
+    ParameterisedOp<'a, Root>: NonLinearOp<M = M, V = M::V, T = M::T>,
+    ParameterisedOp<'a, Out>: NonLinearOp<M = M, V = M::V, T = M::T>,
+{
+    type RhsExplicit = ParameterisedOp<'a, ClosureNoJac<M, F>>;
+    type RhsImplicit = ParameterisedOp<'a, Closure<M, G, H>>;
+}
+
+impl<M, F, G, H, Init, Mass, Root, Out> OdeEquationsImex
+    for OdeSolverEquations<M, ClosureImex<M, F, G, H>, Init, Mass, Root, Out>
+where
+    M: Matrix,
+    F: Fn(&M::V, &M::V, M::T, &mut M::V),
+    G: Fn(&M::V, &M::V, M::T, &mut M::V),
+    H: Fn(&M::V, &M::V, M::T, &M::V, &mut M::V),
+    Init: Op<M = M, V = M::V, T = M::T>,
+    Mass: Op<M = M, V = M::V, T = M::T>,
+    Root: Op<M = M, V = M::V, T = M::T>,
+    Out: Op<M = M, V = M::V, T = M::T>,
+    for<'a> ParameterisedOp<'a, Init>: ConstantOp<M = M, V = M::V, T = M::T>,
+    for<'a> ParameterisedOp<'a, Mass>: LinearOp<M = M, V = M::V, T = M::T>,
+    for<'a> ParameterisedOp<'a, Root>: NonLinearOp<M = M, V = M::V, T = M::T>,
+    for<'a> ParameterisedOp<'a, Out>: NonLinearOp<M = M, V = M::V, T = M::T>,
+{
+    fn rhs_explicit(&self) -> ParameterisedOp<'_, ClosureNoJac<M, F>> {
+        ParameterisedOp::new(self.rhs.explicit(), self.params())
+    }
+    fn rhs_implicit(&self) -> ParameterisedOp<'_, Closure<M, G, H>> {
+        ParameterisedOp::new(self.rhs.implicit(), self.params())
+    }
+}
+
+impl<'a, M, F, G, Init, Mass, Root, Out> OdeEquationsSecondOrderRef<'a>
+    for OdeSolverEquations<M, ClosureSecondOrder<M, F, G>, Init, Mass, Root, Out>
+where
+    M: Matrix,
+    F: Fn(&M::V, &M::V, M::T, &mut M::V),
+    G: Fn(&M::V, &M::V, M::T, &M::V, &mut M::V),
+    Init: Op<M = M, V = M::V, T = M::T>,
+    Mass: Op<M = M, V = M::V, T = M::T>,
+    Root: Op<M = M, V = M::V, T = M::T>,
+    Out: Op<M = M, V = M::V, T = M::T>,
+    ParameterisedOp<'a, Init>: ConstantOp<M = M, V = M::V, T = M::T>,
+    ParameterisedOp<'a, Mass>: LinearOp<M = M, V = M::V, T = M::T>,
+    ParameterisedOp<'a, Root>: NonLinearOp<M = M, V = M::V, T = M::T>,
+    ParameterisedOp<'a, Out>: NonLinearOp<M = M, V = M::V, T = M::T>,
+{
+    type Force = ParameterisedOp<'a, Closure<M, F, G>>;
+}
+
+impl<M, F, G, Init, Mass, Root, Out> OdeEquationsSecondOrder
+    for OdeSolverEquations<M, ClosureSecondOrder<M, F, G>, Init, Mass, Root, Out>
+where
+    M: Matrix,
+    F: Fn(&M::V, &M::V, M::T, &mut M::V),
+    G: Fn(&M::V, &M::V, M::T, &M::V, &mut M::V),
+    Init: Op<M = M, V = M::V, T = M::T>,
+    Mass: Op<M = M, V = M::V, T = M::T>,
+    Root: Op<M = M, V = M::V, T = M::T>,
+    Out: Op<M = M, V = M::V, T = M::T>,
+    for<'a> ParameterisedOp<'a, Init>: ConstantOp<M = M, V = M::V, T = M::T>,
+    for<'a> ParameterisedOp<'a, Mass>: LinearOp<M = M, V = M::V, T = M::T>,
+    for<'a> ParameterisedOp<'a, Root>: NonLinearOp<M = M, V = M::V, T = M::T>,
+    for<'a> ParameterisedOp<'a, Out>: NonLinearOp<M = M, V = M::V, T = M::T>,
+{
+    fn force(&self) -> ParameterisedOp<'_, Closure<M, F, G>> {
+        ParameterisedOp::new(self.rhs.force(), self.params())
+    }
+    fn npositions(&self) -> usize {
+        self.rhs.npositions()
+    }
+}
+
+impl<M, F, G, D, H, Init, Mass, Root, Out> OdeEquationsDelay
+    for OdeSolverEquations<M, ClosureDelay<M, F, G, D, H>, Init, Mass, Root, Out>
+where
+    M: Matrix,
+    F: Fn(&M::V, &M::V, &M::V, M::T, &mut M::V),
+    G: Fn(&M::V, &M::V, &M::V, M::T, &M::V, &mut M::V),
+    D: Fn(&M::V, &M::V, M::T, &mut M::V),
+    H: Fn(&M::V, M::T) -> M::V,
+    Init: Op<M = M, V = M::V, T = M::T>,
+    Mass: Op<M = M, V = M::V, T = M::T>,
+    Root: Op<M = M, V = M::V, T = M::T>,
+    Out: Op<M = M, V = M::V, T = M::T>,
+    for<'a> ParameterisedOp<'a, Init>: ConstantOp<M = M, V = M::V, T = M::T>,
+    for<'a> ParameterisedOp<'a, Mass>: LinearOp<M = M, V = M::V, T = M::T>,
+    for<'a> ParameterisedOp<'a, Root>: NonLinearOp<M = M, V = M::V, T = M::T>,
+    for<'a> ParameterisedOp<'a, Out>: NonLinearOp<M = M, V = M::V, T = M::T>,
+{
+    fn ndelays(&self) -> usize {
+        self.rhs.ndelays()
+    }
+    fn delays_inplace(&self, y: &M::V, t: M::T, tau: &mut M::V) {
+        self.rhs.delays_inplace(y, self.params(), t, tau)
+    }
+    fn history_inplace(&self, t: M::T, y: &mut M::V) {
+        self.rhs.history_inplace(self.params(), t, y)
+    }
+    fn history(&self) -> &RefCell<HermiteInterpolator<M::V>> {
+        self.rhs.history()
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use nalgebra::DVector;
@@ -0,0 +1,1101 @@
+use crate::error::DiffsolError;
+use crate::error::OdeSolverError;
+use crate::ode_solver_error;
+use crate::vector::VectorRef;
+use crate::{
+    scale, AugmentedOdeEquations, DefaultDenseMatrix, DenseMatrix, MatrixView, NoAug, NonLinearOp,
+    OdeEquations, OdeEquationsSens, OdeSolverMethod, OdeSolverProblem, OdeSolverState,
+    OdeSolverStopReason, Op, RootFinder, SdirkState, SensEquations, StateRef, StateRefMut, Tableau,
+    Vector, VectorView, VectorViewMut,
+};
+use num_traits::abs;
+use num_traits::One;
+use num_traits::Pow;
+use num_traits::Zero;
+
+use super::bdf::BdfStatistics;
+use super::method::{
+    check_interpolation_time, check_tstop, init_root_finder, AugmentedOdeSolverMethod,
+    SensitivitiesOdeSolverMethod, TstopCheck,
+};
+use super::sdirk_state::RkLastStep;
+
+impl<'a, M, Eqn, AugEqn> AugmentedOdeSolverMethod<'a, Eqn, AugEqn>
+    for ExplicitRk<'a, Eqn, M, AugEqn>
+where
+    Eqn: OdeEquations,
+    AugEqn: AugmentedOdeEquations<Eqn>,
+    M: DenseMatrix<T = Eqn::T, V = Eqn::V>,
+    Eqn::V: DefaultDenseMatrix<T = Eqn::T>,
+    for<'b> &'b Eqn::V: VectorRef<Eqn::V>,
+{
+    fn into_state_and_eqn(self) -> (Self::State, Option<AugEqn>) {
+        (self.state, self.s_eqn)
+    }
+}
+
+impl<'a, M, Eqn> SensitivitiesOdeSolverMethod<'a, Eqn>
+    for ExplicitRk<'a, Eqn, M, SensEquations<'a, Eqn>>
+where
+    Eqn: OdeEquationsSens,
+    M: DenseMatrix<T = Eqn::T, V = Eqn::V>,
+    Eqn::V: DefaultDenseMatrix<T = Eqn::T>,
+    for<'b> &'b Eqn::V: VectorRef<Eqn::V>,
+{
+}
+
+/// An explicit Runge-Kutta method with an embedded error estimate, suitable for non-stiff problems.
+///
+/// The particular method is defined by the [Tableau] used to create the solver.
+/// If the `beta` matrix of the [Tableau] is present this is used for interpolation, otherwise hermite interpolation is used.
+///
+/// Only the [NonLinearOp] implementation of the right-hand side is used, so no Jacobian is ever evaluated or factorised
+/// (apart from the Jacobian-vector products needed if forward sensitivities are requested). Problems with a mass matrix are not supported.
+///
+/// If the last row of the `a` matrix is the same as the `b` vector and the last element of the `c` vector is 1 (i.e. the method has the first same as last property),
+/// the last stage is reused as the first stage of the next step. Otherwise the solution is formed from the `b` vector and the right-hand side
+/// is evaluated once more at the end of each accepted step.
+///
+/// Restrictions:
+/// - The diagonal and upper triangular part of the `a` matrix must be zero (i.e. an explicit method).
+/// - The first element of the `c` vector must be 0.
+pub struct ExplicitRk<
+    'a,
+    Eqn,
+    M = <<Eqn as Op>::V as DefaultDenseMatrix>::M,
+    AugmentedEqn = NoAug<Eqn>,
+> where
+    M: DenseMatrix<T = Eqn::T, V = Eqn::V>,
+    Eqn: OdeEquations,
+    Eqn::V: DefaultDenseMatrix<T = Eqn::T>,
+    AugmentedEqn: AugmentedOdeEquations<Eqn>,
+{
+    tableau: Tableau<M>,
+    problem: &'a OdeSolverProblem<Eqn>,
+    state: SdirkState<Eqn::V>,
+    diff: M,
+    sdiff: Vec<M>,
+    sgdiff: Vec<M>,
+    gdiff: M,
+    s_eqn: Option<AugmentedEqn>,
+    old_t: Eqn::T,
+    old_y: Eqn::V,
+    old_y_sens: Vec<Eqn::V>,
+    old_f: Eqn::V,
+    old_f_sens: Vec<Eqn::V>,
+    old_g: Eqn::V,
+    old_dg: Eqn::V,
+    old_dsg: Vec<Eqn::V>,
+    a_rows: Vec<Eqn::V>,
+    is_fsal: bool,
+    statistics: BdfStatistics,
+    root_finder: Option<RootFinder<Eqn::V>>,
+    tstop: Option<Eqn::T>,
+    is_state_mutated: bool,
+}
+
+impl<M, Eqn, AugmentedEqn> Clone for ExplicitRk<'_, Eqn, M, AugmentedEqn>
+where
+    M: DenseMatrix<T = Eqn::T, V = Eqn::V>,
+    Eqn: OdeEquations,
+    Eqn::V: DefaultDenseMatrix<T = Eqn::T>,
+    AugmentedEqn: AugmentedOdeEquations<Eqn>,
+{
+    fn clone(&self) -> Self {
+        Self {
+            tableau: self.tableau.clone(),
+            problem: self.problem,
+            state: self.state.clone(),
+            diff: self.diff.clone(),
+            sdiff: self.sdiff.clone(),
+            sgdiff: self.sgdiff.clone(),
+            gdiff: self.gdiff.clone(),
+            s_eqn: self.s_eqn.clone(),
+            old_t: self.old_t,
+            old_y: self.old_y.clone(),
+            old_y_sens: self.old_y_sens.clone(),
+            old_f: self.old_f.clone(),
+            old_f_sens: self.old_f_sens.clone(),
+            old_g: self.old_g.clone(),
+            old_dg: self.old_dg.clone(),
+            old_dsg: self.old_dsg.clone(),
+            a_rows: self.a_rows.clone(),
+            is_fsal: self.is_fsal,
+            statistics: self.statistics.clone(),
+            root_finder: self.root_finder.clone(),
+            tstop: self.tstop,
+            is_state_mutated: self.is_state_mutated,
+        }
+    }
+}
+
+impl<'a, M, Eqn, AugmentedEqn> ExplicitRk<'a, Eqn, M, AugmentedEqn>
+where
+    M: DenseMatrix<T = Eqn::T, V = Eqn::V>,
+    Eqn: OdeEquations,
+    Eqn::V: DefaultDenseMatrix<T = Eqn::T>,
+    AugmentedEqn: AugmentedOdeEquations<Eqn>,
+    for<'b> &'b Eqn::V: VectorRef<Eqn::V>,
+{
+    const SAFETY: f64 = 0.9;
+    const MIN_FACTOR: f64 = 0.2;
+    const MAX_FACTOR: f64 = 10.0;
+    const MIN_TIMESTEP: f64 = 1e-13;
+
+    pub fn new(
+        problem: &'a OdeSolverProblem<Eqn>,
+        mut state: SdirkState<Eqn::V>,
+        tableau: Tableau<M>,
+    ) -> Result<Self, DiffsolError> {
+        // check that the diagonal and upper triangular part of a is zero
+        let s = tableau.s();
+        for i in 0..s {
+            for j in i..s {
+                assert_eq!(
+                    tableau.a()[(i, j)],
+                    Eqn::T::zero(),
+                    "Invalid tableau, expected a(i, j) = 0 for j >= i"
+                );
+            }
+        }
+
+        // check that the first c is 0
+        assert_eq!(
+            tableau.c()[0],
+            Eqn::T::zero(),
+            "Invalid tableau, expected c(0) = 0"
+        );
+
+        // the method is first same as last if the last row of a is the same as b and the last c is 1
+        let is_fsal = tableau.c()[s - 1] == Eqn::T::one()
+            && (0..s).all(|i| tableau.a()[(s - 1, i)] == tableau.b()[i]);
+
+        if problem.eqn.mass().is_some() {
+            return Err(ode_solver_error!(
+                Other,
+                "Explicit Runge-Kutta solvers do not support a mass matrix"
+            ));
+        }
+
+        let mut a_rows = Vec::with_capacity(s);
+        for i in 0..s {
+            let mut row = Vec::with_capacity(i);
+            for j in 0..i {
+                row.push(tableau.a()[(i, j)]);
+            }
+            a_rows.push(Eqn::V::from_vec(row));
+        }
+
+        state.check_consistent_with_problem(problem)?;
+
+        let nstates = state.y.len();
+        let diff = M::zeros(nstates, s);
+        let gdiff_rows = if problem.integrate_out {
+            problem.eqn.out().unwrap().nout()
+        } else {
+            0
+        };
+        let gdiff = M::zeros(gdiff_rows, s);
+
+        let old_f = state.dy.clone();
+        let old_t = state.t;
+        let old_y = state.y.clone();
+        let (old_g, old_dg) = if problem.integrate_out {
+            (state.g.clone(), state.dg.clone())
+        } else {
+            (<Eqn::V as Vector>::zeros(0), <Eqn::V as Vector>::zeros(0))
+        };
+
+        state.set_problem(problem)?;
+        let root_finder = if let Some(root_fn) = problem.eqn.root() {
+            let root_finder = RootFinder::new(root_fn.nout());
+            root_finder.init(&root_fn, &state.y, state.t);
+            Some(root_finder)
+        } else {
+            None
+        };
+
+        Ok(Self {
+            tableau,
+            problem,
+            state,
+            diff,
+            sdiff: vec![],
+            sgdiff: vec![],
+            gdiff,
+            s_eqn: None,
+            old_t,
+            old_y,
+            old_y_sens: vec![],
+            old_f,
+            old_f_sens: vec![],
+            old_g,
+            old_dg,
+            old_dsg: vec![],
+            a_rows,
+            is_fsal,
+            statistics: BdfStatistics::default(),
+            root_finder,
+            tstop: None,
+            is_state_mutated: false,
+        })
+    }
+
+    pub fn new_augmented(
+        problem: &'a OdeSolverProblem<Eqn>,
+        state: SdirkState<Eqn::V>,
+        tableau: Tableau<M>,
+        augmented_eqn: AugmentedEqn,
+    ) -> Result<Self, DiffsolError> {
+        state.check_sens_consistent_with_problem(problem, &augmented_eqn)?;
+        if !augmented_eqn.integrate_main_eqn() {
+            return Err(ode_solver_error!(
+                Other,
+                "Explicit Runge-Kutta solvers must integrate the main equations alongside the augmented equations"
+            ));
+        }
+        let mut ret = Self::new(problem, state, tableau)?;
+        let naug = augmented_eqn.max_index();
+        let nstates = augmented_eqn.rhs().nstates();
+        let s = ret.tableau.s();
+        ret.sdiff = vec![M::zeros(nstates, s); naug];
+        ret.old_f_sens = vec![<Eqn::V as Vector>::zeros(nstates); naug];
+        ret.old_y_sens = ret.state.s.clone();
+        if let Some(out) = augmented_eqn.out() {
+            ret.sgdiff = vec![M::zeros(out.nout(), s); naug];
+            ret.old_dsg = vec![<Eqn::V as Vector>::zeros(out.nout()); naug];
+        }
+        ret.s_eqn = Some(augmented_eqn);
+        Ok(ret)
+    }
+
+    pub fn get_statistics(&self) -> &BdfStatistics {
+        &self.statistics
+    }
+
+    fn handle_tstop(
+        &mut self,
+        tstop: Eqn::T,
+    ) -> Result<Option<OdeSolverStopReason<Eqn::T>>, DiffsolError> {
+        match check_tstop(self.state.t, self.state.h, tstop)? {
+            TstopCheck::Reached => {
+                self.tstop = None;
+                return Ok(Some(OdeSolverStopReason::TstopReached));
+            }
+            TstopCheck::ScaleStep(factor) => {
+                self.state.h *= factor;
+            }
+            TstopCheck::NotReached => {}
+        }
+        Ok(None)
+    }
+
+    fn integrate_sensitivities(&mut self, i: usize, t: Eqn::T, h: Eqn::T) {
+        let s_eqn = self.s_eqn.as_mut().unwrap();
+        s_eqn.update_rhs_out_state(&self.old_y, &self.old_f, t);
+        for j in 0..self.sdiff.len() {
+            s_eqn.set_index(j);
+
+            // stage value s_i = s0 + sum_{k < i} a_ik h f_k
+            let s_i = &mut self.old_y_sens[j];
+            s_i.copy_from(&self.state.s[j]);
+            self.sdiff[j]
+                .columns(0, i)
+                .gemv_o(Eqn::T::one(), &self.a_rows[i], Eqn::T::one(), s_i);
+            s_eqn.rhs().call_inplace(s_i, t, &mut self.old_f_sens[j]);
+            self.sdiff[j]
+                .column_mut(i)
+                .axpy(h, &self.old_f_sens[j], Eqn::T::zero());
+
+            // calculate dsg and store in sgdiff
+            if let Some(out) = s_eqn.out() {
+                out.call_inplace(s_i, t, &mut self.old_dsg[j]);
+                self.sgdiff[j]
+                    .column_mut(i)
+                    .axpy(h, &self.old_dsg[j], Eqn::T::zero());
+            }
+        }
+    }
+
+    fn interpolate_from_diff(y0: &Eqn::V, beta_f: &Eqn::V, diff: &M) -> Eqn::V {
+        // ret = old_y + sum_{i=0}^{s_star-1} beta[i] * diff[:, i]
+        let mut ret = y0.clone();
+        diff.gemv(Eqn::T::one(), beta_f, Eqn::T::one(), &mut ret);
+        ret
+    }
+
+    fn interpolate_beta_function(theta: Eqn::T, beta: &M) -> Eqn::V {
+        let poly_order = beta.ncols();
+        let s_star = beta.nrows();
+        let mut thetav = Vec::with_capacity(poly_order);
+        thetav.push(theta);
+        for i in 1..poly_order {
+            thetav.push(theta * thetav[i - 1]);
+        }
+        // beta_poly = beta * thetav
+        let thetav = Eqn::V::from_vec(thetav);
+        let mut beta_f = <Eqn::V as Vector>::zeros(s_star);
+        beta.gemv(Eqn::T::one(), &thetav, Eqn::T::zero(), &mut beta_f);
+        beta_f
+    }
+
+    fn interpolate_hermite(
+        theta: Eqn::T,
+        dt: Eqn::T,
+        u0: &Eqn::V,
+        u1: &Eqn::V,
+        diff: &M,
+        f1: &Eqn::V,
+    ) -> Eqn::V {
+        // the first stage is always the derivative at the start of the step, but the last stage
+        // is only the derivative at the end of the step for fsal methods, so use f1 instead
+        let hf0 = diff.column(0);
+        u0 * scale(Eqn::T::from(1.0) - theta)
+            + u1 * scale(theta)
+            + ((u1 - u0) * scale(Eqn::T::from(1.0) - Eqn::T::from(2.0) * theta)
+                + hf0 * scale(theta - Eqn::T::from(1.0))
+                + f1 * scale(dt * theta))
+                * scale(theta * (theta - Eqn::T::from(1.0)))
+    }
+
+    /// form the solution at the end of the step for methods that are not first same as last
+    fn form_solution(&mut self) {
+        self.old_y.copy_from(&self.state.y);
+        self.diff.gemv(
+            Eqn::T::one(),
+            self.tableau.b(),
+            Eqn::T::one(),
+            &mut self.old_y,
+        );
+        for j in 0..self.sdiff.len() {
+            self.old_y_sens[j].copy_from(&self.state.s[j]);
+            self.sdiff[j].gemv(
+                Eqn::T::one(),
+                self.tableau.b(),
+                Eqn::T::one(),
+                &mut self.old_y_sens[j],
+            );
+        }
+    }
+
+    /// evaluate the derivatives at the end of the step for methods that are not first same as last
+    fn evaluate_derivatives(&mut self, t: Eqn::T) {
+        self.problem
+            .eqn
+            .rhs()
+            .call_inplace(&self.old_y, t, &mut self.old_f);
+        if self.problem.integrate_out {
+            let out = self.problem.eqn.out().unwrap();
+            out.call_inplace(&self.old_y, t, &mut self.old_dg);
+        }
+        if let Some(s_eqn) = self.s_eqn.as_mut() {
+            s_eqn.update_rhs_out_state(&self.old_y, &self.old_f, t);
+            for j in 0..self.sdiff.len() {
+                s_eqn.set_index(j);
+                s_eqn
+                    .rhs()
+                    .call_inplace(&self.old_y_sens[j], t, &mut self.old_f_sens[j]);
+                if let Some(out) = s_eqn.out() {
+                    out.call_inplace(&self.old_y_sens[j], t, &mut self.old_dsg[j]);
+                }
+            }
+        }
+    }
+
+    fn _update_step_size(&mut self, factor: Eqn::T) -> Result<Eqn::T, DiffsolError> {
+        let new_h = self.state.h * factor;
+
+        // if step size too small, then fail
+        if abs(new_h) < Eqn::T::from(Self::MIN_TIMESTEP) {
+            return Err(DiffsolError::from(OdeSolverError::StepSizeTooSmall {
+                time: self.state.t.into(),
+            }));
+        }
+
+        // update state
+        self.state.h = new_h;
+
+        Ok(new_h)
+    }
+}
+
+impl<'a, M, Eqn, AugmentedEqn> OdeSolverMethod<'a, Eqn> for ExplicitRk<'a, Eqn, M, AugmentedEqn>
+where
+    M: DenseMatrix<T = Eqn::T, V = Eqn::V>,
+    Eqn: OdeEquations,
+    Eqn::V: DefaultDenseMatrix<T = Eqn::T>,
+    AugmentedEqn: AugmentedOdeEquations<Eqn>,
+    for<'b> &'b Eqn::V: VectorRef<Eqn::V>,
+{
+    type State = SdirkState<Eqn::V>;
+
+    fn problem(&self) -> &'a OdeSolverProblem<Eqn> {
+        self.problem
+    }
+
+    fn order(&self) -> usize {
+        self.tableau.order()
+    }
+
+    fn set_state(&mut self, mut state: Self::State) {
+        match state.last_step.take() {
+            Some(last_step) => {
+                // restore the last step of the checkpoint so the solution can still be interpolated within it
+                self.old_t = last_step.t;
+                self.old_y.copy_from(&last_step.y);
+                self.old_g.copy_from(&last_step.g);
+                for (old_y, s) in self.old_y_sens.iter_mut().zip(last_step.s.iter()) {
+                    old_y.copy_from(s);
+                }
+                for (i, diff) in last_step.diff.iter().enumerate() {
+                    self.diff.column_mut(i).copy_from(diff);
+                }
+                for (i, gdiff) in last_step.gdiff.iter().enumerate() {
+                    self.gdiff.column_mut(i).copy_from(gdiff);
+                }
+                for (sdiff, last_sdiff) in self.sdiff.iter_mut().zip(last_step.sdiff.iter()) {
+                    for (i, diff) in last_sdiff.iter().enumerate() {
+                        sdiff.column_mut(i).copy_from(diff);
+                    }
+                }
+
+                // reinitialise root finder if needed
+                init_root_finder(self.problem, self.root_finder.as_ref(), &state.y, state.t);
+                self.is_state_mutated = false;
+            }
+            None => {
+                // the solution can only be interpolated once a step is taken from the new state
+                self.is_state_mutated = true;
+            }
+        }
+        self.state = state;
+    }
+
+    fn into_state(self) -> SdirkState<Eqn::V> {
+        self.state
+    }
+
+    fn checkpoint(&mut self) -> Self::State {
+        let mut state = self.state.clone();
+        if !self.is_state_mutated {
+            let columns = |m: &M| (0..m.ncols()).map(|i| m.column(i).into_owned()).collect();
+            state.last_step = Some(RkLastStep {
+                t: self.old_t,
+                y: self.old_y.clone(),
+                g: self.old_g.clone(),
+                s: self.old_y_sens.clone(),
+                diff: columns(&self.diff),
+                gdiff: columns(&self.gdiff),
+                sdiff: self.sdiff.iter().map(columns).collect(),
+            });
+        }
+        state
+    }
+
+    fn step(&mut self) -> Result<OdeSolverStopReason<Eqn::T>, DiffsolError> {
+        let n = self.state.y.len();
+
+        if self.is_state_mutated {
+            // reinitialise root finder if needed
+            init_root_finder(
+                self.problem,
+                self.root_finder.as_ref(),
+                &self.state.y,
+                self.state.t,
+            );
+            // the first stage is the derivative at the start of the step, so recompute it for the new state
+            self.old_y.copy_from(&self.state.y);
+            for (old_y, s) in self.old_y_sens.iter_mut().zip(self.state.s.iter()) {
+                old_y.copy_from(s);
+            }
+            self.evaluate_derivatives(self.state.t);
+            self.state.dy.copy_from(&self.old_f);
+            if self.problem.integrate_out {
+                self.state.dg.copy_from(&self.old_dg);
+            }
+            for (ds, old_f) in self.state.ds.iter_mut().zip(self.old_f_sens.iter()) {
+                ds.copy_from(old_f);
+            }
+            for (dsg, old_dsg) in self.state.dsg.iter_mut().zip(self.old_dsg.iter()) {
+                dsg.copy_from(old_dsg);
+            }
+            self.old_t = self.state.t;
+
+            // reinitialise tstop if needed
+            if let Some(t_stop) = self.tstop {
+                self.set_stop_time(t_stop)?;
+            }
+
+            self.is_state_mutated = false;
+        }
+
+        let mut error = <Eqn::V as Vector>::zeros(n);
+        let out_error_control = self.problem().output_in_error_control();
+        let mut out_error = if out_error_control {
+            <Eqn::V as Vector>::zeros(self.problem().eqn.out().unwrap().nout())
+        } else {
+            <Eqn::V as Vector>::zeros(0)
+        };
+        let sens_error_control =
+            self.s_eqn.is_some() && self.s_eqn.as_ref().unwrap().include_in_error_control();
+        let mut sens_error = if sens_error_control {
+            <Eqn::V as Vector>::zeros(self.s_eqn.as_ref().unwrap().rhs().nstates())
+        } else {
+            <Eqn::V as Vector>::zeros(0)
+        };
+        let sens_out_error_control =
+            self.s_eqn.is_some() && self.s_eqn.as_ref().unwrap().include_out_in_error_control();
+        let mut sens_out_error = if sens_out_error_control {
+            <Eqn::V as Vector>::zeros(self.s_eqn.as_ref().unwrap().out().unwrap().nout())
+        } else {
+            <Eqn::V as Vector>::zeros(0)
+        };
+
+        let mut factor: Eqn::T;
+
+        // loop until step is accepted
+        loop {
+            let t0 = self.state.t;
+            let h = self.state.h;
+
+            // the first stage is the derivative at the start of the step,
+            // which is the last stage of the previous step
+            {
+                let mut hf = self.diff.column_mut(0);
+                hf.copy_from(&self.state.dy);
+                hf *= scale(h);
+            }
+            for (diff, ds) in self.sdiff.iter_mut().zip(self.state.ds.iter()) {
+                let mut hf = diff.column_mut(0);
+                hf.copy_from(ds);
+                hf *= scale(h);
+            }
+            for (diff, dsg) in self.sgdiff.iter_mut().zip(self.state.dsg.iter()) {
+                let mut hf = diff.column_mut(0);
+                hf.copy_from(dsg);
+                hf *= scale(h);
+            }
+            if self.problem.integrate_out {
+                let mut hf = self.gdiff.column_mut(0);
+                hf.copy_from(&self.state.dg);
+                hf *= scale(h);
+            }
+
+            for i in 1..self.tableau.s() {
+                let t = t0 + self.tableau.c()[i] * h;
+
+                // stage value y_i = y0 + sum_{j < i} a_ij h f_j
+                self.old_y.copy_from(&self.state.y);
+                self.diff.columns(0, i).gemv_o(
+                    Eqn::T::one(),
+                    &self.a_rows[i],
+                    Eqn::T::one(),
+                    &mut self.old_y,
+                );
+                self.problem
+                    .eqn
+                    .rhs()
+                    .call_inplace(&self.old_y, t, &mut self.old_f);
+                self.diff.column_mut(i).axpy(h, &self.old_f, Eqn::T::zero());
+
+                // calculate dg and store in gdiff
+                if self.problem.integrate_out {
+                    let out = self.problem.eqn.out().unwrap();
+                    out.call_inplace(&self.old_y, t, &mut self.old_dg);
+                    self.gdiff
+                        .column_mut(i)
+                        .axpy(h, &self.old_dg, Eqn::T::zero());
+                }
+
+                if self.s_eqn.is_some() {
+                    self.integrate_sensitivities(i, t, h);
+                }
+            }
+
+            if !self.is_fsal {
+                self.form_solution();
+            }
+
+            // old_y now has the new y soln, compute the error
+            let mut ncontributions = 0;
+            let mut error_norm = Eqn::T::zero();
+            self.diff
+                .gemv(Eqn::T::one(), self.tableau.d(), Eqn::T::zero(), &mut error);
+            let atol = &self.problem().atol;
+            let rtol = self.problem().rtol;
+            error_norm += error.squared_norm(&self.old_y, atol, rtol);
+            ncontributions += 1;
+
+            // output errors
+            if out_error_control {
+                self.gdiff.gemv(
+                    Eqn::T::one(),
+                    self.tableau.d(),
+                    Eqn::T::zero(),
+                    &mut out_error,
+                );
+                let atol = self.problem().out_atol.as_ref().unwrap();
+                let rtol = self.problem().out_rtol.unwrap();
+                error_norm += out_error.squared_norm(&self.state.g, atol, rtol);
+                ncontributions += 1;
+            }
+
+            // sensitivity errors
+            if sens_error_control {
+                let atol = self.s_eqn.as_ref().unwrap().atol().unwrap();
+                let rtol = self.s_eqn.as_ref().unwrap().rtol().unwrap();
+                for i in 0..self.sdiff.len() {
+                    self.sdiff[i].gemv(
+                        Eqn::T::one(),
+                        self.tableau.d(),
+                        Eqn::T::zero(),
+                        &mut sens_error,
+                    );
+                    error_norm += sens_error.squared_norm(&self.old_y_sens[i], atol, rtol);
+                    ncontributions += 1;
+                }
+            }
+
+            // sensitivity output errors
+            if sens_out_error_control {
+                let atol = self.s_eqn.as_ref().unwrap().out_atol().unwrap();
+                let rtol = self.s_eqn.as_ref().unwrap().out_rtol().unwrap();
+                for i in 0..self.sgdiff.len() {
+                    self.sgdiff[i].gemv(
+                        Eqn::T::one(),
+                        self.tableau.d(),
+                        Eqn::T::zero(),
+                        &mut sens_out_error,
+                    );
+                    error_norm += sens_out_error.squared_norm(&self.state.sg[i], atol, rtol);
+                    ncontributions += 1;
+                }
+            }
+            if ncontributions > 1 {
+                error_norm /= Eqn::T::from(ncontributions as f64);
+            }
+
+            // adjust step size based on error, the embedded method is one order lower than the main method
+            let order = self.tableau.order() as f64;
+            factor = Eqn::T::from(Self::SAFETY) * error_norm.pow(Eqn::T::from(-0.5 / order));
+            if factor < Eqn::T::from(Self::MIN_FACTOR) {
+                factor = Eqn::T::from(Self::MIN_FACTOR);
+            }
+            if factor > Eqn::T::from(Self::MAX_FACTOR) {
+                factor = Eqn::T::from(Self::MAX_FACTOR);
+            }
+
+            // test error is within tolerance
+            if error_norm <= Eqn::T::from(1.0) {
+                break;
+            }
+            // step is rejected, factor reduces step size, so we try again with the smaller step size
+            self.statistics.number_of_error_test_failures += 1;
+            self._update_step_size(factor)?;
+        }
+
+        if !self.is_fsal {
+            self.evaluate_derivatives(self.state.t + self.state.h);
+        }
+
+        // take the step
+        {
+            let state = &mut self.state;
+            self.old_t = state.t;
+            state.t += state.h;
+
+            // old_y and old_f are the new y and dy
+            std::mem::swap(&mut self.old_f, &mut state.dy);
+            std::mem::swap(&mut self.old_y, &mut state.y);
+
+            for i in 0..self.sdiff.len() {
+                std::mem::swap(&mut self.old_f_sens[i], &mut state.ds[i]);
+                std::mem::swap(&mut self.old_y_sens[i], &mut state.s[i]);
+            }
+
+            for i in 0..self.sgdiff.len() {
+                std::mem::swap(&mut self.old_dsg[i], &mut state.dsg[i]);
+                self.sgdiff[i].gemv(
+                    Eqn::T::one(),
+                    self.tableau.b(),
+                    Eqn::T::one(),
+                    &mut state.sg[i],
+                );
+            }
+
+            // integrate output function
+            if self.problem.integrate_out {
+                self.old_g.copy_from(&state.g);
+                std::mem::swap(&mut self.old_dg, &mut state.dg);
+                self.gdiff
+                    .gemv(Eqn::T::one(), self.tableau.b(), Eqn::T::one(), &mut state.g);
+            }
+        }
+
+        // update step size for next step
+        self._update_step_size(factor)?;
+
+        // update statistics
+        self.statistics.number_of_steps += 1;
+
+        // check for root within accepted step
+        if let Some(root_fn) = self.problem.eqn.root() {
+            let ret = self.root_finder.as_ref().unwrap().check_root(
+                &|t| self.interpolate(t),
+                &root_fn,
+                &self.state.y,
+                self.state.t,
+            );
+            if let Some(root) = ret {
+                return Ok(OdeSolverStopReason::RootFound(root));
+            }
+        }
+
+        // check if we are at tstop
+        if let Some(tstop) = self.tstop {
+            if let Some(reason) = self.handle_tstop(tstop).unwrap() {
+                return Ok(reason);
+            }
+        }
+
+        // just a normal step, no roots or tstop reached
+        Ok(OdeSolverStopReason::InternalTimestep)
+    }
+
+    fn reverse_direction(&mut self) -> Result<(), DiffsolError> {
+        // the next step only depends on the current state, so the sign of the step size can be flipped in place
+        self.state.h = -self.state.h;
+        self.tstop = None;
+        Ok(())
+    }
+
+    fn set_stop_time(&mut self, tstop: <Eqn as Op>::T) -> Result<(), DiffsolError> {
+        self.tstop = Some(tstop);
+        if let Some(OdeSolverStopReason::TstopReached) = self.handle_tstop(tstop)? {
+            let error = OdeSolverError::StopTimeBeforeCurrentTime {
+                stop_time: tstop.into(),
+                state_time: self.state.t.into(),
+            };
+            self.tstop = None;
+            return Err(DiffsolError::from(error));
+        }
+        Ok(())
+    }
+
+    fn interpolate_sens(&self, t: <Eqn as Op>::T) -> Result<Vec<<Eqn as Op>::V>, DiffsolError> {
+        let state = &self.state;
+
+        if self.is_state_mutated {
+            if t == state.t {
+                return Ok(state.s.clone());
+            } else {
+                return Err(ode_solver_error!(InterpolationTimeOutsideCurrentStep));
+            }
+        }
+
+        // check that t is within the last step
+        check_interpolation_time(t, self.old_t, state.t)?;
+        let dt = state.t - self.old_t;
+        let theta = if dt == Eqn::T::zero() {
+            Eqn::T::one()
+        } else {
+            (t - self.old_t) / dt
+        };
+
+        if let Some(beta) = self.tableau.beta() {
+            let beta_f = Self::interpolate_beta_function(theta, beta);
+            let ret = self
+                .old_y_sens
+                .iter()
+                .zip(self.sdiff.iter())
+                .map(|(y, diff)| Self::interpolate_from_diff(y, &beta_f, diff))
+                .collect();
+            Ok(ret)
+        } else {
+            let ret = self
+                .old_y_sens
+                .iter()
+                .zip(state.s.iter())
+                .zip(self.sdiff.iter())
+                .zip(state.ds.iter())
+                .map(|(((s0, s1), diff), ds1)| {
+                    Self::interpolate_hermite(theta, dt, s0, s1, diff, ds1)
+                })
+                .collect();
+            Ok(ret)
+        }
+    }
+
+    fn interpolate(&self, t: <Eqn>::T) -> Result<<Eqn>::V, DiffsolError> {
+        let state = &self.state;
+
+        if self.is_state_mutated {
+            if t == state.t {
+                return Ok(state.y.clone());
+            } else {
+                return Err(ode_solver_error!(InterpolationTimeOutsideCurrentStep));
+            }
+        }
+
+        // check that t is within the last step
+        check_interpolation_time(t, self.old_t, state.t)?;
+
+        let dt = state.t - self.old_t;
+        let theta = if dt == Eqn::T::zero() {
+            Eqn::T::one()
+        } else {
+            (t - self.old_t) / dt
+        };
+
+        if let Some(beta) = self.tableau.beta() {
+            let beta_f = Self::interpolate_beta_function(theta, beta);
+            let ret = Self::interpolate_from_diff(&self.old_y, &beta_f, &self.diff);
+            Ok(ret)
+        } else {
+            let ret =
+                Self::interpolate_hermite(theta, dt, &self.old_y, &state.y, &self.diff, &state.dy);
+            Ok(ret)
+        }
+    }
+
+    fn interpolate_out(&self, t: <Eqn>::T) -> Result<<Eqn>::V, DiffsolError> {
+        let state = &self.state;
+
+        if self.is_state_mutated {
+            if t == state.t {
+                return Ok(state.g.clone());
+            } else {
+                return Err(ode_solver_error!(InterpolationTimeOutsideCurrentStep));
+            }
+        }
+
+        // check that t is within the last step
+        check_interpolation_time(t, self.old_t, state.t)?;
+
+        let dt = state.t - self.old_t;
+        let theta = if dt == Eqn::T::zero() {
+            Eqn::T::one()
+        } else {
+            (t - self.old_t) / dt
+        };
+
+        if let Some(beta) = self.tableau.beta() {
+            let beta_f = Self::interpolate_beta_function(theta, beta);
+            let ret = Self::interpolate_from_diff(&self.old_g, &beta_f, &self.gdiff);
+            Ok(ret)
+        } else {
+            let ret =
+                Self::interpolate_hermite(theta, dt, &self.old_g, &state.g, &self.gdiff, &state.dg);
+            Ok(ret)
+        }
+    }
+
+    fn state(&self) -> StateRef<Eqn::V> {
+        self.state.as_ref()
+    }
+
+    fn state_mut(&mut self) -> StateRefMut<Eqn::V> {
+        self.is_state_mutated = true;
+        self.state.as_mut()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        ode_solver::{
+            test_models::exponential_decay::{
+                exponential_decay_problem, exponential_decay_problem_no_jacobian,
+                exponential_decay_problem_sens, exponential_decay_problem_with_root,
+                negative_exponential_decay_problem,
+            },
+            test_models::gaussian_decay::gaussian_decay_problem,
+            tests::{
+                test_checkpointing, test_interpolate, test_ode_solver, test_problem,
+                test_state_mut, test_state_mut_on_problem,
+            },
+        },
+        OdeEquations, Op,
+    };
+
+    use num_traits::abs;
+
+    type M = nalgebra::DMatrix<f64>;
+
+    #[test]
+    fn explicit_rk_state_mut() {
+        test_state_mut(test_problem::<M>().dopri5().unwrap());
+    }
+
+    #[test]
+    fn explicit_rk_test_interpolate() {
+        test_interpolate(test_problem::<M>().dopri5().unwrap());
+    }
+
+    #[test]
+    fn explicit_rk_test_checkpointing() {
+        let (problem, soln) = exponential_decay_problem::<M>(false);
+        let s1 = problem.dopri5().unwrap();
+        let s2 = problem.dopri5().unwrap();
+        test_checkpointing(soln, s1, s2);
+    }
+
+    #[test]
+    fn explicit_rk_test_state_mut_exponential_decay() {
+        let (p, soln) = exponential_decay_problem::<M>(false);
+        let s = p.dopri5().unwrap();
+        test_state_mut_on_problem(s, soln);
+    }
+
+    #[test]
+    fn explicit_rk_test_nalgebra_negative_exponential_decay() {
+        let (problem, soln) = negative_exponential_decay_problem::<M>(false);
+        let mut s = problem.dopri5().unwrap();
+        test_ode_solver(&mut s, soln, None, false, false);
+    }
+
+    #[test]
+    fn test_dopri5_nalgebra_exponential_decay() {
+        let (problem, soln) = exponential_decay_problem::<M>(false);
+        let mut s = problem.dopri5().unwrap();
+        test_ode_solver(&mut s, soln, None, false, false);
+        assert_eq!(s.get_statistics().number_of_nonlinear_solver_iterations, 0);
+        assert_eq!(s.get_statistics().number_of_linear_solver_setups, 0);
+        assert_eq!(problem.eqn.rhs().statistics().number_of_jac_muls, 0);
+        assert_eq!(problem.eqn.rhs().statistics().number_of_matrix_evals, 0);
+    }
+
+    #[test]
+    fn test_dopri5_nalgebra_exponential_decay_no_jacobian() {
+        let (problem, soln) = exponential_decay_problem_no_jacobian::<M>();
+        let mut s = problem.dopri5().unwrap();
+        test_ode_solver(&mut s, soln, None, false, false);
+    }
+
+    #[test]
+    fn test_dopri5_nalgebra_exponential_decay_sens() {
+        let (problem, soln) = exponential_decay_problem_sens::<M>(false);
+        let mut s = problem.dopri5_sens().unwrap();
+        test_ode_solver(&mut s, soln, None, false, true);
+        assert_eq!(problem.eqn.rhs().statistics().number_of_matrix_evals, 0);
+    }
+
+    #[test]
+    fn test_tstop_dopri5() {
+        let (problem, soln) = exponential_decay_problem::<M>(false);
+        let mut s = problem.dopri5().unwrap();
+        test_ode_solver(&mut s, soln, None, true, false);
+    }
+
+    #[test]
+    fn test_root_finder_dopri5() {
+        let (problem, soln) = exponential_decay_problem_with_root::<M>(false);
+        let mut s = problem.dopri5().unwrap();
+        let y = test_ode_solver(&mut s, soln, None, false, false);
+        assert!(abs(y[0] - 0.6) < 1e-6, "y[0] = {}", y[0]);
+    }
+
+    #[test]
+    fn test_tsit45_nalgebra_exponential_decay() {
+        let (problem, soln) = exponential_decay_problem::<M>(false);
+        let mut s = problem.tsit45().unwrap();
+        test_ode_solver(&mut s, soln, None, false, false);
+    }
+
+    #[test]
+    fn test_tsit45_nalgebra_exponential_decay_sens() {
+        let (problem, soln) = exponential_decay_problem_sens::<M>(false);
+        let mut s = problem.tsit45_sens().unwrap();
+        test_ode_solver(&mut s, soln, None, false, true);
+    }
+
+    #[test]
+    fn test_vern56_nalgebra_exponential_decay() {
+        let (problem, soln) = exponential_decay_problem::<M>(false);
+        let mut s = problem.vern56().unwrap();
+        test_ode_solver(&mut s, soln, None, false, false);
+    }
+
+    #[test]
+    fn test_vern56_nalgebra_gaussian_decay() {
+        let (problem, soln) = gaussian_decay_problem::<M>(false, 10);
+        let mut s = problem.vern56().unwrap();
+        test_ode_solver(&mut s, soln, None, false, false);
+    }
+
+    #[test]
+    fn explicit_rk_non_fsal_test_interpolate() {
+        test_interpolate(test_problem::<M>().vern67().unwrap());
+    }
+
+    #[test]
+    fn test_vern67_nalgebra_exponential_decay() {
+        let (problem, soln) = exponential_decay_problem::<M>(false);
+        let mut s = problem.vern67().unwrap();
+        test_ode_solver(&mut s, soln, None, false, false);
+    }
+
+    #[test]
+    fn test_vern67_nalgebra_exponential_decay_sens() {
+        let (problem, soln) = exponential_decay_problem_sens::<M>(false);
+        let mut s = problem.vern67_sens().unwrap();
+        test_ode_solver(&mut s, soln, None, false, true);
+    }
+
+    #[test]
+    fn test_tstop_vern67() {
+        let (problem, soln) = exponential_decay_problem::<M>(false);
+        let mut s = problem.vern67().unwrap();
+        test_ode_solver(&mut s, soln, None, true, false);
+    }
+
+    #[test]
+    fn test_root_finder_vern67() {
+        let (problem, soln) = exponential_decay_problem_with_root::<M>(false);
+        let mut s = problem.vern67().unwrap();
+        let y = test_ode_solver(&mut s, soln, None, false, false);
+        assert!(abs(y[0] - 0.6) < 1e-6, "y[0] = {}", y[0]);
+    }
+
+    #[test]
+    fn test_vern78_nalgebra_exponential_decay() {
+        let (problem, soln) = exponential_decay_problem::<M>(false);
+        let mut s = problem.vern78().unwrap();
+        test_ode_solver(&mut s, soln, None, false, false);
+    }
+
+    #[test]
+    fn test_vern78_nalgebra_exponential_decay_sens() {
+        let (problem, soln) = exponential_decay_problem_sens::<M>(false);
+        let mut s = problem.vern78_sens().unwrap();
+        test_ode_solver(&mut s, soln, None, false, true);
+    }
+
+    #[test]
+    fn test_tstop_vern78() {
+        let (problem, soln) = exponential_decay_problem::<M>(false);
+        let mut s = problem.vern78().unwrap();
+        test_ode_solver(&mut s, soln, None, true, false);
+    }
+
+    #[test]
+    fn test_vern89_nalgebra_exponential_decay() {
+        let (problem, soln) = exponential_decay_problem::<M>(false);
+        let mut s = problem.vern89().unwrap();
+        test_ode_solver(&mut s, soln, None, false, false);
+    }
+
+    #[test]
+    fn test_vern89_nalgebra_exponential_decay_sens() {
+        let (problem, soln) = exponential_decay_problem_sens::<M>(false);
+        let mut s = problem.vern89_sens().unwrap();
+        test_ode_solver(&mut s, soln, None, false, true);
+    }
+
+    #[test]
+    fn test_tstop_vern89() {
+        let (problem, soln) = exponential_decay_problem::<M>(false);
+        let mut s = problem.vern89().unwrap();
+        test_ode_solver(&mut s, soln, None, true, false);
+    }
+}
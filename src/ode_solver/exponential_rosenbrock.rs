@@ -0,0 +1,726 @@
+use crate::error::DiffsolError;
+use crate::error::OdeSolverError;
+use crate::matrix::MatrixRef;
+use crate::ode_solver_error;
+use crate::vector::VectorRef;
+use crate::{
+    scale, DefaultDenseMatrix, DenseMatrix, ExponentialRosenbrockTableau, NonLinearOp,
+    NonLinearOpJacobian, OdeEquationsImplicit, OdeSolverMethod, OdeSolverProblem, OdeSolverState,
+    OdeSolverStopReason, Op, RootFinder, Scalar, SdirkState, StateRef, StateRefMut, Vector,
+};
+use nalgebra::ComplexField;
+use num_traits::abs;
+use num_traits::One;
+use num_traits::Pow;
+use num_traits::Zero;
+
+use super::bdf::BdfStatistics;
+use super::krylov_phi::KrylovPhi;
+use super::method::{check_interpolation_time, check_tstop, init_root_finder, TstopCheck};
+
+/// An exponential Rosenbrock method with an embedded error estimate, suitable for large stiff (e.g. semi-linear parabolic) problems.
+///
+/// The particular method is defined by the [ExponentialRosenbrockTableau] used to create the solver.
+/// Each step linearises the right-hand side around the start of the step, and the stiff linear part is integrated exactly
+/// using the phi functions of `h J`, where `J` is the Jacobian of the right-hand side at the start of the step.
+/// The action of the phi functions on a vector is approximated using the Arnoldi process, which only requires Jacobian-vector products
+/// (see [crate::NonLinearOpJacobian::jac_mul_inplace]), so the Jacobian is never formed or factorised and no linear or nonlinear solver is needed.
+/// If the Krylov approximation does not converge within the maximum dimension of the Krylov subspace, the step is retried with a smaller step size.
+///
+/// The dense output of each step is given by the continuous extension of the method, which replaces `h` with `theta h` in the phi functions
+/// and scales the coefficient of each `phi_{k+1}` by `theta^{k+1}`. Like the method itself it is exact for linear problems and does not
+/// depend on the right-hand side at the end of the step, which is very sensitive to small errors in the stiff components of the solution.
+///
+/// The time derivative of the right-hand side is approximated using finite differences. The output function is integrated using Simpson's rule
+/// on the dense output of each step, and is not included in the error control. Problems with a mass matrix and forward sensitivities are not supported by this solver.
+///
+/// Restrictions:
+/// - The first element of the `c` vector must be 0, and all elements must be in the interval `[0, 1]`.
+pub struct ExponentialRosenbrock<'a, Eqn, M = <<Eqn as Op>::V as DefaultDenseMatrix>::M>
+where
+    M: DenseMatrix<T = Eqn::T, V = Eqn::V>,
+    Eqn: OdeEquationsImplicit,
+    Eqn::V: DefaultDenseMatrix<T = Eqn::T>,
+{
+    tableau: ExponentialRosenbrockTableau<M>,
+    problem: &'a OdeSolverProblem<Eqn>,
+    state: SdirkState<Eqn::V>,
+    krylov: Vec<KrylovPhi<M>>,
+    stage_inc: Vec<Eqn::V>,
+    ft: Eqn::V,
+    u: Eqn::V,
+    d: Eqn::V,
+    jd: Eqn::V,
+    old_t: Eqn::T,
+    old_y: Eqn::V,
+    old_f: Eqn::V,
+    old_g: Eqn::V,
+    old_dg: Eqn::V,
+    statistics: BdfStatistics,
+    root_finder: Option<RootFinder<Eqn::V>>,
+    tstop: Option<Eqn::T>,
+    is_state_mutated: bool,
+}
+
+impl<M, Eqn> Clone for ExponentialRosenbrock<'_, Eqn, M>
+where
+    M: DenseMatrix<T = Eqn::T, V = Eqn::V>,
+    Eqn: OdeEquationsImplicit,
+    Eqn::V: DefaultDenseMatrix<T = Eqn::T>,
+{
+    fn clone(&self) -> Self {
+        Self {
+            tableau: self.tableau.clone(),
+            problem: self.problem,
+            state: self.state.clone(),
+            krylov: self.krylov.clone(),
+            stage_inc: self.stage_inc.clone(),
+            ft: self.ft.clone(),
+            u: self.u.clone(),
+            d: self.d.clone(),
+            jd: self.jd.clone(),
+            old_t: self.old_t,
+            old_y: self.old_y.clone(),
+            old_f: self.old_f.clone(),
+            old_g: self.old_g.clone(),
+            old_dg: self.old_dg.clone(),
+            statistics: self.statistics.clone(),
+            root_finder: self.root_finder.clone(),
+            tstop: self.tstop,
+            is_state_mutated: self.is_state_mutated,
+        }
+    }
+}
+
+impl<'a, M, Eqn> ExponentialRosenbrock<'a, Eqn, M>
+where
+    M: DenseMatrix<T = Eqn::T, V = Eqn::V>,
+    Eqn: OdeEquationsImplicit,
+    Eqn::V: DefaultDenseMatrix<T = Eqn::T>,
+    for<'b> &'b Eqn::V: VectorRef<Eqn::V>,
+    for<'b> &'b Eqn::M: MatrixRef<Eqn::M>,
+{
+    const SAFETY: f64 = 0.9;
+    const MIN_FACTOR: f64 = 0.2;
+    const MAX_FACTOR: f64 = 10.0;
+    const MIN_TIMESTEP: f64 = 1e-13;
+    const KRYLOV_MAX_DIM: usize = 40;
+    const KRYLOV_TOL: f64 = 0.05;
+    const KRYLOV_FAIL_FACTOR: f64 = 0.5;
+
+    pub fn new(
+        problem: &'a OdeSolverProblem<Eqn>,
+        mut state: SdirkState<Eqn::V>,
+        tableau: ExponentialRosenbrockTableau<M>,
+    ) -> Result<Self, DiffsolError> {
+        if problem.eqn.mass().is_some() {
+            return Err(ode_solver_error!(
+                Other,
+                "Exponential Rosenbrock solvers do not support a mass matrix"
+            ));
+        }
+
+        // check that the c vector is valid
+        let s = tableau.s();
+        assert_eq!(
+            tableau.c()[0],
+            Eqn::T::zero(),
+            "Invalid tableau, expected c(0) = 0"
+        );
+        for i in 0..s {
+            assert!(
+                tableau.c()[i] >= Eqn::T::zero() && tableau.c()[i] <= Eqn::T::one(),
+                "Invalid tableau, expected 0 <= c({}) <= 1",
+                i
+            );
+        }
+
+        state.check_consistent_with_problem(problem)?;
+        state.set_problem(problem)?;
+
+        let nstates = state.y.len();
+        let old_t = state.t;
+        let old_y = state.y.clone();
+        let old_f = state.dy.clone();
+        let old_g = state.g.clone();
+        let old_dg = state.dg.clone();
+
+        let root_finder = if let Some(root_fn) = problem.eqn.root() {
+            let root_finder = RootFinder::new(root_fn.nout());
+            root_finder.init(&root_fn, &state.y, state.t);
+            Some(root_finder)
+        } else {
+            None
+        };
+
+        Ok(Self {
+            tableau,
+            problem,
+            state,
+            krylov: vec![KrylovPhi::new(nstates, Self::KRYLOV_MAX_DIM); s + 1],
+            stage_inc: vec![<Eqn::V as Vector>::zeros(nstates); s + 1],
+            ft: <Eqn::V as Vector>::zeros(nstates),
+            u: <Eqn::V as Vector>::zeros(nstates),
+            d: <Eqn::V as Vector>::zeros(nstates),
+            jd: <Eqn::V as Vector>::zeros(nstates),
+            old_t,
+            old_y,
+            old_f,
+            old_g,
+            old_dg,
+            statistics: BdfStatistics::default(),
+            root_finder,
+            tstop: None,
+            is_state_mutated: false,
+        })
+    }
+
+    /// Returns the solver statistics. No linear solver is used, so the number of Arnoldi iterations is reported as
+    /// `number_of_nonlinear_solver_iterations`, and the number of times the Krylov approximation failed to converge as `number_of_nonlinear_solver_fails`.
+    pub fn get_statistics(&self) -> &BdfStatistics {
+        &self.statistics
+    }
+
+    fn handle_tstop(
+        &mut self,
+        tstop: Eqn::T,
+    ) -> Result<Option<OdeSolverStopReason<Eqn::T>>, DiffsolError> {
+        match check_tstop(self.state.t, self.state.h, tstop)? {
+            TstopCheck::Reached => {
+                self.tstop = None;
+                return Ok(Some(OdeSolverStopReason::TstopReached));
+            }
+            TstopCheck::ScaleStep(factor) => {
+                self.state.h *= factor;
+            }
+            TstopCheck::NotReached => {}
+        }
+        Ok(None)
+    }
+
+    /// increment used to approximate the time derivative at the start of the step
+    fn time_increment(t: Eqn::T, h: Eqn::T) -> Eqn::T {
+        let dt = Eqn::T::EPSILON.sqrt() * (Eqn::T::one() + abs(t));
+        if h < Eqn::T::zero() {
+            -dt
+        } else {
+            dt
+        }
+    }
+
+    /// Build the Krylov subspace `krylov[i]` for the vector `w`, so that the error in `mult * phi_k(h J) w` is within tolerance.
+    /// Returns false if the Krylov approximation did not converge.
+    fn build_krylov(&mut self, i: usize, w: &Eqn::V, h: Eqn::T, p: usize, mult: Eqn::T) -> bool {
+        let problem = self.problem;
+        let state = &self.state;
+        let rhs = problem.eqn.rhs();
+        let tol = Eqn::T::from(Self::KRYLOV_TOL);
+        let krylov = &mut self.krylov[i];
+        let converged = krylov.build(
+            |v, y| rhs.jac_mul_inplace(&state.y, state.t, v, y),
+            w,
+            h,
+            p,
+            |v| abs(mult) * v.squared_norm(&state.y, &problem.atol, problem.rtol).sqrt() / tol,
+        );
+        self.statistics.number_of_nonlinear_solver_iterations += krylov.niter();
+        converged
+    }
+
+    /// Calculate the increments of the stages (stage_inc[1..s]) and the solution (stage_inc[s]) over the step,
+    /// and the error estimate. The Krylov subspaces for `f(t, y)`, `w` and the `D_j` are kept in `krylov[0]`, `krylov[1]`
+    /// and `krylov[j + 1]` for the dense output. Returns false if one of the Krylov approximations did not converge.
+    fn calculate_increments(&mut self, h: Eqn::T, error: &mut Eqn::V) -> bool {
+        let problem = self.problem;
+        let s = self.tableau.s();
+        let nphi = self.tableau.nphi();
+        let t0 = self.state.t;
+        let one = Eqn::T::one();
+        let zero = Eqn::T::zero();
+
+        // the stage at index s is the solution at the end of the step, which has c = 1
+        let c = (0..=s)
+            .map(|i| if i == s { one } else { self.tableau.c()[i] })
+            .collect::<Vec<_>>();
+        for inc in self.stage_inc.iter_mut() {
+            inc.fill(zero);
+        }
+        error.fill(zero);
+
+        // contribution of the rhs, c_i h phi_1(c_i h J) f(t, y)
+        let f0 = self.state.dy.clone();
+        if !self.build_krylov(0, &f0, h, 1, h) {
+            return false;
+        }
+        for (&ci, inc) in c.iter().zip(self.stage_inc.iter_mut()).skip(1) {
+            self.krylov[0].add_phi(ci, &[ci * h], inc);
+        }
+
+        // contribution of the time derivative, c_i^2 h^2 phi_2(c_i h J) w
+        let ft = self.ft.clone();
+        if !self.build_krylov(1, &ft, h, 2, h * h) {
+            return false;
+        }
+        for (&ci, inc) in c.iter().zip(self.stage_inc.iter_mut()).skip(1) {
+            self.krylov[1].add_phi(ci, &[zero, ci * ci * h * h], inc);
+        }
+
+        let mut coeffs = vec![zero; nphi];
+        for j in 1..s {
+            let cj = c[j];
+            let tj = t0 + cj * h;
+
+            // D_j = f(t + c_j h, U_j) - f(t, y) - J (U_j - y) - c_j h w
+            self.u.copy_from(&self.state.y);
+            self.u.axpy(one, &self.stage_inc[j], one);
+            let rhs = problem.eqn.rhs();
+            rhs.call_inplace(&self.u, tj, &mut self.d);
+            rhs.jac_mul_inplace(&self.state.y, t0, &self.stage_inc[j], &mut self.jd);
+            self.d.axpy(-one, &self.state.dy, one);
+            self.d.axpy(-one, &self.jd, one);
+            self.d.axpy(-cj * h, &self.ft, one);
+
+            // contributions of D_j to the later stages, the solution and the error
+            let d = self.d.clone();
+            if !self.build_krylov(j + 1, &d, h, nphi, h) {
+                return false;
+            }
+            let krylov = &self.krylov[j + 1];
+            let stages = c.iter().zip(self.stage_inc.iter_mut()).enumerate();
+            for (i, (&ci, inc)) in stages.take(s).skip(j + 1) {
+                for (k, ck) in coeffs.iter_mut().enumerate() {
+                    *ck = h * self.tableau.a(i)[(j, k)];
+                }
+                krylov.add_phi(ci, &coeffs, inc);
+            }
+            for (k, ck) in coeffs.iter_mut().enumerate() {
+                *ck = h * self.tableau.b()[(j, k)];
+            }
+            krylov.add_phi(one, &coeffs, &mut self.stage_inc[s]);
+            for (k, ck) in coeffs.iter_mut().enumerate() {
+                *ck = h * self.tableau.d()[(j, k)];
+            }
+            krylov.add_phi(one, &coeffs, error);
+        }
+        true
+    }
+
+    /// The solution at `old_t + theta h` within the last step, using the continuous extension of the method
+    /// and the Krylov subspaces of the step (see [Self::calculate_increments])
+    fn dense_output(&self, theta: Eqn::T) -> Eqn::V {
+        let s = self.tableau.s();
+        let nphi = self.tableau.nphi();
+        let h = self.state.t - self.old_t;
+        let zero = Eqn::T::zero();
+        let mut y = self.old_y.clone();
+        self.krylov[0].add_phi(theta, &[theta * h], &mut y);
+        self.krylov[1].add_phi(theta, &[zero, theta * theta * h * h], &mut y);
+        let mut coeffs = vec![zero; nphi];
+        for j in 1..s {
+            let mut theta_pow = theta;
+            for (k, ck) in coeffs.iter_mut().enumerate() {
+                *ck = h * self.tableau.b()[(j, k)] * theta_pow;
+                theta_pow *= theta;
+            }
+            self.krylov[j + 1].add_phi(theta, &coeffs, &mut y);
+        }
+        y
+    }
+
+    fn interpolate_hermite(
+        theta: Eqn::T,
+        dt: Eqn::T,
+        u0: &Eqn::V,
+        u1: &Eqn::V,
+        f0: &Eqn::V,
+        f1: &Eqn::V,
+    ) -> Eqn::V {
+        u0 * scale(Eqn::T::from(1.0) - theta)
+            + u1 * scale(theta)
+            + ((u1 - u0) * scale(Eqn::T::from(1.0) - Eqn::T::from(2.0) * theta)
+                + f0 * scale(dt * (theta - Eqn::T::from(1.0)))
+                + f1 * scale(dt * theta))
+                * scale(theta * (theta - Eqn::T::from(1.0)))
+    }
+
+    fn interpolation_theta(&self, t: Eqn::T) -> Result<Eqn::T, DiffsolError> {
+        let state = &self.state;
+
+        // check that t is within the last step
+        check_interpolation_time(t, self.old_t, state.t)?;
+        let dt = state.t - self.old_t;
+        if dt == Eqn::T::zero() {
+            Ok(Eqn::T::one())
+        } else {
+            Ok((t - self.old_t) / dt)
+        }
+    }
+
+    fn _update_step_size(&mut self, factor: Eqn::T) -> Result<Eqn::T, DiffsolError> {
+        let new_h = self.state.h * factor;
+
+        // if step size too small, then fail
+        if abs(new_h) < Eqn::T::from(Self::MIN_TIMESTEP) {
+            return Err(DiffsolError::from(OdeSolverError::StepSizeTooSmall {
+                time: self.state.t.into(),
+            }));
+        }
+
+        // update state
+        self.state.h = new_h;
+
+        Ok(new_h)
+    }
+}
+
+impl<'a, M, Eqn> OdeSolverMethod<'a, Eqn> for ExponentialRosenbrock<'a, Eqn, M>
+where
+    M: DenseMatrix<T = Eqn::T, V = Eqn::V>,
+    Eqn: OdeEquationsImplicit,
+    Eqn::V: DefaultDenseMatrix<T = Eqn::T>,
+    for<'b> &'b Eqn::V: VectorRef<Eqn::V>,
+    for<'b> &'b Eqn::M: MatrixRef<Eqn::M>,
+{
+    type State = SdirkState<Eqn::V>;
+
+    fn problem(&self) -> &'a OdeSolverProblem<Eqn> {
+        self.problem
+    }
+
+    fn order(&self) -> usize {
+        self.tableau.order()
+    }
+
+    fn set_state(&mut self, state: Self::State) {
+        self.state = state;
+
+        // the rhs is made consistent with the new state on the next step, and the solution can only be interpolated once it is taken
+        self.is_state_mutated = true;
+    }
+
+    fn into_state(self) -> SdirkState<Eqn::V> {
+        self.state
+    }
+
+    fn checkpoint(&mut self) -> Self::State {
+        self.state.clone()
+    }
+
+    fn step(&mut self) -> Result<OdeSolverStopReason<Eqn::T>, DiffsolError> {
+        let n = self.state.y.len();
+        let problem = self.problem;
+        let one = Eqn::T::one();
+
+        if self.is_state_mutated {
+            // reinitialise root finder if needed
+            init_root_finder(
+                problem,
+                self.root_finder.as_ref(),
+                &self.state.y,
+                self.state.t,
+            );
+            // reinitialise tstop if needed
+            if let Some(t_stop) = self.tstop {
+                self.set_stop_time(t_stop)?;
+            }
+            let state = &mut self.state;
+            problem
+                .eqn
+                .rhs()
+                .call_inplace(&state.y, state.t, &mut state.dy);
+            if problem.integrate_out {
+                problem
+                    .eqn
+                    .out()
+                    .unwrap()
+                    .call_inplace(&state.y, state.t, &mut state.dg);
+            }
+
+            self.is_state_mutated = false;
+        }
+
+        // approximate the time derivative of the rhs at the start of the step
+        let t0 = self.state.t;
+        let dt = Self::time_increment(t0, self.state.h);
+        problem
+            .eqn
+            .rhs()
+            .call_inplace(&self.state.y, t0 + dt, &mut self.ft);
+        self.ft.axpy(-one / dt, &self.state.dy, one / dt);
+
+        let mut error = <Eqn::V as Vector>::zeros(n);
+        let mut factor: Eqn::T;
+
+        // loop until step is accepted
+        loop {
+            let h = self.state.h;
+
+            if !self.calculate_increments(h, &mut error) {
+                // krylov approximation did not converge, so reduce step size and try again
+                self.statistics.number_of_nonlinear_solver_fails += 1;
+                self._update_step_size(Eqn::T::from(Self::KRYLOV_FAIL_FACTOR))?;
+                continue;
+            }
+
+            // solution at the end of the step is stored in old_y for now
+            let s = self.tableau.s();
+            self.old_y.copy_from(&self.state.y);
+            self.old_y.axpy(one, &self.stage_inc[s], one);
+
+            // adjust step size based on error, the embedded method is one order lower than the main method
+            let error_norm = error.squared_norm(&self.old_y, &problem.atol, problem.rtol);
+            let order = self.tableau.order() as f64;
+            factor = Eqn::T::from(Self::SAFETY) * error_norm.pow(Eqn::T::from(-0.5 / order));
+            if factor < Eqn::T::from(Self::MIN_FACTOR) {
+                factor = Eqn::T::from(Self::MIN_FACTOR);
+            }
+            if factor > Eqn::T::from(Self::MAX_FACTOR) {
+                factor = Eqn::T::from(Self::MAX_FACTOR);
+            }
+
+            // test error is within tolerance
+            if error_norm <= one {
+                break;
+            }
+            // step is rejected, factor reduces step size, so we try again with the smaller step size
+            self.statistics.number_of_error_test_failures += 1;
+            self._update_step_size(factor)?;
+        }
+
+        // take the step
+        {
+            let state = &mut self.state;
+            let t1 = state.t + state.h;
+            problem
+                .eqn
+                .rhs()
+                .call_inplace(&self.old_y, t1, &mut self.old_f);
+            self.old_t = state.t;
+            state.t = t1;
+
+            // old_y and old_f are now the new y and dy
+            std::mem::swap(&mut self.old_y, &mut state.y);
+            std::mem::swap(&mut self.old_f, &mut state.dy);
+        }
+
+        // integrate output function using simpson's rule on the dense output
+        if problem.integrate_out {
+            let out = problem.eqn.out().unwrap();
+            let dt = self.state.t - self.old_t;
+            let y_mid = self.dense_output(Eqn::T::from(0.5));
+            let state = &mut self.state;
+            let dg_mid = out.call(&y_mid, self.old_t + Eqn::T::from(0.5) * dt);
+            out.call_inplace(&state.y, state.t, &mut self.old_dg);
+            std::mem::swap(&mut self.old_dg, &mut state.dg);
+            self.old_g.copy_from(&state.g);
+            let w = dt / Eqn::T::from(6.0);
+            state.g.axpy(w, &self.old_dg, one);
+            state.g.axpy(Eqn::T::from(4.0) * w, &dg_mid, one);
+            state.g.axpy(w, &state.dg, one);
+        }
+
+        // update step size for next step
+        self._update_step_size(factor)?;
+
+        // update statistics
+        self.statistics.number_of_steps += 1;
+
+        // check for root within accepted step
+        if let Some(root_fn) = problem.eqn.root() {
+            let ret = self.root_finder.as_ref().unwrap().check_root(
+                &|t| self.interpolate(t),
+                &root_fn,
+                &self.state.y,
+                self.state.t,
+            );
+            if let Some(root) = ret {
+                return Ok(OdeSolverStopReason::RootFound(root));
+            }
+        }
+
+        // check if we are at tstop
+        if let Some(tstop) = self.tstop {
+            if let Some(reason) = self.handle_tstop(tstop).unwrap() {
+                return Ok(reason);
+            }
+        }
+
+        // just a normal step, no roots or tstop reached
+        Ok(OdeSolverStopReason::InternalTimestep)
+    }
+
+    fn set_stop_time(&mut self, tstop: <Eqn as Op>::T) -> Result<(), DiffsolError> {
+        self.tstop = Some(tstop);
+        if let Some(OdeSolverStopReason::TstopReached) = self.handle_tstop(tstop)? {
+            let error = OdeSolverError::StopTimeBeforeCurrentTime {
+                stop_time: tstop.into(),
+                state_time: self.state.t.into(),
+            };
+            self.tstop = None;
+            return Err(DiffsolError::from(error));
+        }
+        Ok(())
+    }
+
+    fn interpolate_sens(&self, _t: <Eqn as Op>::T) -> Result<Vec<<Eqn as Op>::V>, DiffsolError> {
+        Ok(vec![])
+    }
+
+    fn interpolate(&self, t: <Eqn>::T) -> Result<<Eqn>::V, DiffsolError> {
+        if self.is_state_mutated {
+            if t == self.state.t {
+                return Ok(self.state.y.clone());
+            } else {
+                return Err(ode_solver_error!(InterpolationTimeOutsideCurrentStep));
+            }
+        }
+        let theta = self.interpolation_theta(t)?;
+        if theta == Eqn::T::one() {
+            return Ok(self.state.y.clone());
+        }
+        Ok(self.dense_output(theta))
+    }
+
+    fn interpolate_out(&self, t: <Eqn>::T) -> Result<<Eqn>::V, DiffsolError> {
+        if self.is_state_mutated {
+            if t == self.state.t {
+                return Ok(self.state.g.clone());
+            } else {
+                return Err(ode_solver_error!(InterpolationTimeOutsideCurrentStep));
+            }
+        }
+        let theta = self.interpolation_theta(t)?;
+        Ok(Self::interpolate_hermite(
+            theta,
+            self.state.t - self.old_t,
+            &self.old_g,
+            &self.state.g,
+            &self.old_dg,
+            &self.state.dg,
+        ))
+    }
+
+    fn state(&self) -> StateRef<Eqn::V> {
+        self.state.as_ref()
+    }
+
+    fn state_mut(&mut self) -> StateRefMut<Eqn::V> {
+        self.is_state_mutated = true;
+        self.state.as_mut()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        ode_solver::{
+            test_models::{
+                exponential_decay::{
+                    exponential_decay_problem, exponential_decay_problem_with_root,
+                    negative_exponential_decay_problem,
+                },
+                robertson_ode::robertson_ode,
+            },
+            tests::{
+                test_checkpointing, test_interpolate, test_ode_solver, test_problem,
+                test_state_mut, test_state_mut_on_problem,
+            },
+        },
+        OdeEquations, Op, SparseColMat,
+    };
+
+    use num_traits::abs;
+
+    type M = nalgebra::DMatrix<f64>;
+
+    #[test]
+    fn exponential_rosenbrock_state_mut() {
+        test_state_mut(test_problem::<M>().exprb43().unwrap());
+    }
+
+    #[test]
+    fn exponential_rosenbrock_test_interpolate() {
+        test_interpolate(test_problem::<M>().exprb43().unwrap());
+    }
+
+    #[test]
+    fn exponential_rosenbrock_test_checkpointing() {
+        let (problem, soln) = exponential_decay_problem::<M>(false);
+        let s1 = problem.exprb43().unwrap();
+        let s2 = problem.exprb43().unwrap();
+        test_checkpointing(soln, s1, s2);
+    }
+
+    #[test]
+    fn exponential_rosenbrock_test_state_mut_exponential_decay() {
+        let (p, soln) = exponential_decay_problem::<M>(false);
+        let s = p.exprb43().unwrap();
+        test_state_mut_on_problem(s, soln);
+    }
+
+    #[test]
+    fn exponential_rosenbrock_test_nalgebra_negative_exponential_decay() {
+        let (problem, soln) = negative_exponential_decay_problem::<M>(false);
+        let mut s = problem.exprb43().unwrap();
+        test_ode_solver(&mut s, soln, None, false, false);
+    }
+
+    #[test]
+    fn test_exprb32_nalgebra_exponential_decay() {
+        let (problem, soln) = exponential_decay_problem::<M>(false);
+        let mut s = problem.exprb32().unwrap();
+        test_ode_solver(&mut s, soln, None, false, false);
+    }
+
+    #[test]
+    fn test_exprb43_nalgebra_exponential_decay() {
+        let (problem, soln) = exponential_decay_problem::<M>(false);
+        let mut s = problem.exprb43().unwrap();
+        test_ode_solver(&mut s, soln, None, false, false);
+        let statistics = s.get_statistics();
+        assert_eq!(statistics.number_of_linear_solver_setups, 0);
+        assert!(statistics.number_of_nonlinear_solver_iterations > 0);
+
+        // the jacobian is only used via jacobian-vector products
+        assert_eq!(problem.eqn.rhs().statistics().number_of_matrix_evals, 0);
+        assert!(problem.eqn.rhs().statistics().number_of_jac_muls > 0);
+    }
+
+    #[test]
+    fn test_exprb43_faer_sparse_exponential_decay() {
+        let (problem, soln) = exponential_decay_problem::<SparseColMat<f64>>(false);
+        let mut s = problem.exprb43().unwrap();
+        test_ode_solver(&mut s, soln, None, false, false);
+        assert_eq!(problem.eqn.rhs().statistics().number_of_matrix_evals, 0);
+    }
+
+    #[test]
+    fn test_exprb32_nalgebra_robertson_ode() {
+        let (problem, soln) = robertson_ode::<M>(false, 1);
+        let mut s = problem.exprb32().unwrap();
+        test_ode_solver(&mut s, soln, None, false, false);
+    }
+
+    #[test]
+    fn test_exprb43_faer_sparse_robertson_ode() {
+        let (problem, soln) = robertson_ode::<SparseColMat<f64>>(false, 3);
+        let mut s = problem.exprb43().unwrap();
+        test_ode_solver(&mut s, soln, None, false, false);
+        assert_eq!(problem.eqn.rhs().statistics().number_of_matrix_evals, 0);
+    }
+
+    #[test]
+    fn test_tstop_exprb43() {
+        let (problem, soln) = exponential_decay_problem::<M>(false);
+        let mut s = problem.exprb43().unwrap();
+        test_ode_solver(&mut s, soln, None, true, false);
+    }
+
+    #[test]
+    fn test_root_finder_exprb43() {
+        let (problem, soln) = exponential_decay_problem_with_root::<M>(false);
+        let mut s = problem.exprb43().unwrap();
+        let y = test_ode_solver(&mut s, soln, None, false, false);
+        assert!(abs(y[0] - 0.6) < 1e-6, "y[0] = {}", y[0]);
+    }
+}
@@ -0,0 +1,137 @@
+use crate::{DenseMatrix, Vector};
+use num_traits::Zero;
+
+/// A tableau for an exponential Rosenbrock method.
+///
+/// For a problem `y' = f(t, y)`, with `J` the Jacobian and `w` the time derivative of `f` at the start of the step, the stages `U_i` are given by
+///
+/// ```text
+/// U_i = y + c_i h phi_1(c_i h J) f(t, y) + c_i^2 h^2 phi_2(c_i h J) w + h sum_{j < i} sum_k a_i(j, k) phi_{k+1}(c_i h J) D_j
+/// D_j = f(t + c_j h, U_j) - f(t, y) - J (U_j - y) - c_j h w
+/// ```
+///
+/// where the `phi_k` are the phi functions (`phi_0(z) = exp(z)` and `phi_{k+1}(z) = (phi_k(z) - 1 / k!) / z`).
+/// The solution is `y + h phi_1(h J) f(t, y) + h^2 phi_2(h J) w + h sum_j sum_k b(j, k) phi_{k+1}(h J) D_j`,
+/// and `h sum_j sum_k d(j, k) phi_{k+1}(h J) D_j` is an estimate of the local error.
+///
+/// Column `k` of the `a_i`, `b` and `d` matrices holds the coefficients of `phi_{k+1}`, and the first row of each is zero since `D_0 = 0`.
+#[derive(Clone)]
+pub struct ExponentialRosenbrockTableau<M: DenseMatrix> {
+    a: Vec<M>,
+    b: M,
+    d: M,
+    c: M::V,
+    order: usize,
+}
+
+impl<M: DenseMatrix> ExponentialRosenbrockTableau<M> {
+    /// exprb32, a 2-stage exponential Rosenbrock method of order 3, with the exponential Rosenbrock-Euler method as the embedded method of order 2,
+    /// from M. Hochbruck, A. Ostermann and J. Schweitzer, Exponential Rosenbrock-type methods, SIAM J. Numer. Anal. 47 (2009) 786–803.
+    pub fn exprb32() -> Self {
+        let a = vec![M::zeros(2, 3), M::zeros(2, 3)];
+
+        let mut b = M::zeros(2, 3);
+        b[(1, 2)] = M::T::from(2.0);
+
+        let d = b.clone();
+
+        let c = M::V::from_vec(vec![M::T::from(0.0), M::T::from(1.0)]);
+        Self::new(a, b, d, c, 3)
+    }
+
+    /// exprb43, a 3-stage exponential Rosenbrock method of order 4, with an embedded method of order 3,
+    /// from M. Hochbruck, A. Ostermann and J. Schweitzer, Exponential Rosenbrock-type methods, SIAM J. Numer. Anal. 47 (2009) 786–803.
+    pub fn exprb43() -> Self {
+        let mut a = vec![M::zeros(3, 4), M::zeros(3, 4), M::zeros(3, 4)];
+        a[2][(1, 0)] = M::T::from(1.0);
+
+        let mut b = M::zeros(3, 4);
+        b[(1, 2)] = M::T::from(16.0);
+        b[(1, 3)] = M::T::from(-48.0);
+        b[(2, 2)] = M::T::from(-2.0);
+        b[(2, 3)] = M::T::from(12.0);
+
+        let mut d = M::zeros(3, 4);
+        d[(1, 3)] = M::T::from(-48.0);
+        d[(2, 3)] = M::T::from(12.0);
+
+        let c = M::V::from_vec(vec![M::T::from(0.0), M::T::from(0.5), M::T::from(1.0)]);
+        Self::new(a, b, d, c, 4)
+    }
+
+    pub fn new(a: Vec<M>, b: M, d: M, c: M::V, order: usize) -> Self {
+        let s = c.len();
+        let nphi = b.ncols();
+        assert!(
+            nphi > 0,
+            "Invalid tableau, expected at least one phi function"
+        );
+        assert_eq!(a.len(), s, "Invalid number of a matrices, expected {}", s);
+        for (i, ai) in a.iter().enumerate() {
+            assert_eq!(
+                ai.nrows(),
+                s,
+                "Invalid number of rows in a_{}, expected {}",
+                i,
+                s
+            );
+            assert_eq!(
+                ai.ncols(),
+                nphi,
+                "Invalid number of columns in a_{}, expected {}",
+                i,
+                nphi
+            );
+            for j in i..s {
+                for k in 0..nphi {
+                    assert_eq!(
+                        ai[(j, k)],
+                        M::T::zero(),
+                        "Invalid tableau, expected a_{}({}, {}) = 0 for j >= i",
+                        i,
+                        j,
+                        k
+                    );
+                }
+            }
+        }
+        assert_eq!(b.nrows(), s, "Invalid number of rows in b, expected {}", s);
+        assert_eq!(d.nrows(), s, "Invalid number of rows in d, expected {}", s);
+        assert_eq!(
+            d.ncols(),
+            nphi,
+            "Invalid number of columns in d, expected {}",
+            nphi
+        );
+        Self { a, b, d, c, order }
+    }
+
+    pub fn order(&self) -> usize {
+        self.order
+    }
+
+    pub fn s(&self) -> usize {
+        self.c.len()
+    }
+
+    /// the number of phi functions used by the method, not including those applied to `f(t, y)` and `w`
+    pub fn nphi(&self) -> usize {
+        self.b.ncols()
+    }
+
+    pub fn a(&self, i: usize) -> &M {
+        &self.a[i]
+    }
+
+    pub fn b(&self) -> &M {
+        &self.b
+    }
+
+    pub fn d(&self) -> &M {
+        &self.d
+    }
+
+    pub fn c(&self) -> &M::V {
+        &self.c
+    }
+}
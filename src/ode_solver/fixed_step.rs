@@ -0,0 +1,106 @@
+use num_traits::abs;
+
+use crate::{
+    error::{DiffsolError, OdeSolverError},
+    ode_solver_error, Scalar,
+};
+
+/// The steps taken by a solver in fixed-step mode (see [crate::Bdf::set_fixed_step] and [crate::Sdirk::set_fixed_step]).
+///
+/// In fixed-step mode the step size is never adapted: error test failures are ignored, and if the nonlinear solver fails to converge
+/// for a step (even after updating the Jacobian) the error is returned rather than reducing the step size. If a stop time is set then the
+/// step that would pass it is shortened so that the solver stops exactly at the stop time.
+#[derive(Clone, Debug)]
+pub enum FixedStep<T: Scalar> {
+    /// Take steps of a constant size `h` (negative to integrate backwards in time)
+    Constant(T),
+    /// Step from one point of the time grid to the next. The grid must be strictly monotonic, and the solver will step to the
+    /// first grid point after the current time, so the grid need not start at the initial time.
+    Grid(Vec<T>),
+}
+
+impl<T: Scalar> FixedStep<T> {
+    pub(crate) fn check(&self) -> Result<(), DiffsolError> {
+        match self {
+            FixedStep::Constant(h) => {
+                if *h == T::zero() || abs(*h) >= T::INFINITY || Scalar::is_nan(*h) {
+                    return Err(ode_solver_error!(
+                        Other,
+                        "Fixed step size must be non-zero and finite"
+                    ));
+                }
+            }
+            FixedStep::Grid(grid) => {
+                let direction = Self::direction(grid);
+                if grid.len() < 2
+                    || grid
+                        .windows(2)
+                        .any(|w| (w[1] - w[0]) * direction <= T::zero())
+                {
+                    return Err(ode_solver_error!(
+                        Other,
+                        "Fixed step time grid must have at least two points and be strictly monotonic"
+                    ));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn direction(grid: &[T]) -> T {
+        if grid.len() > 1 && grid[grid.len() - 1] < grid[0] {
+            -T::one()
+        } else {
+            T::one()
+        }
+    }
+
+    /// Return the size of the step to take from time `t`, or an error if `t` is at (or after) the end of the time grid.
+    pub(crate) fn step_size(&self, t: T) -> Result<T, DiffsolError> {
+        match self {
+            FixedStep::Constant(h) => Ok(*h),
+            FixedStep::Grid(grid) => {
+                let direction = Self::direction(grid);
+                grid.iter()
+                    .find(|&&g| {
+                        let troundoff = T::from(100.0) * T::EPSILON * (abs(t) + abs(g));
+                        (g - t) * direction > troundoff
+                    })
+                    .map(|&g| g - t)
+                    .ok_or_else(|| {
+                        ode_solver_error!(
+                            Other,
+                            format!(
+                                "Time t = {} is at or beyond the end of the fixed step time grid",
+                                t
+                            )
+                        )
+                    })
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::FixedStep;
+
+    #[test]
+    fn test_fixed_step_grid() {
+        let grid = FixedStep::Grid(vec![0.0, 0.5, 1.5, 2.0]);
+        grid.check().unwrap();
+        assert_eq!(grid.step_size(0.0).unwrap(), 0.5);
+        assert_eq!(grid.step_size(0.5).unwrap(), 1.0);
+        assert_eq!(grid.step_size(1.0).unwrap(), 0.5);
+        assert!(grid.step_size(2.0).is_err());
+
+        let backwards = FixedStep::Grid(vec![1.0, 0.0, -1.0]);
+        backwards.check().unwrap();
+        assert_eq!(backwards.step_size(1.0).unwrap(), -1.0);
+
+        assert!(FixedStep::Grid(vec![0.0, 1.0, 1.0]).check().is_err());
+        assert!(FixedStep::Grid(vec![0.0]).check().is_err());
+        assert!(FixedStep::Constant(0.0).check().is_err());
+        assert!(FixedStep::Constant(-0.1).check().is_ok());
+    }
+}
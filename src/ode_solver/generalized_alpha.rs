@@ -0,0 +1,761 @@
+use crate::error::DiffsolError;
+use crate::error::OdeSolverError;
+use crate::matrix::MatrixRef;
+use crate::ode_solver_error;
+use crate::vector::VectorRef;
+use crate::{
+    op::newmark::NewmarkCallable, scale, Convergence, ConvergenceStatus, LinearSolver, NonLinearOp,
+    OdeEquationsSecondOrder, OdeSolverMethod, OdeSolverProblem, OdeSolverState,
+    OdeSolverStopReason, Op, RootFinder, Scalar, SdirkState, StateRef, StateRefMut, Vector,
+};
+use num_traits::abs;
+use num_traits::One;
+use num_traits::Pow;
+use num_traits::Zero;
+
+use super::bdf::BdfStatistics;
+use super::method::{check_interpolation_time, check_tstop, init_root_finder, TstopCheck};
+
+/// The parameters `alpha_m`, `alpha_f`, `beta` and `gamma` of the generalized-alpha method (see [GeneralizedAlpha]).
+#[derive(Clone, Copy, Debug)]
+pub struct GeneralizedAlphaParameters<T: Scalar> {
+    alpha_m: T,
+    alpha_f: T,
+    beta: T,
+    gamma: T,
+}
+
+impl<T: Scalar> GeneralizedAlphaParameters<T> {
+    pub fn new(alpha_m: T, alpha_f: T, beta: T, gamma: T) -> Self {
+        Self {
+            alpha_m,
+            alpha_f,
+            beta,
+            gamma,
+        }
+    }
+
+    /// the Newmark average acceleration (trapezoidal) method, with `beta = 1/4` and `gamma = 1/2`.
+    /// This is unconditionally stable and second order, but has no numerical damping of high frequencies.
+    pub fn newmark() -> Self {
+        Self::new(T::zero(), T::zero(), T::from(0.25), T::from(0.5))
+    }
+
+    /// the generalized-alpha method of Chung & Hulbert (1993), with the parameters chosen to give a spectral radius of `rho_inf` (between 0 and 1) at infinite frequency.
+    /// This is unconditionally stable and second order, and `rho_inf < 1` damps the high frequencies of the problem, with `rho_inf = 0` annihilating them in a single step.
+    ///
+    /// Chung, J., & Hulbert, G. M. (1993). A time integration algorithm for structural dynamics with improved numerical dissipation: the generalized-alpha method. Journal of Applied Mechanics, 60(2), 371-375.
+    pub fn chung_hulbert(rho_inf: T) -> Self {
+        assert!(
+            rho_inf >= T::zero() && rho_inf <= T::one(),
+            "Invalid spectral radius, expected a value between 0 and 1, got {}",
+            rho_inf
+        );
+        let one = T::one();
+        let alpha_m = (T::from(2.0) * rho_inf - one) / (rho_inf + one);
+        let alpha_f = rho_inf / (rho_inf + one);
+        let gamma = T::from(0.5) - alpha_m + alpha_f;
+        let beta = T::from(0.25) * (one - alpha_m + alpha_f) * (one - alpha_m + alpha_f);
+        Self::new(alpha_m, alpha_f, beta, gamma)
+    }
+
+    pub fn alpha_m(&self) -> T {
+        self.alpha_m
+    }
+    pub fn alpha_f(&self) -> T {
+        self.alpha_f
+    }
+    pub fn beta(&self) -> T {
+        self.beta
+    }
+    pub fn gamma(&self) -> T {
+        self.gamma
+    }
+}
+
+/// The generalized-alpha method for second-order problems `M y'' = f(t, y, y')` (see [OdeEquationsSecondOrder]), suitable for damped and stiff
+/// problems in structural dynamics. The Newmark average acceleration method is included as the special case [GeneralizedAlphaParameters::newmark].
+///
+/// Each step solves for the acceleration at the end of the step, such that the equation of motion is satisfied at the intermediate time
+/// `t_{n+1-alpha_f}`, using a Newton iteration with the matrix `(1 - alpha_m) M - (1 - alpha_f) (beta h^2 J_y + gamma h J_v)`, where `J_y` and `J_v`
+/// are the Jacobians of the force with respect to the positions and velocities. This matrix is factorised once per step using the `LS` linear solver.
+///
+/// The step size is controlled using the local error estimate of the positions `h^2 (beta - 1/6) (a_{n+1} - a_n)` (Zienkiewicz & Xie, 1991), and
+/// Hermite interpolation is used for dense output. The acceleration stored in the time derivative of the state is the algorithmic acceleration of the method,
+/// which is a second order approximation to the true acceleration.
+///
+/// Restrictions:
+/// - The mass matrix, if present, must be `diag(I, M)` where `M` is non-singular (i.e. there are no algebraic constraints).
+/// - Forward sensitivities are not supported.
+pub struct GeneralizedAlpha<'a, Eqn, LS>
+where
+    LS: LinearSolver<Eqn::M>,
+    Eqn: OdeEquationsSecondOrder,
+{
+    problem: &'a OdeSolverProblem<Eqn>,
+    params: GeneralizedAlphaParameters<Eqn::T>,
+    op: NewmarkCallable<&'a Eqn>,
+    linear_solver: LS,
+    convergence: Convergence<'a, Eqn::V>,
+    state: SdirkState<Eqn::V>,
+    acc: Eqn::V,
+    acc_new: Eqn::V,
+    delta: Eqn::V,
+    y_new: Eqn::V,
+    corr: Eqn::V,
+    error: Eqn::V,
+    old_t: Eqn::T,
+    old_y: Eqn::V,
+    old_f: Eqn::V,
+    old_g: Eqn::V,
+    old_dg: Eqn::V,
+    statistics: BdfStatistics,
+    root_finder: Option<RootFinder<Eqn::V>>,
+    tstop: Option<Eqn::T>,
+    is_state_mutated: bool,
+}
+
+impl<Eqn, LS> Clone for GeneralizedAlpha<'_, Eqn, LS>
+where
+    LS: LinearSolver<Eqn::M>,
+    Eqn: OdeEquationsSecondOrder,
+    for<'b> &'b Eqn::V: VectorRef<Eqn::V>,
+    for<'b> &'b Eqn::M: MatrixRef<Eqn::M>,
+{
+    fn clone(&self) -> Self {
+        let problem = self.problem;
+        let op = NewmarkCallable::new(&problem.eqn);
+        let mut linear_solver = LS::default();
+        linear_solver.set_problem(&op);
+        Self {
+            problem,
+            params: self.params,
+            op,
+            linear_solver,
+            convergence: self.convergence.clone(),
+            state: self.state.clone(),
+            acc: self.acc.clone(),
+            acc_new: self.acc_new.clone(),
+            delta: self.delta.clone(),
+            y_new: self.y_new.clone(),
+            corr: self.corr.clone(),
+            error: self.error.clone(),
+            old_t: self.old_t,
+            old_y: self.old_y.clone(),
+            old_f: self.old_f.clone(),
+            old_g: self.old_g.clone(),
+            old_dg: self.old_dg.clone(),
+            statistics: self.statistics.clone(),
+            root_finder: self.root_finder.clone(),
+            tstop: self.tstop,
+            is_state_mutated: self.is_state_mutated,
+        }
+    }
+}
+
+impl<'a, Eqn, LS> GeneralizedAlpha<'a, Eqn, LS>
+where
+    LS: LinearSolver<Eqn::M>,
+    Eqn: OdeEquationsSecondOrder,
+    for<'b> &'b Eqn::V: VectorRef<Eqn::V>,
+    for<'b> &'b Eqn::M: MatrixRef<Eqn::M>,
+{
+    const NEWTON_MAXITER: usize = 10;
+    const NEWTON_FAIL_FACTOR: f64 = 0.3;
+    const SAFETY: f64 = 0.9;
+    const MIN_FACTOR: f64 = 0.2;
+    const MAX_FACTOR: f64 = 10.0;
+    const MIN_TIMESTEP: f64 = 1e-13;
+
+    pub fn new(
+        problem: &'a OdeSolverProblem<Eqn>,
+        mut state: SdirkState<Eqn::V>,
+        params: GeneralizedAlphaParameters<Eqn::T>,
+        mut linear_solver: LS,
+    ) -> Result<Self, DiffsolError> {
+        state.check_consistent_with_problem(problem)?;
+        state.set_problem(problem)?;
+
+        let n = problem.eqn.npositions();
+        let op = NewmarkCallable::new(&problem.eqn);
+        linear_solver.set_problem(&op);
+
+        let mut convergence = Convergence::new(problem.rtol, &problem.atol);
+        convergence.set_max_iter(Self::NEWTON_MAXITER);
+
+        let root_finder = if let Some(root_fn) = problem.eqn.root() {
+            let root_finder = RootFinder::new(root_fn.nout());
+            root_finder.init(&root_fn, &state.y, state.t);
+            Some(root_finder)
+        } else {
+            None
+        };
+
+        let old_t = state.t;
+        let old_y = state.y.clone();
+        let old_f = state.dy.clone();
+        let old_g = state.g.clone();
+        let old_dg = state.dg.clone();
+
+        let mut ret = Self {
+            problem,
+            params,
+            op,
+            linear_solver,
+            convergence,
+            acc: <Eqn::V as Vector>::zeros(n),
+            acc_new: <Eqn::V as Vector>::zeros(n),
+            delta: <Eqn::V as Vector>::zeros(n),
+            y_new: <Eqn::V as Vector>::zeros(2 * n),
+            corr: <Eqn::V as Vector>::zeros(2 * n),
+            error: <Eqn::V as Vector>::zeros(2 * n),
+            state,
+            old_t,
+            old_y,
+            old_f,
+            old_g,
+            old_dg,
+            statistics: BdfStatistics::default(),
+            root_finder,
+            tstop: None,
+            is_state_mutated: false,
+        };
+        ret.initialise_acceleration()?;
+        ret.old_f.copy_from(&ret.state.dy);
+        Ok(ret)
+    }
+
+    pub fn get_statistics(&self) -> &BdfStatistics {
+        &self.statistics
+    }
+
+    /// calculate the acceleration `a = M^{-1} f(t, y, v)` for the current state, and set the time derivative of the state to `[v, a]`
+    fn initialise_acceleration(&mut self) -> Result<(), DiffsolError> {
+        let n = self.problem.eqn.npositions();
+        let state = &mut self.state;
+        if self.problem.eqn.mass().is_none() {
+            self.problem
+                .eqn
+                .force()
+                .call_inplace(&state.y, state.t, &mut self.acc);
+        } else {
+            // M a - f(y, v) = 0 is linear in a, so a single newton step from a = 0 gives the solution
+            self.op
+                .set_coefficients(Eqn::T::one(), Eqn::T::zero(), Eqn::T::zero());
+            self.op.set_base(&state.y, &<Eqn::V as Vector>::zeros(n));
+            self.acc.fill(Eqn::T::zero());
+            self.linear_solver
+                .set_linearisation(&self.op, &self.acc, state.t);
+            self.statistics.number_of_linear_solver_setups += 1;
+            self.op.call_inplace(&self.acc, state.t, &mut self.delta);
+            self.linear_solver.solve_in_place(&mut self.delta)?;
+            self.acc.axpy(-Eqn::T::one(), &self.delta, Eqn::T::one());
+        }
+        self.set_derivative();
+        Ok(())
+    }
+
+    /// set the time derivative of the state to `[v, a]`
+    fn set_derivative(&mut self) {
+        let n = self.problem.eqn.npositions();
+        let state = &mut self.state;
+        let dy = state.dy.as_mut_slice();
+        dy[..n].copy_from_slice(&state.y.as_slice()[n..]);
+        dy[n..].copy_from_slice(self.acc.as_slice());
+    }
+
+    /// y_new = [y + h v + h^2 (1/2 - beta) a, v + h (1 - gamma) a] + [cy a_new, cv a_new]
+    fn update_positions_and_velocities(&mut self, h: Eqn::T, cy: Eqn::T, cv: Eqn::T) {
+        let n = self.problem.eqn.npositions();
+        let half = Eqn::T::from(0.5);
+        let one = Eqn::T::one();
+        let beta = self.params.beta;
+        let gamma = self.params.gamma;
+        let y = self.state.y.as_slice();
+        let a = self.acc.as_slice();
+        let a_new = self.acc_new.as_slice();
+        let y_new = self.y_new.as_mut_slice();
+        for i in 0..n {
+            y_new[i] = y[i] + h * y[n + i] + h * h * (half - beta) * a[i] + cy * a_new[i];
+            y_new[n + i] = y[n + i] + h * (one - gamma) * a[i] + cv * a_new[i];
+        }
+    }
+
+    /// solve for the acceleration at the end of a step of size `h`, returns false if the newton iteration did not converge
+    fn solve_acceleration(&mut self, h: Eqn::T) -> Result<bool, DiffsolError> {
+        let n = self.problem.eqn.npositions();
+        let one = Eqn::T::one();
+        let alpha_m = self.params.alpha_m;
+        let alpha_f = self.params.alpha_f;
+        let beta = self.params.beta;
+        let gamma = self.params.gamma;
+        let t0 = self.state.t;
+
+        // the predictor for the end of the step is y_new with a_new = 0, and the equation of motion is evaluated at
+        // x0 + [cy a_new, cv a_new] where x0 = (1 - alpha_f) y_pred + alpha_f y
+        let cy = (one - alpha_f) * beta * h * h;
+        let cv = (one - alpha_f) * gamma * h;
+        self.acc_new.fill(Eqn::T::zero());
+        self.update_positions_and_velocities(h, Eqn::T::zero(), Eqn::T::zero());
+        self.y_new.axpy(alpha_f, &self.state.y, one - alpha_f);
+        let mut m0 = self.acc.clone();
+        m0 *= scale(alpha_m);
+        self.op.set_coefficients(one - alpha_m, cy, cv);
+        self.op.set_base(&self.y_new, &m0);
+        let tf = t0 + (one - alpha_f) * h;
+
+        // start the iteration from the current acceleration
+        self.acc_new.copy_from(&self.acc);
+        self.linear_solver
+            .set_linearisation(&self.op, &self.acc_new, tf);
+        self.statistics.number_of_linear_solver_setups += 1;
+
+        self.convergence.reset();
+        loop {
+            self.op.call_inplace(&self.acc_new, tf, &mut self.delta);
+            self.linear_solver.solve_in_place(&mut self.delta)?;
+            self.acc_new.axpy(-one, &self.delta, one);
+            self.statistics.number_of_nonlinear_solver_iterations += 1;
+
+            // check convergence using the correction to the positions and velocities at the end of the step
+            {
+                let corr = self.corr.as_mut_slice();
+                let delta = self.delta.as_slice();
+                for i in 0..n {
+                    corr[i] = beta * h * h * delta[i];
+                    corr[n + i] = gamma * h * delta[i];
+                }
+            }
+            match self
+                .convergence
+                .check_new_iteration(&mut self.corr, &self.state.y)
+            {
+                ConvergenceStatus::Continue => continue,
+                ConvergenceStatus::Converged => return Ok(true),
+                ConvergenceStatus::Diverged | ConvergenceStatus::MaximumIterations => {
+                    return Ok(false)
+                }
+            }
+        }
+    }
+
+    fn handle_tstop(
+        &mut self,
+        tstop: Eqn::T,
+    ) -> Result<Option<OdeSolverStopReason<Eqn::T>>, DiffsolError> {
+        match check_tstop(self.state.t, self.state.h, tstop)? {
+            TstopCheck::Reached => {
+                self.tstop = None;
+                return Ok(Some(OdeSolverStopReason::TstopReached));
+            }
+            TstopCheck::ScaleStep(factor) => {
+                self.state.h *= factor;
+            }
+            TstopCheck::NotReached => {}
+        }
+        Ok(None)
+    }
+
+    fn interpolate_hermite(
+        theta: Eqn::T,
+        dt: Eqn::T,
+        u0: &Eqn::V,
+        u1: &Eqn::V,
+        f0: &Eqn::V,
+        f1: &Eqn::V,
+    ) -> Eqn::V {
+        u0 * scale(Eqn::T::from(1.0) - theta)
+            + u1 * scale(theta)
+            + ((u1 - u0) * scale(Eqn::T::from(1.0) - Eqn::T::from(2.0) * theta)
+                + f0 * scale(dt * (theta - Eqn::T::from(1.0)))
+                + f1 * scale(dt * theta))
+                * scale(theta * (theta - Eqn::T::from(1.0)))
+    }
+
+    fn interpolation_theta(&self, t: Eqn::T) -> Result<Eqn::T, DiffsolError> {
+        let state = &self.state;
+
+        // check that t is within the last step
+        check_interpolation_time(t, self.old_t, state.t)?;
+        let dt = state.t - self.old_t;
+        if dt == Eqn::T::zero() {
+            Ok(Eqn::T::one())
+        } else {
+            Ok((t - self.old_t) / dt)
+        }
+    }
+
+    fn _update_step_size(&mut self, factor: Eqn::T) -> Result<Eqn::T, DiffsolError> {
+        let new_h = self.state.h * factor;
+
+        // if step size too small, then fail
+        if abs(new_h) < Eqn::T::from(Self::MIN_TIMESTEP) {
+            return Err(DiffsolError::from(OdeSolverError::StepSizeTooSmall {
+                time: self.state.t.into(),
+            }));
+        }
+
+        // update state
+        self.state.h = new_h;
+
+        Ok(new_h)
+    }
+}
+
+impl<'a, Eqn, LS> OdeSolverMethod<'a, Eqn> for GeneralizedAlpha<'a, Eqn, LS>
+where
+    LS: LinearSolver<Eqn::M>,
+    Eqn: OdeEquationsSecondOrder,
+    for<'b> &'b Eqn::V: VectorRef<Eqn::V>,
+    for<'b> &'b Eqn::M: MatrixRef<Eqn::M>,
+{
+    type State = SdirkState<Eqn::V>;
+
+    fn problem(&self) -> &'a OdeSolverProblem<Eqn> {
+        self.problem
+    }
+
+    fn order(&self) -> usize {
+        2
+    }
+
+    fn set_state(&mut self, state: Self::State) {
+        let n = self.problem.eqn.npositions();
+        self.state = state;
+
+        // the algorithmic acceleration is stored in the time derivative of the state
+        self.acc
+            .as_mut_slice()
+            .copy_from_slice(&self.state.dy.as_slice()[n..]);
+    }
+
+    fn into_state(self) -> SdirkState<Eqn::V> {
+        self.state
+    }
+
+    fn checkpoint(&mut self) -> Self::State {
+        self.state.clone()
+    }
+
+    fn step(&mut self) -> Result<OdeSolverStopReason<Eqn::T>, DiffsolError> {
+        let problem = self.problem;
+        let n = problem.eqn.npositions();
+        let one = Eqn::T::one();
+
+        if self.is_state_mutated {
+            // reinitialise root finder if needed
+            init_root_finder(
+                problem,
+                self.root_finder.as_ref(),
+                &self.state.y,
+                self.state.t,
+            );
+            // reinitialise tstop if needed
+            if let Some(t_stop) = self.tstop {
+                self.set_stop_time(t_stop)?;
+            }
+            self.initialise_acceleration()?;
+            if problem.integrate_out {
+                let state = &mut self.state;
+                problem
+                    .eqn
+                    .out()
+                    .unwrap()
+                    .call_inplace(&state.y, state.t, &mut state.dg);
+            }
+
+            self.is_state_mutated = false;
+        }
+
+        let beta = self.params.beta;
+        let gamma = self.params.gamma;
+        let mut factor: Eqn::T;
+
+        // loop until step is accepted
+        loop {
+            let h = self.state.h;
+
+            if !self.solve_acceleration(h)? {
+                // newton iteration did not converge, so reduce step size and try again
+                self.statistics.number_of_nonlinear_solver_fails += 1;
+                self._update_step_size(Eqn::T::from(Self::NEWTON_FAIL_FACTOR))?;
+                continue;
+            }
+            self.update_positions_and_velocities(h, beta * h * h, gamma * h);
+
+            // local error estimate of the positions, e = h^2 (beta - 1/6) (a_new - a)
+            {
+                let c = h * h * (beta - one / Eqn::T::from(6.0));
+                let error = self.error.as_mut_slice();
+                let a = self.acc.as_slice();
+                let a_new = self.acc_new.as_slice();
+                for i in 0..n {
+                    error[i] = c * (a_new[i] - a[i]);
+                    error[n + i] = Eqn::T::zero();
+                }
+            }
+
+            // adjust step size based on error, the local error is O(h^3)
+            let error_norm = self
+                .error
+                .squared_norm(&self.y_new, &problem.atol, problem.rtol);
+            factor = Eqn::T::from(Self::SAFETY) * error_norm.pow(Eqn::T::from(-1.0 / 6.0));
+            if factor < Eqn::T::from(Self::MIN_FACTOR) {
+                factor = Eqn::T::from(Self::MIN_FACTOR);
+            }
+            if factor > Eqn::T::from(Self::MAX_FACTOR) {
+                factor = Eqn::T::from(Self::MAX_FACTOR);
+            }
+
+            // test error is within tolerance
+            if error_norm <= one {
+                break;
+            }
+            // step is rejected, factor reduces step size, so we try again with the smaller step size
+            self.statistics.number_of_error_test_failures += 1;
+            self._update_step_size(factor)?;
+        }
+
+        // take the step
+        {
+            self.old_t = self.state.t;
+            self.state.t += self.state.h;
+
+            // y_new and acc_new are now the new y and acc
+            std::mem::swap(&mut self.state.y, &mut self.y_new);
+            std::mem::swap(&mut self.old_y, &mut self.y_new);
+            std::mem::swap(&mut self.acc, &mut self.acc_new);
+            std::mem::swap(&mut self.old_f, &mut self.state.dy);
+            self.set_derivative();
+        }
+
+        // integrate output function using simpson's rule on the dense output
+        if problem.integrate_out {
+            let out = problem.eqn.out().unwrap();
+            let state = &mut self.state;
+            let dt = state.t - self.old_t;
+            let y_mid = Self::interpolate_hermite(
+                Eqn::T::from(0.5),
+                dt,
+                &self.old_y,
+                &state.y,
+                &self.old_f,
+                &state.dy,
+            );
+            let dg_mid = out.call(&y_mid, self.old_t + Eqn::T::from(0.5) * dt);
+            out.call_inplace(&state.y, state.t, &mut self.old_dg);
+            std::mem::swap(&mut self.old_dg, &mut state.dg);
+            self.old_g.copy_from(&state.g);
+            let w = dt / Eqn::T::from(6.0);
+            state.g.axpy(w, &self.old_dg, one);
+            state.g.axpy(Eqn::T::from(4.0) * w, &dg_mid, one);
+            state.g.axpy(w, &state.dg, one);
+        }
+
+        // update step size for next step
+        self._update_step_size(factor)?;
+
+        // update statistics
+        self.statistics.number_of_steps += 1;
+
+        // check for root within accepted step
+        if let Some(root_fn) = problem.eqn.root() {
+            let ret = self.root_finder.as_ref().unwrap().check_root(
+                &|t| self.interpolate(t),
+                &root_fn,
+                &self.state.y,
+                self.state.t,
+            );
+            if let Some(root) = ret {
+                return Ok(OdeSolverStopReason::RootFound(root));
+            }
+        }
+
+        // check if we are at tstop
+        if let Some(tstop) = self.tstop {
+            if let Some(reason) = self.handle_tstop(tstop).unwrap() {
+                return Ok(reason);
+            }
+        }
+
+        // just a normal step, no roots or tstop reached
+        Ok(OdeSolverStopReason::InternalTimestep)
+    }
+
+    fn set_stop_time(&mut self, tstop: <Eqn as Op>::T) -> Result<(), DiffsolError> {
+        self.tstop = Some(tstop);
+        if let Some(OdeSolverStopReason::TstopReached) = self.handle_tstop(tstop)? {
+            let error = OdeSolverError::StopTimeBeforeCurrentTime {
+                stop_time: tstop.into(),
+                state_time: self.state.t.into(),
+            };
+            self.tstop = None;
+            return Err(DiffsolError::from(error));
+        }
+        Ok(())
+    }
+
+    fn interpolate_sens(&self, _t: <Eqn as Op>::T) -> Result<Vec<<Eqn as Op>::V>, DiffsolError> {
+        Ok(vec![])
+    }
+
+    fn interpolate(&self, t: <Eqn>::T) -> Result<<Eqn>::V, DiffsolError> {
+        if self.is_state_mutated {
+            if t == self.state.t {
+                return Ok(self.state.y.clone());
+            } else {
+                return Err(ode_solver_error!(InterpolationTimeOutsideCurrentStep));
+            }
+        }
+        let theta = self.interpolation_theta(t)?;
+        Ok(Self::interpolate_hermite(
+            theta,
+            self.state.t - self.old_t,
+            &self.old_y,
+            &self.state.y,
+            &self.old_f,
+            &self.state.dy,
+        ))
+    }
+
+    fn interpolate_out(&self, t: <Eqn>::T) -> Result<<Eqn>::V, DiffsolError> {
+        if self.is_state_mutated {
+            if t == self.state.t {
+                return Ok(self.state.g.clone());
+            } else {
+                return Err(ode_solver_error!(InterpolationTimeOutsideCurrentStep));
+            }
+        }
+        let theta = self.interpolation_theta(t)?;
+        Ok(Self::interpolate_hermite(
+            theta,
+            self.state.t - self.old_t,
+            &self.old_g,
+            &self.state.g,
+            &self.old_dg,
+            &self.state.dg,
+        ))
+    }
+
+    fn state(&self) -> StateRef<Eqn::V> {
+        self.state.as_ref()
+    }
+
+    fn state_mut(&mut self) -> StateRefMut<Eqn::V> {
+        self.is_state_mutated = true;
+        self.state.as_mut()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        ode_solver::{
+            test_models::spring_mass::{
+                spring_mass_problem, spring_mass_problem_with_mass, spring_mass_problem_with_root,
+            },
+            tests::test_ode_solver,
+        },
+        FaerSparseLU, GeneralizedAlphaParameters, NalgebraLU, OdeSolverMethod, SparseColMat,
+        Vector,
+    };
+
+    use num_traits::abs;
+
+    type M = nalgebra::DMatrix<f64>;
+    type LS = NalgebraLU<f64>;
+
+    #[test]
+    fn test_chung_hulbert_parameters() {
+        // rho_inf = 1 gives the Newmark average acceleration method
+        let params = GeneralizedAlphaParameters::<f64>::chung_hulbert(1.0);
+        let newmark = GeneralizedAlphaParameters::<f64>::newmark();
+        assert!(abs(params.alpha_m() - 0.5) < 1e-12);
+        assert!(abs(params.alpha_f() - 0.5) < 1e-12);
+        assert!(abs(params.beta() - newmark.beta()) < 1e-12);
+        assert!(abs(params.gamma() - newmark.gamma()) < 1e-12);
+    }
+
+    #[test]
+    fn test_newmark_nalgebra_spring_mass() {
+        let (problem, soln) = spring_mass_problem::<M>(false, 0.1);
+        let mut s = problem.newmark::<LS>().unwrap();
+        test_ode_solver(&mut s, soln, Some(1e-3), false, false);
+        let statistics = s.get_statistics();
+        assert!(statistics.number_of_nonlinear_solver_iterations >= statistics.number_of_steps);
+        assert!(
+            statistics.number_of_linear_solver_setups
+                <= statistics.number_of_steps
+                    + statistics.number_of_error_test_failures
+                    + statistics.number_of_nonlinear_solver_fails
+        );
+    }
+
+    #[test]
+    fn test_generalized_alpha_nalgebra_spring_mass() {
+        let (problem, soln) = spring_mass_problem::<M>(false, 0.1);
+        let mut s = problem.generalized_alpha::<LS>(0.5).unwrap();
+        test_ode_solver(&mut s, soln, Some(1e-3), false, false);
+    }
+
+    #[test]
+    fn test_generalized_alpha_nalgebra_spring_mass_with_mass() {
+        let (problem, soln) = spring_mass_problem_with_mass::<M>(false, 0.1);
+        let mut s = problem.generalized_alpha::<LS>(0.5).unwrap();
+        test_ode_solver(&mut s, soln, Some(1e-3), false, false);
+    }
+
+    #[test]
+    fn test_generalized_alpha_faer_sparse_spring_mass() {
+        let (problem, soln) = spring_mass_problem::<SparseColMat<f64>>(false, 0.1);
+        let mut s = problem.generalized_alpha::<FaerSparseLU<f64>>(0.5).unwrap();
+        test_ode_solver(&mut s, soln, Some(1e-3), false, false);
+    }
+
+    #[test]
+    fn test_tstop_newmark() {
+        let (problem, soln) = spring_mass_problem::<M>(false, 0.1);
+        let mut s = problem.newmark::<LS>().unwrap();
+        test_ode_solver(&mut s, soln, Some(1e-3), true, false);
+    }
+
+    #[test]
+    fn test_root_finder_newmark() {
+        let (problem, soln) = spring_mass_problem_with_root::<M>(false);
+        let mut s = problem.newmark::<LS>().unwrap();
+        let y = test_ode_solver(&mut s, soln, Some(1e-3), false, false);
+        assert!(abs(y[0] - 0.6) < 1e-6, "y[0] = {}", y[0]);
+    }
+
+    #[test]
+    fn newmark_test_checkpointing() {
+        let (problem, _soln) = spring_mass_problem::<M>(false, 0.1);
+        let mut s1 = problem.newmark::<LS>().unwrap();
+        let mut s2 = problem.newmark::<LS>().unwrap();
+        while s1.state().t < 5.0 {
+            s1.step().unwrap();
+        }
+        s2.set_state(s1.checkpoint());
+
+        // the algorithmic acceleration is stored in the state, so both solvers should take identical steps
+        for _ in 0..10 {
+            s1.step().unwrap();
+            s2.step().unwrap();
+            assert_eq!(s1.state().t, s2.state().t);
+            s1.state().y.assert_eq_st(s2.state().y, 1e-12);
+        }
+    }
+
+    #[test]
+    fn newmark_test_state_mut_spring_mass() {
+        let (problem, soln) = spring_mass_problem::<M>(false, 0.1);
+        let mut s = problem.newmark::<LS>().unwrap();
+        let state = s.checkpoint();
+        s.solve(1.0).unwrap();
+
+        // reinit using state_mut
+        s.state_mut().y.copy_from(&state.y);
+        *s.state_mut().t = state.t;
+        test_ode_solver(&mut s, soln, Some(1e-3), false, false);
+    }
+}
@@ -0,0 +1,984 @@
+use crate::error::DiffsolError;
+use crate::error::OdeSolverError;
+use crate::matrix::MatrixRef;
+use crate::ode_solver_error;
+use crate::vector::VectorRef;
+use crate::{
+    nonlinear_solver::NonLinearSolver, op::sdirk::SdirkCallable, scale, Convergence,
+    DefaultDenseMatrix, DenseMatrix, JacobianUpdate, LinearSolver, MatrixView,
+    NewtonNonlinearSolver, NonLinearOp, OdeEquations, OdeEquationsImex, OdeEquationsImexRef,
+    OdeEquationsRef, OdeSolverMethod, OdeSolverProblem, OdeSolverState, OdeSolverStopReason, Op,
+    RootFinder, SdirkState, StateRef, StateRefMut, Tableau, Vector, VectorViewMut,
+};
+use num_traits::abs;
+use num_traits::One;
+use num_traits::Pow;
+use num_traits::Zero;
+
+use super::bdf::BdfStatistics;
+use super::jacobian_update::SolverState;
+use super::method::{check_interpolation_time, check_tstop, init_root_finder, TstopCheck};
+
+/// The equations `dy/dt = F_I(t, y)`, i.e. an [OdeEquationsImex] with only the implicit part of the right-hand side.
+/// These are used to solve for the implicit stages of the [ImexArk] solver, so that only the Jacobian of `F_I` is ever
+/// evaluated and factorised.
+struct ImexImplicitEquations<Eqn: OdeEquationsImex> {
+    eqn: Eqn,
+}
+
+impl<Eqn: OdeEquationsImex + Clone> Clone for ImexImplicitEquations<Eqn> {
+    fn clone(&self) -> Self {
+        Self {
+            eqn: self.eqn.clone(),
+        }
+    }
+}
+
+impl<Eqn: OdeEquationsImex> Op for ImexImplicitEquations<Eqn> {
+    type T = Eqn::T;
+    type V = Eqn::V;
+    type M = Eqn::M;
+
+    fn nstates(&self) -> usize {
+        self.eqn.nstates()
+    }
+    fn nout(&self) -> usize {
+        self.eqn.nout()
+    }
+    fn nparams(&self) -> usize {
+        self.eqn.nparams()
+    }
+}
+
+impl<'a, Eqn: OdeEquationsImex> OdeEquationsRef<'a> for ImexImplicitEquations<Eqn> {
+    type Rhs = <Eqn as OdeEquationsImexRef<'a>>::RhsImplicit;
+    type Mass = <Eqn as OdeEquationsRef<'a>>::Mass;
+    type Root = <Eqn as OdeEquationsRef<'a>>::Root;
+    type Init = <Eqn as OdeEquationsRef<'a>>::Init;
+    type Out = <Eqn as OdeEquationsRef<'a>>::Out;
+}
+
+impl<Eqn: OdeEquationsImex> OdeEquations for ImexImplicitEquations<Eqn> {
+    fn rhs(&self) -> <Self as OdeEquationsRef<'_>>::Rhs {
+        self.eqn.rhs_implicit()
+    }
+
+    fn mass(&self) -> Option<<Self as OdeEquationsRef<'_>>::Mass> {
+        self.eqn.mass()
+    }
+
+    fn root(&self) -> Option<<Self as OdeEquationsRef<'_>>::Root> {
+        self.eqn.root()
+    }
+
+    fn out(&self) -> Option<<Self as OdeEquationsRef<'_>>::Out> {
+        self.eqn.out()
+    }
+
+    fn init(&self) -> <Self as OdeEquationsRef<'_>>::Init {
+        self.eqn.init()
+    }
+
+    // the parameters are shared by the explicit and implicit parts of the wrapped equations
+    fn set_params(&mut self, p: &Self::V) {
+        self.eqn.set_params(p)
+    }
+}
+
+/// An implicit-explicit (IMEX) additive Runge-Kutta method, for problems with a right-hand side `F_E(t, y) + F_I(t, y)` that is split
+/// into a non-stiff part `F_E`, which is integrated explicitly, and a stiff part `F_I`, which is integrated using an ESDIRK method (see [OdeEquationsImex]).
+/// A typical example is a reaction-diffusion problem, where the diffusion term is stiff and (often) linear, and the reaction term is non-stiff.
+///
+/// Each implicit stage is solved using a Newton iteration with the matrix `I - h gamma J_I`, where `J_I` is the Jacobian of `F_I`,
+/// so the Jacobian of the explicit part is never evaluated or factorised.
+///
+/// The particular method is defined by a pair of [Tableau]s (e.g. [Tableau::ark436l2sa_explicit] and [Tableau::ark436l2sa_implicit]).
+/// Hermite interpolation is used for dense output. Problems with a mass matrix and forward sensitivities are not supported by this solver.
+///
+/// Restrictions:
+/// - The two tableaus must have the same `b`, `c` and `d` vectors.
+/// - The diagonal and upper triangular part of the `a` matrix of the explicit tableau must be zero.
+/// - The upper triangular part of the `a` matrix of the implicit tableau must be zero, the first element of the diagonal must be zero,
+///   and the rest of the diagonal must be the same non-zero value (i.e. an ESDIRK method).
+/// - The first element of the `c` vector must be 0.
+pub struct ImexArk<'a, Eqn, LS, M = <<Eqn as Op>::V as DefaultDenseMatrix>::M>
+where
+    M: DenseMatrix<T = Eqn::T, V = Eqn::V>,
+    LS: LinearSolver<Eqn::M>,
+    Eqn: OdeEquationsImex,
+    Eqn::V: DefaultDenseMatrix<T = Eqn::T>,
+{
+    explicit_tableau: Tableau<M>,
+    implicit_tableau: Tableau<M>,
+    problem: &'a OdeSolverProblem<Eqn>,
+    nonlinear_solver: NewtonNonlinearSolver<Eqn::M, LS>,
+    convergence: Convergence<'a, Eqn::V>,
+    op: SdirkCallable<ImexImplicitEquations<&'a Eqn>>,
+    state: SdirkState<Eqn::V>,
+    explicit_diff: M,
+    implicit_diff: M,
+    gdiff: M,
+    explicit_a_rows: Vec<Eqn::V>,
+    implicit_a_rows: Vec<Eqn::V>,
+    f_explicit: Eqn::V,
+    f_implicit: Eqn::V,
+    y_explicit: Eqn::V,
+    dy_implicit: Eqn::V,
+    old_t: Eqn::T,
+    old_y: Eqn::V,
+    old_f: Eqn::V,
+    old_g: Eqn::V,
+    old_dg: Eqn::V,
+    statistics: BdfStatistics,
+    root_finder: Option<RootFinder<Eqn::V>>,
+    tstop: Option<Eqn::T>,
+    is_state_mutated: bool,
+    jacobian_update: JacobianUpdate<Eqn::T>,
+}
+
+impl<M, Eqn, LS> Clone for ImexArk<'_, Eqn, LS, M>
+where
+    M: DenseMatrix<T = Eqn::T, V = Eqn::V>,
+    LS: LinearSolver<Eqn::M>,
+    Eqn: OdeEquationsImex,
+    Eqn::V: DefaultDenseMatrix<T = Eqn::T>,
+    for<'b> &'b Eqn::V: VectorRef<Eqn::V>,
+    for<'b> &'b Eqn::M: MatrixRef<Eqn::M>,
+{
+    fn clone(&self) -> Self {
+        let op = self.op.clone_state(self.op.eqn().clone());
+        let mut nonlinear_solver = NewtonNonlinearSolver::new(LS::default());
+        nonlinear_solver.set_problem(&op);
+        nonlinear_solver.reset_jacobian(&op, &self.dy_implicit, self.state.t);
+        Self {
+            explicit_tableau: self.explicit_tableau.clone(),
+            implicit_tableau: self.implicit_tableau.clone(),
+            problem: self.problem,
+            nonlinear_solver,
+            convergence: self.convergence.clone(),
+            op,
+            state: self.state.clone(),
+            explicit_diff: self.explicit_diff.clone(),
+            implicit_diff: self.implicit_diff.clone(),
+            gdiff: self.gdiff.clone(),
+            explicit_a_rows: self.explicit_a_rows.clone(),
+            implicit_a_rows: self.implicit_a_rows.clone(),
+            f_explicit: self.f_explicit.clone(),
+            f_implicit: self.f_implicit.clone(),
+            y_explicit: self.y_explicit.clone(),
+            dy_implicit: self.dy_implicit.clone(),
+            old_t: self.old_t,
+            old_y: self.old_y.clone(),
+            old_f: self.old_f.clone(),
+            old_g: self.old_g.clone(),
+            old_dg: self.old_dg.clone(),
+            statistics: self.statistics.clone(),
+            root_finder: self.root_finder.clone(),
+            tstop: self.tstop,
+            is_state_mutated: self.is_state_mutated,
+            jacobian_update: self.jacobian_update.clone(),
+        }
+    }
+}
+
+impl<'a, M, Eqn, LS> ImexArk<'a, Eqn, LS, M>
+where
+    LS: LinearSolver<Eqn::M>,
+    M: DenseMatrix<T = Eqn::T, V = Eqn::V>,
+    Eqn: OdeEquationsImex,
+    Eqn::V: DefaultDenseMatrix<T = Eqn::T>,
+    for<'b> &'b Eqn::V: VectorRef<Eqn::V>,
+    for<'b> &'b Eqn::M: MatrixRef<Eqn::M>,
+{
+    const NEWTON_MAXITER: usize = 10;
+    const MIN_FACTOR: f64 = 0.2;
+    const MAX_FACTOR: f64 = 10.0;
+    const MIN_TIMESTEP: f64 = 1e-13;
+
+    pub fn new(
+        problem: &'a OdeSolverProblem<Eqn>,
+        mut state: SdirkState<Eqn::V>,
+        explicit_tableau: Tableau<M>,
+        implicit_tableau: Tableau<M>,
+        linear_solver: LS,
+    ) -> Result<Self, DiffsolError> {
+        if problem.eqn.mass().is_some() {
+            return Err(ode_solver_error!(
+                Other,
+                "IMEX Runge-Kutta solvers do not support a mass matrix"
+            ));
+        }
+
+        // check that the two tableaus describe a valid additive method
+        let s = implicit_tableau.s();
+        assert_eq!(
+            explicit_tableau.s(),
+            s,
+            "Invalid tableaus, expected the same number of stages"
+        );
+        for i in 0..s {
+            assert_eq!(
+                explicit_tableau.b()[i],
+                implicit_tableau.b()[i],
+                "Invalid tableaus, expected the same b vector"
+            );
+            assert_eq!(
+                explicit_tableau.c()[i],
+                implicit_tableau.c()[i],
+                "Invalid tableaus, expected the same c vector"
+            );
+            assert_eq!(
+                explicit_tableau.d()[i],
+                implicit_tableau.d()[i],
+                "Invalid tableaus, expected the same d vector"
+            );
+        }
+        assert_eq!(
+            implicit_tableau.c()[0],
+            Eqn::T::zero(),
+            "Invalid tableau, expected c(0) = 0"
+        );
+
+        // check that the explicit tableau is strictly lower triangular
+        for i in 0..s {
+            for j in i..s {
+                assert_eq!(
+                    explicit_tableau.a()[(i, j)],
+                    Eqn::T::zero(),
+                    "Invalid explicit tableau, expected a(i, j) = 0 for i >= j"
+                );
+            }
+        }
+
+        // check that the implicit tableau is an esdirk method
+        for i in 0..s {
+            for j in (i + 1)..s {
+                assert_eq!(
+                    implicit_tableau.a()[(i, j)],
+                    Eqn::T::zero(),
+                    "Invalid implicit tableau, expected a(i, j) = 0 for i > j"
+                );
+            }
+        }
+        assert_eq!(
+            implicit_tableau.a()[(0, 0)],
+            Eqn::T::zero(),
+            "Invalid implicit tableau, expected a(0, 0) = 0"
+        );
+        let gamma = implicit_tableau.a()[(1, 1)];
+        for i in 1..s {
+            assert_eq!(
+                implicit_tableau.a()[(i, i)],
+                gamma,
+                "Invalid implicit tableau, expected a(i, i) = gamma = {} for i = 1..s-1",
+                gamma
+            );
+        }
+
+        let a_rows = |tableau: &Tableau<M>| {
+            (0..s)
+                .map(|i| Eqn::V::from_vec((0..i).map(|j| tableau.a()[(i, j)]).collect()))
+                .collect::<Vec<_>>()
+        };
+        let explicit_a_rows = a_rows(&explicit_tableau);
+        let implicit_a_rows = a_rows(&implicit_tableau);
+
+        state.check_consistent_with_problem(problem)?;
+        state.set_problem(problem)?;
+
+        let nstates = state.y.len();
+
+        // setup linear solver for first step
+        let mut jacobian_update = JacobianUpdate::default();
+        jacobian_update.update_jacobian(state.h);
+        jacobian_update.update_rhs_jacobian();
+
+        let mut nonlinear_solver = NewtonNonlinearSolver::new(linear_solver);
+        let mut convergence = Convergence::new(problem.rtol, &problem.atol);
+        convergence.set_max_iter(Self::NEWTON_MAXITER);
+
+        let op = SdirkCallable::new(ImexImplicitEquations { eqn: &problem.eqn }, gamma);
+        op.set_h(state.h);
+        op.set_phi_direct(state.y.clone());
+        let dy_implicit = <Eqn::V as Vector>::zeros(nstates);
+        nonlinear_solver.set_problem(&op);
+        nonlinear_solver.reset_jacobian(&op, &dy_implicit, state.t);
+
+        // the two parts of the rhs at the initial state
+        let f_explicit = problem.eqn.rhs_explicit().call(&state.y, state.t);
+        let f_implicit = problem.eqn.rhs_implicit().call(&state.y, state.t);
+
+        let nout = if problem.integrate_out {
+            problem.eqn.out().unwrap().nout()
+        } else {
+            0
+        };
+
+        let root_finder = if let Some(root_fn) = problem.eqn.root() {
+            let root_finder = RootFinder::new(root_fn.nout());
+            root_finder.init(&root_fn, &state.y, state.t);
+            Some(root_finder)
+        } else {
+            None
+        };
+
+        Ok(Self {
+            problem,
+            nonlinear_solver,
+            convergence,
+            op,
+            explicit_diff: M::zeros(nstates, s),
+            implicit_diff: M::zeros(nstates, s),
+            gdiff: M::zeros(nout, s),
+            explicit_a_rows,
+            implicit_a_rows,
+            explicit_tableau,
+            implicit_tableau,
+            f_explicit,
+            f_implicit,
+            y_explicit: <Eqn::V as Vector>::zeros(nstates),
+            dy_implicit,
+            old_t: state.t,
+            old_y: state.y.clone(),
+            old_f: state.dy.clone(),
+            old_g: state.g.clone(),
+            old_dg: state.dg.clone(),
+            state,
+            statistics: BdfStatistics::default(),
+            root_finder,
+            tstop: None,
+            is_state_mutated: false,
+            jacobian_update,
+        })
+    }
+
+    pub fn get_statistics(&self) -> &BdfStatistics {
+        &self.statistics
+    }
+
+    fn handle_tstop(
+        &mut self,
+        tstop: Eqn::T,
+    ) -> Result<Option<OdeSolverStopReason<Eqn::T>>, DiffsolError> {
+        match check_tstop(self.state.t, self.state.h, tstop)? {
+            TstopCheck::Reached => {
+                self.tstop = None;
+                return Ok(Some(OdeSolverStopReason::TstopReached));
+            }
+            TstopCheck::ScaleStep(factor) => {
+                self.state.h *= factor;
+                self.op.set_h(self.state.h);
+            }
+            TstopCheck::NotReached => {}
+        }
+        Ok(None)
+    }
+
+    /// evaluate the explicit and implicit parts of the rhs at the current state
+    /// evaluate the two parts of the rhs at the current state, and set dy to their sum (y might have been modified)
+    fn evaluate_rhs_parts(&mut self) {
+        let state = &mut self.state;
+        self.problem
+            .eqn
+            .rhs_explicit()
+            .call_inplace(&state.y, state.t, &mut self.f_explicit);
+        self.problem
+            .eqn
+            .rhs_implicit()
+            .call_inplace(&state.y, state.t, &mut self.f_implicit);
+        state.dy.copy_from(&self.f_explicit);
+        state
+            .dy
+            .axpy(Eqn::T::one(), &self.f_implicit, Eqn::T::one());
+    }
+
+    fn predict_stage(i: usize, diff: &M, dy: &mut Eqn::V, tableau: &Tableau<M>) {
+        if i == 1 {
+            dy.copy_from_view(&diff.column(i - 1));
+        } else {
+            let c =
+                (tableau.c()[i] - tableau.c()[i - 2]) / (tableau.c()[i - 1] - tableau.c()[i - 2]);
+            // dy = c1  + c * (c1 - c2)
+            dy.copy_from_view(&diff.column(i - 1));
+            dy.axpy_v(-c, &diff.column(i - 2), Eqn::T::one() + c);
+        }
+    }
+
+    fn interpolate_hermite(
+        theta: Eqn::T,
+        dt: Eqn::T,
+        u0: &Eqn::V,
+        u1: &Eqn::V,
+        f0: &Eqn::V,
+        f1: &Eqn::V,
+    ) -> Eqn::V {
+        u0 * scale(Eqn::T::from(1.0) - theta)
+            + u1 * scale(theta)
+            + ((u1 - u0) * scale(Eqn::T::from(1.0) - Eqn::T::from(2.0) * theta)
+                + f0 * scale(dt * (theta - Eqn::T::from(1.0)))
+                + f1 * scale(dt * theta))
+                * scale(theta * (theta - Eqn::T::from(1.0)))
+    }
+
+    fn _jacobian_updates(&mut self, h: Eqn::T, state: SolverState) {
+        if self.jacobian_update.check_rhs_jacobian_update(h, &state) {
+            self.op.set_jacobian_is_stale();
+            self.nonlinear_solver
+                .reset_jacobian(&self.op, &self.dy_implicit, self.state.t);
+            self.jacobian_update.update_rhs_jacobian();
+            self.jacobian_update.update_jacobian(h);
+        } else if self.jacobian_update.check_jacobian_update(h, &state) {
+            self.nonlinear_solver
+                .reset_jacobian(&self.op, &self.dy_implicit, self.state.t);
+            self.jacobian_update.update_jacobian(h);
+        }
+    }
+
+    fn _update_step_size(&mut self, factor: Eqn::T) -> Result<Eqn::T, DiffsolError> {
+        let new_h = self.state.h * factor;
+
+        // if step size too small, then fail
+        if abs(new_h) < Eqn::T::from(Self::MIN_TIMESTEP) {
+            return Err(DiffsolError::from(OdeSolverError::StepSizeTooSmall {
+                time: self.state.t.into(),
+            }));
+        }
+
+        self.op.set_h(new_h);
+        self.state.h = new_h;
+
+        Ok(new_h)
+    }
+}
+
+impl<'a, M, Eqn, LS> OdeSolverMethod<'a, Eqn> for ImexArk<'a, Eqn, LS, M>
+where
+    LS: LinearSolver<Eqn::M>,
+    M: DenseMatrix<T = Eqn::T, V = Eqn::V>,
+    Eqn: OdeEquationsImex,
+    Eqn::V: DefaultDenseMatrix<T = Eqn::T>,
+    for<'b> &'b Eqn::V: VectorRef<Eqn::V>,
+    for<'b> &'b Eqn::M: MatrixRef<Eqn::M>,
+{
+    type State = SdirkState<Eqn::V>;
+
+    fn problem(&self) -> &'a OdeSolverProblem<Eqn> {
+        self.problem
+    }
+
+    fn order(&self) -> usize {
+        self.implicit_tableau.order()
+    }
+
+    fn set_state(&mut self, state: Self::State) {
+        self.state = state;
+
+        // the rhs parts are evaluated on the next step, and the solution can only be interpolated once it is taken
+        self.is_state_mutated = true;
+
+        // reinitialise jacobian updates as if a checkpoint was taken
+        self.op.set_h(self.state.h);
+        self._jacobian_updates(self.state.h, SolverState::Checkpoint);
+    }
+
+    fn into_state(self) -> SdirkState<Eqn::V> {
+        self.state
+    }
+
+    fn checkpoint(&mut self) -> Self::State {
+        self._jacobian_updates(self.state.h, SolverState::Checkpoint);
+        self.state.clone()
+    }
+
+    fn step(&mut self) -> Result<OdeSolverStopReason<Eqn::T>, DiffsolError> {
+        let n = self.state.y.len();
+
+        if self.is_state_mutated {
+            // reinitialise root finder if needed
+            init_root_finder(
+                self.problem,
+                self.root_finder.as_ref(),
+                &self.state.y,
+                self.state.t,
+            );
+            // reinitialise tstop if needed
+            if let Some(t_stop) = self.tstop {
+                self.set_stop_time(t_stop)?;
+            }
+            self.op.set_h(self.state.h);
+            self.evaluate_rhs_parts();
+
+            self.is_state_mutated = false;
+        }
+
+        let mut updated_jacobian = false;
+        let mut error = <Eqn::V as Vector>::zeros(n);
+        let out_error_control = self.problem().output_in_error_control();
+        let mut out_error = if out_error_control {
+            <Eqn::V as Vector>::zeros(self.problem().eqn.out().unwrap().nout())
+        } else {
+            <Eqn::V as Vector>::zeros(0)
+        };
+
+        let mut factor: Eqn::T;
+
+        // loop until step is accepted
+        'step: loop {
+            let t0 = self.state.t;
+            let h = self.state.h;
+
+            // the first stage is explicit in both parts and is just the rhs at the start of the step
+            {
+                let mut hf = self.explicit_diff.column_mut(0);
+                hf.copy_from(&self.f_explicit);
+                hf *= scale(h);
+            }
+            {
+                let mut hf = self.implicit_diff.column_mut(0);
+                hf.copy_from(&self.f_implicit);
+                hf *= scale(h);
+            }
+            if self.problem.integrate_out {
+                let mut hf = self.gdiff.column_mut(0);
+                hf.copy_from(&self.state.dg);
+                hf *= scale(h);
+            }
+
+            for i in 1..self.implicit_tableau.s() {
+                let t = t0 + self.implicit_tableau.c()[i] * h;
+
+                // the explicit contribution to the stage, y0 + sum_{j < i} ae_ij h f_E(y_j)
+                self.y_explicit.copy_from(&self.state.y);
+                self.explicit_diff.columns(0, i).gemv_o(
+                    Eqn::T::one(),
+                    &self.explicit_a_rows[i],
+                    Eqn::T::one(),
+                    &mut self.y_explicit,
+                );
+
+                // solve for the implicit part of the stage, y_i = phi + gamma h f_I(y_i)
+                self.op.set_phi(
+                    &self.implicit_diff.columns(0, i),
+                    &self.y_explicit,
+                    &self.implicit_a_rows[i],
+                );
+                Self::predict_stage(
+                    i,
+                    &self.implicit_diff,
+                    &mut self.dy_implicit,
+                    &self.implicit_tableau,
+                );
+                let solve_result = self.nonlinear_solver.solve_in_place_with_jacobian(
+                    &self.op,
+                    &mut self.dy_implicit,
+                    t,
+                    &self.state.y,
+                    &mut self.convergence,
+                );
+                self.statistics.number_of_nonlinear_solver_iterations += self.convergence.niter();
+
+                // handle solve failure
+                if solve_result.is_err() {
+                    self.statistics.number_of_nonlinear_solver_fails += 1;
+                    if !updated_jacobian {
+                        // newton iteration did not converge, so update jacobian and try again
+                        updated_jacobian = true;
+                        self._jacobian_updates(h, SolverState::FirstConvergenceFail);
+                    } else {
+                        // newton iteration did not converge and jacobian has been updated, so we reduce step size and try again
+                        let new_h = self._update_step_size(Eqn::T::from(0.3))?;
+                        self._jacobian_updates(new_h, SolverState::SecondConvergenceFail);
+                    }
+                    // try again....
+                    continue 'step;
+                }
+
+                // old_y now has the stage value
+                self.old_y.copy_from(&self.op.get_last_f_eval());
+                self.implicit_diff
+                    .column_mut(i)
+                    .copy_from(&self.dy_implicit);
+
+                // explicit part of the rhs at the stage value, using old_f as a temporary
+                self.problem
+                    .eqn
+                    .rhs_explicit()
+                    .call_inplace(&self.old_y, t, &mut self.old_f);
+                self.explicit_diff
+                    .column_mut(i)
+                    .axpy(h, &self.old_f, Eqn::T::zero());
+
+                // calculate dg and store in gdiff
+                if self.problem.integrate_out {
+                    let out = self.problem.eqn.out().unwrap();
+                    out.call_inplace(&self.old_y, t, &mut self.old_dg);
+                    self.gdiff
+                        .column_mut(i)
+                        .axpy(h, &self.old_dg, Eqn::T::zero());
+                }
+            }
+
+            // form the solution, y1 = y0 + sum_i b_i h (f_E(y_i) + f_I(y_i))
+            self.old_y.copy_from(&self.state.y);
+            self.explicit_diff.gemv(
+                Eqn::T::one(),
+                self.implicit_tableau.b(),
+                Eqn::T::one(),
+                &mut self.old_y,
+            );
+            self.implicit_diff.gemv(
+                Eqn::T::one(),
+                self.implicit_tableau.b(),
+                Eqn::T::one(),
+                &mut self.old_y,
+            );
+
+            // compute the error
+            let mut ncontributions = 0;
+            let mut error_norm = Eqn::T::zero();
+            self.explicit_diff.gemv(
+                Eqn::T::one(),
+                self.implicit_tableau.d(),
+                Eqn::T::zero(),
+                &mut error,
+            );
+            self.implicit_diff.gemv(
+                Eqn::T::one(),
+                self.implicit_tableau.d(),
+                Eqn::T::one(),
+                &mut error,
+            );
+            let atol = &self.problem().atol;
+            let rtol = self.problem().rtol;
+            error_norm += error.squared_norm(&self.old_y, atol, rtol);
+            ncontributions += 1;
+
+            // output errors
+            if out_error_control {
+                self.gdiff.gemv(
+                    Eqn::T::one(),
+                    self.implicit_tableau.d(),
+                    Eqn::T::zero(),
+                    &mut out_error,
+                );
+                let atol = self.problem().out_atol.as_ref().unwrap();
+                let rtol = self.problem().out_rtol.unwrap();
+                error_norm += out_error.squared_norm(&self.state.g, atol, rtol);
+                ncontributions += 1;
+            }
+            if ncontributions > 1 {
+                error_norm /= Eqn::T::from(ncontributions as f64);
+            }
+
+            // adjust step size based on error, the embedded method is one order lower than the main method
+            let maxiter = self.convergence.max_iter() as f64;
+            let niter = self.convergence.niter() as f64;
+            let safety = Eqn::T::from(0.9 * (2.0 * maxiter + 1.0) / (2.0 * maxiter + niter));
+            let order = self.implicit_tableau.order() as f64;
+            factor = safety * error_norm.pow(Eqn::T::from(-0.5 / order));
+            if factor < Eqn::T::from(Self::MIN_FACTOR) {
+                factor = Eqn::T::from(Self::MIN_FACTOR);
+            }
+            if factor > Eqn::T::from(Self::MAX_FACTOR) {
+                factor = Eqn::T::from(Self::MAX_FACTOR);
+            }
+
+            // test error is within tolerance
+            if error_norm <= Eqn::T::from(1.0) {
+                break 'step;
+            }
+            // step is rejected, factor reduces step size, so we try again with the smaller step size
+            self.statistics.number_of_error_test_failures += 1;
+            let new_h = self._update_step_size(factor)?;
+            self._jacobian_updates(new_h, SolverState::ErrorTestFail);
+        }
+
+        // take the step
+        {
+            let t1 = self.state.t + self.state.h;
+
+            // evaluate the two parts of the rhs (and the output) at the new solution,
+            // these give the first stage of the next step
+            self.problem
+                .eqn
+                .rhs_explicit()
+                .call_inplace(&self.old_y, t1, &mut self.f_explicit);
+            self.problem
+                .eqn
+                .rhs_implicit()
+                .call_inplace(&self.old_y, t1, &mut self.f_implicit);
+            self.old_f.copy_from(&self.f_explicit);
+            self.old_f
+                .axpy(Eqn::T::one(), &self.f_implicit, Eqn::T::one());
+
+            let state = &mut self.state;
+            self.old_t = state.t;
+            state.t = t1;
+
+            // old_y and old_f are the new y and dy
+            std::mem::swap(&mut self.old_f, &mut state.dy);
+            std::mem::swap(&mut self.old_y, &mut state.y);
+
+            // integrate output function
+            if self.problem.integrate_out {
+                let out = self.problem.eqn.out().unwrap();
+                out.call_inplace(&state.y, t1, &mut self.old_dg);
+                std::mem::swap(&mut self.old_dg, &mut state.dg);
+                self.old_g.copy_from(&state.g);
+                self.gdiff.gemv(
+                    Eqn::T::one(),
+                    self.implicit_tableau.b(),
+                    Eqn::T::one(),
+                    &mut state.g,
+                );
+            }
+        }
+
+        // update step size for next step
+        let new_h = self._update_step_size(factor)?;
+        self._jacobian_updates(new_h, SolverState::StepSuccess);
+
+        // update statistics
+        self.statistics.number_of_linear_solver_setups = self.op.number_of_jac_evals();
+        self.statistics.number_of_steps += 1;
+        self.jacobian_update.step();
+
+        // check for root within accepted step
+        if let Some(root_fn) = self.problem.eqn.root() {
+            let ret = self.root_finder.as_ref().unwrap().check_root(
+                &|t| self.interpolate(t),
+                &root_fn,
+                &self.state.y,
+                self.state.t,
+            );
+            if let Some(root) = ret {
+                return Ok(OdeSolverStopReason::RootFound(root));
+            }
+        }
+
+        // check if we are at tstop
+        if let Some(tstop) = self.tstop {
+            if let Some(reason) = self.handle_tstop(tstop).unwrap() {
+                return Ok(reason);
+            }
+        }
+
+        // just a normal step, no roots or tstop reached
+        Ok(OdeSolverStopReason::InternalTimestep)
+    }
+
+    fn set_stop_time(&mut self, tstop: <Eqn as Op>::T) -> Result<(), DiffsolError> {
+        self.tstop = Some(tstop);
+        if let Some(OdeSolverStopReason::TstopReached) = self.handle_tstop(tstop)? {
+            let error = OdeSolverError::StopTimeBeforeCurrentTime {
+                stop_time: tstop.into(),
+                state_time: self.state.t.into(),
+            };
+            self.tstop = None;
+            return Err(DiffsolError::from(error));
+        }
+        Ok(())
+    }
+
+    fn interpolate_sens(&self, _t: <Eqn as Op>::T) -> Result<Vec<<Eqn as Op>::V>, DiffsolError> {
+        Ok(vec![])
+    }
+
+    fn interpolate(&self, t: <Eqn>::T) -> Result<<Eqn>::V, DiffsolError> {
+        let state = &self.state;
+
+        if self.is_state_mutated {
+            if t == state.t {
+                return Ok(state.y.clone());
+            } else {
+                return Err(ode_solver_error!(InterpolationTimeOutsideCurrentStep));
+            }
+        }
+
+        // check that t is within the last step
+        check_interpolation_time(t, self.old_t, state.t)?;
+
+        let dt = state.t - self.old_t;
+        let theta = if dt == Eqn::T::zero() {
+            Eqn::T::one()
+        } else {
+            (t - self.old_t) / dt
+        };
+
+        Ok(Self::interpolate_hermite(
+            theta,
+            dt,
+            &self.old_y,
+            &state.y,
+            &self.old_f,
+            &state.dy,
+        ))
+    }
+
+    fn interpolate_out(&self, t: <Eqn>::T) -> Result<<Eqn>::V, DiffsolError> {
+        let state = &self.state;
+
+        if self.is_state_mutated {
+            if t == state.t {
+                return Ok(state.g.clone());
+            } else {
+                return Err(ode_solver_error!(InterpolationTimeOutsideCurrentStep));
+            }
+        }
+
+        // check that t is within the last step
+        check_interpolation_time(t, self.old_t, state.t)?;
+
+        let dt = state.t - self.old_t;
+        let theta = if dt == Eqn::T::zero() {
+            Eqn::T::one()
+        } else {
+            (t - self.old_t) / dt
+        };
+
+        Ok(Self::interpolate_hermite(
+            theta,
+            dt,
+            &self.old_g,
+            &state.g,
+            &self.old_dg,
+            &state.dg,
+        ))
+    }
+
+    fn state(&self) -> StateRef<Eqn::V> {
+        self.state.as_ref()
+    }
+
+    fn state_mut(&mut self) -> StateRefMut<Eqn::V> {
+        self.is_state_mutated = true;
+        self.state.as_mut()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        ode_solver::{
+            test_models::exponential_decay::{
+                exponential_decay_problem_imex, exponential_decay_problem_imex_with_root,
+            },
+            tests::test_ode_solver,
+        },
+        NalgebraLU, NonLinearOp, OdeEquations, OdeEquationsImex, OdeSolverMethod, OdeSolverState,
+        Op, Tableau, Vector,
+    };
+
+    use super::ImexImplicitEquations;
+
+    use num_traits::abs;
+
+    type M = nalgebra::DMatrix<f64>;
+    type LS = NalgebraLU<f64>;
+
+    #[test]
+    fn ark436l2sa_tableaus_are_consistent() {
+        let explicit = Tableau::<M>::ark436l2sa_explicit();
+        let implicit = Tableau::<M>::ark436l2sa_implicit();
+        for tableau in [&explicit, &implicit] {
+            let b_sum = (0..tableau.s()).map(|i| tableau.b()[i]).sum::<f64>();
+            assert!(abs(b_sum - 1.0) < 1e-12, "sum(b) = {}", b_sum);
+            for i in 0..tableau.s() {
+                let row_sum = (0..tableau.s()).map(|j| tableau.a()[(i, j)]).sum::<f64>();
+                assert!(
+                    abs(row_sum - tableau.c()[i]) < 1e-12,
+                    "row {} sums to {}, c = {}",
+                    i,
+                    row_sum,
+                    tableau.c()[i]
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_imex_implicit_equations_set_params() {
+        let (problem, _soln) = exponential_decay_problem_imex::<M>(false);
+        let mut eqn = ImexImplicitEquations { eqn: problem.eqn };
+        let y = nalgebra::DVector::from_element(2, 1.0);
+        let f = eqn.rhs().call(&y, 0.0);
+        assert!(abs(f[0] + 0.05) < 1e-12, "f = {}", f[0]);
+
+        // both parts of the rhs use the new parameters
+        eqn.set_params(&nalgebra::DVector::from_vec(vec![0.4, 1.0]));
+        let f = eqn.rhs().call(&y, 0.0);
+        assert!(abs(f[0] + 0.2) < 1e-12, "f = {}", f[0]);
+        let f = eqn.eqn.rhs_explicit().call(&y, 0.0);
+        assert!(abs(f[0] + 0.2) < 1e-12, "f = {}", f[0]);
+    }
+
+    #[test]
+    fn test_ark436l2sa_nalgebra_exponential_decay() {
+        let (problem, soln) = exponential_decay_problem_imex::<M>(false);
+        let mut s = problem.ark436l2sa::<LS>().unwrap();
+        test_ode_solver(&mut s, soln, None, false, false);
+        assert!(s.get_statistics().number_of_steps > 0);
+        assert_eq!(s.get_statistics().number_of_nonlinear_solver_fails, 0);
+
+        // only the implicit part of the rhs is ever differentiated
+        assert_eq!(
+            problem.eqn.rhs_explicit().statistics().number_of_jac_muls,
+            0
+        );
+        assert!(problem.eqn.rhs_implicit().statistics().number_of_jac_muls > 0);
+        assert!(problem.eqn.rhs_explicit().statistics().number_of_calls > 0);
+    }
+
+    #[test]
+    fn test_tstop_ark436l2sa() {
+        let (problem, soln) = exponential_decay_problem_imex::<M>(false);
+        let mut s = problem.ark436l2sa::<LS>().unwrap();
+        test_ode_solver(&mut s, soln, None, true, false);
+    }
+
+    #[test]
+    fn test_root_finder_ark436l2sa() {
+        let (problem, soln) = exponential_decay_problem_imex_with_root::<M>(false);
+        let mut s = problem.ark436l2sa::<LS>().unwrap();
+        let y = test_ode_solver(&mut s, soln, None, false, false);
+        assert!(abs(y[0] - 0.6) < 1e-6, "y[0] = {}", y[0]);
+    }
+
+    #[test]
+    fn test_ark436l2sa_checkpointing() {
+        let (problem, soln) = exponential_decay_problem_imex::<M>(false);
+        let mut s1 = problem.ark436l2sa::<LS>().unwrap();
+        let mut s2 = problem.ark436l2sa::<LS>().unwrap();
+        let half_t = soln.solution_points[soln.solution_points.len() / 2].t;
+        while s1.state().t <= half_t {
+            s1.step().unwrap();
+        }
+        s2.set_state(s1.checkpoint());
+        // s2 can only interpolate within the steps taken after the checkpoint
+        let checkpoint_t = s1.state().t;
+        for point in soln.solution_points.iter().filter(|p| p.t > checkpoint_t) {
+            while s2.state().t < point.t {
+                s1.step().unwrap();
+                s2.step().unwrap();
+                assert_eq!(s1.state().t, s2.state().t);
+            }
+            let y1 = s1.interpolate(point.t).unwrap();
+            let y2 = s2.interpolate(point.t).unwrap();
+            y1.assert_eq_st(&y2, 1e-12);
+            y2.assert_eq_norm(&point.state, &problem.atol, problem.rtol, 15.0);
+        }
+    }
+
+    #[test]
+    fn test_ark436l2sa_state_mut() {
+        let (problem, soln) = exponential_decay_problem_imex::<M>(false);
+        let mut s = problem.ark436l2sa::<LS>().unwrap();
+        let state = s.checkpoint();
+        s.solve(1.0).unwrap();
+
+        // reinit using state_mut
+        s.state_mut().y.copy_from(state.as_ref().y);
+        *s.state_mut().t = state.as_ref().t;
+        test_ode_solver(&mut s, soln, None, false, false);
+    }
+}
@@ -0,0 +1,320 @@
+use num_traits::{One, Zero};
+use std::cell::RefCell;
+
+use crate::{
+    op::nonlinear_op::NonLinearOpJacobian, AugmentedOdeEquations, ConstantOp, NonLinearOp,
+    OdeEquations, OdeEquationsImplicit, OdeEquationsRef, OdeSolverProblem, Op, Vector,
+};
+
+/// Initial condition of the sensitivities wrt the initial state, the ith column of the identity matrix.
+/// The column to evaluate is set using [Self::set_state_index].
+pub struct InitialSensInit<'a, Eqn>
+where
+    Eqn: OdeEquationsImplicit,
+{
+    eqn: &'a Eqn,
+    index: usize,
+}
+
+impl<'a, Eqn> InitialSensInit<'a, Eqn>
+where
+    Eqn: OdeEquationsImplicit,
+{
+    pub fn new(eqn: &'a Eqn) -> Self {
+        Self { eqn, index: 0 }
+    }
+    pub fn set_state_index(&mut self, index: usize) {
+        self.index = index;
+    }
+}
+
+impl<Eqn> Op for InitialSensInit<'_, Eqn>
+where
+    Eqn: OdeEquationsImplicit,
+{
+    type T = Eqn::T;
+    type V = Eqn::V;
+    type M = Eqn::M;
+
+    fn nstates(&self) -> usize {
+        self.eqn.rhs().nstates()
+    }
+    fn nout(&self) -> usize {
+        self.eqn.rhs().nstates()
+    }
+    fn nparams(&self) -> usize {
+        0
+    }
+}
+
+impl<Eqn> ConstantOp for InitialSensInit<'_, Eqn>
+where
+    Eqn: OdeEquationsImplicit,
+{
+    fn call_inplace(&self, _t: Self::T, y: &mut Self::V) {
+        y.fill(Eqn::T::zero());
+        y[self.index] = Eqn::T::one();
+    }
+}
+
+/// Right-hand side of the sensitivity equations wrt the initial state is
+///
+/// F(s, t) = J * s
+///
+/// where J is the Jacobian of the right-hand side evaluated at the current state, which is set using [Self::update_state].
+/// Unlike [crate::SensRhs] there is no forcing term, since the right-hand side does not depend on the initial state.
+pub struct InitialSensRhs<'a, Eqn>
+where
+    Eqn: OdeEquations,
+{
+    eqn: &'a Eqn,
+    y: RefCell<Eqn::V>,
+}
+
+impl<'a, Eqn> InitialSensRhs<'a, Eqn>
+where
+    Eqn: OdeEquationsImplicit,
+{
+    pub fn new(eqn: &'a Eqn, allocate: bool) -> Self {
+        let nstates = if allocate { eqn.rhs().nstates() } else { 0 };
+        Self {
+            eqn,
+            y: RefCell::new(<Eqn::V as Vector>::zeros(nstates)),
+        }
+    }
+
+    pub fn update_state(&mut self, y: &Eqn::V, _dy: &Eqn::V, _t: Eqn::T) {
+        self.y.borrow_mut().copy_from(y);
+    }
+}
+
+impl<Eqn> Op for InitialSensRhs<'_, Eqn>
+where
+    Eqn: OdeEquationsImplicit,
+{
+    type T = Eqn::T;
+    type V = Eqn::V;
+    type M = Eqn::M;
+
+    fn nstates(&self) -> usize {
+        self.eqn.rhs().nstates()
+    }
+    fn nout(&self) -> usize {
+        self.eqn.rhs().nstates()
+    }
+    fn nparams(&self) -> usize {
+        0
+    }
+}
+
+impl<Eqn> NonLinearOp for InitialSensRhs<'_, Eqn>
+where
+    Eqn: OdeEquationsImplicit,
+{
+    fn call_inplace(&self, x: &Self::V, t: Self::T, y: &mut Self::V) {
+        let state_y = self.y.borrow();
+        self.eqn.rhs().jac_mul_inplace(&state_y, t, x, y);
+    }
+}
+
+impl<Eqn> NonLinearOpJacobian for InitialSensRhs<'_, Eqn>
+where
+    Eqn: OdeEquationsImplicit,
+{
+    fn jac_mul_inplace(&self, _x: &Self::V, t: Self::T, v: &Self::V, y: &mut Self::V) {
+        let state_y = self.y.borrow();
+        self.eqn.rhs().jac_mul_inplace(&state_y, t, v, y);
+    }
+    fn jacobian_inplace(&self, _x: &Self::V, t: Self::T, y: &mut Self::M) {
+        let state_y = self.y.borrow();
+        self.eqn.rhs().jacobian_inplace(&state_y, t, y);
+    }
+}
+
+/// Sensitivity equations wrt the initial state for ODEs without a mass matrix:
+///
+/// ds/dt = J * s
+/// s(t_0) = e_i
+///
+/// where
+///  J is the Jacobian of the right-hand side
+///  s is the sensitivity of the state wrt the ith component of the initial state
+///  e_i is the ith column of the identity matrix
+///
+/// Integrating all `nstates` of these equations gives the full state transition matrix `dy(t)/dy(t_0)`,
+/// which is used by the multiple shooting method of [crate::BvpProblem]. The sensitivities are included in the
+/// error control of the solver using the tolerances of the problem.
+pub struct InitialSensEquations<'a, Eqn>
+where
+    Eqn: OdeEquationsImplicit,
+{
+    eqn: &'a Eqn,
+    rhs: InitialSensRhs<'a, Eqn>,
+    init: InitialSensInit<'a, Eqn>,
+    atol: &'a Eqn::V,
+    rtol: Eqn::T,
+}
+
+impl<Eqn> Clone for InitialSensEquations<'_, Eqn>
+where
+    Eqn: OdeEquationsImplicit,
+{
+    fn clone(&self) -> Self {
+        Self {
+            eqn: self.eqn,
+            rhs: InitialSensRhs::new(self.eqn, false),
+            init: InitialSensInit::new(self.eqn),
+            rtol: self.rtol,
+            atol: self.atol,
+        }
+    }
+}
+
+impl<Eqn> std::fmt::Debug for InitialSensEquations<'_, Eqn>
+where
+    Eqn: OdeEquationsImplicit,
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "InitialSensEquations")
+    }
+}
+
+impl<'a, Eqn> InitialSensEquations<'a, Eqn>
+where
+    Eqn: OdeEquationsImplicit,
+{
+    pub(crate) fn new(problem: &'a OdeSolverProblem<Eqn>) -> Self {
+        let eqn = &problem.eqn;
+        let rhs = InitialSensRhs::new(eqn, true);
+        let init = InitialSensInit::new(eqn);
+        Self {
+            eqn,
+            rhs,
+            init,
+            rtol: problem.rtol,
+            atol: &problem.atol,
+        }
+    }
+}
+
+impl<Eqn> Op for InitialSensEquations<'_, Eqn>
+where
+    Eqn: OdeEquationsImplicit,
+{
+    type T = Eqn::T;
+    type V = Eqn::V;
+    type M = Eqn::M;
+
+    fn nstates(&self) -> usize {
+        self.eqn.rhs().nstates()
+    }
+    fn nout(&self) -> usize {
+        self.eqn.rhs().nout()
+    }
+    fn nparams(&self) -> usize {
+        0
+    }
+}
+
+impl<'a, 'b, Eqn> OdeEquationsRef<'a> for InitialSensEquations<'b, Eqn>
+where
+    Eqn: OdeEquationsImplicit,
+{
+    type Rhs = &'a InitialSensRhs<'b, Eqn>;
+    type Mass = <Eqn as OdeEquationsRef<'a>>::Mass;
+    type Root = <Eqn as OdeEquationsRef<'a>>::Root;
+    type Init = &'a InitialSensInit<'b, Eqn>;
+    type Out = <Eqn as OdeEquationsRef<'a>>::Out;
+}
+
+impl<'a, Eqn> OdeEquations for InitialSensEquations<'a, Eqn>
+where
+    Eqn: OdeEquationsImplicit,
+{
+    fn rhs(&self) -> &InitialSensRhs<'a, Eqn> {
+        &self.rhs
+    }
+    fn mass(&self) -> Option<<Eqn as OdeEquationsRef<'_>>::Mass> {
+        self.eqn.mass()
+    }
+    fn root(&self) -> Option<<Eqn as OdeEquationsRef<'_>>::Root> {
+        None
+    }
+    fn init(&self) -> &InitialSensInit<'a, Eqn> {
+        &self.init
+    }
+    fn out(&self) -> Option<<Eqn as OdeEquationsRef<'_>>::Out> {
+        None
+    }
+    fn set_params(&mut self, _p: &Self::V) {}
+}
+
+impl<Eqn: OdeEquationsImplicit> AugmentedOdeEquations<Eqn> for InitialSensEquations<'_, Eqn> {
+    fn include_in_error_control(&self) -> bool {
+        true
+    }
+    fn include_out_in_error_control(&self) -> bool {
+        false
+    }
+    fn rtol(&self) -> Option<Eqn::T> {
+        Some(self.rtol)
+    }
+    fn atol(&self) -> Option<&Eqn::V> {
+        Some(self.atol)
+    }
+    fn out_atol(&self) -> Option<&Eqn::V> {
+        None
+    }
+    fn out_rtol(&self) -> Option<Eqn::T> {
+        None
+    }
+
+    fn max_index(&self) -> usize {
+        self.nstates()
+    }
+    fn update_rhs_out_state(&mut self, y: &Eqn::V, dy: &Eqn::V, t: Eqn::T) {
+        self.rhs.update_state(y, dy, t);
+    }
+    fn update_init_state(&mut self, _t: Eqn::T) {}
+    fn set_index(&mut self, index: usize) {
+        self.init.set_state_index(index);
+    }
+    fn integrate_main_eqn(&self) -> bool {
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        ode_solver::test_models::exponential_decay::exponential_decay_problem,
+        AugmentedOdeEquations, ConstantOp, InitialSensEquations, NonLinearOp, OdeEquations, Vector,
+    };
+    type Mcpu = nalgebra::DMatrix<f64>;
+    type Vcpu = nalgebra::DVector<f64>;
+
+    #[test]
+    fn test_initial_sens_exponential() {
+        // dy/dt = -ay (p = [a, y0])
+        let (problem, _soln) = exponential_decay_problem::<Mcpu>(false);
+        let mut sens_eqn = InitialSensEquations::new(&problem);
+        assert_eq!(sens_eqn.max_index(), 2);
+
+        // s_1(0) = |0|
+        //          |1|
+        sens_eqn.set_index(1);
+        let s0 = sens_eqn.init().call(0.0);
+        s0.assert_eq_st(&Vcpu::from_vec(vec![0.0, 1.0]), 1e-10);
+
+        // F(s, t) = J * s
+        // J = |-a 0|
+        //     |0 -a|
+        // F(s, t) = |-a 0| |1| = |-0.1|
+        //           |0 -a| |2|   |-0.2|
+        let y = Vcpu::from_vec(vec![1.0, 1.0]);
+        sens_eqn.update_rhs_out_state(&y, &y, 0.0);
+        let s = Vcpu::from_vec(vec![1.0, 2.0]);
+        let f = sens_eqn.rhs().call(&s, 0.0);
+        f.assert_eq_st(&Vcpu::from_vec(vec![-0.1, -0.2]), 1e-10);
+    }
+}
@@ -1,4 +1,4 @@
-use crate::Scalar;
+use crate::{Scalar, SolverOptions};
 
 pub enum SolverState {
     StepSuccess,
@@ -32,6 +32,16 @@ impl<T: Scalar> JacobianUpdate<T> {
         }
     }
 
+    pub fn from_options(options: &SolverOptions<T>) -> Self {
+        Self {
+            threshold_to_update_jacobian: options.jacobian_update_threshold,
+            threshold_to_update_rhs_jacobian: options.rhs_jacobian_update_threshold,
+            update_jacobian_after_steps: options.jacobian_update_steps,
+            update_rhs_jacobian_after_steps: options.rhs_jacobian_update_steps,
+            ..Self::new()
+        }
+    }
+
     pub fn update_jacobian(&mut self, h: T) {
         self.steps_since_jacobian_eval = 0;
         self.h_at_last_jacobian_update = h;
@@ -0,0 +1,299 @@
+use num_traits::{abs, One, Zero};
+
+use crate::{DenseMatrix, Scalar, Vector};
+
+/// Approximates the action of the phi functions `phi_k(tau h A) w`, `k = 1, ..., p`, of a linear operator `A` on a vector `w`.
+///
+/// The Arnoldi process is used to project `A` onto the Krylov subspace `span{w, A w, A^2 w, ...}`, so only the action of `A` on a vector is required
+/// and `A` is never formed or factorised. The phi functions of the resulting small upper Hessenberg matrix are then calculated using the matrix exponential
+/// of an augmented matrix (see Y. Saad, Analysis of some Krylov subspace approximations to the matrix exponential operator, SIAM J. Numer. Anal. 29 (1992) 209–228).
+///
+/// The phi functions are defined by `phi_0(z) = exp(z)` and `phi_{k+1}(z) = (phi_k(z) - 1 / k!) / z`.
+#[derive(Clone)]
+pub(crate) struct KrylovPhi<M: DenseMatrix> {
+    basis: Vec<M::V>,
+    hessenberg: M,
+    next: M::V,
+    beta: M::T,
+    h: M::T,
+    m: usize,
+    max_dim: usize,
+    niter: usize,
+}
+
+impl<M: DenseMatrix> KrylovPhi<M> {
+    const TAYLOR_TERMS: usize = 16;
+
+    pub(crate) fn new(nstates: usize, max_dim: usize) -> Self {
+        let max_dim = max_dim.min(nstates).max(1);
+        Self {
+            basis: vec![M::V::zeros(nstates); max_dim],
+            hessenberg: M::zeros(max_dim + 1, max_dim),
+            next: M::V::zeros(nstates),
+            beta: M::T::zero(),
+            h: M::T::zero(),
+            m: 0,
+            max_dim,
+            niter: 0,
+        }
+    }
+
+    /// the number of Arnoldi iterations used by the last call to [Self::build]
+    pub(crate) fn niter(&self) -> usize {
+        self.niter
+    }
+
+    /// Build the Krylov subspace for the vector `w` and the operator `A`, given by its action `a(v, y)`, i.e. `y = A v`.
+    ///
+    /// At each iteration the error in `phi_k(h A) w`, `k = 1, ..., p`, is estimated and the process stops once `norm(error) <= 1`,
+    /// or once the subspace is invariant under `A` (in which case the approximation is exact).
+    /// Returns `false` if the maximum dimension of the subspace is reached before this happens.
+    pub(crate) fn build(
+        &mut self,
+        a: impl Fn(&M::V, &mut M::V),
+        w: &M::V,
+        h: M::T,
+        p: usize,
+        norm: impl Fn(&M::V) -> M::T,
+    ) -> bool {
+        self.h = h;
+        self.m = 0;
+        self.niter = 0;
+        self.beta = w.norm();
+        if self.beta == M::T::zero() {
+            return true;
+        }
+        self.basis[0].axpy(M::T::one() / self.beta, w, M::T::zero());
+        for j in 0..self.max_dim {
+            a(&self.basis[j], &mut self.next);
+            let anorm = self.next.norm();
+
+            // modified Gram-Schmidt
+            for i in 0..=j {
+                let hij = self
+                    .next
+                    .binary_fold(&self.basis[i], M::T::zero(), |acc, x, y, _| acc + x * y);
+                self.hessenberg[(i, j)] = hij;
+                self.next.axpy(-hij, &self.basis[i], M::T::one());
+            }
+            let hnext = self.next.norm();
+            self.hessenberg[(j + 1, j)] = hnext;
+            self.m = j + 1;
+            self.niter += 1;
+
+            // error estimate is beta h h_{m+1,m} e_m^T phi_{k+1}(h H_m) e_1 v_{m+1}, using the unnormalised next basis vector.
+            // The phi functions overflow if h H_m has a large positive eigenvalue, so a nan is kept as the estimate (not converged)
+            let phi = self.phi_small(M::T::one(), p + 1);
+            let mut e = M::T::zero();
+            for k in 1..=p {
+                let ek = abs(phi[(j, k)]);
+                if ek > e || ek.is_nan() {
+                    e = ek;
+                }
+            }
+            let error = self.beta * abs(h) * e * norm(&self.next);
+
+            // the subspace is invariant (up to roundoff) if hnext is negligible compared with A v_j, or if it is the whole space,
+            // in either case the approximation is exact but the roundoff in hnext can be amplified by the error estimate for stiff operators
+            let breakdown = hnext <= M::T::from(100.0) * M::T::EPSILON * anorm;
+            if !error.is_nan() && (breakdown || error <= M::T::one() || self.m == w.len()) {
+                return true;
+            }
+            if j + 1 < self.max_dim {
+                self.basis[j + 1].axpy(M::T::one() / hnext, &self.next, M::T::zero());
+            }
+        }
+        false
+    }
+
+    /// y = y + sum_k coeffs_k phi_{k+1}(tau h A) w, using the Krylov subspace from the last call to [Self::build]
+    pub(crate) fn add_phi(&self, tau: M::T, coeffs: &[M::T], y: &mut M::V) {
+        if self.m == 0 || coeffs.iter().all(|c| *c == M::T::zero()) {
+            return;
+        }
+        let p = coeffs.len();
+        let phi = self.phi_small(tau, p);
+        for i in 0..self.m {
+            let mut zi = M::T::zero();
+            for (k, ck) in coeffs.iter().enumerate() {
+                zi += *ck * phi[(i, k)];
+            }
+            y.axpy(self.beta * zi, &self.basis[i], M::T::one());
+        }
+    }
+
+    /// returns the m x p matrix with columns phi_k(tau h H_m) e_1, k = 1, ..., p,
+    /// which are the last p columns of the exponential of the augmented matrix
+    /// | tau h H_m  e_1  0 |
+    /// |     0       0   I |
+    /// |     0       0   0 |
+    fn phi_small(&self, tau: M::T, p: usize) -> M {
+        let m = self.m;
+        let n = m + p;
+        let th = tau * self.h;
+        let mut b = M::zeros(n, n);
+        for j in 0..m {
+            for i in 0..=(j + 1).min(m - 1) {
+                b[(i, j)] = th * self.hessenberg[(i, j)];
+            }
+        }
+        b[(0, m)] = M::T::one();
+        for k in 1..p {
+            b[(m + k - 1, m + k)] = M::T::one();
+        }
+        let e = Self::expm(&b);
+        let mut ret = M::zeros(m, p);
+        for k in 0..p {
+            for i in 0..m {
+                ret[(i, k)] = e[(i, m + k)];
+            }
+        }
+        ret
+    }
+
+    /// the matrix exponential of a small dense matrix, using scaling and squaring of a truncated Taylor series
+    fn expm(a: &M) -> M {
+        let n = a.nrows();
+        let one = M::T::one();
+
+        // scale so that the 1-norm of the matrix is at most 1/2
+        let mut norm = M::T::zero();
+        for j in 0..n {
+            let mut col = M::T::zero();
+            for i in 0..n {
+                col += abs(a[(i, j)]);
+            }
+            if col > norm {
+                norm = col;
+            }
+        }
+        let mut factor = one;
+        let mut nsquarings = 0;
+        while norm * factor > M::T::from(0.5) {
+            factor /= M::T::from(2.0);
+            nsquarings += 1;
+        }
+        let mut a_scaled = M::zeros(n, n);
+        for j in 0..n {
+            for i in 0..n {
+                a_scaled[(i, j)] = a[(i, j)] * factor;
+            }
+        }
+
+        // r = I + a (I + a / 2 (I + a / 3 (...))) using Horner's method
+        let mut r = M::zeros(n, n);
+        let mut tmp = M::zeros(n, n);
+        for i in 0..n {
+            r[(i, i)] = one;
+        }
+        for k in (1..=Self::TAYLOR_TERMS).rev() {
+            tmp.gemm(one / M::T::from(k as f64), &a_scaled, &r, M::T::zero());
+            for i in 0..n {
+                tmp[(i, i)] += one;
+            }
+            std::mem::swap(&mut r, &mut tmp);
+        }
+        for _ in 0..nsquarings {
+            tmp.gemm(one, &r, &r, M::T::zero());
+            std::mem::swap(&mut r, &mut tmp);
+        }
+        r
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::KrylovPhi;
+    use nalgebra::{DMatrix, DVector};
+
+    type M = DMatrix<f64>;
+
+    fn phi(k: usize, z: f64) -> f64 {
+        // phi_k(z) = (phi_{k-1}(z) - 1 / (k-1)!) / z
+        let mut ret = z.exp();
+        let mut fact = 1.0;
+        for j in 0..k {
+            if j > 0 {
+                fact *= j as f64;
+            }
+            ret = (ret - 1.0 / fact) / z;
+        }
+        ret
+    }
+
+    #[test]
+    fn expm_of_diagonal_matrix() {
+        let a = M::from_diagonal(&DVector::from_vec(vec![-30.0, 0.5, 2.0]));
+        let e = KrylovPhi::<M>::expm(&a);
+        for i in 0..3 {
+            let expect = a[(i, i)].exp();
+            assert!(
+                (e[(i, i)] - expect).abs() < 1e-12 * expect.max(1.0),
+                "exp({}) = {}, expected {}",
+                a[(i, i)],
+                e[(i, i)],
+                expect
+            );
+        }
+    }
+
+    #[test]
+    fn phi_actions_of_diagonal_operator() {
+        // A = diag(-1, -1.5, -1.5^2, ...), so phi_k(tau h A) w = phi_k(tau h a_i) w_i
+        let n = 20;
+        let diag = DVector::from_vec((0..n).map(|i| -(1.5f64).powi(i as i32)).collect());
+        let w = DVector::from_vec((0..n).map(|i| 1.0 + i as f64).collect());
+        let mut krylov = KrylovPhi::<M>::new(n, 30);
+        let a = |v: &DVector<f64>, y: &mut DVector<f64>| {
+            y.copy_from(v);
+            y.component_mul_assign(&diag);
+        };
+        let h = 0.1;
+        assert!(krylov.build(a, &w, h, 3, |v| v.norm() * 1e10));
+        for (tau, k) in [(1.0, 1), (0.5, 1), (1.0, 2), (1.0, 3)] {
+            let mut coeffs = vec![0.0; k];
+            coeffs[k - 1] = 1.0;
+            let mut y = DVector::zeros(n);
+            krylov.add_phi(tau, &coeffs, &mut y);
+            for i in 0..n {
+                let expect = phi(k, tau * h * diag[i]) * w[i];
+                assert!(
+                    (y[i] - expect).abs() < 1e-8,
+                    "phi_{}({}) w = {}, expected {}",
+                    k,
+                    tau * h * diag[i],
+                    y[i],
+                    expect
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn phi_actions_of_stiff_operator_use_whole_space() {
+        // the error estimate would never be satisfied for such a stiff operator, but the whole space gives the exact result
+        let n = 5;
+        let diag = DVector::from_vec((0..n).map(|i| -(100.0f64).powi(i as i32)).collect());
+        let w = DVector::from_vec((0..n).map(|i| 1.0 + i as f64).collect());
+        let mut krylov = KrylovPhi::<M>::new(n, 30);
+        let a = |v: &DVector<f64>, y: &mut DVector<f64>| {
+            y.copy_from(v);
+            y.component_mul_assign(&diag);
+        };
+        let h = 10.0;
+        assert!(krylov.build(a, &w, h, 1, |v| v.norm() * 1e10));
+        assert_eq!(krylov.niter(), n);
+        let mut y = DVector::zeros(n);
+        krylov.add_phi(1.0, &[1.0], &mut y);
+        for i in 0..n {
+            let expect = phi(1, h * diag[i]) * w[i];
+            assert!(
+                (y[i] - expect).abs() < 1e-8 * expect.abs().max(1.0),
+                "phi_1({}) w = {}, expected {}",
+                h * diag[i],
+                y[i],
+                expect
+            );
+        }
+    }
+}
@@ -0,0 +1,436 @@
+use num_traits::{abs, One, Zero};
+use serde::Serialize;
+
+use crate::{
+    error::DiffsolError, matrix::MatrixRef, scale, Adams, Bdf, BdfState, DefaultDenseMatrix,
+    DenseMatrix, LinearSolver, NewtonNonlinearSolver, NonLinearOpJacobian, OdeEquationsImplicit,
+    OdeSolverMethod, OdeSolverProblem, OdeSolverStopReason, Op, StateRef, StateRefMut, Vector,
+    VectorRef,
+};
+
+use super::bdf::BdfStatistics;
+
+/// The method currently used by the [Lsoda] solver to take each step
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+pub enum LsodaMethod {
+    Adams,
+    Bdf,
+}
+
+#[derive(Clone, Debug, Serialize, Default)]
+pub struct LsodaStatistics {
+    pub number_of_steps: usize,
+    pub number_of_switches_to_bdf: usize,
+    pub number_of_switches_to_adams: usize,
+}
+
+/// An automatic stiffness-switching solver, in the style of LSODA \[1\].
+///
+/// The solver starts with the non-stiff [Adams] method, and switches to the stiff [Bdf] method (and back again) as
+/// the stiffness of the problem changes. Both methods store the solution using the same modified divided differences,
+/// so the state ([BdfState]) is transferred between the two solvers directly, keeping the solution history.
+///
+/// Stiffness is detected as follows:
+/// - While using the Adams method, the step size of a stiff problem is limited by the convergence of the fixed-point
+///   iteration rather than by the accuracy of the solution, leading to repeated convergence failures.
+///   If `STIFF_STEPS` steps have a convergence failure (with no more than `NON_STIFF_STEPS` steps between them), the solver
+///   switches to the Bdf method.
+/// - While using the Bdf method, every `STIFFNESS_CHECK_INTERVAL` steps the spectral radius `rho` of the Jacobian is
+///   estimated using a few iterations of the power method. If `|h| * rho` is less than `NON_STIFF_RATIO`, the fixed-point iteration
+///   used by the Adams method will converge quickly at the current step size, and the solver switches to the Adams method.
+///
+/// A switch is always made at the start of the next call to [OdeSolverMethod::step], so that interpolation within the
+/// last step is done by the method that took the step. Mass matrices and forward sensitivities are not supported.
+///
+/// # References
+///
+/// \[1\] Petzold, L. (1983). Automatic selection of methods for solving stiff and nonstiff systems of ordinary differential equations. SIAM Journal on Scientific and Statistical Computing, 4(1), 136-148.
+pub struct Lsoda<'a, Eqn, LS, M = <<Eqn as Op>::V as DefaultDenseMatrix>::M>
+where
+    Eqn: OdeEquationsImplicit,
+    Eqn::V: DefaultDenseMatrix,
+    LS: LinearSolver<Eqn::M>,
+    M: DenseMatrix<T = Eqn::T, V = Eqn::V>,
+{
+    adams: Adams<'a, Eqn, M>,
+    bdf: Bdf<'a, Eqn, NewtonNonlinearSolver<Eqn::M, LS>, M>,
+    method: LsodaMethod,
+    switch_pending: bool,
+    n_stiff_steps: usize,
+    n_non_stiff_steps: usize,
+    n_steps_since_check: usize,
+    tstop: Option<Eqn::T>,
+    statistics: LsodaStatistics,
+}
+
+impl<M, Eqn, LS> Clone for Lsoda<'_, Eqn, LS, M>
+where
+    Eqn: OdeEquationsImplicit,
+    Eqn::V: DefaultDenseMatrix,
+    LS: LinearSolver<Eqn::M>,
+    M: DenseMatrix<T = Eqn::T, V = Eqn::V>,
+{
+    fn clone(&self) -> Self {
+        Self {
+            adams: self.adams.clone(),
+            bdf: self.bdf.clone(),
+            method: self.method,
+            switch_pending: self.switch_pending,
+            n_stiff_steps: self.n_stiff_steps,
+            n_non_stiff_steps: self.n_non_stiff_steps,
+            n_steps_since_check: self.n_steps_since_check,
+            tstop: self.tstop,
+            statistics: self.statistics.clone(),
+        }
+    }
+}
+
+impl<'a, M, Eqn, LS> Lsoda<'a, Eqn, LS, M>
+where
+    Eqn: OdeEquationsImplicit,
+    Eqn::V: DefaultDenseMatrix,
+    LS: LinearSolver<Eqn::M>,
+    M: DenseMatrix<T = Eqn::T, V = Eqn::V>,
+    for<'b> &'b Eqn::V: VectorRef<Eqn::V>,
+    for<'b> &'b Eqn::M: MatrixRef<Eqn::M>,
+{
+    const STIFF_STEPS: usize = 5;
+    const NON_STIFF_STEPS: usize = 20;
+    const STIFFNESS_CHECK_INTERVAL: usize = 20;
+    const POWER_ITERATIONS: usize = 10;
+    const NON_STIFF_RATIO: f64 = 0.5;
+
+    pub fn new(
+        problem: &'a OdeSolverProblem<Eqn>,
+        state: BdfState<Eqn::V, M>,
+        linear_solver: LS,
+    ) -> Result<Self, DiffsolError> {
+        let adams = Adams::new(problem, state.clone())?;
+        let bdf = Bdf::new(problem, state, NewtonNonlinearSolver::new(linear_solver))?;
+        Ok(Self {
+            adams,
+            bdf,
+            method: LsodaMethod::Adams,
+            switch_pending: false,
+            n_stiff_steps: 0,
+            n_non_stiff_steps: 0,
+            n_steps_since_check: 0,
+            tstop: None,
+            statistics: LsodaStatistics::default(),
+        })
+    }
+
+    pub fn get_statistics(&self) -> &LsodaStatistics {
+        &self.statistics
+    }
+
+    /// statistics of the steps taken using the Adams method
+    pub fn get_adams_statistics(&self) -> &BdfStatistics {
+        self.adams.get_statistics()
+    }
+
+    /// statistics of the steps taken using the Bdf method
+    pub fn get_bdf_statistics(&self) -> &BdfStatistics {
+        self.bdf.get_statistics()
+    }
+
+    /// the method that will be used to take the next step
+    pub fn method(&self) -> LsodaMethod {
+        match (self.method, self.switch_pending) {
+            (method, false) => method,
+            (LsodaMethod::Adams, true) => LsodaMethod::Bdf,
+            (LsodaMethod::Bdf, true) => LsodaMethod::Adams,
+        }
+    }
+
+    fn switch_method(&mut self) -> Result<(), DiffsolError> {
+        match self.method {
+            LsodaMethod::Adams => {
+                let state = self.adams.checkpoint();
+                self.bdf.set_state(state);
+                self.method = LsodaMethod::Bdf;
+                self.statistics.number_of_switches_to_bdf += 1;
+            }
+            LsodaMethod::Bdf => {
+                let state = self.bdf.checkpoint();
+                self.adams.set_state(state);
+                self.method = LsodaMethod::Adams;
+                self.statistics.number_of_switches_to_adams += 1;
+            }
+        }
+        self.switch_pending = false;
+        self.n_stiff_steps = 0;
+        self.n_non_stiff_steps = 0;
+        self.n_steps_since_check = 0;
+        if let Some(tstop) = self.tstop {
+            match self.method {
+                LsodaMethod::Adams => self.adams.set_stop_time(tstop)?,
+                LsodaMethod::Bdf => self.bdf.set_stop_time(tstop)?,
+            }
+        }
+        Ok(())
+    }
+
+    /// estimate the spectral radius of the Jacobian at the current state using the power method
+    fn estimate_spectral_radius(&self) -> Eqn::T {
+        let problem = self.bdf.problem();
+        let state = self.bdf.state();
+        let rhs = problem.eqn.rhs();
+        let n = state.y.len();
+        let mut v = Eqn::V::from_element(n, Eqn::T::one());
+        v *= scale(Eqn::T::one() / v.norm());
+        let mut jv = Eqn::V::zeros(n);
+        let mut rho = Eqn::T::zero();
+        for _ in 0..Self::POWER_ITERATIONS {
+            rhs.jac_mul_inplace(state.y, state.t, &v, &mut jv);
+            let norm = jv.norm();
+            if norm > rho {
+                rho = norm;
+            }
+            if norm == Eqn::T::zero() {
+                break;
+            }
+            v.copy_from(&jv);
+            v *= scale(Eqn::T::one() / norm);
+        }
+        rho
+    }
+
+    fn check_stiffness(&mut self, adams_fixed_point_failed: bool) {
+        match self.method {
+            LsodaMethod::Adams => {
+                if adams_fixed_point_failed {
+                    self.n_stiff_steps += 1;
+                    self.n_non_stiff_steps = 0;
+                } else {
+                    self.n_non_stiff_steps += 1;
+                    if self.n_non_stiff_steps >= Self::NON_STIFF_STEPS {
+                        self.n_stiff_steps = 0;
+                    }
+                }
+                if self.n_stiff_steps >= Self::STIFF_STEPS {
+                    self.switch_pending = true;
+                }
+            }
+            LsodaMethod::Bdf => {
+                self.n_steps_since_check += 1;
+                if self.n_steps_since_check >= Self::STIFFNESS_CHECK_INTERVAL {
+                    self.n_steps_since_check = 0;
+                    let h = abs(self.bdf.state().h);
+                    if h * self.estimate_spectral_radius() < Eqn::T::from(Self::NON_STIFF_RATIO) {
+                        self.switch_pending = true;
+                    }
+                }
+            }
+        }
+    }
+}
+
+impl<'a, M, Eqn, LS> OdeSolverMethod<'a, Eqn> for Lsoda<'a, Eqn, LS, M>
+where
+    Eqn: OdeEquationsImplicit,
+    Eqn::V: DefaultDenseMatrix,
+    LS: LinearSolver<Eqn::M>,
+    M: DenseMatrix<T = Eqn::T, V = Eqn::V>,
+    for<'b> &'b Eqn::V: VectorRef<Eqn::V>,
+    for<'b> &'b Eqn::M: MatrixRef<Eqn::M>,
+{
+    type State = BdfState<Eqn::V, M>;
+
+    fn problem(&self) -> &'a OdeSolverProblem<Eqn> {
+        self.adams.problem()
+    }
+
+    fn order(&self) -> usize {
+        match self.method {
+            LsodaMethod::Adams => self.adams.order(),
+            LsodaMethod::Bdf => self.bdf.order(),
+        }
+    }
+
+    fn set_state(&mut self, state: Self::State) {
+        match self.method {
+            LsodaMethod::Adams => self.adams.set_state(state),
+            LsodaMethod::Bdf => self.bdf.set_state(state),
+        }
+    }
+
+    fn into_state(self) -> Self::State {
+        match self.method {
+            LsodaMethod::Adams => self.adams.into_state(),
+            LsodaMethod::Bdf => self.bdf.into_state(),
+        }
+    }
+
+    fn checkpoint(&mut self) -> Self::State {
+        match self.method {
+            LsodaMethod::Adams => self.adams.checkpoint(),
+            LsodaMethod::Bdf => self.bdf.checkpoint(),
+        }
+    }
+
+    fn step(&mut self) -> Result<OdeSolverStopReason<Eqn::T>, DiffsolError> {
+        if self.switch_pending {
+            self.switch_method()?;
+        }
+        let (ret, adams_fixed_point_failed) = match self.method {
+            LsodaMethod::Adams => {
+                let nfails = self.adams.get_statistics().number_of_nonlinear_solver_fails;
+                let ret = self.adams.step()?;
+                let nfails_after = self.adams.get_statistics().number_of_nonlinear_solver_fails;
+                (ret, nfails_after > nfails)
+            }
+            LsodaMethod::Bdf => (self.bdf.step()?, false),
+        };
+        self.statistics.number_of_steps += 1;
+        if let OdeSolverStopReason::TstopReached = ret {
+            self.tstop = None;
+        }
+        self.check_stiffness(adams_fixed_point_failed);
+        Ok(ret)
+    }
+
+    fn set_stop_time(&mut self, tstop: Eqn::T) -> Result<(), DiffsolError> {
+        match self.method {
+            LsodaMethod::Adams => self.adams.set_stop_time(tstop)?,
+            LsodaMethod::Bdf => self.bdf.set_stop_time(tstop)?,
+        }
+        self.tstop = Some(tstop);
+        Ok(())
+    }
+
+    fn interpolate(&self, t: Eqn::T) -> Result<Eqn::V, DiffsolError> {
+        match self.method {
+            LsodaMethod::Adams => self.adams.interpolate(t),
+            LsodaMethod::Bdf => self.bdf.interpolate(t),
+        }
+    }
+
+    fn interpolate_out(&self, t: Eqn::T) -> Result<Eqn::V, DiffsolError> {
+        match self.method {
+            LsodaMethod::Adams => self.adams.interpolate_out(t),
+            LsodaMethod::Bdf => self.bdf.interpolate_out(t),
+        }
+    }
+
+    fn interpolate_sens(&self, _t: Eqn::T) -> Result<Vec<Eqn::V>, DiffsolError> {
+        Ok(vec![])
+    }
+
+    fn state(&self) -> StateRef<Eqn::V> {
+        match self.method {
+            LsodaMethod::Adams => self.adams.state(),
+            LsodaMethod::Bdf => self.bdf.state(),
+        }
+    }
+
+    fn state_mut(&mut self) -> StateRefMut<Eqn::V> {
+        // the state is reinitialised on the next step, so there is no history to keep and
+        // we can switch now
+        if self.switch_pending {
+            // the stop time is reset on the next step, so ignore any error here
+            let _ = self.switch_method();
+        }
+        match self.method {
+            LsodaMethod::Adams => self.adams.state_mut(),
+            LsodaMethod::Bdf => self.bdf.state_mut(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::ode_solver::{
+        lsoda::LsodaMethod,
+        test_models::{
+            exponential_decay::{
+                exponential_decay_problem, exponential_decay_problem_with_root,
+                negative_exponential_decay_problem,
+            },
+            robertson_ode::robertson_ode,
+        },
+        tests::{
+            test_checkpointing, test_interpolate, test_ode_solver, test_problem, test_state_mut,
+            test_state_mut_on_problem,
+        },
+    };
+
+    use num_traits::abs;
+
+    type M = nalgebra::DMatrix<f64>;
+    type LS = crate::NalgebraLU<f64>;
+
+    #[test]
+    fn lsoda_state_mut() {
+        test_state_mut(test_problem::<M>().lsoda::<LS>().unwrap());
+    }
+
+    #[test]
+    fn lsoda_test_interpolate() {
+        test_interpolate(test_problem::<M>().lsoda::<LS>().unwrap());
+    }
+
+    #[test]
+    fn lsoda_test_checkpointing() {
+        let (problem, soln) = exponential_decay_problem::<M>(false);
+        let s1 = problem.lsoda::<LS>().unwrap();
+        let s2 = problem.lsoda::<LS>().unwrap();
+        test_checkpointing(soln, s1, s2);
+    }
+
+    #[test]
+    fn lsoda_test_state_mut_exponential_decay() {
+        let (p, soln) = exponential_decay_problem::<M>(false);
+        let s = p.lsoda::<LS>().unwrap();
+        test_state_mut_on_problem(s, soln);
+    }
+
+    #[test]
+    fn lsoda_test_nalgebra_negative_exponential_decay() {
+        let (problem, soln) = negative_exponential_decay_problem::<M>(false);
+        let mut s = problem.lsoda::<LS>().unwrap();
+        test_ode_solver(&mut s, soln, None, false, false);
+    }
+
+    #[test]
+    fn test_lsoda_nalgebra_exponential_decay_stays_non_stiff() {
+        let (problem, soln) = exponential_decay_problem::<M>(false);
+        let mut s = problem.lsoda::<LS>().unwrap();
+        test_ode_solver(&mut s, soln, None, false, false);
+        assert_eq!(s.get_statistics().number_of_switches_to_bdf, 0);
+        assert_eq!(s.method(), LsodaMethod::Adams);
+        assert_eq!(s.get_bdf_statistics().number_of_steps, 0);
+        assert_eq!(
+            s.get_statistics().number_of_steps,
+            s.get_adams_statistics().number_of_steps
+        );
+    }
+
+    #[test]
+    fn test_lsoda_nalgebra_robertson_ode_switches_to_bdf() {
+        let (problem, soln) = robertson_ode::<M>(false, 1);
+        let mut s = problem.lsoda::<LS>().unwrap();
+        test_ode_solver(&mut s, soln, None, false, false);
+        assert!(s.get_statistics().number_of_switches_to_bdf >= 1);
+        assert_eq!(s.method(), LsodaMethod::Bdf);
+        assert_eq!(
+            s.get_statistics().number_of_steps,
+            s.get_adams_statistics().number_of_steps + s.get_bdf_statistics().number_of_steps
+        );
+    }
+
+    #[test]
+    fn test_tstop_lsoda() {
+        let (problem, soln) = robertson_ode::<M>(false, 1);
+        let mut s = problem.lsoda::<LS>().unwrap();
+        test_ode_solver(&mut s, soln, None, true, false);
+    }
+
+    #[test]
+    fn test_root_finder_lsoda() {
+        let (problem, soln) = exponential_decay_problem_with_root::<M>(false);
+        let mut s = problem.lsoda::<LS>().unwrap();
+        let y = test_ode_solver(&mut s, soln, None, false, false);
+        assert!(abs(y[0] - 0.6) < 1e-6, "y[0] = {}", y[0]);
+    }
+}
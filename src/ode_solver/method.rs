@@ -1,3 +1,4 @@
+use num_traits::{abs, One, Zero};
 use std::cell::RefCell;
 use std::rc::Rc;
 
@@ -8,8 +9,8 @@ use crate::{
     scalar::Scalar,
     AdjointContext, AdjointEquations, AugmentedOdeEquations, Checkpointing, DefaultDenseMatrix,
     DenseMatrix, HermiteInterpolator, LinearSolver, Matrix, NonLinearOp, OdeEquations,
-    OdeEquationsAdjoint, OdeEquationsSens, OdeSolverProblem, OdeSolverState, Op, SensEquations,
-    StateRef, StateRefMut, Vector, VectorViewMut,
+    OdeEquationsAdjoint, OdeEquationsSecondOrder, OdeEquationsSens, OdeSolverProblem,
+    OdeSolverState, Op, RootFinder, SensEquations, StateRef, StateRefMut, Vector, VectorViewMut,
 };
 
 #[derive(Debug, PartialEq)]
@@ -72,6 +73,15 @@ where
     /// account the mutated state, this could be expensive for multi-step methods.
     fn state_mut(&mut self) -> StateRefMut<Eqn::V>;
 
+    /// Reverse the direction of integration by flipping the sign of the step size `h` of the state, any stop time should be set again afterwards using [Self::set_stop_time].
+    /// The default implementation sets the step size using [Self::state_mut], so the solver is reinitialised on the next step. Solvers that can rescale their
+    /// history to a new step size flip the sign in place instead, and solvers that can only integrate forwards in time return an error.
+    fn reverse_direction(&mut self) -> Result<(), DiffsolError> {
+        let h = self.state().h;
+        *self.state_mut().h = -h;
+        Ok(())
+    }
+
     /// Step the solution forward by one step, altering the internal state of the solver.
     /// The return value is a `Result` containing the reason for stopping the solver, possible reasons are:
     /// - `InternalTimestep`: The solver has taken a step forward in time, the internal state of the solver is at time self.state().t
@@ -80,7 +90,9 @@ where
     fn step(&mut self) -> Result<OdeSolverStopReason<Eqn::T>, DiffsolError>;
 
     /// Set a stop time for the solver. The solver will stop when the internal time reaches this time.
-    /// Once it stops, the stop time is unset. If `tstop` is at or before the current internal time, an error is returned.
+    /// Once it stops, the stop time is unset. The solver integrates in the direction given by the sign of the step size `h` of the state
+    /// (negative to integrate backwards in time, set using [crate::OdeBuilder::h0] or [Self::state_mut]), and if `tstop` is at or behind
+    /// the current internal time in this direction, an error is returned.
     fn set_stop_time(&mut self, tstop: Eqn::T) -> Result<(), DiffsolError>;
 
     /// Interpolate the solution at a given time. This time should be between the current time and the last solver time step
@@ -98,6 +110,7 @@ where
     /// Using the provided state, solve the problem up to time `final_time`
     /// Returns a Vec of solution values at timepoints chosen by the solver.
     /// After the solver has finished, the internal state of the solver is at time `final_time`.
+    /// If `final_time` is before the current time then the problem is integrated backwards in time.
     #[allow(clippy::type_complexity)]
     fn solve(
         &mut self,
@@ -139,6 +152,7 @@ where
             self.state().y,
             self.state().g,
         );
+        set_direction(self, final_time)?;
         self.set_stop_time(final_time)?;
         while self.step()? != OdeSolverStopReason::TstopReached {
             write_out(
@@ -172,6 +186,7 @@ where
     /// Using the provided state, solve the problem up to time `t_eval[t_eval.len()-1]`
     /// Returns a Vec of solution values at timepoints given by `t_eval`.
     /// After the solver has finished, the internal state of the solver is at time `t_eval[t_eval.len()-1]`.
+    /// The times in `t_eval` must be increasing, or decreasing to integrate backwards in time, starting from the current time.
     fn solve_dense(
         &mut self,
         t_eval: &[Eqn::T],
@@ -187,11 +202,8 @@ where
         };
         let mut ret = <<Eqn::V as DefaultDenseMatrix>::M as Matrix>::zeros(nrows, t_eval.len());
 
-        // check t_eval is increasing and all values are greater than or equal to the current time
-        let t0 = self.state().t;
-        if t_eval.windows(2).any(|w| w[0] > w[1] || w[0] < t0) {
-            return Err(ode_solver_error!(InvalidTEval));
-        }
+        // check t_eval is monotonic in the direction of integration, starting from the current time
+        let direction = check_t_eval(self, t_eval)?;
 
         fn write_out<Eqn: OdeEquations>(
             p: &OdeSolverProblem<Eqn>,
@@ -218,7 +230,7 @@ where
         self.set_stop_time(t_eval[t_eval.len() - 1])?;
         let mut step_reason = OdeSolverStopReason::InternalTimestep;
         for (i, t) in t_eval.iter().take(t_eval.len() - 1).enumerate() {
-            while self.state().t < *t {
+            while (*t - self.state().t) * direction > Eqn::T::zero() {
                 step_reason = self.step()?;
             }
             if self.problem().integrate_out {
@@ -342,6 +354,7 @@ where
     /// Returns a tuple `(y, sens)`, where `y` is a dense matrix of solution values at timepoints given by `t_eval`,
     /// and `sens` is a Vec of dense matrices, the ith element of the Vec are the the sensitivities with respect to the ith parameter.
     /// After the solver has finished, the internal state of the solver is at time `t_eval[t_eval.len()-1]`.
+    /// As for [Self::solve_dense], `t_eval` can be decreasing to integrate backwards in time.
     #[allow(clippy::type_complexity)]
     fn solve_dense_sensitivities(
         &mut self,
@@ -374,17 +387,14 @@ where
                 self.problem().eqn.rhs().nparams()
             ];
 
-        // check t_eval is increasing and all values are greater than or equal to the current time
-        let t0 = self.state().t;
-        if t_eval.windows(2).any(|w| w[0] > w[1] || w[0] < t0) {
-            return Err(ode_solver_error!(InvalidTEval));
-        }
+        // check t_eval is monotonic in the direction of integration, starting from the current time
+        let direction = check_t_eval(self, t_eval)?;
 
         // do loop
         self.set_stop_time(t_eval[t_eval.len() - 1])?;
         let mut step_reason = OdeSolverStopReason::InternalTimestep;
         for (i, t) in t_eval.iter().take(t_eval.len() - 1).enumerate() {
-            while self.state().t < *t {
+            while (*t - self.state().t) * direction > Eqn::T::zero() {
                 step_reason = self.step()?;
             }
             let y = self.interpolate(*t)?;
@@ -409,6 +419,110 @@ where
     }
 }
 
+/// Reverse the step size of the solver if needed so that it integrates towards `t_end`, returning the direction of integration (1 or -1).
+fn set_direction<'a, Eqn, S>(solver: &mut S, t_end: Eqn::T) -> Result<Eqn::T, DiffsolError>
+where
+    Eqn: OdeEquations + 'a,
+    S: OdeSolverMethod<'a, Eqn>,
+{
+    let (t, h) = (solver.state().t, solver.state().h);
+    if (t_end - t) * h < Eqn::T::zero() {
+        solver.reverse_direction()?;
+    }
+    if solver.state().h < Eqn::T::zero() {
+        Ok(-Eqn::T::one())
+    } else {
+        Ok(Eqn::T::one())
+    }
+}
+
+/// Check that `t_eval` is monotonic and starts at or after the current time of the solver, in the direction from the current time to
+/// the last element of `t_eval`. The step size of the solver is set to integrate in this direction, which is returned.
+fn check_t_eval<'a, Eqn, S>(solver: &mut S, t_eval: &[Eqn::T]) -> Result<Eqn::T, DiffsolError>
+where
+    Eqn: OdeEquations + 'a,
+    S: OdeSolverMethod<'a, Eqn>,
+{
+    let (t0, h) = (solver.state().t, solver.state().h);
+    let t_end = *t_eval
+        .last()
+        .ok_or_else(|| ode_solver_error!(InvalidTEval))?;
+    let direction = if t_end < t0 || (t_end == t0 && h < Eqn::T::zero()) {
+        -Eqn::T::one()
+    } else {
+        Eqn::T::one()
+    };
+    if (t_eval[0] - t0) * direction < Eqn::T::zero()
+        || t_eval
+            .windows(2)
+            .any(|w| (w[1] - w[0]) * direction < Eqn::T::zero())
+    {
+        return Err(ode_solver_error!(InvalidTEval));
+    }
+    set_direction(solver, t_end)
+}
+
+/// What a solver needs to do about its stop time before its next step, see [check_tstop]
+pub(crate) enum TstopCheck<T> {
+    /// the solver is at the stop time
+    Reached,
+    /// the next step would go beyond the stop time, so the step size needs to be scaled by this factor
+    ScaleStep(T),
+    /// the next step will not reach the stop time
+    NotReached,
+}
+
+/// Check the time `t` and step size `h` of a solver against the stop time `tstop`, allowing for rounding errors in `t`.
+/// Returns an error if `tstop` is behind `t` in the direction of integration.
+pub(crate) fn check_tstop<T: Scalar>(t: T, h: T, tstop: T) -> Result<TstopCheck<T>, DiffsolError> {
+    // check if we are at tstop
+    let troundoff = T::from(100.0) * T::EPSILON * (abs(t) + abs(h));
+    if abs(t - tstop) <= troundoff {
+        return Ok(TstopCheck::Reached);
+    } else if (h > T::zero() && tstop < t - troundoff) || (h < T::zero() && tstop > t + troundoff) {
+        return Err(DiffsolError::from(
+            OdeSolverError::StopTimeBeforeCurrentTime {
+                stop_time: tstop.into(),
+                state_time: t.into(),
+            },
+        ));
+    }
+
+    // check if the next step will be beyond tstop, if so the step size needs adjusting
+    if (h > T::zero() && t + h > tstop + troundoff) || (h < T::zero() && t + h < tstop - troundoff)
+    {
+        Ok(TstopCheck::ScaleStep((tstop - t) / h))
+    } else {
+        Ok(TstopCheck::NotReached)
+    }
+}
+
+/// Check that `t` is within the last step of a solver from `t_old` to `t_new`, which might be in the opposite direction to the
+/// current step size if the direction has since been reversed
+pub(crate) fn check_interpolation_time<T: Scalar>(
+    t: T,
+    t_old: T,
+    t_new: T,
+) -> Result<(), DiffsolError> {
+    let is_forward = t_new >= t_old;
+    if (is_forward && (t > t_new || t < t_old)) || (!is_forward && (t < t_new || t > t_old)) {
+        return Err(ode_solver_error!(InterpolationTimeOutsideCurrentStep));
+    }
+    Ok(())
+}
+
+/// Reinitialise the root finder of a solver at the state `y` at time `t`, if the problem has a root function
+pub(crate) fn init_root_finder<Eqn: OdeEquations>(
+    problem: &OdeSolverProblem<Eqn>,
+    root_finder: Option<&RootFinder<Eqn::V>>,
+    y: &Eqn::V,
+    t: Eqn::T,
+) {
+    if let Some(root_fn) = problem.eqn.root() {
+        root_finder.unwrap().init(&root_fn, y, t);
+    }
+}
+
 pub trait AugmentedOdeSolverMethod<'a, Eqn, AugmentedEqn>: OdeSolverMethod<'a, Eqn>
 where
     Eqn: OdeEquations + 'a,
@@ -424,6 +538,69 @@ where
 {
 }
 
+/// Methods for solvers of second-order problems (see [OdeEquationsSecondOrder]), which give the positions `y` and velocities `v` of the solution
+/// separately. The state of these problems is `[y, v]`. This trait is implemented for any [OdeSolverMethod] with second-order equations.
+pub trait SecondOrderOdeSolverMethod<'a, Eqn>: OdeSolverMethod<'a, Eqn>
+where
+    Eqn: OdeEquationsSecondOrder + 'a,
+{
+    /// Get the positions `y` of the current state
+    fn positions(&self) -> Eqn::V {
+        let n = self.problem().eqn.npositions();
+        Eqn::V::from_vec(self.state().y.as_slice()[..n].to_vec())
+    }
+
+    /// Get the velocities `v` of the current state
+    fn velocities(&self) -> Eqn::V {
+        let n = self.problem().eqn.npositions();
+        Eqn::V::from_vec(self.state().y.as_slice()[n..].to_vec())
+    }
+
+    /// Solve the problem up to time `final_time` as in [OdeSolverMethod::solve], returning the positions and velocities
+    /// at each internal time step as separate matrices, along with the times.
+    /// Returns an error if the problem has an output function, use [OdeSolverMethod::solve] instead.
+    #[allow(clippy::type_complexity)]
+    fn solve_second_order(
+        &mut self,
+        final_time: Eqn::T,
+    ) -> Result<
+        (
+            <Eqn::V as DefaultDenseMatrix>::M,
+            <Eqn::V as DefaultDenseMatrix>::M,
+            Vec<Eqn::T>,
+        ),
+        DiffsolError,
+    >
+    where
+        Eqn::V: DefaultDenseMatrix,
+    {
+        if self.problem().eqn.out().is_some() {
+            return Err(ode_solver_error!(
+                Other,
+                "Cannot split the solution into positions and velocities if the problem has an output function"
+            ));
+        }
+        let n = self.problem().eqn.npositions();
+        let (y, t) = self.solve(final_time)?;
+        let mut positions = <<Eqn::V as DefaultDenseMatrix>::M as Matrix>::zeros(n, t.len());
+        let mut velocities = <<Eqn::V as DefaultDenseMatrix>::M as Matrix>::zeros(n, t.len());
+        for j in 0..t.len() {
+            for i in 0..n {
+                positions[(i, j)] = y[(i, j)];
+                velocities[(i, j)] = y[(n + i, j)];
+            }
+        }
+        Ok((positions, velocities, t))
+    }
+}
+
+impl<'a, Eqn, S> SecondOrderOdeSolverMethod<'a, Eqn> for S
+where
+    Eqn: OdeEquationsSecondOrder + 'a,
+    S: OdeSolverMethod<'a, Eqn>,
+{
+}
+
 pub trait AdjointOdeSolverMethod<'a, Eqn>: OdeSolverMethod<'a, Eqn>
 where
     Eqn: OdeEquationsAdjoint + 'a,
@@ -474,11 +651,105 @@ mod test {
     use crate::{
         ode_solver::test_models::exponential_decay::{
             exponential_decay_problem, exponential_decay_problem_adjoint,
-            exponential_decay_problem_sens,
+            exponential_decay_problem_sens, exponential_decay_problem_with_root,
         },
-        scale, NalgebraLU, OdeSolverMethod, Vector,
+        scale, NalgebraLU, NonLinearOp, OdeEquations, OdeSolverMethod, OdeSolverStopReason, Vector,
     };
 
+    type V = nalgebra::DVector<f64>;
+
+    // solve the exponential decay problem from t = 0 backwards to t = -5, then forwards again to t = 2
+    fn check_solve_backwards<'a, Eqn>(mut s: impl OdeSolverMethod<'a, Eqn>)
+    where
+        Eqn: OdeEquations<V = V, T = f64> + 'a,
+    {
+        let problem = s.problem();
+        let k = 0.1;
+        let y0 = V::from_vec(vec![1.0, 1.0]);
+        let expect = |t: f64| &y0 * scale(f64::exp(-k * t));
+
+        let (y, t) = s.solve(-5.0).unwrap();
+        assert!(t.windows(2).all(|w| w[1] < w[0]));
+        assert!((t[t.len() - 1] + 5.0).abs() < 1e-10);
+        for (i, t_i) in t.iter().enumerate() {
+            let y_i = y.column(i).into_owned();
+            y_i.assert_eq_norm(&expect(*t_i), &problem.atol, problem.rtol, 15.0);
+        }
+
+        let t_eval = [-5.0, -3.0, 0.0, 2.0];
+        let y = s.solve_dense(&t_eval).unwrap();
+        for (i, t_i) in t_eval.iter().enumerate() {
+            let y_i = y.column(i).into_owned();
+            y_i.assert_eq_norm(&expect(*t_i), &problem.atol, problem.rtol, 15.0);
+        }
+    }
+
+    #[test]
+    fn test_solve_backwards() {
+        type M = nalgebra::DMatrix<f64>;
+        type LS = NalgebraLU<f64>;
+        let (problem, _soln) = exponential_decay_problem::<M>(false);
+        check_solve_backwards(problem.bdf::<LS>().unwrap());
+        check_solve_backwards(problem.esdirk34::<LS>().unwrap());
+        check_solve_backwards(problem.tr_bdf2::<LS>().unwrap());
+        check_solve_backwards(problem.tsit45().unwrap());
+        check_solve_backwards(problem.rodas4::<LS>().unwrap());
+        check_solve_backwards(problem.radau::<LS>().unwrap());
+        check_solve_backwards(problem.adams().unwrap());
+        check_solve_backwards(problem.lsoda::<LS>().unwrap());
+    }
+
+    #[test]
+    fn test_dense_solve_backwards() {
+        let (problem, soln) = exponential_decay_problem::<nalgebra::DMatrix<f64>>(false);
+        let mut s = problem.bdf::<NalgebraLU<f64>>().unwrap();
+
+        // solve forwards to the last solution point, then backwards through the solution points
+        let t_eval = soln
+            .solution_points
+            .iter()
+            .rev()
+            .map(|p| p.t)
+            .collect::<Vec<_>>();
+        s.solve(t_eval[0]).unwrap();
+        let y = s.solve_dense(t_eval.as_slice()).unwrap();
+        for (i, soln_pt) in soln.solution_points.iter().rev().enumerate() {
+            let y_i = y.column(i).into_owned();
+            y_i.assert_eq_norm(&soln_pt.state, &problem.atol, problem.rtol, 15.0);
+        }
+
+        // t_eval must be monotonic and start at the current time in the direction of integration
+        assert!(s.solve_dense(&[-1.0, -2.0, -1.5]).is_err());
+        assert!(s.solve_dense(&[1.0, -1.0]).is_err());
+        assert!(s.solve_dense(&[]).is_err());
+    }
+
+    #[test]
+    fn test_root_backwards() {
+        let (problem, _soln) = exponential_decay_problem_with_root::<nalgebra::DMatrix<f64>>(false);
+        let mut s = problem.bdf::<NalgebraLU<f64>>().unwrap();
+
+        // start at t = 10 and integrate backwards, the root y = 0.6 is at t = 10 ln(1 / 0.6)
+        let k = 0.1;
+        let state = s.state_mut();
+        state.y.fill(f64::exp(-k * 10.0));
+        problem.eqn.rhs().call_inplace(state.y, 10.0, state.dy);
+        *state.t = 10.0;
+        *state.h = -1.0;
+        s.set_stop_time(0.0).unwrap();
+        let t_root = loop {
+            match s.step().unwrap() {
+                OdeSolverStopReason::RootFound(t) => break t,
+                OdeSolverStopReason::TstopReached => panic!("root not found"),
+                OdeSolverStopReason::InternalTimestep => {}
+            }
+        };
+        let expected = f64::ln(1.0 / 0.6) / k;
+        assert!((t_root - expected).abs() < 1e-4, "t_root = {}", t_root);
+        let y_root = s.interpolate(t_root).unwrap();
+        assert!((y_root[0] - 0.6).abs() < 1e-4, "y = {}", y_root[0]);
+    }
+
     #[test]
     fn test_solve() {
         let (problem, _soln) = exponential_decay_problem::<nalgebra::DMatrix<f64>>(false);
@@ -1,16 +1,43 @@
+pub mod adams;
 pub mod adjoint_equations;
 pub mod bdf;
+pub mod bdf_dae;
 pub mod bdf_state;
+pub mod brownian;
 pub mod builder;
+pub mod bvp;
 pub mod checkpointing;
+pub mod constraints;
+pub mod dae_problem;
+pub mod dde;
 pub mod equations;
+pub mod explicit_rk;
+pub mod exponential_rosenbrock;
+pub mod exponential_rosenbrock_tableau;
+pub mod fixed_step;
+pub mod generalized_alpha;
+pub mod imex_ark;
+pub mod initial_sens_equations;
 pub mod jacobian_update;
+pub mod krylov_phi;
+pub mod lsoda;
 pub mod method;
+pub mod options;
+pub mod parareal;
 pub mod problem;
+pub mod projection;
+pub mod radau;
+pub mod rosenbrock;
+pub mod rosenbrock_tableau;
+pub mod sde;
+pub mod sde_problem;
 pub mod sdirk;
 pub mod sdirk_state;
 pub mod sens_equations;
 pub mod state;
+pub mod step_controller;
+pub mod symplectic;
+pub mod symplectic_tableau;
 pub mod tableau;
 pub mod test_models;
 
@@ -29,14 +56,17 @@ mod tests {
     use crate::matrix::Matrix;
     use crate::op::unit::UnitCallable;
     use crate::op::ParameterisedOp;
+    use crate::scalar::Scalar;
+    use crate::{
+        error::{DiffsolError, OdeSolverError},
+        ConstantOp, DefaultDenseMatrix, DefaultSolver, IController, LinearSolver,
+        ManifoldProjection, NonLinearOp, Op, StepController, Vector,
+    };
     use crate::{
         op::OpStatistics, AdjointOdeSolverMethod, AugmentedOdeSolverMethod, CraneliftModule,
         NonLinearOpJacobian, OdeBuilder, OdeEquations, OdeEquationsAdjoint, OdeEquationsImplicit,
         OdeEquationsRef, OdeSolverMethod, OdeSolverProblem, OdeSolverState, OdeSolverStopReason,
     };
-    use crate::{
-        ConstantOp, DefaultDenseMatrix, DefaultSolver, LinearSolver, NonLinearOp, Op, Vector,
-    };
     use num_traits::One;
     use num_traits::Zero;
 
@@ -531,6 +561,293 @@ mod tests {
         }
     }
 
+    /// Step a solver that has been put in fixed-step mode until it reaches the stop time `expected_t[expected_t.len() - 1]`,
+    /// checking that it steps exactly to each of the times in `expected_t`, and that the final state matches `expected_y` to within the relative tolerance `tol`
+    pub fn test_fixed_step<'a, Eqn, Method>(
+        mut s: Method,
+        expected_t: &[Eqn::T],
+        expected_y: &Eqn::V,
+        tol: Eqn::T,
+    ) where
+        Eqn: OdeEquations + 'a,
+        Method: OdeSolverMethod<'a, Eqn>,
+    {
+        let t_stop = expected_t[expected_t.len() - 1];
+        s.set_stop_time(t_stop).unwrap();
+        for (i, &t) in expected_t.iter().enumerate() {
+            let reason = s.step().unwrap();
+            assert_eq!(
+                reason == OdeSolverStopReason::TstopReached,
+                i == expected_t.len() - 1
+            );
+            let troundoff = Eqn::T::from(100.0) * Eqn::T::EPSILON * (t.abs() + Eqn::T::one());
+            assert!(
+                (s.state().t - t).abs() <= troundoff,
+                "t = {}, expected {}",
+                s.state().t,
+                t
+            );
+        }
+        let error = (s.state().y.clone() - expected_y).norm();
+        assert!(
+            error < tol * expected_y.norm(),
+            "error = {} at t = {}",
+            error,
+            t_stop
+        );
+    }
+
+    /// Check a solver for a problem with [SolverOptions::max_step] and [SolverOptions::max_steps] set: the step size never exceeds `max_step`, and the solver
+    /// fails once `max_steps` steps have been taken, with the count of steps reset by setting the stop time, by setting the state and by mutating the state.
+    pub fn test_max_step_and_max_steps<'a, Eqn, Method>(mut s: Method)
+    where
+        Eqn: OdeEquations + 'a,
+        Method: OdeSolverMethod<'a, Eqn>,
+    {
+        let options = &s.problem().options;
+        let max_step = options.max_step;
+        let max_steps = options.max_steps.unwrap();
+        let t_stop = s.state().t + max_step * Eqn::T::from(10.0 * max_steps as f64);
+        let checkpoint = s.checkpoint();
+        let check_max_steps = |s: &mut Method| {
+            for _ in 0..max_steps {
+                let t0 = s.state().t;
+                s.step().unwrap();
+                let h = s.state().t - t0;
+                // h is the difference of two times, so allow for the rounding error in t
+                let tol = Eqn::T::from(4.0) * Eqn::T::EPSILON * (max_step + t0.abs());
+                assert!(h.abs() <= max_step + tol, "h = {}", h);
+            }
+            let err = s.step().unwrap_err();
+            assert!(
+                matches!(
+                    err,
+                    DiffsolError::OdeSolverError(OdeSolverError::MaxStepsReached { .. })
+                ),
+                "{}",
+                err
+            );
+        };
+
+        s.set_stop_time(t_stop).unwrap();
+        check_max_steps(&mut s);
+        s.set_stop_time(t_stop).unwrap();
+        check_max_steps(&mut s);
+        s.set_state(checkpoint);
+        check_max_steps(&mut s);
+        let t = s.state().t;
+        *s.state_mut().t = t;
+        check_max_steps(&mut s);
+    }
+
+    /// A step controller that behaves as [IController], and records whether each call was for an accepted or a rejected step, and the step size
+    #[derive(Clone, Default)]
+    pub struct RecordingController {
+        pub calls: std::sync::Arc<std::sync::Mutex<Vec<(bool, f64)>>>,
+    }
+
+    impl StepController<f64> for RecordingController {
+        fn accept(&mut self, h: f64, error_norm: f64, k: usize, safety: f64) -> f64 {
+            self.calls.lock().unwrap().push((true, h));
+            IController.accept(h, error_norm, k, safety)
+        }
+        fn reject(&mut self, h: f64, error_norm: f64, k: usize, safety: f64) -> f64 {
+            self.calls.lock().unwrap().push((false, h));
+            IController.reject(h, error_norm, k, safety)
+        }
+        fn reset(&mut self) {}
+    }
+
+    /// Solve up to `final_time` with a solver that is using `controller`, and check the calls made to the controller against the steps taken:
+    /// each accepted step size passed to the controller is the size of the next step taken by the solver, and a rejected step is always retried with a smaller step size.
+    /// Returns the number of accepted and rejected steps passed to the controller, and the number of steps taken.
+    pub fn test_step_controller<'a, Eqn, Method>(
+        s: &mut Method,
+        controller: &RecordingController,
+        final_time: f64,
+    ) -> (usize, usize, usize)
+    where
+        Eqn: OdeEquations<T = f64> + 'a,
+        Method: OdeSolverMethod<'a, Eqn>,
+    {
+        let mut steps = Vec::new();
+        s.set_stop_time(final_time).unwrap();
+        loop {
+            let t0 = s.state().t;
+            let reason = s.step().unwrap();
+            steps.push(s.state().t - t0);
+            if reason == OdeSolverStopReason::TstopReached {
+                break;
+            }
+        }
+
+        let calls = controller.calls.lock().unwrap();
+        let mut step = 0;
+        for (i, &(accepted, h)) in calls.iter().enumerate() {
+            if accepted {
+                // the controller might not be called for every step (e.g. when the order of a BDF method changes)
+                while step < steps.len() && (steps[step] - h).abs() > 1e-12 * h.abs() {
+                    step += 1;
+                }
+                assert!(
+                    step < steps.len(),
+                    "accepted step of size {} was not taken",
+                    h
+                );
+                step += 1;
+            } else if let Some(&(_, h_next)) = calls.get(i + 1) {
+                assert!(
+                    h_next.abs() < h.abs(),
+                    "step size not reduced after rejection"
+                );
+            }
+        }
+        let naccept = calls.iter().filter(|(accepted, _)| *accepted).count();
+        (naccept, calls.len() - naccept, steps.len())
+    }
+
+    /// The energy `y_i^2 + v_i^2` of each of the two oscillators of the undamped [test_models::spring_mass::spring_mass_problem], relative to
+    /// its initial value, which is conserved by the exact solution
+    pub fn spring_mass_energy<M>() -> ManifoldProjection<'static, M>
+    where
+        M: Matrix,
+        M::V: DefaultDenseMatrix,
+        <M::V as DefaultDenseMatrix>::M: DefaultSolver,
+    {
+        let e0 = [M::T::one(), M::T::from(0.25)];
+        ManifoldProjection::from_closures(
+            move |x: &M::V, _t: M::T, g: &mut M::V| {
+                for i in 0..2 {
+                    g[i] = x[i] * x[i] + x[i + 2] * x[i + 2] - e0[i];
+                }
+            },
+            |x: &M::V, _t: M::T, v: &M::V, y: &mut M::V| {
+                for i in 0..2 {
+                    y[i] = M::T::from(2.0) * (x[i] * v[i] + x[i + 2] * v[i + 2]);
+                }
+            },
+            4,
+            2,
+        )
+    }
+
+    /// Solve the undamped [test_models::spring_mass::spring_mass_problem] with a solver that projects onto [spring_mass_energy], checking that
+    /// the energy of each oscillator is conserved to within `tol` after every step, and that the solution still matches `soln` at each of its time points.
+    pub fn test_projection<'a, Eqn, Method>(
+        mut s: Method,
+        soln: OdeSolverSolution<Eqn::V>,
+        tol: Eqn::T,
+    ) where
+        Eqn: OdeEquations + 'a,
+        Method: OdeSolverMethod<'a, Eqn>,
+    {
+        let e0 = [Eqn::T::one(), Eqn::T::from(0.25)];
+        for point in soln.solution_points.iter().skip(1) {
+            // stop at each solution point, since only the solution at the end of each step is projected
+            s.set_stop_time(point.t).unwrap();
+            loop {
+                let reason = s.step().unwrap();
+                let y = s.state().y;
+                for i in 0..2 {
+                    let g = y[i] * y[i] + y[i + 2] * y[i + 2] - e0[i];
+                    assert!(g.abs() < tol, "g = {} at t = {}", g, s.state().t);
+                }
+                if reason == OdeSolverStopReason::TstopReached {
+                    break;
+                }
+            }
+            let error = s.state().y.clone() - &point.state;
+            let error_norm = error
+                .squared_norm(&point.state, &s.problem().atol, s.problem().rtol)
+                .sqrt();
+            assert!(
+                error_norm < Eqn::T::from(15.0),
+                "error_norm: {} at t = {}",
+                error_norm,
+                point.t
+            );
+        }
+    }
+
+    /// The [test_models::robertson_ode::robertson_ode] problem with loose tolerances, for which the concentrations go negative (and then blow up)
+    /// unless they are constrained, with a [crate::Constraint::NonNegative] constraint on each concentration
+    #[allow(clippy::type_complexity)]
+    pub fn robertson_ode_with_constraints<M>() -> (
+        OdeSolverProblem<impl OdeEquationsImplicit<M = M, V = M::V, T = M::T>>,
+        OdeSolverSolution<M::V>,
+    )
+    where
+        M: Matrix<T = f64> + 'static,
+    {
+        let (mut problem, soln) = test_models::robertson_ode::robertson_ode::<M>(false, 1);
+        problem.rtol = 1e-3;
+        problem.atol = M::V::from_element(3, 1e-3);
+        problem.options.constraints = Some(vec![crate::Constraint::NonNegative; 3]);
+        (problem, soln)
+    }
+
+    /// Solve a problem with constraints up to each time point of `soln`, checking that the solution satisfies the constraints after every step,
+    /// and that it matches `soln` at each of its time points.
+    pub fn test_constraints<'a, Eqn, Method>(s: &mut Method, soln: &OdeSolverSolution<Eqn::V>)
+    where
+        Eqn: OdeEquations + 'a,
+        Method: OdeSolverMethod<'a, Eqn>,
+    {
+        let constraints = s.problem().options.constraints.clone().unwrap();
+        for point in soln.solution_points.iter().skip(1) {
+            s.set_stop_time(point.t).unwrap();
+            loop {
+                let reason = s.step().unwrap();
+                for (c, &y) in constraints.iter().zip(s.state().y.as_slice()) {
+                    assert!(
+                        c.is_satisfied(y),
+                        "y = {} does not satisfy {:?} at t = {}",
+                        y,
+                        c,
+                        s.state().t
+                    );
+                }
+                if reason == OdeSolverStopReason::TstopReached {
+                    break;
+                }
+            }
+            let error = s.state().y.clone() - &point.state;
+            let error_norm = error
+                .squared_norm(&point.state, &s.problem().atol, s.problem().rtol)
+                .sqrt();
+            assert!(
+                error_norm < Eqn::T::from(15.0),
+                "error_norm: {} at t = {}",
+                error_norm,
+                point.t
+            );
+        }
+    }
+
+    /// Solve up to each time point of `soln` with a solver using a non-default [crate::ErrorNorm], checking that the first state matches `soln` to
+    /// within `tol` relative to its value (only the first state is checked, so that the norm can exclude the other states from the error control).
+    pub fn test_error_norm<'a, Eqn, Method>(
+        mut s: Method,
+        soln: &OdeSolverSolution<Eqn::V>,
+        tol: Eqn::T,
+    ) where
+        Eqn: OdeEquations + 'a,
+        Method: OdeSolverMethod<'a, Eqn>,
+    {
+        assert!(s.problem().options.error_norm.is_some());
+        for point in soln.solution_points.iter().skip(1) {
+            s.set_stop_time(point.t).unwrap();
+            while s.step().unwrap() != OdeSolverStopReason::TstopReached {}
+            let error = (s.state().y[0] - point.state[0]).abs();
+            assert!(
+                error < tol * point.state[0].abs(),
+                "error = {} at t = {}",
+                error,
+                point.t
+            );
+        }
+    }
+
     pub fn test_state_mut_on_problem<'a, Eqn, Method>(
         mut s: Method,
         soln: OdeSolverSolution<Eqn::V>,
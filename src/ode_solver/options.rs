@@ -0,0 +1,159 @@
+use std::sync::Arc;
+
+use crate::{
+    error::{DiffsolError, OdeSolverError},
+    ode_solver_error, Constraint, ErrorNorm, IndexType, Scalar,
+};
+
+/// Options that control the step size selection and nonlinear solves of the [crate::Bdf] and [crate::Sdirk] solvers.
+///
+/// The options are stored in [crate::OdeSolverProblem::options] and can be set using [crate::OdeBuilder::solver_options], e.g.
+///
+/// ```rust
+/// use diffsol::{OdeBuilder, SolverOptions};
+/// type M = nalgebra::DMatrix<f64>;
+///
+/// let builder = OdeBuilder::<M>::new().solver_options(SolverOptions {
+///     max_step: 0.1,
+///     max_steps: Some(10_000),
+///     ..Default::default()
+/// });
+/// ```
+///
+/// Options that are `None` use the default of each solver.
+#[derive(Clone, Debug)]
+pub struct SolverOptions<T: Scalar> {
+    /// Maximum absolute step size (default: infinity).
+    pub max_step: T,
+    /// Minimum absolute step size, the solver fails with a [OdeSolverError::StepSizeTooSmall] error if the step size is reduced below this
+    /// (default: `1e-32` for [crate::Bdf] and `1e-13` for [crate::Sdirk]).
+    pub min_step: Option<T>,
+    /// Maximum number of steps taken after the stop time is set (i.e. during a call to [crate::OdeSolverMethod::solve] or [crate::OdeSolverMethod::solve_dense]),
+    /// the solver fails with a [OdeSolverError::MaxStepsReached] error if more steps are needed (default: unlimited).
+    pub max_steps: Option<usize>,
+    /// Maximum order of the [crate::Bdf] solver, at least 1 (default: 5, higher values are limited to 5).
+    pub max_order: IndexType,
+    /// Maximum number of Newton iterations for each nonlinear solve (default: 4 for [crate::Bdf] and 10 for [crate::Sdirk]).
+    pub max_newton_iter: Option<IndexType>,
+    /// The Jacobian is updated after a successful step if the step size has changed by more than this fraction since the last update (default: 0.3).
+    pub jacobian_update_threshold: T,
+    /// The Jacobian of the right-hand side is re-evaluated after a Newton convergence failure if the step size has changed by less than this fraction
+    /// since the last update (default: 0.2).
+    pub rhs_jacobian_update_threshold: T,
+    /// Maximum number of steps between updates of the Jacobian (default: 20).
+    pub jacobian_update_steps: usize,
+    /// Maximum number of steps between evaluations of the Jacobian of the right-hand side (default: 50).
+    pub rhs_jacobian_update_steps: usize,
+    /// Inequality constraints for each component of the state (see [Constraint]), or `None` for no constraints (default: `None`).
+    /// Set using [crate::OdeBuilder::constraints].
+    pub constraints: Option<Vec<Constraint>>,
+    /// Norm used for the error test of each step and to check the convergence of the Newton iterations (see [ErrorNorm]),
+    /// or `None` for the weighted RMS norm (default: `None`). Set using [crate::OdeBuilder::error_norm].
+    pub error_norm: Option<Arc<dyn ErrorNorm<T>>>,
+    /// Use the [crate::LineSearchNewtonNonlinearSolver] instead of the [crate::NewtonNonlinearSolver] to calculate consistent initial conditions for problems
+    /// with a mass matrix when the solver state is created (e.g. using [crate::OdeSolverProblem::bdf_state]), which is more robust if the initial guess
+    /// of the algebraic variables is poor (default: false).
+    pub line_search_consistent_init: bool,
+}
+
+impl<T: Scalar> Default for SolverOptions<T> {
+    fn default() -> Self {
+        Self {
+            max_step: T::INFINITY,
+            min_step: None,
+            max_steps: None,
+            max_order: 5,
+            max_newton_iter: None,
+            jacobian_update_threshold: T::from(0.3),
+            rhs_jacobian_update_threshold: T::from(0.2),
+            jacobian_update_steps: 20,
+            rhs_jacobian_update_steps: 50,
+            constraints: None,
+            error_norm: None,
+            line_search_consistent_init: false,
+        }
+    }
+}
+
+impl<T: Scalar> SolverOptions<T> {
+    pub(crate) fn check(&self) -> Result<(), DiffsolError> {
+        let zero = T::from(0.0);
+        if self.max_step <= zero || Scalar::is_nan(self.max_step) {
+            return Err(ode_solver_error!(
+                BuilderError,
+                "Maximum step size must be positive"
+            ));
+        }
+        if let Some(min_step) = self.min_step {
+            if min_step < zero || min_step > self.max_step {
+                return Err(ode_solver_error!(
+                    BuilderError,
+                    "Minimum step size must be non-negative and no larger than the maximum step size"
+                ));
+            }
+        }
+        if self.max_order < 1 {
+            return Err(ode_solver_error!(
+                BuilderError,
+                "Maximum order must be at least 1"
+            ));
+        }
+        if self.max_newton_iter == Some(0) {
+            return Err(ode_solver_error!(
+                BuilderError,
+                "Maximum number of Newton iterations must be at least 1"
+            ));
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::SolverOptions;
+    use crate::OdeBuilder;
+
+    type M = nalgebra::DMatrix<f64>;
+
+    #[test]
+    fn test_solver_options_check() {
+        SolverOptions::<f64>::default().check().unwrap();
+        let invalid = [
+            SolverOptions {
+                max_step: 0.0,
+                ..Default::default()
+            },
+            SolverOptions {
+                max_step: f64::NAN,
+                ..Default::default()
+            },
+            SolverOptions {
+                max_step: 1.0,
+                min_step: Some(2.0),
+                ..Default::default()
+            },
+            SolverOptions {
+                max_order: 0,
+                ..Default::default()
+            },
+            SolverOptions {
+                max_newton_iter: Some(0),
+                ..Default::default()
+            },
+        ];
+        for options in invalid {
+            assert!(options.check().is_err(), "{:?}", options);
+        }
+
+        // invalid options are reported when the problem is built
+        let result = OdeBuilder::<M>::new()
+            .rhs(|x, _p, _t, y| y[0] = -x[0])
+            .init(|_p, _t| nalgebra::DVector::from_element(1, 1.0))
+            .solver_options(SolverOptions {
+                max_step: -1.0,
+                ..Default::default()
+            })
+            .build();
+        assert!(result.is_err());
+    }
+}
@@ -0,0 +1,387 @@
+use nalgebra::ComplexField;
+use std::marker::PhantomData;
+
+use num_traits::{One, Zero};
+
+use crate::{
+    error::{DiffsolError, OdeSolverError},
+    ode_solver_error, DefaultDenseMatrix, DenseMatrix, Matrix, NonLinearOp, OdeEquations,
+    OdeSolverMethod, OdeSolverState, OdeSolverStopReason, Op, Vector, VectorViewMut,
+};
+
+/// A parallel-in-time driver for the solution of an ODE using the Parareal algorithm \[1\].
+///
+/// The time interval `[t0, tf]` is split into `nslices` time slices of equal length. An initial guess for the solution at the start of each
+/// slice is found by integrating sequentially using a cheap `coarse` solver `G`. Each iteration then propagates the solution across every slice
+/// using the accurate `fine` solver `F`, with the slices solved in parallel on separate threads, and then updates the solution at the start of each slice
+/// with a sequential correction sweep using the coarse solver:
+///
+/// $$
+///  U_{n+1}^{k+1} = G(U_n^{k+1}) + F(U_n^k) - G(U_n^k)
+/// $$
+///
+/// The iteration has converged when the change in the solution at the start of every slice is less than the tolerances of the problem.
+/// After `k` iterations the first `k` slices are exact (i.e. they match the fine solver), so the iteration always terminates after at most `nslices`
+/// iterations, but any speedup requires convergence in many fewer iterations than this.
+///
+/// Both solvers can be any [OdeSolverMethod], and are used as templates: each propagation starts from a copy of the solver with its state replaced by the
+/// solution at the start of the slice, so the solvers passed to [Self::new] are not modified. The coarse solver will typically be the same method
+/// as the fine solver applied to a copy of the problem with looser tolerances, or a lower order method.
+///
+/// To run the fine solver on multiple threads it must be [Send], which requires the equations of the problem to be [Sync]. This is the case for problems created using
+/// [crate::OdeBuilder] with the default right-hand side, mass and output closures.
+///
+/// Restrictions:
+/// - Problems with a mass matrix are not supported, since the derivative at the start of each slice is found by evaluating the right-hand side.
+/// - Forward sensitivities, adjoints and integrating the output function are not supported.
+/// - The solution can only be integrated forwards in time, [Self::new] and [Self::solve_dense] return a [OdeSolverError::BackwardIntegrationNotSupported]
+///   error if either solver has a negative step size or the last time in `t_eval` is before the current time.
+///
+/// \[1\] Lions, J. L., Maday, Y., & Turinici, G. (2001). Résolution d'EDP par un schéma en temps «pararéel». Comptes Rendus de l'Académie des Sciences-Series I-Mathematics, 332(7), 661-668.
+pub struct Parareal<'a, Eqn, Coarse, Fine>
+where
+    Eqn: OdeEquations + 'a,
+    Coarse: OdeSolverMethod<'a, Eqn>,
+    Fine: OdeSolverMethod<'a, Eqn>,
+{
+    coarse: Coarse,
+    fine: Fine,
+    nslices: usize,
+    max_iter: usize,
+    niter: usize,
+    _phantom: PhantomData<&'a Eqn>,
+}
+
+impl<'a, Eqn, Coarse, Fine> Parareal<'a, Eqn, Coarse, Fine>
+where
+    Eqn: OdeEquations + 'a,
+    Coarse: OdeSolverMethod<'a, Eqn>,
+    Fine: OdeSolverMethod<'a, Eqn>,
+{
+    /// Create a new Parareal driver from a `coarse` and `fine` solver, which must have the same initial time, and the number of time slices `nslices`.
+    /// The solution is computed from the current state of the fine solver.
+    pub fn new(coarse: Coarse, fine: Fine, nslices: usize) -> Result<Self, DiffsolError> {
+        if nslices == 0 {
+            return Err(ode_solver_error!(
+                Other,
+                "Parareal requires at least one time slice"
+            ));
+        }
+        if coarse.state().t != fine.state().t {
+            return Err(ode_solver_error!(
+                Other,
+                "Parareal coarse and fine solvers must have the same initial time"
+            ));
+        }
+        for problem in [coarse.problem(), fine.problem()] {
+            if problem.eqn.mass().is_some() {
+                return Err(ode_solver_error!(
+                    Other,
+                    "Parareal does not support problems with a mass matrix"
+                ));
+            }
+            if problem.integrate_out {
+                return Err(ode_solver_error!(
+                    Other,
+                    "Parareal does not support integrating the output function"
+                ));
+            }
+        }
+        if coarse.state().h < Eqn::T::zero() || fine.state().h < Eqn::T::zero() {
+            return Err(ode_solver_error!(BackwardIntegrationNotSupported));
+        }
+        if !coarse.state().s.is_empty() || !fine.state().s.is_empty() {
+            return Err(ode_solver_error!(SensitivityNotSupported));
+        }
+        Ok(Self {
+            coarse,
+            fine,
+            nslices,
+            max_iter: nslices,
+            niter: 0,
+            _phantom: PhantomData,
+        })
+    }
+
+    pub fn coarse(&self) -> &Coarse {
+        &self.coarse
+    }
+
+    pub fn fine(&self) -> &Fine {
+        &self.fine
+    }
+
+    pub fn nslices(&self) -> usize {
+        self.nslices
+    }
+
+    /// Set the maximum number of iterations (default `nslices`). If the iteration has not converged after this many iterations then
+    /// [Self::solve_dense] returns an error.
+    pub fn set_max_iter(&mut self, max_iter: usize) {
+        self.max_iter = max_iter;
+    }
+
+    pub fn max_iter(&self) -> usize {
+        self.max_iter
+    }
+
+    /// Number of iterations taken by the last call to [Self::solve_dense]
+    pub fn niter(&self) -> usize {
+        self.niter
+    }
+
+    /// Solve the problem up to time `t_eval[t_eval.len()-1]`, starting from the current state of the fine solver.
+    /// Returns a dense matrix of solution values (or outputs if the problem has an output function) at timepoints given by `t_eval`,
+    /// as for [OdeSolverMethod::solve_dense]. Unlike [OdeSolverMethod::solve_dense], the times in `t_eval` must be increasing.
+    pub fn solve_dense(
+        &mut self,
+        t_eval: &[Eqn::T],
+    ) -> Result<<Eqn::V as DefaultDenseMatrix>::M, DiffsolError>
+    where
+        Eqn::V: DefaultDenseMatrix + Send + Sync,
+        Eqn::T: Send + Sync,
+        Fine: Send,
+    {
+        let problem = self.fine.problem();
+        let n = self.nslices;
+
+        // check t_eval is increasing and all values are greater than or equal to the current time
+        let t0 = self.fine.state().t;
+        if !t_eval.is_empty() && t_eval[t_eval.len() - 1] < t0 {
+            return Err(ode_solver_error!(BackwardIntegrationNotSupported));
+        }
+        if t_eval.is_empty()
+            || t_eval[0] < t0
+            || t_eval.windows(2).any(|w| w[0] > w[1])
+            || t_eval[t_eval.len() - 1] <= t0
+        {
+            return Err(ode_solver_error!(InvalidTEval));
+        }
+        let tf = t_eval[t_eval.len() - 1];
+
+        // time slices, and the times in t_eval that fall in [t_slices[i], t_slices[i + 1]) for each slice
+        let mut t_slices = (0..n)
+            .map(|i| t0 + (tf - t0) * Eqn::T::from(i as f64) / Eqn::T::from(n as f64))
+            .collect::<Vec<_>>();
+        t_slices.push(tf);
+        let slice_t_eval = t_slices
+            .windows(2)
+            .map(|w| {
+                let start = t_eval.partition_point(|&t| t < w[0]);
+                let end = t_eval.partition_point(|&t| t < w[1]);
+                &t_eval[start..end]
+            })
+            .collect::<Vec<_>>();
+
+        // initial guess using the coarse solver
+        let mut u = Vec::with_capacity(n + 1);
+        u.push(self.fine.state().y.clone());
+        let mut coarse_y = Vec::with_capacity(n);
+        for i in 0..n {
+            let (y, _) = propagate(&self.coarse, &u[i], t_slices[i], t_slices[i + 1], &[])?;
+            u.push(y.clone());
+            coarse_y.push(y);
+        }
+
+        let mut fine_out = vec![(Eqn::V::zeros(0), Vec::new()); n];
+        self.niter = 0;
+        loop {
+            // fine propagation of each slice in parallel, the first niter slices have already converged
+            let k = self.niter;
+            let results = std::thread::scope(|s| {
+                let handles = (k..n)
+                    .map(|i| {
+                        let fine = self.fine.clone();
+                        let (y, t_start, t_end, t_eval) =
+                            (&u[i], t_slices[i], t_slices[i + 1], slice_t_eval[i]);
+                        s.spawn(move || propagate(&fine, y, t_start, t_end, t_eval))
+                    })
+                    .collect::<Vec<_>>();
+                handles
+                    .into_iter()
+                    .map(|h| h.join().unwrap_or_else(|e| std::panic::resume_unwind(e)))
+                    .collect::<Vec<_>>()
+            });
+            for (i, result) in (k..n).zip(results) {
+                fine_out[i] = result?;
+            }
+            self.niter += 1;
+
+            // sequential correction using the coarse solver
+            let mut max_change = Eqn::T::zero();
+            for i in k..n {
+                let (g, _) = propagate(&self.coarse, &u[i], t_slices[i], t_slices[i + 1], &[])?;
+                let mut y = g.clone();
+                y.axpy(Eqn::T::one(), &fine_out[i].0, Eqn::T::one());
+                y.axpy(-Eqn::T::one(), &coarse_y[i], Eqn::T::one());
+                coarse_y[i] = g;
+
+                let mut change = y.clone();
+                change.axpy(-Eqn::T::one(), &u[i + 1], Eqn::T::one());
+                let change = change.squared_norm(&y, &problem.atol, problem.rtol).sqrt();
+                if change > max_change {
+                    max_change = change;
+                }
+                u[i + 1] = y;
+            }
+
+            if max_change < Eqn::T::one() || self.niter >= n {
+                break;
+            }
+            if self.niter >= self.max_iter {
+                return Err(ode_solver_error!(
+                    Other,
+                    format!(
+                        "Parareal did not converge after {} iterations",
+                        self.max_iter
+                    )
+                ));
+            }
+        }
+
+        // write out the solution
+        let nrows = match problem.eqn.out() {
+            Some(out) => out.nout(),
+            None => problem.eqn.rhs().nstates(),
+        };
+        let mut ret = <<Eqn::V as DefaultDenseMatrix>::M as Matrix>::zeros(nrows, t_eval.len());
+        let t_y = slice_t_eval
+            .iter()
+            .zip(fine_out.iter())
+            .flat_map(|(t, (_, ys))| t.iter().copied().zip(ys.iter()))
+            .chain(
+                t_eval[t_eval.partition_point(|&t| t < tf)..]
+                    .iter()
+                    .map(|&t| (t, &u[n])),
+            );
+        for (i, (t, y)) in t_y.enumerate() {
+            match problem.eqn.out() {
+                Some(out) => ret.column_mut(i).copy_from(&out.call(y, t)),
+                None => ret.column_mut(i).copy_from(y),
+            }
+        }
+        Ok(ret)
+    }
+}
+
+// propagate the solution `y` from time `t0` to time `t1` using a copy of `solver`, returning the solution at `t1`, and at each of the times in `t_eval`
+// (which must lie in `[t0, t1)`)
+fn propagate<'a, Eqn, Method>(
+    solver: &Method,
+    y: &Eqn::V,
+    t0: Eqn::T,
+    t1: Eqn::T,
+    t_eval: &[Eqn::T],
+) -> Result<(Eqn::V, Vec<Eqn::V>), DiffsolError>
+where
+    Eqn: OdeEquations + 'a,
+    Method: OdeSolverMethod<'a, Eqn>,
+{
+    let mut solver = solver.clone();
+    let problem = solver.problem();
+
+    // the step size of the solver was chosen for its own initial state, so recompute it for the new initial state
+    let mut state = solver.checkpoint();
+    {
+        let state = state.as_mut();
+        state.y.copy_from(y);
+        *state.t = t0;
+        problem.eqn.rhs().call_inplace(y, t0, state.dy);
+    }
+    state.set_step_size(problem, solver.order());
+    solver.set_state(state);
+
+    // the rest of the state (e.g. the differences of Bdf) still refers to the old solution, so mark it as modified so it is reinitialised from y and dy
+    solver.state_mut();
+
+    solver.set_stop_time(t1)?;
+    let mut ys = Vec::with_capacity(t_eval.len());
+    let mut step_reason = OdeSolverStopReason::InternalTimestep;
+    for &t in t_eval {
+        if t == t0 {
+            ys.push(y.clone());
+            continue;
+        }
+        while solver.state().t < t {
+            step_reason = solver.step()?;
+        }
+        ys.push(solver.interpolate(t)?);
+    }
+    while step_reason != OdeSolverStopReason::TstopReached {
+        step_reason = solver.step()?;
+    }
+    Ok((solver.state().y.clone(), ys))
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        error::{DiffsolError, OdeSolverError},
+        ode_solver::test_models::exponential_decay::exponential_decay_problem,
+        NalgebraLU, OdeSolverMethod, Parareal,
+    };
+
+    type M = nalgebra::DMatrix<f64>;
+    type LS = NalgebraLU<f64>;
+
+    #[test]
+    fn parareal_exponential_decay() {
+        let (problem, soln) = exponential_decay_problem::<M>(false);
+        let (mut coarse_problem, _soln) = exponential_decay_problem::<M>(false);
+        coarse_problem.rtol = 1e-2;
+        coarse_problem.atol.fill(1e-2);
+        let coarse = coarse_problem.bdf::<LS>().unwrap();
+        let fine = problem.bdf::<LS>().unwrap();
+        let mut parareal = Parareal::new(coarse, fine, 4).unwrap();
+
+        let t_eval = soln.solution_points.iter().map(|p| p.t).collect::<Vec<_>>();
+        let y = parareal.solve_dense(&t_eval).unwrap();
+        assert!(parareal.niter() >= 1 && parareal.niter() <= 4);
+        for (i, point) in soln.solution_points.iter().enumerate() {
+            let error = (y.column(i) - &point.state).norm();
+            assert!(error < 1e-4 * point.state.norm());
+        }
+
+        // the solution matches the fine solver to within the tolerances
+        let y_fine = problem.bdf::<LS>().unwrap().solve_dense(&t_eval).unwrap();
+        assert!((y - y_fine).norm() < 1e-4);
+    }
+
+    #[test]
+    fn parareal_coarse_equals_fine() {
+        // if the coarse solver is the same as the fine solver then the initial guess is already converged
+        let (problem, soln) = exponential_decay_problem::<M>(false);
+        let coarse = problem.bdf::<LS>().unwrap();
+        let fine = problem.bdf::<LS>().unwrap();
+        let mut parareal = Parareal::new(coarse, fine, 3).unwrap();
+        let t_eval = soln.solution_points.iter().map(|p| p.t).collect::<Vec<_>>();
+        parareal.solve_dense(&t_eval).unwrap();
+        assert_eq!(parareal.niter(), 1);
+    }
+
+    #[test]
+    fn parareal_errors() {
+        let (problem, _soln) = exponential_decay_problem::<M>(false);
+        let coarse = problem.bdf::<LS>().unwrap();
+        let fine = problem.bdf::<LS>().unwrap();
+        assert!(Parareal::new(coarse.clone(), fine.clone(), 0).is_err());
+        let mut parareal = Parareal::new(coarse, fine, 2).unwrap();
+        assert!(parareal.solve_dense(&[]).is_err());
+        assert!(parareal.solve_dense(&[0.0]).is_err());
+        assert!(parareal.solve_dense(&[2.0, 1.0]).is_err());
+
+        // only forwards integration is supported
+        let err = parareal.solve_dense(&[-1.0]).unwrap_err();
+        assert!(
+            matches!(
+                err,
+                DiffsolError::OdeSolverError(OdeSolverError::BackwardIntegrationNotSupported)
+            ),
+            "{}",
+            err
+        );
+        let mut backwards = problem.bdf::<LS>().unwrap();
+        backwards.reverse_direction().unwrap();
+        assert!(Parareal::new(backwards.clone(), backwards, 2).is_err());
+    }
+}
@@ -1,8 +1,15 @@
 use crate::{
-    error::DiffsolError, vector::Vector, AugmentedOdeEquationsImplicit, Bdf, BdfState,
-    DefaultDenseMatrix, DenseMatrix, LinearSolver, MatrixRef, NewtonNonlinearSolver, OdeEquations,
-    OdeEquationsImplicit, OdeEquationsSens, OdeSolverState, Sdirk, SdirkState, SensEquations,
-    Tableau, VectorRef,
+    error::{DiffsolError, OdeSolverError},
+    ode_solver_error,
+    vector::Vector,
+    Adams, AugmentedOdeEquations, AugmentedOdeEquationsImplicit, Bdf, BdfState, Dde,
+    DefaultDenseMatrix, DenseMatrix, ExplicitRk, ExponentialRosenbrock,
+    ExponentialRosenbrockTableau, GeneralizedAlpha, GeneralizedAlphaParameters, ImexArk,
+    LinearSolver, Lsoda, ManifoldProjection, MatrixRef, NewtonNonlinearSolver, NonLinearOp,
+    OdeEquations, OdeEquationsDelay, OdeEquationsImex, OdeEquationsImplicit,
+    OdeEquationsSecondOrder, OdeEquationsSens, OdeSolverMethod, OdeSolverState, Op, Radau,
+    Rosenbrock, RosenbrockTableau, Sdirk, SdirkState, SensEquations, SolverOptions, Symplectic,
+    SymplecticTableau, Tableau, VectorRef,
 };
 
 pub struct OdeSolverProblem<Eqn>
@@ -21,6 +28,8 @@ where
     pub out_atol: Option<Eqn::V>,
     pub param_rtol: Option<Eqn::T>,
     pub param_atol: Option<Eqn::V>,
+    pub options: SolverOptions<Eqn::T>,
+    pub projection: Option<ManifoldProjection<'static, Eqn::M>>,
 }
 
 macro_rules! sdirk_solver_from_tableau {
@@ -97,6 +106,175 @@ macro_rules! sdirk_solver_from_tableau {
     };
 }
 
+macro_rules! explicit_rk_solver_from_tableau {
+    ($state:ident, $state_sens:ident, $method:ident, $method_sens:ident, $method_solver:ident, $method_solver_sens:ident, $tableau:ident) => {
+        pub fn $state(&self) -> Result<SdirkState<Eqn::V>, DiffsolError> {
+            self.explicit_rk_state(&Tableau::<<Eqn::V as DefaultDenseMatrix>::M>::$tableau())
+        }
+
+        pub fn $state_sens(&self) -> Result<SdirkState<Eqn::V>, DiffsolError>
+        where
+            Eqn: OdeEquationsSens,
+        {
+            self.explicit_rk_state_sens(&Tableau::<<Eqn::V as DefaultDenseMatrix>::M>::$tableau())
+        }
+
+        pub fn $method_solver(
+            &self,
+            state: SdirkState<Eqn::V>,
+        ) -> Result<ExplicitRk<'_, Eqn>, DiffsolError> {
+            self.explicit_rk_solver(
+                state,
+                Tableau::<<Eqn::V as DefaultDenseMatrix>::M>::$tableau(),
+            )
+        }
+
+        pub fn $method_solver_sens(
+            &self,
+            state: SdirkState<Eqn::V>,
+        ) -> Result<
+            ExplicitRk<'_, Eqn, <Eqn::V as DefaultDenseMatrix>::M, SensEquations<Eqn>>,
+            DiffsolError,
+        >
+        where
+            Eqn: OdeEquationsSens,
+        {
+            self.explicit_rk_solver_sens(
+                state,
+                Tableau::<<Eqn::V as DefaultDenseMatrix>::M>::$tableau(),
+            )
+        }
+
+        pub fn $method(&self) -> Result<ExplicitRk<'_, Eqn>, DiffsolError> {
+            let state = self.$state()?;
+            self.$method_solver(state)
+        }
+
+        pub fn $method_sens(
+            &self,
+        ) -> Result<
+            ExplicitRk<'_, Eqn, <Eqn::V as DefaultDenseMatrix>::M, SensEquations<Eqn>>,
+            DiffsolError,
+        >
+        where
+            Eqn: OdeEquationsSens,
+        {
+            let state = self.$state_sens()?;
+            self.$method_solver_sens(state)
+        }
+    };
+}
+
+macro_rules! rosenbrock_solver_from_tableau {
+    ($state:ident, $state_sens:ident, $method:ident, $method_sens:ident, $method_solver:ident, $method_solver_sens:ident, $tableau:ident) => {
+        pub fn $state<LS: LinearSolver<Eqn::M>>(&self) -> Result<SdirkState<Eqn::V>, DiffsolError>
+        where
+            Eqn: OdeEquationsImplicit,
+        {
+            self.rosenbrock_state::<LS, _>(
+                &RosenbrockTableau::<<Eqn::V as DefaultDenseMatrix>::M>::$tableau(),
+            )
+        }
+
+        pub fn $state_sens<LS: LinearSolver<Eqn::M>>(
+            &self,
+        ) -> Result<SdirkState<Eqn::V>, DiffsolError>
+        where
+            Eqn: OdeEquationsSens,
+        {
+            self.rosenbrock_state_sens::<LS, _>(&RosenbrockTableau::<
+                <Eqn::V as DefaultDenseMatrix>::M,
+            >::$tableau())
+        }
+
+        pub fn $method_solver<LS: LinearSolver<Eqn::M>>(
+            &self,
+            state: SdirkState<Eqn::V>,
+        ) -> Result<Rosenbrock<'_, Eqn, LS>, DiffsolError>
+        where
+            Eqn: OdeEquationsImplicit,
+        {
+            self.rosenbrock_solver(
+                state,
+                RosenbrockTableau::<<Eqn::V as DefaultDenseMatrix>::M>::$tableau(),
+            )
+        }
+
+        pub fn $method_solver_sens<LS: LinearSolver<Eqn::M>>(
+            &self,
+            state: SdirkState<Eqn::V>,
+        ) -> Result<
+            Rosenbrock<'_, Eqn, LS, <Eqn::V as DefaultDenseMatrix>::M, SensEquations<Eqn>>,
+            DiffsolError,
+        >
+        where
+            Eqn: OdeEquationsSens,
+        {
+            self.rosenbrock_solver_sens(
+                state,
+                RosenbrockTableau::<<Eqn::V as DefaultDenseMatrix>::M>::$tableau(),
+            )
+        }
+
+        pub fn $method<LS: LinearSolver<Eqn::M>>(
+            &self,
+        ) -> Result<Rosenbrock<'_, Eqn, LS>, DiffsolError>
+        where
+            Eqn: OdeEquationsImplicit,
+        {
+            let state = self.$state::<LS>()?;
+            self.$method_solver::<LS>(state)
+        }
+
+        pub fn $method_sens<LS: LinearSolver<Eqn::M>>(
+            &self,
+        ) -> Result<
+            Rosenbrock<'_, Eqn, LS, <Eqn::V as DefaultDenseMatrix>::M, SensEquations<Eqn>>,
+            DiffsolError,
+        >
+        where
+            Eqn: OdeEquationsSens,
+        {
+            let state = self.$state_sens::<LS>()?;
+            self.$method_solver_sens::<LS>(state)
+        }
+    };
+}
+
+macro_rules! exponential_rosenbrock_solver_from_tableau {
+    ($state:ident, $method:ident, $method_solver:ident, $tableau:ident) => {
+        pub fn $state(&self) -> Result<SdirkState<Eqn::V>, DiffsolError>
+        where
+            Eqn: OdeEquationsImplicit,
+        {
+            self.exponential_rosenbrock_state(&ExponentialRosenbrockTableau::<
+                <Eqn::V as DefaultDenseMatrix>::M,
+            >::$tableau())
+        }
+
+        pub fn $method_solver(
+            &self,
+            state: SdirkState<Eqn::V>,
+        ) -> Result<ExponentialRosenbrock<'_, Eqn>, DiffsolError>
+        where
+            Eqn: OdeEquationsImplicit,
+        {
+            self.exponential_rosenbrock_solver(
+                state,
+                ExponentialRosenbrockTableau::<<Eqn::V as DefaultDenseMatrix>::M>::$tableau(),
+            )
+        }
+
+        pub fn $method(&self) -> Result<ExponentialRosenbrock<'_, Eqn>, DiffsolError>
+        where
+            Eqn: OdeEquationsImplicit,
+        {
+            let state = self.$state()?;
+            self.$method_solver(state)
+        }
+    };
+}
+
 impl<Eqn> OdeSolverProblem<Eqn>
 where
     Eqn: OdeEquations,
@@ -141,9 +319,48 @@ where
             t0,
             h0,
             integrate_out,
+            options: SolverOptions::default(),
+            projection: None,
         })
     }
 
+    /// Set the options used by the solvers (see [SolverOptions]), returning an error if the options are invalid or
+    /// do not match the number of states of the equations.
+    pub fn with_options(mut self, options: SolverOptions<Eqn::T>) -> Result<Self, DiffsolError> {
+        options.check()?;
+        let nstates = self.eqn.rhs().nstates();
+        if let Some(constraints) = options.constraints.as_ref() {
+            if constraints.len() != nstates {
+                return Err(ode_solver_error!(
+                    BuilderError,
+                    format!(
+                        "Number of constraints ({}) must be equal to the number of states ({})",
+                        constraints.len(),
+                        nstates
+                    )
+                ));
+            }
+        }
+        if let Some(error_norm) = options.error_norm.as_ref() {
+            error_norm.check(nstates)?;
+        }
+        self.options = options;
+        Ok(self)
+    }
+
+    /// Set the projection of the solution onto the manifold of a set of invariants after each step of the [crate::Bdf] and [crate::Sdirk] solvers
+    /// (see [ManifoldProjection]), returning an error if the invariants do not match the number of states of the equations.
+    pub fn with_projection(
+        mut self,
+        projection: Option<ManifoldProjection<'static, Eqn::M>>,
+    ) -> Result<Self, DiffsolError> {
+        if let Some(projection) = projection.as_ref() {
+            projection.check(self.eqn.rhs().nstates())?;
+        }
+        self.projection = projection;
+        Ok(self)
+    }
+
     pub fn eqn(&self) -> &Eqn {
         &self.eqn
     }
@@ -314,6 +531,372 @@ where
         self.sdirk_solver_aug::<LS, DM, _>(state, tableau, sens_eqn)
     }
 
+    pub fn explicit_rk_state<DM: DenseMatrix>(
+        &self,
+        tableau: &Tableau<DM>,
+    ) -> Result<SdirkState<Eqn::V>, DiffsolError> {
+        SdirkState::new_explicit(self, tableau.order())
+    }
+
+    pub fn explicit_rk_state_sens<DM: DenseMatrix>(
+        &self,
+        tableau: &Tableau<DM>,
+    ) -> Result<SdirkState<Eqn::V>, DiffsolError>
+    where
+        Eqn: OdeEquationsSens,
+    {
+        SdirkState::new_explicit_with_sensitivities(self, tableau.order())
+    }
+
+    pub fn explicit_rk_solver<DM: DenseMatrix<V = Eqn::V, T = Eqn::T>>(
+        &self,
+        state: SdirkState<Eqn::V>,
+        tableau: Tableau<DM>,
+    ) -> Result<ExplicitRk<'_, Eqn, DM>, DiffsolError> {
+        ExplicitRk::new(self, state, tableau)
+    }
+
+    pub(crate) fn explicit_rk_solver_aug<
+        DM: DenseMatrix<V = Eqn::V, T = Eqn::T>,
+        Aug: AugmentedOdeEquations<Eqn>,
+    >(
+        &self,
+        state: SdirkState<Eqn::V>,
+        tableau: Tableau<DM>,
+        aug_eqn: Aug,
+    ) -> Result<ExplicitRk<'_, Eqn, DM, Aug>, DiffsolError> {
+        ExplicitRk::new_augmented(self, state, tableau, aug_eqn)
+    }
+
+    pub fn explicit_rk_solver_sens<DM: DenseMatrix<V = Eqn::V, T = Eqn::T>>(
+        &self,
+        state: SdirkState<Eqn::V>,
+        tableau: Tableau<DM>,
+    ) -> Result<ExplicitRk<'_, Eqn, DM, SensEquations<Eqn>>, DiffsolError>
+    where
+        Eqn: OdeEquationsSens,
+    {
+        let sens_eqn = SensEquations::new(self);
+        self.explicit_rk_solver_aug::<DM, _>(state, tableau, sens_eqn)
+    }
+
+    pub fn rosenbrock_state<LS: LinearSolver<Eqn::M>, DM: DenseMatrix>(
+        &self,
+        tableau: &RosenbrockTableau<DM>,
+    ) -> Result<SdirkState<Eqn::V>, DiffsolError>
+    where
+        Eqn: OdeEquationsImplicit,
+    {
+        SdirkState::new::<LS, _>(self, tableau.order())
+    }
+
+    pub fn rosenbrock_state_sens<LS: LinearSolver<Eqn::M>, DM: DenseMatrix>(
+        &self,
+        tableau: &RosenbrockTableau<DM>,
+    ) -> Result<SdirkState<Eqn::V>, DiffsolError>
+    where
+        Eqn: OdeEquationsSens,
+    {
+        SdirkState::new_with_sensitivities::<LS, _>(self, tableau.order())
+    }
+
+    pub fn rosenbrock_solver<LS: LinearSolver<Eqn::M>, DM: DenseMatrix<V = Eqn::V, T = Eqn::T>>(
+        &self,
+        state: SdirkState<Eqn::V>,
+        tableau: RosenbrockTableau<DM>,
+    ) -> Result<Rosenbrock<'_, Eqn, LS, DM>, DiffsolError>
+    where
+        Eqn: OdeEquationsImplicit,
+    {
+        Rosenbrock::new(self, state, tableau, LS::default())
+    }
+
+    pub(crate) fn rosenbrock_solver_aug<
+        LS: LinearSolver<Eqn::M>,
+        DM: DenseMatrix<V = Eqn::V, T = Eqn::T>,
+        Aug: AugmentedOdeEquations<Eqn>,
+    >(
+        &self,
+        state: SdirkState<Eqn::V>,
+        tableau: RosenbrockTableau<DM>,
+        aug_eqn: Aug,
+    ) -> Result<Rosenbrock<'_, Eqn, LS, DM, Aug>, DiffsolError>
+    where
+        Eqn: OdeEquationsImplicit,
+    {
+        Rosenbrock::new_augmented(self, state, tableau, LS::default(), aug_eqn)
+    }
+
+    pub fn rosenbrock_solver_sens<
+        LS: LinearSolver<Eqn::M>,
+        DM: DenseMatrix<V = Eqn::V, T = Eqn::T>,
+    >(
+        &self,
+        state: SdirkState<Eqn::V>,
+        tableau: RosenbrockTableau<DM>,
+    ) -> Result<Rosenbrock<'_, Eqn, LS, DM, SensEquations<Eqn>>, DiffsolError>
+    where
+        Eqn: OdeEquationsSens,
+    {
+        let sens_eqn = SensEquations::new(self);
+        self.rosenbrock_solver_aug::<LS, DM, _>(state, tableau, sens_eqn)
+    }
+
+    pub fn radau_state<LS: LinearSolver<Eqn::M>>(&self) -> Result<SdirkState<Eqn::V>, DiffsolError>
+    where
+        Eqn: OdeEquationsImplicit,
+    {
+        SdirkState::new::<LS, _>(self, 5)
+    }
+
+    pub fn radau_solver<LS: LinearSolver<Eqn::M>>(
+        &self,
+        state: SdirkState<Eqn::V>,
+    ) -> Result<Radau<'_, Eqn, LS>, DiffsolError>
+    where
+        Eqn: OdeEquationsImplicit,
+    {
+        Radau::new(self, state, LS::default())
+    }
+
+    pub fn radau<LS: LinearSolver<Eqn::M>>(&self) -> Result<Radau<'_, Eqn, LS>, DiffsolError>
+    where
+        Eqn: OdeEquationsImplicit,
+    {
+        let state = self.radau_state::<LS>()?;
+        self.radau_solver(state)
+    }
+
+    pub fn adams_state(&self) -> Result<BdfState<Eqn::V>, DiffsolError> {
+        BdfState::new_explicit(self, 1)
+    }
+
+    pub fn adams_solver(&self, state: BdfState<Eqn::V>) -> Result<Adams<'_, Eqn>, DiffsolError> {
+        Adams::new(self, state)
+    }
+
+    pub fn adams(&self) -> Result<Adams<'_, Eqn>, DiffsolError> {
+        let state = self.adams_state()?;
+        self.adams_solver(state)
+    }
+
+    pub fn lsoda_solver<LS: LinearSolver<Eqn::M>>(
+        &self,
+        state: BdfState<Eqn::V>,
+    ) -> Result<Lsoda<'_, Eqn, LS>, DiffsolError>
+    where
+        Eqn: OdeEquationsImplicit,
+    {
+        Lsoda::new(self, state, LS::default())
+    }
+
+    pub fn lsoda<LS: LinearSolver<Eqn::M>>(&self) -> Result<Lsoda<'_, Eqn, LS>, DiffsolError>
+    where
+        Eqn: OdeEquationsImplicit,
+    {
+        let state = self.adams_state()?;
+        self.lsoda_solver(state)
+    }
+
+    pub fn exponential_rosenbrock_state<DM: DenseMatrix>(
+        &self,
+        tableau: &ExponentialRosenbrockTableau<DM>,
+    ) -> Result<SdirkState<Eqn::V>, DiffsolError>
+    where
+        Eqn: OdeEquationsImplicit,
+    {
+        SdirkState::new_explicit(self, tableau.order())
+    }
+
+    pub fn exponential_rosenbrock_solver<DM: DenseMatrix<V = Eqn::V, T = Eqn::T>>(
+        &self,
+        state: SdirkState<Eqn::V>,
+        tableau: ExponentialRosenbrockTableau<DM>,
+    ) -> Result<ExponentialRosenbrock<'_, Eqn, DM>, DiffsolError>
+    where
+        Eqn: OdeEquationsImplicit,
+    {
+        ExponentialRosenbrock::new(self, state, tableau)
+    }
+
+    pub fn imex_ark_state<DM: DenseMatrix>(
+        &self,
+        tableau: &Tableau<DM>,
+    ) -> Result<SdirkState<Eqn::V>, DiffsolError>
+    where
+        Eqn: OdeEquationsImex,
+    {
+        SdirkState::new_explicit(self, tableau.order())
+    }
+
+    pub fn imex_ark_solver<LS: LinearSolver<Eqn::M>, DM: DenseMatrix<V = Eqn::V, T = Eqn::T>>(
+        &self,
+        state: SdirkState<Eqn::V>,
+        explicit_tableau: Tableau<DM>,
+        implicit_tableau: Tableau<DM>,
+    ) -> Result<ImexArk<'_, Eqn, LS, DM>, DiffsolError>
+    where
+        Eqn: OdeEquationsImex,
+    {
+        ImexArk::new(
+            self,
+            state,
+            explicit_tableau,
+            implicit_tableau,
+            LS::default(),
+        )
+    }
+
+    pub fn ark436l2sa_state(&self) -> Result<SdirkState<Eqn::V>, DiffsolError>
+    where
+        Eqn: OdeEquationsImex,
+    {
+        let tableau = Tableau::<<Eqn::V as DefaultDenseMatrix>::M>::ark436l2sa_implicit();
+        self.imex_ark_state(&tableau)
+    }
+
+    pub fn ark436l2sa_solver<LS: LinearSolver<Eqn::M>>(
+        &self,
+        state: SdirkState<Eqn::V>,
+    ) -> Result<ImexArk<'_, Eqn, LS>, DiffsolError>
+    where
+        Eqn: OdeEquationsImex,
+    {
+        self.imex_ark_solver(
+            state,
+            Tableau::ark436l2sa_explicit(),
+            Tableau::ark436l2sa_implicit(),
+        )
+    }
+
+    pub fn ark436l2sa<LS: LinearSolver<Eqn::M>>(&self) -> Result<ImexArk<'_, Eqn, LS>, DiffsolError>
+    where
+        Eqn: OdeEquationsImex,
+    {
+        let state = self.ark436l2sa_state()?;
+        self.ark436l2sa_solver(state)
+    }
+
+    /// Create a new state for the [Symplectic] solvers. The step size of these solvers is fixed, and is set to the initial step size of the problem (see [crate::OdeBuilder::h0]).
+    pub fn symplectic_state(&self) -> Result<SdirkState<Eqn::V>, DiffsolError>
+    where
+        Eqn: OdeEquationsSecondOrder,
+    {
+        let mut state = SdirkState::new_without_initialise(self)?;
+        state.set_consistent_explicit(self)?;
+        Ok(state)
+    }
+
+    pub fn symplectic_solver(
+        &self,
+        state: SdirkState<Eqn::V>,
+        tableau: SymplecticTableau<Eqn::V>,
+    ) -> Result<Symplectic<'_, Eqn>, DiffsolError>
+    where
+        Eqn: OdeEquationsSecondOrder,
+    {
+        Symplectic::new(self, state, tableau)
+    }
+
+    /// Create a [Symplectic] solver using the velocity Verlet method, see [SymplecticTableau::velocity_verlet].
+    pub fn velocity_verlet(&self) -> Result<Symplectic<'_, Eqn>, DiffsolError>
+    where
+        Eqn: OdeEquationsSecondOrder,
+    {
+        let state = self.symplectic_state()?;
+        self.symplectic_solver(state, SymplecticTableau::velocity_verlet())
+    }
+
+    /// Create a [Symplectic] solver using the fourth-order method of Yoshida, see [SymplecticTableau::yoshida4].
+    pub fn yoshida4(&self) -> Result<Symplectic<'_, Eqn>, DiffsolError>
+    where
+        Eqn: OdeEquationsSecondOrder,
+    {
+        let state = self.symplectic_state()?;
+        self.symplectic_solver(state, SymplecticTableau::yoshida4())
+    }
+
+    /// Create a new state for the [GeneralizedAlpha] solver. The acceleration in the time derivative of the state is calculated when the solver is created.
+    pub fn generalized_alpha_state(&self) -> Result<SdirkState<Eqn::V>, DiffsolError>
+    where
+        Eqn: OdeEquationsSecondOrder,
+    {
+        let mut state = SdirkState::new_without_initialise(self)?;
+        {
+            let state = state.as_mut();
+            self.eqn.rhs().call_inplace(state.y, *state.t, state.dy);
+        }
+        state.set_step_size(self, 2);
+        Ok(state)
+    }
+
+    pub fn generalized_alpha_solver<LS: LinearSolver<Eqn::M>>(
+        &self,
+        state: SdirkState<Eqn::V>,
+        params: GeneralizedAlphaParameters<Eqn::T>,
+    ) -> Result<GeneralizedAlpha<'_, Eqn, LS>, DiffsolError>
+    where
+        Eqn: OdeEquationsSecondOrder,
+    {
+        GeneralizedAlpha::new(self, state, params, LS::default())
+    }
+
+    /// Create a [GeneralizedAlpha] solver using the Newmark average acceleration method, see [GeneralizedAlphaParameters::newmark].
+    pub fn newmark<LS: LinearSolver<Eqn::M>>(
+        &self,
+    ) -> Result<GeneralizedAlpha<'_, Eqn, LS>, DiffsolError>
+    where
+        Eqn: OdeEquationsSecondOrder,
+    {
+        let state = self.generalized_alpha_state()?;
+        self.generalized_alpha_solver(state, GeneralizedAlphaParameters::newmark())
+    }
+
+    /// Create a [GeneralizedAlpha] solver with the spectral radius `rho_inf` at infinite frequency, see [GeneralizedAlphaParameters::chung_hulbert].
+    pub fn generalized_alpha<LS: LinearSolver<Eqn::M>>(
+        &self,
+        rho_inf: Eqn::T,
+    ) -> Result<GeneralizedAlpha<'_, Eqn, LS>, DiffsolError>
+    where
+        Eqn: OdeEquationsSecondOrder,
+    {
+        let state = self.generalized_alpha_state()?;
+        self.generalized_alpha_solver(state, GeneralizedAlphaParameters::chung_hulbert(rho_inf))
+    }
+
+    /// Create a [Dde] solver for delay differential equations that uses the given solver to take each step.
+    pub fn dde_solver<'a, Method>(
+        &'a self,
+        solver: Method,
+    ) -> Result<Dde<'a, Eqn, Method>, DiffsolError>
+    where
+        Eqn: OdeEquationsDelay,
+        Method: OdeSolverMethod<'a, Eqn>,
+    {
+        Dde::new(solver)
+    }
+
+    /// Create a [Dde] solver for delay differential equations that uses the [Bdf] solver to take each step.
+    #[allow(clippy::type_complexity)]
+    pub fn dde_bdf<LS: LinearSolver<Eqn::M>>(
+        &self,
+    ) -> Result<Dde<'_, Eqn, Bdf<'_, Eqn, NewtonNonlinearSolver<Eqn::M, LS>>>, DiffsolError>
+    where
+        Eqn: OdeEquationsDelay + OdeEquationsImplicit,
+    {
+        self.dde_solver(self.bdf::<LS>()?)
+    }
+
+    /// Create a [Dde] solver for delay differential equations that uses the [Sdirk] solver with the TR-BDF2 tableau to take each step.
+    pub fn dde_tr_bdf2<LS: LinearSolver<Eqn::M>>(
+        &self,
+    ) -> Result<Dde<'_, Eqn, Sdirk<'_, Eqn, LS>>, DiffsolError>
+    where
+        Eqn: OdeEquationsDelay + OdeEquationsImplicit,
+    {
+        self.dde_solver(self.tr_bdf2::<LS>()?)
+    }
+
     sdirk_solver_from_tableau!(
         tr_bdf2_state,
         tr_bdf2_state_sens,
@@ -332,6 +915,89 @@ where
         esdirk34_solver_sens,
         esdirk34
     );
+    explicit_rk_solver_from_tableau!(
+        dopri5_state,
+        dopri5_state_sens,
+        dopri5,
+        dopri5_sens,
+        dopri5_solver,
+        dopri5_solver_sens,
+        dopri5
+    );
+    explicit_rk_solver_from_tableau!(
+        tsit45_state,
+        tsit45_state_sens,
+        tsit45,
+        tsit45_sens,
+        tsit45_solver,
+        tsit45_solver_sens,
+        tsit45
+    );
+    explicit_rk_solver_from_tableau!(
+        vern56_state,
+        vern56_state_sens,
+        vern56,
+        vern56_sens,
+        vern56_solver,
+        vern56_solver_sens,
+        vern56
+    );
+    explicit_rk_solver_from_tableau!(
+        vern67_state,
+        vern67_state_sens,
+        vern67,
+        vern67_sens,
+        vern67_solver,
+        vern67_solver_sens,
+        vern67
+    );
+    explicit_rk_solver_from_tableau!(
+        vern78_state,
+        vern78_state_sens,
+        vern78,
+        vern78_sens,
+        vern78_solver,
+        vern78_solver_sens,
+        vern78
+    );
+    explicit_rk_solver_from_tableau!(
+        vern89_state,
+        vern89_state_sens,
+        vern89,
+        vern89_sens,
+        vern89_solver,
+        vern89_solver_sens,
+        vern89
+    );
+    rosenbrock_solver_from_tableau!(
+        rodas4_state,
+        rodas4_state_sens,
+        rodas4,
+        rodas4_sens,
+        rodas4_solver,
+        rodas4_solver_sens,
+        rodas4
+    );
+    rosenbrock_solver_from_tableau!(
+        rodas5_state,
+        rodas5_state_sens,
+        rodas5,
+        rodas5_sens,
+        rodas5_solver,
+        rodas5_solver_sens,
+        rodas5
+    );
+    rosenbrock_solver_from_tableau!(
+        rodas5p_state,
+        rodas5p_state_sens,
+        rodas5p,
+        rodas5p_sens,
+        rodas5p_solver,
+        rodas5p_solver_sens,
+        rodas5p
+    );
+    exponential_rosenbrock_solver_from_tableau!(exprb32_state, exprb32, exprb32_solver, exprb32);
+    exponential_rosenbrock_solver_from_tableau!(exprb43_state, exprb43, exprb43_solver, exprb43);
 }
 
 #[derive(Debug, Clone)]
@@ -0,0 +1,292 @@
+use std::{ops::SubAssign, sync::Arc};
+
+use num_traits::{One, Zero};
+
+use crate::{
+    error::{DiffsolError, OdeSolverError},
+    ode_solver_error, Convergence, ConvergenceStatus, DefaultDenseMatrix, DefaultSolver,
+    DenseMatrix, ErrorNorm, LinearSolver, Matrix, MatrixCommon, NonLinearOp, NonLinearOpJacobian,
+    Op, Vector,
+};
+
+/// Projection of the solution onto the manifold defined by a set of invariants `g(y) = 0` (see [crate::OdeBuilder::projection],
+/// [crate::Bdf::set_projection] and [crate::Sdirk::set_projection]).
+///
+/// Many models conserve quantities such as total mass or energy, which slowly drift during long integrations. After each accepted step
+/// the solver replaces the solution `y` by the closest point on the manifold, using a simplified Newton iteration `y <- y - G^T (G G^T)^{-1} g(y)`,
+/// where `G` is the Jacobian of `g` at the unprojected solution. The small `m x m` matrix `G G^T` is factorised once using the default
+/// [LinearSolver] of the dense matrix type (e.g. [crate::NalgebraLU] or [crate::FaerLU]). The iteration has its own [Convergence] test, using the
+/// tolerances and error norm of the problem, and stops when the update is small compared with the tolerances.
+///
+/// The invariants are given by an operator with `nstates` inputs (the state) and `nout` outputs (one for each invariant), and `G` is computed using
+/// [NonLinearOpJacobian::jac_mul_inplace], and must have full row rank. The operator must be [Send] and [Sync] so that the solver can be sent between threads. Only the state is projected, not the integrated outputs or the sensitivities.
+/// If the projection fails then the step is rejected and retried with a smaller step size (or in fixed-step mode, the error is returned).
+#[derive(Clone)]
+pub struct ManifoldProjection<'a, M: Matrix> {
+    invariants: Arc<dyn NonLinearOpJacobian<T = M::T, V = M::V, M = M> + Send + Sync + 'a>,
+    project: ProjectFn<M>,
+}
+
+// the projection, instantiated with the dense matrix type used to solve the normal equations (see [ManifoldProjection::new])
+type ProjectFn<M> = fn(
+    &ManifoldProjection<'_, M>,
+    &mut <M as MatrixCommon>::V,
+    <M as MatrixCommon>::T,
+    &mut Convergence<'_, <M as MatrixCommon>::V>,
+) -> Result<(), DiffsolError>;
+
+impl<'a, M: Matrix> ManifoldProjection<'a, M> {
+    /// Create a new projection onto the manifold `g(y) = 0`, where `g` is given by `invariants`.
+    pub fn new(
+        invariants: impl NonLinearOpJacobian<T = M::T, V = M::V, M = M> + Send + Sync + 'a,
+    ) -> Self
+    where
+        M::V: DefaultDenseMatrix,
+        <M::V as DefaultDenseMatrix>::M: DefaultSolver,
+    {
+        Self {
+            invariants: Arc::new(invariants),
+            project: Self::project_dense::<<M::V as DefaultDenseMatrix>::M>,
+        }
+    }
+
+    /// Create a new projection onto the manifold `g(y) = 0` of a system with `nstates` states, where the `ninvariants` invariants are given by closures.
+    ///
+    /// # Arguments
+    ///
+    /// - `invariants`: Function of type Fn(x: &V, t: S, g: &mut V) that computes the invariants `g(x)`.
+    /// - `invariants_jac`: Function of type Fn(x: &V, t: S, v: &V, y: &mut V) that computes the multiplication of the Jacobian of the invariants with the vector v.
+    pub fn from_closures<F, G>(
+        invariants: F,
+        invariants_jac: G,
+        nstates: usize,
+        ninvariants: usize,
+    ) -> Self
+    where
+        M::V: DefaultDenseMatrix,
+        <M::V as DefaultDenseMatrix>::M: DefaultSolver,
+        F: Fn(&M::V, M::T, &mut M::V) + Send + Sync + 'a,
+        G: Fn(&M::V, M::T, &M::V, &mut M::V) + Send + Sync + 'a,
+    {
+        Self::new(InvariantsClosure::<M, F, G> {
+            func: invariants,
+            jacobian_action: invariants_jac,
+            nstates,
+            nout: ninvariants,
+            _phantom: std::marker::PhantomData,
+        })
+    }
+
+    /// Return the number of invariants.
+    pub fn ninvariants(&self) -> usize {
+        self.invariants.nout()
+    }
+
+    pub(crate) fn check(&self, nstates: usize) -> Result<(), DiffsolError> {
+        let m = self.ninvariants();
+        if self.invariants.nstates() != nstates || m == 0 || m > nstates {
+            return Err(ode_solver_error!(
+                Other,
+                format!(
+                    "Invariants must have {} inputs and between 1 and {} outputs, but have {} inputs and {} outputs",
+                    nstates,
+                    nstates,
+                    self.invariants.nstates(),
+                    m
+                )
+            ));
+        }
+        Ok(())
+    }
+
+    /// Project `y` at time `t` onto the manifold, using the tolerances `rtol` and `atol` and the norm `error_norm` (see [crate::SolverOptions::error_norm])
+    /// to decide when the Newton iteration has converged.
+    pub(crate) fn project(
+        &self,
+        y: &mut M::V,
+        t: M::T,
+        rtol: M::T,
+        atol: &M::V,
+        error_norm: Option<Arc<dyn ErrorNorm<M::T>>>,
+    ) -> Result<(), DiffsolError> {
+        let mut convergence = Convergence::new(rtol, atol);
+        convergence.set_error_norm(error_norm);
+        (self.project)(self, y, t, &mut convergence)
+    }
+
+    fn project_dense<Md>(
+        projection: &ManifoldProjection<'_, M>,
+        y: &mut M::V,
+        t: M::T,
+        convergence: &mut Convergence<'_, M::V>,
+    ) -> Result<(), DiffsolError>
+    where
+        Md: DenseMatrix<T = M::T, V = M::V> + DefaultSolver,
+    {
+        let invariants = &projection.invariants;
+        let n = y.len();
+        let m = projection.ninvariants();
+
+        // G (m x n), computed one column at a time
+        let mut jac = Md::zeros(m, n);
+        let mut v = M::V::zeros(n);
+        let mut col = M::V::zeros(m);
+        for j in 0..n {
+            v[j] = M::T::one();
+            invariants.jac_mul_inplace(y, t, &v, &mut col);
+            v[j] = M::T::zero();
+            for i in 0..m {
+                jac[(i, j)] = col[i];
+            }
+        }
+
+        // factorise G G^T (m x m)
+        let mut a = Md::zeros(m, m);
+        for i in 0..m {
+            for k in 0..=i {
+                let mut sum = M::T::zero();
+                for j in 0..n {
+                    sum += jac[(i, j)] * jac[(k, j)];
+                }
+                a[(i, k)] = sum;
+                a[(k, i)] = sum;
+            }
+        }
+        let normal_equations = NormalEquations { a };
+        let mut g = M::V::zeros(m);
+        let mut linear_solver = Md::default_solver();
+        linear_solver.set_problem(&normal_equations);
+        linear_solver.set_linearisation(&normal_equations, &g, t);
+
+        // simplified newton iteration
+        let mut dy = M::V::zeros(n);
+        convergence.reset();
+        loop {
+            invariants.call_inplace(y, t, &mut g);
+            linear_solver.solve_in_place(&mut g).map_err(|_| {
+                ode_solver_error!(
+                    Other,
+                    format!(
+                        "Jacobian of the invariants does not have full row rank at time {}",
+                        t
+                    )
+                )
+            })?;
+
+            // dy = G^T (G G^T)^{-1} g(y)
+            for j in 0..n {
+                let mut sum = M::T::zero();
+                for i in 0..m {
+                    sum += jac[(i, j)] * g[i];
+                }
+                dy[j] = sum;
+            }
+            y.sub_assign(&dy);
+
+            match convergence.check_new_iteration(&mut dy, y) {
+                ConvergenceStatus::Continue => continue,
+                ConvergenceStatus::Converged => return Ok(()),
+                ConvergenceStatus::Diverged | ConvergenceStatus::MaximumIterations => break,
+            }
+        }
+        Err(ode_solver_error!(
+            Other,
+            format!(
+                "Projection onto the invariant manifold did not converge at time {}",
+                t
+            )
+        ))
+    }
+}
+
+// the invariants given to [ManifoldProjection::from_closures]
+struct InvariantsClosure<M, F, G>
+where
+    M: Matrix,
+    F: Fn(&M::V, M::T, &mut M::V),
+    G: Fn(&M::V, M::T, &M::V, &mut M::V),
+{
+    func: F,
+    jacobian_action: G,
+    nstates: usize,
+    nout: usize,
+    // the closures are Send and Sync, whether or not the matrix type is
+    _phantom: std::marker::PhantomData<fn() -> M>,
+}
+
+impl<M, F, G> Op for InvariantsClosure<M, F, G>
+where
+    M: Matrix,
+    F: Fn(&M::V, M::T, &mut M::V),
+    G: Fn(&M::V, M::T, &M::V, &mut M::V),
+{
+    type T = M::T;
+    type V = M::V;
+    type M = M;
+    fn nstates(&self) -> usize {
+        self.nstates
+    }
+    fn nout(&self) -> usize {
+        self.nout
+    }
+    fn nparams(&self) -> usize {
+        0
+    }
+}
+
+impl<M, F, G> NonLinearOp for InvariantsClosure<M, F, G>
+where
+    M: Matrix,
+    F: Fn(&M::V, M::T, &mut M::V),
+    G: Fn(&M::V, M::T, &M::V, &mut M::V),
+{
+    fn call_inplace(&self, x: &M::V, t: M::T, y: &mut M::V) {
+        (self.func)(x, t, y)
+    }
+}
+
+impl<M, F, G> NonLinearOpJacobian for InvariantsClosure<M, F, G>
+where
+    M: Matrix,
+    F: Fn(&M::V, M::T, &mut M::V),
+    G: Fn(&M::V, M::T, &M::V, &mut M::V),
+{
+    fn jac_mul_inplace(&self, x: &M::V, t: M::T, v: &M::V, y: &mut M::V) {
+        (self.jacobian_action)(x, t, v, y)
+    }
+}
+
+// the linear operator `z -> G G^T z` of the normal equations of the projection, so that they can be solved using a [LinearSolver]
+struct NormalEquations<Md: DenseMatrix> {
+    a: Md,
+}
+
+impl<Md: DenseMatrix> Op for NormalEquations<Md> {
+    type T = Md::T;
+    type V = Md::V;
+    type M = Md;
+    fn nstates(&self) -> usize {
+        self.a.ncols()
+    }
+    fn nout(&self) -> usize {
+        self.a.nrows()
+    }
+    fn nparams(&self) -> usize {
+        0
+    }
+}
+
+impl<Md: DenseMatrix> NonLinearOp for NormalEquations<Md> {
+    fn call_inplace(&self, x: &Md::V, _t: Md::T, y: &mut Md::V) {
+        self.a.gemv(Md::T::one(), x, Md::T::zero(), y);
+    }
+}
+
+impl<Md: DenseMatrix> NonLinearOpJacobian for NormalEquations<Md> {
+    fn jac_mul_inplace(&self, _x: &Md::V, _t: Md::T, v: &Md::V, y: &mut Md::V) {
+        self.a.gemv(Md::T::one(), v, Md::T::zero(), y);
+    }
+    fn jacobian_inplace(&self, _x: &Md::V, _t: Md::T, y: &mut Md) {
+        y.copy_from(&self.a);
+    }
+}
@@ -0,0 +1,975 @@
+use crate::error::DiffsolError;
+use crate::error::OdeSolverError;
+use crate::matrix::MatrixRef;
+use crate::ode_solver_error;
+use crate::vector::VectorRef;
+use crate::{
+    op::{radau::RadauComplexCallable, sdirk::SdirkCallable},
+    scale, LinearOp, LinearSolver, NonLinearOp, OdeEquationsImplicit, OdeSolverMethod,
+    OdeSolverProblem, OdeSolverState, OdeSolverStopReason, Op, RootFinder, Scalar, SdirkState,
+    StateRef, StateRefMut, Vector,
+};
+use nalgebra::ComplexField;
+use num_traits::abs;
+use num_traits::One;
+use num_traits::Pow;
+use num_traits::Zero;
+
+use super::bdf::BdfStatistics;
+use super::method::{check_interpolation_time, check_tstop, init_root_finder, TstopCheck};
+
+/// Coefficients of the 3-stage Radau IIA method, along with the transformation that block-diagonalises its Butcher matrix.
+///
+/// Taken from Hairer, E., & Wanner, G. (1996). Solving Ordinary Differential Equations II: Stiff and Differential-Algebraic Problems. Springer Series in Computational Mathematics, Volume 14.
+#[derive(Clone)]
+struct RadauCoefficients<T: Scalar> {
+    c: [T; 3],
+    a: [[T; 3]; 3],
+    t: [[T; 3]; 3],
+    ti: [[T; 3]; 3],
+    /// weights used to form the embedded error estimate
+    dd: [T; 3],
+    /// real eigenvalue of the inverse of the Butcher matrix
+    gamma: T,
+    /// complex eigenvalue pair of the inverse of the Butcher matrix, `alpha +- i beta`
+    alpha: T,
+    beta: T,
+}
+
+impl<T: Scalar> RadauCoefficients<T> {
+    fn new() -> Self {
+        let sq6 = 6.0_f64.sqrt();
+        let c1 = (4.0 - sq6) / 10.0;
+        let c2 = (4.0 + sq6) / 10.0;
+        let c = [T::from(c1), T::from(c2), T::one()];
+        let a = [
+            [
+                T::from((88.0 - 7.0 * sq6) / 360.0),
+                T::from((296.0 - 169.0 * sq6) / 1800.0),
+                T::from((-2.0 + 3.0 * sq6) / 225.0),
+            ],
+            [
+                T::from((296.0 + 169.0 * sq6) / 1800.0),
+                T::from((88.0 + 7.0 * sq6) / 360.0),
+                T::from((-2.0 - 3.0 * sq6) / 225.0),
+            ],
+            [
+                T::from((16.0 - sq6) / 36.0),
+                T::from((16.0 + sq6) / 36.0),
+                T::from(1.0 / 9.0),
+            ],
+        ];
+        let t = [
+            [
+                T::from(9.123_239_487_089_294e-2),
+                T::from(-0.141_255_295_020_954_2),
+                T::from(-3.002_919_410_514_742_4e-2),
+            ],
+            [
+                T::from(0.241_717_932_707_107),
+                T::from(0.204_129_352_293_799_93),
+                T::from(0.382_942_112_757_261_9),
+            ],
+            [T::from(0.966_048_182_615_093), T::one(), T::zero()],
+        ];
+        let ti = [
+            [
+                T::from(4.325_579_890_063_155),
+                T::from(0.339_199_251_815_809_87),
+                T::from(0.541_770_539_935_874_9),
+            ],
+            [
+                T::from(-4.178_718_591_551_905),
+                T::from(-0.327_682_820_761_062_4),
+                T::from(0.476_623_554_500_550_45),
+            ],
+            [
+                T::from(-0.502_872_634_945_786_9),
+                T::from(2.571_926_949_855_605),
+                T::from(-0.596_039_204_828_225),
+            ],
+        ];
+        let dd = [
+            T::from(-(13.0 + 7.0 * sq6) / 3.0),
+            T::from((-13.0 + 7.0 * sq6) / 3.0),
+            T::from(-1.0 / 3.0),
+        ];
+        let cbrt81 = 81.0_f64.powf(1.0 / 3.0);
+        let cbrt9 = 9.0_f64.powf(1.0 / 3.0);
+        let u1 = (6.0 + cbrt81 - cbrt9) / 30.0;
+        let al = (12.0 - cbrt81 + cbrt9) / 60.0;
+        let be = (cbrt81 + cbrt9) * 3.0_f64.sqrt() / 60.0;
+        let cno = al * al + be * be;
+        Self {
+            c,
+            a,
+            t,
+            ti,
+            dd,
+            gamma: T::from(1.0 / u1),
+            alpha: T::from(al / cno),
+            beta: T::from(be / cno),
+        }
+    }
+}
+
+/// An implicit Runge-Kutta method using the 3-stage Radau IIA tableau (order 5), suitable for very stiff problems and
+/// differential-algebraic equations of index 1 (i.e. problems with a singular mass matrix).
+///
+/// The nonlinear system for the three stages is solved using a simplified Newton iteration, where the Butcher matrix is
+/// transformed to block-diagonal form so that each iteration only requires the solution of one real linear system
+/// of size `n` and one complex linear system, which is solved as a real system of size `2n`. Both systems are factorised
+/// using the `LS` linear solver. The Jacobian is reused between steps if the Newton iteration converges quickly.
+///
+/// A continuous extension of order 3 is used for interpolation, and the step size is controlled using an embedded
+/// error estimate of order 3, following the RADAU5 code of Hairer & Wanner.
+///
+/// Forward sensitivities are not supported by this solver.
+pub struct Radau<'a, Eqn, LS>
+where
+    LS: LinearSolver<Eqn::M>,
+    Eqn: OdeEquationsImplicit,
+{
+    problem: &'a OdeSolverProblem<Eqn>,
+    coeffs: RadauCoefficients<Eqn::T>,
+    op: SdirkCallable<&'a Eqn>,
+    complex_op: RadauComplexCallable<&'a Eqn>,
+    linear_solver: LS,
+    complex_linear_solver: LS,
+    state: SdirkState<Eqn::V>,
+    z: Vec<Eqn::V>,
+    zg: Vec<Eqn::V>,
+    cont: Vec<Eqn::V>,
+    gcont: Vec<Eqn::V>,
+    old_t: Eqn::T,
+    has_cont: bool,
+    jacobian_is_stale: bool,
+    factorised_h: Option<Eqn::T>,
+    faccon: Eqn::T,
+    theta: Eqn::T,
+    statistics: BdfStatistics,
+    root_finder: Option<RootFinder<Eqn::V>>,
+    tstop: Option<Eqn::T>,
+    is_state_mutated: bool,
+}
+
+impl<Eqn, LS> Clone for Radau<'_, Eqn, LS>
+where
+    LS: LinearSolver<Eqn::M>,
+    Eqn: OdeEquationsImplicit,
+    for<'b> &'b Eqn::V: VectorRef<Eqn::V>,
+    for<'b> &'b Eqn::M: MatrixRef<Eqn::M>,
+{
+    fn clone(&self) -> Self {
+        let problem = self.problem;
+        let op = self.op.clone_state(&problem.eqn);
+        let complex_op =
+            RadauComplexCallable::new(&problem.eqn, self.coeffs.alpha, self.coeffs.beta);
+        let mut linear_solver = LS::default();
+        linear_solver.set_problem(&op);
+        let mut complex_linear_solver = LS::default();
+        complex_linear_solver.set_problem(&complex_op);
+        Self {
+            problem,
+            coeffs: self.coeffs.clone(),
+            op,
+            complex_op,
+            linear_solver,
+            complex_linear_solver,
+            state: self.state.clone(),
+            z: self.z.clone(),
+            zg: self.zg.clone(),
+            cont: self.cont.clone(),
+            gcont: self.gcont.clone(),
+            old_t: self.old_t,
+            has_cont: self.has_cont,
+            // the linear solvers are not factorised, so force a new jacobian
+            jacobian_is_stale: true,
+            factorised_h: None,
+            faccon: self.faccon,
+            theta: self.theta,
+            statistics: self.statistics.clone(),
+            root_finder: self.root_finder.clone(),
+            tstop: self.tstop,
+            is_state_mutated: self.is_state_mutated,
+        }
+    }
+}
+
+impl<'a, Eqn, LS> Radau<'a, Eqn, LS>
+where
+    LS: LinearSolver<Eqn::M>,
+    Eqn: OdeEquationsImplicit,
+    for<'b> &'b Eqn::V: VectorRef<Eqn::V>,
+    for<'b> &'b Eqn::M: MatrixRef<Eqn::M>,
+{
+    const SAFETY: f64 = 0.9;
+    const MIN_FACTOR: f64 = 0.2;
+    const MAX_FACTOR: f64 = 8.0;
+    const MIN_TIMESTEP: f64 = 1e-13;
+    const MAX_NEWTON_ITER: usize = 7;
+    const NEWTON_FAIL_FACTOR: f64 = 0.5;
+    /// the jacobian is reused for the next step if the newton convergence rate is below this value
+    const JACOBIAN_REUSE_THETA: f64 = 0.001;
+    /// the step size is left unchanged if the factor is within this range, to avoid refactorising the linear systems
+    const KEEP_STEP_MIN_FACTOR: f64 = 1.0;
+    const KEEP_STEP_MAX_FACTOR: f64 = 1.2;
+
+    pub fn new(
+        problem: &'a OdeSolverProblem<Eqn>,
+        mut state: SdirkState<Eqn::V>,
+        mut linear_solver: LS,
+    ) -> Result<Self, DiffsolError> {
+        let coeffs = RadauCoefficients::new();
+
+        // the real linear system is (gamma / h) M - J, which is (gamma / h) times the jacobian of the sdirk callable with c = 1 / gamma
+        let op = SdirkCallable::new(&problem.eqn, Eqn::T::one() / coeffs.gamma);
+        op.set_h(state.h);
+        linear_solver.set_problem(&op);
+
+        // the complex linear system is ((alpha + i beta) / h) M - J
+        let complex_op = RadauComplexCallable::new(&problem.eqn, coeffs.alpha, coeffs.beta);
+        complex_op.set_h(state.h);
+        let mut complex_linear_solver = LS::default();
+        complex_linear_solver.set_problem(&complex_op);
+
+        state.check_consistent_with_problem(problem)?;
+
+        let nstates = state.y.len();
+        let nout = if problem.integrate_out {
+            problem.eqn.out().unwrap().nout()
+        } else {
+            0
+        };
+
+        let old_t = state.t;
+        state.set_problem(problem)?;
+        let root_finder = if let Some(root_fn) = problem.eqn.root() {
+            let root_finder = RootFinder::new(root_fn.nout());
+            root_finder.init(&root_fn, &state.y, state.t);
+            Some(root_finder)
+        } else {
+            None
+        };
+
+        Ok(Self {
+            problem,
+            coeffs,
+            op,
+            complex_op,
+            linear_solver,
+            complex_linear_solver,
+            state,
+            z: vec![<Eqn::V as Vector>::zeros(nstates); 3],
+            zg: vec![<Eqn::V as Vector>::zeros(nout); 3],
+            cont: vec![<Eqn::V as Vector>::zeros(nstates); 3],
+            gcont: vec![<Eqn::V as Vector>::zeros(nout); 3],
+            old_t,
+            has_cont: false,
+            jacobian_is_stale: true,
+            factorised_h: None,
+            faccon: Eqn::T::one(),
+            theta: Eqn::T::one(),
+            statistics: BdfStatistics::default(),
+            root_finder,
+            tstop: None,
+            is_state_mutated: false,
+        })
+    }
+
+    pub fn get_statistics(&self) -> &BdfStatistics {
+        &self.statistics
+    }
+
+    /// forget the previous step, the next step will use a new jacobian and zero starting values for the newton iteration
+    fn reset_step_history(&mut self) {
+        self.has_cont = false;
+        self.jacobian_is_stale = true;
+        self.factorised_h = None;
+    }
+
+    fn handle_tstop(
+        &mut self,
+        tstop: Eqn::T,
+    ) -> Result<Option<OdeSolverStopReason<Eqn::T>>, DiffsolError> {
+        match check_tstop(self.state.t, self.state.h, tstop)? {
+            TstopCheck::Reached => {
+                self.tstop = None;
+                return Ok(Some(OdeSolverStopReason::TstopReached));
+            }
+            TstopCheck::ScaleStep(factor) => {
+                self.state.h *= factor;
+            }
+            TstopCheck::NotReached => {}
+        }
+        Ok(None)
+    }
+
+    /// y_i = alpha sum_j m_ij x_j
+    fn transform(m: &[[Eqn::T; 3]; 3], alpha: Eqn::T, x: &[Eqn::V], y: &mut [Eqn::V]) {
+        for (y_i, m_i) in y.iter_mut().zip(m.iter()) {
+            y_i.fill(Eqn::T::zero());
+            for (x_j, m_ij) in x.iter().zip(m_i.iter()) {
+                y_i.axpy(alpha * *m_ij, x_j, Eqn::T::one());
+            }
+        }
+    }
+
+    /// y = M x, or y = x if there is no mass matrix
+    fn mass_mul(problem: &OdeSolverProblem<Eqn>, x: &Eqn::V, t: Eqn::T, y: &mut Eqn::V) {
+        if let Some(mass) = problem.eqn.mass() {
+            mass.gemv_inplace(x, t, Eqn::T::zero(), y);
+        } else {
+            y.copy_from(x);
+        }
+    }
+
+    /// evaluate the continuous extension `cont` of the last step at `s = (t - t_end) / h_last`, relative to the value at the end of the step
+    fn eval_cont(c: &[Eqn::T; 3], cont: &[Eqn::V], s: Eqn::T, y: &mut Eqn::V) {
+        let one = Eqn::T::one();
+        let c1m1 = c[0] - one;
+        let c2m1 = c[1] - one;
+        // y = s (cont_0 + (s - c2m1) (cont_1 + (s - c1m1) cont_2))
+        y.copy_from(&cont[2]);
+        y.axpy(one, &cont[1], s - c1m1);
+        y.axpy(one, &cont[0], s - c2m1);
+        *y *= scale(s);
+    }
+
+    /// calculate the coefficients of the continuous extension from the stage increments `z`
+    fn update_cont(c: &[Eqn::T; 3], z: &[Eqn::V], cont: &mut [Eqn::V]) {
+        let one = Eqn::T::one();
+        let [c1, c2, _] = *c;
+        let c1m1 = c1 - one;
+        let c2m1 = c2 - one;
+        let c1mc2 = c1 - c2;
+
+        // cont_0 = (z_1 - z_2) / (c2 - 1)
+        cont[0].copy_from(&z[1]);
+        cont[0].axpy(-one / c2m1, &z[2], one / c2m1);
+
+        // ak = (z_0 - z_1) / (c1 - c2), acont3 = (ak - z_0 / c1) / c2
+        let mut ak = z[0].clone();
+        ak.axpy(-one / c1mc2, &z[1], one / c1mc2);
+        let mut acont3 = ak.clone();
+        acont3.axpy(-one / (c1 * c2), &z[0], one / c2);
+
+        // cont_1 = (ak - cont_0) / (c1 - 1), cont_2 = cont_1 - acont3
+        let (cont0, rest) = cont.split_at_mut(1);
+        let (cont1, cont2) = rest.split_at_mut(1);
+        cont1[0].copy_from(&ak);
+        cont1[0].axpy(-one / c1m1, &cont0[0], one / c1m1);
+        cont2[0].copy_from(&cont1[0]);
+        cont2[0].axpy(-one, &acont3, one);
+    }
+
+    /// derivative of the continuous extension at the end of the step
+    fn cont_derivative(c: &[Eqn::T; 3], cont: &[Eqn::V], h: Eqn::T, dy: &mut Eqn::V) {
+        let one = Eqn::T::one();
+        let c1m1 = c[0] - one;
+        let c2m1 = c[1] - one;
+        // dy = (cont_0 - c2m1 (cont_1 - c1m1 cont_2)) / h
+        dy.copy_from(&cont[1]);
+        dy.axpy(-c1m1, &cont[2], one);
+        dy.axpy(one / h, &cont[0], -c2m1 / h);
+    }
+
+    /// starting values for the stage increments, extrapolated from the continuous extension of the previous step if available
+    fn starting_values(&mut self, h: Eqn::T) {
+        if self.has_cont {
+            let hold = self.state.t - self.old_t;
+            let c = &self.coeffs.c;
+            for (z, c_k) in self.z.iter_mut().zip(c.iter()) {
+                Self::eval_cont(c, &self.cont, *c_k * h / hold, z);
+            }
+        } else {
+            for z in self.z.iter_mut() {
+                z.fill(Eqn::T::zero());
+            }
+        }
+    }
+
+    /// form and factorise the real and complex linear systems for step size `h`, re-evaluating the jacobian if it is stale
+    fn factorise(&mut self, h: Eqn::T) {
+        let t0 = self.state.t;
+        let n = self.state.y.len();
+        if self.jacobian_is_stale {
+            self.op.set_phi_direct(self.state.y.clone());
+            self.op.set_jacobian_is_stale();
+        }
+        self.op.set_h(h);
+        self.linear_solver
+            .set_linearisation(&self.op, &<Eqn::V as Vector>::zeros(n), t0);
+        self.complex_op
+            .set_jacobians(&self.op.rhs_jac(), &self.op.mass_jac());
+        self.complex_op.set_h(h);
+        self.complex_linear_solver.set_linearisation(
+            &self.complex_op,
+            &<Eqn::V as Vector>::zeros(2 * n),
+            t0,
+        );
+        self.statistics.number_of_linear_solver_setups += 1;
+        self.factorised_h = Some(h);
+    }
+
+    /// solve the nonlinear system for the stage increments `z` using a simplified newton iteration, returns the number
+    /// of iterations or `None` if the iteration did not converge
+    fn solve_stages(&mut self, h: Eqn::T) -> Result<Option<usize>, DiffsolError> {
+        let problem = self.problem;
+        let n = self.state.y.len();
+        let t0 = self.state.t;
+        let one = Eqn::T::one();
+        let c = self.coeffs.c;
+        let t = self.coeffs.t;
+        let ti = self.coeffs.ti;
+        let gamma_h = self.coeffs.gamma / h;
+        let alpha_h = self.coeffs.alpha / h;
+        let beta_h = self.coeffs.beta / h;
+
+        let rtol = problem.rtol;
+        let mut fnewt = Eqn::T::from(0.03);
+        if rtol.sqrt() < fnewt {
+            fnewt = rtol.sqrt();
+        }
+        if fnewt < Eqn::T::from(10.0) * Eqn::T::EPSILON / rtol {
+            fnewt = Eqn::T::from(10.0) * Eqn::T::EPSILON / rtol;
+        }
+
+        // transformed stage increments w = T^{-1} z
+        let mut w = vec![<Eqn::V as Vector>::zeros(n); 3];
+        Self::transform(&ti, one, &self.z, &mut w);
+
+        let mut f = vec![<Eqn::V as Vector>::zeros(n); 3];
+        let mut mw = vec![<Eqn::V as Vector>::zeros(n); 3];
+        let mut r = vec![<Eqn::V as Vector>::zeros(n); 3];
+        let mut rc = <Eqn::V as Vector>::zeros(2 * n);
+        let mut y = <Eqn::V as Vector>::zeros(n);
+
+        if self.faccon < Eqn::T::EPSILON {
+            self.faccon = Eqn::T::EPSILON;
+        }
+        self.faccon = self.faccon.pow(Eqn::T::from(0.8));
+        let mut dynold = Eqn::T::zero();
+        let mut thqold = Eqn::T::one();
+        let mut niter = 0;
+        loop {
+            if niter >= Self::MAX_NEWTON_ITER {
+                return Ok(None);
+            }
+
+            // f_k = f(t0 + c_k h, y0 + z_k)
+            for ((f_k, z_k), c_k) in f.iter_mut().zip(self.z.iter()).zip(c.iter()) {
+                y.copy_from(&self.state.y);
+                y.axpy(one, z_k, one);
+                problem.eqn.rhs().call_inplace(&y, t0 + *c_k * h, f_k);
+            }
+            for (mw_k, w_k) in mw.iter_mut().zip(w.iter()) {
+                Self::mass_mul(problem, w_k, t0, mw_k);
+            }
+
+            // r = T^{-1} f - (Lambda / h) M w, where Lambda is the block-diagonal form of the inverse Butcher matrix
+            Self::transform(&ti, one, &f, &mut r);
+            r[0].axpy(-gamma_h, &mw[0], one);
+            r[1].axpy(-alpha_h, &mw[1], one);
+            r[1].axpy(beta_h, &mw[2], one);
+            r[2].axpy(-beta_h, &mw[1], one);
+            r[2].axpy(-alpha_h, &mw[2], one);
+
+            // solve the real system ((gamma / h) M - J) dw_0 = r_0
+            r[0] *= scale(one / gamma_h);
+            self.linear_solver.solve_in_place(&mut r[0])?;
+
+            // solve the complex system (((alpha + i beta) / h) M - J) (dw_1 + i dw_2) = r_1 + i r_2
+            rc.as_mut_slice()[..n].copy_from_slice(r[1].as_slice());
+            rc.as_mut_slice()[n..].copy_from_slice(r[2].as_slice());
+            self.complex_linear_solver.solve_in_place(&mut rc)?;
+            r[1].as_mut_slice().copy_from_slice(&rc.as_slice()[..n]);
+            r[2].as_mut_slice().copy_from_slice(&rc.as_slice()[n..]);
+
+            self.statistics.number_of_nonlinear_solver_iterations += 1;
+
+            // check convergence rate
+            let dyno = ((r[0].squared_norm(&self.state.y, &problem.atol, rtol)
+                + r[1].squared_norm(&self.state.y, &problem.atol, rtol)
+                + r[2].squared_norm(&self.state.y, &problem.atol, rtol))
+                / Eqn::T::from(3.0))
+            .sqrt();
+            if niter > 0 {
+                let thq = dyno / dynold;
+                self.theta = if niter == 1 {
+                    thq
+                } else {
+                    (thq * thqold).sqrt()
+                };
+                thqold = thq;
+                if self.theta >= Eqn::T::from(0.99) {
+                    return Ok(None);
+                }
+                self.faccon = self.theta / (one - self.theta);
+            }
+            dynold = if dyno > Eqn::T::EPSILON {
+                dyno
+            } else {
+                Eqn::T::EPSILON
+            };
+
+            // update the increments, z = T w
+            for (w_k, r_k) in w.iter_mut().zip(r.iter()) {
+                w_k.axpy(one, r_k, one);
+            }
+            Self::transform(&t, one, &w, &mut self.z);
+            niter += 1;
+
+            if self.faccon * dyno <= fnewt {
+                return Ok(Some(niter));
+            }
+        }
+    }
+
+    /// calculate the squared norm of the error estimate for the current stage increments
+    fn error_norm(
+        &mut self,
+        h: Eqn::T,
+        f0: &Eqn::V,
+        rejected: bool,
+    ) -> Result<Eqn::T, DiffsolError> {
+        let problem = self.problem;
+        let t0 = self.state.t;
+        let n = self.state.y.len();
+        let one = Eqn::T::one();
+        let dd = self.coeffs.dd;
+        let scale_h = h / self.coeffs.gamma;
+
+        // f1 = sum_k dd_k z_k / h, err = (h / gamma) ((gamma / h) M - J)^{-1} (f0 + M f1)
+        let mut f1 = <Eqn::V as Vector>::zeros(n);
+        for (z_k, dd_k) in self.z.iter().zip(dd.iter()) {
+            f1.axpy(*dd_k / h, z_k, one);
+        }
+        let mut mf1 = <Eqn::V as Vector>::zeros(n);
+        Self::mass_mul(problem, &f1, t0, &mut mf1);
+        let mut err = mf1.clone();
+        err.axpy(one, f0, one);
+        err *= scale(scale_h);
+        self.linear_solver.solve_in_place(&mut err)?;
+        let mut error_norm = err.squared_norm(&self.state.y, &problem.atol, problem.rtol);
+
+        // the estimate can be unreliable for stiff components, so improve it using one more evaluation of the rhs
+        if error_norm >= one && (rejected || !self.has_cont) {
+            let mut y = self.state.y.clone();
+            y.axpy(one, &err, one);
+            problem.eqn.rhs().call_inplace(&y, t0, &mut err);
+            err.axpy(one, &mf1, one);
+            err *= scale(scale_h);
+            self.linear_solver.solve_in_place(&mut err)?;
+            error_norm = err.squared_norm(&self.state.y, &problem.atol, problem.rtol);
+        }
+
+        let mut ncontributions = 1;
+        if problem.output_in_error_control() {
+            // the iteration matrix for the output is (gamma / h) I, so the error is (h / gamma) (g0 + sum_k dd_k zg_k / h)
+            let out = problem.eqn.out().unwrap();
+            let mut out_error = out.call(&self.state.y, t0);
+            out_error *= scale(scale_h);
+            for (zg_k, dd_k) in self.zg.iter().zip(dd.iter()) {
+                out_error.axpy(*dd_k / self.coeffs.gamma, zg_k, one);
+            }
+            let atol = problem.out_atol.as_ref().unwrap();
+            let rtol = problem.out_rtol.unwrap();
+            error_norm += out_error.squared_norm(&self.state.g, atol, rtol);
+            ncontributions += 1;
+        }
+        if ncontributions > 1 {
+            error_norm /= Eqn::T::from(ncontributions as f64);
+        }
+        Ok(error_norm)
+    }
+
+    /// integrate the output function over the stages, zg_k = h sum_j a_kj g(t0 + c_j h, y0 + z_j)
+    fn integrate_out(&mut self, h: Eqn::T) {
+        let problem = self.problem;
+        let out = problem.eqn.out().unwrap();
+        let t0 = self.state.t;
+        let one = Eqn::T::one();
+        let mut y = self.state.y.clone();
+        let g = (0..3)
+            .map(|j| {
+                y.copy_from(&self.state.y);
+                y.axpy(one, &self.z[j], one);
+                out.call(&y, t0 + self.coeffs.c[j] * h)
+            })
+            .collect::<Vec<_>>();
+        Self::transform(&self.coeffs.a, h, &g, &mut self.zg);
+    }
+
+    fn interpolation_s(&self, t: Eqn::T) -> Result<Eqn::T, DiffsolError> {
+        let state = &self.state;
+
+        // check that t is within the last step
+        check_interpolation_time(t, self.old_t, state.t)?;
+        let dt = state.t - self.old_t;
+        if dt == Eqn::T::zero() {
+            Ok(Eqn::T::zero())
+        } else {
+            Ok((t - state.t) / dt)
+        }
+    }
+
+    fn _update_step_size(&mut self, factor: Eqn::T) -> Result<Eqn::T, DiffsolError> {
+        let new_h = self.state.h * factor;
+
+        // if step size too small, then fail
+        if abs(new_h) < Eqn::T::from(Self::MIN_TIMESTEP) {
+            return Err(DiffsolError::from(OdeSolverError::StepSizeTooSmall {
+                time: self.state.t.into(),
+            }));
+        }
+
+        // update state
+        self.state.h = new_h;
+
+        Ok(new_h)
+    }
+}
+
+impl<'a, Eqn, LS> OdeSolverMethod<'a, Eqn> for Radau<'a, Eqn, LS>
+where
+    LS: LinearSolver<Eqn::M>,
+    Eqn: OdeEquationsImplicit,
+    for<'b> &'b Eqn::V: VectorRef<Eqn::V>,
+    for<'b> &'b Eqn::M: MatrixRef<Eqn::M>,
+{
+    type State = SdirkState<Eqn::V>;
+
+    fn problem(&self) -> &'a OdeSolverProblem<Eqn> {
+        self.problem
+    }
+
+    fn order(&self) -> usize {
+        5
+    }
+
+    fn set_state(&mut self, state: Self::State) {
+        self.state = state;
+        self.reset_step_history();
+    }
+
+    fn into_state(self) -> SdirkState<Eqn::V> {
+        self.state
+    }
+
+    fn checkpoint(&mut self) -> Self::State {
+        self.reset_step_history();
+        self.state.clone()
+    }
+
+    fn step(&mut self) -> Result<OdeSolverStopReason<Eqn::T>, DiffsolError> {
+        let problem = self.problem;
+        let one = Eqn::T::one();
+
+        if self.is_state_mutated {
+            // reinitialise root finder if needed
+            init_root_finder(
+                problem,
+                self.root_finder.as_ref(),
+                &self.state.y,
+                self.state.t,
+            );
+            // reinitialise tstop if needed
+            if let Some(t_stop) = self.tstop {
+                self.set_stop_time(t_stop)?;
+            }
+            self.reset_step_history();
+            self.is_state_mutated = false;
+        }
+
+        let t0 = self.state.t;
+        let f0 = problem.eqn.rhs().call(&self.state.y, t0);
+        let mut jacobian_is_current = self.jacobian_is_stale;
+        let mut rejected = false;
+        let mut factor: Eqn::T;
+
+        // loop until step is accepted
+        loop {
+            let h = self.state.h;
+            if self.jacobian_is_stale || self.factorised_h != Some(h) {
+                self.factorise(h);
+                self.jacobian_is_stale = false;
+            }
+
+            // solve for the stages, on failure reduce the step size and update the jacobian if it is out of date
+            self.starting_values(h);
+            let niter = match self.solve_stages(h)? {
+                Some(niter) => niter,
+                None => {
+                    self.statistics.number_of_nonlinear_solver_fails += 1;
+                    if !jacobian_is_current {
+                        self.jacobian_is_stale = true;
+                        jacobian_is_current = true;
+                    }
+                    self._update_step_size(Eqn::T::from(Self::NEWTON_FAIL_FACTOR))?;
+                    rejected = true;
+                    continue;
+                }
+            };
+
+            if problem.integrate_out {
+                self.integrate_out(h);
+            }
+
+            // compute the error and the new step size
+            let error_norm = self.error_norm(h, &f0, rejected)?;
+            let max_iter = Self::MAX_NEWTON_ITER as f64;
+            let safety = Self::SAFETY
+                * f64::min(
+                    1.0,
+                    (1.0 + 2.0 * max_iter) / (niter as f64 + 2.0 * max_iter),
+                );
+            // error_norm is the squared norm, so this is err^(-1/4)
+            factor = Eqn::T::from(safety) * error_norm.pow(Eqn::T::from(-0.125));
+            if factor < Eqn::T::from(Self::MIN_FACTOR) {
+                factor = Eqn::T::from(Self::MIN_FACTOR);
+            }
+            if factor > Eqn::T::from(Self::MAX_FACTOR) {
+                factor = Eqn::T::from(Self::MAX_FACTOR);
+            }
+
+            // test error is within tolerance
+            if error_norm <= one {
+                break;
+            }
+
+            // step is rejected, factor reduces step size, so we try again with the smaller step size
+            self.statistics.number_of_error_test_failures += 1;
+            if !self.has_cont {
+                // on the first step, a large reduction is usually needed
+                factor = Eqn::T::from(0.1);
+            }
+            if !jacobian_is_current {
+                self.jacobian_is_stale = true;
+                jacobian_is_current = true;
+            }
+            rejected = true;
+            self._update_step_size(factor)?;
+        }
+
+        // take the step
+        {
+            let h = self.state.h;
+            let c = &self.coeffs.c;
+            Self::update_cont(c, &self.z, &mut self.cont);
+            self.old_t = self.state.t;
+            let state = &mut self.state;
+            state.t += h;
+            state.y.axpy(one, &self.z[2], one);
+            Self::cont_derivative(c, &self.cont, h, &mut state.dy);
+
+            // integrate output function
+            if problem.integrate_out {
+                Self::update_cont(c, &self.zg, &mut self.gcont);
+                state.g.axpy(one, &self.zg[2], one);
+                Self::cont_derivative(c, &self.gcont, h, &mut state.dg);
+            }
+            self.has_cont = true;
+        }
+
+        // reuse the jacobian if the newton iteration converged quickly
+        self.jacobian_is_stale = self.theta > Eqn::T::from(Self::JACOBIAN_REUSE_THETA);
+
+        // update step size for next step, after a rejection the step size is not increased
+        if rejected && factor > one {
+            factor = one;
+        }
+        if !self.jacobian_is_stale
+            && factor >= Eqn::T::from(Self::KEEP_STEP_MIN_FACTOR)
+            && factor <= Eqn::T::from(Self::KEEP_STEP_MAX_FACTOR)
+        {
+            factor = one;
+        }
+        self._update_step_size(factor)?;
+
+        // update statistics
+        self.statistics.number_of_steps += 1;
+
+        // check for root within accepted step
+        if let Some(root_fn) = problem.eqn.root() {
+            let ret = self.root_finder.as_ref().unwrap().check_root(
+                &|t| self.interpolate(t),
+                &root_fn,
+                &self.state.y,
+                self.state.t,
+            );
+            if let Some(root) = ret {
+                return Ok(OdeSolverStopReason::RootFound(root));
+            }
+        }
+
+        // check if we are at tstop
+        if let Some(tstop) = self.tstop {
+            if let Some(reason) = self.handle_tstop(tstop).unwrap() {
+                return Ok(reason);
+            }
+        }
+
+        // just a normal step, no roots or tstop reached
+        Ok(OdeSolverStopReason::InternalTimestep)
+    }
+
+    fn set_stop_time(&mut self, tstop: <Eqn as Op>::T) -> Result<(), DiffsolError> {
+        self.tstop = Some(tstop);
+        if let Some(OdeSolverStopReason::TstopReached) = self.handle_tstop(tstop)? {
+            let error = OdeSolverError::StopTimeBeforeCurrentTime {
+                stop_time: tstop.into(),
+                state_time: self.state.t.into(),
+            };
+            self.tstop = None;
+            return Err(DiffsolError::from(error));
+        }
+        Ok(())
+    }
+
+    fn interpolate_sens(&self, _t: <Eqn as Op>::T) -> Result<Vec<<Eqn as Op>::V>, DiffsolError> {
+        Ok(vec![])
+    }
+
+    fn interpolate(&self, t: <Eqn>::T) -> Result<<Eqn>::V, DiffsolError> {
+        if self.is_state_mutated {
+            if t == self.state.t {
+                return Ok(self.state.y.clone());
+            } else {
+                return Err(ode_solver_error!(InterpolationTimeOutsideCurrentStep));
+            }
+        }
+        let s = self.interpolation_s(t)?;
+        let mut ret = <Eqn::V as Vector>::zeros(self.state.y.len());
+        Self::eval_cont(&self.coeffs.c, &self.cont, s, &mut ret);
+        ret.axpy(Eqn::T::one(), &self.state.y, Eqn::T::one());
+        Ok(ret)
+    }
+
+    fn interpolate_out(&self, t: <Eqn>::T) -> Result<<Eqn>::V, DiffsolError> {
+        if self.is_state_mutated {
+            if t == self.state.t {
+                return Ok(self.state.g.clone());
+            } else {
+                return Err(ode_solver_error!(InterpolationTimeOutsideCurrentStep));
+            }
+        }
+        let s = self.interpolation_s(t)?;
+        let mut ret = <Eqn::V as Vector>::zeros(self.state.g.len());
+        Self::eval_cont(&self.coeffs.c, &self.gcont, s, &mut ret);
+        ret.axpy(Eqn::T::one(), &self.state.g, Eqn::T::one());
+        Ok(ret)
+    }
+
+    fn state(&self) -> StateRef<Eqn::V> {
+        self.state.as_ref()
+    }
+
+    fn state_mut(&mut self) -> StateRefMut<Eqn::V> {
+        self.is_state_mutated = true;
+        self.state.as_mut()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        ode_solver::{
+            test_models::{
+                exponential_decay::{
+                    exponential_decay_problem, exponential_decay_problem_with_root,
+                    negative_exponential_decay_problem,
+                },
+                robertson::robertson,
+                robertson_ode::robertson_ode,
+            },
+            tests::{
+                test_checkpointing, test_interpolate, test_ode_solver, test_problem,
+                test_state_mut, test_state_mut_on_problem,
+            },
+        },
+        NalgebraLU, OdeEquations, Op,
+    };
+
+    use num_traits::abs;
+
+    type M = nalgebra::DMatrix<f64>;
+    type LS = NalgebraLU<f64>;
+
+    #[test]
+    fn radau_state_mut() {
+        test_state_mut(test_problem::<M>().radau::<LS>().unwrap());
+    }
+
+    #[test]
+    fn radau_test_interpolate() {
+        test_interpolate(test_problem::<M>().radau::<LS>().unwrap());
+    }
+
+    #[test]
+    fn radau_test_checkpointing() {
+        let (problem, soln) = exponential_decay_problem::<M>(false);
+        let s1 = problem.radau::<LS>().unwrap();
+        let s2 = problem.radau::<LS>().unwrap();
+        test_checkpointing(soln, s1, s2);
+    }
+
+    #[test]
+    fn radau_test_state_mut_exponential_decay() {
+        let (p, soln) = exponential_decay_problem::<M>(false);
+        let s = p.radau::<LS>().unwrap();
+        test_state_mut_on_problem(s, soln);
+    }
+
+    #[test]
+    fn radau_test_nalgebra_negative_exponential_decay() {
+        let (problem, soln) = negative_exponential_decay_problem::<M>(false);
+        let mut s = problem.radau::<LS>().unwrap();
+        test_ode_solver(&mut s, soln, None, false, false);
+    }
+
+    #[test]
+    fn test_radau_nalgebra_exponential_decay() {
+        let (problem, soln) = exponential_decay_problem::<M>(false);
+        let mut s = problem.radau::<LS>().unwrap();
+        test_ode_solver(&mut s, soln, None, false, false);
+        let statistics = s.get_statistics();
+        assert!(statistics.number_of_nonlinear_solver_iterations >= statistics.number_of_steps);
+        assert!(
+            statistics.number_of_linear_solver_setups
+                <= statistics.number_of_steps
+                    + statistics.number_of_error_test_failures
+                    + statistics.number_of_nonlinear_solver_fails
+        );
+    }
+
+    #[test]
+    fn test_radau_nalgebra_robertson() {
+        let (problem, soln) = robertson::<M>(false);
+        let mut s = problem.radau::<LS>().unwrap();
+        test_ode_solver(&mut s, soln, None, false, false);
+    }
+
+    #[test]
+    fn test_radau_nalgebra_robertson_ode() {
+        let (problem, soln) = robertson_ode::<M>(false, 1);
+        let mut s = problem.radau::<LS>().unwrap();
+        test_ode_solver(&mut s, soln, None, false, false);
+        assert!(problem.eqn.rhs().statistics().number_of_matrix_evals > 0);
+    }
+
+    #[test]
+    fn test_tstop_radau() {
+        let (problem, soln) = exponential_decay_problem::<M>(false);
+        let mut s = problem.radau::<LS>().unwrap();
+        test_ode_solver(&mut s, soln, None, true, false);
+    }
+
+    #[test]
+    fn test_root_finder_radau() {
+        let (problem, soln) = exponential_decay_problem_with_root::<M>(false);
+        let mut s = problem.radau::<LS>().unwrap();
+        let y = test_ode_solver(&mut s, soln, None, false, false);
+        assert!(abs(y[0] - 0.6) < 1e-6, "y[0] = {}", y[0]);
+    }
+}
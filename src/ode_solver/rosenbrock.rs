@@ -0,0 +1,1066 @@
+use crate::error::DiffsolError;
+use crate::error::OdeSolverError;
+use crate::matrix::MatrixRef;
+use crate::ode_solver_error;
+use crate::vector::VectorRef;
+use crate::{
+    op::sdirk::SdirkCallable, scale, AugmentedOdeEquations, DefaultDenseMatrix, DenseMatrix,
+    LinearOp, LinearSolver, MatrixView, NoAug, NonLinearOp, OdeEquationsImplicit, OdeEquationsSens,
+    OdeSolverMethod, OdeSolverProblem, OdeSolverState, OdeSolverStopReason, Op, RootFinder,
+    RosenbrockTableau, Scalar, SdirkState, SensEquations, StateRef, StateRefMut, Vector,
+    VectorViewMut,
+};
+use nalgebra::ComplexField;
+use num_traits::abs;
+use num_traits::One;
+use num_traits::Pow;
+use num_traits::Zero;
+
+use super::bdf::BdfStatistics;
+use super::method::{
+    check_interpolation_time, check_tstop, init_root_finder, AugmentedOdeSolverMethod,
+    SensitivitiesOdeSolverMethod, TstopCheck,
+};
+
+impl<'a, M, Eqn, LS, AugEqn> AugmentedOdeSolverMethod<'a, Eqn, AugEqn>
+    for Rosenbrock<'a, Eqn, LS, M, AugEqn>
+where
+    Eqn: OdeEquationsImplicit,
+    AugEqn: AugmentedOdeEquations<Eqn>,
+    M: DenseMatrix<T = Eqn::T, V = Eqn::V>,
+    LS: LinearSolver<Eqn::M>,
+    Eqn::V: DefaultDenseMatrix<T = Eqn::T>,
+    for<'b> &'b Eqn::V: VectorRef<Eqn::V>,
+    for<'b> &'b Eqn::M: MatrixRef<Eqn::M>,
+{
+    fn into_state_and_eqn(self) -> (Self::State, Option<AugEqn>) {
+        (self.state, self.s_eqn)
+    }
+}
+
+impl<'a, M, Eqn, LS> SensitivitiesOdeSolverMethod<'a, Eqn>
+    for Rosenbrock<'a, Eqn, LS, M, SensEquations<'a, Eqn>>
+where
+    Eqn: OdeEquationsSens,
+    M: DenseMatrix<T = Eqn::T, V = Eqn::V>,
+    LS: LinearSolver<Eqn::M>,
+    Eqn::V: DefaultDenseMatrix<T = Eqn::T>,
+    for<'b> &'b Eqn::V: VectorRef<Eqn::V>,
+    for<'b> &'b Eqn::M: MatrixRef<Eqn::M>,
+{
+}
+
+/// A Rosenbrock (linearly implicit Runge-Kutta) method with an embedded error estimate, suitable for moderately stiff problems.
+///
+/// The particular method is defined by the [RosenbrockTableau] used to create the solver.
+/// Each stage requires a single linear solve with the matrix `M - h gamma J`, where `J` is the Jacobian of the right-hand side at the start of the step,
+/// so no nonlinear solver is needed. The Jacobian is re-evaluated and the matrix factorised once per step, and refactorised if the step is rejected.
+/// Problems with a mass matrix (including singular mass matrices) are supported.
+///
+/// The time derivative of the right-hand side is approximated using finite differences, as is the dependence of the output function
+/// and any forward sensitivity equations on the state (these are not included in the Jacobian).
+///
+/// Restrictions:
+/// - The diagonal and upper triangular part of the `a` and `c_mat` matrices must be zero.
+/// - The first element of the `c` vector must be 0.
+pub struct Rosenbrock<
+    'a,
+    Eqn,
+    LS,
+    M = <<Eqn as Op>::V as DefaultDenseMatrix>::M,
+    AugmentedEqn = NoAug<Eqn>,
+> where
+    M: DenseMatrix<T = Eqn::T, V = Eqn::V>,
+    LS: LinearSolver<Eqn::M>,
+    Eqn: OdeEquationsImplicit,
+    Eqn::V: DefaultDenseMatrix<T = Eqn::T>,
+    AugmentedEqn: AugmentedOdeEquations<Eqn>,
+{
+    tableau: RosenbrockTableau<M>,
+    problem: &'a OdeSolverProblem<Eqn>,
+    op: SdirkCallable<&'a Eqn>,
+    linear_solver: LS,
+    state: SdirkState<Eqn::V>,
+    diff: M,
+    sdiff: Vec<M>,
+    sgdiff: Vec<M>,
+    gdiff: M,
+    s_eqn: Option<AugmentedEqn>,
+    old_t: Eqn::T,
+    old_y: Eqn::V,
+    old_y_sens: Vec<Eqn::V>,
+    old_g: Eqn::V,
+    f0: Eqn::V,
+    ft: Eqn::V,
+    g0: Eqn::V,
+    gt: Eqn::V,
+    f0_sens: Vec<Eqn::V>,
+    ft_sens: Vec<Eqn::V>,
+    g0_sens: Vec<Eqn::V>,
+    gt_sens: Vec<Eqn::V>,
+    a_rows: Vec<Eqn::V>,
+    c_rows: Vec<Eqn::V>,
+    dy_weights: Eqn::V,
+    statistics: BdfStatistics,
+    root_finder: Option<RootFinder<Eqn::V>>,
+    tstop: Option<Eqn::T>,
+    is_state_mutated: bool,
+}
+
+impl<M, Eqn, LS, AugmentedEqn> Clone for Rosenbrock<'_, Eqn, LS, M, AugmentedEqn>
+where
+    M: DenseMatrix<T = Eqn::T, V = Eqn::V>,
+    LS: LinearSolver<Eqn::M>,
+    Eqn: OdeEquationsImplicit,
+    Eqn::V: DefaultDenseMatrix<T = Eqn::T>,
+    AugmentedEqn: AugmentedOdeEquations<Eqn>,
+    for<'b> &'b Eqn::V: VectorRef<Eqn::V>,
+    for<'b> &'b Eqn::M: MatrixRef<Eqn::M>,
+{
+    fn clone(&self) -> Self {
+        let problem = self.problem;
+        let op = self.op.clone_state(&problem.eqn);
+        let mut linear_solver = LS::default();
+        linear_solver.set_problem(&op);
+        Self {
+            tableau: self.tableau.clone(),
+            problem,
+            op,
+            linear_solver,
+            state: self.state.clone(),
+            diff: self.diff.clone(),
+            sdiff: self.sdiff.clone(),
+            sgdiff: self.sgdiff.clone(),
+            gdiff: self.gdiff.clone(),
+            s_eqn: self.s_eqn.clone(),
+            old_t: self.old_t,
+            old_y: self.old_y.clone(),
+            old_y_sens: self.old_y_sens.clone(),
+            old_g: self.old_g.clone(),
+            f0: self.f0.clone(),
+            ft: self.ft.clone(),
+            g0: self.g0.clone(),
+            gt: self.gt.clone(),
+            f0_sens: self.f0_sens.clone(),
+            ft_sens: self.ft_sens.clone(),
+            g0_sens: self.g0_sens.clone(),
+            gt_sens: self.gt_sens.clone(),
+            a_rows: self.a_rows.clone(),
+            c_rows: self.c_rows.clone(),
+            dy_weights: self.dy_weights.clone(),
+            statistics: self.statistics.clone(),
+            root_finder: self.root_finder.clone(),
+            tstop: self.tstop,
+            is_state_mutated: self.is_state_mutated,
+        }
+    }
+}
+
+impl<'a, M, Eqn, LS, AugmentedEqn> Rosenbrock<'a, Eqn, LS, M, AugmentedEqn>
+where
+    M: DenseMatrix<T = Eqn::T, V = Eqn::V>,
+    LS: LinearSolver<Eqn::M>,
+    Eqn: OdeEquationsImplicit,
+    Eqn::V: DefaultDenseMatrix<T = Eqn::T>,
+    AugmentedEqn: AugmentedOdeEquations<Eqn>,
+    for<'b> &'b Eqn::V: VectorRef<Eqn::V>,
+    for<'b> &'b Eqn::M: MatrixRef<Eqn::M>,
+{
+    const SAFETY: f64 = 0.9;
+    const MIN_FACTOR: f64 = 0.2;
+    const MAX_FACTOR: f64 = 10.0;
+    const MIN_TIMESTEP: f64 = 1e-13;
+
+    pub fn new(
+        problem: &'a OdeSolverProblem<Eqn>,
+        mut state: SdirkState<Eqn::V>,
+        tableau: RosenbrockTableau<M>,
+        mut linear_solver: LS,
+    ) -> Result<Self, DiffsolError> {
+        // check that the diagonal and upper triangular part of a and c_mat is zero
+        let s = tableau.s();
+        for i in 0..s {
+            for j in i..s {
+                assert_eq!(
+                    tableau.a()[(i, j)],
+                    Eqn::T::zero(),
+                    "Invalid tableau, expected a(i, j) = 0 for j >= i"
+                );
+                assert_eq!(
+                    tableau.c_mat()[(i, j)],
+                    Eqn::T::zero(),
+                    "Invalid tableau, expected c_mat(i, j) = 0 for j >= i"
+                );
+            }
+        }
+
+        // check that the first c is 0
+        assert_eq!(
+            tableau.c()[0],
+            Eqn::T::zero(),
+            "Invalid tableau, expected c(0) = 0"
+        );
+
+        let mut a_rows = Vec::with_capacity(s);
+        let mut c_rows = Vec::with_capacity(s);
+        for i in 0..s {
+            let mut a_row = Vec::with_capacity(i);
+            let mut c_row = Vec::with_capacity(i);
+            for j in 0..i {
+                a_row.push(tableau.a()[(i, j)]);
+                c_row.push(tableau.c_mat()[(i, j)]);
+            }
+            a_rows.push(Eqn::V::from_vec(a_row));
+            c_rows.push(Eqn::V::from_vec(c_row));
+        }
+
+        // weights for the derivative of the continuous extension at the end of the step
+        let mut dy_weights = tableau.b().clone();
+        dy_weights.axpy_v(-Eqn::T::one(), &tableau.dense().column(0), Eqn::T::one());
+        dy_weights.axpy_v(-Eqn::T::one(), &tableau.dense().column(1), Eqn::T::one());
+
+        // the linear system for each stage is M - h gamma J, which is the jacobian of the sdirk callable with c = gamma
+        let op = SdirkCallable::new(&problem.eqn, tableau.gamma());
+        op.set_h(state.h);
+        linear_solver.set_problem(&op);
+
+        state.check_consistent_with_problem(problem)?;
+
+        let nstates = state.y.len();
+        let diff = M::zeros(nstates, s);
+        let nout = if problem.integrate_out {
+            problem.eqn.out().unwrap().nout()
+        } else {
+            0
+        };
+        let gdiff = M::zeros(nout, s);
+
+        let old_t = state.t;
+        let old_y = state.y.clone();
+        let old_g = if problem.integrate_out {
+            state.g.clone()
+        } else {
+            <Eqn::V as Vector>::zeros(0)
+        };
+
+        state.set_problem(problem)?;
+        let root_finder = if let Some(root_fn) = problem.eqn.root() {
+            let root_finder = RootFinder::new(root_fn.nout());
+            root_finder.init(&root_fn, &state.y, state.t);
+            Some(root_finder)
+        } else {
+            None
+        };
+
+        Ok(Self {
+            tableau,
+            problem,
+            op,
+            linear_solver,
+            state,
+            diff,
+            sdiff: vec![],
+            sgdiff: vec![],
+            gdiff,
+            s_eqn: None,
+            old_t,
+            old_y,
+            old_y_sens: vec![],
+            old_g,
+            f0: <Eqn::V as Vector>::zeros(nstates),
+            ft: <Eqn::V as Vector>::zeros(nstates),
+            g0: <Eqn::V as Vector>::zeros(nout),
+            gt: <Eqn::V as Vector>::zeros(nout),
+            f0_sens: vec![],
+            ft_sens: vec![],
+            g0_sens: vec![],
+            gt_sens: vec![],
+            a_rows,
+            c_rows,
+            dy_weights,
+            statistics: BdfStatistics::default(),
+            root_finder,
+            tstop: None,
+            is_state_mutated: false,
+        })
+    }
+
+    pub fn new_augmented(
+        problem: &'a OdeSolverProblem<Eqn>,
+        state: SdirkState<Eqn::V>,
+        tableau: RosenbrockTableau<M>,
+        linear_solver: LS,
+        augmented_eqn: AugmentedEqn,
+    ) -> Result<Self, DiffsolError> {
+        state.check_sens_consistent_with_problem(problem, &augmented_eqn)?;
+        if !augmented_eqn.integrate_main_eqn() {
+            return Err(ode_solver_error!(
+                Other,
+                "Rosenbrock solvers must integrate the main equations alongside the augmented equations"
+            ));
+        }
+        let mut ret = Self::new(problem, state, tableau, linear_solver)?;
+        let naug = augmented_eqn.max_index();
+        let nstates = augmented_eqn.rhs().nstates();
+        let s = ret.tableau.s();
+        ret.sdiff = vec![M::zeros(nstates, s); naug];
+        ret.f0_sens = vec![<Eqn::V as Vector>::zeros(nstates); naug];
+        ret.ft_sens = vec![<Eqn::V as Vector>::zeros(nstates); naug];
+        ret.old_y_sens = ret.state.s.clone();
+        if let Some(out) = augmented_eqn.out() {
+            ret.sgdiff = vec![M::zeros(out.nout(), s); naug];
+            ret.g0_sens = vec![<Eqn::V as Vector>::zeros(out.nout()); naug];
+            ret.gt_sens = vec![<Eqn::V as Vector>::zeros(out.nout()); naug];
+        }
+        ret.s_eqn = Some(augmented_eqn);
+        Ok(ret)
+    }
+
+    pub fn get_statistics(&self) -> &BdfStatistics {
+        &self.statistics
+    }
+
+    fn handle_tstop(
+        &mut self,
+        tstop: Eqn::T,
+    ) -> Result<Option<OdeSolverStopReason<Eqn::T>>, DiffsolError> {
+        match check_tstop(self.state.t, self.state.h, tstop)? {
+            TstopCheck::Reached => {
+                self.tstop = None;
+                return Ok(Some(OdeSolverStopReason::TstopReached));
+            }
+            TstopCheck::ScaleStep(factor) => {
+                self.state.h *= factor;
+            }
+            TstopCheck::NotReached => {}
+        }
+        Ok(None)
+    }
+
+    /// increment used to approximate the time derivatives at the start of the step
+    fn time_increment(t: Eqn::T, h: Eqn::T) -> Eqn::T {
+        let dt = Eqn::T::EPSILON.sqrt() * (Eqn::T::one() + abs(t));
+        if h < Eqn::T::zero() {
+            -dt
+        } else {
+            dt
+        }
+    }
+
+    /// increment used to approximate the directional derivative at `y` in direction `v`, or `None` if `v` is zero
+    fn direction_increment(y: &Eqn::V, v: &Eqn::V) -> Option<Eqn::T> {
+        let vnorm = v.norm();
+        if vnorm == Eqn::T::zero() {
+            None
+        } else {
+            Some(Eqn::T::EPSILON.sqrt() * (Eqn::T::one() + y.norm()) / vnorm)
+        }
+    }
+
+    /// y = M x + y, or y = x + y if there is no mass matrix
+    fn mass_mul_add(problem: &OdeSolverProblem<Eqn>, x: &Eqn::V, t: Eqn::T, y: &mut Eqn::V) {
+        if let Some(mass) = problem.eqn.mass() {
+            mass.gemv_inplace(x, t, Eqn::T::one(), y);
+        } else {
+            y.axpy(Eqn::T::one(), x, Eqn::T::one());
+        }
+    }
+
+    /// evaluate the right-hand side and output functions, and approximate their time derivatives, at the start of the step
+    fn evaluate_start_of_step(&mut self) {
+        let problem = self.problem;
+        let t0 = self.state.t;
+        let dt = Self::time_increment(t0, self.state.h);
+        let rhs = problem.eqn.rhs();
+        rhs.call_inplace(&self.state.y, t0, &mut self.f0);
+        rhs.call_inplace(&self.state.y, t0 + dt, &mut self.ft);
+        self.ft
+            .axpy(-Eqn::T::one() / dt, &self.f0, Eqn::T::one() / dt);
+        if problem.integrate_out {
+            let out = problem.eqn.out().unwrap();
+            out.call_inplace(&self.state.y, t0, &mut self.g0);
+            out.call_inplace(&self.state.y, t0 + dt, &mut self.gt);
+            self.gt
+                .axpy(-Eqn::T::one() / dt, &self.g0, Eqn::T::one() / dt);
+        }
+        if let Some(s_eqn) = self.s_eqn.as_mut() {
+            s_eqn.update_rhs_out_state(&self.state.y, &self.f0, t0);
+            for j in 0..self.sdiff.len() {
+                s_eqn.set_index(j);
+                s_eqn
+                    .rhs()
+                    .call_inplace(&self.state.s[j], t0, &mut self.f0_sens[j]);
+                if let Some(out) = s_eqn.out() {
+                    out.call_inplace(&self.state.s[j], t0, &mut self.g0_sens[j]);
+                }
+            }
+            s_eqn.update_rhs_out_state(&self.state.y, &self.f0, t0 + dt);
+            for j in 0..self.sdiff.len() {
+                s_eqn.set_index(j);
+                s_eqn
+                    .rhs()
+                    .call_inplace(&self.state.s[j], t0 + dt, &mut self.ft_sens[j]);
+                self.ft_sens[j].axpy(-Eqn::T::one() / dt, &self.f0_sens[j], Eqn::T::one() / dt);
+                if let Some(out) = s_eqn.out() {
+                    out.call_inplace(&self.state.s[j], t0 + dt, &mut self.gt_sens[j]);
+                    self.gt_sens[j].axpy(-Eqn::T::one() / dt, &self.g0_sens[j], Eqn::T::one() / dt);
+                }
+            }
+        }
+    }
+
+    /// calculate stage `i` of the output function, given the main stage `u`
+    fn integrate_out_stage(&mut self, i: usize, t: Eqn::T, h: Eqn::T, y_i: &Eqn::V, u: &Eqn::V) {
+        let t0 = self.state.t;
+        let out = self.problem.eqn.out().unwrap();
+        let one = Eqn::T::one();
+
+        // r_i = g(t_i, y_i) + h gamma_sum_i g_t + sum_{j < i} (c_ij / h) u_j
+        let mut r = out.call(y_i, t);
+        r.axpy(h * self.tableau.gamma_sum()[i], &self.gt, one);
+        if i > 0 {
+            self.gdiff
+                .columns(0, i)
+                .gemv_o(one / h, &self.c_rows[i], one, &mut r);
+        }
+
+        // the output function is not part of the jacobian, so its dependence on y is approximated using finite differences
+        if let Some(eps) = Self::direction_increment(&self.state.y, u) {
+            let mut y_pert = self.state.y.clone();
+            y_pert.axpy(eps, u, one);
+            let g_pert = out.call(&y_pert, t0);
+            r.axpy(one / eps, &g_pert, one);
+            r.axpy(-one / eps, &self.g0, one);
+        }
+
+        // the iteration matrix for the output is the identity / (h gamma)
+        self.gdiff
+            .column_mut(i)
+            .axpy(h * self.tableau.gamma(), &r, Eqn::T::zero());
+    }
+
+    /// calculate stage `i` of the augmented equations, given the main stage value `y_i`, the rhs `f_i` at this stage value and the main stage `u`
+    fn integrate_sensitivities(
+        &mut self,
+        i: usize,
+        t: Eqn::T,
+        h: Eqn::T,
+        y_i: &Eqn::V,
+        f_i: &Eqn::V,
+        u: &Eqn::V,
+    ) -> Result<(), DiffsolError> {
+        let problem = self.problem;
+        let t0 = self.state.t;
+        let one = Eqn::T::one();
+        let zero = Eqn::T::zero();
+        let hgamma = h * self.tableau.gamma();
+        let gamma_sum = self.tableau.gamma_sum()[i];
+        let naug = self.sdiff.len();
+        let s_eqn = self.s_eqn.as_mut().unwrap();
+        let nstates = s_eqn.rhs().nstates();
+        let nout = s_eqn.out().map(|out| out.nout()).unwrap_or(0);
+
+        // the augmented equations depend on y, which is not part of their jacobian, so this dependence is approximated using finite differences
+        let mut ky = vec![<Eqn::V as Vector>::zeros(nstates); naug];
+        let mut kg = vec![<Eqn::V as Vector>::zeros(nout); naug];
+        if let Some(eps) = Self::direction_increment(&self.state.y, u) {
+            let mut y_pert = self.state.y.clone();
+            y_pert.axpy(eps, u, one);
+            s_eqn.update_rhs_out_state(&y_pert, &self.f0, t0);
+            for j in 0..naug {
+                s_eqn.set_index(j);
+                s_eqn.rhs().call_inplace(&self.state.s[j], t0, &mut ky[j]);
+                ky[j].axpy(-one / eps, &self.f0_sens[j], one / eps);
+                if let Some(out) = s_eqn.out() {
+                    out.call_inplace(&self.state.s[j], t0, &mut kg[j]);
+                    kg[j].axpy(-one / eps, &self.g0_sens[j], one / eps);
+                }
+            }
+        }
+
+        let mut s_i = <Eqn::V as Vector>::zeros(nstates);
+        let mut tmp = <Eqn::V as Vector>::zeros(nstates);
+        let mut rg = vec![<Eqn::V as Vector>::zeros(nout); naug];
+        s_eqn.update_rhs_out_state(y_i, f_i, t);
+        for j in 0..naug {
+            s_eqn.set_index(j);
+
+            // stage value s_i = s0 + sum_{k < i} a_ik us_k
+            s_i.copy_from(&self.state.s[j]);
+            if i > 0 {
+                self.sdiff[j]
+                    .columns(0, i)
+                    .gemv_o(one, &self.a_rows[i], one, &mut s_i);
+            }
+
+            // r_i = f_s(t_i, y_i, s_i) + h gamma_sum_i f_s_t + (df_s/dy) u + M sum_{k < i} (c_ik / h) us_k
+            let mut r = s_eqn.rhs().call(&s_i, t);
+            r.axpy(h * gamma_sum, &self.ft_sens[j], one);
+            r.axpy(one, &ky[j], one);
+            if i > 0 {
+                self.sdiff[j]
+                    .columns(0, i)
+                    .gemv_o(one / h, &self.c_rows[i], zero, &mut tmp);
+                Self::mass_mul_add(problem, &tmp, t0, &mut r);
+            }
+            r *= scale(hgamma);
+            self.linear_solver.solve_in_place(&mut r)?;
+            self.sdiff[j].column_mut(i).copy_from(&r);
+
+            // the sensitivity outputs, apart from their dependence on s which is added below
+            if let Some(out) = s_eqn.out() {
+                out.call_inplace(&s_i, t, &mut rg[j]);
+                rg[j].axpy(h * gamma_sum, &self.gt_sens[j], one);
+                rg[j].axpy(one, &kg[j], one);
+                if i > 0 {
+                    self.sgdiff[j]
+                        .columns(0, i)
+                        .gemv_o(one / h, &self.c_rows[i], one, &mut rg[j]);
+                }
+            }
+        }
+
+        // the sensitivity outputs are linear in s, so their dependence on s can be calculated exactly at the start of the step
+        if nout > 0 {
+            s_eqn.update_rhs_out_state(&self.state.y, &self.f0, t0);
+            let mut g = <Eqn::V as Vector>::zeros(nout);
+            for (j, rg_j) in rg.iter_mut().enumerate() {
+                s_eqn.set_index(j);
+                s_i.copy_from(&self.state.s[j]);
+                s_i.axpy_v(one, &self.sdiff[j].column(i), one);
+                s_eqn.out().unwrap().call_inplace(&s_i, t0, &mut g);
+                rg_j.axpy(one, &g, one);
+                rg_j.axpy(-one, &self.g0_sens[j], one);
+                self.sgdiff[j].column_mut(i).axpy(hgamma, rg_j, zero);
+            }
+        }
+        Ok(())
+    }
+
+    fn interpolate_weights(&self, theta: Eqn::T) -> Eqn::V {
+        // w = theta b + theta (1 - theta) (h1 + theta h2)
+        let one = Eqn::T::one();
+        let mut w = self.tableau.b().clone();
+        w *= scale(theta);
+        let thetav = Eqn::V::from_vec(vec![one, theta]);
+        self.tableau
+            .dense()
+            .gemv(theta * (one - theta), &thetav, one, &mut w);
+        w
+    }
+
+    fn interpolate_from_diff(y0: &Eqn::V, w: &Eqn::V, diff: &M) -> Eqn::V {
+        let mut ret = y0.clone();
+        diff.gemv(Eqn::T::one(), w, Eqn::T::one(), &mut ret);
+        ret
+    }
+
+    fn interpolation_theta(&self, t: Eqn::T) -> Result<Eqn::T, DiffsolError> {
+        let state = &self.state;
+
+        // check that t is within the last step
+        check_interpolation_time(t, self.old_t, state.t)?;
+        let dt = state.t - self.old_t;
+        if dt == Eqn::T::zero() {
+            Ok(Eqn::T::one())
+        } else {
+            Ok((t - self.old_t) / dt)
+        }
+    }
+
+    fn _update_step_size(&mut self, factor: Eqn::T) -> Result<Eqn::T, DiffsolError> {
+        let new_h = self.state.h * factor;
+
+        // if step size too small, then fail
+        if abs(new_h) < Eqn::T::from(Self::MIN_TIMESTEP) {
+            return Err(DiffsolError::from(OdeSolverError::StepSizeTooSmall {
+                time: self.state.t.into(),
+            }));
+        }
+
+        // update state
+        self.state.h = new_h;
+
+        Ok(new_h)
+    }
+}
+
+impl<'a, M, Eqn, LS, AugmentedEqn> OdeSolverMethod<'a, Eqn>
+    for Rosenbrock<'a, Eqn, LS, M, AugmentedEqn>
+where
+    M: DenseMatrix<T = Eqn::T, V = Eqn::V>,
+    LS: LinearSolver<Eqn::M>,
+    Eqn: OdeEquationsImplicit,
+    Eqn::V: DefaultDenseMatrix<T = Eqn::T>,
+    AugmentedEqn: AugmentedOdeEquations<Eqn>,
+    for<'b> &'b Eqn::V: VectorRef<Eqn::V>,
+    for<'b> &'b Eqn::M: MatrixRef<Eqn::M>,
+{
+    type State = SdirkState<Eqn::V>;
+
+    fn problem(&self) -> &'a OdeSolverProblem<Eqn> {
+        self.problem
+    }
+
+    fn order(&self) -> usize {
+        self.tableau.order()
+    }
+
+    fn set_state(&mut self, state: Self::State) {
+        self.state = state;
+    }
+
+    fn into_state(self) -> SdirkState<Eqn::V> {
+        self.state
+    }
+
+    fn checkpoint(&mut self) -> Self::State {
+        self.state.clone()
+    }
+
+    fn step(&mut self) -> Result<OdeSolverStopReason<Eqn::T>, DiffsolError> {
+        let n = self.state.y.len();
+        let problem = self.problem;
+        let one = Eqn::T::one();
+        let zero = Eqn::T::zero();
+
+        if self.is_state_mutated {
+            // reinitialise root finder if needed
+            init_root_finder(
+                problem,
+                self.root_finder.as_ref(),
+                &self.state.y,
+                self.state.t,
+            );
+            // reinitialise tstop if needed
+            if let Some(t_stop) = self.tstop {
+                self.set_stop_time(t_stop)?;
+            }
+
+            self.is_state_mutated = false;
+        }
+
+        let mut error = <Eqn::V as Vector>::zeros(n);
+        let out_error_control = problem.output_in_error_control();
+        let mut out_error = if out_error_control {
+            <Eqn::V as Vector>::zeros(problem.eqn.out().unwrap().nout())
+        } else {
+            <Eqn::V as Vector>::zeros(0)
+        };
+        let sens_error_control =
+            self.s_eqn.is_some() && self.s_eqn.as_ref().unwrap().include_in_error_control();
+        let mut sens_error = if sens_error_control {
+            <Eqn::V as Vector>::zeros(self.s_eqn.as_ref().unwrap().rhs().nstates())
+        } else {
+            <Eqn::V as Vector>::zeros(0)
+        };
+        let sens_out_error_control =
+            self.s_eqn.is_some() && self.s_eqn.as_ref().unwrap().include_out_in_error_control();
+        let mut sens_out_error = if sens_out_error_control {
+            <Eqn::V as Vector>::zeros(self.s_eqn.as_ref().unwrap().out().unwrap().nout())
+        } else {
+            <Eqn::V as Vector>::zeros(0)
+        };
+
+        let mut y_i = <Eqn::V as Vector>::zeros(n);
+        let mut f_i = <Eqn::V as Vector>::zeros(n);
+        let mut u = <Eqn::V as Vector>::zeros(n);
+        let mut tmp = <Eqn::V as Vector>::zeros(n);
+
+        // the jacobian is evaluated once per step at the start of the step
+        let t0 = self.state.t;
+        self.evaluate_start_of_step();
+        self.op.set_phi_direct(self.state.y.clone());
+        self.op.set_jacobian_is_stale();
+
+        let mut factor: Eqn::T;
+
+        // loop until step is accepted
+        loop {
+            let h = self.state.h;
+            let hgamma = h * self.tableau.gamma();
+
+            // form and factorise M - h gamma J, the jacobian is only re-evaluated on the first attempt
+            self.op.set_h(h);
+            tmp.fill(zero);
+            self.linear_solver.set_linearisation(&self.op, &tmp, t0);
+            self.statistics.number_of_linear_solver_setups += 1;
+
+            for i in 0..self.tableau.s() {
+                let t = t0 + self.tableau.c()[i] * h;
+
+                // stage value y_i = y0 + sum_{j < i} a_ij u_j
+                y_i.copy_from(&self.state.y);
+                if i == 0 {
+                    f_i.copy_from(&self.f0);
+                } else {
+                    self.diff
+                        .columns(0, i)
+                        .gemv_o(one, &self.a_rows[i], one, &mut y_i);
+                    problem.eqn.rhs().call_inplace(&y_i, t, &mut f_i);
+                }
+
+                // r_i = f(t_i, y_i) + h gamma_sum_i f_t + M sum_{j < i} (c_ij / h) u_j
+                u.copy_from(&f_i);
+                u.axpy(h * self.tableau.gamma_sum()[i], &self.ft, one);
+                if i > 0 {
+                    self.diff
+                        .columns(0, i)
+                        .gemv_o(one / h, &self.c_rows[i], zero, &mut tmp);
+                    Self::mass_mul_add(problem, &tmp, t0, &mut u);
+                }
+
+                // solve (M - h gamma J) u_i = h gamma r_i
+                u *= scale(hgamma);
+                self.linear_solver.solve_in_place(&mut u)?;
+                self.diff.column_mut(i).copy_from(&u);
+
+                if problem.integrate_out {
+                    self.integrate_out_stage(i, t, h, &y_i, &u);
+                }
+
+                if self.s_eqn.is_some() {
+                    self.integrate_sensitivities(i, t, h, &y_i, &f_i, &u)?;
+                }
+            }
+
+            // form the solution at the end of the step in old_y
+            self.old_y.copy_from(&self.state.y);
+            self.diff.gemv(one, self.tableau.b(), one, &mut self.old_y);
+            for j in 0..self.sdiff.len() {
+                self.old_y_sens[j].copy_from(&self.state.s[j]);
+                self.sdiff[j].gemv(one, self.tableau.b(), one, &mut self.old_y_sens[j]);
+            }
+
+            // compute the error
+            let mut ncontributions = 0;
+            let mut error_norm = Eqn::T::zero();
+            self.diff.gemv(one, self.tableau.d(), zero, &mut error);
+            error_norm += error.squared_norm(&self.old_y, &problem.atol, problem.rtol);
+            ncontributions += 1;
+
+            // output errors
+            if out_error_control {
+                self.gdiff.gemv(one, self.tableau.d(), zero, &mut out_error);
+                let atol = problem.out_atol.as_ref().unwrap();
+                let rtol = problem.out_rtol.unwrap();
+                error_norm += out_error.squared_norm(&self.state.g, atol, rtol);
+                ncontributions += 1;
+            }
+
+            // sensitivity errors
+            if sens_error_control {
+                let atol = self.s_eqn.as_ref().unwrap().atol().unwrap();
+                let rtol = self.s_eqn.as_ref().unwrap().rtol().unwrap();
+                for i in 0..self.sdiff.len() {
+                    self.sdiff[i].gemv(one, self.tableau.d(), zero, &mut sens_error);
+                    error_norm += sens_error.squared_norm(&self.old_y_sens[i], atol, rtol);
+                    ncontributions += 1;
+                }
+            }
+
+            // sensitivity output errors
+            if sens_out_error_control {
+                let atol = self.s_eqn.as_ref().unwrap().out_atol().unwrap();
+                let rtol = self.s_eqn.as_ref().unwrap().out_rtol().unwrap();
+                for i in 0..self.sgdiff.len() {
+                    self.sgdiff[i].gemv(one, self.tableau.d(), zero, &mut sens_out_error);
+                    error_norm += sens_out_error.squared_norm(&self.state.sg[i], atol, rtol);
+                    ncontributions += 1;
+                }
+            }
+            if ncontributions > 1 {
+                error_norm /= Eqn::T::from(ncontributions as f64);
+            }
+
+            // adjust step size based on error, the error estimate is of the same order as the method
+            let order = self.tableau.order() as f64;
+            factor = Eqn::T::from(Self::SAFETY) * error_norm.pow(Eqn::T::from(-0.5 / order));
+            if factor < Eqn::T::from(Self::MIN_FACTOR) {
+                factor = Eqn::T::from(Self::MIN_FACTOR);
+            }
+            if factor > Eqn::T::from(Self::MAX_FACTOR) {
+                factor = Eqn::T::from(Self::MAX_FACTOR);
+            }
+
+            // test error is within tolerance
+            if error_norm <= Eqn::T::from(1.0) {
+                break;
+            }
+            // step is rejected, factor reduces step size, so we try again with the smaller step size
+            self.statistics.number_of_error_test_failures += 1;
+            self._update_step_size(factor)?;
+        }
+
+        // take the step
+        {
+            let state = &mut self.state;
+            let h = state.h;
+            self.old_t = state.t;
+            state.t += h;
+
+            // old_y is the new y, the derivatives are from the continuous extension
+            std::mem::swap(&mut self.old_y, &mut state.y);
+            self.diff
+                .gemv(one / h, &self.dy_weights, zero, &mut state.dy);
+
+            for i in 0..self.sdiff.len() {
+                std::mem::swap(&mut self.old_y_sens[i], &mut state.s[i]);
+                self.sdiff[i].gemv(one / h, &self.dy_weights, zero, &mut state.ds[i]);
+            }
+
+            for i in 0..self.sgdiff.len() {
+                self.sgdiff[i].gemv(one, self.tableau.b(), one, &mut state.sg[i]);
+                self.sgdiff[i].gemv(one / h, &self.dy_weights, zero, &mut state.dsg[i]);
+            }
+
+            // integrate output function
+            if problem.integrate_out {
+                self.old_g.copy_from(&state.g);
+                self.gdiff.gemv(one, self.tableau.b(), one, &mut state.g);
+                self.gdiff
+                    .gemv(one / h, &self.dy_weights, zero, &mut state.dg);
+            }
+        }
+
+        // update step size for next step
+        self._update_step_size(factor)?;
+
+        // update statistics
+        self.statistics.number_of_steps += 1;
+
+        // check for root within accepted step
+        if let Some(root_fn) = problem.eqn.root() {
+            let ret = self.root_finder.as_ref().unwrap().check_root(
+                &|t| self.interpolate(t),
+                &root_fn,
+                &self.state.y,
+                self.state.t,
+            );
+            if let Some(root) = ret {
+                return Ok(OdeSolverStopReason::RootFound(root));
+            }
+        }
+
+        // check if we are at tstop
+        if let Some(tstop) = self.tstop {
+            if let Some(reason) = self.handle_tstop(tstop).unwrap() {
+                return Ok(reason);
+            }
+        }
+
+        // just a normal step, no roots or tstop reached
+        Ok(OdeSolverStopReason::InternalTimestep)
+    }
+
+    fn set_stop_time(&mut self, tstop: <Eqn as Op>::T) -> Result<(), DiffsolError> {
+        self.tstop = Some(tstop);
+        if let Some(OdeSolverStopReason::TstopReached) = self.handle_tstop(tstop)? {
+            let error = OdeSolverError::StopTimeBeforeCurrentTime {
+                stop_time: tstop.into(),
+                state_time: self.state.t.into(),
+            };
+            self.tstop = None;
+            return Err(DiffsolError::from(error));
+        }
+        Ok(())
+    }
+
+    fn interpolate_sens(&self, t: <Eqn as Op>::T) -> Result<Vec<<Eqn as Op>::V>, DiffsolError> {
+        if self.is_state_mutated {
+            if t == self.state.t {
+                return Ok(self.state.s.clone());
+            } else {
+                return Err(ode_solver_error!(InterpolationTimeOutsideCurrentStep));
+            }
+        }
+        let theta = self.interpolation_theta(t)?;
+        let w = self.interpolate_weights(theta);
+        let ret = self
+            .old_y_sens
+            .iter()
+            .zip(self.sdiff.iter())
+            .map(|(y, diff)| Self::interpolate_from_diff(y, &w, diff))
+            .collect();
+        Ok(ret)
+    }
+
+    fn interpolate(&self, t: <Eqn>::T) -> Result<<Eqn>::V, DiffsolError> {
+        if self.is_state_mutated {
+            if t == self.state.t {
+                return Ok(self.state.y.clone());
+            } else {
+                return Err(ode_solver_error!(InterpolationTimeOutsideCurrentStep));
+            }
+        }
+        let theta = self.interpolation_theta(t)?;
+        let w = self.interpolate_weights(theta);
+        Ok(Self::interpolate_from_diff(&self.old_y, &w, &self.diff))
+    }
+
+    fn interpolate_out(&self, t: <Eqn>::T) -> Result<<Eqn>::V, DiffsolError> {
+        if self.is_state_mutated {
+            if t == self.state.t {
+                return Ok(self.state.g.clone());
+            } else {
+                return Err(ode_solver_error!(InterpolationTimeOutsideCurrentStep));
+            }
+        }
+        let theta = self.interpolation_theta(t)?;
+        let w = self.interpolate_weights(theta);
+        Ok(Self::interpolate_from_diff(&self.old_g, &w, &self.gdiff))
+    }
+
+    fn state(&self) -> StateRef<Eqn::V> {
+        self.state.as_ref()
+    }
+
+    fn state_mut(&mut self) -> StateRefMut<Eqn::V> {
+        self.is_state_mutated = true;
+        self.state.as_mut()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        ode_solver::{
+            test_models::{
+                exponential_decay::{
+                    exponential_decay_problem, exponential_decay_problem_sens,
+                    exponential_decay_problem_with_root, negative_exponential_decay_problem,
+                },
+                robertson::{robertson, robertson_sens},
+                robertson_ode::robertson_ode,
+            },
+            tests::{
+                test_checkpointing, test_interpolate, test_ode_solver, test_problem,
+                test_state_mut, test_state_mut_on_problem,
+            },
+        },
+        NalgebraLU, OdeEquations, Op,
+    };
+
+    use num_traits::abs;
+
+    type M = nalgebra::DMatrix<f64>;
+    type LS = NalgebraLU<f64>;
+
+    #[test]
+    fn rosenbrock_state_mut() {
+        test_state_mut(test_problem::<M>().rodas4::<LS>().unwrap());
+    }
+
+    #[test]
+    fn rosenbrock_test_interpolate() {
+        test_interpolate(test_problem::<M>().rodas4::<LS>().unwrap());
+    }
+
+    #[test]
+    fn rosenbrock_test_checkpointing() {
+        let (problem, soln) = exponential_decay_problem::<M>(false);
+        let s1 = problem.rodas4::<LS>().unwrap();
+        let s2 = problem.rodas4::<LS>().unwrap();
+        test_checkpointing(soln, s1, s2);
+    }
+
+    #[test]
+    fn rosenbrock_test_state_mut_exponential_decay() {
+        let (p, soln) = exponential_decay_problem::<M>(false);
+        let s = p.rodas4::<LS>().unwrap();
+        test_state_mut_on_problem(s, soln);
+    }
+
+    #[test]
+    fn rosenbrock_test_nalgebra_negative_exponential_decay() {
+        let (problem, soln) = negative_exponential_decay_problem::<M>(false);
+        let mut s = problem.rodas4::<LS>().unwrap();
+        test_ode_solver(&mut s, soln, None, false, false);
+    }
+
+    #[test]
+    fn test_rodas4_nalgebra_exponential_decay() {
+        let (problem, soln) = exponential_decay_problem::<M>(false);
+        let mut s = problem.rodas4::<LS>().unwrap();
+        test_ode_solver(&mut s, soln, None, false, false);
+        let statistics = s.get_statistics();
+        assert_eq!(statistics.number_of_nonlinear_solver_iterations, 0);
+        assert_eq!(
+            statistics.number_of_linear_solver_setups,
+            statistics.number_of_steps + statistics.number_of_error_test_failures
+        );
+    }
+
+    #[test]
+    fn test_rodas4_nalgebra_exponential_decay_sens() {
+        let (problem, soln) = exponential_decay_problem_sens::<M>(false);
+        let mut s = problem.rodas4_sens::<LS>().unwrap();
+        test_ode_solver(&mut s, soln, None, false, true);
+    }
+
+    #[test]
+    fn test_rodas5_nalgebra_exponential_decay() {
+        let (problem, soln) = exponential_decay_problem::<M>(false);
+        let mut s = problem.rodas5::<LS>().unwrap();
+        test_ode_solver(&mut s, soln, None, false, false);
+    }
+
+    #[test]
+    fn test_rodas5p_nalgebra_exponential_decay() {
+        let (problem, soln) = exponential_decay_problem::<M>(false);
+        let mut s = problem.rodas5p::<LS>().unwrap();
+        test_ode_solver(&mut s, soln, None, false, false);
+    }
+
+    #[test]
+    fn test_rodas5p_nalgebra_exponential_decay_sens() {
+        let (problem, soln) = exponential_decay_problem_sens::<M>(false);
+        let mut s = problem.rodas5p_sens::<LS>().unwrap();
+        test_ode_solver(&mut s, soln, None, false, true);
+    }
+
+    #[test]
+    fn test_rodas4_nalgebra_robertson() {
+        let (problem, soln) = robertson::<M>(false);
+        let mut s = problem.rodas4::<LS>().unwrap();
+        test_ode_solver(&mut s, soln, None, false, false);
+        assert_eq!(s.get_statistics().number_of_nonlinear_solver_iterations, 0);
+    }
+
+    #[test]
+    fn test_rodas5p_nalgebra_robertson() {
+        let (problem, soln) = robertson::<M>(false);
+        let mut s = problem.rodas5p::<LS>().unwrap();
+        test_ode_solver(&mut s, soln, None, false, false);
+    }
+
+    #[test]
+    fn test_rodas4_nalgebra_robertson_sens() {
+        let (problem, soln) = robertson_sens::<M>();
+        let mut s = problem.rodas4_sens::<LS>().unwrap();
+        test_ode_solver(&mut s, soln, None, false, true);
+    }
+
+    #[test]
+    fn test_rodas5_nalgebra_robertson_ode() {
+        let (problem, soln) = robertson_ode::<M>(false, 1);
+        let mut s = problem.rodas5::<LS>().unwrap();
+        test_ode_solver(&mut s, soln, None, false, false);
+        assert!(problem.eqn.rhs().statistics().number_of_matrix_evals > 0);
+    }
+
+    #[test]
+    fn test_tstop_rodas4() {
+        let (problem, soln) = exponential_decay_problem::<M>(false);
+        let mut s = problem.rodas4::<LS>().unwrap();
+        test_ode_solver(&mut s, soln, None, true, false);
+    }
+
+    #[test]
+    fn test_root_finder_rodas4() {
+        let (problem, soln) = exponential_decay_problem_with_root::<M>(false);
+        let mut s = problem.rodas4::<LS>().unwrap();
+        let y = test_ode_solver(&mut s, soln, None, false, false);
+        assert!(abs(y[0] - 0.6) < 1e-6, "y[0] = {}", y[0]);
+    }
+}
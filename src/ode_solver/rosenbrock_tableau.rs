@@ -0,0 +1,463 @@
+use crate::{DenseMatrix, Vector};
+use num_traits::{One, Zero};
+
+/// A tableau for a Rosenbrock (linearly implicit Runge-Kutta) method.
+///
+/// The coefficients are stored in the transformed form of Hairer & Wanner, which avoids a matrix-vector product with the Jacobian at each stage.
+/// For a problem `M y' = f(t, y)`, each stage `u_i` is found by solving the linear system
+///
+/// ```text
+/// (M / (h gamma) - J) u_i = f(t + c_i h, y + sum_{j < i} a_ij u_j) + M sum_{j < i} (c_mat_ij / h) u_j + h gamma_sum_i df/dt
+/// ```
+///
+/// where `J` is the Jacobian of `f` evaluated at the start of the step. The solution is then `y + sum_i b_i u_i`,
+/// and `sum_i d_i u_i` is an estimate of the local error.
+///
+/// The `dense` matrix has two columns `h1` and `h2` which define a continuous extension of the method,
+/// `y(theta) = y0 + sum_i (theta b_i + theta (1 - theta) (h1_i + theta h2_i)) u_i`.
+#[derive(Clone)]
+pub struct RosenbrockTableau<M: DenseMatrix> {
+    a: M,
+    c_mat: M,
+    gamma: M::T,
+    c: M::V,
+    gamma_sum: M::V,
+    b: M::V,
+    d: M::V,
+    order: usize,
+    dense: M,
+}
+
+impl<M: DenseMatrix> RosenbrockTableau<M> {
+    /// RODAS4, a 6-stage, stiffly accurate Rosenbrock method of order 4(3)
+    /// from E. Hairer and G. Wanner, Solving Ordinary Differential Equations II: Stiff and Differential-Algebraic Problems, 2nd edition, Springer (1996).
+    ///
+    /// The coefficients and continuous extension are those of the `RODAS` code by the same authors.
+    pub fn rodas4() -> Self {
+        let mut a = M::zeros(6, 6);
+        a[(1, 0)] = M::T::from(1.544);
+        a[(2, 0)] = M::T::from(0.9466785280815826);
+        a[(2, 1)] = M::T::from(0.2557011698983284);
+        a[(3, 0)] = M::T::from(3.314825187068521);
+        a[(3, 1)] = M::T::from(2.896124015972201);
+        a[(3, 2)] = M::T::from(0.9986419139977817);
+        a[(4, 0)] = M::T::from(1.221224509226641);
+        a[(4, 1)] = M::T::from(6.019134481288629);
+        a[(4, 2)] = M::T::from(12.53708332932087);
+        a[(4, 3)] = M::T::from(-0.687886036105895);
+        a[(5, 0)] = M::T::from(1.221224509226641);
+        a[(5, 1)] = M::T::from(6.019134481288629);
+        a[(5, 2)] = M::T::from(12.53708332932087);
+        a[(5, 3)] = M::T::from(-0.687886036105895);
+        a[(5, 4)] = M::T::from(1.0);
+
+        let mut c_mat = M::zeros(6, 6);
+        c_mat[(1, 0)] = M::T::from(-5.6688);
+        c_mat[(2, 0)] = M::T::from(-2.430093356833875);
+        c_mat[(2, 1)] = M::T::from(-0.2063599157091915);
+        c_mat[(3, 0)] = M::T::from(-0.1073529058151375);
+        c_mat[(3, 1)] = M::T::from(-9.594562251023355);
+        c_mat[(3, 2)] = M::T::from(-20.47028614809616);
+        c_mat[(4, 0)] = M::T::from(7.496443313967647);
+        c_mat[(4, 1)] = M::T::from(-10.24680431464352);
+        c_mat[(4, 2)] = M::T::from(-33.99990352819905);
+        c_mat[(4, 3)] = M::T::from(11.7089089320616);
+        c_mat[(5, 0)] = M::T::from(8.083246795921522);
+        c_mat[(5, 1)] = M::T::from(-7.981132988064893);
+        c_mat[(5, 2)] = M::T::from(-31.52159432874371);
+        c_mat[(5, 3)] = M::T::from(16.31930543123136);
+        c_mat[(5, 4)] = M::T::from(-6.058818238834054);
+
+        let c = M::V::from_vec(vec![
+            M::T::from(0.0),
+            M::T::from(0.386),
+            M::T::from(0.21),
+            M::T::from(0.63),
+            M::T::from(1.0),
+            M::T::from(1.0),
+        ]);
+        let gamma_sum = M::V::from_vec(vec![
+            M::T::from(0.25),
+            M::T::from(-0.1043),
+            M::T::from(0.1035),
+            M::T::from(-0.0362),
+            M::T::from(0.0),
+            M::T::from(0.0),
+        ]);
+
+        let mut dense = M::zeros(6, 2);
+        dense[(0, 0)] = M::T::from(10.12623508344586);
+        dense[(1, 0)] = M::T::from(-7.487995877610167);
+        dense[(2, 0)] = M::T::from(-34.80091861555747);
+        dense[(3, 0)] = M::T::from(-7.992771707568823);
+        dense[(4, 0)] = M::T::from(1.025137723295662);
+        dense[(0, 1)] = M::T::from(-0.6762803392801253);
+        dense[(1, 1)] = M::T::from(6.087714651680015);
+        dense[(2, 1)] = M::T::from(16.43084320892478);
+        dense[(3, 1)] = M::T::from(24.76722511418386);
+        dense[(4, 1)] = M::T::from(-6.594389125716872);
+
+        let order = 4;
+
+        Self::new_stiffly_accurate(a, c_mat, M::T::from(0.25), c, gamma_sum, order, dense)
+    }
+
+    /// RODAS5, an 8-stage, stiffly accurate Rosenbrock method of order 5(4)
+    /// from G. Di Marzo, RODAS5(4) - Méthodes de Rosenbrock d'ordre 5(4) adaptées aux problèmes différentiels-algébriques, MSc thesis, University of Geneva (1993).
+    ///
+    /// The published method has no continuous extension, so the third order extension used here was constructed to minimise the fourth order residuals.
+    pub fn rodas5() -> Self {
+        let mut a = M::zeros(8, 8);
+        a[(1, 0)] = M::T::from(2.0);
+        a[(2, 0)] = M::T::from(3.040894194418781);
+        a[(2, 1)] = M::T::from(1.041747909077569);
+        a[(3, 0)] = M::T::from(2.576417536461461);
+        a[(3, 1)] = M::T::from(1.62208306077664);
+        a[(3, 2)] = M::T::from(-0.9089668560264532);
+        a[(4, 0)] = M::T::from(2.760842080225597);
+        a[(4, 1)] = M::T::from(1.446624659844071);
+        a[(4, 2)] = M::T::from(-0.3036980084553738);
+        a[(4, 3)] = M::T::from(0.2877498600325443);
+        a[(5, 0)] = M::T::from(-14.09640773051259);
+        a[(5, 1)] = M::T::from(6.925207756232704);
+        a[(5, 2)] = M::T::from(-41.47510893210728);
+        a[(5, 3)] = M::T::from(2.343771018586405);
+        a[(5, 4)] = M::T::from(24.13215229196062);
+        a[(6, 0)] = M::T::from(-14.09640773051259);
+        a[(6, 1)] = M::T::from(6.925207756232704);
+        a[(6, 2)] = M::T::from(-41.47510893210728);
+        a[(6, 3)] = M::T::from(2.343771018586405);
+        a[(6, 4)] = M::T::from(24.13215229196062);
+        a[(6, 5)] = M::T::from(1.0);
+        a[(7, 0)] = M::T::from(-14.09640773051259);
+        a[(7, 1)] = M::T::from(6.925207756232704);
+        a[(7, 2)] = M::T::from(-41.47510893210728);
+        a[(7, 3)] = M::T::from(2.343771018586405);
+        a[(7, 4)] = M::T::from(24.13215229196062);
+        a[(7, 5)] = M::T::from(1.0);
+        a[(7, 6)] = M::T::from(1.0);
+
+        let mut c_mat = M::zeros(8, 8);
+        c_mat[(1, 0)] = M::T::from(-10.31323885133993);
+        c_mat[(2, 0)] = M::T::from(-21.04823117650003);
+        c_mat[(2, 1)] = M::T::from(-7.234992135176716);
+        c_mat[(3, 0)] = M::T::from(32.22751541853323);
+        c_mat[(3, 1)] = M::T::from(-4.943732386540191);
+        c_mat[(3, 2)] = M::T::from(19.44922031041879);
+        c_mat[(4, 0)] = M::T::from(-20.69865579590063);
+        c_mat[(4, 1)] = M::T::from(-8.816374604402768);
+        c_mat[(4, 2)] = M::T::from(1.260436877740897);
+        c_mat[(4, 3)] = M::T::from(-0.7495647613787146);
+        c_mat[(5, 0)] = M::T::from(-46.22004352711257);
+        c_mat[(5, 1)] = M::T::from(-17.49534862857472);
+        c_mat[(5, 2)] = M::T::from(-289.6389582892057);
+        c_mat[(5, 3)] = M::T::from(93.60855400400906);
+        c_mat[(5, 4)] = M::T::from(318.3822534212147);
+        c_mat[(6, 0)] = M::T::from(34.20013733472935);
+        c_mat[(6, 1)] = M::T::from(-14.1553540271769);
+        c_mat[(6, 2)] = M::T::from(57.823356409884);
+        c_mat[(6, 3)] = M::T::from(25.83362985412365);
+        c_mat[(6, 4)] = M::T::from(1.408950972071624);
+        c_mat[(6, 5)] = M::T::from(-6.551835421242162);
+        c_mat[(7, 0)] = M::T::from(42.57076742291101);
+        c_mat[(7, 1)] = M::T::from(-13.80770672017997);
+        c_mat[(7, 2)] = M::T::from(93.98938432427124);
+        c_mat[(7, 3)] = M::T::from(18.77919633714503);
+        c_mat[(7, 4)] = M::T::from(-31.5835918722337);
+        c_mat[(7, 5)] = M::T::from(-6.685968952921985);
+        c_mat[(7, 6)] = M::T::from(-5.810979938412932);
+
+        let c = M::V::from_vec(vec![
+            M::T::from(0.0),
+            M::T::from(0.38),
+            M::T::from(0.3878509998321533),
+            M::T::from(0.483971893787384),
+            M::T::from(0.457047700881958),
+            M::T::from(1.0),
+            M::T::from(1.0),
+            M::T::from(1.0),
+        ]);
+        let gamma_sum = M::V::from_vec(vec![
+            M::T::from(0.19),
+            M::T::from(-0.18230792253337147),
+            M::T::from(-0.3192318321868749),
+            M::T::from(0.3449828624725343),
+            M::T::from(-0.37741756439208984),
+            M::T::from(0.0),
+            M::T::from(0.0),
+            M::T::from(0.0),
+        ]);
+
+        let mut dense = M::zeros(8, 2);
+        dense[(0, 0)] = M::T::from(22.842425011099383);
+        dense[(1, 0)] = M::T::from(-7.505046058701024);
+        dense[(2, 0)] = M::T::from(4.918657577167184);
+        dense[(3, 0)] = M::T::from(0.11754853334369003);
+        dense[(4, 0)] = M::T::from(11.07168998234607);
+        dense[(5, 0)] = M::T::from(0.22566261153882666);
+        dense[(6, 0)] = M::T::from(0.6242445642169073);
+        dense[(0, 1)] = M::T::from(-1.8800493641311007);
+        dense[(1, 1)] = M::T::from(-0.08192980788666732);
+        dense[(2, 1)] = M::T::from(13.152675795605981);
+        dense[(3, 1)] = M::T::from(23.08701966470063);
+        dense[(4, 1)] = M::T::from(9.071121045719602);
+        dense[(5, 1)] = M::T::from(-4.036803992918059);
+        dense[(6, 1)] = M::T::from(-3.083783865200255);
+
+        let order = 5;
+
+        Self::new_stiffly_accurate(a, c_mat, M::T::from(0.19), c, gamma_sum, order, dense)
+    }
+
+    /// RODAS5P, an 8-stage, stiffly accurate Rosenbrock method of order 5(4)
+    /// from G. Steinebach, Construction of Rosenbrock-Wanner method Rodas5P and numerical benchmarks within the Julia Differential Equations package, BIT Numerical Mathematics 63, 27 (2023).
+    ///
+    /// The third order continuous extension was constructed in the same way as for [Self::rodas5].
+    pub fn rodas5p() -> Self {
+        let mut a = M::zeros(8, 8);
+        a[(1, 0)] = M::T::from(3.0);
+        a[(2, 0)] = M::T::from(2.849394379747939);
+        a[(2, 1)] = M::T::from(0.45842242204463923);
+        a[(3, 0)] = M::T::from(-6.954028509809101);
+        a[(3, 1)] = M::T::from(2.489845061869568);
+        a[(3, 2)] = M::T::from(-10.358996098473584);
+        a[(4, 0)] = M::T::from(2.8029986275628964);
+        a[(4, 1)] = M::T::from(0.5072464736228206);
+        a[(4, 2)] = M::T::from(-0.3988312541770524);
+        a[(4, 3)] = M::T::from(-0.04721187230404641);
+        a[(5, 0)] = M::T::from(-7.502846399306121);
+        a[(5, 1)] = M::T::from(2.561846144803919);
+        a[(5, 2)] = M::T::from(-11.627539656261098);
+        a[(5, 3)] = M::T::from(-0.18268767659942256);
+        a[(5, 4)] = M::T::from(0.030198172008377946);
+        a[(6, 0)] = M::T::from(-7.502846399306121);
+        a[(6, 1)] = M::T::from(2.561846144803919);
+        a[(6, 2)] = M::T::from(-11.627539656261098);
+        a[(6, 3)] = M::T::from(-0.18268767659942256);
+        a[(6, 4)] = M::T::from(0.030198172008377946);
+        a[(6, 5)] = M::T::from(1.0);
+        a[(7, 0)] = M::T::from(-7.502846399306121);
+        a[(7, 1)] = M::T::from(2.561846144803919);
+        a[(7, 2)] = M::T::from(-11.627539656261098);
+        a[(7, 3)] = M::T::from(-0.18268767659942256);
+        a[(7, 4)] = M::T::from(0.030198172008377946);
+        a[(7, 5)] = M::T::from(1.0);
+        a[(7, 6)] = M::T::from(1.0);
+
+        let mut c_mat = M::zeros(8, 8);
+        c_mat[(1, 0)] = M::T::from(-14.155112264123755);
+        c_mat[(2, 0)] = M::T::from(-17.97296035885952);
+        c_mat[(2, 1)] = M::T::from(-2.859693295451294);
+        c_mat[(3, 0)] = M::T::from(147.12150275711716);
+        c_mat[(3, 1)] = M::T::from(-1.41221402718213);
+        c_mat[(3, 2)] = M::T::from(71.68940251302358);
+        c_mat[(4, 0)] = M::T::from(165.43517024871676);
+        c_mat[(4, 1)] = M::T::from(-0.4592823456491126);
+        c_mat[(4, 2)] = M::T::from(42.90938336958603);
+        c_mat[(4, 3)] = M::T::from(-5.961986721573306);
+        c_mat[(5, 0)] = M::T::from(24.854864614690072);
+        c_mat[(5, 1)] = M::T::from(-3.0009227002832186);
+        c_mat[(5, 2)] = M::T::from(47.4931110020768);
+        c_mat[(5, 3)] = M::T::from(5.5814197821558125);
+        c_mat[(5, 4)] = M::T::from(-0.6610691825249471);
+        c_mat[(6, 0)] = M::T::from(30.91273214028599);
+        c_mat[(6, 1)] = M::T::from(-3.1208243349937974);
+        c_mat[(6, 2)] = M::T::from(77.79954646070892);
+        c_mat[(6, 3)] = M::T::from(34.28646028294783);
+        c_mat[(6, 4)] = M::T::from(-19.097331116725623);
+        c_mat[(6, 5)] = M::T::from(-28.087943162872662);
+        c_mat[(7, 0)] = M::T::from(37.80277123390563);
+        c_mat[(7, 1)] = M::T::from(-3.2571969029072276);
+        c_mat[(7, 2)] = M::T::from(112.26918849496327);
+        c_mat[(7, 3)] = M::T::from(66.9347231244047);
+        c_mat[(7, 4)] = M::T::from(-40.06618937091002);
+        c_mat[(7, 5)] = M::T::from(-54.66780262877968);
+        c_mat[(7, 6)] = M::T::from(-9.48861652309627);
+
+        let c = M::V::from_vec(vec![
+            M::T::from(0.0),
+            M::T::from(0.6358126895828704),
+            M::T::from(0.4095798393397535),
+            M::T::from(0.9769306725060716),
+            M::T::from(0.4288403609558664),
+            M::T::from(1.0),
+            M::T::from(1.0),
+            M::T::from(1.0),
+        ]);
+        let gamma_sum = M::V::from_vec(vec![
+            M::T::from(0.21193756319429014),
+            M::T::from(-0.42387512638858027),
+            M::T::from(-0.3384627126235924),
+            M::T::from(1.8046452872882734),
+            M::T::from(2.325825639765069),
+            M::T::from(0.0),
+            M::T::from(0.0),
+            M::T::from(0.0),
+        ]);
+
+        let mut dense = M::zeros(8, 2);
+        dense[(0, 0)] = M::T::from(26.027167232165926);
+        dense[(1, 0)] = M::T::from(-3.2268070294960745);
+        dense[(2, 0)] = M::T::from(7.44245410666668);
+        dense[(3, 0)] = M::T::from(-7.57055520004726);
+        dense[(4, 0)] = M::T::from(3.9974029750933586);
+        dense[(5, 0)] = M::T::from(4.193541916112183);
+        dense[(6, 0)] = M::T::from(-1.2878226593949247);
+        dense[(0, 1)] = M::T::from(-11.025581244053544);
+        dense[(1, 1)] = M::T::from(2.166966247522772);
+        dense[(2, 1)] = M::T::from(13.28082526512213);
+        dense[(3, 1)] = M::T::from(-3.7422259056842417);
+        dense[(4, 1)] = M::T::from(6.235941403136837);
+        dense[(5, 1)] = M::T::from(4.527218157546303);
+        dense[(6, 1)] = M::T::from(1.729999650336204);
+
+        let order = 5;
+
+        Self::new_stiffly_accurate(
+            a,
+            c_mat,
+            M::T::from(0.21193756319429014),
+            c,
+            gamma_sum,
+            order,
+            dense,
+        )
+    }
+
+    /// Create a stiffly accurate method, where the solution is the last stage value plus the last stage,
+    /// and the last stage is used as the error estimate.
+    fn new_stiffly_accurate(
+        a: M,
+        c_mat: M,
+        gamma: M::T,
+        c: M::V,
+        gamma_sum: M::V,
+        order: usize,
+        dense: M,
+    ) -> Self {
+        let s = c.len();
+        let mut b = M::V::zeros(s);
+        for j in 0..s - 1 {
+            b[j] = a[(s - 1, j)];
+        }
+        b[s - 1] = M::T::one();
+        let mut d = M::V::zeros(s);
+        d[s - 1] = M::T::one();
+        Self::new(a, c_mat, gamma, c, gamma_sum, b, d, order, dense)
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    pub fn new(
+        a: M,
+        c_mat: M,
+        gamma: M::T,
+        c: M::V,
+        gamma_sum: M::V,
+        b: M::V,
+        d: M::V,
+        order: usize,
+        dense: M,
+    ) -> Self {
+        let s = c.len();
+        assert_eq!(a.nrows(), s, "Invalid number of rows in a, expected {}", s);
+        assert_eq!(
+            a.ncols(),
+            s,
+            "Invalid number of columns in a, expected {}",
+            s
+        );
+        assert_eq!(
+            c_mat.nrows(),
+            s,
+            "Invalid number of rows in c_mat, expected {}",
+            s
+        );
+        assert_eq!(
+            c_mat.ncols(),
+            s,
+            "Invalid number of columns in c_mat, expected {}",
+            s
+        );
+        assert_eq!(
+            gamma_sum.len(),
+            s,
+            "Invalid number of elements in gamma_sum, expected {}",
+            s
+        );
+        assert_eq!(
+            b.len(),
+            s,
+            "Invalid number of elements in b, expected {}",
+            s
+        );
+        assert_eq!(
+            d.len(),
+            s,
+            "Invalid number of elements in d, expected {}",
+            s
+        );
+        assert_eq!(
+            dense.nrows(),
+            s,
+            "Invalid number of rows in dense, expected {}",
+            s
+        );
+        assert_eq!(
+            dense.ncols(),
+            2,
+            "Invalid number of columns in dense, expected 2"
+        );
+        assert!(gamma > M::T::zero(), "Invalid gamma, expected gamma > 0");
+        Self {
+            a,
+            c_mat,
+            gamma,
+            c,
+            gamma_sum,
+            b,
+            d,
+            order,
+            dense,
+        }
+    }
+
+    pub fn order(&self) -> usize {
+        self.order
+    }
+
+    pub fn s(&self) -> usize {
+        self.c.len()
+    }
+
+    pub fn a(&self) -> &M {
+        &self.a
+    }
+
+    pub fn c_mat(&self) -> &M {
+        &self.c_mat
+    }
+
+    pub fn gamma(&self) -> M::T {
+        self.gamma
+    }
+
+    pub fn c(&self) -> &M::V {
+        &self.c
+    }
+
+    pub fn gamma_sum(&self) -> &M::V {
+        &self.gamma_sum
+    }
+
+    pub fn b(&self) -> &M::V {
+        &self.b
+    }
+
+    pub fn d(&self) -> &M::V {
+        &self.d
+    }
+
+    pub fn dense(&self) -> &M {
+        &self.dense
+    }
+}
@@ -0,0 +1,632 @@
+use crate::error::DiffsolError;
+use crate::error::OdeSolverError;
+use crate::ode_solver_error;
+use crate::vector::VectorRef;
+use crate::{
+    scale, BrownianPath, NoiseType, NonLinearOp, OdeEquations, OdeSolverMethod, OdeSolverProblem,
+    OdeSolverState, OdeSolverStopReason, Op, RootFinder, SdeProblem, SdirkState, StateRef,
+    StateRefMut, Vector,
+};
+use nalgebra::ComplexField;
+use num_traits::abs;
+use num_traits::One;
+use num_traits::Pow;
+use num_traits::Zero;
+
+use super::bdf::BdfStatistics;
+use super::method::{check_interpolation_time, check_tstop, init_root_finder, TstopCheck};
+
+/// The method used by the [Sde] solver.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum SdeMethod {
+    /// The Euler-Maruyama method, with a fixed step size. Strong order 0.5 for general noise, and strong order 1 for additive noise. Supports all [NoiseType]s.
+    EulerMaruyama,
+    /// The derivative-free (Runge-Kutta) Milstein method, with a fixed step size. Strong order 1 for diagonal and additive noise, general noise is not supported
+    /// as it would need the Lévy areas between the Wiener processes.
+    Milstein,
+    /// The SRA1 stochastic Runge-Kutta method of Rößler (2010), with an adaptive step size. Strong order 1.5 for additive noise, other noise types are not supported.
+    Sra1,
+}
+
+/// A solver for stochastic differential equations `dy = f(t, y) dt + G(t, y) dW` (see [SdeProblem]).
+///
+/// The noise is given by a [BrownianPath], which stores every sample of the Wiener processes that the solver has used. Given the same seed and step sizes the solution
+/// is reproducible, and a rejected step is retried on the same path using the Brownian bridge. The path can be cloned (see [Self::brownian_path]) and given to
+/// another solver (see [SdeProblem::sde_solver]) to compare solutions on the same realisation of the noise.
+///
+/// The Euler-Maruyama and Milstein methods use a fixed step size, given by the step size of the initial state (see [crate::OdeBuilder::h0]), which is only reduced
+/// to stop at a stop time given by [OdeSolverMethod::set_stop_time]. The SRA1 method chooses its step size based on an estimate of the local error,
+/// using the absolute and relative tolerances of the problem. The solution is linearly interpolated between steps.
+///
+/// [OdeSolverMethod::order] returns the strong order of the method rounded up.
+///
+/// Restrictions:
+/// - The problem must not have a mass matrix.
+/// - Integrating the output function and forward sensitivities are not supported.
+pub struct Sde<'a, Eqn, F>
+where
+    Eqn: OdeEquations,
+    F: Fn(&Eqn::V, &Eqn::V, Eqn::T, &Eqn::V, &mut Eqn::V),
+{
+    problem: &'a SdeProblem<Eqn, F>,
+    method: SdeMethod,
+    state: SdirkState<Eqn::V>,
+    path: BrownianPath<Eqn::V>,
+    h: Eqn::T,
+    old_t: Eqn::T,
+    old_y: Eqn::V,
+    y_new: Eqn::V,
+    f_new: Eqn::V,
+    dw: Eqn::V,
+    dz: Eqn::V,
+    noise: Eqn::V,
+    g0: Eqn::V,
+    g1: Eqn::V,
+    g2: Eqn::V,
+    g3: Eqn::V,
+    error: Eqn::V,
+    statistics: BdfStatistics,
+    root_finder: Option<RootFinder<Eqn::V>>,
+    tstop: Option<Eqn::T>,
+    is_state_mutated: bool,
+}
+
+impl<Eqn, F> Clone for Sde<'_, Eqn, F>
+where
+    Eqn: OdeEquations,
+    F: Fn(&Eqn::V, &Eqn::V, Eqn::T, &Eqn::V, &mut Eqn::V),
+{
+    fn clone(&self) -> Self {
+        Self {
+            problem: self.problem,
+            method: self.method,
+            state: self.state.clone(),
+            path: self.path.clone(),
+            h: self.h,
+            old_t: self.old_t,
+            old_y: self.old_y.clone(),
+            y_new: self.y_new.clone(),
+            f_new: self.f_new.clone(),
+            dw: self.dw.clone(),
+            dz: self.dz.clone(),
+            noise: self.noise.clone(),
+            g0: self.g0.clone(),
+            g1: self.g1.clone(),
+            g2: self.g2.clone(),
+            g3: self.g3.clone(),
+            error: self.error.clone(),
+            statistics: self.statistics.clone(),
+            root_finder: self.root_finder.clone(),
+            tstop: self.tstop,
+            is_state_mutated: self.is_state_mutated,
+        }
+    }
+}
+
+impl<'a, Eqn, F> Sde<'a, Eqn, F>
+where
+    Eqn: OdeEquations,
+    F: Fn(&Eqn::V, &Eqn::V, Eqn::T, &Eqn::V, &mut Eqn::V),
+    for<'b> &'b Eqn::V: VectorRef<Eqn::V>,
+{
+    const SAFETY: f64 = 0.9;
+    const MIN_FACTOR: f64 = 0.2;
+    const MAX_FACTOR: f64 = 10.0;
+    const MIN_TIMESTEP: f64 = 1e-13;
+
+    pub fn new(
+        problem: &'a SdeProblem<Eqn, F>,
+        mut state: SdirkState<Eqn::V>,
+        method: SdeMethod,
+        path: BrownianPath<Eqn::V>,
+    ) -> Result<Self, DiffsolError> {
+        let ode = &problem.ode;
+        if ode.eqn.mass().is_some() {
+            return Err(ode_solver_error!(
+                Other,
+                "Sde solvers do not support a mass matrix"
+            ));
+        }
+        if ode.integrate_out {
+            return Err(ode_solver_error!(
+                Other,
+                "Sde solvers do not support integrating the output function"
+            ));
+        }
+        match (method, problem.noise()) {
+            (SdeMethod::Milstein, NoiseType::General(_)) => {
+                return Err(ode_solver_error!(
+                    Other,
+                    "The Milstein method does not support general noise"
+                ));
+            }
+            (SdeMethod::Sra1, NoiseType::Diagonal | NoiseType::General(_)) => {
+                return Err(ode_solver_error!(
+                    Other,
+                    "The SRA1 method only supports additive noise"
+                ));
+            }
+            _ => {}
+        }
+        if path.nnoise() != problem.nnoise() {
+            return Err(ode_solver_error!(
+                Other,
+                format!(
+                    "The Brownian path has {} Wiener processes, but the problem has {}",
+                    path.nnoise(),
+                    problem.nnoise()
+                )
+            ));
+        }
+
+        state.check_consistent_with_problem(ode)?;
+        state.set_problem(ode)?;
+
+        let nstates = ode.eqn.rhs().nstates();
+        let nnoise = problem.nnoise();
+        let h = state.h;
+        let old_t = state.t;
+        let old_y = state.y.clone();
+
+        let root_finder = if let Some(root_fn) = ode.eqn.root() {
+            let root_finder = RootFinder::new(root_fn.nout());
+            root_finder.init(&root_fn, &state.y, state.t);
+            Some(root_finder)
+        } else {
+            None
+        };
+
+        Ok(Self {
+            problem,
+            method,
+            state,
+            path,
+            h,
+            old_t,
+            old_y,
+            y_new: <Eqn::V as Vector>::zeros(nstates),
+            f_new: <Eqn::V as Vector>::zeros(nstates),
+            dw: <Eqn::V as Vector>::zeros(nnoise),
+            dz: <Eqn::V as Vector>::zeros(nnoise),
+            noise: <Eqn::V as Vector>::zeros(nnoise),
+            g0: <Eqn::V as Vector>::zeros(nstates),
+            g1: <Eqn::V as Vector>::zeros(nstates),
+            g2: <Eqn::V as Vector>::zeros(nstates),
+            g3: <Eqn::V as Vector>::zeros(nstates),
+            error: <Eqn::V as Vector>::zeros(nstates),
+            statistics: BdfStatistics::default(),
+            root_finder,
+            tstop: None,
+            is_state_mutated: false,
+        })
+    }
+
+    pub fn method(&self) -> SdeMethod {
+        self.method
+    }
+
+    /// Returns the Brownian path sampled so far, this can be cloned to solve the problem again on the same path.
+    pub fn brownian_path(&self) -> &BrownianPath<Eqn::V> {
+        &self.path
+    }
+
+    /// Returns the solver statistics. No linear or nonlinear solver is used, so only the number of steps and error test failures are recorded.
+    pub fn get_statistics(&self) -> &BdfStatistics {
+        &self.statistics
+    }
+
+    fn handle_tstop(
+        &mut self,
+        tstop: Eqn::T,
+    ) -> Result<Option<OdeSolverStopReason<Eqn::T>>, DiffsolError> {
+        match check_tstop(self.state.t, self.state.h, tstop)? {
+            TstopCheck::Reached => {
+                self.tstop = None;
+                return Ok(Some(OdeSolverStopReason::TstopReached));
+            }
+            TstopCheck::ScaleStep(factor) => {
+                self.state.h *= factor;
+            }
+            TstopCheck::NotReached => {}
+        }
+        Ok(None)
+    }
+
+    /// calculate the new state `y_new` at `t + h` from the current state, using the Wiener increments `dw` and `dz` over the step.
+    /// Returns the squared norm of the local error estimate for adaptive methods, or zero for fixed-step methods.
+    fn try_step(&mut self, h: Eqn::T) -> Eqn::T {
+        let problem = self.problem;
+        let rhs = problem.ode.eqn.rhs();
+        let t = self.state.t;
+        let y = &self.state.y;
+        let f0 = &self.state.dy;
+        self.path.increment(t, t + h, &mut self.dw, &mut self.dz);
+
+        match self.method {
+            SdeMethod::Milstein if problem.noise() == NoiseType::Diagonal => {
+                // the derivative of the diffusion is approximated using a supporting value y_hat = y + h f(t, y) + sqrt(h) g(t, y),
+                // y_new = y + h f(t, y) + g(t, y) dw + (g(t, y_hat) - g(t, y)) (dw^2 - h) / (2 sqrt(h))
+                let sqrt_h = h.sqrt();
+                self.noise.fill(Eqn::T::one());
+                problem.diffusion_inplace(y, t, &self.noise, &mut self.g0);
+                self.y_new.copy_from(y);
+                self.y_new.axpy(h, f0, Eqn::T::one());
+                self.y_new.axpy(sqrt_h, &self.g0, Eqn::T::one());
+                problem.diffusion_inplace(&self.y_new, t, &self.noise, &mut self.g1);
+                let two = Eqn::T::from(2.0);
+                let y_new = self.y_new.as_mut_slice();
+                let (y, f0) = (y.as_slice(), f0.as_slice());
+                let (g0, g1) = (self.g0.as_slice(), self.g1.as_slice());
+                let dw = self.dw.as_slice();
+                for i in 0..y_new.len() {
+                    y_new[i] = y[i]
+                        + h * f0[i]
+                        + g0[i] * dw[i]
+                        + (g1[i] - g0[i]) * (dw[i] * dw[i] - h) / (two * sqrt_h);
+                }
+                Eqn::T::zero()
+            }
+            // for additive noise the Milstein correction is zero, and the Milstein method is the same as Euler-Maruyama
+            SdeMethod::EulerMaruyama | SdeMethod::Milstein => {
+                // y_new = y + h f(t, y) + G(t, y) dw
+                problem.diffusion_inplace(y, t, &self.dw, &mut self.g0);
+                self.y_new.copy_from(y);
+                self.y_new.axpy(h, f0, Eqn::T::one());
+                self.y_new.axpy(Eqn::T::one(), &self.g0, Eqn::T::one());
+                Eqn::T::zero()
+            }
+            SdeMethod::Sra1 => {
+                let one = Eqn::T::one();
+                let two_thirds = Eqn::T::from(2.0 / 3.0);
+                let one_third = Eqn::T::from(1.0 / 3.0);
+
+                // g0 = G(t) dz / h, g1 = G(t + h) dz / h, g2 = G(t) dw, g3 = G(t + h) dw
+                self.noise.copy_from(&self.dz);
+                self.noise *= scale(one / h);
+                problem.diffusion_inplace(y, t, &self.noise, &mut self.g0);
+                problem.diffusion_inplace(y, t + h, &self.noise, &mut self.g1);
+                problem.diffusion_inplace(y, t, &self.dw, &mut self.g2);
+                problem.diffusion_inplace(y, t + h, &self.dw, &mut self.g3);
+
+                // second stage H2 = y + 3/4 h f(t, y) + 3/2 G(t + h) dz / h
+                self.y_new.copy_from(y);
+                self.y_new.axpy(Eqn::T::from(0.75) * h, f0, one);
+                self.y_new.axpy(Eqn::T::from(1.5), &self.g1, one);
+                rhs.call_inplace(&self.y_new, t + Eqn::T::from(0.75) * h, &mut self.f_new);
+
+                // y_new = y + h (f1 / 3 + 2 f2 / 3) + G(t + h) (dw - dz / h) + G(t) dz / h
+                self.y_new.copy_from(y);
+                self.y_new.axpy(one_third * h, f0, one);
+                self.y_new.axpy(two_thirds * h, &self.f_new, one);
+                self.y_new.axpy(one, &self.g3, one);
+                self.y_new.axpy(-one, &self.g1, one);
+                self.y_new.axpy(one, &self.g0, one);
+
+                // the error estimate is the difference with the Euler-Maruyama step,
+                // 2/3 h (f2 - f1) + (G(t + h) - G(t)) (dw - dz / h)
+                self.error.copy_from(&self.f_new);
+                self.error.axpy(-one, f0, one);
+                self.error *= scale(two_thirds * h);
+                self.error.axpy(one, &self.g3, one);
+                self.error.axpy(-one, &self.g1, one);
+                self.error.axpy(-one, &self.g2, one);
+                self.error.axpy(one, &self.g0, one);
+                let ode = &problem.ode;
+                self.error.squared_norm(y, &ode.atol, ode.rtol)
+            }
+        }
+    }
+
+    fn interpolation_theta(&self, t: Eqn::T) -> Result<Eqn::T, DiffsolError> {
+        let state = &self.state;
+        check_interpolation_time(t, self.old_t, state.t)?;
+        let dt = state.t - self.old_t;
+        if dt == Eqn::T::zero() {
+            Ok(Eqn::T::one())
+        } else {
+            Ok((t - self.old_t) / dt)
+        }
+    }
+}
+
+impl<'a, Eqn, F> OdeSolverMethod<'a, Eqn> for Sde<'a, Eqn, F>
+where
+    Eqn: OdeEquations,
+    F: Fn(&Eqn::V, &Eqn::V, Eqn::T, &Eqn::V, &mut Eqn::V),
+    for<'b> &'b Eqn::V: VectorRef<Eqn::V>,
+{
+    type State = SdirkState<Eqn::V>;
+
+    fn problem(&self) -> &'a OdeSolverProblem<Eqn> {
+        &self.problem.ode
+    }
+
+    fn order(&self) -> usize {
+        match self.method {
+            SdeMethod::EulerMaruyama | SdeMethod::Milstein => 1,
+            SdeMethod::Sra1 => 2,
+        }
+    }
+
+    fn set_state(&mut self, state: Self::State) {
+        self.state = state;
+        self.h = self.state.h;
+
+        // make sure the drift is consistent with the new state
+        let state = &mut self.state;
+        self.problem
+            .ode
+            .eqn
+            .rhs()
+            .call_inplace(&state.y, state.t, &mut state.dy);
+    }
+
+    fn into_state(self) -> SdirkState<Eqn::V> {
+        self.state
+    }
+
+    fn checkpoint(&mut self) -> Self::State {
+        self.state.clone()
+    }
+
+    fn step(&mut self) -> Result<OdeSolverStopReason<Eqn::T>, DiffsolError> {
+        let problem = self.problem;
+        let ode = &problem.ode;
+
+        if self.is_state_mutated {
+            // reinitialise root finder if needed
+            init_root_finder(ode, self.root_finder.as_ref(), &self.state.y, self.state.t);
+            // the step size might have been changed by the user
+            self.h = self.state.h;
+            // reinitialise tstop if needed
+            if let Some(t_stop) = self.tstop {
+                self.set_stop_time(t_stop)?;
+            }
+            let state = &mut self.state;
+            ode.eqn.rhs().call_inplace(&state.y, state.t, &mut state.dy);
+
+            self.is_state_mutated = false;
+        }
+
+        // save the start of the step for interpolation
+        self.old_t = self.state.t;
+        self.old_y.copy_from(&self.state.y);
+
+        let mut h = self.state.h;
+        let factor = loop {
+            let error_norm = self.try_step(h);
+            if self.method != SdeMethod::Sra1 {
+                break Eqn::T::one();
+            }
+            // the error estimate is the difference with Euler-Maruyama, which has strong order 1 for additive noise
+            let mut factor = if error_norm > Eqn::T::zero() {
+                Eqn::T::from(Self::SAFETY) * error_norm.pow(Eqn::T::from(-0.25))
+            } else {
+                Eqn::T::from(Self::MAX_FACTOR)
+            };
+            if factor < Eqn::T::from(Self::MIN_FACTOR) {
+                factor = Eqn::T::from(Self::MIN_FACTOR);
+            }
+            if factor > Eqn::T::from(Self::MAX_FACTOR) {
+                factor = Eqn::T::from(Self::MAX_FACTOR);
+            }
+            if error_norm <= Eqn::T::one() {
+                break factor;
+            }
+
+            // step rejected, try again with a smaller step on the same path
+            self.statistics.number_of_error_test_failures += 1;
+            h *= factor;
+            if abs(h) < Eqn::T::from(Self::MIN_TIMESTEP) {
+                return Err(DiffsolError::from(OdeSolverError::StepSizeTooSmall {
+                    time: self.state.t.into(),
+                }));
+            }
+        };
+
+        // take the step
+        {
+            let state = &mut self.state;
+            state.t = self.old_t + h;
+            std::mem::swap(&mut state.y, &mut self.y_new);
+            ode.eqn.rhs().call_inplace(&state.y, state.t, &mut state.dy);
+        }
+
+        // update the step size, for fixed-step methods restore the step size in case it was reduced to stop at tstop
+        if self.method == SdeMethod::Sra1 {
+            self.h = h * factor;
+        }
+        self.state.h = self.h;
+
+        // update statistics
+        self.statistics.number_of_steps += 1;
+
+        // check for root within accepted step
+        if let Some(root_fn) = ode.eqn.root() {
+            let ret = self.root_finder.as_ref().unwrap().check_root(
+                &|t| self.interpolate(t),
+                &root_fn,
+                &self.state.y,
+                self.state.t,
+            );
+            if let Some(root) = ret {
+                return Ok(OdeSolverStopReason::RootFound(root));
+            }
+        }
+
+        // check if we are at tstop
+        if let Some(tstop) = self.tstop {
+            if let Some(reason) = self.handle_tstop(tstop).unwrap() {
+                return Ok(reason);
+            }
+        }
+
+        // just a normal step, no roots or tstop reached
+        Ok(OdeSolverStopReason::InternalTimestep)
+    }
+
+    fn set_stop_time(&mut self, tstop: <Eqn as Op>::T) -> Result<(), DiffsolError> {
+        self.tstop = Some(tstop);
+        if let Some(OdeSolverStopReason::TstopReached) = self.handle_tstop(tstop)? {
+            let error = OdeSolverError::StopTimeBeforeCurrentTime {
+                stop_time: tstop.into(),
+                state_time: self.state.t.into(),
+            };
+            self.tstop = None;
+            return Err(DiffsolError::from(error));
+        }
+        Ok(())
+    }
+
+    fn interpolate_sens(&self, _t: <Eqn as Op>::T) -> Result<Vec<<Eqn as Op>::V>, DiffsolError> {
+        Ok(vec![])
+    }
+
+    fn interpolate(&self, t: <Eqn>::T) -> Result<<Eqn>::V, DiffsolError> {
+        if self.is_state_mutated {
+            if t == self.state.t {
+                return Ok(self.state.y.clone());
+            } else {
+                return Err(ode_solver_error!(InterpolationTimeOutsideCurrentStep));
+            }
+        }
+        let theta = self.interpolation_theta(t)?;
+        let mut y = self.old_y.clone();
+        y.axpy(theta, &self.state.y, Eqn::T::one() - theta);
+        Ok(y)
+    }
+
+    fn interpolate_out(&self, _t: <Eqn>::T) -> Result<<Eqn>::V, DiffsolError> {
+        Err(ode_solver_error!(
+            Other,
+            "Sde solvers do not support integrating the output function"
+        ))
+    }
+
+    fn state(&self) -> StateRef<Eqn::V> {
+        self.state.as_ref()
+    }
+
+    fn state_mut(&mut self) -> StateRefMut<Eqn::V> {
+        self.is_state_mutated = true;
+        self.state.as_mut()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        ode_solver::test_models::{
+            geometric_brownian_motion::{gbm_exact, gbm_problem},
+            ornstein_uhlenbeck::ou_problem,
+        },
+        NoiseType, OdeSolverMethod, OdeSolverStopReason, SdeMethod, Vector,
+    };
+
+    type M = nalgebra::DMatrix<f64>;
+    type V = nalgebra::DVector<f64>;
+
+    fn solve_to<'a, S: OdeSolverMethod<'a, Eqn>, Eqn: crate::OdeEquations<V = V, T = f64> + 'a>(
+        s: &mut S,
+        t: f64,
+    ) -> V {
+        s.set_stop_time(t).unwrap();
+        while s.step().unwrap() != OdeSolverStopReason::TstopReached {}
+        s.state().y.clone()
+    }
+
+    // mean error of a method at t = 1 compared with the exact solution of geometric brownian motion, over several paths
+    fn gbm_mean_error(method: SdeMethod) -> f64 {
+        let problem = gbm_problem::<M>(1e-3);
+        let nseeds = 10;
+        let mut error = 0.0;
+        for seed in 0..nseeds {
+            let mut s = match method {
+                SdeMethod::EulerMaruyama => problem.euler_maruyama(seed).unwrap(),
+                SdeMethod::Milstein => problem.milstein(seed).unwrap(),
+                SdeMethod::Sra1 => problem.sra1(seed).unwrap(),
+            };
+            let y = solve_to(&mut s, 1.0);
+            let mut path = s.brownian_path().clone();
+            let mut w = V::zeros(2);
+            path.sample(1.0, &mut w);
+            let y_exact = gbm_exact::<M>(problem.params(), 1.0, &w);
+            error += (y - y_exact).abs().max() / nseeds as f64;
+        }
+        error
+    }
+
+    #[test]
+    fn test_euler_maruyama_and_milstein_gbm() {
+        let em_error = gbm_mean_error(SdeMethod::EulerMaruyama);
+        let milstein_error = gbm_mean_error(SdeMethod::Milstein);
+        assert!(em_error < 5e-3, "em_error = {}", em_error);
+        assert!(milstein_error < 5e-4, "milstein_error = {}", milstein_error);
+        assert!(milstein_error < em_error);
+    }
+
+    #[test]
+    fn test_euler_maruyama_fixed_step() {
+        let problem = gbm_problem::<M>(1e-2);
+        let mut s = problem.euler_maruyama(0).unwrap();
+        solve_to(&mut s, 1.0);
+        assert_eq!(s.get_statistics().number_of_steps, 100);
+        assert_eq!(s.order(), 1);
+    }
+
+    #[test]
+    fn test_sra1_ou_matches_fine_euler_maruyama() {
+        let problem = ou_problem::<M>(0.1, NoiseType::Additive(1));
+        let mut s = problem.sra1(42).unwrap();
+        let y = solve_to(&mut s, 1.0);
+        let nsteps = s.get_statistics().number_of_steps;
+        assert!(nsteps < 1000, "nsteps = {}", nsteps);
+
+        // reference solution using a much smaller step on the same path
+        let mut state = problem.sde_state().unwrap();
+        state.h = 2e-4;
+        let path = s.brownian_path().clone();
+        let mut s_ref = problem
+            .sde_solver(SdeMethod::EulerMaruyama, state, path)
+            .unwrap();
+        let y_ref = solve_to(&mut s_ref, 1.0);
+        y.assert_eq_st(&y_ref, 5e-3);
+    }
+
+    #[test]
+    fn test_sde_is_reproducible() {
+        let problem = gbm_problem::<M>(1e-2);
+        let mut s1 = problem.milstein(7).unwrap();
+        let mut s2 = problem.milstein(7).unwrap();
+        let mut s3 = problem.milstein(8).unwrap();
+        let y1 = solve_to(&mut s1, 1.0);
+        let y2 = solve_to(&mut s2, 1.0);
+        let y3 = solve_to(&mut s3, 1.0);
+        assert_eq!(y1, y2);
+        assert_ne!(y1, y3);
+
+        let problem = ou_problem::<M>(0.1, NoiseType::Additive(1));
+        let mut s1 = problem.sra1(7).unwrap();
+        let mut s2 = problem.sra1(7).unwrap();
+        assert_eq!(solve_to(&mut s1, 1.0), solve_to(&mut s2, 1.0));
+        assert_eq!(s1.brownian_path().times(), s2.brownian_path().times());
+    }
+
+    #[test]
+    fn test_sde_rejects_unsupported_noise() {
+        let problem = ou_problem::<M>(0.1, NoiseType::General(1));
+        assert!(problem.euler_maruyama(0).is_ok());
+        assert!(problem.milstein(0).is_err());
+        assert!(problem.sra1(0).is_err());
+
+        let problem = gbm_problem::<M>(0.1);
+        assert!(problem.milstein(0).is_ok());
+        assert!(problem.sra1(0).is_err());
+
+        let state = problem.sde_state().unwrap();
+        let path = crate::BrownianPath::new(1, 0.0, 0);
+        assert!(problem
+            .sde_solver(SdeMethod::EulerMaruyama, state, path)
+            .is_err());
+    }
+}
@@ -0,0 +1,123 @@
+use crate::{
+    error::DiffsolError, op::closure_diffusion::ClosureDiffusion, BrownianPath, OdeEquations,
+    OdeSolverProblem, OdeSolverState, Sde, SdeMethod, SdirkState, Vector, VectorRef,
+};
+
+/// The structure of the diffusion term `G(t, y)` of a stochastic differential equation `dy = f(t, y) dt + G(t, y) dW`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum NoiseType {
+    /// one independent Wiener process per state, `G = diag(g(t, y))`
+    Diagonal,
+    /// a general `nstates x nnoise` matrix `G(t, y)`, with the number of independent Wiener processes given
+    General(usize),
+    /// a general `nstates x nnoise` matrix `G(t)` that does not depend on the state, with the number of independent Wiener processes given
+    Additive(usize),
+}
+
+/// A stochastic differential equation (SDE) problem of the form
+///
+/// $$
+///  dy = f(t, y) dt + G(t, y) dW
+/// $$
+///
+/// where `W` is a vector of independent Wiener processes. The drift `f` and everything else about the problem (initial state, tolerances, root and output functions)
+/// is given by the ODE problem [Self::ode], and the diffusion `G` is given by a closure computing its action on a vector of Wiener increments (see [NoiseType]).
+///
+/// SDE problems can be created using [crate::OdeBuilder::build_sde], and solved using the [Sde] solver (e.g. [Self::euler_maruyama], [Self::milstein], [Self::sra1]).
+pub struct SdeProblem<Eqn, F>
+where
+    Eqn: OdeEquations,
+    F: Fn(&Eqn::V, &Eqn::V, Eqn::T, &Eqn::V, &mut Eqn::V),
+{
+    pub ode: OdeSolverProblem<Eqn>,
+    pub diffusion: ClosureDiffusion<Eqn::M, F>,
+    p: Eqn::V,
+}
+
+impl<Eqn, F> SdeProblem<Eqn, F>
+where
+    Eqn: OdeEquations,
+    F: Fn(&Eqn::V, &Eqn::V, Eqn::T, &Eqn::V, &mut Eqn::V),
+{
+    /// Create a new SDE problem from an ODE problem giving the drift, the diffusion and the parameters `p` passed to the diffusion.
+    pub fn new(
+        ode: OdeSolverProblem<Eqn>,
+        diffusion: ClosureDiffusion<Eqn::M, F>,
+        p: Eqn::V,
+    ) -> Self {
+        Self { ode, diffusion, p }
+    }
+
+    pub fn params(&self) -> &Eqn::V {
+        &self.p
+    }
+
+    /// Set the parameters of both the drift and the diffusion.
+    pub fn set_params(&mut self, p: &Eqn::V) {
+        self.ode.eqn_mut().set_params(p);
+        self.p.copy_from(p);
+    }
+
+    pub fn noise(&self) -> NoiseType {
+        self.diffusion.noise()
+    }
+
+    /// the number of independent Wiener processes
+    pub fn nnoise(&self) -> usize {
+        self.diffusion.nnoise()
+    }
+
+    /// computes the action of the diffusion on a vector of Wiener increments, `y = G(t, x) dw`
+    pub fn diffusion_inplace(&self, x: &Eqn::V, t: Eqn::T, dw: &Eqn::V, y: &mut Eqn::V) {
+        self.diffusion.call_inplace(x, &self.p, t, dw, y)
+    }
+}
+
+impl<Eqn, F> SdeProblem<Eqn, F>
+where
+    Eqn: OdeEquations,
+    F: Fn(&Eqn::V, &Eqn::V, Eqn::T, &Eqn::V, &mut Eqn::V),
+    for<'b> &'b Eqn::V: VectorRef<Eqn::V>,
+{
+    /// Create a new state for the [Sde] solver, the initial step size is given by the ODE problem (see [crate::OdeBuilder::h0]).
+    pub fn sde_state(&self) -> Result<SdirkState<Eqn::V>, DiffsolError> {
+        let mut state = SdirkState::new_without_initialise(&self.ode)?;
+        state.set_consistent_explicit(&self.ode)?;
+        Ok(state)
+    }
+
+    /// Create a new [Sde] solver from a state and a Brownian path, this can be used to solve the problem on the same path as another solver.
+    pub fn sde_solver(
+        &self,
+        method: SdeMethod,
+        state: SdirkState<Eqn::V>,
+        path: BrownianPath<Eqn::V>,
+    ) -> Result<Sde<'_, Eqn, F>, DiffsolError> {
+        Sde::new(self, state, method, path)
+    }
+
+    fn sde_solver_with_seed(
+        &self,
+        method: SdeMethod,
+        seed: u64,
+    ) -> Result<Sde<'_, Eqn, F>, DiffsolError> {
+        let state = self.sde_state()?;
+        let path = BrownianPath::new(self.nnoise(), state.t, seed);
+        self.sde_solver(method, state, path)
+    }
+
+    /// Create a fixed-step [Sde] solver using the Euler-Maruyama method (see [SdeMethod::EulerMaruyama]), with the noise generated from `seed`.
+    pub fn euler_maruyama(&self, seed: u64) -> Result<Sde<'_, Eqn, F>, DiffsolError> {
+        self.sde_solver_with_seed(SdeMethod::EulerMaruyama, seed)
+    }
+
+    /// Create a fixed-step [Sde] solver using the Milstein method (see [SdeMethod::Milstein]), with the noise generated from `seed`.
+    pub fn milstein(&self, seed: u64) -> Result<Sde<'_, Eqn, F>, DiffsolError> {
+        self.sde_solver_with_seed(SdeMethod::Milstein, seed)
+    }
+
+    /// Create an adaptive [Sde] solver using the SRA1 method (see [SdeMethod::Sra1]), with the noise generated from `seed`.
+    pub fn sra1(&self, seed: u64) -> Result<Sde<'_, Eqn, F>, DiffsolError> {
+        self.sde_solver_with_seed(SdeMethod::Sra1, seed)
+    }
+}
@@ -1,3 +1,5 @@
+use nalgebra::ComplexField;
+
 use crate::error::DiffsolError;
 use crate::error::OdeSolverError;
 use crate::matrix::MatrixRef;
@@ -5,28 +7,34 @@ use crate::ode_solver_error;
 use crate::vector::VectorRef;
 use crate::AdjointEquations;
 use crate::DefaultDenseMatrix;
+use crate::FixedStep;
+use crate::IController;
 use crate::LinearSolver;
 use crate::NewtonNonlinearSolver;
 use crate::NoAug;
 use crate::OdeSolverStopReason;
 use crate::RootFinder;
 use crate::SdirkState;
+use crate::StepController;
 use crate::Tableau;
 use crate::{
     nonlinear_solver::NonLinearSolver, op::sdirk::SdirkCallable, scale, AdjointOdeSolverMethod,
     AugmentedOdeEquations, AugmentedOdeEquationsImplicit, Convergence, DenseMatrix, JacobianUpdate,
-    NonLinearOp, OdeEquationsAdjoint, OdeEquationsImplicit, OdeSolverMethod, OdeSolverProblem,
-    OdeSolverState, Op, Scalar, StateRef, StateRefMut, Vector, VectorViewMut,
+    ManifoldProjection, NonLinearOp, OdeEquationsAdjoint, OdeEquationsImplicit, OdeSolverMethod,
+    OdeSolverProblem, OdeSolverState, Op, StateRef, StateRefMut, Vector, VectorViewMut,
 };
 use num_traits::abs;
 use num_traits::One;
-use num_traits::Pow;
 use num_traits::Zero;
 use std::ops::MulAssign;
 
 use super::bdf::BdfStatistics;
+use super::constraints::{check_constraints, constraint_step_factor};
 use super::jacobian_update::SolverState;
-use super::method::AugmentedOdeSolverMethod;
+use super::method::{
+    check_interpolation_time, check_tstop, init_root_finder, AugmentedOdeSolverMethod, TstopCheck,
+};
+use crate::nonlinear_solver::error_norm::squared_norm;
 
 impl<'a, M, Eqn, LS, AugEqn> AugmentedOdeSolverMethod<'a, Eqn, AugEqn>
     for Sdirk<'a, Eqn, LS, M, AugEqn>
@@ -116,6 +124,10 @@ pub struct Sdirk<
     tstop: Option<Eqn::T>,
     is_state_mutated: bool,
     jacobian_update: JacobianUpdate<Eqn::T>,
+    fixed_step: Option<FixedStep<Eqn::T>>,
+    step_controller: Box<dyn StepController<Eqn::T>>,
+    steps_since_stop_time: usize,
+    projection: Option<ManifoldProjection<'a, Eqn::M>>,
 }
 
 impl<M, Eqn, LS, AugmentedEqn> Clone for Sdirk<'_, Eqn, LS, M, AugmentedEqn>
@@ -169,6 +181,10 @@ where
             tstop: self.tstop,
             is_state_mutated: self.is_state_mutated,
             jacobian_update: self.jacobian_update.clone(),
+            fixed_step: self.fixed_step.clone(),
+            projection: self.projection.clone(),
+            step_controller: self.step_controller.clone(),
+            steps_since_stop_time: self.steps_since_stop_time,
         }
     }
 }
@@ -269,7 +285,7 @@ where
         }
 
         // setup linear solver for first step
-        let mut jacobian_update = JacobianUpdate::default();
+        let mut jacobian_update = JacobianUpdate::from_options(&problem.options);
         jacobian_update.update_jacobian(state.h);
         jacobian_update.update_rhs_jacobian();
 
@@ -277,7 +293,13 @@ where
 
         // set max iterations for nonlinear solver
         let mut convergence = Convergence::new(problem.rtol, &problem.atol);
-        convergence.set_max_iter(Self::NEWTON_MAXITER);
+        convergence.set_error_norm(problem.options.error_norm.clone());
+        convergence.set_max_iter(
+            problem
+                .options
+                .max_newton_iter
+                .unwrap_or(Self::NEWTON_MAXITER),
+        );
 
         let op = if integrate_main_eqn {
             let callable = SdirkCallable::new(&problem.eqn, gamma);
@@ -293,6 +315,9 @@ where
         let statistics = BdfStatistics::default();
 
         state.check_consistent_with_problem(problem)?;
+        if let Some(constraints) = problem.options.constraints.as_ref() {
+            check_constraints(constraints, state.y.as_slice())?;
+        }
 
         let nstates = state.y.len();
         let order = tableau.s();
@@ -348,6 +373,10 @@ where
             tstop: None,
             is_state_mutated: false,
             jacobian_update,
+            fixed_step: None,
+            projection: problem.projection.clone(),
+            step_controller: Box::new(IController),
+            steps_since_stop_time: 0,
         })
     }
 
@@ -392,40 +421,60 @@ where
         &self.statistics
     }
 
-    fn handle_tstop(
+    /// Run the solver in fixed-step mode, taking the steps given by `fixed_step` with error control disabled (see [FixedStep]),
+    /// or pass `None` to return to adaptive step size control.
+    pub fn set_fixed_step(
         &mut self,
-        tstop: Eqn::T,
-    ) -> Result<Option<OdeSolverStopReason<Eqn::T>>, DiffsolError> {
-        let state = &mut self.state;
+        fixed_step: Option<FixedStep<Eqn::T>>,
+    ) -> Result<(), DiffsolError> {
+        if let Some(fixed_step) = fixed_step.as_ref() {
+            fixed_step.check()?;
+        }
+        self.fixed_step = fixed_step;
+        Ok(())
+    }
 
-        // check if the we are at tstop
-        let troundoff = Eqn::T::from(100.0) * Eqn::T::EPSILON * (abs(state.t) + abs(state.h));
-        if abs(state.t - tstop) <= troundoff {
-            self.tstop = None;
-            return Ok(Some(OdeSolverStopReason::TstopReached));
-        } else if (state.h > M::T::zero() && tstop < state.t - troundoff)
-            || (state.h < M::T::zero() && tstop > state.t + troundoff)
-        {
-            return Err(DiffsolError::from(
-                OdeSolverError::StopTimeBeforeCurrentTime {
-                    stop_time: tstop.into(),
-                    state_time: state.t.into(),
-                },
-            ));
+    pub fn fixed_step(&self) -> Option<&FixedStep<Eqn::T>> {
+        self.fixed_step.as_ref()
+    }
+
+    /// Project the solution onto the manifold of a set of invariants after each step (see [ManifoldProjection]),
+    /// or pass `None` to turn off the projection.
+    pub fn set_projection(
+        &mut self,
+        projection: Option<ManifoldProjection<'a, Eqn::M>>,
+    ) -> Result<(), DiffsolError> {
+        if let Some(projection) = projection.as_ref() {
+            projection.check(self.state.y.len())?;
         }
+        self.projection = projection;
+        Ok(())
+    }
 
-        // check if the next step will be beyond tstop, if so adjust the step size
-        if (state.h > M::T::zero() && state.t + state.h > tstop + troundoff)
-            || (state.h < M::T::zero() && state.t + state.h < tstop - troundoff)
-        {
-            let factor = (tstop - state.t) / state.h;
-            state.h *= factor;
-            if let Some(op) = self.op.as_mut() {
-                op.set_h(state.h);
+    /// Set the controller used to choose the step size after each step (default [IController]).
+    pub fn set_step_controller(&mut self, step_controller: impl StepController<Eqn::T> + 'static) {
+        self.step_controller = Box::new(step_controller);
+    }
+
+    fn handle_tstop(
+        &mut self,
+        tstop: Eqn::T,
+    ) -> Result<Option<OdeSolverStopReason<Eqn::T>>, DiffsolError> {
+        match check_tstop(self.state.t, self.state.h, tstop)? {
+            TstopCheck::Reached => {
+                self.tstop = None;
+                return Ok(Some(OdeSolverStopReason::TstopReached));
             }
-            if let Some(s_op) = self.s_op.as_mut() {
-                s_op.set_h(state.h);
+            TstopCheck::ScaleStep(factor) => {
+                self.state.h *= factor;
+                if let Some(op) = self.op.as_mut() {
+                    op.set_h(self.state.h);
+                }
+                if let Some(s_op) = self.s_op.as_mut() {
+                    s_op.set_h(self.state.h);
+                }
             }
+            TstopCheck::NotReached => {}
         }
         Ok(None)
     }
@@ -464,8 +513,13 @@ where
 
             // solve
             let op = self.s_op.as_ref().unwrap();
-            self.nonlinear_solver
-                .solve_in_place(op, ds, t, s0, &mut self.convergence)?;
+            self.nonlinear_solver.solve_in_place_with_jacobian(
+                op,
+                ds,
+                t,
+                s0,
+                &mut self.convergence,
+            )?;
 
             self.old_y_sens[j].copy_from(&op.get_last_f_eval());
             self.statistics.number_of_nonlinear_solver_iterations += self.convergence.niter();
@@ -538,11 +592,33 @@ where
         }
     }
 
+    // limit the step size factor returned by the step controller
+    fn clamp_factor(factor: Eqn::T) -> Eqn::T {
+        if factor < Eqn::T::from(Self::MIN_FACTOR) {
+            Eqn::T::from(Self::MIN_FACTOR)
+        } else if factor > Eqn::T::from(Self::MAX_FACTOR) {
+            Eqn::T::from(Self::MAX_FACTOR)
+        } else {
+            factor
+        }
+    }
+
     fn _update_step_size(&mut self, factor: Eqn::T) -> Result<Eqn::T, DiffsolError> {
+        // limit the new step size to the maximum step size, unless the steps are prescribed in fixed-step mode
+        let mut factor = factor;
+        let max_step = self.problem.options.max_step;
+        if self.fixed_step.is_none() && abs(factor * self.state.h) > max_step {
+            factor = max_step / abs(factor * self.state.h) * factor;
+        }
         let new_h = self.state.h * factor;
 
         // if step size too small, then fail
-        if abs(new_h) < Eqn::T::from(Self::MIN_TIMESTEP) {
+        let min_step = self
+            .problem
+            .options
+            .min_step
+            .unwrap_or(Eqn::T::from(Self::MIN_TIMESTEP));
+        if abs(new_h) < min_step {
             return Err(DiffsolError::from(OdeSolverError::StepSizeTooSmall {
                 time: self.state.t.into(),
             }));
@@ -585,6 +661,7 @@ where
 
     fn set_state(&mut self, state: Self::State) {
         self.state = state;
+        self.steps_since_stop_time = 0;
 
         // update the op with the new state
         if let Some(op) = self.op.as_mut() {
@@ -608,22 +685,60 @@ where
         let n = self.state.y.len();
 
         if self.is_state_mutated {
-            // reinitalise root finder if needed
-            if let Some(root_fn) = self.problem.eqn.root() {
-                let state = &self.state;
-                self.root_finder
-                    .as_ref()
-                    .unwrap()
-                    .init(&root_fn, &state.y, state.t);
-            }
+            // reinitialise root finder if needed
+            init_root_finder(
+                self.problem,
+                self.root_finder.as_ref(),
+                &self.state.y,
+                self.state.t,
+            );
             // reinitialise tstop if needed
             if let Some(t_stop) = self.tstop {
                 self.set_stop_time(t_stop)?;
             }
 
+            // the step size might have been modified (e.g. reversed to integrate backwards in time)
+            if let Some(op) = self.op.as_mut() {
+                op.set_h(self.state.h);
+            }
+            if let Some(s_op) = self.s_op.as_mut() {
+                s_op.set_h(self.state.h);
+            }
+            self._jacobian_updates(self.state.h, SolverState::StepSuccess);
+
+            self.step_controller.reset();
+            self.steps_since_stop_time = 0;
             self.is_state_mutated = false;
         }
 
+        if let Some(max_steps) = self.problem.options.max_steps {
+            if self.steps_since_stop_time >= max_steps {
+                return Err(DiffsolError::from(OdeSolverError::MaxStepsReached {
+                    max_steps,
+                    time: self.state.t.into(),
+                }));
+            }
+        }
+
+        // in fixed-step mode set the prescribed step size, shortened if needed to stop at tstop
+        if let Some(fixed_step) = self.fixed_step.as_ref() {
+            let t = self.state.t;
+            let mut h = fixed_step.step_size(t)?;
+            if let Some(tstop) = self.tstop {
+                if (h > Eqn::T::zero() && t + h > tstop) || (h < Eqn::T::zero() && t + h < tstop) {
+                    h = tstop - t;
+                }
+            }
+            if h != self.state.h {
+                let new_h = self._update_step_size(h / self.state.h)?;
+                self._jacobian_updates(new_h, SolverState::StepSuccess);
+            }
+        } else if abs(self.state.h) > self.problem.options.max_step {
+            // the step size might be larger than the maximum (e.g. the initial step size)
+            let new_h = self._update_step_size(Eqn::T::one())?;
+            self._jacobian_updates(new_h, SolverState::StepSuccess);
+        }
+
         // optionally do the first step
         let start = if self.is_sdirk { 0 } else { 1 };
         let mut updated_jacobian = false;
@@ -656,10 +771,8 @@ where
             <Eqn::V as Vector>::zeros(0)
         };
 
-        let mut factor: Eqn::T;
-
-        // loop until step is accepted
-        'step: loop {
+        // loop until step is accepted, returning the error norm and safety factor of the accepted step
+        let (error_norm, safety) = 'step: loop {
             let t0 = self.state.t;
             let h = self.state.h;
             // if start == 1, then we need to compute the first stage
@@ -703,7 +816,7 @@ where
                 if let Some(op) = self.op.as_mut() {
                     op.set_phi(&self.diff.columns(0, i), &self.state.y, &self.a_rows[i]);
                     Self::predict_stage(i, &self.diff, &mut self.old_f, &self.tableau);
-                    solve_result = self.nonlinear_solver.solve_in_place(
+                    solve_result = self.nonlinear_solver.solve_in_place_with_jacobian(
                         op,
                         &mut self.old_f,
                         t,
@@ -726,12 +839,15 @@ where
                 }
 
                 // handle solve failure
-                if solve_result.is_err() {
+                if let Err(err) = solve_result {
                     self.statistics.number_of_nonlinear_solver_fails += 1;
                     if !updated_jacobian {
                         // newton iteration did not converge, so update jacobian and try again
                         updated_jacobian = true;
                         self._jacobian_updates(h, SolverState::FirstConvergenceFail);
+                    } else if self.fixed_step.is_some() {
+                        // the step size is prescribed in fixed-step mode, so report the failure
+                        return Err(err);
                     } else {
                         // newton iteration did not converge and jacobian has been updated, so we reduce step size and try again
                         let new_h = self._update_step_size(Eqn::T::from(0.3))?;
@@ -761,6 +877,31 @@ where
                     }
                 }
             }
+
+            // check the inequality constraints on the new solution, which is the last stage (stored in old_y)
+            if let (Some(constraints), true) =
+                (self.problem.options.constraints.as_ref(), self.op.is_some())
+            {
+                if let Some(factor) = constraint_step_factor(
+                    constraints,
+                    self.state.y.as_slice(),
+                    self.old_y.as_mut_slice(),
+                    self.problem.atol.as_slice(),
+                    self.problem.rtol,
+                ) {
+                    if self.fixed_step.is_some() {
+                        return Err(ode_solver_error!(
+                            Other,
+                            format!("Constraints violated at time {} in fixed-step mode", t0 + h)
+                        ));
+                    }
+                    self.statistics.number_of_constraint_failures += 1;
+                    let new_h = self._update_step_size(factor)?;
+                    self._jacobian_updates(new_h, SolverState::ErrorTestFail);
+                    continue 'step;
+                }
+            }
+
             let mut ncontributions = 0;
             let mut error_norm = Eqn::T::zero();
             // successfully solved for all stages, now compute error
@@ -771,7 +912,13 @@ where
                 // compute error norm
                 let atol = &self.problem().atol;
                 let rtol = self.problem().rtol;
-                error_norm += error.squared_norm(&self.old_y, atol, rtol);
+                error_norm += squared_norm(
+                    self.problem.options.error_norm.as_ref(),
+                    &error,
+                    &self.old_y,
+                    atol,
+                    rtol,
+                );
                 ncontributions += 1;
 
                 // output errors
@@ -832,26 +979,45 @@ where
             let maxiter = self.convergence.max_iter() as f64;
             let niter = self.convergence.niter() as f64;
             let safety = Eqn::T::from(0.9 * (2.0 * maxiter + 1.0) / (2.0 * maxiter + niter));
-            let order = self.tableau.order() as f64;
-            factor = safety * error_norm.pow(Eqn::T::from(-0.5 / (order + 1.0)));
-            if factor < Eqn::T::from(Self::MIN_FACTOR) {
-                factor = Eqn::T::from(Self::MIN_FACTOR);
-            }
-            if factor > Eqn::T::from(Self::MAX_FACTOR) {
-                factor = Eqn::T::from(Self::MAX_FACTOR);
+            let k = self.tableau.order() + 1;
+            let accepted = error_norm <= Eqn::T::from(1.0) || self.fixed_step.is_some();
+
+            // project the new solution onto the invariant manifold, if this fails then reduce the step size and try again
+            if let (Some(projection), true) =
+                (self.projection.as_ref(), accepted && self.op.is_some())
+            {
+                let problem = self.problem;
+                if let Err(err) = projection.project(
+                    &mut self.old_y,
+                    t0 + h,
+                    problem.rtol,
+                    &problem.atol,
+                    problem.options.error_norm.clone(),
+                ) {
+                    if self.fixed_step.is_some() {
+                        return Err(err);
+                    }
+                    let new_h = self._update_step_size(Eqn::T::from(0.25))?;
+                    self._jacobian_updates(new_h, SolverState::ErrorTestFail);
+                    continue 'step;
+                }
             }
 
-            // test error is within tolerance
-            if error_norm <= Eqn::T::from(1.0) {
-                break 'step;
+            // test error is within tolerance (always passes in fixed-step mode), the step controller is only told
+            // about the accepted step once the step has been taken below
+            if accepted {
+                break 'step (error_norm, safety);
             }
             // step is rejected, factor reduces step size, so we try again with the smaller step size
             self.statistics.number_of_error_test_failures += 1;
+            let factor =
+                Self::clamp_factor(self.step_controller.reject(h, error_norm.sqrt(), k, safety));
             let new_h = self._update_step_size(factor)?;
             self._jacobian_updates(new_h, SolverState::ErrorTestFail);
-        }
+        };
 
         // take the step
+        let h = self.state.h;
         {
             let state = &mut self.state;
             self.old_t = state.t;
@@ -888,9 +1054,14 @@ where
             }
         }
 
-        // update step size for next step
-        let new_h = self._update_step_size(factor)?;
-        self._jacobian_updates(new_h, SolverState::StepSuccess);
+        // update step size for next step (in fixed-step mode this is set at the start of the next step)
+        if self.fixed_step.is_none() {
+            let k = self.tableau.order() + 1;
+            let factor =
+                Self::clamp_factor(self.step_controller.accept(h, error_norm.sqrt(), k, safety));
+            let new_h = self._update_step_size(factor)?;
+            self._jacobian_updates(new_h, SolverState::StepSuccess);
+        }
 
         // update statistics
         if let Some(op) = self.op.as_ref() {
@@ -899,6 +1070,7 @@ where
             self.statistics.number_of_linear_solver_setups = s_op.number_of_jac_evals();
         }
         self.statistics.number_of_steps += 1;
+        self.steps_since_stop_time += 1;
         self.jacobian_update.step();
 
         // check for root within accepted step
@@ -914,7 +1086,7 @@ where
             }
         }
 
-        // check if the we are at tstop
+        // check if we are at tstop
         if let Some(tstop) = self.tstop {
             if let Some(reason) = self.handle_tstop(tstop).unwrap() {
                 return Ok(reason);
@@ -925,8 +1097,16 @@ where
         Ok(OdeSolverStopReason::InternalTimestep)
     }
 
+    fn reverse_direction(&mut self) -> Result<(), DiffsolError> {
+        let new_h = self._update_step_size(-Eqn::T::one())?;
+        self._jacobian_updates(new_h, SolverState::StepSuccess);
+        self.tstop = None;
+        Ok(())
+    }
+
     fn set_stop_time(&mut self, tstop: <Eqn as Op>::T) -> Result<(), DiffsolError> {
         self.tstop = Some(tstop);
+        self.steps_since_stop_time = 0;
         if let Some(OdeSolverStopReason::TstopReached) = self.handle_tstop(tstop)? {
             let error = OdeSolverError::StopTimeBeforeCurrentTime {
                 stop_time: tstop.into(),
@@ -949,13 +1129,8 @@ where
             }
         }
 
-        // check that t is within the current step depending on the direction
-        let is_forward = state.h > Eqn::T::zero();
-        if (is_forward && (t > state.t || t < self.old_t))
-            || (!is_forward && (t < state.t || t > self.old_t))
-        {
-            return Err(ode_solver_error!(InterpolationTimeOutsideCurrentStep));
-        }
+        // check that t is within the last step
+        check_interpolation_time(t, self.old_t, state.t)?;
         let dt = state.t - self.old_t;
         let theta = if dt == Eqn::T::zero() {
             Eqn::T::one()
@@ -995,13 +1170,8 @@ where
             }
         }
 
-        // check that t is within the current step depending on the direction
-        let is_forward = state.h > Eqn::T::zero();
-        if (is_forward && (t > state.t || t < self.old_t))
-            || (!is_forward && (t < state.t || t > self.old_t))
-        {
-            return Err(ode_solver_error!(InterpolationTimeOutsideCurrentStep));
-        }
+        // check that t is within the last step
+        check_interpolation_time(t, self.old_t, state.t)?;
 
         let dt = state.t - self.old_t;
         let theta = if dt == Eqn::T::zero() {
@@ -1031,13 +1201,8 @@ where
             }
         }
 
-        // check that t is within the current step depending on the direction
-        let is_forward = state.h > Eqn::T::zero();
-        if (is_forward && (t > state.t || t < self.old_t))
-            || (!is_forward && (t < state.t || t > self.old_t))
-        {
-            return Err(ode_solver_error!(InterpolationTimeOutsideCurrentStep));
-        }
+        // check that t is within the last step
+        check_interpolation_time(t, self.old_t, state.t)?;
 
         let dt = state.t - self.old_t;
         let theta = if dt == Eqn::T::zero() {
@@ -1080,15 +1245,24 @@ mod test {
                 heat2d::head2d_problem,
                 robertson::{robertson, robertson_sens},
                 robertson_ode::robertson_ode,
+                spring_mass::spring_mass_problem,
             },
             tests::{
-                test_checkpointing, test_interpolate, test_ode_solver, test_ode_solver_adjoint,
-                test_problem, test_state_mut, test_state_mut_on_problem,
+                robertson_ode_with_constraints, spring_mass_energy, test_checkpointing,
+                test_constraints, test_error_norm, test_fixed_step, test_interpolate,
+                test_max_step_and_max_steps, test_ode_solver, test_ode_solver_adjoint,
+                test_problem, test_projection, test_state_mut, test_state_mut_on_problem,
+                test_step_controller, RecordingController,
             },
         },
-        FaerSparseLU, NalgebraLU, OdeEquations, OdeSolverMethod, Op, SparseColMat, Vector,
+        BiCGStab, Constraint, ErrorNorm, FaerSparseLU, FixedStep, Gmres, Jacobi,
+        ManifoldProjection, MaxNorm, NalgebraLU, OdeBuilder, OdeEquations, OdeSolverMethod, Op,
+        PidController, PredictiveController, SolverOptions, SparseColMat, Vector, WeightedL2Norm,
     };
 
+    use std::sync::Arc;
+
+    use nalgebra::DVector;
     use num_traits::abs;
 
     type M = nalgebra::DMatrix<f64>;
@@ -1136,6 +1310,7 @@ mod test {
         number_of_error_test_failures: 0
         number_of_nonlinear_solver_iterations: 116
         number_of_nonlinear_solver_fails: 0
+        number_of_constraint_failures: 0
         "###);
         insta::assert_yaml_snapshot!(problem.eqn.rhs().statistics(), @r###"
         number_of_calls: 118
@@ -1156,6 +1331,7 @@ mod test {
         number_of_error_test_failures: 1
         number_of_nonlinear_solver_iterations: 540
         number_of_nonlinear_solver_fails: 0
+        number_of_constraint_failures: 0
         "###);
         insta::assert_yaml_snapshot!(problem.eqn.rhs().statistics(), @r###"
         number_of_calls: 218
@@ -1176,6 +1352,7 @@ mod test {
         number_of_error_test_failures: 0
         number_of_nonlinear_solver_iterations: 84
         number_of_nonlinear_solver_fails: 0
+        number_of_constraint_failures: 0
         "###);
         insta::assert_yaml_snapshot!(problem.eqn.rhs().statistics(), @r###"
         number_of_calls: 86
@@ -1196,6 +1373,7 @@ mod test {
         number_of_error_test_failures: 1
         number_of_nonlinear_solver_iterations: 332
         number_of_nonlinear_solver_fails: 0
+        number_of_constraint_failures: 0
         "###);
         insta::assert_yaml_snapshot!(problem.eqn.rhs().statistics(), @r###"
         number_of_calls: 128
@@ -1242,6 +1420,7 @@ mod test {
         number_of_error_test_failures: 0
         number_of_nonlinear_solver_iterations: 1921
         number_of_nonlinear_solver_fails: 18
+        number_of_constraint_failures: 0
         "###);
         insta::assert_yaml_snapshot!(problem.eqn.rhs().statistics(), @r###"
         number_of_calls: 1924
@@ -1262,6 +1441,7 @@ mod test {
         number_of_error_test_failures: 0
         number_of_nonlinear_solver_iterations: 4544
         number_of_nonlinear_solver_fails: 36
+        number_of_constraint_failures: 0
         "###);
         insta::assert_yaml_snapshot!(problem.eqn.rhs().statistics(), @r###"
         number_of_calls: 1443
@@ -1282,6 +1462,7 @@ mod test {
         number_of_error_test_failures: 0
         number_of_nonlinear_solver_iterations: 1793
         number_of_nonlinear_solver_fails: 24
+        number_of_constraint_failures: 0
         "###);
         insta::assert_yaml_snapshot!(problem.eqn.rhs().statistics(), @r###"
         number_of_calls: 1796
@@ -1291,6 +1472,33 @@ mod test {
         "###);
     }
 
+    #[test]
+    fn test_esdirk34_nalgebra_robertson_pid_controller() {
+        let (problem, soln) = robertson::<M>(false);
+        let mut s = problem.esdirk34::<LS>().unwrap();
+        s.set_step_controller(PidController::default());
+        test_ode_solver(&mut s, soln, None, false, false);
+    }
+
+    #[test]
+    fn test_step_controller_calls_esdirk34() {
+        let (problem, _soln) = robertson::<M>(false);
+        let controller = RecordingController::default();
+        let mut s = problem.esdirk34::<LS>().unwrap();
+        s.set_step_controller(controller.clone());
+        let (naccept, nreject, nsteps) = test_step_controller(&mut s, &controller, 1.0);
+        assert_eq!(naccept, nsteps);
+        assert_eq!(nreject, s.get_statistics().number_of_error_test_failures);
+    }
+
+    #[test]
+    fn test_esdirk34_nalgebra_robertson_predictive_controller() {
+        let (problem, soln) = robertson::<M>(false);
+        let mut s = problem.esdirk34::<LS>().unwrap();
+        s.set_step_controller(PredictiveController::default());
+        test_ode_solver(&mut s, soln, None, false, false);
+    }
+
     #[test]
     fn test_esdirk34_nalgebra_robertson_sens() {
         let (problem, soln) = robertson_sens::<M>();
@@ -1302,6 +1510,7 @@ mod test {
         number_of_error_test_failures: 0
         number_of_nonlinear_solver_iterations: 4442
         number_of_nonlinear_solver_fails: 44
+        number_of_constraint_failures: 0
         "###);
         insta::assert_yaml_snapshot!(problem.eqn.rhs().statistics(), @r###"
         number_of_calls: 1492
@@ -1322,6 +1531,7 @@ mod test {
         number_of_error_test_failures: 1
         number_of_nonlinear_solver_iterations: 2601
         number_of_nonlinear_solver_fails: 15
+        number_of_constraint_failures: 0
         "###);
         insta::assert_yaml_snapshot!(problem.eqn.rhs().statistics(), @r###"
         number_of_calls: 2603
@@ -1338,6 +1548,21 @@ mod test {
         test_ode_solver(&mut s, soln, None, false, false);
     }
 
+    #[test]
+    fn test_tr_bdf2_krylov_heat2d() {
+        let (problem, soln) = head2d_problem::<SparseColMat<f64>, 10>();
+        let mut s = problem.tr_bdf2::<Gmres<SparseColMat<f64>>>().unwrap();
+        test_ode_solver(&mut s, soln, None, false, false);
+        let (problem, soln) = head2d_problem::<SparseColMat<f64>, 10>();
+        let mut s = problem.tr_bdf2::<BiCGStab<SparseColMat<f64>>>().unwrap();
+        test_ode_solver(&mut s, soln, None, false, false);
+        let (problem, soln) = head2d_problem::<SparseColMat<f64>, 10>();
+        let mut s = problem
+            .tr_bdf2::<BiCGStab<SparseColMat<f64>, Jacobi<SparseColMat<f64>>>>()
+            .unwrap();
+        test_ode_solver(&mut s, soln, None, false, false);
+    }
+
     #[test]
     fn test_tstop_tr_bdf2() {
         let (problem, soln) = exponential_decay_problem::<M>(false);
@@ -1345,6 +1570,36 @@ mod test {
         test_ode_solver(&mut s, soln, None, true, false);
     }
 
+    #[test]
+    fn test_fixed_step_esdirk34() {
+        let (problem, soln) = exponential_decay_problem::<M>(false);
+        let expected = &soln.solution_points[1];
+        let mut s = problem.esdirk34::<LS>().unwrap();
+        s.set_fixed_step(Some(FixedStep::Constant(0.25))).unwrap();
+        test_fixed_step(s, &[0.25, 0.5, 0.75, 1.0], &expected.state, 1e-5);
+        let mut s = problem.esdirk34::<LS>().unwrap();
+        s.set_fixed_step(Some(FixedStep::Grid(vec![0.0, 0.1, 0.3, 0.7, 1.5])))
+            .unwrap();
+        test_fixed_step(s, &[0.1, 0.3, 0.7, 1.0], &expected.state, 1e-4);
+
+        // the prescribed steps are taken even if they are larger than max_step
+        let (mut problem_max_step, _soln) = exponential_decay_problem::<M>(false);
+        problem_max_step.options.max_step = 0.1;
+        let mut s = problem_max_step.esdirk34::<LS>().unwrap();
+        s.set_fixed_step(Some(FixedStep::Grid(vec![0.0, 0.5, 1.0])))
+            .unwrap();
+        test_fixed_step(s, &[0.5, 1.0], &expected.state, 1e-4);
+
+        // stepping past the end of the grid is an error
+        let mut s = problem.esdirk34::<LS>().unwrap();
+        s.set_fixed_step(Some(FixedStep::Grid(vec![0.0, 0.5, 1.0])))
+            .unwrap();
+        s.step().unwrap();
+        s.step().unwrap();
+        assert!(abs(s.state().t - 1.0) < 1e-12);
+        assert!(s.step().is_err());
+    }
+
     #[test]
     fn test_root_finder_tr_bdf2() {
         let (problem, soln) = exponential_decay_problem_with_root::<M>(false);
@@ -1398,4 +1653,91 @@ mod test {
             assert!((t - expected_t[i]).abs() < 1e-4);
         }
     }
+
+    #[test]
+    fn test_solver_options_esdirk34() {
+        let (mut problem, _soln) = exponential_decay_problem::<M>(false);
+        problem.options = SolverOptions {
+            max_step: 0.2,
+            max_steps: Some(20),
+            ..Default::default()
+        };
+        test_max_step_and_max_steps(problem.esdirk34::<LS>().unwrap());
+    }
+
+    #[test]
+    fn test_constraints_esdirk34() {
+        let (problem, soln) = robertson_ode_with_constraints::<M>();
+        let mut s = problem.esdirk34::<LS>().unwrap();
+        test_constraints(&mut s, &soln);
+
+        // dy/dt = -1, y(0) = 1 reaches zero at t = 1, so a step of size 2 violates the constraint y >= 0 and is rejected
+        let problem = OdeBuilder::<M>::new()
+            .rhs_implicit(|_x, _p, _t, y| y[0] = -1.0, |_x, _p, _t, _v, y| y[0] = 0.0)
+            .init(|_p, _t| nalgebra::DVector::from_element(1, 1.0))
+            .constraints([Constraint::NonNegative])
+            .build()
+            .unwrap();
+        let mut state = problem.esdirk34_state::<LS>().unwrap();
+        state.h = 2.0;
+        let mut s = problem.esdirk34_solver::<LS>(state).unwrap();
+        s.step().unwrap();
+        assert!(s.state().y[0] >= 0.0, "y = {}", s.state().y[0]);
+        assert!(s.state().t < 1.0, "t = {}", s.state().t);
+        assert!(s.get_statistics().number_of_constraint_failures > 0);
+
+        // the initial state must satisfy the constraints
+        let problem = OdeBuilder::<M>::new()
+            .rhs_implicit(|_x, _p, _t, y| y[0] = 1.0, |_x, _p, _t, _v, y| y[0] = 0.0)
+            .init(|_p, _t| nalgebra::DVector::from_element(1, 1.0))
+            .constraints([Constraint::Negative])
+            .build()
+            .unwrap();
+        assert!(problem.esdirk34::<LS>().is_err());
+    }
+
+    #[test]
+    fn test_projection_esdirk34() {
+        let (problem, soln) = spring_mass_problem::<M>(false, 0.0);
+        let problem = problem.with_projection(Some(spring_mass_energy())).unwrap();
+        test_projection(problem.esdirk34::<LS>().unwrap(), soln, 1e-8);
+
+        // the projection can also be set on the solver, and the invariants must have the same number of inputs as there are states
+        let (problem, _soln) = spring_mass_problem::<M>(false, 0.0);
+        let mut s = problem.esdirk34::<LS>().unwrap();
+        s.set_projection(Some(spring_mass_energy())).unwrap();
+        let wrong_size = || {
+            ManifoldProjection::from_closures(
+                |_x: &DVector<f64>, _t, g: &mut DVector<f64>| g[0] = 0.0,
+                |_x: &DVector<f64>, _t, _v: &DVector<f64>, y: &mut DVector<f64>| y[0] = 0.0,
+                3,
+                1,
+            )
+        };
+        assert!(s.set_projection(Some(wrong_size())).is_err());
+        let (problem, _soln) = spring_mass_problem::<M>(false, 0.0);
+        assert!(problem.with_projection(Some(wrong_size())).is_err());
+    }
+
+    #[test]
+    fn test_error_norm_esdirk34() {
+        // the max norm and a weighted L2 norm that excludes the second component
+        let (mut problem, soln) = exponential_decay_problem::<M>(false);
+        let norms: [Arc<dyn ErrorNorm<f64>>; 2] = [
+            Arc::new(MaxNorm),
+            Arc::new(WeightedL2Norm::new(vec![1.0, 0.0])),
+        ];
+        for norm in norms {
+            problem.options.error_norm = Some(norm);
+            test_error_norm(problem.esdirk34::<LS>().unwrap(), &soln, 1e-4);
+        }
+
+        // the weights must match the number of states
+        let problem = OdeBuilder::<M>::new()
+            .rhs_implicit(|_x, _p, _t, y| y[0] = -1.0, |_x, _p, _t, _v, y| y[0] = 0.0)
+            .init(|_p, _t| DVector::from_element(1, 1.0))
+            .error_norm(WeightedL2Norm::new(vec![1.0, 1.0]))
+            .build();
+        assert!(problem.is_err());
+    }
 }
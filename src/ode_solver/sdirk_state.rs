@@ -17,6 +17,20 @@ pub struct SdirkState<V: Vector> {
     pub(crate) dsg: Vec<V>,
     pub(crate) t: V::T,
     pub(crate) h: V::T,
+    pub(crate) last_step: Option<RkLastStep<V>>,
+}
+
+/// The start and stage increments of the last step taken by an explicit Runge-Kutta solver, stored in a checkpoint
+/// so that the solution can still be interpolated within this step after the solver is restored from it
+#[derive(Clone)]
+pub(crate) struct RkLastStep<V: Vector> {
+    pub(crate) t: V::T,
+    pub(crate) y: V,
+    pub(crate) g: V,
+    pub(crate) s: Vec<V>,
+    pub(crate) diff: Vec<V>,
+    pub(crate) gdiff: Vec<V>,
+    pub(crate) sdiff: Vec<Vec<V>>,
 }
 
 impl<V> SdirkState<V> where V: Vector {}
@@ -52,6 +66,7 @@ where
             dsg: state.dsg,
             t: state.t,
             h: state.h,
+            last_step: None,
         }
     }
 
@@ -370,6 +370,7 @@ mod tests {
             s: Vec::new(),
             ds: Vec::new(),
             h: 0.0,
+            last_step: None,
         };
         // S = f_p - M_p * dy/dt
         // f_p = -y (a = 0.1)
@@ -410,6 +411,7 @@ mod tests {
             s: Vec::new(),
             ds: Vec::new(),
             h: 0.0,
+            last_step: None,
         };
 
         // S = f_p - M_p * dy/dt
@@ -459,6 +461,7 @@ mod tests {
             s: Vec::new(),
             ds: Vec::new(),
             h: 0.0,
+            last_step: None,
         };
 
         // S = f_p - M_p * dy/dt
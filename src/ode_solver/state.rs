@@ -5,8 +5,9 @@ use crate::{
     error::{DiffsolError, OdeSolverError},
     nonlinear_solver::{convergence::Convergence, NonLinearSolver},
     ode_solver_error, scale, AugmentedOdeEquations, AugmentedOdeEquationsImplicit, ConstantOp,
-    InitOp, LinearSolver, NewtonNonlinearSolver, NonLinearOp, OdeEquations, OdeEquationsImplicit,
-    OdeEquationsSens, OdeSolverProblem, Op, SensEquations, Vector,
+    InitOp, LineSearchNewtonNonlinearSolver, LinearSolver, NewtonNonlinearSolver, NonLinearOp,
+    OdeEquations, OdeEquationsImplicit, OdeEquationsSens, OdeSolverProblem, Op, SensEquations,
+    Vector,
 };
 
 /// A state holding those variables that are common to all ODE solver states,
@@ -137,7 +138,8 @@ pub trait OdeSolverState<V: Vector>: Clone + Sized {
     }
 
     /// Create a new solver state from an ODE problem.
-    /// This function will make the state consistent with any algebraic constraints using a default nonlinear solver.
+    /// This function will make the state consistent with any algebraic constraints using a default nonlinear solver, which is the [NewtonNonlinearSolver]
+    /// or the [LineSearchNewtonNonlinearSolver] if [crate::SolverOptions::line_search_consistent_init] is set.
     /// It will also set the initial step size based on the given solver.
     /// If you want to create a state without this default initialisation, use [Self::new_without_initialise] instead.
     /// You can then use [Self::set_consistent] and [Self::set_step_size] to set the state up if you need to.
@@ -150,8 +152,13 @@ pub trait OdeSolverState<V: Vector>: Clone + Sized {
         LS: LinearSolver<Eqn::M>,
     {
         let mut ret = Self::new_without_initialise(ode_problem)?;
-        let mut root_solver = NewtonNonlinearSolver::new(LS::default());
-        ret.set_consistent(ode_problem, &mut root_solver)?;
+        if ode_problem.options.line_search_consistent_init {
+            let mut root_solver = LineSearchNewtonNonlinearSolver::new(LS::default());
+            ret.set_consistent(ode_problem, &mut root_solver)?;
+        } else {
+            let mut root_solver = NewtonNonlinearSolver::new(LS::default());
+            ret.set_consistent(ode_problem, &mut root_solver)?;
+        }
         ret.set_step_size(ode_problem, solver_order);
         Ok(ret)
     }
@@ -168,6 +175,44 @@ pub trait OdeSolverState<V: Vector>: Clone + Sized {
         Self::new_with_augmented::<LS, _, _>(ode_problem, &mut augmented_eqn, solver_order)
     }
 
+    /// Create a new solver state from an ODE problem for use with an explicit solver.
+    /// Unlike [Self::new], this does not require a Jacobian for the right-hand side or a linear solver,
+    /// but the problem must not have a mass matrix. The initial step size is set based on the given solver order.
+    fn new_explicit<Eqn>(
+        ode_problem: &OdeSolverProblem<Eqn>,
+        solver_order: usize,
+    ) -> Result<Self, DiffsolError>
+    where
+        Eqn: OdeEquations<T = V::T, V = V>,
+    {
+        let mut ret = Self::new_without_initialise(ode_problem)?;
+        ret.set_consistent_explicit(ode_problem)?;
+        ret.set_step_size(ode_problem, solver_order);
+        Ok(ret)
+    }
+
+    fn new_explicit_with_sensitivities<Eqn>(
+        ode_problem: &OdeSolverProblem<Eqn>,
+        solver_order: usize,
+    ) -> Result<Self, DiffsolError>
+    where
+        Eqn: OdeEquationsSens<T = V::T, V = V>,
+    {
+        let mut augmented_eqn = SensEquations::new(ode_problem);
+        let mut ret = Self::new_without_initialise_augmented(ode_problem, &mut augmented_eqn)?;
+        ret.set_consistent_explicit(ode_problem)?;
+        let state = ret.as_mut();
+        augmented_eqn.update_rhs_out_state(state.y, state.dy, *state.t);
+        for i in 0..augmented_eqn.max_index() {
+            augmented_eqn.set_index(i);
+            augmented_eqn
+                .rhs()
+                .call_inplace(&state.s[i], *state.t, &mut state.ds[i]);
+        }
+        ret.set_step_size(ode_problem, solver_order);
+        Ok(ret)
+    }
+
     fn new_with_augmented<LS, Eqn, AugmentedEqn>(
         ode_problem: &OdeSolverProblem<Eqn>,
         augmented_eqn: &mut AugmentedEqn,
@@ -179,8 +224,13 @@ pub trait OdeSolverState<V: Vector>: Clone + Sized {
         LS: LinearSolver<Eqn::M>,
     {
         let mut ret = Self::new_without_initialise_augmented(ode_problem, augmented_eqn)?;
-        let mut root_solver = NewtonNonlinearSolver::new(LS::default());
-        ret.set_consistent(ode_problem, &mut root_solver)?;
+        if ode_problem.options.line_search_consistent_init {
+            let mut root_solver = LineSearchNewtonNonlinearSolver::new(LS::default());
+            ret.set_consistent(ode_problem, &mut root_solver)?;
+        } else {
+            let mut root_solver = NewtonNonlinearSolver::new(LS::default());
+            ret.set_consistent(ode_problem, &mut root_solver)?;
+        }
         let mut root_solver_sens = NewtonNonlinearSolver::new(LS::default());
         ret.set_consistent_augmented(ode_problem, augmented_eqn, &mut root_solver_sens)?;
         ret.set_step_size(ode_problem, solver_order);
@@ -348,11 +398,40 @@ pub trait OdeSolverState<V: Vector>: Clone + Sized {
         let yerr = y_tmp.clone();
         root_solver.reset_jacobian(&f, &y_tmp, *state.t);
         let mut convergence = Convergence::new(rtol, atol);
-        root_solver.solve_in_place(&f, &mut y_tmp, *state.t, &yerr, &mut convergence)?;
+        root_solver.solve_in_place_with_jacobian(
+            &f,
+            &mut y_tmp,
+            *state.t,
+            &yerr,
+            &mut convergence,
+        )?;
         f.scatter_soln(&y_tmp, state.y, state.dy);
         Ok(())
     }
 
+    /// Calculate the time derivative of the state for an explicit solver, which only requires a call to the right-hand side.
+    /// Returns an error if the problem has a mass matrix.
+    fn set_consistent_explicit<Eqn>(
+        &mut self,
+        ode_problem: &OdeSolverProblem<Eqn>,
+    ) -> Result<(), DiffsolError>
+    where
+        Eqn: OdeEquations<T = V::T, V = V>,
+    {
+        if ode_problem.eqn.mass().is_some() {
+            return Err(ode_solver_error!(
+                Other,
+                "Explicit solvers do not support a mass matrix"
+            ));
+        }
+        let state = self.as_mut();
+        ode_problem
+            .eqn
+            .rhs()
+            .call_inplace(state.y, *state.t, state.dy);
+        Ok(())
+    }
+
     /// Calculate the initial sensitivity vectors and their time derivatives, based on the equations of the problem.
     /// Note that this function assumes that the state is already consistent with the algebraic constraints
     /// (either via [Self::set_consistent] or by setting the state up manually).
@@ -391,7 +470,13 @@ pub trait OdeSolverState<V: Vector>: Clone + Sized {
             y.copy_from_indices(state.y, &f.algebraic_indices);
             let yerr = y.clone();
             root_solver.reset_jacobian(&f, &y, *state.t);
-            root_solver.solve_in_place(&f, &mut y, *state.t, &yerr, &mut convergence)?;
+            root_solver.solve_in_place_with_jacobian(
+                &f,
+                &mut y,
+                *state.t,
+                &yerr,
+                &mut convergence,
+            )?;
             f.scatter_soln(&y, &mut state.s[i], &mut state.ds[i]);
         }
         Ok(())
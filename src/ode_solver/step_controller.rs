@@ -0,0 +1,280 @@
+use crate::Scalar;
+
+/// Chooses the step size of an adaptive solver from the error estimates of the steps taken so far.
+///
+/// After each attempted step the solver calls [Self::accept] or [Self::reject] with the weighted root-mean-square norm of the error estimate
+/// (the step is accepted if this is less than or equal to one), and multiplies the step size by the returned factor.
+/// `k` is the order of the error estimate plus one (i.e. the error is `O(h^k)`), and `safety` is a safety factor less than one chosen by the solver
+/// (which may depend on the number of nonlinear solver iterations). The solver limits the returned factor to a range suitable for the method,
+/// and calls [Self::reset] whenever the history of the step sizes is no longer valid (e.g. after [crate::OdeSolverMethod::state_mut] or a change of order).
+///
+/// A controller can be set for the [crate::Bdf] and [crate::Sdirk] solvers using [crate::Bdf::set_step_controller] and [crate::Sdirk::set_step_controller].
+/// The built-in controllers are [IController] (the default), [PiController], [PidController] and [PredictiveController].
+pub trait StepController<T: Scalar>: StepControllerClone<T> + Send + Sync {
+    /// Return the factor to multiply the step size by after an accepted step of size `h` with error norm `error_norm`.
+    fn accept(&mut self, h: T, error_norm: T, k: usize, safety: T) -> T;
+
+    /// Return the factor to multiply the step size by after a rejected step of size `h` with error norm `error_norm`.
+    fn reject(&mut self, h: T, error_norm: T, k: usize, safety: T) -> T;
+
+    /// Forget the history of the previous steps.
+    fn reset(&mut self);
+}
+
+/// Helper trait to allow a boxed [StepController] to be cloned, implemented for all controllers that implement [Clone].
+pub trait StepControllerClone<T: Scalar> {
+    fn clone_box(&self) -> Box<dyn StepController<T>>;
+}
+
+impl<T: Scalar, C: StepController<T> + Clone + 'static> StepControllerClone<T> for C {
+    fn clone_box(&self) -> Box<dyn StepController<T>> {
+        Box::new(self.clone())
+    }
+}
+
+impl<T: Scalar> Clone for Box<dyn StepController<T>> {
+    fn clone(&self) -> Self {
+        self.clone_box()
+    }
+}
+
+// errors are bounded below when stored in the history, so that a very small error does not make the next factor vanish
+const MIN_HISTORY_ERROR: f64 = 1e-4;
+
+fn history_error<T: Scalar>(error_norm: T) -> T {
+    let min_error = T::from(MIN_HISTORY_ERROR);
+    if error_norm > min_error {
+        error_norm
+    } else {
+        min_error
+    }
+}
+
+fn i_factor<T: Scalar>(error_norm: T, k: usize, safety: T) -> T {
+    safety * error_norm.pow(-T::one() / T::from(k as f64))
+}
+
+/// The standard integral (I) controller, the factor is
+///
+/// $$
+/// \text{safety} \cdot e_n^{-1/k}
+/// $$
+#[derive(Clone, Debug, Default)]
+pub struct IController;
+
+impl<T: Scalar> StepController<T> for IController {
+    fn accept(&mut self, _h: T, error_norm: T, k: usize, safety: T) -> T {
+        i_factor(error_norm, k, safety)
+    }
+    fn reject(&mut self, _h: T, error_norm: T, k: usize, safety: T) -> T {
+        i_factor(error_norm, k, safety)
+    }
+    fn reset(&mut self) {}
+}
+
+/// A proportional-integral (PI) controller \[1\], the factor after an accepted step is
+///
+/// $$
+/// \text{safety} \cdot e_n^{-\beta_1/k} e_{n-1}^{-\beta_2/k}
+/// $$
+///
+/// where `e_{n-1}` is the error of the previous accepted step. Rejected steps use the [IController] factor.
+/// The default parameters are those of the H211PI controller of \[1\], which gives smooth step size sequences.
+/// The parameters `β_1 = 0.7`, `β_2 = -0.4` recommended by Hairer and Wanner react faster to changes in the error.
+///
+/// \[1\] Söderlind, G. (2003). Digital filters in adaptive time-stepping. ACM Transactions on Mathematical Software (TOMS), 29(1), 1-26.
+#[derive(Clone, Debug)]
+pub struct PiController<T: Scalar> {
+    beta: [T; 2],
+    error_prev: Option<T>,
+}
+
+impl<T: Scalar> PiController<T> {
+    pub fn new(beta1: T, beta2: T) -> Self {
+        Self {
+            beta: [beta1, beta2],
+            error_prev: None,
+        }
+    }
+}
+
+impl<T: Scalar> Default for PiController<T> {
+    fn default() -> Self {
+        Self::new(T::from(1.0 / 6.0), T::from(1.0 / 6.0))
+    }
+}
+
+impl<T: Scalar> StepController<T> for PiController<T> {
+    fn accept(&mut self, _h: T, error_norm: T, k: usize, safety: T) -> T {
+        let k = T::from(k as f64);
+        let mut factor = safety * error_norm.pow(-self.beta[0] / k);
+        if let Some(error_prev) = self.error_prev {
+            factor *= error_prev.pow(-self.beta[1] / k);
+        }
+        self.error_prev = Some(history_error(error_norm));
+        factor
+    }
+    fn reject(&mut self, _h: T, error_norm: T, k: usize, safety: T) -> T {
+        i_factor(error_norm, k, safety)
+    }
+    fn reset(&mut self) {
+        self.error_prev = None;
+    }
+}
+
+/// A proportional-integral-derivative (PID) controller \[1\], the factor after an accepted step is
+///
+/// $$
+/// \text{safety} \cdot e_n^{-\beta_1/k} e_{n-1}^{-\beta_2/k} e_{n-2}^{-\beta_3/k}
+/// $$
+///
+/// where `e_{n-1}` and `e_{n-2}` are the errors of the previous two accepted steps. Rejected steps use the [IController] factor.
+/// The default parameters are those of the H312PID controller of \[1\].
+///
+/// \[1\] Söderlind, G. (2003). Digital filters in adaptive time-stepping. ACM Transactions on Mathematical Software (TOMS), 29(1), 1-26.
+#[derive(Clone, Debug)]
+pub struct PidController<T: Scalar> {
+    beta: [T; 3],
+    error_prev: [Option<T>; 2],
+}
+
+impl<T: Scalar> PidController<T> {
+    pub fn new(beta1: T, beta2: T, beta3: T) -> Self {
+        Self {
+            beta: [beta1, beta2, beta3],
+            error_prev: [None, None],
+        }
+    }
+}
+
+impl<T: Scalar> Default for PidController<T> {
+    fn default() -> Self {
+        Self::new(T::from(1.0 / 18.0), T::from(1.0 / 9.0), T::from(1.0 / 18.0))
+    }
+}
+
+impl<T: Scalar> StepController<T> for PidController<T> {
+    fn accept(&mut self, _h: T, error_norm: T, k: usize, safety: T) -> T {
+        let k = T::from(k as f64);
+        let mut factor = safety * error_norm.pow(-self.beta[0] / k);
+        for (error_prev, beta) in self.error_prev.iter().zip(self.beta[1..].iter()) {
+            if let Some(error_prev) = error_prev {
+                factor *= error_prev.pow(-*beta / k);
+            }
+        }
+        self.error_prev = [Some(history_error(error_norm)), self.error_prev[0]];
+        factor
+    }
+    fn reject(&mut self, _h: T, error_norm: T, k: usize, safety: T) -> T {
+        i_factor(error_norm, k, safety)
+    }
+    fn reset(&mut self) {
+        self.error_prev = [None, None];
+    }
+}
+
+/// The predictive controller of Gustafsson \[1\], as used in the RADAU5 code of Hairer and Wanner. After an accepted step of size `h_n`
+/// following an accepted step of size `h_{n-1}`, the factor is the minimum of the [IController] factor and
+///
+/// $$
+/// \text{safety} \cdot \frac{h_n}{h_{n-1}} \left( \frac{e_{n-1}}{e_n^2} \right)^{1/k}
+/// $$
+///
+/// which reduces the step size before the error test fails when the error is increasing. The step size is not increased after a rejected step.
+///
+/// \[1\] Gustafsson, K. (1994). Control-theoretic techniques for stepsize selection in implicit Runge-Kutta methods. ACM Transactions on Mathematical Software (TOMS), 20(4), 496-517.
+#[derive(Clone, Debug)]
+pub struct PredictiveController<T: Scalar> {
+    prev: Option<(T, T)>,
+    last_rejected: bool,
+}
+
+impl<T: Scalar> Default for PredictiveController<T> {
+    fn default() -> Self {
+        Self {
+            prev: None,
+            last_rejected: false,
+        }
+    }
+}
+
+impl<T: Scalar> StepController<T> for PredictiveController<T> {
+    fn accept(&mut self, h: T, error_norm: T, k: usize, safety: T) -> T {
+        let mut factor = i_factor(error_norm, k, safety);
+        if let Some((h_prev, error_prev)) = self.prev {
+            let predicted = safety
+                * (h / h_prev)
+                * (error_prev / (error_norm * error_norm)).pow(T::one() / T::from(k as f64));
+            if predicted < factor {
+                factor = predicted;
+            }
+        }
+        if self.last_rejected && factor > T::one() {
+            factor = T::one();
+        }
+        self.prev = Some((h, history_error(error_norm)));
+        self.last_rejected = false;
+        factor
+    }
+    fn reject(&mut self, _h: T, error_norm: T, k: usize, safety: T) -> T {
+        self.last_rejected = true;
+        i_factor(error_norm, k, safety)
+    }
+    fn reset(&mut self) {
+        self.prev = None;
+        self.last_rejected = false;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{IController, PiController, PidController, PredictiveController, StepController};
+
+    #[test]
+    fn test_step_controllers_constant_error() {
+        // with a constant error of one the factor is the safety factor for all controllers
+        let mut controllers: Vec<Box<dyn StepController<f64>>> = vec![
+            Box::new(IController),
+            Box::new(PiController::default()),
+            Box::new(PidController::default()),
+            Box::new(PredictiveController::default()),
+        ];
+        for controller in controllers.iter_mut() {
+            for _ in 0..3 {
+                let factor = controller.accept(0.1, 1.0, 3, 0.9);
+                assert!((factor - 0.9).abs() < 1e-12);
+            }
+        }
+    }
+
+    #[test]
+    fn test_step_controllers_history() {
+        // I controller: 0.9 * 0.5^(-1/2)
+        let mut i = IController;
+        let factor = StepController::<f64>::accept(&mut i, 0.1, 0.5, 2, 0.9);
+        assert!((factor - 0.9 * 2.0f64.sqrt()).abs() < 1e-12);
+
+        // PI controller uses the previous error
+        let mut pi = PiController::new(0.7, -0.4);
+        pi.accept(0.1, 0.5, 2, 0.9);
+        let factor = pi.accept(0.1, 0.25, 2, 0.9);
+        let expected = 0.9 * 0.25f64.powf(-0.35) * 0.5f64.powf(0.2);
+        assert!((factor - expected).abs() < 1e-12);
+        pi.reset();
+        let factor = pi.accept(0.1, 0.25, 2, 0.9);
+        assert!((factor - 0.9 * 0.25f64.powf(-0.35)).abs() < 1e-12);
+
+        // predictive controller reduces the step when the error is increasing
+        let mut predictive = PredictiveController::default();
+        predictive.accept(0.1, 0.1, 2, 0.9);
+        let factor = predictive.accept(0.1, 0.5, 2, 0.9);
+        let i_factor = 0.9 * 0.5f64.powf(-0.5);
+        assert!(factor < i_factor);
+        assert!((factor - 0.9 * (0.1f64 / 0.25).sqrt()).abs() < 1e-12);
+
+        // and does not increase the step after a rejection
+        predictive.reject(0.1, 2.0, 2, 0.9);
+        let factor = predictive.accept(0.05, 0.01, 2, 0.9);
+        assert_eq!(factor, 1.0);
+    }
+}
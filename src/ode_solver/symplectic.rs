@@ -0,0 +1,502 @@
+use crate::error::DiffsolError;
+use crate::error::OdeSolverError;
+use crate::ode_solver_error;
+use crate::vector::VectorRef;
+use crate::{
+    scale, NonLinearOp, OdeEquationsSecondOrder, OdeSolverMethod, OdeSolverProblem, OdeSolverState,
+    OdeSolverStopReason, Op, RootFinder, SdirkState, StateRef, StateRefMut, SymplecticTableau,
+    Vector,
+};
+use num_traits::One;
+use num_traits::Zero;
+
+use super::bdf::BdfStatistics;
+use super::method::{check_interpolation_time, check_tstop, init_root_finder, TstopCheck};
+
+/// A fixed-step symplectic solver for second-order problems `y'' = f(t, y, y')` (see [OdeEquationsSecondOrder]).
+///
+/// Each step is a composition of velocity Verlet (kick-drift-kick) sub-steps, defined by the [SymplecticTableau] used to create the solver
+/// (e.g. [SymplecticTableau::velocity_verlet] or [SymplecticTableau::yoshida4]). The velocities are updated using the force at the start and end of each sub-step,
+/// and the positions are updated using the velocity at the middle of each sub-step, so each sub-step needs one evaluation of the force.
+///
+/// The method is symplectic, and so conserves a modified energy over long times, if the force is independent of the velocities (i.e. a Hamiltonian system with a separable Hamiltonian).
+/// Otherwise the force is evaluated using the velocity at the middle of the sub-step, and the method is only first order. For damped or stiff problems use [crate::GeneralizedAlpha] instead.
+///
+/// The step size is fixed, and is given by the step size of the initial state (see [crate::OdeBuilder::h0]). There is no error control, the step size is only reduced
+/// to stop at a stop time given by [OdeSolverMethod::set_stop_time]. The output function is integrated using Simpson's rule on the dense output of each step.
+///
+/// Restrictions:
+/// - The problem must not have a mass matrix.
+/// - Forward sensitivities are not supported.
+pub struct Symplectic<'a, Eqn>
+where
+    Eqn: OdeEquationsSecondOrder,
+{
+    tableau: SymplecticTableau<Eqn::V>,
+    problem: &'a OdeSolverProblem<Eqn>,
+    state: SdirkState<Eqn::V>,
+    h: Eqn::T,
+    acc: Eqn::V,
+    old_t: Eqn::T,
+    old_y: Eqn::V,
+    old_f: Eqn::V,
+    old_g: Eqn::V,
+    old_dg: Eqn::V,
+    statistics: BdfStatistics,
+    root_finder: Option<RootFinder<Eqn::V>>,
+    tstop: Option<Eqn::T>,
+    is_state_mutated: bool,
+}
+
+impl<Eqn> Clone for Symplectic<'_, Eqn>
+where
+    Eqn: OdeEquationsSecondOrder,
+{
+    fn clone(&self) -> Self {
+        Self {
+            tableau: self.tableau.clone(),
+            problem: self.problem,
+            state: self.state.clone(),
+            h: self.h,
+            acc: self.acc.clone(),
+            old_t: self.old_t,
+            old_y: self.old_y.clone(),
+            old_f: self.old_f.clone(),
+            old_g: self.old_g.clone(),
+            old_dg: self.old_dg.clone(),
+            statistics: self.statistics.clone(),
+            root_finder: self.root_finder.clone(),
+            tstop: self.tstop,
+            is_state_mutated: self.is_state_mutated,
+        }
+    }
+}
+
+impl<'a, Eqn> Symplectic<'a, Eqn>
+where
+    Eqn: OdeEquationsSecondOrder,
+    for<'b> &'b Eqn::V: VectorRef<Eqn::V>,
+{
+    pub fn new(
+        problem: &'a OdeSolverProblem<Eqn>,
+        mut state: SdirkState<Eqn::V>,
+        tableau: SymplecticTableau<Eqn::V>,
+    ) -> Result<Self, DiffsolError> {
+        if problem.eqn.mass().is_some() {
+            return Err(ode_solver_error!(
+                Other,
+                "Symplectic solvers do not support a mass matrix"
+            ));
+        }
+
+        state.check_consistent_with_problem(problem)?;
+        state.set_problem(problem)?;
+
+        let n = problem.eqn.npositions();
+        let h = state.h;
+        let acc = <Eqn::V as Vector>::from_vec(state.dy.as_slice()[n..].to_vec());
+        let old_t = state.t;
+        let old_y = state.y.clone();
+        let old_f = state.dy.clone();
+        let old_g = state.g.clone();
+        let old_dg = state.dg.clone();
+
+        let root_finder = if let Some(root_fn) = problem.eqn.root() {
+            let root_finder = RootFinder::new(root_fn.nout());
+            root_finder.init(&root_fn, &state.y, state.t);
+            Some(root_finder)
+        } else {
+            None
+        };
+
+        Ok(Self {
+            tableau,
+            problem,
+            state,
+            h,
+            acc,
+            old_t,
+            old_y,
+            old_f,
+            old_g,
+            old_dg,
+            statistics: BdfStatistics::default(),
+            root_finder,
+            tstop: None,
+            is_state_mutated: false,
+        })
+    }
+
+    /// Returns the solver statistics. No linear or nonlinear solver is used, so only the number of steps is recorded.
+    pub fn get_statistics(&self) -> &BdfStatistics {
+        &self.statistics
+    }
+
+    fn handle_tstop(
+        &mut self,
+        tstop: Eqn::T,
+    ) -> Result<Option<OdeSolverStopReason<Eqn::T>>, DiffsolError> {
+        match check_tstop(self.state.t, self.state.h, tstop)? {
+            TstopCheck::Reached => {
+                self.tstop = None;
+                return Ok(Some(OdeSolverStopReason::TstopReached));
+            }
+            TstopCheck::ScaleStep(factor) => {
+                self.state.h *= factor;
+            }
+            TstopCheck::NotReached => {}
+        }
+        Ok(None)
+    }
+
+    /// recalculate the acceleration and the time derivative of the state from the current state
+    fn update_derivatives(&mut self) {
+        let n = self.problem.eqn.npositions();
+        let state = &mut self.state;
+        self.problem
+            .eqn
+            .force()
+            .call_inplace(&state.y, state.t, &mut self.acc);
+        let dy = state.dy.as_mut_slice();
+        dy[..n].copy_from_slice(&state.y.as_slice()[n..]);
+        dy[n..].copy_from_slice(self.acc.as_slice());
+    }
+
+    fn interpolate_hermite(
+        theta: Eqn::T,
+        dt: Eqn::T,
+        u0: &Eqn::V,
+        u1: &Eqn::V,
+        f0: &Eqn::V,
+        f1: &Eqn::V,
+    ) -> Eqn::V {
+        u0 * scale(Eqn::T::from(1.0) - theta)
+            + u1 * scale(theta)
+            + ((u1 - u0) * scale(Eqn::T::from(1.0) - Eqn::T::from(2.0) * theta)
+                + f0 * scale(dt * (theta - Eqn::T::from(1.0)))
+                + f1 * scale(dt * theta))
+                * scale(theta * (theta - Eqn::T::from(1.0)))
+    }
+
+    fn interpolation_theta(&self, t: Eqn::T) -> Result<Eqn::T, DiffsolError> {
+        let state = &self.state;
+
+        // check that t is within the last step
+        check_interpolation_time(t, self.old_t, state.t)?;
+        let dt = state.t - self.old_t;
+        if dt == Eqn::T::zero() {
+            Ok(Eqn::T::one())
+        } else {
+            Ok((t - self.old_t) / dt)
+        }
+    }
+}
+
+impl<'a, Eqn> OdeSolverMethod<'a, Eqn> for Symplectic<'a, Eqn>
+where
+    Eqn: OdeEquationsSecondOrder,
+    for<'b> &'b Eqn::V: VectorRef<Eqn::V>,
+{
+    type State = SdirkState<Eqn::V>;
+
+    fn problem(&self) -> &'a OdeSolverProblem<Eqn> {
+        self.problem
+    }
+
+    fn order(&self) -> usize {
+        self.tableau.order()
+    }
+
+    fn set_state(&mut self, state: Self::State) {
+        self.state = state;
+        self.h = self.state.h;
+
+        // make sure the acceleration is consistent with the new state
+        self.update_derivatives();
+    }
+
+    fn into_state(self) -> SdirkState<Eqn::V> {
+        self.state
+    }
+
+    fn checkpoint(&mut self) -> Self::State {
+        self.state.clone()
+    }
+
+    fn step(&mut self) -> Result<OdeSolverStopReason<Eqn::T>, DiffsolError> {
+        let n = self.problem.eqn.npositions();
+        let problem = self.problem;
+        let half = Eqn::T::from(0.5);
+
+        if self.is_state_mutated {
+            // reinitialise root finder if needed
+            init_root_finder(
+                problem,
+                self.root_finder.as_ref(),
+                &self.state.y,
+                self.state.t,
+            );
+            // the step size might have been changed by the user
+            self.h = self.state.h;
+            // reinitialise tstop if needed
+            if let Some(t_stop) = self.tstop {
+                self.set_stop_time(t_stop)?;
+            }
+            self.update_derivatives();
+            if problem.integrate_out {
+                let state = &mut self.state;
+                problem
+                    .eqn
+                    .out()
+                    .unwrap()
+                    .call_inplace(&state.y, state.t, &mut state.dg);
+            }
+
+            self.is_state_mutated = false;
+        }
+
+        // save the start of the step for interpolation
+        self.old_t = self.state.t;
+        self.old_y.copy_from(&self.state.y);
+        self.old_f.copy_from(&self.state.dy);
+
+        // velocity Verlet sub-steps, the acceleration at the start of each sub-step is in self.acc
+        let force = problem.eqn.force();
+        let h = self.state.h;
+        let mut t = self.state.t;
+        for i in 0..self.tableau.s() {
+            let hw = h * self.tableau.weights()[i];
+
+            // kick and drift
+            {
+                let y = self.state.y.as_mut_slice();
+                let acc = self.acc.as_slice();
+                for j in 0..n {
+                    y[n + j] += half * hw * acc[j];
+                    y[j] += hw * y[n + j];
+                }
+            }
+            t += hw;
+
+            // kick with the force at the end of the sub-step
+            force.call_inplace(&self.state.y, t, &mut self.acc);
+            {
+                let y = self.state.y.as_mut_slice();
+                let acc = self.acc.as_slice();
+                for j in 0..n {
+                    y[n + j] += half * hw * acc[j];
+                }
+            }
+        }
+
+        // take the step
+        {
+            let state = &mut self.state;
+            state.t = self.old_t + h;
+            let dy = state.dy.as_mut_slice();
+            dy[..n].copy_from_slice(&state.y.as_slice()[n..]);
+            dy[n..].copy_from_slice(self.acc.as_slice());
+        }
+
+        // integrate output function using simpson's rule on the dense output
+        if problem.integrate_out {
+            let out = problem.eqn.out().unwrap();
+            let state = &mut self.state;
+            let dt = state.t - self.old_t;
+            let y_mid =
+                Self::interpolate_hermite(half, dt, &self.old_y, &state.y, &self.old_f, &state.dy);
+            let dg_mid = out.call(&y_mid, self.old_t + half * dt);
+            out.call_inplace(&state.y, state.t, &mut self.old_dg);
+            std::mem::swap(&mut self.old_dg, &mut state.dg);
+            self.old_g.copy_from(&state.g);
+            let w = dt / Eqn::T::from(6.0);
+            state.g.axpy(w, &self.old_dg, Eqn::T::one());
+            state.g.axpy(Eqn::T::from(4.0) * w, &dg_mid, Eqn::T::one());
+            state.g.axpy(w, &state.dg, Eqn::T::one());
+        }
+
+        // restore the fixed step size, in case it was reduced to stop at tstop
+        self.state.h = self.h;
+
+        // update statistics
+        self.statistics.number_of_steps += 1;
+
+        // check for root within accepted step
+        if let Some(root_fn) = problem.eqn.root() {
+            let ret = self.root_finder.as_ref().unwrap().check_root(
+                &|t| self.interpolate(t),
+                &root_fn,
+                &self.state.y,
+                self.state.t,
+            );
+            if let Some(root) = ret {
+                return Ok(OdeSolverStopReason::RootFound(root));
+            }
+        }
+
+        // check if we are at tstop
+        if let Some(tstop) = self.tstop {
+            if let Some(reason) = self.handle_tstop(tstop).unwrap() {
+                return Ok(reason);
+            }
+        }
+
+        // just a normal step, no roots or tstop reached
+        Ok(OdeSolverStopReason::InternalTimestep)
+    }
+
+    fn set_stop_time(&mut self, tstop: <Eqn as Op>::T) -> Result<(), DiffsolError> {
+        self.tstop = Some(tstop);
+        if let Some(OdeSolverStopReason::TstopReached) = self.handle_tstop(tstop)? {
+            let error = OdeSolverError::StopTimeBeforeCurrentTime {
+                stop_time: tstop.into(),
+                state_time: self.state.t.into(),
+            };
+            self.tstop = None;
+            return Err(DiffsolError::from(error));
+        }
+        Ok(())
+    }
+
+    fn interpolate_sens(&self, _t: <Eqn as Op>::T) -> Result<Vec<<Eqn as Op>::V>, DiffsolError> {
+        Ok(vec![])
+    }
+
+    fn interpolate(&self, t: <Eqn>::T) -> Result<<Eqn>::V, DiffsolError> {
+        if self.is_state_mutated {
+            if t == self.state.t {
+                return Ok(self.state.y.clone());
+            } else {
+                return Err(ode_solver_error!(InterpolationTimeOutsideCurrentStep));
+            }
+        }
+        let theta = self.interpolation_theta(t)?;
+        Ok(Self::interpolate_hermite(
+            theta,
+            self.state.t - self.old_t,
+            &self.old_y,
+            &self.state.y,
+            &self.old_f,
+            &self.state.dy,
+        ))
+    }
+
+    fn interpolate_out(&self, t: <Eqn>::T) -> Result<<Eqn>::V, DiffsolError> {
+        if self.is_state_mutated {
+            if t == self.state.t {
+                return Ok(self.state.g.clone());
+            } else {
+                return Err(ode_solver_error!(InterpolationTimeOutsideCurrentStep));
+            }
+        }
+        let theta = self.interpolation_theta(t)?;
+        Ok(Self::interpolate_hermite(
+            theta,
+            self.state.t - self.old_t,
+            &self.old_g,
+            &self.state.g,
+            &self.old_dg,
+            &self.state.dg,
+        ))
+    }
+
+    fn state(&self) -> StateRef<Eqn::V> {
+        self.state.as_ref()
+    }
+
+    fn state_mut(&mut self) -> StateRefMut<Eqn::V> {
+        self.is_state_mutated = true;
+        self.state.as_mut()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        ode_solver::{
+            test_models::spring_mass::{
+                spring_mass_problem, spring_mass_problem_with_mass, spring_mass_problem_with_root,
+            },
+            tests::{test_checkpointing, test_ode_solver, test_state_mut_on_problem},
+        },
+        OdeSolverMethod, SecondOrderOdeSolverMethod,
+    };
+
+    use num_traits::abs;
+
+    type M = nalgebra::DMatrix<f64>;
+
+    #[test]
+    fn test_velocity_verlet_nalgebra_spring_mass() {
+        let (problem, soln) = spring_mass_problem::<M>(false, 0.0);
+        let mut s = problem.velocity_verlet().unwrap();
+        test_ode_solver(&mut s, soln, Some(1e-3), false, false);
+
+        // fixed step size of 0.01
+        assert!(s.get_statistics().number_of_steps >= 900);
+        assert_eq!(s.positions().len(), 2);
+        assert_eq!(s.velocities().len(), 2);
+    }
+
+    #[test]
+    fn test_yoshida4_nalgebra_spring_mass() {
+        let (problem, soln) = spring_mass_problem::<M>(false, 0.0);
+        let mut s = problem.yoshida4().unwrap();
+        test_ode_solver(&mut s, soln, Some(1e-5), false, false);
+    }
+
+    #[test]
+    fn test_velocity_verlet_conserves_energy() {
+        let (problem, _soln) = spring_mass_problem::<M>(false, 0.0);
+        let mut s = problem.velocity_verlet().unwrap();
+        let energy = |y: &nalgebra::DVector<f64>, v: &nalgebra::DVector<f64>| {
+            0.5 * (y.norm_squared() + v.norm_squared())
+        };
+        let e0 = energy(&s.positions(), &s.velocities());
+        while s.state().t < 100.0 {
+            s.step().unwrap();
+        }
+        let e1 = energy(&s.positions(), &s.velocities());
+        assert!(abs(e1 - e0) < 1e-4 * e0, "e0 = {}, e1 = {}", e0, e1);
+    }
+
+    #[test]
+    fn test_symplectic_rejects_mass_matrix() {
+        let (problem, _soln) = spring_mass_problem_with_mass::<M>(false, 0.0);
+        assert!(problem.velocity_verlet().is_err());
+        let state = problem.generalized_alpha_state().unwrap();
+        assert!(problem
+            .symplectic_solver(state, crate::SymplecticTableau::velocity_verlet())
+            .is_err());
+    }
+
+    #[test]
+    fn test_tstop_velocity_verlet() {
+        let (problem, soln) = spring_mass_problem::<M>(false, 0.0);
+        let mut s = problem.velocity_verlet().unwrap();
+        test_ode_solver(&mut s, soln, Some(1e-3), true, false);
+    }
+
+    #[test]
+    fn test_root_finder_velocity_verlet() {
+        let (problem, soln) = spring_mass_problem_with_root::<M>(false);
+        let mut s = problem.velocity_verlet().unwrap();
+        let y = test_ode_solver(&mut s, soln, Some(1e-3), false, false);
+        assert!(abs(y[0] - 0.6) < 1e-6, "y[0] = {}", y[0]);
+    }
+
+    #[test]
+    fn yoshida4_test_checkpointing() {
+        let (problem, soln) = spring_mass_problem::<M>(false, 0.0);
+        let s1 = problem.yoshida4().unwrap();
+        let s2 = problem.yoshida4().unwrap();
+        test_checkpointing(soln, s1, s2);
+    }
+
+    #[test]
+    fn yoshida4_test_state_mut_spring_mass() {
+        let (problem, soln) = spring_mass_problem::<M>(false, 0.0);
+        let s = problem.yoshida4().unwrap();
+        test_state_mut_on_problem(s, soln);
+    }
+}
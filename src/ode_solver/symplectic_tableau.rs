@@ -0,0 +1,60 @@
+use crate::Vector;
+use num_traits::{abs, One, Zero};
+
+/// The weights of a symplectic composition method.
+///
+/// A step of size `h` is made of a sequence of velocity Verlet (kick-drift-kick) sub-steps, the `i`-th sub-step having size `w_i h`.
+/// The weights must sum to one. Composing velocity Verlet sub-steps in this way preserves the symplectic structure and time-reversibility of the method.
+#[derive(Clone)]
+pub struct SymplecticTableau<V: Vector> {
+    weights: V,
+    order: usize,
+}
+
+impl<V: Vector> SymplecticTableau<V> {
+    /// the velocity Verlet method, a symplectic method of order 2
+    pub fn velocity_verlet() -> Self {
+        let weights = V::from_vec(vec![V::T::one()]);
+        Self::new(weights, 2)
+    }
+
+    /// the fourth-order symplectic method of H. Yoshida, Construction of higher order symplectic integrators, Phys. Lett. A 150 (1990) 262–268,
+    /// which is the composition of three velocity Verlet sub-steps
+    pub fn yoshida4() -> Self {
+        let cbrt2 = 2.0f64.powf(1.0 / 3.0);
+        let w1 = 1.0 / (2.0 - cbrt2);
+        let w0 = -cbrt2 / (2.0 - cbrt2);
+        let weights = V::from_vec(vec![V::T::from(w1), V::T::from(w0), V::T::from(w1)]);
+        Self::new(weights, 4)
+    }
+
+    pub fn new(weights: V, order: usize) -> Self {
+        assert!(
+            !weights.is_empty(),
+            "Invalid tableau, expected at least one sub-step"
+        );
+        let mut sum = V::T::zero();
+        for i in 0..weights.len() {
+            sum += weights[i];
+        }
+        assert!(
+            abs(sum - V::T::one()) < V::T::from(1e-12),
+            "Invalid tableau, expected the weights to sum to 1, got {}",
+            sum
+        );
+        Self { weights, order }
+    }
+
+    pub fn order(&self) -> usize {
+        self.order
+    }
+
+    /// the number of velocity Verlet sub-steps per step
+    pub fn s(&self) -> usize {
+        self.weights.len()
+    }
+
+    pub fn weights(&self) -> &V {
+        &self.weights
+    }
+}
@@ -113,6 +113,990 @@ impl<M: DenseMatrix> Tableau<M> {
         Self::new(a, b, c, d, 3, None)
     }
 
+    /// The explicit Dormand-Prince 5(4) method (DOPRI5)
+    /// from Dormand, J. R., & Prince, P. J. (1980). A family of embedded Runge-Kutta formulae. Journal of computational and applied mathematics, 6(1), 19-26.
+    ///
+    /// continuous extension from :
+    /// Hairer, E., Nørsett, S. P., & Wanner, G. (1993). Solving Ordinary Differential Equations I: Nonstiff Problems, Section II.6 (the `contd5` dense output of the DOPRI5 code).
+    pub fn dopri5() -> Self {
+        let mut a = M::zeros(7, 7);
+        a[(1, 0)] = M::T::from(1.0 / 5.0);
+
+        a[(2, 0)] = M::T::from(3.0 / 40.0);
+        a[(2, 1)] = M::T::from(9.0 / 40.0);
+
+        a[(3, 0)] = M::T::from(44.0 / 45.0);
+        a[(3, 1)] = M::T::from(-56.0 / 15.0);
+        a[(3, 2)] = M::T::from(32.0 / 9.0);
+
+        a[(4, 0)] = M::T::from(19372.0 / 6561.0);
+        a[(4, 1)] = M::T::from(-25360.0 / 2187.0);
+        a[(4, 2)] = M::T::from(64448.0 / 6561.0);
+        a[(4, 3)] = M::T::from(-212.0 / 729.0);
+
+        a[(5, 0)] = M::T::from(9017.0 / 3168.0);
+        a[(5, 1)] = M::T::from(-355.0 / 33.0);
+        a[(5, 2)] = M::T::from(46732.0 / 5247.0);
+        a[(5, 3)] = M::T::from(49.0 / 176.0);
+        a[(5, 4)] = M::T::from(-5103.0 / 18656.0);
+
+        a[(6, 0)] = M::T::from(35.0 / 384.0);
+        a[(6, 1)] = M::T::zero();
+        a[(6, 2)] = M::T::from(500.0 / 1113.0);
+        a[(6, 3)] = M::T::from(125.0 / 192.0);
+        a[(6, 4)] = M::T::from(-2187.0 / 6784.0);
+        a[(6, 5)] = M::T::from(11.0 / 84.0);
+
+        let b = M::V::from_vec(vec![
+            a[(6, 0)],
+            a[(6, 1)],
+            a[(6, 2)],
+            a[(6, 3)],
+            a[(6, 4)],
+            a[(6, 5)],
+            M::T::zero(),
+        ]);
+
+        let c = M::V::from_vec(vec![
+            M::T::zero(),
+            M::T::from(1.0 / 5.0),
+            M::T::from(3.0 / 10.0),
+            M::T::from(4.0 / 5.0),
+            M::T::from(8.0 / 9.0),
+            M::T::one(),
+            M::T::one(),
+        ]);
+
+        // d = b - b_hat
+        let d = M::V::from_vec(vec![
+            M::T::from(71.0 / 57600.0),
+            M::T::zero(),
+            M::T::from(-71.0 / 16695.0),
+            M::T::from(71.0 / 1920.0),
+            M::T::from(-17253.0 / 339200.0),
+            M::T::from(22.0 / 525.0),
+            M::T::from(-1.0 / 40.0),
+        ]);
+
+        // the dense output of Hairer et al. is
+        // y(theta) = y0 + theta * (ydiff + (1 - theta) * (h k1 - ydiff + theta * (2 ydiff - h k1 - h k7 + (1 - theta) * h sum_i dense_i k_i)))
+        // where ydiff = h sum_i b_i k_i, expanding in powers of theta gives the beta coefficients below
+        let dense = [
+            -12715105075.0 / 11282082432.0,
+            0.0,
+            87487479700.0 / 32700410799.0,
+            -10690763975.0 / 1880347072.0,
+            701980252875.0 / 199316789632.0,
+            -1453857185.0 / 822651844.0,
+            69997945.0 / 29380423.0,
+        ];
+        let mut beta = M::zeros(7, 4);
+        for (i, &dense_i) in dense.iter().enumerate() {
+            let dense_i = M::T::from(dense_i);
+            let first = if i == 0 { M::T::one() } else { M::T::zero() };
+            let last = if i == 6 { M::T::one() } else { M::T::zero() };
+            beta[(i, 0)] = first;
+            beta[(i, 1)] = M::T::from(3.0) * b[i] - M::T::from(2.0) * first - last + dense_i;
+            beta[(i, 2)] = M::T::from(-2.0) * b[i] + first + last - M::T::from(2.0) * dense_i;
+            beta[(i, 3)] = dense_i;
+        }
+
+        Self::new(a, b, c, d, 5, Some(beta))
+    }
+
+    /// Tsitouras 5(4) explicit Runge-Kutta method (Tsit5)
+    /// from Tsitouras, C. (2011). Runge–Kutta pairs of order 5(4) satisfying only the first column simplifying assumption. Computers & Mathematics with Applications, 62(2), 770-775.
+    ///
+    /// continuous extension from the same reference.
+    pub fn tsit45() -> Self {
+        let c = M::V::from_vec(vec![
+            M::T::zero(),
+            M::T::from(0.161),
+            M::T::from(0.327),
+            M::T::from(0.9),
+            M::T::from(0.9800255409045097),
+            M::T::one(),
+            M::T::one(),
+        ]);
+
+        let b = M::V::from_vec(vec![
+            M::T::from(0.09646076681806523),
+            M::T::from(0.01),
+            M::T::from(0.4798896504144996),
+            M::T::from(1.379008574103742),
+            M::T::from(-3.290069515436081),
+            M::T::from(2.324710524099774),
+            M::T::zero(),
+        ]);
+
+        // the first column of a is given by the row sum condition a(i, 0) = c(i) - sum_{j > 0} a(i, j)
+        let mut a = M::zeros(7, 7);
+        a[(2, 1)] = M::T::from(0.335480655492357);
+
+        a[(3, 1)] = M::T::from(-6.359448489975075);
+        a[(3, 2)] = M::T::from(4.362295432869581);
+
+        a[(4, 1)] = M::T::from(-11.74888356406283);
+        a[(4, 2)] = M::T::from(7.495539342889836);
+        a[(4, 3)] = M::T::from(-0.09249506636175525);
+
+        a[(5, 1)] = M::T::from(-12.92096931784711);
+        a[(5, 2)] = M::T::from(8.159367898576159);
+        a[(5, 3)] = M::T::from(-0.071584973281401);
+        a[(5, 4)] = M::T::from(-0.02826905039406838);
+
+        for i in 1..6 {
+            let mut a_sum = M::T::zero();
+            for j in 1..i {
+                a_sum += a[(i, j)];
+            }
+            a[(i, 0)] = c[i] - a_sum;
+        }
+        for j in 0..6 {
+            a[(6, j)] = b[j];
+        }
+
+        // d = b - b_hat
+        let d = M::V::from_vec(vec![
+            M::T::from(-0.001780011052225777),
+            M::T::from(-0.0008164344596567469),
+            M::T::from(0.007880878010261995),
+            M::T::from(-0.1447110071732629),
+            M::T::from(0.5823571654525552),
+            M::T::from(-0.45808210592918697),
+            M::T::from(0.015151515151515152),
+        ]);
+
+        // the continuous extension is b_i(theta) = sum_j beta(i, j) theta^(j + 1)
+        let mut beta = M::zeros(7, 4);
+        beta[(0, 0)] = M::T::one();
+        beta[(0, 1)] = M::T::from(-2.763706197274826);
+        beta[(0, 2)] = M::T::from(2.9132554618219126);
+        beta[(0, 3)] = M::T::from(-1.0530884977290216);
+        beta[(1, 1)] = M::T::from(0.1317);
+        beta[(1, 2)] = M::T::from(-0.2234);
+        beta[(1, 3)] = M::T::from(0.1017);
+        beta[(2, 1)] = M::T::from(3.9302962368947516);
+        beta[(2, 2)] = M::T::from(-5.941033872131505);
+        beta[(2, 3)] = M::T::from(2.490627285651253);
+        beta[(3, 1)] = M::T::from(-12.411077166933676);
+        beta[(3, 2)] = M::T::from(30.33818863028232);
+        beta[(3, 3)] = M::T::from(-16.548102889244902);
+        beta[(4, 1)] = M::T::from(37.50931341651104);
+        beta[(4, 2)] = M::T::from(-88.1789048947664);
+        beta[(4, 3)] = M::T::from(47.37952196281928);
+        beta[(5, 1)] = M::T::from(-27.896526289197286);
+        beta[(5, 2)] = M::T::from(65.09189467479366);
+        beta[(5, 3)] = M::T::from(-34.87065786149661);
+        beta[(6, 1)] = M::T::from(1.5);
+        beta[(6, 2)] = M::T::from(-4.0);
+        beta[(6, 3)] = M::T::from(2.5);
+
+        Self::new(a, b, c, d, 5, Some(beta))
+    }
+
+    /// Verner's "most efficient" 6(5) explicit Runge-Kutta method (Vern6)
+    /// from Verner, J. H. (2010). Numerically optimal Runge–Kutta pairs with interpolants. Numerical Algorithms, 53(2), 383-396.
+    ///
+    /// The method has the first same as last property. An extra stage at c = 1/2 is appended before the last stage to give
+    /// an order 5 continuous extension that is C1 continuous between steps.
+    ///
+    /// The published coefficients are rounded, so they have been corrected (in exact arithmetic) by the smallest relative change
+    /// that makes the row sum, stage order and quadrature conditions of the method, its embedded method and its continuous extension hold exactly.
+    pub fn vern56() -> Self {
+        let mut a = M::zeros(10, 10);
+        a[(1, 0)] = M::T::from(0.06);
+
+        a[(2, 0)] = M::T::from(0.019239962962962962);
+        a[(2, 1)] = M::T::from(0.07669337037037037);
+
+        a[(3, 0)] = M::T::from(0.035975);
+        a[(3, 2)] = M::T::from(0.107925);
+
+        a[(4, 0)] = M::T::from(1.3186834152331484);
+        a[(4, 2)] = M::T::from(-5.042058063628562);
+        a[(4, 3)] = M::T::from(4.220674648395414);
+
+        a[(5, 0)] = M::T::from(-41.872591664327516);
+        a[(5, 2)] = M::T::from(159.4325621631375);
+        a[(5, 3)] = M::T::from(-122.11921356501003);
+        a[(5, 4)] = M::T::from(5.531743066200054);
+
+        a[(6, 0)] = M::T::from(-54.43015693531517);
+        a[(6, 2)] = M::T::from(207.06725136501566);
+        a[(6, 3)] = M::T::from(-158.61081378458886);
+        a[(6, 4)] = M::T::from(6.991816585950656);
+        a[(6, 5)] = M::T::from(-0.01859723106230935);
+
+        a[(7, 0)] = M::T::from(-54.66374178728198);
+        a[(7, 2)] = M::T::from(207.95280625538936);
+        a[(7, 3)] = M::T::from(-159.2889574744995);
+        a[(7, 4)] = M::T::from(7.018743740796944);
+        a[(7, 5)] = M::T::from(-0.018338785905045722);
+        a[(7, 6)] = M::T::from(-0.0005119484997882099);
+
+        // extra stage at c = 1/2 used only by the continuous extension
+        a[(8, 0)] = M::T::from(-0.008004344558378113);
+        a[(8, 3)] = M::T::from(0.3541775268963063);
+        a[(8, 4)] = M::T::from(0.15864409124024953);
+        a[(8, 5)] = M::T::from(0.001627584348528714);
+        a[(8, 6)] = M::T::from(-0.004358907215383469);
+        a[(8, 7)] = M::T::from(-0.002085950711322968);
+
+        let b = M::V::from_vec(vec![
+            M::T::from(0.03438957868357036),
+            M::T::zero(),
+            M::T::zero(),
+            M::T::from(0.2582624555633503),
+            M::T::from(0.4209371189673537),
+            M::T::from(4.40539646966931),
+            M::T::from(-176.48311902429865),
+            M::T::from(172.36413340141507),
+            M::T::zero(),
+            M::T::zero(),
+        ]);
+        for j in 0..9 {
+            a[(9, j)] = b[j];
+        }
+
+        let c = M::V::from_vec(vec![
+            M::T::zero(),
+            M::T::from(0.06),
+            M::T::from(0.09593333333333333),
+            M::T::from(0.1439),
+            M::T::from(0.4973),
+            M::T::from(0.9725),
+            M::T::from(0.9995),
+            M::T::one(),
+            M::T::from(0.5),
+            M::T::one(),
+        ]);
+
+        // d = b - b_hat
+        let d = M::V::from_vec(vec![
+            M::T::from(-0.014658466174113802),
+            M::T::zero(),
+            M::T::zero(),
+            M::T::from(0.03303487362676431),
+            M::T::from(-0.04836076484824183),
+            M::T::from(3.586185596054026),
+            M::T::from(-175.86367308006734),
+            M::T::from(172.36413340141507),
+            M::T::zero(),
+            M::T::from(-0.05666156000615866),
+        ]);
+
+        // the continuous extension is b_i(theta) = sum_j beta(i, j) theta^(j + 1)
+        let mut beta = M::zeros(10, 5);
+        beta[(0, 0)] = M::T::one();
+        beta[(0, 1)] = M::T::from(-5.50265935628263);
+        beta[(0, 2)] = M::T::from(11.110683987698557);
+        beta[(0, 3)] = M::T::from(-9.541442013131373);
+        beta[(0, 4)] = M::T::from(2.9678069603990163);
+        beta[(3, 1)] = M::T::from(6.710359705775308);
+        beta[(3, 2)] = M::T::from(-18.11981897528181);
+        beta[(3, 3)] = M::T::from(17.399871111054445);
+        beta[(3, 4)] = M::T::from(-5.732149385984595);
+        beta[(4, 1)] = M::T::from(6.2348371247901815);
+        beta[(4, 2)] = M::T::from(-21.570827901761024);
+        beta[(4, 3)] = M::T::from(26.541830023988275);
+        beta[(4, 4)] = M::T::from(-10.784902128050078);
+        beta[(5, 1)] = M::T::from(12.037357307541367);
+        beta[(5, 2)] = M::T::from(-61.578582661074776);
+        beta[(5, 3)] = M::T::from(109.072075747872);
+        beta[(5, 4)] = M::T::from(-55.12545392466929);
+        beta[(6, 1)] = M::T::from(-414.7189973012724);
+        beta[(6, 2)] = M::T::from(1877.5176835076397);
+        beta[(6, 3)] = M::T::from(-3393.2939702329554);
+        beta[(6, 4)] = M::T::from(1754.0121650022895);
+        beta[(7, 1)] = M::T::from(403.8718680955233);
+        beta[(7, 2)] = M::T::from(-1822.8251280490097);
+        beta[(7, 3)] = M::T::from(3295.8553188185247);
+        beta[(7, 4)] = M::T::from(-1704.5379254636234);
+        beta[(8, 1)] = M::T::from(-8.000000075540774);
+        beta[(8, 2)] = M::T::from(32.000000809362405);
+        beta[(8, 3)] = M::T::from(-40.0000013921025);
+        beta[(8, 4)] = M::T::from(16.00000065828086);
+        beta[(9, 1)] = M::T::from(-0.6327655005343653);
+        beta[(9, 2)] = M::T::from(3.4659892824265635);
+        beta[(9, 3)] = M::T::from(-6.033682063250031);
+        beta[(9, 4)] = M::T::from(3.200458281357833);
+
+        Self::new(a, b, c, d, 6, Some(beta))
+    }
+
+    /// Verner's "most efficient" 7(6) explicit Runge-Kutta method (Vern7)
+    /// from Verner, J. H. (2010). Numerically optimal Runge–Kutta pairs with interpolants. Numerical Algorithms, 53(2), 383-396.
+    ///
+    /// The method does not have the first same as last property. An extra stage at c = 1/2 is appended to give
+    /// an order 5 continuous extension.
+    ///
+    /// As for [Self::vern56], the rounded published coefficients have been corrected so that the row sum, stage order and quadrature conditions hold exactly.
+    pub fn vern67() -> Self {
+        let mut a = M::zeros(11, 11);
+        a[(1, 0)] = M::T::from(0.005);
+
+        a[(2, 0)] = M::T::from(-1.07679012345679);
+        a[(2, 1)] = M::T::from(1.185679012345679);
+
+        a[(3, 0)] = M::T::from(0.04083333333333333);
+        a[(3, 2)] = M::T::from(0.1225);
+
+        a[(4, 0)] = M::T::from(0.6389139236255726);
+        a[(4, 2)] = M::T::from(-2.455672638223657);
+        a[(4, 3)] = M::T::from(2.272258714598084);
+
+        a[(5, 0)] = M::T::from(-2.6615773750187572);
+        a[(5, 2)] = M::T::from(10.804513886456139);
+        a[(5, 3)] = M::T::from(-8.3539146573962);
+        a[(5, 4)] = M::T::from(0.8204875949566569);
+
+        a[(6, 0)] = M::T::from(6.067741434696772);
+        a[(6, 2)] = M::T::from(-24.711273635911088);
+        a[(6, 3)] = M::T::from(20.427517930788895);
+        a[(6, 4)] = M::T::from(-1.9061579788166472);
+        a[(6, 5)] = M::T::from(1.0061722492420682);
+
+        a[(7, 0)] = M::T::from(12.054670076253203);
+        a[(7, 2)] = M::T::from(-49.75478495046899);
+        a[(7, 3)] = M::T::from(41.142888638604674);
+        a[(7, 4)] = M::T::from(-4.461760149974004);
+        a[(7, 5)] = M::T::from(2.042334822239175);
+        a[(7, 6)] = M::T::from(-0.09834843665406107);
+
+        a[(8, 0)] = M::T::from(10.138146522881808);
+        a[(8, 2)] = M::T::from(-42.6411360317175);
+        a[(8, 3)] = M::T::from(35.76384003992257);
+        a[(8, 4)] = M::T::from(-4.3480228403929075);
+        a[(8, 5)] = M::T::from(2.0098622683770357);
+        a[(8, 6)] = M::T::from(0.3487490460338272);
+        a[(8, 7)] = M::T::from(-0.27143900510483127);
+
+        a[(9, 0)] = M::T::from(-45.030072034298676);
+        a[(9, 2)] = M::T::from(187.3272437654589);
+        a[(9, 3)] = M::T::from(-154.02882369350186);
+        a[(9, 4)] = M::T::from(18.56465306347536);
+        a[(9, 5)] = M::T::from(-7.141809679295079);
+        a[(9, 6)] = M::T::from(1.3088085781613787);
+
+        // extra stage at c = 1/2 used only by the continuous extension
+        a[(10, 0)] = M::T::from(0.03182075359176513);
+        a[(10, 3)] = M::T::from(0.30056151993644764);
+        a[(10, 4)] = M::T::from(0.15683621644173934);
+        a[(10, 5)] = M::T::from(0.020561404906564117);
+        a[(10, 6)] = M::T::from(-0.011737740461133274);
+        a[(10, 7)] = M::T::from(-0.004737798608507369);
+        a[(10, 8)] = M::T::from(0.007361313081173634);
+        a[(10, 9)] = M::T::from(-0.0006656688880492276);
+
+        let b = M::V::from_vec(vec![
+            M::T::from(0.04715561848627222),
+            M::T::zero(),
+            M::T::zero(),
+            M::T::from(0.25750564298434153),
+            M::T::from(0.2621665397741262),
+            M::T::from(0.15216092656738558),
+            M::T::from(0.4939969170032485),
+            M::T::from(-0.2943031171403251),
+            M::T::from(0.08131747232495111),
+            M::T::zero(),
+            M::T::zero(),
+        ]);
+
+        let c = M::V::from_vec(vec![
+            M::T::zero(),
+            M::T::from(0.005),
+            M::T::from(0.10888888888888888),
+            M::T::from(0.16333333333333333),
+            M::T::from(0.4555),
+            M::T::from(0.6095094489978381),
+            M::T::from(0.884),
+            M::T::from(0.925),
+            M::T::one(),
+            M::T::one(),
+            M::T::from(0.5),
+        ]);
+
+        // d = b - b_hat
+        let d = M::V::from_vec(vec![
+            M::T::from(0.0025470118799310465),
+            M::T::zero(),
+            M::T::zero(),
+            M::T::from(-0.009658394872795753),
+            M::T::from(0.04206470975639692),
+            M::T::from(-0.06668224374693013),
+            M::T::from(0.2650097464621282),
+            M::T::from(-0.2943031171403251),
+            M::T::from(0.08131747232495111),
+            M::T::from(-0.020295184663356277),
+            M::T::zero(),
+        ]);
+
+        // the continuous extension is b_i(theta) = sum_j beta(i, j) theta^(j + 1)
+        let mut beta = M::zeros(11, 5);
+        beta[(0, 0)] = M::T::one();
+        beta[(0, 1)] = M::T::from(-4.978662065612539);
+        beta[(0, 2)] = M::T::from(9.579346507711644);
+        beta[(0, 3)] = M::T::from(-7.811119961858402);
+        beta[(0, 4)] = M::T::from(2.25759113824557);
+        beta[(3, 1)] = M::T::from(6.072391524508608);
+        beta[(3, 2)] = M::T::from(-15.676010452119264);
+        beta[(3, 3)] = M::T::from(13.772007064906001);
+        beta[(3, 4)] = M::T::from(-3.9108824943110023);
+        beta[(4, 1)] = M::T::from(7.2927246304712385);
+        beta[(4, 2)] = M::T::from(-35.11939326035613);
+        beta[(4, 3)] = M::T::from(55.20184579556053);
+        beta[(4, 4)] = M::T::from(-27.11301062590152);
+        beta[(5, 1)] = M::T::from(1.1968412393254455);
+        beta[(5, 2)] = M::T::from(-5.984273415604282);
+        beta[(5, 3)] = M::T::from(8.674791596917435);
+        beta[(5, 4)] = M::T::from(-3.7351984940712137);
+        beta[(6, 1)] = M::T::from(5.877161445746535);
+        beta[(6, 2)] = M::T::from(-32.78519526356637);
+        beta[(6, 3)] = M::T::from(56.406531609541275);
+        beta[(6, 4)] = M::T::from(-29.00450087471819);
+        beta[(7, 1)] = M::T::from(-5.1808888075089685);
+        beta[(7, 2)] = M::T::from(28.426356552912527);
+        beta[(7, 3)] = M::T::from(-49.325948471164956);
+        beta[(7, 4)] = M::T::from(25.786177608621074);
+        beta[(8, 1)] = M::T::from(0.7862933497512159);
+        beta[(8, 2)] = M::T::from(-3.986932127803996);
+        beta[(8, 3)] = M::T::from(6.946622139862506);
+        beta[(8, 4)] = M::T::from(-3.6646658894847746);
+        beta[(9, 1)] = M::T::from(-0.39919465001290677);
+        beta[(9, 2)] = M::T::from(2.212768125484105);
+        beta[(9, 3)] = M::T::from(-3.8647297737491417);
+        beta[(9, 4)] = M::T::from(2.0511562982779434);
+        beta[(10, 1)] = M::T::from(-10.666666666668627);
+        beta[(10, 2)] = M::T::from(53.33333333334176);
+        beta[(10, 3)] = M::T::from(-80.00000000001525);
+        beta[(10, 4)] = M::T::from(37.33333333334211);
+
+        Self::new(a, b, c, d, 7, Some(beta))
+    }
+
+    /// Verner's "most efficient" 8(7) explicit Runge-Kutta method (Vern8)
+    /// from Verner, J. H. (2010). Numerically optimal Runge–Kutta pairs with interpolants. Numerical Algorithms, 53(2), 383-396.
+    ///
+    /// The method does not have the first same as last property. The embedded method uses its own last stage in place of
+    /// the one in the reference, and an extra stage at c = 1/2 is appended to give an order 5 continuous extension.
+    pub fn vern78() -> Self {
+        let mut a = M::zeros(14, 14);
+        a[(1, 0)] = M::T::from(0.05);
+
+        a[(2, 0)] = M::T::from(-0.0069931640625);
+        a[(2, 1)] = M::T::from(0.1135556640625);
+
+        a[(3, 0)] = M::T::from(0.0399609375);
+        a[(3, 2)] = M::T::from(0.1198828125);
+
+        a[(4, 0)] = M::T::from(0.36139756280045754);
+        a[(4, 2)] = M::T::from(-1.3415240667004928);
+        a[(4, 3)] = M::T::from(1.3701265039000352);
+
+        a[(5, 0)] = M::T::from(0.049047202797202795);
+        a[(5, 3)] = M::T::from(0.23509720422144048);
+        a[(5, 4)] = M::T::from(0.18085559298135673);
+
+        a[(6, 0)] = M::T::from(0.06169289044289044);
+        a[(6, 3)] = M::T::from(0.11236568314640277);
+        a[(6, 4)] = M::T::from(-0.03885046071451367);
+        a[(6, 5)] = M::T::from(0.01979188712522046);
+
+        a[(7, 0)] = M::T::from(-1.767630240222327);
+        a[(7, 3)] = M::T::from(-62.5);
+        a[(7, 4)] = M::T::from(-6.061889377376669);
+        a[(7, 5)] = M::T::from(5.6508231982227635);
+        a[(7, 6)] = M::T::from(65.62169641937624);
+
+        a[(8, 0)] = M::T::from(-1.1809450665549708);
+        a[(8, 3)] = M::T::from(-41.50473441114321);
+        a[(8, 4)] = M::T::from(-4.434438319103725);
+        a[(8, 5)] = M::T::from(4.260408188586133);
+        a[(8, 6)] = M::T::from(43.75364022446172);
+        a[(8, 7)] = M::T::from(0.00787142548991231);
+
+        a[(9, 0)] = M::T::from(-1.2814059994414884);
+        a[(9, 3)] = M::T::from(-45.047139960139866);
+        a[(9, 4)] = M::T::from(-4.731362069449577);
+        a[(9, 5)] = M::T::from(4.514967016593808);
+        a[(9, 6)] = M::T::from(47.44909557172985);
+        a[(9, 7)] = M::T::from(0.010592282971116612);
+        a[(9, 8)] = M::T::from(-0.0057468422638446166);
+
+        a[(10, 0)] = M::T::from(-1.7244701342624853);
+        a[(10, 3)] = M::T::from(-60.92349008483054);
+        a[(10, 4)] = M::T::from(-5.951518376222393);
+        a[(10, 5)] = M::T::from(5.556523730698456);
+        a[(10, 6)] = M::T::from(63.98301198033305);
+        a[(10, 7)] = M::T::from(0.014642028250414961);
+        a[(10, 8)] = M::T::from(0.06460408772358203);
+        a[(10, 9)] = M::T::from(-0.0793032316900888);
+
+        a[(11, 0)] = M::T::from(-3.301622667747079);
+        a[(11, 3)] = M::T::from(-118.01127235975251);
+        a[(11, 4)] = M::T::from(-10.141422388456112);
+        a[(11, 5)] = M::T::from(9.139311332232058);
+        a[(11, 6)] = M::T::from(123.37594282840426);
+        a[(11, 7)] = M::T::from(4.62324437887458);
+        a[(11, 8)] = M::T::from(-3.3832777380682018);
+        a[(11, 9)] = M::T::from(4.527592100324618);
+        a[(11, 10)] = M::T::from(-5.828495485811623);
+
+        // stage only used by the embedded method, it agrees with the previous stage up to order 6
+        // so the error estimate is of order 8
+        a[(12, 0)] = M::T::from(-3.3013781082374343);
+        a[(12, 3)] = M::T::from(-118.01127235975251);
+        a[(12, 4)] = M::T::from(-10.141422388456112);
+        a[(12, 5)] = M::T::from(9.141329141288855);
+        a[(12, 6)] = M::T::from(123.37517755302537);
+        a[(12, 7)] = M::T::from(13.204328865029971);
+        a[(12, 8)] = M::T::from(-7.227047021411292);
+        a[(12, 9)] = M::T::from(10.075327198776053);
+        a[(12, 10)] = M::T::from(-16.115042880262898);
+
+        // extra stage at c = 1/2 used only by the continuous extension
+        a[(13, 0)] = M::T::from(0.04673633348730565);
+        a[(13, 3)] = M::T::from(0.12252830448309057);
+        a[(13, 4)] = M::T::from(0.11758477704136094);
+        a[(13, 5)] = M::T::from(0.0983804620351951);
+        a[(13, 6)] = M::T::from(0.1213762616998206);
+        a[(13, 7)] = M::T::from(-0.003654427246145516);
+        a[(13, 8)] = M::T::from(-0.00619241552459936);
+        a[(13, 9)] = M::T::from(-0.006020445258895172);
+        a[(13, 10)] = M::T::from(-0.003970186470943021);
+        a[(13, 11)] = M::T::from(0.006615667876905103);
+        a[(13, 12)] = M::T::from(0.006615667876905103);
+
+        let b = M::V::from_vec(vec![
+            M::T::from(0.04427989419007951),
+            M::T::zero(),
+            M::T::zero(),
+            M::T::zero(),
+            M::T::zero(),
+            M::T::from(0.3541049391724449),
+            M::T::from(0.2479692154956438),
+            M::T::from(-15.694202038838084),
+            M::T::from(25.084064965558564),
+            M::T::from(-31.738367786260277),
+            M::T::from(22.938283273988784),
+            M::T::from(-0.2361324633071542),
+            M::T::zero(),
+            M::T::zero(),
+        ]);
+
+        let c = M::V::from_vec(vec![
+            M::T::zero(),
+            M::T::from(0.05),
+            M::T::from(0.1065625),
+            M::T::from(0.15984375),
+            M::T::from(0.39),
+            M::T::from(0.465),
+            M::T::from(0.155),
+            M::T::from(0.943),
+            M::T::from(0.901802041735857),
+            M::T::from(0.909),
+            M::T::from(0.94),
+            M::T::one(),
+            M::T::one(),
+            M::T::from(0.5),
+        ]);
+
+        // d = b - b_hat
+        let d = M::V::from_vec(vec![
+            M::T::zero(),
+            M::T::zero(),
+            M::T::zero(),
+            M::T::zero(),
+            M::T::zero(),
+            M::T::zero(),
+            M::T::zero(),
+            M::T::zero(),
+            M::T::zero(),
+            M::T::zero(),
+            M::T::zero(),
+            M::T::one(),
+            M::T::from(-1.0),
+            M::T::zero(),
+        ]);
+
+        // the continuous extension is b_i(theta) = sum_j beta(i, j) theta^(j + 1)
+        let mut beta = M::zeros(14, 5);
+        beta[(0, 0)] = M::T::from(1.0);
+        beta[(0, 1)] = M::T::from(-5.291937003393453);
+        beta[(0, 2)] = M::T::from(10.71863253077721);
+        beta[(0, 3)] = M::T::from(-9.294778805025238);
+        beta[(0, 4)] = M::T::from(2.912363171831561);
+        beta[(5, 1)] = M::T::from(-0.5028228757854999);
+        beta[(5, 2)] = M::T::from(4.619509461414373);
+        beta[(5, 3)] = M::T::from(-6.399241080088426);
+        beta[(5, 4)] = M::T::from(2.6366594336319964);
+        beta[(6, 1)] = M::T::from(6.721662184467797);
+        beta[(6, 2)] = M::T::from(-18.670202295361303);
+        beta[(6, 3)] = M::T::from(18.308548931714924);
+        beta[(6, 4)] = M::T::from(-6.1120396053257755);
+        beta[(7, 1)] = M::T::from(-3.5218891842148627);
+        beta[(7, 2)] = M::T::from(-5.952263454968152);
+        beta[(7, 3)] = M::T::from(-0.9166322318531752);
+        beta[(7, 4)] = M::T::from(-5.303417167801894);
+        beta[(8, 1)] = M::T::from(6.623841682386567);
+        beta[(8, 2)] = M::T::from(3.3073352101216105);
+        beta[(8, 3)] = M::T::from(11.640254271184475);
+        beta[(8, 4)] = M::T::from(3.5126338018659102);
+        beta[(9, 1)] = M::T::from(-7.539938023858245);
+        beta[(9, 2)] = M::T::from(-10.873777679260447);
+        beta[(9, 3)] = M::T::from(-2.788620147024958);
+        beta[(9, 4)] = M::T::from(-10.536031936116627);
+        beta[(10, 1)] = M::T::from(6.1499113559413585);
+        beta[(10, 2)] = M::T::from(3.5670294325955094);
+        beta[(10, 3)] = M::T::from(9.009112337390757);
+        beta[(10, 4)] = M::T::from(4.212230148061158);
+        beta[(11, 1)] = M::T::from(-0.5975129549882114);
+        beta[(11, 2)] = M::T::from(3.2361035376197025);
+        beta[(11, 3)] = M::T::from(-5.67890365888168);
+        beta[(11, 4)] = M::T::from(2.804180612943035);
+        beta[(12, 1)] = M::T::from(-0.5384798391614228);
+        beta[(12, 2)] = M::T::from(3.295136653446491);
+        beta[(12, 3)] = M::T::from(-5.619870543054891);
+        beta[(12, 4)] = M::T::from(2.8632137287698236);
+        beta[(13, 1)] = M::T::from(-1.5028353413940274);
+        beta[(13, 2)] = M::T::from(6.752496603615004);
+        beta[(13, 3)] = M::T::from(-8.25986907436179);
+        beta[(13, 4)] = M::T::from(3.0102078121408122);
+
+        Self::new(a, b, c, d, 8, Some(beta))
+    }
+
+    /// Verner's "most efficient" 9(8) explicit Runge-Kutta method (Vern9)
+    /// from Verner, J. H. (2010). Numerically optimal Runge–Kutta pairs with interpolants. Numerical Algorithms, 53(2), 383-396.
+    ///
+    /// The method does not have the first same as last property. The embedded method uses its own last stage in place of
+    /// the one in the reference, and an extra stage at c = 1/2 is appended to give an order 5 continuous extension.
+    pub fn vern89() -> Self {
+        let mut a = M::zeros(17, 17);
+        a[(1, 0)] = M::T::from(0.03462);
+
+        a[(2, 0)] = M::T::from(-0.038933543885728734);
+        a[(2, 1)] = M::T::from(0.13595789452450918);
+
+        a[(3, 0)] = M::T::from(0.03638413148954267);
+        a[(3, 2)] = M::T::from(0.109152394468628);
+
+        a[(4, 0)] = M::T::from(2.02576391439397);
+        a[(4, 2)] = M::T::from(-7.638023836496292);
+        a[(4, 3)] = M::T::from(6.173259922102322);
+
+        a[(5, 0)] = M::T::from(0.05112275589406061);
+        a[(5, 3)] = M::T::from(0.17708237945550215);
+        a[(5, 4)] = M::T::from(0.0008027762409222502);
+
+        a[(6, 0)] = M::T::from(0.13160063579752163);
+        a[(6, 3)] = M::T::from(-0.29572762526696367);
+        a[(6, 4)] = M::T::from(0.08781378035642952);
+        a[(6, 5)] = M::T::from(0.6213052975225275);
+
+        a[(7, 0)] = M::T::from(0.07166666666666667);
+        a[(7, 5)] = M::T::from(0.33055335789153195);
+        a[(7, 6)] = M::T::from(0.24277997544180138);
+
+        a[(8, 0)] = M::T::from(0.071806640625);
+        a[(8, 5)] = M::T::from(0.3294380283228177);
+        a[(8, 6)] = M::T::from(0.11651900292718229);
+        a[(8, 7)] = M::T::from(-0.034013671875);
+
+        a[(9, 0)] = M::T::from(0.04836757646340647);
+        a[(9, 5)] = M::T::from(0.03928989925676164);
+        a[(9, 6)] = M::T::from(0.10547409458903446);
+        a[(9, 7)] = M::T::from(-0.021438652846483126);
+        a[(9, 8)] = M::T::from(-0.10412291746271944);
+
+        a[(10, 0)] = M::T::from(-0.026645614872014785);
+        a[(10, 5)] = M::T::from(0.03333333333333333);
+        a[(10, 6)] = M::T::from(-0.1631072244872467);
+        a[(10, 7)] = M::T::from(0.033960816841277615);
+        a[(10, 8)] = M::T::from(0.1572319413814626);
+        a[(10, 9)] = M::T::from(0.21522674780318796);
+
+        a[(11, 0)] = M::T::from(0.036890092487086225);
+        a[(11, 5)] = M::T::from(-0.1465181576725543);
+        a[(11, 6)] = M::T::from(0.22425777681720244);
+        a[(11, 7)] = M::T::from(0.022944057170660725);
+        a[(11, 8)] = M::T::from(-0.003585005290572876);
+        a[(11, 9)] = M::T::from(0.08669223316444385);
+        a[(11, 10)] = M::T::from(0.43838406519683376);
+
+        a[(12, 0)] = M::T::from(-0.48660122151133406);
+        a[(12, 5)] = M::T::from(-6.304602650282853);
+        a[(12, 6)] = M::T::from(-0.2812456182894726);
+        a[(12, 7)] = M::T::from(-2.6790192362198493);
+        a[(12, 8)] = M::T::from(0.5188156639241576);
+        a[(12, 9)] = M::T::from(1.3653531876033418);
+        a[(12, 10)] = M::T::from(5.8850910885039465);
+        a[(12, 11)] = M::T::from(2.8028087862720628);
+
+        a[(13, 0)] = M::T::from(0.41853674577534716);
+        a[(13, 5)] = M::T::from(6.724547581906459);
+        a[(13, 6)] = M::T::from(-0.4254442801646118);
+        a[(13, 7)] = M::T::from(3.3432791530012658);
+        a[(13, 8)] = M::T::from(0.6170816631175378);
+        a[(13, 9)] = M::T::from(-0.9299661239399328);
+        a[(13, 10)] = M::T::from(-6.099948804751011);
+        a[(13, 11)] = M::T::from(-3.002206187889399);
+        a[(13, 12)] = M::T::from(0.2553202529443446);
+
+        a[(14, 0)] = M::T::from(-0.7793740861228846);
+        a[(14, 5)] = M::T::from(-13.937342538107776);
+        a[(14, 6)] = M::T::from(1.2520488533793572);
+        a[(14, 7)] = M::T::from(-14.69150040801687);
+        a[(14, 8)] = M::T::from(-0.4947050585331417);
+        a[(14, 9)] = M::T::from(2.2429749091462368);
+        a[(14, 10)] = M::T::from(13.367893803828643);
+        a[(14, 11)] = M::T::from(14.396650486650687);
+        a[(14, 12)] = M::T::from(-0.79758133317768);
+        a[(14, 13)] = M::T::from(0.4409353709534278);
+
+        // stage only used by the embedded method, it agrees with the previous stage up to order 7
+        // so the error estimate is of order 9
+        a[(15, 0)] = M::T::from(-0.7376697035147132);
+        a[(15, 5)] = M::T::from(-13.937342538107776);
+        a[(15, 6)] = M::T::from(1.2520488533793572);
+        a[(15, 7)] = M::T::from(-7.55416443443839);
+        a[(15, 8)] = M::T::from(-1.0674002394747144);
+        a[(15, 9)] = M::T::from(2.145572468715938);
+        a[(15, 10)] = M::T::from(13.535277738487277);
+        a[(15, 11)] = M::T::from(7.430694987528761);
+        a[(15, 12)] = M::T::from(-0.403033948716089);
+        a[(15, 13)] = M::T::from(0.3360168161403501);
+
+        // extra stage at c = 1/2 used only by the continuous extension
+        a[(16, 0)] = M::T::from(0.03554060672941662);
+        a[(16, 5)] = M::T::from(0.1302720375915595);
+        a[(16, 6)] = M::T::from(0.0510523763758745);
+        a[(16, 7)] = M::T::from(0.01355109614381673);
+        a[(16, 8)] = M::T::from(0.07465630818929953);
+        a[(16, 9)] = M::T::from(0.08422006108025612);
+        a[(16, 10)] = M::T::from(0.13034918219041136);
+        a[(16, 11)] = M::T::from(0.008782122573372922);
+        a[(16, 12)] = M::T::from(-0.02590285474970426);
+        a[(16, 13)] = M::T::from(-0.022255758236289242);
+        a[(16, 14)] = M::T::from(0.009867411055993095);
+        a[(16, 15)] = M::T::from(0.009867411055993095);
+
+        let b = M::V::from_vec(vec![
+            M::T::from(0.014611976858423152),
+            M::T::zero(),
+            M::T::zero(),
+            M::T::zero(),
+            M::T::zero(),
+            M::T::zero(),
+            M::T::zero(),
+            M::T::from(-0.3915211862331339),
+            M::T::from(0.23109325002895065),
+            M::T::from(0.12747667699928525),
+            M::T::from(0.2246434176204158),
+            M::T::from(0.5684352689748513),
+            M::T::from(0.058258715572158275),
+            M::T::from(0.13643174034822156),
+            M::T::from(0.030570139830827976),
+            M::T::zero(),
+            M::T::zero(),
+        ]);
+
+        let c = M::V::from_vec(vec![
+            M::T::zero(),
+            M::T::from(0.03462),
+            M::T::from(0.09702435063878044),
+            M::T::from(0.14553652595817068),
+            M::T::from(0.561),
+            M::T::from(0.229007911590485),
+            M::T::from(0.544992088409515),
+            M::T::from(0.645),
+            M::T::from(0.48375),
+            M::T::from(0.06757),
+            M::T::from(0.25),
+            M::T::from(0.6590650618730999),
+            M::T::from(0.8206),
+            M::T::from(0.9012),
+            M::T::one(),
+            M::T::one(),
+            M::T::from(0.5),
+        ]);
+
+        // d = b - b_hat
+        let d = M::V::from_vec(vec![
+            M::T::zero(),
+            M::T::zero(),
+            M::T::zero(),
+            M::T::zero(),
+            M::T::zero(),
+            M::T::zero(),
+            M::T::zero(),
+            M::T::zero(),
+            M::T::zero(),
+            M::T::zero(),
+            M::T::zero(),
+            M::T::zero(),
+            M::T::zero(),
+            M::T::zero(),
+            M::T::one(),
+            M::T::from(-1.0),
+            M::T::zero(),
+        ]);
+
+        // the continuous extension is b_i(theta) = sum_j beta(i, j) theta^(j + 1)
+        let mut beta = M::zeros(17, 5);
+        beta[(0, 0)] = M::T::from(1.0);
+        beta[(0, 1)] = M::T::from(-4.502182197079796);
+        beta[(0, 2)] = M::T::from(9.509917921915036);
+        beta[(0, 3)] = M::T::from(-8.97256464677355);
+        beta[(0, 4)] = M::T::from(2.979440898796733);
+        beta[(5, 1)] = M::T::from(3.2404349500487815);
+        beta[(5, 2)] = M::T::from(-8.587437926324323);
+        beta[(5, 3)] = M::T::from(8.182837572540368);
+        beta[(5, 4)] = M::T::from(-2.835834596264825);
+        beta[(6, 1)] = M::T::from(-1.1944414156332637);
+        beta[(6, 2)] = M::T::from(4.863589521892158);
+        beta[(6, 3)] = M::T::from(-6.005999791130598);
+        beta[(6, 4)] = M::T::from(2.3368516848717027);
+        beta[(7, 1)] = M::T::from(-0.9113828993886758);
+        beta[(7, 2)] = M::T::from(2.2005389969265092);
+        beta[(7, 3)] = M::T::from(-2.037744213493615);
+        beta[(7, 4)] = M::T::from(0.35706692972264736);
+        beta[(8, 1)] = M::T::from(-0.7666996033066397);
+        beta[(8, 2)] = M::T::from(4.442816741201869);
+        beta[(8, 3)] = M::T::from(-6.042086533693784);
+        beta[(8, 4)] = M::T::from(2.597062645827505);
+        beta[(9, 1)] = M::T::from(0.7597946006239643);
+        beta[(9, 2)] = M::T::from(-3.90847659123597);
+        beta[(9, 3)] = M::T::from(5.271599368120397);
+        beta[(9, 4)] = M::T::from(-1.9954407005091064);
+        beta[(10, 1)] = M::T::from(3.062464035278401);
+        beta[(10, 2)] = M::T::from(-7.551172536285925);
+        beta[(10, 3)] = M::T::from(6.982238588359007);
+        beta[(10, 4)] = M::T::from(-2.268886669731067);
+        beta[(11, 1)] = M::T::from(-0.5317670742730131);
+        beta[(11, 2)] = M::T::from(1.8068301849492538);
+        beta[(11, 3)] = M::T::from(-0.923718152635698);
+        beta[(11, 4)] = M::T::from(0.21709031093430853);
+        beta[(12, 1)] = M::T::from(1.4356880868899657);
+        beta[(12, 2)] = M::T::from(-6.402081630368217);
+        beta[(12, 3)] = M::T::from(9.122789548085372);
+        beta[(12, 4)] = M::T::from(-4.0981372890349625);
+        beta[(13, 1)] = M::T::from(1.7081534143008885);
+        beta[(13, 2)] = M::T::from(-6.803725622336889);
+        beta[(13, 3)] = M::T::from(9.098848467279954);
+        beta[(13, 4)] = M::T::from(-3.8668445188957317);
+        beta[(14, 1)] = M::T::from(-0.6625387308980643);
+        beta[(14, 2)] = M::T::from(2.882769927223113);
+        beta[(14, 3)] = M::T::from(-4.200601021875764);
+        beta[(14, 4)] = M::T::from(2.0109399653815436);
+        beta[(15, 1)] = M::T::from(-0.6701812658557712);
+        beta[(15, 2)] = M::T::from(2.8751273922654064);
+        beta[(15, 3)] = M::T::from(-4.208243556833471);
+        beta[(15, 4)] = M::T::from(2.0032974304238365);
+        beta[(16, 1)] = M::T::from(-0.9673419007067768);
+        beta[(16, 2)] = M::T::from(4.671303620177978);
+        beta[(16, 3)] = M::T::from(-6.267355627948618);
+        beta[(16, 4)] = M::T::from(2.563393908477416);
+
+        Self::new(a, b, c, d, 9, Some(beta))
+    }
+
+    /// The shared `b`, `c` and `d` vectors of the ARK4(3)6L\[2\]SA additive Runge-Kutta method,
+    /// from Kennedy, C. A., & Carpenter, M. H. (2003). Additive Runge–Kutta schemes for convection–diffusion–reaction equations. Applied Numerical Mathematics, 44(1-2), 139-181.
+    fn ark436l2sa_bcd() -> (M::V, M::V, M::V) {
+        let b = [
+            82889.0 / 524892.0,
+            0.0,
+            15625.0 / 83664.0,
+            69875.0 / 102672.0,
+            -2260.0 / 8211.0,
+            1.0 / 4.0,
+        ];
+        let b_hat = [
+            4586570599.0 / 29645900160.0,
+            0.0,
+            178811875.0 / 945068544.0,
+            814220225.0 / 1159782912.0,
+            -3700637.0 / 11593932.0,
+            61727.0 / 225920.0,
+        ];
+        let c = [0.0, 1.0 / 2.0, 83.0 / 250.0, 31.0 / 50.0, 17.0 / 20.0, 1.0];
+        let d = b
+            .iter()
+            .zip(b_hat.iter())
+            .map(|(b, b_hat)| M::T::from(b - b_hat))
+            .collect();
+        let b = b.iter().map(|&b| M::T::from(b)).collect();
+        let c = c.iter().map(|&c| M::T::from(c)).collect();
+        (M::V::from_vec(b), M::V::from_vec(c), M::V::from_vec(d))
+    }
+
+    /// The explicit part of the ARK4(3)6L\[2\]SA additive Runge-Kutta method, to be used with [Self::ark436l2sa_implicit]
+    /// from Kennedy, C. A., & Carpenter, M. H. (2003). Additive Runge–Kutta schemes for convection–diffusion–reaction equations. Applied Numerical Mathematics, 44(1-2), 139-181.
+    ///
+    /// no continuous extension is included so hermite interpolation is used.
+    pub fn ark436l2sa_explicit() -> Self {
+        let mut a = M::zeros(6, 6);
+        a[(1, 0)] = M::T::from(1.0 / 2.0);
+
+        a[(2, 0)] = M::T::from(13861.0 / 62500.0);
+        a[(2, 1)] = M::T::from(6889.0 / 62500.0);
+
+        a[(3, 0)] = M::T::from(-116923316275.0 / 2393684061468.0);
+        a[(3, 1)] = M::T::from(-2731218467317.0 / 15368042101831.0);
+        a[(3, 2)] = M::T::from(9408046702089.0 / 11113171139209.0);
+
+        a[(4, 0)] = M::T::from(-451086348788.0 / 2902428689909.0);
+        a[(4, 1)] = M::T::from(-2682348792572.0 / 7519795681897.0);
+        a[(4, 2)] = M::T::from(12662868775082.0 / 11960479115383.0);
+        a[(4, 3)] = M::T::from(3355817975965.0 / 11060851509271.0);
+
+        a[(5, 0)] = M::T::from(647845179188.0 / 3216320057751.0);
+        a[(5, 1)] = M::T::from(73281519250.0 / 8382639484533.0);
+        a[(5, 2)] = M::T::from(552539513391.0 / 3454668386233.0);
+        a[(5, 3)] = M::T::from(3354512671639.0 / 8306763924573.0);
+        a[(5, 4)] = M::T::from(4040.0 / 17871.0);
+
+        let (b, c, d) = Self::ark436l2sa_bcd();
+        Self::new(a, b, c, d, 4, None)
+    }
+
+    /// The implicit (ESDIRK) part of the ARK4(3)6L\[2\]SA additive Runge-Kutta method, to be used with [Self::ark436l2sa_explicit]
+    /// from Kennedy, C. A., & Carpenter, M. H. (2003). Additive Runge–Kutta schemes for convection–diffusion–reaction equations. Applied Numerical Mathematics, 44(1-2), 139-181.
+    ///
+    /// no continuous extension is included so hermite interpolation is used.
+    pub fn ark436l2sa_implicit() -> Self {
+        let mut a = M::zeros(6, 6);
+        let gamma = M::T::from(1.0 / 4.0);
+        a[(1, 0)] = gamma;
+        a[(1, 1)] = gamma;
+
+        a[(2, 0)] = M::T::from(8611.0 / 62500.0);
+        a[(2, 1)] = M::T::from(-1743.0 / 31250.0);
+        a[(2, 2)] = gamma;
+
+        a[(3, 0)] = M::T::from(5012029.0 / 34652500.0);
+        a[(3, 1)] = M::T::from(-654441.0 / 2922500.0);
+        a[(3, 2)] = M::T::from(174375.0 / 388108.0);
+        a[(3, 3)] = gamma;
+
+        a[(4, 0)] = M::T::from(15267082809.0 / 155376265600.0);
+        a[(4, 1)] = M::T::from(-71443401.0 / 120774400.0);
+        a[(4, 2)] = M::T::from(730878875.0 / 902184768.0);
+        a[(4, 3)] = M::T::from(2285395.0 / 8070912.0);
+        a[(4, 4)] = gamma;
+
+        let (b, c, d) = Self::ark436l2sa_bcd();
+
+        // stiffly accurate, so the last row of a is b
+        for j in 0..6 {
+            a[(5, j)] = b[j];
+        }
+
+        Self::new(a, b, c, d, 4, None)
+    }
+
     pub fn new(a: M, b: M::V, c: M::V, d: M::V, order: usize, beta: Option<M>) -> Self {
         let s = c.len();
         assert_eq!(a.ncols(), s, "Invalid number of rows in a, expected {}", s);
@@ -180,3 +1164,117 @@ impl<M: DenseMatrix> Tableau<M> {
         self.beta.as_ref()
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::Tableau;
+    use crate::{Matrix, Vector};
+
+    type M = nalgebra::DMatrix<f64>;
+
+    /// Check that the sum of `terms` is `expected`, to within a tolerance relative to the largest term so that
+    /// the check allows for rounding errors in tableaus with large coefficients
+    fn check_sum(terms: impl Iterator<Item = f64>, expected: f64, condition: &str) {
+        let (sum, scale) = terms.fold((0.0, 1.0_f64), |(sum, scale), x| {
+            (sum + x, scale.max(x.abs()))
+        });
+        assert!(
+            (sum - expected).abs() < 1e-14 * scale,
+            "{}: error {:e}",
+            condition,
+            sum - expected
+        );
+    }
+
+    /// Check the row sum condition `c_i = sum_j a_ij`, the quadrature conditions `sum_i b_i c_i^(k - 1) = 1 / k` for the main and
+    /// embedded methods, the remaining order conditions up to order 4, and the quadrature conditions of the continuous extension
+    fn check_order_conditions(tableau: Tableau<M>, name: &str) {
+        let (a, b, c, d) = (tableau.a(), tableau.b(), tableau.c(), tableau.d());
+        let s = tableau.s();
+        let p = tableau.order();
+        for i in 0..s {
+            check_sum(
+                (0..s).map(|j| a[(i, j)]),
+                c[i],
+                &format!("{} row sum {}", name, i),
+            );
+        }
+
+        let b_hat = (0..s).map(|i| b[i] - d[i]).collect::<Vec<_>>();
+        for k in 1..=p {
+            let expected = 1.0 / k as f64;
+            check_sum(
+                (0..s).map(|i| b[i] * c[i].powi(k as i32 - 1)),
+                expected,
+                &format!("{} quadrature condition of order {}", name, k),
+            );
+            if k < p {
+                check_sum(
+                    (0..s).map(|i| b_hat[i] * c[i].powi(k as i32 - 1)),
+                    expected,
+                    &format!("{} embedded quadrature condition of order {}", name, k),
+                );
+            }
+        }
+
+        let pairs = || (0..s).flat_map(|i| (0..s).map(move |j| (i, j)));
+        check_sum(
+            pairs().map(|(i, j)| b[i] * a[(i, j)] * c[j]),
+            1.0 / 6.0,
+            &format!("{} order condition sum b_i a_ij c_j", name),
+        );
+        check_sum(
+            pairs().map(|(i, j)| b[i] * c[i] * a[(i, j)] * c[j]),
+            1.0 / 8.0,
+            &format!("{} order condition sum b_i c_i a_ij c_j", name),
+        );
+        check_sum(
+            pairs().map(|(i, j)| b[i] * a[(i, j)] * c[j] * c[j]),
+            1.0 / 12.0,
+            &format!("{} order condition sum b_i a_ij c_j^2", name),
+        );
+        check_sum(
+            pairs().flat_map(|(i, j)| (0..s).map(move |k| b[i] * a[(i, j)] * a[(j, k)] * c[k])),
+            1.0 / 24.0,
+            &format!("{} order condition sum b_i a_ij a_jk c_k", name),
+        );
+
+        // the continuous extension b_i(theta) = sum_j beta_ij theta^(j + 1) must satisfy b_i(1) = b_i and the quadrature
+        // conditions for each power of theta
+        if let Some(beta) = tableau.beta() {
+            for i in 0..s {
+                check_sum(
+                    (0..beta.ncols()).map(|j| beta[(i, j)]),
+                    b[i],
+                    &format!("{} continuous extension at theta = 1 for stage {}", name, i),
+                );
+            }
+            for j in 0..beta.ncols() {
+                for k in 1..=beta.ncols() {
+                    let expected = if k == j + 1 { 1.0 / k as f64 } else { 0.0 };
+                    check_sum(
+                        (0..s).map(|i| beta[(i, j)] * c[i].powi(k as i32 - 1)),
+                        expected,
+                        &format!(
+                            "{} continuous extension quadrature condition of order {} for theta^{}",
+                            name,
+                            k,
+                            j + 1
+                        ),
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_explicit_tableau_order_conditions() {
+        check_order_conditions(Tableau::<M>::dopri5(), "dopri5");
+        check_order_conditions(Tableau::<M>::tsit45(), "tsit45");
+        check_order_conditions(Tableau::<M>::vern56(), "vern56");
+        check_order_conditions(Tableau::<M>::vern67(), "vern67");
+        check_order_conditions(Tableau::<M>::vern78(), "vern78");
+        check_order_conditions(Tableau::<M>::vern89(), "vern89");
+        check_order_conditions(Tableau::<M>::ark436l2sa_explicit(), "ark436l2sa_explicit");
+    }
+}
@@ -0,0 +1,130 @@
+use crate::{
+    matrix::Matrix, ode_solver::problem::OdeSolverSolution, OdeBuilder, OdeEquationsDelay,
+    OdeEquationsImplicit, OdeSolverProblem, Scalar, Vector,
+};
+use num_traits::{Pow, Zero};
+
+// delayed exponential decay, for each state y_i
+// y_i'(t) = -a y_i(t - tau) (p = [a, tau])
+// with constant history y_i(t) = c_i for t <= 0
+fn delay_decay<M: Matrix>(_x: &M::V, xlag: &M::V, p: &M::V, _t: M::T, y: &mut M::V) {
+    for i in 0..y.len() {
+        y[i] = -p[0] * xlag[i];
+    }
+}
+
+// the rhs does not depend on the current state
+fn delay_decay_jacobian<M: Matrix>(
+    _x: &M::V,
+    _xlag: &M::V,
+    _p: &M::V,
+    _t: M::T,
+    _v: &M::V,
+    y: &mut M::V,
+) {
+    y.fill(M::T::zero());
+}
+
+fn delay_decay_delays<M: Matrix>(_x: &M::V, p: &M::V, _t: M::T, tau: &mut M::V) {
+    tau[0] = p[1];
+}
+
+// the delay is tau + (y_0 - y_0_exact(t)), which is state-dependent but equal to tau along the solution
+fn delay_decay_state_dependent_delays<M: Matrix>(x: &M::V, p: &M::V, t: M::T, tau: &mut M::V) {
+    tau[0] = p[1] + x[0] - history_value::<M::T>(0) * delay_decay_exact(p[0], p[1], t);
+}
+
+fn history_value<T: Scalar>(i: usize) -> T {
+    T::from(1.0 / (i as f64 + 1.0))
+}
+
+fn delay_decay_history<M: Matrix>(_p: &M::V, _t: M::T) -> M::V {
+    M::V::from_vec(vec![history_value(0), history_value(1)])
+}
+
+fn delay_decay_init<M: Matrix>(p: &M::V, t: M::T) -> M::V {
+    delay_decay_history::<M>(p, t)
+}
+
+// the solution for a unit history, using the method of steps
+// y(t) = sum_{k=0}^{n} (-a)^k (t - (k - 1) tau)^k / k!, for t in [(n - 1) tau, n tau]
+fn delay_decay_exact<T: Scalar>(a: T, tau: T, t: T) -> T {
+    let mut y = T::zero();
+    let mut factorial = T::one();
+    let mut k = 0;
+    while t >= T::from(k as f64 - 1.0) * tau {
+        if k > 0 {
+            factorial *= T::from(k as f64);
+        }
+        let s = t - T::from(k as f64 - 1.0) * tau;
+        y += Pow::pow(-a, k) * Pow::pow(s, k) / factorial;
+        k += 1;
+    }
+    y
+}
+
+fn delay_decay_solution<M: Matrix>(a: f64, tau: f64) -> OdeSolverSolution<M::V> {
+    let mut soln = OdeSolverSolution::default();
+    for i in 0..8 {
+        let t = M::T::from(0.5 * (i as f64 + 1.0));
+        let y = delay_decay_exact(M::T::from(a), M::T::from(tau), t);
+        let y = M::V::from_vec(vec![
+            history_value::<M::T>(0) * y,
+            history_value::<M::T>(1) * y,
+        ]);
+        soln.push(y, t);
+    }
+    soln
+}
+
+#[allow(clippy::type_complexity)]
+pub fn delay_decay_problem<M: Matrix + 'static>(
+    use_coloring: bool,
+) -> (
+    OdeSolverProblem<impl OdeEquationsDelay<M = M, V = M::V, T = M::T> + OdeEquationsImplicit>,
+    OdeSolverSolution<M::V>,
+) {
+    let a = 1.0;
+    let tau = 1.0;
+    let problem = OdeBuilder::<M>::new()
+        .p([a, tau])
+        .use_coloring(use_coloring)
+        .rhs_delay(
+            delay_decay::<M>,
+            delay_decay_jacobian::<M>,
+            delay_decay_delays::<M>,
+            1,
+            delay_decay_history::<M>,
+        )
+        .init(delay_decay_init::<M>)
+        .build()
+        .unwrap();
+    let soln = delay_decay_solution::<M>(a, tau);
+    (problem, soln)
+}
+
+#[allow(clippy::type_complexity)]
+pub fn delay_decay_state_dependent_problem<M: Matrix + 'static>(
+    use_coloring: bool,
+) -> (
+    OdeSolverProblem<impl OdeEquationsDelay<M = M, V = M::V, T = M::T> + OdeEquationsImplicit>,
+    OdeSolverSolution<M::V>,
+) {
+    let a = 1.0;
+    let tau = 1.0;
+    let problem = OdeBuilder::<M>::new()
+        .p([a, tau])
+        .use_coloring(use_coloring)
+        .rhs_delay(
+            delay_decay::<M>,
+            delay_decay_jacobian::<M>,
+            delay_decay_state_dependent_delays::<M>,
+            1,
+            delay_decay_history::<M>,
+        )
+        .init(delay_decay_init::<M>)
+        .build()
+        .unwrap();
+    let soln = delay_decay_solution::<M>(a, tau);
+    (problem, soln)
+}
@@ -1,7 +1,7 @@
 use crate::{
     matrix::Matrix, ode_solver::problem::OdeSolverSolution, scalar::scale, ConstantOp, OdeBuilder,
-    OdeEquations, OdeEquationsAdjoint, OdeEquationsImplicit, OdeEquationsSens, OdeSolverProblem,
-    Vector,
+    OdeEquations, OdeEquationsAdjoint, OdeEquationsImex, OdeEquationsImplicit, OdeEquationsSens,
+    OdeSolverProblem, Vector,
 };
 use nalgebra::ComplexField;
 use num_traits::{One, Zero};
@@ -14,6 +14,25 @@ fn exponential_decay<M: Matrix>(x: &M::V, p: &M::V, _t: M::T, y: &mut M::V) {
     y.mul_assign(scale(-p[0]));
 }
 
+// split of the exponential decay problem into two halves
+// dy/dt = -ay/2 - ay/2 (p = [a, y0])
+fn exponential_decay_half<M: Matrix>(x: &M::V, p: &M::V, _t: M::T, y: &mut M::V) {
+    y.copy_from(x);
+    y.mul_assign(scale(-p[0] / M::T::from(2.0)));
+}
+
+// Jv = -av/2
+fn exponential_decay_half_jacobian<M: Matrix>(
+    _x: &M::V,
+    p: &M::V,
+    _t: M::T,
+    v: &M::V,
+    y: &mut M::V,
+) {
+    y.copy_from(v);
+    y.mul_assign(scale(-p[0] / M::T::from(2.0)));
+}
+
 // df/dp v = -yv (p = [a, y0])
 // df/dp = | -y  0 |
 //         | -y  0 |
@@ -241,6 +260,97 @@ pub fn exponential_decay_problem<M: Matrix + 'static>(
     (problem, soln)
 }
 
+#[allow(clippy::type_complexity)]
+pub fn exponential_decay_problem_no_jacobian<M: Matrix + 'static>() -> (
+    OdeSolverProblem<impl OdeEquations<M = M, V = M::V, T = M::T>>,
+    OdeSolverSolution<M::V>,
+) {
+    let h = 1.0;
+    let k = 0.1;
+    let y0 = 1.0;
+    let problem = OdeBuilder::<M>::new()
+        .h0(h)
+        .p([k, y0])
+        .rhs(exponential_decay::<M>)
+        .init(exponential_decay_init::<M>)
+        .build()
+        .unwrap();
+    let p = [M::T::from(k), M::T::from(y0)];
+    let mut soln = OdeSolverSolution::default();
+    for i in 0..10 {
+        let t = M::T::from(i as f64);
+        let y0: M::V = problem.eqn.init().call(M::T::zero());
+        let y = y0 * scale(M::T::exp(-p[0] * t));
+        soln.push(y, t);
+    }
+    (problem, soln)
+}
+
+#[allow(clippy::type_complexity)]
+pub fn exponential_decay_problem_imex<M: Matrix + 'static>(
+    use_coloring: bool,
+) -> (
+    OdeSolverProblem<impl OdeEquationsImex<M = M, V = M::V, T = M::T>>,
+    OdeSolverSolution<M::V>,
+) {
+    let h = 1.0;
+    let k = 0.1;
+    let y0 = 1.0;
+    let problem = OdeBuilder::<M>::new()
+        .h0(h)
+        .p([k, y0])
+        .use_coloring(use_coloring)
+        .rhs_imex(
+            exponential_decay_half::<M>,
+            exponential_decay_half::<M>,
+            exponential_decay_half_jacobian::<M>,
+        )
+        .init(exponential_decay_init::<M>)
+        .build()
+        .unwrap();
+    let p = [M::T::from(k), M::T::from(y0)];
+    let mut soln = OdeSolverSolution::default();
+    for i in 0..10 {
+        let t = M::T::from(i as f64);
+        let y0: M::V = problem.eqn.init().call(M::T::zero());
+        let y = y0 * scale(M::T::exp(-p[0] * t));
+        soln.push(y, t);
+    }
+    (problem, soln)
+}
+
+#[allow(clippy::type_complexity)]
+pub fn exponential_decay_problem_imex_with_root<M: Matrix + 'static>(
+    use_coloring: bool,
+) -> (
+    OdeSolverProblem<impl OdeEquationsImex<M = M, V = M::V, T = M::T>>,
+    OdeSolverSolution<M::V>,
+) {
+    let k = 0.1;
+    let y0 = 1.0;
+    let problem = OdeBuilder::<M>::new()
+        .p([k, y0])
+        .use_coloring(use_coloring)
+        .rhs_imex(
+            exponential_decay_half::<M>,
+            exponential_decay_half::<M>,
+            exponential_decay_half_jacobian::<M>,
+        )
+        .init(exponential_decay_init::<M>)
+        .root(exponential_decay_root::<M>, 1)
+        .build()
+        .unwrap();
+    let p = [M::T::from(k), M::T::from(y0)];
+    let mut soln = OdeSolverSolution::default();
+    for i in 0..10 {
+        let t = M::T::from(i as f64);
+        let y0: M::V = problem.eqn.init().call(M::T::zero());
+        let y = y0 * scale(M::T::exp(-p[0] * t));
+        soln.push(y, t);
+    }
+    (problem, soln)
+}
+
 #[allow(clippy::type_complexity)]
 pub fn exponential_decay_problem_with_root<M: Matrix + 'static>(
     use_coloring: bool,
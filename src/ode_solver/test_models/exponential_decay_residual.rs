@@ -0,0 +1,78 @@
+use crate::{
+    matrix::Matrix, ode_solver::problem::OdeSolverSolution, DaeProblem, OdeBuilder,
+    OdeEquationsResidual, Vector,
+};
+
+// exponential decay problem with algebraic constraint, in fully implicit form with a residual that is nonlinear in y'
+// g(y0') - g(-a y0) = 0, where g(x) = x + x^3 is monotonic so this is equivalent to y0' = -a y0
+// y1 - y0 = 0
+fn exponential_decay_residual<M: Matrix>(x: &M::V, dx: &M::V, p: &M::V, _t: M::T, y: &mut M::V) {
+    let a = p[0];
+    y[0] = dx[0] + dx[0] * dx[0] * dx[0] + a * x[0] + a * a * a * x[0] * x[0] * x[0];
+    y[1] = x[1] - x[0];
+}
+
+// dF/dy = | a + 3 a^3 y0^2, 0 |
+//         | -1,             1 |
+fn exponential_decay_residual_jac<M: Matrix>(
+    x: &M::V,
+    _dx: &M::V,
+    p: &M::V,
+    _t: M::T,
+    v: &M::V,
+    y: &mut M::V,
+) {
+    let a = p[0];
+    y[0] = (a + M::T::from(3.0) * a * a * a * x[0] * x[0]) * v[0];
+    y[1] = v[1] - v[0];
+}
+
+// dF/dy' = | 1 + 3 y0'^2, 0 |
+//          | 0,           0 |
+fn exponential_decay_residual_jac_dot<M: Matrix>(
+    _x: &M::V,
+    dx: &M::V,
+    _p: &M::V,
+    _t: M::T,
+    v: &M::V,
+    y: &mut M::V,
+) {
+    y[0] = (M::T::from(1.0) + M::T::from(3.0) * dx[0] * dx[0]) * v[0];
+    y[1] = M::T::from(0.0);
+}
+
+// the algebraic variable is deliberately inconsistent
+fn exponential_decay_residual_init<M: Matrix>(_p: &M::V, _t: M::T) -> M::V {
+    M::V::from_vec(vec![M::T::from(1.0), M::T::from(0.0)])
+}
+
+#[allow(clippy::type_complexity)]
+pub fn exponential_decay_residual_problem<M: Matrix + 'static>() -> (
+    DaeProblem<impl OdeEquationsResidual<M = M, V = M::V, T = M::T>>,
+    OdeSolverSolution<M::V>,
+) {
+    let a = 0.1;
+    let problem = OdeBuilder::<M>::new()
+        .p([a])
+        .rtol(1e-6)
+        .atol([1e-6])
+        .build_residual(
+            exponential_decay_residual::<M>,
+            exponential_decay_residual_jac::<M>,
+            exponential_decay_residual_jac_dot::<M>,
+            exponential_decay_residual_init::<M>,
+            &[1],
+        )
+        .unwrap();
+
+    let mut soln = OdeSolverSolution::default();
+    for i in 0..10 {
+        let t = i as f64;
+        let y = (-a * t).exp();
+        soln.push(
+            M::V::from_vec(vec![M::T::from(y), M::T::from(y)]),
+            M::T::from(t),
+        );
+    }
+    (problem, soln)
+}
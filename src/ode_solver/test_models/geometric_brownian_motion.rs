@@ -0,0 +1,49 @@
+use crate::{matrix::Matrix, NoiseType, OdeBuilder, OdeEquations, SdeProblem, Vector};
+use nalgebra::ComplexField;
+use num_traits::Zero;
+
+// geometric brownian motion, for each state y_i
+// dy_i = mu y_i dt + sigma y_i dW_i (p = [mu, sigma])
+fn gbm_drift<M: Matrix>(x: &M::V, p: &M::V, _t: M::T, y: &mut M::V) {
+    for i in 0..y.len() {
+        y[i] = p[0] * x[i];
+    }
+}
+
+// diagonal noise, y_i = sigma x_i dw_i
+fn gbm_diffusion<M: Matrix>(x: &M::V, p: &M::V, _t: M::T, dw: &M::V, y: &mut M::V) {
+    for i in 0..y.len() {
+        y[i] = p[1] * x[i] * dw[i];
+    }
+}
+
+fn gbm_init<M: Matrix>(_p: &M::V, _t: M::T) -> M::V {
+    M::V::from_vec(vec![M::T::from(1.0), M::T::from(2.0)])
+}
+
+/// the exact solution y_i(t) = y_i(0) exp((mu - sigma^2 / 2) t + sigma W_i(t)), given the Brownian path at time t
+pub fn gbm_exact<M: Matrix>(p: &M::V, t: M::T, w: &M::V) -> M::V {
+    let y0 = gbm_init::<M>(p, M::T::zero());
+    let mut y = M::V::zeros(y0.len());
+    let drift = p[0] - p[1] * p[1] / M::T::from(2.0);
+    for i in 0..y.len() {
+        y[i] = y0[i] * (drift * t + p[1] * w[i]).exp();
+    }
+    y
+}
+
+#[allow(clippy::type_complexity)]
+pub fn gbm_problem<M: Matrix + 'static>(
+    h0: f64,
+) -> SdeProblem<
+    impl OdeEquations<M = M, V = M::V, T = M::T>,
+    impl Fn(&M::V, &M::V, M::T, &M::V, &mut M::V),
+> {
+    OdeBuilder::<M>::new()
+        .p([0.1, 0.2])
+        .h0(h0)
+        .rhs(gbm_drift::<M>)
+        .init(gbm_init::<M>)
+        .build_sde(gbm_diffusion::<M>, NoiseType::Diagonal)
+        .unwrap()
+}
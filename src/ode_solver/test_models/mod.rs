@@ -1,9 +1,15 @@
+pub mod delay_decay;
 pub mod dydt_y2;
 pub mod exponential_decay;
+pub mod exponential_decay_residual;
 pub mod exponential_decay_with_algebraic;
 pub mod foodweb;
 pub mod gaussian_decay;
+pub mod geometric_brownian_motion;
 pub mod heat2d;
+pub mod ornstein_uhlenbeck;
+pub mod quadratic_bvp;
 pub mod robertson;
 pub mod robertson_ode;
 pub mod robertson_ode_with_sens;
+pub mod spring_mass;
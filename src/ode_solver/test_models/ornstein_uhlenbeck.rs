@@ -0,0 +1,38 @@
+use crate::{matrix::Matrix, NoiseType, OdeBuilder, OdeEquations, SdeProblem, Vector};
+
+// Ornstein-Uhlenbeck process, for each state y_i driven by the same Wiener process
+// dy_i = theta_i (mu - y_i) dt + sigma_i(t) dW (p = [theta_0, theta_1, mu, sigma])
+fn ou_drift<M: Matrix>(x: &M::V, p: &M::V, _t: M::T, y: &mut M::V) {
+    for i in 0..y.len() {
+        y[i] = p[i] * (p[2] - x[i]);
+    }
+}
+
+// the noise is additive, y_i = sigma_i(t) dw_0, with sigma_0 = sigma and sigma_1 = sigma (1 + t)
+fn ou_diffusion<M: Matrix>(_x: &M::V, p: &M::V, t: M::T, dw: &M::V, y: &mut M::V) {
+    y[0] = p[3] * dw[0];
+    y[1] = p[3] * (M::T::from(1.0) + t) * dw[0];
+}
+
+fn ou_init<M: Matrix>(_p: &M::V, _t: M::T) -> M::V {
+    M::V::from_vec(vec![M::T::from(1.0), M::T::from(-1.0)])
+}
+
+#[allow(clippy::type_complexity)]
+pub fn ou_problem<M: Matrix + 'static>(
+    h0: f64,
+    noise: NoiseType,
+) -> SdeProblem<
+    impl OdeEquations<M = M, V = M::V, T = M::T>,
+    impl Fn(&M::V, &M::V, M::T, &M::V, &mut M::V),
+> {
+    OdeBuilder::<M>::new()
+        .p([1.0, 2.0, 0.5, 0.3])
+        .rtol(1e-4)
+        .atol([1e-4])
+        .h0(h0)
+        .rhs(ou_drift::<M>)
+        .init(ou_init::<M>)
+        .build_sde(ou_diffusion::<M>, noise)
+        .unwrap()
+}
@@ -0,0 +1,88 @@
+use crate::{
+    matrix::Matrix, ode_solver::problem::OdeSolverSolution, BvpProblem, OdeBuilder,
+    OdeEquationsImplicit, Vector,
+};
+use num_traits::Zero;
+
+// second order nonlinear boundary value problem
+// y'' = a y^2 (p = [a]), 0 <= t <= 1
+// y(0) = 4, y(1) = 1
+// with a = 3/2 this has the solution y = 4 / (1 + t)^2
+// written as a first order system
+// y0' = y1
+// y1' = a y0^2
+fn quadratic_bvp<M: Matrix>(x: &M::V, p: &M::V, _t: M::T, y: &mut M::V) {
+    y[0] = x[1];
+    y[1] = p[0] * x[0] * x[0];
+}
+
+// J = | 0        1 |
+//     | 2 a y0   0 |
+fn quadratic_bvp_jacobian<M: Matrix>(x: &M::V, p: &M::V, _t: M::T, v: &M::V, y: &mut M::V) {
+    y[0] = v[1];
+    y[1] = M::T::from(2.0) * p[0] * x[0] * v[0];
+}
+
+// not used by the boundary value solvers
+fn quadratic_bvp_init<M: Matrix>(_p: &M::V, _t: M::T) -> M::V {
+    M::V::from_vec(vec![M::T::from(4.0), M::T::from(-3.0)])
+}
+
+// g(ya, yb) = | ya0 - 4 |
+//             | yb0 - 1 |
+fn quadratic_bvp_bc<M: Matrix>(ya: &M::V, yb: &M::V, r: &mut M::V) {
+    r[0] = ya[0] - M::T::from(4.0);
+    r[1] = yb[0] - M::T::from(1.0);
+}
+
+// dg/dya = | 1 0 |
+//          | 0 0 |
+fn quadratic_bvp_bc_jac_a<M: Matrix>(_ya: &M::V, _yb: &M::V, v: &M::V, r: &mut M::V) {
+    r[0] = v[0];
+    r[1] = M::T::zero();
+}
+
+// dg/dyb = | 0 0 |
+//          | 1 0 |
+fn quadratic_bvp_bc_jac_b<M: Matrix>(_ya: &M::V, _yb: &M::V, v: &M::V, r: &mut M::V) {
+    r[0] = M::T::zero();
+    r[1] = v[0];
+}
+
+#[allow(clippy::type_complexity)]
+pub fn quadratic_bvp_problem<M: Matrix + 'static>() -> (
+    BvpProblem<
+        impl OdeEquationsImplicit<M = M, V = M::V, T = M::T>,
+        impl Fn(&M::V, &M::V, &mut M::V),
+        impl Fn(&M::V, &M::V, &M::V, &mut M::V),
+        impl Fn(&M::V, &M::V, &M::V, &mut M::V),
+    >,
+    OdeSolverSolution<M::V>,
+) {
+    let ode = OdeBuilder::<M>::new()
+        .p([1.5])
+        .rtol(1e-8)
+        .atol([1e-8])
+        .rhs_implicit(quadratic_bvp::<M>, quadratic_bvp_jacobian::<M>)
+        .init(quadratic_bvp_init::<M>)
+        .build()
+        .unwrap();
+    let problem = BvpProblem::new(
+        ode,
+        quadratic_bvp_bc::<M>,
+        quadratic_bvp_bc_jac_a::<M>,
+        quadratic_bvp_bc_jac_b::<M>,
+    );
+
+    let mut soln = OdeSolverSolution::default();
+    for i in 0..5 {
+        let t = i as f64 / 4.0;
+        let y = 4.0 / ((1.0 + t) * (1.0 + t));
+        let dy = -8.0 / ((1.0 + t) * (1.0 + t) * (1.0 + t));
+        soln.push(
+            M::V::from_vec(vec![M::T::from(y), M::T::from(dy)]),
+            M::T::from(t),
+        );
+    }
+    (problem, soln)
+}
@@ -0,0 +1,148 @@
+use crate::{
+    matrix::Matrix, ode_solver::problem::OdeSolverSolution, ConstantOp, OdeBuilder,
+    OdeEquationsImplicit, OdeEquationsSecondOrder, OdeSolverProblem, Vector,
+};
+use nalgebra::ComplexField;
+use num_traits::Zero;
+
+// damped spring-mass problem, for each of the positions y_i
+// m y_i'' = -m k y_i - m c y_i' (p = [k, c, m])
+// the full state is x = [y, v]
+fn spring_mass_force<M: Matrix>(x: &M::V, p: &M::V, _t: M::T, y: &mut M::V) {
+    let n = y.len();
+    for i in 0..n {
+        y[i] = -p[2] * (p[0] * x[i] + p[1] * x[n + i]);
+    }
+}
+
+// J = -m | kI  cI |
+// Jv = -m (k v_y + c v_v)
+fn spring_mass_force_jacobian<M: Matrix>(_x: &M::V, p: &M::V, _t: M::T, v: &M::V, y: &mut M::V) {
+    let n = y.len();
+    for i in 0..n {
+        y[i] = -p[2] * (p[0] * v[i] + p[1] * v[n + i]);
+    }
+}
+
+// y = M x + beta * y, with M = diag(I, m I)
+fn spring_mass_mass<M: Matrix>(x: &M::V, p: &M::V, _t: M::T, beta: M::T, y: &mut M::V) {
+    let n = y.len() / 2;
+    for i in 0..n {
+        y[i] = x[i] + beta * y[i];
+        y[n + i] = p[2] * x[n + i] + beta * y[n + i];
+    }
+}
+
+fn spring_mass_init<M: Matrix>(_p: &M::V, _t: M::T) -> M::V {
+    M::V::from_vec(vec![
+        M::T::from(1.0),
+        M::T::from(0.5),
+        M::T::zero(),
+        M::T::zero(),
+    ])
+}
+
+fn spring_mass_root<M: Matrix>(x: &M::V, _p: &M::V, _t: M::T, y: &mut M::V) {
+    y[0] = x[0] - M::T::from(0.6);
+}
+
+// the solution of y'' = -k y - c y' for each position, which is underdamped for c^2 < 4k
+fn spring_mass_solution<M: Matrix + 'static>(
+    problem: &OdeSolverProblem<impl OdeEquationsSecondOrder<M = M, V = M::V, T = M::T>>,
+    k: f64,
+    c: f64,
+) -> OdeSolverSolution<M::V> {
+    let y0: M::V = problem.eqn.init().call(M::T::zero());
+    let n = y0.len() / 2;
+    let omega = M::T::from((k - c * c / 4.0).sqrt());
+    let (k, c) = (M::T::from(k), M::T::from(c));
+    let half = M::T::from(0.5);
+    let mut soln = OdeSolverSolution::default();
+    for i in 0..10 {
+        let t = M::T::from(i as f64);
+        let decay = ComplexField::exp(-half * c * t);
+        let (sin, cos) = (ComplexField::sin(omega * t), ComplexField::cos(omega * t));
+        let mut y = M::V::zeros(2 * n);
+        for j in 0..n {
+            let (yj, vj) = (y0[j], y0[n + j]);
+            y[j] = decay * (yj * cos + (vj + half * c * yj) / omega * sin);
+            y[n + j] = decay * (vj * cos - (k * yj + half * c * vj) / omega * sin);
+        }
+        soln.push(y, t);
+    }
+    soln
+}
+
+#[allow(clippy::type_complexity)]
+pub fn spring_mass_problem<M: Matrix + 'static>(
+    use_coloring: bool,
+    damping: f64,
+) -> (
+    OdeSolverProblem<
+        impl OdeEquationsSecondOrder<M = M, V = M::V, T = M::T> + OdeEquationsImplicit,
+    >,
+    OdeSolverSolution<M::V>,
+) {
+    let k = 1.0;
+    let m = 1.0;
+    let problem = OdeBuilder::<M>::new()
+        .h0(0.01)
+        .p([k, damping, m])
+        .use_coloring(use_coloring)
+        .rhs_second_order(spring_mass_force::<M>, spring_mass_force_jacobian::<M>)
+        .init(spring_mass_init::<M>)
+        .build()
+        .unwrap();
+    let soln = spring_mass_solution(&problem, k, damping);
+    (problem, soln)
+}
+
+#[allow(clippy::type_complexity)]
+pub fn spring_mass_problem_with_mass<M: Matrix + 'static>(
+    use_coloring: bool,
+    damping: f64,
+) -> (
+    OdeSolverProblem<
+        impl OdeEquationsSecondOrder<M = M, V = M::V, T = M::T> + OdeEquationsImplicit,
+    >,
+    OdeSolverSolution<M::V>,
+) {
+    let k = 1.0;
+    let m = 2.0;
+    let problem = OdeBuilder::<M>::new()
+        .h0(0.01)
+        .p([k, damping, m])
+        .use_coloring(use_coloring)
+        .rhs_second_order(spring_mass_force::<M>, spring_mass_force_jacobian::<M>)
+        .mass(spring_mass_mass::<M>)
+        .init(spring_mass_init::<M>)
+        .build()
+        .unwrap();
+    let soln = spring_mass_solution(&problem, k, damping);
+    (problem, soln)
+}
+
+#[allow(clippy::type_complexity)]
+pub fn spring_mass_problem_with_root<M: Matrix + 'static>(
+    use_coloring: bool,
+) -> (
+    OdeSolverProblem<
+        impl OdeEquationsSecondOrder<M = M, V = M::V, T = M::T> + OdeEquationsImplicit,
+    >,
+    OdeSolverSolution<M::V>,
+) {
+    let k = 1.0;
+    let damping = 0.1;
+    let m = 1.0;
+    let problem = OdeBuilder::<M>::new()
+        .h0(0.01)
+        .p([k, damping, m])
+        .use_coloring(use_coloring)
+        .rhs_second_order(spring_mass_force::<M>, spring_mass_force_jacobian::<M>)
+        .init(spring_mass_init::<M>)
+        .root(spring_mass_root::<M>, 1)
+        .build()
+        .unwrap();
+    let soln = spring_mass_solution(&problem, k, damping);
+    (problem, soln)
+}
@@ -0,0 +1,185 @@
+use crate::{
+    matrix::DenseMatrix, scale, Matrix, NonLinearOp, NonLinearOpJacobian, OdeEquationsResidual, Op,
+    Vector,
+};
+use num_traits::{One, Zero};
+use std::ops::MulAssign;
+use std::{
+    cell::RefCell,
+    ops::{AddAssign, Deref, SubAssign},
+};
+
+// callable to solve for F(t, y, (y - y0 + psi) / c) = 0
+pub struct BdfResidualCallable<'a, Eqn: OdeEquationsResidual> {
+    eqn: &'a Eqn,
+    psi_neg_y0: RefCell<Eqn::V>,
+    c: RefCell<Eqn::T>,
+    dy: RefCell<Eqn::V>,
+    tmp: RefCell<Eqn::V>,
+    number_of_jac_evals: RefCell<usize>,
+}
+
+impl<'a, Eqn: OdeEquationsResidual> BdfResidualCallable<'a, Eqn> {
+    pub fn new(eqn: &'a Eqn) -> Self {
+        let n = eqn.nstates();
+        Self {
+            eqn,
+            psi_neg_y0: RefCell::new(<Eqn::V as Vector>::zeros(n)),
+            c: RefCell::new(Eqn::T::zero()),
+            dy: RefCell::new(<Eqn::V as Vector>::zeros(n)),
+            tmp: RefCell::new(<Eqn::V as Vector>::zeros(n)),
+            number_of_jac_evals: RefCell::new(0),
+        }
+    }
+
+    #[cfg(test)]
+    fn set_c_direct(&mut self, c: Eqn::T) {
+        self.c.replace(c);
+    }
+
+    #[cfg(test)]
+    fn set_psi_neg_y0_direct(&mut self, psi_neg_y0: Eqn::V) {
+        self.psi_neg_y0.replace(psi_neg_y0);
+    }
+
+    pub fn eqn(&self) -> &'a Eqn {
+        self.eqn
+    }
+
+    pub fn number_of_jac_evals(&self) -> usize {
+        *self.number_of_jac_evals.borrow()
+    }
+
+    pub fn set_c(&self, h: Eqn::T, alpha: Eqn::T) {
+        self.c.replace(h * alpha);
+    }
+
+    pub fn set_psi_and_y0<M: DenseMatrix<V = Eqn::V, T = Eqn::T>>(
+        &self,
+        diff: &M,
+        gamma: &[Eqn::T],
+        alpha: &[Eqn::T],
+        order: usize,
+        y0: &Eqn::V,
+    ) {
+        // same psi term as the mass-matrix form of the BDF method (see [crate::op::bdf::BdfCallable])
+        let mut psi = self.psi_neg_y0.borrow_mut();
+        psi.axpy_v(gamma[1], &diff.column(1), Eqn::T::zero());
+        for (i, &gamma_i) in gamma.iter().enumerate().take(order + 1).skip(2) {
+            psi.axpy_v(gamma_i, &diff.column(i), Eqn::T::one());
+        }
+        psi.mul_assign(scale(alpha[order]));
+
+        // now negate y0
+        psi.sub_assign(y0);
+    }
+
+    // y' = (y - y0 + psi) / c
+    fn set_dy(&self, x: &Eqn::V) {
+        let mut dy = self.dy.borrow_mut();
+        dy.copy_from(x);
+        dy.add_assign(self.psi_neg_y0.borrow().deref());
+        let c = *self.c.borrow();
+        dy.mul_assign(scale(Eqn::T::one() / c));
+    }
+}
+
+impl<Eqn: OdeEquationsResidual> Op for BdfResidualCallable<'_, Eqn> {
+    type V = Eqn::V;
+    type T = Eqn::T;
+    type M = Eqn::M;
+    fn nstates(&self) -> usize {
+        self.eqn.nstates()
+    }
+    fn nout(&self) -> usize {
+        self.eqn.nout()
+    }
+    fn nparams(&self) -> usize {
+        self.eqn.nparams()
+    }
+}
+
+impl<Eqn: OdeEquationsResidual> NonLinearOp for BdfResidualCallable<'_, Eqn> {
+    // F(t, y, (y - y0 + psi) / c)
+    fn call_inplace(&self, x: &Eqn::V, t: Eqn::T, y: &mut Eqn::V) {
+        self.set_dy(x);
+        self.eqn.residual_inplace(x, self.dy.borrow().deref(), t, y);
+    }
+}
+
+impl<Eqn: OdeEquationsResidual> NonLinearOpJacobian for BdfResidualCallable<'_, Eqn> {
+    // (dF/dy + 1/c dF/dy') v
+    fn jac_mul_inplace(&self, x: &Eqn::V, t: Eqn::T, v: &Eqn::V, y: &mut Eqn::V) {
+        self.set_dy(x);
+        let dy = self.dy.borrow();
+        let mut tmp = self.tmp.borrow_mut();
+        self.eqn.residual_jac_mul_inplace(x, &dy, t, v, y);
+        self.eqn
+            .residual_jac_dot_mul_inplace(x, &dy, t, v, &mut tmp);
+        let c = *self.c.borrow();
+        y.axpy(Eqn::T::one() / c, &tmp, Eqn::T::one());
+    }
+
+    // dF/dy + 1/c dF/dy'
+    fn jacobian_inplace(&self, x: &Self::V, t: Self::T, y: &mut Self::M) {
+        self.set_dy(x);
+        let c = *self.c.borrow();
+        self.eqn
+            .residual_jacobian_inplace(x, self.dy.borrow().deref(), t, Eqn::T::one() / c, y);
+        let number_of_jac_evals = *self.number_of_jac_evals.borrow() + 1;
+        self.number_of_jac_evals.replace(number_of_jac_evals);
+    }
+
+    fn jacobian_sparsity(&self) -> Option<<Self::M as Matrix>::Sparsity> {
+        self.eqn.residual_jacobian_sparsity()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::ode_solver::test_models::exponential_decay_residual::exponential_decay_residual_problem;
+    use crate::vector::Vector;
+    use crate::{NonLinearOp, NonLinearOpJacobian};
+
+    use super::BdfResidualCallable;
+    type Mcpu = nalgebra::DMatrix<f64>;
+    type Vcpu = nalgebra::DVector<f64>;
+
+    #[test]
+    fn test_bdf_residual_callable() {
+        let (problem, _soln) = exponential_decay_residual_problem::<Mcpu>();
+        let mut callable = BdfResidualCallable::new(&problem.eqn);
+        let c = 0.1;
+        let psi_neg_y0 = Vcpu::from_vec(vec![1.1, 1.2]);
+        callable.set_c_direct(c);
+        callable.set_psi_neg_y0_direct(psi_neg_y0);
+        let y = Vcpu::from_vec(vec![1.0, 1.0]);
+        let t = 0.0;
+        let mut y_out = Vcpu::from_vec(vec![0.0, 0.0]);
+
+        // F(y, y') = |y'_0 + y'_0^3 + 0.1 y_0 + 0.001 y_0^3|
+        //            |y_1 - y_0                            |
+        // y' = (y + psi_neg_y0) / c = |21|
+        //                             |22|
+        // i.e. F = |21 + 9261 + 0.1 + 0.001| = |9282.101|
+        //          |1 - 1                  |   |0       |
+        callable.call_inplace(&y, t, &mut y_out);
+        let y_out_expect = Vcpu::from_vec(vec![9282.101, 0.0]);
+        y_out.assert_eq_st(&y_out_expect, 1e-8);
+
+        // dF/dy = |0.1 + 0.003 y_0^2 0|, dF/dy' = |1 + 3 y'_0^2 0|
+        //         |-1                1|           |0            0|
+        // J = dF/dy + 1/c dF/dy' = |0.103 0| + 10 |1324 0| = |13240.103 0|
+        //                          |-1    1|      |0    0|   |-1        1|
+        let v = Vcpu::from_vec(vec![1.0, 1.0]);
+        callable.jac_mul_inplace(&y, t, &v, &mut y_out);
+        let y_out_expect = Vcpu::from_vec(vec![13240.103, 0.0]);
+        y_out.assert_eq_st(&y_out_expect, 1e-8);
+
+        let jac = callable.jacobian(&y, t);
+        assert!((jac[(0, 0)] - 13240.103).abs() < 1e-8);
+        assert_eq!(jac[(0, 1)], 0.0);
+        assert_eq!(jac[(1, 0)], -1.0);
+        assert_eq!(jac[(1, 1)], 1.0);
+    }
+}
@@ -1,11 +1,9 @@
-use std::cell::RefCell;
-
 use crate::{
     find_jacobian_non_zeros, jacobian::JacobianColoring, Matrix, MatrixSparsity, NonLinearOp,
     NonLinearOpJacobian, Op,
 };
 
-use super::{BuilderOp, OpStatistics, ParameterisedOp};
+use super::{AtomicOpStatistics, BuilderOp, OpStatistics, ParameterisedOp};
 
 pub struct Closure<M, F, G>
 where
@@ -20,7 +18,7 @@ where
     nparams: usize,
     coloring: Option<JacobianColoring<M>>,
     sparsity: Option<M::Sparsity>,
-    statistics: RefCell<OpStatistics>,
+    statistics: AtomicOpStatistics,
 }
 
 impl<M, F, G> Closure<M, F, G>
@@ -36,7 +34,7 @@ where
             nstates,
             nparams,
             nout,
-            statistics: RefCell::new(OpStatistics::default()),
+            statistics: AtomicOpStatistics::new(),
             coloring: None,
             sparsity: None,
         }
@@ -95,7 +93,7 @@ where
         self.nparams
     }
     fn statistics(&self) -> OpStatistics {
-        self.statistics.borrow().clone()
+        self.statistics.get()
     }
 }
 
@@ -106,7 +104,7 @@ where
     G: Fn(&M::V, &M::V, M::T, &M::V, &mut M::V),
 {
     fn call_inplace(&self, x: &M::V, t: M::T, y: &mut M::V) {
-        self.op.statistics.borrow_mut().increment_call();
+        self.op.statistics.increment_call();
         (self.op.func)(x, self.p, t, y)
     }
 }
@@ -118,11 +116,11 @@ where
     G: Fn(&M::V, &M::V, M::T, &M::V, &mut M::V),
 {
     fn jac_mul_inplace(&self, x: &M::V, t: M::T, v: &M::V, y: &mut M::V) {
-        self.op.statistics.borrow_mut().increment_jac_mul();
+        self.op.statistics.increment_jac_mul();
         (self.op.jacobian_action)(x, self.p, t, v, y)
     }
     fn jacobian_inplace(&self, x: &Self::V, t: Self::T, y: &mut Self::M) {
-        self.op.statistics.borrow_mut().increment_matrix();
+        self.op.statistics.increment_matrix();
         if let Some(coloring) = self.op.coloring.as_ref() {
             coloring.jacobian_inplace(self, x, t, y);
         } else {
@@ -0,0 +1,213 @@
+use std::cell::RefCell;
+
+use crate::{
+    find_jacobian_non_zeros, jacobian::JacobianColoring, HermiteInterpolator, Matrix,
+    MatrixSparsity, NonLinearOp, NonLinearOpJacobian, Op, Vector,
+};
+
+use super::{BuilderOp, OpStatistics, ParameterisedOp};
+
+/// The right-hand side of a delay differential equation `f(t, y(t), y(t - tau_1), ..., y(t - tau_m))`, where the delays `tau_i(t, y)`
+/// can depend on both the time and the current state.
+///
+/// The delayed states are evaluated using the user-provided history function for times before the start of the solution,
+/// and using the accepted steps stored in [Self::history] otherwise. The delayed states are passed to the closures as a single
+/// vector `xlag` of length `m * n`, where `xlag[i * n + j]` is the `j`-th state at time `t - tau_i`.
+/// The Jacobian is only taken with respect to the current state `y(t)`.
+pub struct ClosureDelay<M, F, G, D, H>
+where
+    M: Matrix,
+    F: Fn(&M::V, &M::V, &M::V, M::T, &mut M::V),
+    G: Fn(&M::V, &M::V, &M::V, M::T, &M::V, &mut M::V),
+    D: Fn(&M::V, &M::V, M::T, &mut M::V),
+    H: Fn(&M::V, M::T) -> M::V,
+{
+    func: F,
+    jacobian_action: G,
+    delays: D,
+    history_fn: H,
+    history: RefCell<HermiteInterpolator<M::V>>,
+    xlag: RefCell<M::V>,
+    tau: RefCell<M::V>,
+    ndelays: usize,
+    nstates: usize,
+    nout: usize,
+    nparams: usize,
+    coloring: Option<JacobianColoring<M>>,
+    sparsity: Option<M::Sparsity>,
+    statistics: RefCell<OpStatistics>,
+}
+
+impl<M, F, G, D, H> ClosureDelay<M, F, G, D, H>
+where
+    M: Matrix,
+    F: Fn(&M::V, &M::V, &M::V, M::T, &mut M::V),
+    G: Fn(&M::V, &M::V, &M::V, M::T, &M::V, &mut M::V),
+    D: Fn(&M::V, &M::V, M::T, &mut M::V),
+    H: Fn(&M::V, M::T) -> M::V,
+{
+    #[allow(clippy::too_many_arguments)]
+    pub fn new(
+        func: F,
+        jacobian_action: G,
+        delays: D,
+        history_fn: H,
+        ndelays: usize,
+        nstates: usize,
+        nout: usize,
+        nparams: usize,
+    ) -> Self {
+        Self {
+            func,
+            jacobian_action,
+            delays,
+            history_fn,
+            history: RefCell::new(HermiteInterpolator::default()),
+            xlag: RefCell::new(M::V::zeros(ndelays * nstates)),
+            tau: RefCell::new(M::V::zeros(ndelays)),
+            ndelays,
+            nstates,
+            nout,
+            nparams,
+            statistics: RefCell::new(OpStatistics::default()),
+            coloring: None,
+            sparsity: None,
+        }
+    }
+
+    pub fn calculate_sparsity(&mut self, y0: &M::V, t0: M::T, p: &M::V) {
+        let param_op = ParameterisedOp { op: self, p };
+        let non_zeros = find_jacobian_non_zeros(&param_op, y0, t0);
+        self.sparsity = Some(
+            MatrixSparsity::try_from_indices(self.nout(), self.nstates(), non_zeros.clone())
+                .expect("invalid sparsity pattern"),
+        );
+        self.coloring = Some(JacobianColoring::new(
+            self.sparsity.as_ref().unwrap(),
+            &non_zeros,
+        ));
+    }
+
+    pub fn ndelays(&self) -> usize {
+        self.ndelays
+    }
+
+    /// The accepted steps of the solution, used to evaluate the delayed states. This is filled in by the delay solver (see [crate::Dde]).
+    pub fn history(&self) -> &RefCell<HermiteInterpolator<M::V>> {
+        &self.history
+    }
+
+    pub fn delays_inplace(&self, x: &M::V, p: &M::V, t: M::T, tau: &mut M::V) {
+        (self.delays)(x, p, t, tau)
+    }
+
+    /// Evaluate the solution at time `t`, using the history function if `t` is before the first accepted step
+    pub fn history_inplace(&self, p: &M::V, t: M::T, y: &mut M::V) {
+        if self.history.borrow().extrapolate(t, y).is_none() {
+            y.copy_from(&(self.history_fn)(p, t));
+        }
+    }
+
+    // fill in the delayed states for the current state x at time t
+    fn set_lagged(&self, x: &M::V, p: &M::V, t: M::T) {
+        let mut tau = self.tau.borrow_mut();
+        let mut xlag = self.xlag.borrow_mut();
+        (self.delays)(x, p, t, &mut tau);
+        let n = self.nstates;
+        let mut y = M::V::zeros(n);
+        for i in 0..self.ndelays {
+            self.history_inplace(p, t - tau[i], &mut y);
+            xlag.as_mut_slice()[i * n..(i + 1) * n].copy_from_slice(y.as_slice());
+        }
+    }
+}
+
+impl<M, F, G, D, H> BuilderOp for ClosureDelay<M, F, G, D, H>
+where
+    M: Matrix,
+    F: Fn(&M::V, &M::V, &M::V, M::T, &mut M::V),
+    G: Fn(&M::V, &M::V, &M::V, M::T, &M::V, &mut M::V),
+    D: Fn(&M::V, &M::V, M::T, &mut M::V),
+    H: Fn(&M::V, M::T) -> M::V,
+{
+    fn calculate_sparsity(&mut self, y0: &M::V, t0: M::T, p: &M::V) {
+        self.calculate_sparsity(y0, t0, p);
+    }
+
+    fn set_nstates(&mut self, nstates: usize) {
+        self.nstates = nstates;
+        self.xlag = RefCell::new(M::V::zeros(self.ndelays * nstates));
+    }
+    fn set_nout(&mut self, nout: usize) {
+        self.nout = nout;
+    }
+    fn set_nparams(&mut self, nparams: usize) {
+        self.nparams = nparams;
+    }
+}
+
+impl<M, F, G, D, H> Op for ClosureDelay<M, F, G, D, H>
+where
+    M: Matrix,
+    F: Fn(&M::V, &M::V, &M::V, M::T, &mut M::V),
+    G: Fn(&M::V, &M::V, &M::V, M::T, &M::V, &mut M::V),
+    D: Fn(&M::V, &M::V, M::T, &mut M::V),
+    H: Fn(&M::V, M::T) -> M::V,
+{
+    type V = M::V;
+    type T = M::T;
+    type M = M;
+    fn nstates(&self) -> usize {
+        self.nstates
+    }
+    fn nout(&self) -> usize {
+        self.nout
+    }
+    fn nparams(&self) -> usize {
+        self.nparams
+    }
+    fn statistics(&self) -> OpStatistics {
+        self.statistics.borrow().clone()
+    }
+}
+
+impl<M, F, G, D, H> NonLinearOp for ParameterisedOp<'_, ClosureDelay<M, F, G, D, H>>
+where
+    M: Matrix,
+    F: Fn(&M::V, &M::V, &M::V, M::T, &mut M::V),
+    G: Fn(&M::V, &M::V, &M::V, M::T, &M::V, &mut M::V),
+    D: Fn(&M::V, &M::V, M::T, &mut M::V),
+    H: Fn(&M::V, M::T) -> M::V,
+{
+    fn call_inplace(&self, x: &M::V, t: M::T, y: &mut M::V) {
+        self.op.statistics.borrow_mut().increment_call();
+        self.op.set_lagged(x, self.p, t);
+        (self.op.func)(x, &self.op.xlag.borrow(), self.p, t, y)
+    }
+}
+
+impl<M, F, G, D, H> NonLinearOpJacobian for ParameterisedOp<'_, ClosureDelay<M, F, G, D, H>>
+where
+    M: Matrix,
+    F: Fn(&M::V, &M::V, &M::V, M::T, &mut M::V),
+    G: Fn(&M::V, &M::V, &M::V, M::T, &M::V, &mut M::V),
+    D: Fn(&M::V, &M::V, M::T, &mut M::V),
+    H: Fn(&M::V, M::T) -> M::V,
+{
+    fn jac_mul_inplace(&self, x: &M::V, t: M::T, v: &M::V, y: &mut M::V) {
+        self.op.statistics.borrow_mut().increment_jac_mul();
+        self.op.set_lagged(x, self.p, t);
+        (self.op.jacobian_action)(x, &self.op.xlag.borrow(), self.p, t, v, y)
+    }
+    fn jacobian_inplace(&self, x: &Self::V, t: Self::T, y: &mut Self::M) {
+        self.op.statistics.borrow_mut().increment_matrix();
+        if let Some(coloring) = self.op.coloring.as_ref() {
+            coloring.jacobian_inplace(self, x, t, y);
+        } else {
+            self._default_jacobian_inplace(x, t, y);
+        }
+    }
+    fn jacobian_sparsity(&self) -> Option<<Self::M as Matrix>::Sparsity> {
+        self.op.sparsity.clone()
+    }
+}
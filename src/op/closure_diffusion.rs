@@ -0,0 +1,79 @@
+use std::{cell::RefCell, marker::PhantomData};
+
+use crate::{Matrix, NoiseType, Op};
+
+use super::OpStatistics;
+
+/// The diffusion term `G(t, y)` of a stochastic differential equation, given as a closure that computes the action `G(t, y) dw` on a vector of Wiener increments `dw`.
+///
+/// The shape of `G` is given by the [NoiseType]: for diagonal noise `G = diag(g(t, y))` and the closure should compute `g_i(t, y) dw_i`,
+/// otherwise `G` is a `nstates x nnoise` matrix.
+pub struct ClosureDiffusion<M, F>
+where
+    M: Matrix,
+    F: Fn(&M::V, &M::V, M::T, &M::V, &mut M::V),
+{
+    func: F,
+    noise: NoiseType,
+    nstates: usize,
+    nparams: usize,
+    statistics: RefCell<OpStatistics>,
+    _phantom: PhantomData<M>,
+}
+
+impl<M, F> ClosureDiffusion<M, F>
+where
+    M: Matrix,
+    F: Fn(&M::V, &M::V, M::T, &M::V, &mut M::V),
+{
+    pub fn new(func: F, noise: NoiseType, nstates: usize, nparams: usize) -> Self {
+        Self {
+            func,
+            noise,
+            nstates,
+            nparams,
+            statistics: RefCell::new(OpStatistics::default()),
+            _phantom: PhantomData,
+        }
+    }
+
+    pub fn noise(&self) -> NoiseType {
+        self.noise
+    }
+
+    /// the number of independent Wiener processes
+    pub fn nnoise(&self) -> usize {
+        match self.noise {
+            NoiseType::Diagonal => self.nstates,
+            NoiseType::General(nnoise) | NoiseType::Additive(nnoise) => nnoise,
+        }
+    }
+
+    /// computes `y = G(t, x) dw`
+    pub fn call_inplace(&self, x: &M::V, p: &M::V, t: M::T, dw: &M::V, y: &mut M::V) {
+        self.statistics.borrow_mut().increment_call();
+        (self.func)(x, p, t, dw, y)
+    }
+}
+
+impl<M, F> Op for ClosureDiffusion<M, F>
+where
+    M: Matrix,
+    F: Fn(&M::V, &M::V, M::T, &M::V, &mut M::V),
+{
+    type V = M::V;
+    type T = M::T;
+    type M = M;
+    fn nstates(&self) -> usize {
+        self.nstates
+    }
+    fn nout(&self) -> usize {
+        self.nstates
+    }
+    fn nparams(&self) -> usize {
+        self.nparams
+    }
+    fn statistics(&self) -> OpStatistics {
+        self.statistics.borrow().clone()
+    }
+}
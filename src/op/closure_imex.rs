@@ -0,0 +1,134 @@
+use num_traits::One;
+use std::cell::RefCell;
+
+use crate::{Closure, ClosureNoJac, Matrix, NonLinearOp, Op, Vector};
+
+use super::{BuilderOp, OpStatistics, ParameterisedOp};
+
+/// A right-hand side `F(x) = F_E(x) + F_I(x)` that is split into a non-stiff explicit part `F_E` (given without a Jacobian),
+/// and a stiff implicit part `F_I` (given with the action of its Jacobian).
+///
+/// The sum is a [NonLinearOp] without a Jacobian, the two parts are available separately via [crate::OdeEquationsImex].
+pub struct ClosureImex<M, F, G, H>
+where
+    M: Matrix,
+    F: Fn(&M::V, &M::V, M::T, &mut M::V),
+    G: Fn(&M::V, &M::V, M::T, &mut M::V),
+    H: Fn(&M::V, &M::V, M::T, &M::V, &mut M::V),
+{
+    explicit: ClosureNoJac<M, F>,
+    implicit: Closure<M, G, H>,
+    tmp: RefCell<M::V>,
+}
+
+impl<M, F, G, H> ClosureImex<M, F, G, H>
+where
+    M: Matrix,
+    F: Fn(&M::V, &M::V, M::T, &mut M::V),
+    G: Fn(&M::V, &M::V, M::T, &mut M::V),
+    H: Fn(&M::V, &M::V, M::T, &M::V, &mut M::V),
+{
+    pub fn new(
+        explicit_func: F,
+        implicit_func: G,
+        implicit_jacobian_action: H,
+        nstates: usize,
+        nout: usize,
+        nparams: usize,
+    ) -> Self {
+        Self {
+            explicit: ClosureNoJac::new(explicit_func, nstates, nout, nparams),
+            implicit: Closure::new(
+                implicit_func,
+                implicit_jacobian_action,
+                nstates,
+                nout,
+                nparams,
+            ),
+            tmp: RefCell::new(M::V::zeros(nout)),
+        }
+    }
+
+    pub fn explicit(&self) -> &ClosureNoJac<M, F> {
+        &self.explicit
+    }
+
+    pub fn implicit(&self) -> &Closure<M, G, H> {
+        &self.implicit
+    }
+}
+
+impl<M, F, G, H> BuilderOp for ClosureImex<M, F, G, H>
+where
+    M: Matrix,
+    F: Fn(&M::V, &M::V, M::T, &mut M::V),
+    G: Fn(&M::V, &M::V, M::T, &mut M::V),
+    H: Fn(&M::V, &M::V, M::T, &M::V, &mut M::V),
+{
+    fn calculate_sparsity(&mut self, y0: &M::V, t0: M::T, p: &M::V) {
+        // only the jacobian of the implicit part is ever used
+        self.implicit.calculate_sparsity(y0, t0, p);
+    }
+    fn set_nstates(&mut self, nstates: usize) {
+        self.explicit.set_nstates(nstates);
+        self.implicit.set_nstates(nstates);
+    }
+    fn set_nout(&mut self, nout: usize) {
+        self.explicit.set_nout(nout);
+        self.implicit.set_nout(nout);
+        self.tmp = RefCell::new(M::V::zeros(nout));
+    }
+    fn set_nparams(&mut self, nparams: usize) {
+        self.explicit.set_nparams(nparams);
+        self.implicit.set_nparams(nparams);
+    }
+}
+
+impl<M, F, G, H> Op for ClosureImex<M, F, G, H>
+where
+    M: Matrix,
+    F: Fn(&M::V, &M::V, M::T, &mut M::V),
+    G: Fn(&M::V, &M::V, M::T, &mut M::V),
+    H: Fn(&M::V, &M::V, M::T, &M::V, &mut M::V),
+{
+    type V = M::V;
+    type T = M::T;
+    type M = M;
+    fn nstates(&self) -> usize {
+        self.implicit.nstates()
+    }
+    fn nout(&self) -> usize {
+        self.implicit.nout()
+    }
+    fn nparams(&self) -> usize {
+        self.implicit.nparams()
+    }
+    fn statistics(&self) -> OpStatistics {
+        let explicit = self.explicit.statistics();
+        let implicit = self.implicit.statistics();
+        OpStatistics {
+            number_of_calls: explicit.number_of_calls + implicit.number_of_calls,
+            number_of_jac_muls: explicit.number_of_jac_muls + implicit.number_of_jac_muls,
+            number_of_matrix_evals: explicit.number_of_matrix_evals
+                + implicit.number_of_matrix_evals,
+            number_of_jac_adj_muls: explicit.number_of_jac_adj_muls
+                + implicit.number_of_jac_adj_muls,
+        }
+    }
+}
+
+impl<M, F, G, H> NonLinearOp for ParameterisedOp<'_, ClosureImex<M, F, G, H>>
+where
+    M: Matrix,
+    F: Fn(&M::V, &M::V, M::T, &mut M::V),
+    G: Fn(&M::V, &M::V, M::T, &mut M::V),
+    H: Fn(&M::V, &M::V, M::T, &M::V, &mut M::V),
+{
+    // y = F_E(x) + F_I(x)
+    fn call_inplace(&self, x: &M::V, t: M::T, y: &mut M::V) {
+        ParameterisedOp::new(&self.op.explicit, self.p).call_inplace(x, t, y);
+        let mut tmp = self.op.tmp.borrow_mut();
+        ParameterisedOp::new(&self.op.implicit, self.p).call_inplace(x, t, &mut *tmp);
+        y.axpy(M::T::one(), &*tmp, M::T::one());
+    }
+}
@@ -1,8 +1,6 @@
-use std::cell::RefCell;
-
 use crate::{Matrix, NonLinearOp, Op};
 
-use super::{BuilderOp, OpStatistics, ParameterisedOp};
+use super::{AtomicOpStatistics, BuilderOp, OpStatistics, ParameterisedOp};
 
 pub struct ClosureNoJac<M, F>
 where
@@ -13,7 +11,7 @@ where
     nstates: usize,
     nout: usize,
     nparams: usize,
-    statistics: RefCell<OpStatistics>,
+    statistics: AtomicOpStatistics,
     _phantom: std::marker::PhantomData<M>,
 }
 
@@ -28,7 +26,7 @@ where
             nstates,
             nparams,
             nout,
-            statistics: RefCell::new(OpStatistics::default()),
+            statistics: AtomicOpStatistics::new(),
             _phantom: std::marker::PhantomData,
         }
     }
@@ -71,7 +69,7 @@ where
         self.nparams
     }
     fn statistics(&self) -> OpStatistics {
-        self.statistics.borrow().clone()
+        self.statistics.get()
     }
 }
 
@@ -81,7 +79,7 @@ where
     F: Fn(&M::V, &M::V, M::T, &mut M::V),
 {
     fn call_inplace(&self, x: &M::V, t: M::T, y: &mut M::V) {
-        self.op.statistics.borrow_mut().increment_call();
+        self.op.statistics.increment_call();
         (self.op.func)(x, self.p, t, y)
     }
 }
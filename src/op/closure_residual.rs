@@ -0,0 +1,120 @@
+use std::cell::RefCell;
+
+use crate::{Matrix, OdeEquationsResidual, Op, Vector};
+
+use super::OpStatistics;
+
+/// Fully implicit equations in residual form `F(t, y, y') = 0`, given as closures for the residual, the Jacobian-vector products
+/// of the residual wrt `y` and `y'`, and the initial state (see [OdeEquationsResidual]).
+pub struct ClosureResidual<M, F, G, H, I>
+where
+    M: Matrix,
+    F: Fn(&M::V, &M::V, &M::V, M::T, &mut M::V),
+    G: Fn(&M::V, &M::V, &M::V, M::T, &M::V, &mut M::V),
+    H: Fn(&M::V, &M::V, &M::V, M::T, &M::V, &mut M::V),
+    I: Fn(&M::V, M::T) -> M::V,
+{
+    residual: F,
+    residual_jac: G,
+    residual_jac_dot: H,
+    init: I,
+    p: M::V,
+    algebraic_indices: <M::V as Vector>::Index,
+    nstates: usize,
+    statistics: RefCell<OpStatistics>,
+}
+
+impl<M, F, G, H, I> ClosureResidual<M, F, G, H, I>
+where
+    M: Matrix,
+    F: Fn(&M::V, &M::V, &M::V, M::T, &mut M::V),
+    G: Fn(&M::V, &M::V, &M::V, M::T, &M::V, &mut M::V),
+    H: Fn(&M::V, &M::V, &M::V, M::T, &M::V, &mut M::V),
+    I: Fn(&M::V, M::T) -> M::V,
+{
+    pub fn new(
+        residual: F,
+        residual_jac: G,
+        residual_jac_dot: H,
+        init: I,
+        algebraic_indices: <M::V as Vector>::Index,
+        nstates: usize,
+        p: M::V,
+    ) -> Self {
+        Self {
+            residual,
+            residual_jac,
+            residual_jac_dot,
+            init,
+            p,
+            algebraic_indices,
+            nstates,
+            statistics: RefCell::new(OpStatistics::default()),
+        }
+    }
+
+    pub fn params(&self) -> &M::V {
+        &self.p
+    }
+}
+
+impl<M, F, G, H, I> Op for ClosureResidual<M, F, G, H, I>
+where
+    M: Matrix,
+    F: Fn(&M::V, &M::V, &M::V, M::T, &mut M::V),
+    G: Fn(&M::V, &M::V, &M::V, M::T, &M::V, &mut M::V),
+    H: Fn(&M::V, &M::V, &M::V, M::T, &M::V, &mut M::V),
+    I: Fn(&M::V, M::T) -> M::V,
+{
+    type V = M::V;
+    type T = M::T;
+    type M = M;
+    fn nstates(&self) -> usize {
+        self.nstates
+    }
+    fn nout(&self) -> usize {
+        self.nstates
+    }
+    fn nparams(&self) -> usize {
+        self.p.len()
+    }
+    fn statistics(&self) -> OpStatistics {
+        self.statistics.borrow().clone()
+    }
+}
+
+impl<M, F, G, H, I> OdeEquationsResidual for ClosureResidual<M, F, G, H, I>
+where
+    M: Matrix,
+    F: Fn(&M::V, &M::V, &M::V, M::T, &mut M::V),
+    G: Fn(&M::V, &M::V, &M::V, M::T, &M::V, &mut M::V),
+    H: Fn(&M::V, &M::V, &M::V, M::T, &M::V, &mut M::V),
+    I: Fn(&M::V, M::T) -> M::V,
+{
+    fn residual_inplace(&self, y: &M::V, dy: &M::V, t: M::T, r: &mut M::V) {
+        self.statistics.borrow_mut().increment_call();
+        (self.residual)(y, dy, &self.p, t, r)
+    }
+
+    fn residual_jac_mul_inplace(&self, y: &M::V, dy: &M::V, t: M::T, v: &M::V, r: &mut M::V) {
+        self.statistics.borrow_mut().increment_jac_mul();
+        (self.residual_jac)(y, dy, &self.p, t, v, r)
+    }
+
+    fn residual_jac_dot_mul_inplace(&self, y: &M::V, dy: &M::V, t: M::T, v: &M::V, r: &mut M::V) {
+        self.statistics.borrow_mut().increment_jac_mul();
+        (self.residual_jac_dot)(y, dy, &self.p, t, v, r)
+    }
+
+    fn init_inplace(&self, t: M::T, y: &mut M::V) {
+        y.copy_from(&(self.init)(&self.p, t));
+    }
+
+    fn algebraic_indices(&self) -> <M::V as Vector>::Index {
+        self.algebraic_indices.clone()
+    }
+
+    fn set_params(&mut self, p: &M::V) {
+        self.p.copy_from(p);
+    }
+}
@@ -0,0 +1,170 @@
+use std::cell::RefCell;
+
+use crate::{
+    find_jacobian_non_zeros, jacobian::JacobianColoring, Closure, Matrix, MatrixSparsity,
+    NonLinearOp, NonLinearOpJacobian, Op, Vector,
+};
+
+use super::{BuilderOp, OpStatistics, ParameterisedOp};
+
+/// The right-hand side of a second-order ODE `M y'' = f(t, y, y')`, written in first-order form for the state `x = [y, v]` (with `v = y'`), i.e.
+///
+/// ```text
+/// dy/dt = v
+/// M dv/dt = f(t, y, v)
+/// ```
+///
+/// The force `f` is given as a closure of the full state `x = [y, v]` that returns a vector of half the length of the state, along with the action of its Jacobian
+/// with respect to the full state. The first-order form is a [NonLinearOpJacobian], so the problem can be solved by any of the solvers,
+/// and the force is available separately via [crate::OdeEquationsSecondOrder] for the second-order solvers.
+pub struct ClosureSecondOrder<M, F, G>
+where
+    M: Matrix,
+    F: Fn(&M::V, &M::V, M::T, &mut M::V),
+    G: Fn(&M::V, &M::V, M::T, &M::V, &mut M::V),
+{
+    force: Closure<M, F, G>,
+    tmp: RefCell<M::V>,
+    coloring: Option<JacobianColoring<M>>,
+    sparsity: Option<M::Sparsity>,
+    statistics: RefCell<OpStatistics>,
+}
+
+impl<M, F, G> ClosureSecondOrder<M, F, G>
+where
+    M: Matrix,
+    F: Fn(&M::V, &M::V, M::T, &mut M::V),
+    G: Fn(&M::V, &M::V, M::T, &M::V, &mut M::V),
+{
+    pub fn new(force: F, force_jacobian_action: G, nstates: usize, nparams: usize) -> Self {
+        let npositions = nstates / 2;
+        Self {
+            force: Closure::new(force, force_jacobian_action, nstates, npositions, nparams),
+            tmp: RefCell::new(M::V::zeros(npositions)),
+            coloring: None,
+            sparsity: None,
+            statistics: RefCell::new(OpStatistics::default()),
+        }
+    }
+
+    pub fn force(&self) -> &Closure<M, F, G> {
+        &self.force
+    }
+
+    /// the number of positions `y`, which is half the number of states
+    pub fn npositions(&self) -> usize {
+        self.force.nout()
+    }
+
+    pub fn calculate_sparsity(&mut self, y0: &M::V, t0: M::T, p: &M::V) {
+        let param_op = ParameterisedOp { op: self, p };
+        let non_zeros = find_jacobian_non_zeros(&param_op, y0, t0);
+        self.sparsity = Some(
+            MatrixSparsity::try_from_indices(self.nout(), self.nstates(), non_zeros.clone())
+                .expect("invalid sparsity pattern"),
+        );
+        self.coloring = Some(JacobianColoring::new(
+            self.sparsity.as_ref().unwrap(),
+            &non_zeros,
+        ));
+    }
+}
+
+impl<M, F, G> BuilderOp for ClosureSecondOrder<M, F, G>
+where
+    M: Matrix,
+    F: Fn(&M::V, &M::V, M::T, &mut M::V),
+    G: Fn(&M::V, &M::V, M::T, &M::V, &mut M::V),
+{
+    fn calculate_sparsity(&mut self, y0: &M::V, t0: M::T, p: &M::V) {
+        self.calculate_sparsity(y0, t0, p);
+    }
+    fn set_nstates(&mut self, nstates: usize) {
+        assert_eq!(
+            nstates % 2,
+            0,
+            "The state of a second-order ODE must contain the positions followed by the velocities, expected an even number of states, got {}",
+            nstates
+        );
+        self.force.set_nstates(nstates);
+        self.force.set_nout(nstates / 2);
+        self.tmp = RefCell::new(M::V::zeros(nstates / 2));
+    }
+    fn set_nout(&mut self, _nout: usize) {
+        // the first-order form always has the same number of outputs as states,
+        // and the number of outputs of the force is set with the number of states
+    }
+    fn set_nparams(&mut self, nparams: usize) {
+        self.force.set_nparams(nparams);
+    }
+}
+
+impl<M, F, G> Op for ClosureSecondOrder<M, F, G>
+where
+    M: Matrix,
+    F: Fn(&M::V, &M::V, M::T, &mut M::V),
+    G: Fn(&M::V, &M::V, M::T, &M::V, &mut M::V),
+{
+    type V = M::V;
+    type T = M::T;
+    type M = M;
+    fn nstates(&self) -> usize {
+        self.force.nstates()
+    }
+    fn nout(&self) -> usize {
+        self.force.nstates()
+    }
+    fn nparams(&self) -> usize {
+        self.force.nparams()
+    }
+    fn statistics(&self) -> OpStatistics {
+        let mut statistics = self.force.statistics();
+        statistics.number_of_matrix_evals += self.statistics.borrow().number_of_matrix_evals;
+        statistics
+    }
+}
+
+impl<M, F, G> NonLinearOp for ParameterisedOp<'_, ClosureSecondOrder<M, F, G>>
+where
+    M: Matrix,
+    F: Fn(&M::V, &M::V, M::T, &mut M::V),
+    G: Fn(&M::V, &M::V, M::T, &M::V, &mut M::V),
+{
+    // y = [v, f(t, x)]
+    fn call_inplace(&self, x: &M::V, t: M::T, y: &mut M::V) {
+        let n = self.op.npositions();
+        let mut tmp = self.op.tmp.borrow_mut();
+        ParameterisedOp::new(&self.op.force, self.p).call_inplace(x, t, &mut *tmp);
+        let y = y.as_mut_slice();
+        y[..n].copy_from_slice(&x.as_slice()[n..]);
+        y[n..].copy_from_slice(tmp.as_slice());
+    }
+}
+
+impl<M, F, G> NonLinearOpJacobian for ParameterisedOp<'_, ClosureSecondOrder<M, F, G>>
+where
+    M: Matrix,
+    F: Fn(&M::V, &M::V, M::T, &mut M::V),
+    G: Fn(&M::V, &M::V, M::T, &M::V, &mut M::V),
+{
+    // y = [dv, f'(t, x) [dy, dv]]
+    fn jac_mul_inplace(&self, x: &M::V, t: M::T, v: &M::V, y: &mut M::V) {
+        let n = self.op.npositions();
+        let mut tmp = self.op.tmp.borrow_mut();
+        ParameterisedOp::new(&self.op.force, self.p).jac_mul_inplace(x, t, v, &mut *tmp);
+        let y = y.as_mut_slice();
+        y[..n].copy_from_slice(&v.as_slice()[n..]);
+        y[n..].copy_from_slice(tmp.as_slice());
+    }
+    fn jacobian_inplace(&self, x: &Self::V, t: Self::T, y: &mut Self::M) {
+        self.op.statistics.borrow_mut().increment_matrix();
+        if let Some(coloring) = self.op.coloring.as_ref() {
+            coloring.jacobian_inplace(self, x, t, y);
+        } else {
+            self._default_jacobian_inplace(x, t, y);
+        }
+    }
+    fn jacobian_sparsity(&self) -> Option<<Self::M as Matrix>::Sparsity> {
+        self.op.sparsity.clone()
+    }
+}
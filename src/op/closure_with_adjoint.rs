@@ -1,5 +1,3 @@
-use std::cell::RefCell;
-
 use crate::{
     jacobian::{
         find_adjoint_non_zeros, find_jacobian_non_zeros, find_sens_adjoint_non_zeros,
@@ -9,7 +7,7 @@ use crate::{
     NonLinearOpSensAdjoint, Op, Vector,
 };
 
-use super::{BuilderOp, OpStatistics, ParameterisedOp};
+use super::{AtomicOpStatistics, BuilderOp, OpStatistics, ParameterisedOp};
 
 #[derive(Clone)]
 pub struct ClosureWithAdjoint<M, F, G, H, I>
@@ -33,7 +31,7 @@ where
     coloring_adjoint: Option<JacobianColoring<M>>,
     sens_sparsity: Option<M::Sparsity>,
     coloring_sens_adjoint: Option<JacobianColoring<M>>,
-    statistics: RefCell<OpStatistics>,
+    statistics: AtomicOpStatistics,
 }
 
 impl<M, F, G, H, I> ClosureWithAdjoint<M, F, G, H, I>
@@ -61,7 +59,7 @@ where
             nstates,
             nout,
             nparams,
-            statistics: RefCell::new(OpStatistics::default()),
+            statistics: AtomicOpStatistics::new(),
             coloring: None,
             sparsity: None,
             sparsity_adjoint: None,
@@ -133,7 +131,7 @@ where
         self.nparams
     }
     fn statistics(&self) -> OpStatistics {
-        self.statistics.borrow().clone()
+        self.statistics.get()
     }
 }
 
@@ -170,7 +168,7 @@ where
     I: Fn(&M::V, &M::V, M::T, &M::V, &mut M::V),
 {
     fn call_inplace(&self, x: &M::V, t: M::T, y: &mut M::V) {
-        self.op.statistics.borrow_mut().increment_call();
+        self.op.statistics.increment_call();
         (self.op.func)(x, self.p, t, y)
     }
 }
@@ -184,11 +182,11 @@ where
     I: Fn(&M::V, &M::V, M::T, &M::V, &mut M::V),
 {
     fn jac_mul_inplace(&self, x: &M::V, t: M::T, v: &M::V, y: &mut M::V) {
-        self.op.statistics.borrow_mut().increment_jac_mul();
+        self.op.statistics.increment_jac_mul();
         (self.op.jacobian_action)(x, self.p, t, v, y)
     }
     fn jacobian_inplace(&self, x: &Self::V, t: Self::T, y: &mut Self::M) {
-        self.op.statistics.borrow_mut().increment_matrix();
+        self.op.statistics.increment_matrix();
         if let Some(coloring) = self.op.coloring.as_ref() {
             coloring.jacobian_inplace(self, x, t, y);
         } else {
@@ -209,7 +207,7 @@ where
     I: Fn(&M::V, &M::V, M::T, &M::V, &mut M::V),
 {
     fn jac_transpose_mul_inplace(&self, x: &Self::V, t: Self::T, v: &Self::V, y: &mut Self::V) {
-        self.op.statistics.borrow_mut().increment_jac_adj_mul();
+        self.op.statistics.increment_jac_adj_mul();
         (self.op.jacobian_adjoint_action)(x, self.p, t, v, y);
     }
 
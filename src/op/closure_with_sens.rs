@@ -1,11 +1,9 @@
-use std::cell::RefCell;
-
 use crate::{
     jacobian::{find_jacobian_non_zeros, find_sens_non_zeros, JacobianColoring},
     Matrix, MatrixSparsity, NonLinearOp, NonLinearOpJacobian, NonLinearOpSens, Op, Vector,
 };
 
-use super::{BuilderOp, OpStatistics, ParameterisedOp};
+use super::{AtomicOpStatistics, BuilderOp, OpStatistics, ParameterisedOp};
 
 pub struct ClosureWithSens<M, F, G, H>
 where
@@ -21,7 +19,7 @@ where
     sens_coloring: Option<JacobianColoring<M>>,
     sparsity: Option<M::Sparsity>,
     sens_sparsity: Option<M::Sparsity>,
-    statistics: RefCell<OpStatistics>,
+    statistics: AtomicOpStatistics,
 }
 
 impl<M, F, G, H> ClosureWithSens<M, F, G, H>
@@ -46,7 +44,7 @@ where
             nstates,
             nout,
             nparams,
-            statistics: RefCell::new(OpStatistics::default()),
+            statistics: AtomicOpStatistics::new(),
             coloring: None,
             sparsity: None,
             sens_coloring: None,
@@ -121,7 +119,7 @@ where
         self.nparams
     }
     fn statistics(&self) -> OpStatistics {
-        self.statistics.borrow().clone()
+        self.statistics.get()
     }
 }
 
@@ -133,7 +131,7 @@ where
     H: Fn(&M::V, &M::V, M::T, &M::V, &mut M::V),
 {
     fn call_inplace(&self, x: &M::V, t: M::T, y: &mut M::V) {
-        self.op.statistics.borrow_mut().increment_call();
+        self.op.statistics.increment_call();
         (self.op.func)(x, self.p, t, y)
     }
 }
@@ -146,11 +144,11 @@ where
     H: Fn(&M::V, &M::V, M::T, &M::V, &mut M::V),
 {
     fn jac_mul_inplace(&self, x: &M::V, t: M::T, v: &M::V, y: &mut M::V) {
-        self.op.statistics.borrow_mut().increment_jac_mul();
+        self.op.statistics.increment_jac_mul();
         (self.op.jacobian_action)(x, self.p, t, v, y)
     }
     fn jacobian_inplace(&self, x: &Self::V, t: Self::T, y: &mut Self::M) {
-        self.op.statistics.borrow_mut().increment_matrix();
+        self.op.statistics.increment_matrix();
         if let Some(coloring) = self.op.coloring.as_ref() {
             coloring.jacobian_inplace(self, x, t, y);
         } else {
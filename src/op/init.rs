@@ -1,9 +1,9 @@
 use crate::{
-    scale, LinearOp, Matrix, MatrixSparsityRef, NonLinearOpJacobian, OdeEquationsImplicit, Vector,
-    VectorIndex,
+    scale, LinearOp, Matrix, MatrixSparsityRef, NonLinearOpJacobian, OdeEquationsImplicit,
+    OdeEquationsResidual, Vector, VectorIndex,
 };
 use num_traits::{One, Zero};
-use std::cell::RefCell;
+use std::cell::{Cell, RefCell};
 
 use super::{NonLinearOp, Op};
 
@@ -13,8 +13,12 @@ use super::{NonLinearOp, Op};
 /// Brown, P. N., Hindmarsh, A. C., & Petzold, L. R. (1998). Consistent initial condition calculation for differential-algebraic systems. SIAM Journal on Scientific Computing, 19(5), 1495-1512.
 pub struct InitOp<'a, Eqn: OdeEquationsImplicit> {
     eqn: &'a Eqn,
+    // jacobian at the initial guess, used for its sparsity pattern and the first linearisation
     jac: Eqn::M,
+    is_first_linearisation: Cell<bool>,
+    m_u: Eqn::M,
     pub y0: RefCell<Eqn::V>,
+    tmp: RefCell<Eqn::V>,
     pub algebraic_indices: <Eqn::V as Vector>::Index,
     neg_mass: Eqn::M,
 }
@@ -24,8 +28,6 @@ impl<'a, Eqn: OdeEquationsImplicit> InitOp<'a, Eqn> {
         let n = eqn.rhs().nstates();
         let mass_diagonal = eqn.mass().unwrap().matrix(t0).diagonal();
         let algebraic_indices = mass_diagonal.filter_indices(|x| x == Eqn::T::zero());
-
-        let rhs_jac = eqn.rhs().jacobian(y0, t0);
         let mass = eqn.mass().unwrap().matrix(t0);
 
         // equations are:
@@ -36,32 +38,49 @@ impl<'a, Eqn: OdeEquationsImplicit> InitOp<'a, Eqn> {
         // want to solve for du, v, so jacobian is
         // J = (-M_u, df/dv)
         //     (0,    dg/dv)
-        // note rhs_jac = (df/du df/dv)
-        //                (dg/du dg/dv)
-        // according to the algebraic indices.
         let (m_u, _, _, _) = mass.split_at_indices(&algebraic_indices);
         let m_u = m_u * scale(-Eqn::T::one());
-        let (_, dfdv, _, dgdv) = rhs_jac.split_at_indices(&algebraic_indices);
         let zero_ll =
             <Eqn::M as Matrix>::zeros(algebraic_indices.len(), n - algebraic_indices.len());
         let zero_ur =
             <Eqn::M as Matrix>::zeros(n - algebraic_indices.len(), algebraic_indices.len());
         let zero_lr = <Eqn::M as Matrix>::zeros(algebraic_indices.len(), algebraic_indices.len());
-        let jac = Eqn::M::combine_at_indices(&m_u, &dfdv, &zero_ll, &dgdv, &algebraic_indices);
         let neg_mass =
             Eqn::M::combine_at_indices(&m_u, &zero_ur, &zero_ll, &zero_lr, &algebraic_indices);
+        let jac = Self::calculate_jacobian(eqn, &m_u, y0, t0, &algebraic_indices);
 
-        let y0 = y0.clone();
-        let y0 = RefCell::new(y0);
+        let y0 = RefCell::new(y0.clone());
+        let tmp = RefCell::new(Eqn::V::zeros(n));
         Self {
             eqn,
             jac,
+            is_first_linearisation: Cell::new(true),
+            m_u,
             y0,
+            tmp,
             neg_mass,
             algebraic_indices,
         }
     }
 
+    // note rhs_jac = (df/du df/dv)
+    //                (dg/du dg/dv)
+    // according to the algebraic indices.
+    fn calculate_jacobian(
+        eqn: &Eqn,
+        m_u: &Eqn::M,
+        y0: &Eqn::V,
+        t: Eqn::T,
+        algebraic_indices: &<Eqn::V as Vector>::Index,
+    ) -> Eqn::M {
+        let n = eqn.rhs().nstates();
+        let rhs_jac = eqn.rhs().jacobian(y0, t);
+        let (_, dfdv, _, dgdv) = rhs_jac.split_at_indices(algebraic_indices);
+        let zero_ll =
+            <Eqn::M as Matrix>::zeros(algebraic_indices.len(), n - algebraic_indices.len());
+        Eqn::M::combine_at_indices(m_u, &dfdv, &zero_ll, &dgdv, algebraic_indices)
+    }
+
     pub fn scatter_soln(&self, soln: &Eqn::V, y: &mut Eqn::V, dy: &mut Eqn::V) {
         let tmp = dy.clone();
         dy.copy_from(soln);
@@ -103,14 +122,29 @@ impl<Eqn: OdeEquationsImplicit> NonLinearOp for InitOp<'_, Eqn> {
 }
 
 impl<Eqn: OdeEquationsImplicit> NonLinearOpJacobian for InitOp<'_, Eqn> {
-    // J v
-    fn jac_mul_inplace(&self, _x: &Eqn::V, _t: Eqn::T, v: &Eqn::V, y: &mut Eqn::V) {
-        self.jac.gemv(Eqn::T::one(), v, Eqn::T::one(), y);
+    // J v = -M_u v_u + df/dv v_v
+    //             dg/dv v_v
+    // the jacobian is evaluated at the algebraic states in x, so that it is updated as the iteration progresses
+    fn jac_mul_inplace(&self, x: &Eqn::V, t: Eqn::T, v: &Eqn::V, y: &mut Eqn::V) {
+        let mut y0 = self.y0.borrow_mut();
+        y0.copy_from_indices(x, &self.algebraic_indices);
+        let mut tmp = self.tmp.borrow_mut();
+        tmp.fill(Eqn::T::zero());
+        tmp.copy_from_indices(v, &self.algebraic_indices);
+        self.eqn.rhs().jac_mul_inplace(&y0, t, &tmp, y);
+        self.neg_mass.gemv(Eqn::T::one(), v, Eqn::T::one(), y);
     }
 
-    // M - c * f'(y)
-    fn jacobian_inplace(&self, _x: &Self::V, _t: Self::T, y: &mut Self::M) {
-        y.copy_from(&self.jac);
+    // the first linearisation uses the jacobian at the initial guess, later ones (e.g. by a line search solver) re-evaluate it at x
+    fn jacobian_inplace(&self, x: &Self::V, t: Self::T, y: &mut Self::M) {
+        if self.is_first_linearisation.replace(false) {
+            y.copy_from(&self.jac);
+            return;
+        }
+        let mut y0 = self.y0.borrow_mut();
+        y0.copy_from_indices(x, &self.algebraic_indices);
+        let jac = Self::calculate_jacobian(self.eqn, &self.m_u, &y0, t, &self.algebraic_indices);
+        y.copy_from(&jac);
     }
 
     fn jacobian_sparsity(&self) -> Option<<Self::M as Matrix>::Sparsity> {
@@ -118,11 +152,108 @@ impl<Eqn: OdeEquationsImplicit> NonLinearOpJacobian for InitOp<'_, Eqn> {
     }
 }
 
+/// Consistent initial conditions for a fully implicit DAE system `F(t, y, y') = 0`.
+///
+/// This generalises [InitOp] to equations in residual form, again following Brown, Hindmarsh & Petzold (1998):
+/// given the differential states `u` and a guess for the algebraic states `v`, we solve `F(t, u, v, du, dv) = 0` for `du` and `v`,
+/// keeping `u` and `dv` fixed. The unknown vector `x` holds `du` at the differential indices and `v` at the algebraic indices.
+pub struct ResidualInitOp<'a, Eqn: OdeEquationsResidual> {
+    eqn: &'a Eqn,
+    pub y0: RefCell<Eqn::V>,
+    pub dy0: RefCell<Eqn::V>,
+    pub algebraic_indices: <Eqn::V as Vector>::Index,
+    differential_indices: <Eqn::V as Vector>::Index,
+    tmp: RefCell<Eqn::V>,
+    tmp_dot: RefCell<Eqn::V>,
+}
+
+impl<'a, Eqn: OdeEquationsResidual> ResidualInitOp<'a, Eqn> {
+    pub fn new(eqn: &'a Eqn, y0: &Eqn::V, dy0: &Eqn::V) -> Self {
+        let n = eqn.nstates();
+        let algebraic_indices = eqn.algebraic_indices();
+        let mut is_differential = Eqn::V::from_element(n, Eqn::T::one());
+        is_differential.assign_at_indices(&algebraic_indices, Eqn::T::zero());
+        let differential_indices = is_differential.filter_indices(|x| x == Eqn::T::one());
+        Self {
+            eqn,
+            y0: RefCell::new(y0.clone()),
+            dy0: RefCell::new(dy0.clone()),
+            algebraic_indices,
+            differential_indices,
+            tmp: RefCell::new(Eqn::V::zeros(n)),
+            tmp_dot: RefCell::new(Eqn::V::zeros(n)),
+        }
+    }
+
+    /// returns the unknowns `x = (du, v)` corresponding to the current `y0` and `dy0`, to be used as the initial guess
+    pub fn initial_guess(&self) -> Eqn::V {
+        let mut x = self.dy0.borrow().clone();
+        x.copy_from_indices(&self.y0.borrow(), &self.algebraic_indices);
+        x
+    }
+
+    pub fn scatter_soln(&self, soln: &Eqn::V, y: &mut Eqn::V, dy: &mut Eqn::V) {
+        dy.copy_from_indices(soln, &self.differential_indices);
+        y.copy_from_indices(soln, &self.algebraic_indices);
+    }
+}
+
+impl<Eqn: OdeEquationsResidual> Op for ResidualInitOp<'_, Eqn> {
+    type V = Eqn::V;
+    type T = Eqn::T;
+    type M = Eqn::M;
+    fn nstates(&self) -> usize {
+        self.eqn.nstates()
+    }
+    fn nout(&self) -> usize {
+        self.eqn.nout()
+    }
+    fn nparams(&self) -> usize {
+        self.eqn.nparams()
+    }
+}
+
+impl<Eqn: OdeEquationsResidual> NonLinearOp for ResidualInitOp<'_, Eqn> {
+    // F(t, u, v, du, dv)
+    fn call_inplace(&self, x: &Eqn::V, t: Eqn::T, y: &mut Eqn::V) {
+        let mut y0 = self.y0.borrow_mut();
+        let mut dy0 = self.dy0.borrow_mut();
+        self.scatter_soln(x, &mut y0, &mut dy0);
+        self.eqn.residual_inplace(&y0, &dy0, t, y);
+    }
+}
+
+impl<Eqn: OdeEquationsResidual> NonLinearOpJacobian for ResidualInitOp<'_, Eqn> {
+    // J v = dF/d(du) v_u + dF/dv v_v
+    fn jac_mul_inplace(&self, x: &Eqn::V, t: Eqn::T, v: &Eqn::V, y: &mut Eqn::V) {
+        let mut y0 = self.y0.borrow_mut();
+        let mut dy0 = self.dy0.borrow_mut();
+        self.scatter_soln(x, &mut y0, &mut dy0);
+
+        let mut tmp = self.tmp.borrow_mut();
+        tmp.fill(Eqn::T::zero());
+        tmp.copy_from_indices(v, &self.differential_indices);
+        self.eqn.residual_jac_dot_mul_inplace(&y0, &dy0, t, &tmp, y);
+
+        tmp.fill(Eqn::T::zero());
+        tmp.copy_from_indices(v, &self.algebraic_indices);
+        let mut tmp_dot = self.tmp_dot.borrow_mut();
+        self.eqn
+            .residual_jac_mul_inplace(&y0, &dy0, t, &tmp, &mut tmp_dot);
+        y.axpy(Eqn::T::one(), &tmp_dot, Eqn::T::one());
+    }
+
+    fn jacobian_sparsity(&self) -> Option<<Self::M as Matrix>::Sparsity> {
+        self.eqn.residual_jacobian_sparsity()
+    }
+}
+
 #[cfg(test)]
 mod tests {
 
+    use crate::ode_solver::test_models::exponential_decay_residual::exponential_decay_residual_problem;
     use crate::ode_solver::test_models::exponential_decay_with_algebraic::exponential_decay_with_algebraic_problem;
-    use crate::op::init::InitOp;
+    use crate::op::init::{InitOp, ResidualInitOp};
     use crate::vector::Vector;
     use crate::{NonLinearOp, NonLinearOpJacobian};
 
@@ -180,5 +311,52 @@ mod tests {
         assert_eq!(jac[(2, 0)], 0.0);
         assert_eq!(jac[(2, 1)], 0.0);
         assert_eq!(jac[(2, 2)], 1.0);
+
+        // J v = |-1|
+        //       |-2|
+        //       |3|
+        let v = Vcpu::from_vec(vec![1.0, 2.0, 3.0]);
+        let jv = initop.jac_mul(&du_v, t, &v);
+        jv.assert_eq_st(&Vcpu::from_vec(vec![-1.0, -2.0, 3.0]), 1e-10);
+    }
+
+    #[test]
+    fn test_residual_initop() {
+        let (problem, _soln) = exponential_decay_residual_problem::<Mcpu>();
+        let y0 = Vcpu::from_vec(vec![1.0, 3.0]);
+        let dy0 = Vcpu::from_vec(vec![4.0, 6.0]);
+        let t = 0.0;
+        let initop = ResidualInitOp::new(&problem.eqn, &y0, &dy0);
+
+        // y = |1| (u)
+        //     |3| (v)
+        // dy = |4| (du)
+        //      |6| (dv)
+        // x = (du, v) = |4|
+        //               |3|
+        let x = initop.initial_guess();
+        x.assert_eq_st(&Vcpu::from_vec(vec![4.0, 3.0]), 1e-10);
+
+        // F = |du + du^3 + 0.1 u + 0.001 u^3| = |4 + 64 + 0.1 + 0.001| = |68.101|
+        //     |v - u                        |   |3 - 1                |   |2     |
+        let mut y_out = Vcpu::from_vec(vec![0.0, 0.0]);
+        initop.call_inplace(&x, t, &mut y_out);
+        y_out.assert_eq_st(&Vcpu::from_vec(vec![68.101, 2.0]), 1e-10);
+
+        // J = (dF/d(du), dF/dv) = |1 + 3 du^2 0| = |49 0|
+        //                         |0          1|   |0  1|
+        let jac = initop.jacobian(&x, t);
+        assert_eq!(jac[(0, 0)], 49.0);
+        assert_eq!(jac[(0, 1)], 0.0);
+        assert_eq!(jac[(1, 0)], 0.0);
+        assert_eq!(jac[(1, 1)], 1.0);
+
+        // scatter the solution back into the state and derivative
+        let mut y = y0.clone();
+        let mut dy = dy0.clone();
+        let soln = Vcpu::from_vec(vec![-0.1, 1.0]);
+        initop.scatter_soln(&soln, &mut y, &mut dy);
+        y.assert_eq_st(&Vcpu::from_vec(vec![1.0, 1.0]), 1e-10);
+        dy.assert_eq_st(&Vcpu::from_vec(vec![-0.1, 6.0]), 1e-10);
     }
 }
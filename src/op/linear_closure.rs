@@ -1,11 +1,9 @@
-use std::cell::RefCell;
-
 use crate::{
     find_matrix_non_zeros, jacobian::JacobianColoring, matrix::sparsity::MatrixSparsity, LinearOp,
     Matrix, Op,
 };
 
-use super::{BuilderOp, OpStatistics, ParameterisedOp};
+use super::{AtomicOpStatistics, BuilderOp, OpStatistics, ParameterisedOp};
 
 pub struct LinearClosure<M, F>
 where
@@ -18,7 +16,7 @@ where
     nparams: usize,
     coloring: Option<JacobianColoring<M>>,
     sparsity: Option<M::Sparsity>,
-    statistics: RefCell<OpStatistics>,
+    statistics: AtomicOpStatistics,
 }
 
 impl<M, F> LinearClosure<M, F>
@@ -30,7 +28,7 @@ where
         Self {
             func,
             nstates,
-            statistics: RefCell::new(OpStatistics::default()),
+            statistics: AtomicOpStatistics::new(),
             nout,
             nparams,
             coloring: None,
@@ -71,7 +69,7 @@ where
     }
 
     fn statistics(&self) -> OpStatistics {
-        self.statistics.borrow().clone()
+        self.statistics.get()
     }
 }
 
@@ -100,12 +98,12 @@ where
     F: Fn(&M::V, &M::V, M::T, M::T, &mut M::V),
 {
     fn gemv_inplace(&self, x: &M::V, t: M::T, beta: M::T, y: &mut M::V) {
-        self.op.statistics.borrow_mut().increment_call();
+        self.op.statistics.increment_call();
         (self.op.func)(x, self.p, t, beta, y)
     }
 
     fn matrix_inplace(&self, t: Self::T, y: &mut Self::M) {
-        self.op.statistics.borrow_mut().increment_matrix();
+        self.op.statistics.increment_matrix();
         if let Some(coloring) = &self.op.coloring {
             coloring.matrix_inplace(self, t, y);
         } else {
@@ -1,11 +1,9 @@
-use std::cell::RefCell;
-
 use crate::{
     find_matrix_non_zeros, find_transpose_non_zeros, jacobian::JacobianColoring,
     matrix::sparsity::MatrixSparsity, LinearOp, LinearOpTranspose, Matrix, Op,
 };
 
-use super::{BuilderOp, OpStatistics, ParameterisedOp};
+use super::{AtomicOpStatistics, BuilderOp, OpStatistics, ParameterisedOp};
 
 pub struct LinearClosureWithAdjoint<M, F, G>
 where
@@ -22,7 +20,7 @@ where
     sparsity: Option<M::Sparsity>,
     coloring_adjoint: Option<JacobianColoring<M>>,
     sparsity_adjoint: Option<M::Sparsity>,
-    statistics: RefCell<OpStatistics>,
+    statistics: AtomicOpStatistics,
 }
 
 impl<M, F, G> LinearClosureWithAdjoint<M, F, G>
@@ -36,7 +34,7 @@ where
             func,
             func_adjoint,
             nstates,
-            statistics: RefCell::new(OpStatistics::default()),
+            statistics: AtomicOpStatistics::new(),
             nout,
             nparams,
             coloring: None,
@@ -92,7 +90,7 @@ where
     }
 
     fn statistics(&self) -> OpStatistics {
-        self.statistics.borrow().clone()
+        self.statistics.get()
     }
 }
 
@@ -124,12 +122,12 @@ where
     G: Fn(&M::V, &M::V, M::T, M::T, &mut M::V),
 {
     fn gemv_inplace(&self, x: &M::V, t: M::T, beta: M::T, y: &mut M::V) {
-        self.op.statistics.borrow_mut().increment_call();
+        self.op.statistics.increment_call();
         (self.op.func)(x, self.p, t, beta, y)
     }
 
     fn matrix_inplace(&self, t: Self::T, y: &mut Self::M) {
-        self.op.statistics.borrow_mut().increment_matrix();
+        self.op.statistics.increment_matrix();
         if let Some(coloring) = &self.op.coloring {
             coloring.matrix_inplace(self, t, y);
         } else {
@@ -5,10 +5,17 @@ use crate::{
 
 use nonlinear_op::NonLinearOpJacobian;
 use serde::Serialize;
+use std::sync::atomic::{AtomicUsize, Ordering};
 
 pub mod bdf;
+pub mod bdf_residual;
 pub mod closure;
+pub mod closure_delay;
+pub mod closure_diffusion;
+pub mod closure_imex;
 pub mod closure_no_jac;
+pub mod closure_residual;
+pub mod closure_second_order;
 pub mod closure_with_adjoint;
 pub mod closure_with_sens;
 pub mod constant_closure;
@@ -21,7 +28,9 @@ pub mod linear_closure_with_adjoint;
 pub mod linear_op;
 pub mod linearise;
 pub mod matrix;
+pub mod newmark;
 pub mod nonlinear_op;
+pub mod radau;
 pub mod sdirk;
 pub mod unit;
 
@@ -122,6 +131,61 @@ impl OpStatistics {
     }
 }
 
+/// Counters for [OpStatistics] that can be incremented through a shared reference from multiple threads.
+/// This is used by the closure operators created by [crate::OdeBuilder] so that the resulting equations are `Sync`,
+/// and a problem can be solved by several solvers running on different threads (e.g. by [crate::Parareal]).
+#[derive(Default)]
+pub struct AtomicOpStatistics {
+    number_of_calls: AtomicUsize,
+    number_of_jac_muls: AtomicUsize,
+    number_of_matrix_evals: AtomicUsize,
+    number_of_jac_adj_muls: AtomicUsize,
+}
+
+impl AtomicOpStatistics {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn increment_call(&self) {
+        self.number_of_calls.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn increment_jac_mul(&self) {
+        self.number_of_jac_muls.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn increment_jac_adj_mul(&self) {
+        self.number_of_jac_adj_muls.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn increment_matrix(&self) {
+        self.number_of_matrix_evals.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Return a snapshot of the current counts
+    pub fn get(&self) -> OpStatistics {
+        OpStatistics {
+            number_of_calls: self.number_of_calls.load(Ordering::Relaxed),
+            number_of_jac_muls: self.number_of_jac_muls.load(Ordering::Relaxed),
+            number_of_matrix_evals: self.number_of_matrix_evals.load(Ordering::Relaxed),
+            number_of_jac_adj_muls: self.number_of_jac_adj_muls.load(Ordering::Relaxed),
+        }
+    }
+}
+
+impl Clone for AtomicOpStatistics {
+    fn clone(&self) -> Self {
+        let stats = self.get();
+        Self {
+            number_of_calls: AtomicUsize::new(stats.number_of_calls),
+            number_of_jac_muls: AtomicUsize::new(stats.number_of_jac_muls),
+            number_of_matrix_evals: AtomicUsize::new(stats.number_of_matrix_evals),
+            number_of_jac_adj_muls: AtomicUsize::new(stats.number_of_jac_adj_muls),
+        }
+    }
+}
+
 impl<C: Op> Op for &C {
     type T = C::T;
     type V = C::V;
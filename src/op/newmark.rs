@@ -0,0 +1,170 @@
+use crate::{
+    find_jacobian_non_zeros, jacobian::JacobianColoring, LinearOp, Matrix, MatrixSparsity,
+    NonLinearOpJacobian, OdeEquationsSecondOrder, Vector,
+};
+use num_traits::{One, Zero};
+use std::cell::RefCell;
+
+use super::{NonLinearOp, Op};
+
+// callable to solve for the acceleration a in the generalized-alpha and Newmark methods
+//
+// F(a) = M (cm * a + m0) - f(x0 + [cy * a, cv * a]) = 0
+//
+// where the full state x is the positions followed by the velocities, M is the mass matrix acting on the velocities
+// and f is the force, the coefficients and the vectors x0 and m0 are set using [NewmarkCallable::set_coefficients] and [NewmarkCallable::set_base]
+pub struct NewmarkCallable<Eqn: OdeEquationsSecondOrder> {
+    eqn: Eqn,
+    cm: RefCell<Eqn::T>,
+    cy: RefCell<Eqn::T>,
+    cv: RefCell<Eqn::T>,
+    x0: RefCell<Eqn::V>,
+    m0: RefCell<Eqn::V>,
+    x: RefCell<Eqn::V>,
+    z: RefCell<Eqn::V>,
+    mz: RefCell<Eqn::V>,
+    coloring: Option<JacobianColoring<Eqn::M>>,
+    sparsity: Option<<Eqn::M as Matrix>::Sparsity>,
+}
+
+impl<Eqn: OdeEquationsSecondOrder> NewmarkCallable<Eqn> {
+    pub fn new(eqn: Eqn) -> Self {
+        let n = eqn.npositions();
+        let mut ret = Self {
+            cm: RefCell::new(Eqn::T::one()),
+            cy: RefCell::new(Eqn::T::zero()),
+            cv: RefCell::new(Eqn::T::zero()),
+            x0: RefCell::new(<Eqn::V as Vector>::zeros(2 * n)),
+            m0: RefCell::new(<Eqn::V as Vector>::zeros(n)),
+            x: RefCell::new(<Eqn::V as Vector>::zeros(2 * n)),
+            z: RefCell::new(<Eqn::V as Vector>::zeros(2 * n)),
+            mz: RefCell::new(<Eqn::V as Vector>::zeros(2 * n)),
+            coloring: None,
+            sparsity: None,
+            eqn,
+        };
+
+        // the sparsity pattern of the jacobian is only needed for sparse matrices
+        if <Eqn::M as Matrix>::is_sparse() {
+            let non_zeros =
+                find_jacobian_non_zeros(&ret, &<Eqn::V as Vector>::zeros(n), Eqn::T::zero());
+            let sparsity = <Eqn::M as Matrix>::Sparsity::try_from_indices(n, n, non_zeros.clone())
+                .expect("invalid sparsity pattern");
+            ret.coloring = Some(JacobianColoring::new(&sparsity, &non_zeros));
+            ret.sparsity = Some(sparsity);
+        }
+        ret
+    }
+
+    pub fn eqn(&self) -> &Eqn {
+        &self.eqn
+    }
+
+    /// set the coefficients `cm`, `cy` and `cv` of the callable
+    pub fn set_coefficients(&self, cm: Eqn::T, cy: Eqn::T, cv: Eqn::T) {
+        self.cm.replace(cm);
+        self.cy.replace(cy);
+        self.cv.replace(cv);
+    }
+
+    /// set the full state `x0` and the vector `m0` of the callable
+    pub fn set_base(&self, x0: &Eqn::V, m0: &Eqn::V) {
+        self.x0.borrow_mut().copy_from(x0);
+        self.m0.borrow_mut().copy_from(m0);
+    }
+
+    // x = x0 + [cy * a, cv * a]
+    fn set_x(&self, a: &Eqn::V) {
+        let n = a.len();
+        let cy = *self.cy.borrow();
+        let cv = *self.cv.borrow();
+        let mut x = self.x.borrow_mut();
+        x.copy_from(&self.x0.borrow());
+        let x = x.as_mut_slice();
+        let a = a.as_slice();
+        for i in 0..n {
+            x[i] += cy * a[i];
+            x[n + i] += cv * a[i];
+        }
+    }
+
+    // y = alpha * M v + beta * y, where M is the mass matrix acting on the velocities
+    fn mass_gemv(&self, v: &Eqn::V, t: Eqn::T, alpha: Eqn::T, beta: Eqn::T, y: &mut Eqn::V) {
+        if let Some(mass) = self.eqn.mass() {
+            let n = v.len();
+            let mut z = self.z.borrow_mut();
+            let mut mz = self.mz.borrow_mut();
+            {
+                let z = z.as_mut_slice();
+                z[..n].fill(Eqn::T::zero());
+                z[n..].copy_from_slice(v.as_slice());
+            }
+            mass.call_inplace(&z, t, &mut mz);
+            let mv = <Eqn::V as Vector>::from_vec(mz.as_slice()[n..].to_vec());
+            y.axpy(alpha, &mv, beta);
+        } else {
+            y.axpy(alpha, v, beta);
+        }
+    }
+}
+
+impl<Eqn: OdeEquationsSecondOrder> Op for NewmarkCallable<Eqn> {
+    type V = Eqn::V;
+    type T = Eqn::T;
+    type M = Eqn::M;
+    fn nstates(&self) -> usize {
+        self.eqn.npositions()
+    }
+    fn nout(&self) -> usize {
+        self.eqn.npositions()
+    }
+    fn nparams(&self) -> usize {
+        self.eqn.rhs().nparams()
+    }
+}
+
+impl<Eqn: OdeEquationsSecondOrder> NonLinearOp for NewmarkCallable<Eqn> {
+    // F(a) = M (cm * a + m0) - f(x0 + [cy * a, cv * a])
+    fn call_inplace(&self, a: &Eqn::V, t: Eqn::T, y: &mut Eqn::V) {
+        self.set_x(a);
+        self.eqn.force().call_inplace(&self.x.borrow(), t, y);
+        let mut ma = self.m0.borrow().clone();
+        ma.axpy(*self.cm.borrow(), a, Eqn::T::one());
+        self.mass_gemv(&ma, t, Eqn::T::one(), -Eqn::T::one(), y);
+    }
+}
+
+impl<Eqn: OdeEquationsSecondOrder> NonLinearOpJacobian for NewmarkCallable<Eqn> {
+    // (cm * M - cy * f_y - cv * f_v) v
+    fn jac_mul_inplace(&self, a: &Eqn::V, t: Eqn::T, v: &Eqn::V, y: &mut Eqn::V) {
+        let n = v.len();
+        self.set_x(a);
+        let mut dx = <Eqn::V as Vector>::zeros(2 * n);
+        {
+            let cy = *self.cy.borrow();
+            let cv = *self.cv.borrow();
+            let dx = dx.as_mut_slice();
+            let v = v.as_slice();
+            for i in 0..n {
+                dx[i] = cy * v[i];
+                dx[n + i] = cv * v[i];
+            }
+        }
+        self.eqn
+            .force()
+            .jac_mul_inplace(&self.x.borrow(), t, &dx, y);
+        self.mass_gemv(v, t, *self.cm.borrow(), -Eqn::T::one(), y);
+    }
+
+    fn jacobian_inplace(&self, x: &Self::V, t: Self::T, y: &mut Self::M) {
+        if let Some(coloring) = self.coloring.as_ref() {
+            coloring.jacobian_inplace(self, x, t, y);
+        } else {
+            self._default_jacobian_inplace(x, t, y);
+        }
+    }
+
+    fn jacobian_sparsity(&self) -> Option<<Self::M as Matrix>::Sparsity> {
+        self.sparsity.clone()
+    }
+}
@@ -0,0 +1,217 @@
+use crate::{
+    matrix::MatrixRef, ode_solver::equations::OdeEquations, scale, LinearOp, Matrix,
+    MatrixSparsity, MatrixSparsityRef, NonLinearOpJacobian, OdeEquationsImplicit, Vector,
+    VectorIndex, VectorRef,
+};
+use num_traits::{One, Zero};
+use std::cell::RefCell;
+
+use super::{NonLinearOp, Op};
+
+// callable for the complex linear system ((alpha + i beta) / h) M - J that arises in the radau solver,
+// written as the real system of twice the size
+//
+// | (alpha / h) M - J   -(beta / h) M      |
+// | (beta / h) M         (alpha / h) M - J |
+//
+// the jacobians J and M are not evaluated by this callable, they are set using [RadauComplexCallable::set_jacobians]
+pub struct RadauComplexCallable<Eqn: OdeEquations> {
+    eqn: Eqn,
+    alpha: Eqn::T,
+    beta: Eqn::T,
+    h: RefCell<Eqn::T>,
+    rhs_jac: RefCell<Eqn::M>,
+    mass_jac: RefCell<Eqn::M>,
+    indices: <Eqn::V as Vector>::Index,
+    diag_sparsity: Option<<Eqn::M as Matrix>::Sparsity>,
+    sparsity: Option<<Eqn::M as Matrix>::Sparsity>,
+}
+
+impl<Eqn: OdeEquationsImplicit> RadauComplexCallable<Eqn> {
+    pub fn new(eqn: Eqn, alpha: Eqn::T, beta: Eqn::T) -> Self {
+        let n = eqn.rhs().nstates();
+        let h = RefCell::new(Eqn::T::zero());
+        let rhs_jac = RefCell::new(<Eqn::M as Matrix>::zeros(n, n));
+        let mass_jac = RefCell::new(<Eqn::M as Matrix>::zeros(n, n));
+
+        // the second half of the states are the imaginary parts
+        let indices = <Eqn::V as Vector>::Index::from_slice(&(n..2 * n).collect::<Vec<_>>());
+
+        // the diagonal blocks have the union of the mass and rhs jacobian sparsity patterns,
+        // the off-diagonal blocks have the sparsity pattern of the mass matrix
+        let mass_sparsity = if let Some(mass) = eqn.mass() {
+            mass.sparsity()
+        } else {
+            Some(<Eqn::M as Matrix>::Sparsity::new_diagonal(n))
+        };
+        let (diag_sparsity, sparsity) = if let Some(rhs_jac_sparsity) =
+            eqn.rhs().jacobian_sparsity()
+        {
+            let mass_sparsity = mass_sparsity.expect("Mass matrix must have a sparsity pattern if the rhs jacobian has a sparsity pattern");
+            let diag_sparsity = mass_sparsity
+                .clone()
+                .union(rhs_jac_sparsity.as_ref())
+                .unwrap();
+            let diag = <Eqn::M as Matrix>::new_from_sparsity(n, n, Some(diag_sparsity.clone()));
+            let off_diag = <Eqn::M as Matrix>::new_from_sparsity(n, n, Some(mass_sparsity));
+            let jac = Eqn::M::combine_at_indices(&diag, &off_diag, &off_diag, &diag, &indices);
+            let sparsity = jac.sparsity().map(|s| s.to_owned());
+            (Some(diag_sparsity), sparsity)
+        } else {
+            (None, None)
+        };
+
+        Self {
+            eqn,
+            alpha,
+            beta,
+            h,
+            rhs_jac,
+            mass_jac,
+            indices,
+            diag_sparsity,
+            sparsity,
+        }
+    }
+
+    pub fn set_h(&self, h: Eqn::T) {
+        self.h.replace(h);
+    }
+
+    /// set the rhs jacobian `J` and mass matrix `M` used to form the linear system
+    pub fn set_jacobians(&self, rhs_jac: &Eqn::M, mass_jac: &Eqn::M) {
+        self.rhs_jac.replace(rhs_jac.clone());
+        self.mass_jac.replace(mass_jac.clone());
+    }
+
+    pub fn eqn(&self) -> &Eqn {
+        &self.eqn
+    }
+}
+
+impl<Eqn: OdeEquations> Op for RadauComplexCallable<Eqn> {
+    type V = Eqn::V;
+    type T = Eqn::T;
+    type M = Eqn::M;
+    fn nstates(&self) -> usize {
+        2 * self.eqn.rhs().nstates()
+    }
+    fn nout(&self) -> usize {
+        2 * self.eqn.rhs().nstates()
+    }
+    fn nparams(&self) -> usize {
+        self.eqn.rhs().nparams()
+    }
+}
+
+impl<Eqn: OdeEquationsImplicit> NonLinearOp for RadauComplexCallable<Eqn>
+where
+    for<'b> &'b Eqn::V: VectorRef<Eqn::V>,
+    for<'b> &'b Eqn::M: MatrixRef<Eqn::M>,
+{
+    // the operator is linear, so F(x) = A x
+    fn call_inplace(&self, x: &Eqn::V, _t: Eqn::T, y: &mut Eqn::V) {
+        let n = self.eqn.rhs().nstates();
+        let h = *self.h.borrow();
+        let a = self.alpha / h;
+        let b = self.beta / h;
+        let rhs_jac = self.rhs_jac.borrow();
+        let mass_jac = self.mass_jac.borrow();
+        let x_re = Eqn::V::from_vec(x.as_slice()[..n].to_vec());
+        let x_im = Eqn::V::from_vec(x.as_slice()[n..].to_vec());
+
+        // y_re = (a M - J) x_re - b M x_im
+        let mut y_re = <Eqn::V as Vector>::zeros(n);
+        rhs_jac.gemv(-Eqn::T::one(), &x_re, Eqn::T::zero(), &mut y_re);
+        mass_jac.gemv(a, &x_re, Eqn::T::one(), &mut y_re);
+        mass_jac.gemv(-b, &x_im, Eqn::T::one(), &mut y_re);
+
+        // y_im = b M x_re + (a M - J) x_im
+        let mut y_im = <Eqn::V as Vector>::zeros(n);
+        rhs_jac.gemv(-Eqn::T::one(), &x_im, Eqn::T::zero(), &mut y_im);
+        mass_jac.gemv(a, &x_im, Eqn::T::one(), &mut y_im);
+        mass_jac.gemv(b, &x_re, Eqn::T::one(), &mut y_im);
+
+        let y = y.as_mut_slice();
+        y[..n].copy_from_slice(y_re.as_slice());
+        y[n..].copy_from_slice(y_im.as_slice());
+    }
+}
+
+impl<Eqn: OdeEquationsImplicit> NonLinearOpJacobian for RadauComplexCallable<Eqn>
+where
+    for<'b> &'b Eqn::V: VectorRef<Eqn::V>,
+    for<'b> &'b Eqn::M: MatrixRef<Eqn::M>,
+{
+    fn jac_mul_inplace(&self, _x: &Eqn::V, t: Eqn::T, v: &Eqn::V, y: &mut Eqn::V) {
+        self.call_inplace(v, t, y);
+    }
+
+    fn jacobian_inplace(&self, _x: &Self::V, _t: Self::T, y: &mut Self::M) {
+        let n = self.eqn.rhs().nstates();
+        let h = *self.h.borrow();
+        let a = self.alpha / h;
+        let b = self.beta / h;
+        let rhs_jac = self.rhs_jac.borrow();
+        let mass_jac = self.mass_jac.borrow();
+
+        let mut diag = <Eqn::M as Matrix>::new_from_sparsity(n, n, self.diag_sparsity.clone());
+        diag.scale_add_and_assign(&(&*mass_jac * scale(a)), -Eqn::T::one(), &rhs_jac);
+        let upper = &*mass_jac * scale(-b);
+        let lower = &*mass_jac * scale(b);
+        let jac = Eqn::M::combine_at_indices(&diag, &upper, &lower, &diag, &self.indices);
+        y.copy_from(&jac);
+    }
+
+    fn jacobian_sparsity(&self) -> Option<<Self::M as Matrix>::Sparsity> {
+        self.sparsity.clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::ode_solver::test_models::robertson::robertson;
+    use crate::op::sdirk::SdirkCallable;
+    use crate::vector::Vector;
+    use crate::Matrix;
+    use crate::NonLinearOpJacobian;
+
+    use super::RadauComplexCallable;
+    type Mcpu = nalgebra::DMatrix<f64>;
+    type Vcpu = nalgebra::DVector<f64>;
+
+    #[test]
+    fn test_radau_complex_robertson_jacobian() {
+        let (problem, _soln) = robertson::<Mcpu>(false);
+        let h = 1.3;
+        let t = 0.9;
+        let y = Vcpu::from_vec(vec![1.1, 1.2, 1.3]);
+
+        // use the sdirk callable to evaluate the rhs jacobian and mass matrix
+        let sdirk_callable = SdirkCallable::new(&problem.eqn, 1.0);
+        sdirk_callable.set_h(h);
+        sdirk_callable.set_phi_direct(y.clone());
+        let _ = sdirk_callable.jacobian(&Vcpu::zeros(3), t);
+
+        let callable = RadauComplexCallable::new(&problem.eqn, 2.0, 3.0);
+        callable.set_h(h);
+        callable.set_jacobians(&sdirk_callable.rhs_jac(), &sdirk_callable.mass_jac());
+
+        let x = Vcpu::zeros(6);
+        let v = Vcpu::from_vec(vec![2.0, 3.0, 4.0, 5.0, 6.0, 7.0]);
+        let jac = callable.jacobian(&x, t);
+        let jac_mul_v = callable.jac_mul(&x, t, &v);
+        let mut jac_mul_v2 = Vcpu::zeros(6);
+        jac.gemv(1.0, &v, 0.0, &mut jac_mul_v2);
+        jac_mul_v.assert_eq_st(&jac_mul_v2, 1e-10);
+
+        // the mass matrix is diag(1, 1, 0), so the off-diagonal blocks are diag(-b, -b, 0) and diag(b, b, 0)
+        let b = 3.0 / h;
+        assert_eq!(jac[(0, 3)], -b);
+        assert_eq!(jac[(1, 4)], -b);
+        assert_eq!(jac[(2, 5)], 0.0);
+        assert_eq!(jac[(3, 0)], b);
+        assert_eq!(jac[(4, 1)], b);
+        assert_eq!(jac[(5, 2)], 0.0);
+    }
+}
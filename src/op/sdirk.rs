@@ -20,6 +20,7 @@ pub struct SdirkCallable<Eqn: OdeEquations> {
     h: RefCell<Eqn::T>,
     phi: RefCell<Eqn::V>,
     tmp: RefCell<Eqn::V>,
+    jac_tmp: RefCell<Eqn::V>,
     rhs_jac: RefCell<Eqn::M>,
     mass_jac: RefCell<Eqn::M>,
     jacobian_is_stale: RefCell<bool>,
@@ -35,6 +36,7 @@ impl<Eqn: OdeEquationsImplicit> SdirkCallable<Eqn> {
             h: RefCell::new(*self.h.borrow()),
             phi: RefCell::new(self.phi.borrow().clone()),
             tmp: RefCell::new(self.tmp.borrow().clone()),
+            jac_tmp: RefCell::new(self.jac_tmp.borrow().clone()),
             rhs_jac: RefCell::new(self.rhs_jac.borrow().clone()),
             mass_jac: RefCell::new(self.mass_jac.borrow().clone()),
             jacobian_is_stale: RefCell::new(*self.jacobian_is_stale.borrow()),
@@ -54,6 +56,7 @@ impl<Eqn: OdeEquationsImplicit> SdirkCallable<Eqn> {
         let jacobian_is_stale = RefCell::new(false);
         let number_of_jac_evals = RefCell::new(0);
         let tmp = RefCell::new(<Eqn::V as Vector>::zeros(n));
+        let jac_tmp = RefCell::new(<Eqn::V as Vector>::zeros(n));
         let rhs_jac = RefCell::new(<Eqn::M as Matrix>::zeros(0, 0));
         let mass_jac = RefCell::new(<Eqn::M as Matrix>::zeros(0, 0));
         let sparsity = None;
@@ -67,6 +70,7 @@ impl<Eqn: OdeEquationsImplicit> SdirkCallable<Eqn> {
             jacobian_is_stale,
             number_of_jac_evals,
             tmp,
+            jac_tmp,
             sparsity,
         }
     }
@@ -82,6 +86,7 @@ impl<Eqn: OdeEquationsImplicit> SdirkCallable<Eqn> {
         let jacobian_is_stale = RefCell::new(true);
         let number_of_jac_evals = RefCell::new(0);
         let tmp = RefCell::new(<Eqn::V as Vector>::zeros(n));
+        let jac_tmp = RefCell::new(<Eqn::V as Vector>::zeros(n));
 
         // create the mass and rhs jacobians according to the sparsity pattern
         let rhs_jac = RefCell::new(Eqn::M::new_from_sparsity(
@@ -127,6 +132,7 @@ impl<Eqn: OdeEquationsImplicit> SdirkCallable<Eqn> {
             jacobian_is_stale,
             number_of_jac_evals,
             tmp,
+            jac_tmp,
         }
     }
 
@@ -145,8 +151,13 @@ impl<Eqn: OdeEquationsImplicit> SdirkCallable<Eqn> {
     pub fn eqn(&self) -> &Eqn {
         &self.eqn
     }
-    #[allow(dead_code)]
-    fn set_phi_direct(&self, phi: Eqn::V) {
+    pub(crate) fn rhs_jac(&self) -> Ref<Eqn::M> {
+        self.rhs_jac.borrow()
+    }
+    pub(crate) fn mass_jac(&self) -> Ref<Eqn::M> {
+        self.mass_jac.borrow()
+    }
+    pub(crate) fn set_phi_direct(&self, phi: Eqn::V) {
         let mut phi_ref = self.phi.borrow_mut();
         phi_ref.copy_from(&phi);
     }
@@ -221,12 +232,15 @@ where
 {
     // (M - c * h * f'(phi + c * y)) v
     fn jac_mul_inplace(&self, x: &Eqn::V, t: Eqn::T, v: &Eqn::V, y: &mut Eqn::V) {
-        self.set_tmp(x);
-        let tmp = self.tmp.borrow();
         let h = *self.h.borrow().deref();
         let c = self.c;
 
-        self.eqn.rhs().jac_mul_inplace(&tmp, t, v, y);
+        // phi + c * x is stored separately so that the point returned by get_last_f_eval is not overwritten
+        let mut jac_tmp = self.jac_tmp.borrow_mut();
+        jac_tmp.copy_from(self.phi.borrow().deref());
+        jac_tmp.axpy(c, x, Eqn::T::one());
+
+        self.eqn.rhs().jac_mul_inplace(&jac_tmp, t, v, y);
 
         // y = Mv - c h y
         if let Some(mass) = self.eqn.mass() {
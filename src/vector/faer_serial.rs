@@ -209,6 +209,11 @@ impl<'a, T: Scalar> VectorView<'a> for ColRef<'a, T> {
     fn norm(&self) -> T {
         self.norm_l2()
     }
+    fn as_slice(&self) -> &[T] {
+        self.try_as_col_major()
+            .expect("vector view is not contiguous")
+            .as_slice()
+    }
     fn squared_norm(&self, y: &Self::Owned, atol: &Self::Owned, rtol: Self::T) -> Self::T {
         let mut acc = T::zero();
         if y.len() != self.nrows() || y.nrows() != atol.nrows() {
@@ -284,5 +289,6 @@ mod tests {
             v.as_ref().squared_norm(&y, &atol, rtol),
             errorn_check
         );
+        assert_eq!(VectorView::as_slice(&v.as_ref()), &[1.0, -2.0, 3.0]);
     }
 }
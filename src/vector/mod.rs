@@ -100,6 +100,7 @@ pub trait VectorView<'a>:
     type Owned;
     fn squared_norm(&self, y: &Self::Owned, atol: &Self::Owned, rtol: Self::T) -> Self::T;
     fn norm(&self) -> Self::T;
+    fn as_slice(&self) -> &[Self::T];
     fn into_owned(self) -> Self::Owned;
 }
 
@@ -59,6 +59,9 @@ impl<'a, T: Scalar> VectorView<'a> for DVectorView<'a, T> {
     fn norm(&self) -> T {
         self.norm()
     }
+    fn as_slice(&self) -> &[T] {
+        self.as_slice()
+    }
     fn squared_norm(&self, y: &Self::Owned, atol: &Self::Owned, rtol: Self::T) -> Self::T {
         let mut acc = T::zero();
         if y.len() != self.len() || y.len() != atol.len() {
@@ -439,6 +439,9 @@ impl<'a> VectorView<'a> for SundialsVectorView<'a> {
         let ones = SundialsVector::from_element(self.len(), 1.0);
         unsafe { N_VWL2Norm_Serial(self.sundials_vector(), ones.sundials_vector()) }
     }
+    fn as_slice(&self) -> &[Self::T] {
+        self.0.as_slice()
+    }
     fn squared_norm(&self, y: &Self::Owned, atol: &Self::Owned, rtol: Self::T) -> Self::T {
         let mut acc = 0.0;
         if y.len() != self.len() || y.len() != atol.len() {